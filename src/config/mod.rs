@@ -26,11 +26,40 @@ pub struct TerraformConfig {
     pub executable_path: Option<String>,
     pub project_directory: Option<String>,
     pub auto_init: Option<bool>,
+    /// Path to a JSON risk policy used by analyze_plan
+    #[serde(default)]
+    pub risk_policy_file: Option<String>,
+    /// Directory of Rego policies used by evaluate_policies
+    #[serde(default)]
+    pub policy_dir: Option<String>,
+    /// JSON resource category mapping checked before the built-in one
+    #[serde(default)]
+    pub resource_categories_file: Option<String>,
+    /// Terraform CLI config passed as TF_CLI_CONFIG_FILE; relative to the project directory
+    #[serde(default)]
+    pub cli_config_file: Option<String>,
+    /// Pass `<workspace>.tfvars` to plan and apply when it exists in the project
+    #[serde(default)]
+    pub auto_tfvars_by_workspace: Option<bool>,
+    /// Run terraform as `terraform -chdir=<project>` instead of in the project directory
+    #[serde(default)]
+    pub use_chdir: Option<bool>,
+    /// Resource type patterns whose deletion or replacement analyze_plan
+    /// scores highest; a risk policy's own list takes precedence
+    #[serde(default)]
+    pub critical_resource_types: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct McpConfig {
     pub tools: Vec<String>,
+    /// When set, only these tools are listed and callable
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+    /// Tools that are never listed or callable, even when allowed or when
+    /// dangerous operations are enabled
+    #[serde(default)]
+    pub denied_tools: Vec<String>,
 }
 
 pub fn init_default() -> anyhow::Result<Config> {
@@ -55,6 +84,13 @@ pub fn init_default() -> anyhow::Result<Config> {
             executable_path: None,
             project_directory: None,
             auto_init: Some(true),
+            risk_policy_file: None,
+            policy_dir: None,
+            resource_categories_file: None,
+            cli_config_file: None,
+            auto_tfvars_by_workspace: None,
+            use_chdir: None,
+            critical_resource_types: None,
         },
         mcp: McpConfig {
             tools: vec![
@@ -63,6 +99,8 @@ pub fn init_default() -> anyhow::Result<Config> {
                 "get_terraform_plan".to_string(),
                 "apply_terraform".to_string(),
             ],
+            allowed_tools: None,
+            denied_tools: Vec::new(),
         },
     })
 }
@@ -1,6 +1,10 @@
 use crate::config::{self, Config};
+use crate::registry::cache::{DOCUMENTATION_CACHE_TTL, PROVIDERS_CACHE_TTL};
 use crate::shared::logging;
-use crate::terraform::model::{DetailedValidationResult, TerraformAnalysis};
+use crate::shared::security::is_sensitive_env_var;
+use crate::terraform::model::{
+    AnalysisReport, DetailedValidationResult, IssueSeverity, TerraformAnalysis,
+};
 use crate::terraform::service::TerraformService;
 use std::path::{Path, PathBuf};
 
@@ -34,17 +38,134 @@ fn create_sample_terraform_file(dir: &Path) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Conditions under which `tfmcp analyze` exits non-zero; with none set it only reports
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnalysisGates {
+    /// Fail when the report has critical issues
+    pub fail_on_critical: bool,
+    /// Fail when the module health score is below this value
+    pub fail_under: Option<u8>,
+    /// Fail when the cohesion score is below this value
+    pub min_cohesion: Option<u8>,
+    /// Fail when the security scan finds any hardcoded secret
+    pub fail_on_secrets: bool,
+}
+
+impl AnalysisGates {
+    pub fn is_empty(&self) -> bool {
+        !self.fail_on_critical
+            && self.fail_under.is_none()
+            && self.min_cohesion.is_none()
+            && !self.fail_on_secrets
+    }
+
+    /// Describe each gate `report` fails
+    pub fn failures(&self, report: &AnalysisReport) -> Vec<String> {
+        let mut failures = Vec::new();
+        if self.fail_on_critical && report.critical_issues > 0 {
+            failures.push(format!(
+                "Analysis found {} critical issues",
+                report.critical_issues
+            ));
+        }
+        if let Some(threshold) = self.fail_under {
+            let score = report.module_health.health_score;
+            if score < threshold {
+                failures.push(format!(
+                    "Module health score {} is below {}",
+                    score, threshold
+                ));
+            }
+        }
+        if let Some(threshold) = self.min_cohesion {
+            let cohesion = &report.module_health.cohesion_analysis;
+            if cohesion.score < threshold {
+                // Name the resource categories that spread the module out
+                let categories: Vec<String> = cohesion
+                    .resource_type_groups
+                    .iter()
+                    .map(|group| format!("{} ({})", group.name, group.resource_types.join(", ")))
+                    .collect();
+                failures.push(format!(
+                    "Cohesion score {} is below {}: resources span {} categories: {}",
+                    cohesion.score,
+                    threshold,
+                    categories.len(),
+                    categories.join("; ")
+                ));
+            }
+        }
+        let secrets = report.guidelines.hardcoded_secrets.len();
+        if self.fail_on_secrets && secrets > 0 {
+            failures.push(format!("Security scan found {} hardcoded secrets", secrets));
+        }
+        failures
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum TfMcpError {
-    #[error("Terraform binary not found")]
-    TerraformNotFound,
+    #[error(
+        "Terraform binary not found: {0}. Install Terraform from https://developer.hashicorp.com/terraform/install (or OpenTofu from https://opentofu.org/docs/intro/install/), or set TFMCP_TERRAFORM_BIN to the path of the binary."
+    )]
+    TerraformNotFound(String),
 
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+/// Environment variable naming the terraform binary to use, by path or by name on PATH
+pub const TERRAFORM_BIN_ENV: &str = "TFMCP_TERRAFORM_BIN";
+
+/// Locate the terraform binary before any command needs it.
+///
+/// `TFMCP_TERRAFORM_BIN` wins over `terraform.executable_path` from the
+/// config file, which wins over looking up `binary_name` on PATH.
+pub fn resolve_terraform_binary(
+    env_override: Option<&str>,
+    configured_path: Option<&str>,
+    binary_name: &str,
+) -> Result<PathBuf, TfMcpError> {
+    let (candidate, source) = match (env_override, configured_path) {
+        (Some(bin), _) if !bin.trim().is_empty() => (bin.trim(), TERRAFORM_BIN_ENV),
+        (_, Some(path)) => (path, "terraform.executable_path"),
+        _ => {
+            return which::which(binary_name).map_err(|_| {
+                TfMcpError::TerraformNotFound(format!("'{}' is not on PATH", binary_name))
+            });
+        }
+    };
+
+    // A bare name is looked up on PATH; anything else is a file path
+    let path = PathBuf::from(candidate);
+    if path.components().count() == 1 && !path.is_absolute() {
+        if let Ok(found) = which::which(candidate) {
+            return Ok(found);
+        }
+    }
+    let path = if path.is_absolute() {
+        path
+    } else {
+        std::env::current_dir()
+            .map_err(|e| TfMcpError::Other(e.into()))?
+            .join(path)
+    };
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(TfMcpError::TerraformNotFound(format!(
+            "{} points to '{}', which does not exist",
+            source,
+            path.display()
+        )))
+    }
+}
+
+/// Environment variable prefixes surfaced in the effective configuration
+const CONFIG_ENV_PREFIXES: &[&str] = &["TFMCP_", "TERRAFORM_", "TF_"];
+
+#[derive(Clone)]
 pub struct TfMcp {
-    #[allow(dead_code)]
     config: Config,
     terraform_service: TerraformService,
 }
@@ -164,54 +285,16 @@ impl TfMcp {
         };
 
         // Check if terraform is installed
-        let terraform_path = match &config.terraform.executable_path {
-            Some(path) => {
-                let path_buf = PathBuf::from(path);
-                if path_buf.is_absolute() {
-                    logging::info(&format!("Using specified Terraform binary: {}", path));
-                    path_buf
-                } else {
-                    // Convert to absolute path
-                    let abs_path = std::env::current_dir()?.join(path);
-                    logging::info(&format!(
-                        "Converting relative Terraform path to absolute: {}",
-                        abs_path.display()
-                    ));
-                    abs_path
-                }
-            }
-            None => {
-                // Read TERRAFORM_BINARY_NAME env var, fallback to "terraform"
-                let terraform_binary = std::env::var("TERRAFORM_BINARY_NAME")
-                    .unwrap_or_else(|_| "terraform".to_string());
-                match which::which(&terraform_binary) {
-                    Ok(path) => {
-                        logging::info(&format!(
-                            "Found Terraform binary '{}' in PATH: {}",
-                            terraform_binary,
-                            path.display()
-                        ));
-                        path
-                    }
-                    Err(_) => {
-                        logging::error(&format!(
-                            "Terraform binary '{}' not found in PATH",
-                            terraform_binary
-                        ));
-                        return Err(TfMcpError::TerraformNotFound.into());
-                    }
-                }
-            }
-        };
-
-        // Verify Terraform binary exists
-        if !terraform_path.exists() {
-            logging::error(&format!(
-                "Terraform binary not found at: {}",
-                terraform_path.display()
-            ));
-            return Err(TfMcpError::TerraformNotFound.into());
-        }
+        let terraform_path = resolve_terraform_binary(
+            std::env::var(TERRAFORM_BIN_ENV).ok().as_deref(),
+            config.terraform.executable_path.as_deref(),
+            &std::env::var("TERRAFORM_BINARY_NAME").unwrap_or_else(|_| "terraform".to_string()),
+        )
+        .inspect_err(|e| logging::error(&e.to_string()))?;
+        logging::info(&format!(
+            "Using Terraform binary: {}",
+            terraform_path.display()
+        ));
 
         // Create a sample Terraform file if the directory doesn't have .tf files
         // This ensures we can always start the MCP server even without a valid Terraform project
@@ -243,13 +326,59 @@ impl TfMcp {
             create_sample_terraform_file(&project_directory)?;
         }
 
-        let terraform_service = TerraformService::new(terraform_path, project_directory);
+        if let Some(path) = std::env::var("TFMCP_RESOURCE_CATEGORIES")
+            .ok()
+            .or_else(|| config.terraform.resource_categories_file.clone())
+        {
+            let path = crate::shared::utils::path::expand_tilde(&path);
+            let categories =
+                crate::terraform::categories::ResourceCategories::with_override_file(&path)?;
+            logging::info(&format!(
+                "Using resource categories from {}",
+                path.display()
+            ));
+            crate::terraform::categories::install(categories);
+        }
 
-        logging::info("TfMcp initialized successfully");
-        Ok(Self {
+        let mut terraform_service = TerraformService::new(terraform_path, project_directory);
+        terraform_service.set_auto_tfvars_by_workspace(
+            config.terraform.auto_tfvars_by_workspace.unwrap_or(false),
+        );
+        terraform_service.set_use_chdir(config.terraform.use_chdir.unwrap_or(false));
+        let mut tfmcp = Self {
             config,
             terraform_service,
-        })
+        };
+        tfmcp.apply_cli_config()?;
+
+        logging::info("TfMcp initialized successfully");
+        Ok(tfmcp)
+    }
+
+    /// Resolve `terraform.cli_config_file` against the current project and
+    /// hand it to the service, failing if the file does not exist
+    fn apply_cli_config(&mut self) -> anyhow::Result<()> {
+        let cli_config_file = match &self.config.terraform.cli_config_file {
+            Some(path) => {
+                let resolved = crate::terraform::cli_config::resolve_cli_config_file(
+                    self.terraform_service.get_project_directory(),
+                    path,
+                )?;
+                logging::info(&format!(
+                    "Using Terraform CLI config: {}",
+                    resolved.display()
+                ));
+                Some(resolved)
+            }
+            None => None,
+        };
+        self.terraform_service.set_cli_config_file(cli_config_file);
+        Ok(())
+    }
+
+    /// The Terraform CLI config in effect and its provider mirror settings
+    pub fn get_cli_config(&self) -> crate::terraform::cli_config::CliConfigInfo {
+        self.terraform_service.get_cli_config()
     }
 
     pub async fn analyze_terraform(&mut self) -> anyhow::Result<()> {
@@ -258,7 +387,31 @@ impl TfMcp {
         Ok(())
     }
 
-    #[allow(dead_code)]
+    /// Analysis, guideline checks and module health in one report, for `tfmcp analyze --json`
+    pub async fn analysis_report(&self) -> anyhow::Result<AnalysisReport> {
+        let analysis = self.terraform_service.analyze_configurations().await?;
+        let guidelines = self.terraform_service.run_security_scan().await?;
+        let module_health = self.terraform_service.analyze_module_health().await?;
+
+        let critical_issues = module_health
+            .issues
+            .iter()
+            .filter(|issue| matches!(issue.severity, IssueSeverity::Critical))
+            .count()
+            + guidelines
+                .hardcoded_secrets
+                .iter()
+                .filter(|secret| secret.severity == "critical")
+                .count();
+
+        Ok(AnalysisReport {
+            analysis,
+            guidelines,
+            module_health,
+            critical_issues,
+        })
+    }
+
     pub async fn get_terraform_analysis(&self) -> anyhow::Result<TerraformAnalysis> {
         self.terraform_service.analyze_configurations().await
     }
@@ -268,34 +421,120 @@ impl TfMcp {
         self.terraform_service.get_version().await
     }
 
-    pub async fn get_terraform_plan(&self) -> anyhow::Result<String> {
-        self.terraform_service.get_plan().await
+    /// Get installed version details and required_version compatibility
+    pub async fn get_version_info(&self) -> anyhow::Result<crate::terraform::version::VersionInfo> {
+        self.terraform_service.get_version_info().await
     }
 
-    pub async fn apply_terraform(&self, auto_approve: bool) -> anyhow::Result<String> {
-        self.terraform_service.apply(auto_approve).await
+    pub async fn get_terraform_plan(
+        &self,
+        auto_init: bool,
+        extra_args: &[String],
+    ) -> anyhow::Result<String> {
+        self.terraform_service.get_plan(auto_init, extra_args).await
     }
 
-    pub async fn init_terraform(&self) -> anyhow::Result<String> {
-        self.terraform_service.init().await
+    /// Plan with each `plan -json` message passed to `on_message` as it is emitted
+    pub async fn get_terraform_plan_streamed(
+        &self,
+        auto_init: bool,
+        extra_args: &[String],
+        on_message: &mut (dyn FnMut(serde_json::Value) + Send),
+    ) -> anyhow::Result<crate::terraform::plan_analyzer::PlanAnalysis> {
+        let policy = self.risk_policy()?;
+        self.terraform_service
+            .get_plan_streamed(auto_init, extra_args, policy.as_ref(), on_message)
+            .await
     }
 
-    pub async fn get_state(&self) -> anyhow::Result<String> {
-        self.terraform_service.get_state().await
+    /// Preview the resources a destroy would remove, without destroying anything
+    pub async fn plan_destroy(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::plan_analyzer::DestroyPreview> {
+        self.terraform_service.plan_destroy(auto_init).await
+    }
+
+    pub async fn apply_terraform(
+        &self,
+        auto_approve: bool,
+        auto_init: bool,
+        extra_args: &[String],
+        confirm_destructive: bool,
+    ) -> anyhow::Result<String> {
+        self.terraform_service
+            .apply(auto_approve, auto_init, extra_args, confirm_destructive)
+            .await
+    }
+
+    /// Run a plan into a plan file and record its hash
+    pub async fn save_plan(
+        &self,
+        plan_file: Option<&str>,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::saved_plan::SavedPlan> {
+        self.terraform_service.save_plan(plan_file, auto_init).await
+    }
+
+    /// Apply a plan file after verifying its recorded hash
+    pub async fn apply_saved_plan(
+        &self,
+        plan_file: Option<&str>,
+        confirm_destructive: bool,
+    ) -> anyhow::Result<crate::terraform::saved_plan::SavedPlanApply> {
+        self.terraform_service
+            .apply_saved_plan(plan_file, confirm_destructive)
+            .await
+    }
+
+    /// Save a plan and issue a single-use approval token for it
+    pub async fn propose_apply(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::approval::ApplyProposal> {
+        self.terraform_service.propose_apply(auto_init).await
+    }
+
+    /// Apply the plan approved by a `propose_apply` token
+    pub async fn confirm_apply(
+        &self,
+        token: &str,
+        confirm_destructive: bool,
+    ) -> anyhow::Result<crate::terraform::saved_plan::SavedPlanApply> {
+        self.terraform_service
+            .confirm_apply(token, confirm_destructive)
+            .await
+    }
+
+    pub async fn init_terraform(&self, extra_args: &[String]) -> anyhow::Result<String> {
+        self.terraform_service.init(extra_args).await
+    }
+
+    pub async fn get_state(
+        &self,
+        auto_init: bool,
+        workspace: Option<&str>,
+    ) -> anyhow::Result<(Option<String>, String)> {
+        self.terraform_service.get_state(auto_init, workspace).await
     }
 
     pub async fn list_resources(&self) -> anyhow::Result<Vec<String>> {
         self.terraform_service.list_resources().await
     }
 
-    pub async fn validate_configuration(&self) -> anyhow::Result<String> {
-        self.terraform_service.validate().await
+    pub async fn validate_configuration(
+        &self,
+        path: Option<&str>,
+        auto_init: bool,
+    ) -> anyhow::Result<(String, crate::terraform::model::TerraformValidateOutput)> {
+        self.terraform_service.validate_path(path, auto_init).await
     }
 
     pub async fn validate_configuration_detailed(
         &self,
+        path: Option<&str>,
     ) -> anyhow::Result<DetailedValidationResult> {
-        self.terraform_service.validate_detailed().await
+        self.terraform_service.validate_detailed(path).await
     }
 
     pub async fn destroy_terraform(&self, auto_approve: bool) -> anyhow::Result<String> {
@@ -376,6 +615,10 @@ impl TfMcp {
                         project_directory.to_string_lossy().to_string(),
                     );
                 }
+                if let Err(e) = self.apply_cli_config() {
+                    self.terraform_service.set_cli_config_file(None);
+                    logging::warn(&format!("{}; not using a CLI config file", e));
+                }
                 logging::info(&format!(
                     "Successfully changed project directory to: {}",
                     project_directory.display()
@@ -389,11 +632,115 @@ impl TfMcp {
         }
     }
 
+    /// Effective configuration (config file merged with environment) with secrets redacted
+    pub async fn effective_config(&self) -> serde_json::Value {
+        let env: Vec<(String, String)> = std::env::vars().collect();
+        self.effective_config_from_env(env).await
+    }
+
+    /// Build the effective configuration from the given environment variables
+    pub async fn effective_config_from_env(
+        &self,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> serde_json::Value {
+        let terraform_path = self.terraform_service.get_terraform_path();
+        let (flavor, version) =
+            match crate::terraform::version::detect_version(terraform_path).await {
+                Ok((flavor, version)) => (Some(flavor), Some(version)),
+                Err(_) => (None, None),
+            };
+
+        let mut environment: Vec<(String, String)> = env
+            .into_iter()
+            .filter(|(name, _)| CONFIG_ENV_PREFIXES.iter().any(|p| name.starts_with(p)))
+            .map(|(name, value)| {
+                let value = if is_sensitive_env_var(&name) {
+                    "[REDACTED]".to_string()
+                } else {
+                    value
+                };
+                (name, value)
+            })
+            .collect();
+        environment.sort();
+        let environment: serde_json::Map<String, serde_json::Value> = environment
+            .into_iter()
+            .map(|(name, value)| (name, serde_json::Value::String(value)))
+            .collect();
+
+        let policy = self.terraform_service.get_security_policy();
+
+        serde_json::json!({
+            "project_directory": self.terraform_service.get_project_directory(),
+            "terraform": {
+                "binary_path": terraform_path,
+                "flavor": flavor,
+                "version": version,
+                "auto_init": self.config.terraform.auto_init,
+                "risk_policy_file": self.risk_policy_path(),
+                "policy_dir": self.policy_dir(),
+                "cli_config_file": self.config.terraform.cli_config_file,
+                "auto_tfvars_by_workspace": self.config.terraform.auto_tfvars_by_workspace.unwrap_or(false),
+                "use_chdir": self.config.terraform.use_chdir.unwrap_or(false),
+                "critical_resource_types": self.config.terraform.critical_resource_types,
+                "resource_categories_file": std::env::var("TFMCP_RESOURCE_CATEGORIES")
+                    .ok()
+                    .or_else(|| self.config.terraform.resource_categories_file.clone()),
+            },
+            "security": {
+                "allow_dangerous_operations": policy.allow_dangerous_operations,
+                "allow_auto_approve": policy.allow_auto_approve,
+                "allowed_commands": policy.allowed_commands,
+                "blocked_file_patterns": policy.blocked_file_patterns,
+                "max_resource_limit": policy.max_resource_limit,
+                "audit_logging_enabled": policy.audit_logging.enabled,
+            },
+            "cache": {
+                "documentation_ttl_secs": DOCUMENTATION_CACHE_TTL.as_secs(),
+                "providers_ttl_secs": PROVIDERS_CACHE_TTL.as_secs(),
+            },
+            "mcp": {
+                "tools": self.config.mcp.tools,
+                "allowed_tools": self.config.mcp.allowed_tools,
+                "denied_tools": self.config.mcp.denied_tools,
+            },
+            "environment": environment,
+        })
+    }
+
+    /// MCP settings from the config file, including the tool allow/deny lists
+    pub fn mcp_config(&self) -> &config::McpConfig {
+        &self.config.mcp
+    }
+
+    /// Handle used to cancel running terraform commands on shutdown
+    pub fn process_tracker(&self) -> crate::shared::process::ProcessTracker {
+        self.terraform_service.process_tracker().clone()
+    }
+
     // 現在のプロジェクトディレクトリを取得するメソッド
     pub fn get_project_directory(&self) -> PathBuf {
         self.terraform_service.get_project_directory().clone()
     }
 
+    pub fn list_project_files(&self) -> anyhow::Result<Vec<crate::terraform::files::ProjectFile>> {
+        self.terraform_service.list_project_files()
+    }
+
+    /// Analyze every Terraform root matching `pattern`, capped at
+    /// `max_directories` (and never more than `MAX_ANALYZE_DIRECTORIES`)
+    pub async fn analyze_all(
+        &self,
+        pattern: &str,
+        max_directories: Option<usize>,
+    ) -> anyhow::Result<crate::terraform::model::MultiDirectoryAnalysis> {
+        use crate::terraform::files::MAX_ANALYZE_DIRECTORIES;
+        let max = max_directories
+            .unwrap_or(MAX_ANALYZE_DIRECTORIES)
+            .min(MAX_ANALYZE_DIRECTORIES);
+        self.terraform_service.analyze_all(pattern, max).await
+    }
+
     // Module health analysis methods
 
     /// Analyze module health based on whitebox principles
@@ -403,6 +750,34 @@ impl TfMcp {
         self.terraform_service.analyze_module_health().await
     }
 
+    /// Append a health result to the project's score history
+    pub fn record_module_health(
+        &self,
+        health: &crate::terraform::model::ModuleHealthAnalysis,
+    ) -> anyhow::Result<crate::terraform::health_history::HealthHistoryEntry> {
+        self.health_history()?
+            .record(self.terraform_service.get_project_directory(), health)
+    }
+
+    /// Recorded health scores of the project and the change since the previous run
+    pub fn get_health_trend(
+        &self,
+    ) -> anyhow::Result<crate::terraform::health_history::HealthTrend> {
+        self.health_history()?
+            .trend(self.terraform_service.get_project_directory())
+    }
+
+    /// Health history store: TFMCP_HISTORY_DIR, then ~/.tfmcp/history
+    fn health_history(&self) -> anyhow::Result<crate::terraform::health_history::HealthHistory> {
+        use crate::terraform::health_history::HealthHistory;
+        std::env::var("TFMCP_HISTORY_DIR")
+            .ok()
+            .map(|path| crate::shared::utils::path::expand_tilde(&path))
+            .or_else(HealthHistory::default_directory)
+            .map(HealthHistory::new)
+            .ok_or_else(|| anyhow::anyhow!("Cannot locate the health history directory"))
+    }
+
     /// Build resource dependency graph for visualization
     pub async fn get_dependency_graph(
         &self,
@@ -410,6 +785,30 @@ impl TfMcp {
         self.terraform_service.get_dependency_graph().await
     }
 
+    /// Resources that depend on `address`, directly and transitively
+    pub async fn impact_analysis(
+        &self,
+        address: &str,
+    ) -> anyhow::Result<crate::terraform::model::ImpactAnalysis> {
+        self.terraform_service.impact_analysis(address).await
+    }
+
+    /// Where a resource, variable or output is declared
+    pub async fn locate_definition(
+        &self,
+        query: &str,
+    ) -> anyhow::Result<crate::terraform::model::DefinitionLookup> {
+        self.terraform_service.locate_definition(query).await
+    }
+
+    /// Dependency subgraph around a resource
+    pub async fn resource_subgraph(
+        &self,
+        address: &str,
+    ) -> anyhow::Result<crate::terraform::model::ResourceSubgraph> {
+        self.terraform_service.resource_subgraph(address).await
+    }
+
     /// Generate refactoring suggestions
     pub async fn suggest_refactoring(
         &self,
@@ -430,8 +829,113 @@ impl TfMcp {
     pub async fn analyze_plan(
         &self,
         include_risk: bool,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::plan_analyzer::PlanAnalysis> {
+        let policy = self.risk_policy()?;
+        self.terraform_service
+            .analyze_plan(include_risk, auto_init, policy.as_ref())
+            .await
+    }
+
+    /// Analyze a plan file or plan JSON already in the project, with risk scoring
+    pub async fn analyze_plan_file(
+        &self,
+        plan_file: &str,
+        include_risk: bool,
     ) -> anyhow::Result<crate::terraform::plan_analyzer::PlanAnalysis> {
-        self.terraform_service.analyze_plan(include_risk).await
+        let policy = self.risk_policy()?;
+        self.terraform_service
+            .analyze_plan_file(plan_file, include_risk, policy.as_ref())
+            .await
+    }
+
+    /// Path of the risk policy file: TFMCP_RISK_POLICY, then the config file setting
+    fn risk_policy_path(&self) -> Option<PathBuf> {
+        std::env::var("TFMCP_RISK_POLICY")
+            .ok()
+            .or_else(|| self.config.terraform.risk_policy_file.clone())
+            .map(|path| crate::shared::utils::path::expand_tilde(&path))
+    }
+
+    /// Load the configured risk policy, read fresh on each call so edits apply immediately
+    /// Risk policy from the policy file, with `terraform.critical_resource_types`
+    /// filled in when the file does not list critical types itself
+    fn risk_policy(&self) -> anyhow::Result<Option<crate::terraform::plan_analyzer::RiskPolicy>> {
+        let mut policy = self
+            .risk_policy_path()
+            .map(|path| crate::terraform::plan_analyzer::RiskPolicy::load(&path))
+            .transpose()?;
+        if let Some(types) = &self.config.terraform.critical_resource_types {
+            let policy = policy.get_or_insert_with(Default::default);
+            if policy.critical_resource_types.is_none() {
+                policy.critical_resource_types = Some(types.clone());
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Evaluate the current plan against the configured Rego policies with conftest
+    pub async fn evaluate_policies(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::conftest::PolicyEvaluation> {
+        self.terraform_service
+            .evaluate_policies(self.policy_dir().as_deref(), auto_init)
+            .await
+    }
+
+    /// Policy directory for conftest: TFMCP_POLICY_DIR, then the config file setting
+    fn policy_dir(&self) -> Option<PathBuf> {
+        std::env::var("TFMCP_POLICY_DIR")
+            .ok()
+            .or_else(|| self.config.terraform.policy_dir.clone())
+            .map(|path| crate::shared::utils::path::expand_tilde(&path))
+    }
+
+    /// Plan and render the attribute changes as a reviewable diff
+    pub async fn format_plan_diff(&self, auto_init: bool) -> anyhow::Result<String> {
+        self.terraform_service.format_plan_diff(auto_init).await
+    }
+
+    /// Plan summary, risk and optional cost delta with a proceed/caution/block verdict
+    pub async fn pre_apply_report(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::pre_apply::PreApplyReport> {
+        let policy = self.risk_policy()?;
+        self.terraform_service
+            .pre_apply_report(auto_init, policy.as_ref())
+            .await
+    }
+
+    /// Plan and store the result as a snapshot for later comparison
+    pub async fn save_plan_snapshot(
+        &self,
+        id: Option<&str>,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::plan_snapshot::PlanSnapshot> {
+        self.terraform_service
+            .save_plan_snapshot(id, auto_init)
+            .await
+    }
+
+    /// Compare a stored plan snapshot against a fresh plan
+    pub async fn diff_plans(
+        &self,
+        id: &str,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::plan_snapshot::PlanDiff> {
+        self.terraform_service
+            .diff_plan_snapshot(id, auto_init)
+            .await
+    }
+
+    /// Validate, then plan only if the configuration is valid
+    pub async fn preflight(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::plan_analyzer::PreflightResult> {
+        self.terraform_service.preflight(auto_init).await
     }
 
     /// Analyze terraform state with optional drift detection
@@ -439,9 +943,11 @@ impl TfMcp {
         &self,
         resource_type: Option<&str>,
         detect_drift: bool,
+        auto_init: bool,
+        workspace: Option<&str>,
     ) -> anyhow::Result<crate::terraform::state_analyzer::StateAnalysis> {
         self.terraform_service
-            .analyze_state(resource_type, detect_drift)
+            .analyze_state(resource_type, detect_drift, auto_init, workspace)
             .await
     }
 
@@ -450,8 +956,11 @@ impl TfMcp {
         &self,
         action: &str,
         name: Option<&str>,
+        with_counts: bool,
     ) -> anyhow::Result<crate::terraform::workspace::WorkspaceResult> {
-        self.terraform_service.workspace(action, name).await
+        self.terraform_service
+            .workspace(action, name, with_counts)
+            .await
     }
 
     /// Import a resource
@@ -489,8 +998,9 @@ impl TfMcp {
     pub async fn output(
         &self,
         name: Option<&str>,
+        reveal_sensitive: bool,
     ) -> anyhow::Result<crate::terraform::output::OutputResult> {
-        self.terraform_service.output(name).await
+        self.terraform_service.output(name, reveal_sensitive).await
     }
 
     /// Execute taint/untaint operation
@@ -517,4 +1027,184 @@ impl TfMcp {
     ) -> anyhow::Result<crate::terraform::providers::ProvidersResult> {
         self.terraform_service.get_providers(include_lock).await
     }
+
+    /// Whether terraform operations are recorded in the audit log
+    pub fn audit_log_enabled(&self) -> bool {
+        self.terraform_service
+            .get_security_policy()
+            .audit_logging
+            .enabled
+    }
+
+    /// The last `count` audit log entries, with secrets in arguments redacted
+    pub fn recent_audit_entries(
+        &self,
+        count: usize,
+    ) -> anyhow::Result<Vec<crate::shared::security::AuditLogEntry>> {
+        self.terraform_service.recent_audit_entries(count)
+    }
+
+    /// Report where state is stored according to the backend configuration
+    pub fn get_backend_config(&self) -> anyhow::Result<crate::terraform::backend::BackendConfig> {
+        self.terraform_service.get_backend_config()
+    }
+
+    /// Scan terraform state for attributes holding secrets
+    pub async fn scan_state_for_secrets(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<crate::terraform::state_secrets::StateSecretScan> {
+        self.terraform_service
+            .scan_state_for_secrets(auto_init)
+            .await
+    }
+
+    /// Find state resources no longer declared in the configuration
+    pub async fn find_orphaned_resources(
+        &self,
+    ) -> anyhow::Result<crate::terraform::orphans::OrphanReport> {
+        self.terraform_service.find_orphaned_resources().await
+    }
+
+    /// Classify the source of every module call in the project
+    pub async fn check_module_sources(
+        &self,
+    ) -> anyhow::Result<Vec<crate::terraform::module_source::ModuleSourceCheck>> {
+        self.terraform_service.check_module_sources().await
+    }
+
+    /// Flag resources commonly associated with high cost
+    pub async fn identify_cost_drivers(
+        &self,
+    ) -> anyhow::Result<crate::terraform::cost_drivers::CostDriverReport> {
+        self.terraform_service.identify_cost_drivers().await
+    }
+
+    /// Pull terraform state with sensitive values masked
+    pub async fn pull_state(
+        &self,
+        auto_init: bool,
+        max_bytes: Option<usize>,
+    ) -> anyhow::Result<crate::terraform::state_pull::PulledState> {
+        self.terraform_service
+            .pull_state(auto_init, max_bytes)
+            .await
+    }
+
+    /// Get the schema of a resource or data source type from the installed providers
+    pub async fn get_resource_schema(
+        &self,
+        resource_type: &str,
+        data_source: bool,
+    ) -> anyhow::Result<crate::terraform::schema::ResourceSchema> {
+        self.terraform_service
+            .resource_schema(resource_type, data_source)
+            .await
+    }
+
+    /// Fetch and cache the provider schemas for argument checks. With
+    /// `background` the fetch runs in a spawned task and `None` is returned
+    /// right away.
+    pub async fn warm_provider_schemas(
+        &self,
+        background: bool,
+    ) -> anyhow::Result<Option<crate::terraform::schema_hints::WarmedSchemas>> {
+        if !background {
+            return self
+                .terraform_service
+                .warm_provider_schemas()
+                .await
+                .map(Some);
+        }
+        let service = self.terraform_service.clone();
+        tokio::spawn(async move {
+            if let Err(e) = service.warm_provider_schemas().await {
+                logging::warn(&format!("Failed to warm provider schemas: {}", e));
+            }
+        });
+        Ok(None)
+    }
+
+    /// Scaffold a resource block from the locally installed provider schema
+    pub async fn scaffold_resource(
+        &self,
+        resource_type: &str,
+        name: &str,
+    ) -> anyhow::Result<Option<crate::terraform::scaffold::ResourceScaffold>> {
+        self.terraform_service
+            .scaffold_resource(resource_type, name)
+            .await
+    }
+
+    /// Starter tfvars for the project's variables, optionally written to a file
+    pub async fn generate_tfvars(
+        &self,
+        include_defaults: bool,
+        write_to: Option<&str>,
+        force: bool,
+    ) -> anyhow::Result<crate::terraform::tfvars::TfvarsSkeleton> {
+        self.terraform_service
+            .generate_tfvars(include_defaults, write_to, force)
+            .await
+    }
+
+    /// Resource and data source types of an installed provider
+    pub async fn list_provider_resources(
+        &self,
+        namespace: &str,
+        name: &str,
+        filter: Option<&str>,
+    ) -> anyhow::Result<Option<crate::terraform::provider_resources::ProviderResourceList>> {
+        self.terraform_service
+            .list_provider_resources(namespace, name, filter)
+            .await
+    }
+
+    /// Verify the provider lock file against configured constraints
+    pub async fn verify_provider_lock(
+        &self,
+    ) -> anyhow::Result<crate::terraform::providers::LockVerification> {
+        self.terraform_service.verify_provider_lock().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_binary_reports_install_hint() {
+        let err = resolve_terraform_binary(Some("/nonexistent/bin/terraform"), None, "terraform")
+            .expect_err("bogus path should not resolve");
+        let message = err.to_string();
+        assert!(message.contains("/nonexistent/bin/terraform"));
+        assert!(message.contains("TFMCP_TERRAFORM_BIN points to"));
+        assert!(message.contains("https://developer.hashicorp.com/terraform/install"));
+
+        let err = resolve_terraform_binary(None, None, "tfmcp-no-such-terraform")
+            .expect_err("unknown binary name should not resolve");
+        assert!(
+            err.to_string()
+                .contains("'tfmcp-no-such-terraform' is not on PATH")
+        );
+    }
+
+    #[test]
+    fn test_env_override_takes_precedence_over_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("tofu");
+        std::fs::write(&binary, "").unwrap();
+
+        let resolved = resolve_terraform_binary(
+            Some(binary.to_str().unwrap()),
+            Some("/nonexistent/terraform"),
+            "terraform",
+        )
+        .unwrap();
+        assert_eq!(resolved, binary);
+
+        let err = resolve_terraform_binary(None, Some("/nonexistent/terraform"), "terraform")
+            .expect_err("configured path does not exist");
+        assert!(err.to_string().contains("terraform.executable_path"));
+    }
 }
@@ -3,9 +3,13 @@ pub mod registry {
     pub mod batch;
     pub mod cache;
     pub mod client;
+    pub mod doc_search;
     pub mod fallback;
     pub mod policy;
     pub mod provider;
+    pub mod rate_limit;
+    #[cfg(test)]
+    mod test_server;
 
     // Re-export commonly used items
     pub use batch::BatchFetcher;
@@ -31,24 +35,52 @@ pub mod prompts {
 
 pub mod shared {
     pub mod logging;
+    pub mod process;
     pub mod security;
     pub mod utils;
 }
 
 pub mod terraform {
+    pub mod analysis_cache;
     pub mod analyzer;
+    pub mod approval;
+    pub mod backend;
+    pub mod categories;
+    pub mod cli_config;
+    pub mod conftest;
+    pub mod cost_drivers;
+    pub mod error;
+    pub mod file_cache;
+    pub mod files;
     pub mod fmt;
     pub mod graph;
+    pub mod health_history;
     pub mod import_helper;
+    pub mod json_config;
     pub mod model;
+    pub mod module_source;
+    pub mod orphans;
     pub mod output;
     pub mod parser;
     pub mod plan_analyzer;
+    pub mod plan_diff;
+    pub mod plan_snapshot;
+    pub mod pre_apply;
+    pub mod provider_resources;
     pub mod providers;
     pub mod refresh;
+    pub mod resource_list;
+    pub mod saved_plan;
+    pub mod scaffold;
+    pub mod schema;
+    pub mod schema_hints;
     pub mod service;
     pub mod state_analyzer;
+    pub mod state_pull;
+    pub mod state_secrets;
     pub mod taint;
+    pub mod tfvars;
+    pub mod version;
     pub mod workspace;
 }
 
@@ -57,9 +89,11 @@ pub mod core {
 }
 
 pub mod mcp {
+    pub mod output_validation;
     pub mod resources;
     pub mod server;
     pub mod types;
+    pub mod watcher;
 }
 
 pub mod config;
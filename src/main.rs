@@ -7,14 +7,17 @@ mod registry;
 mod shared;
 mod terraform;
 
-use clap::{Parser, Subcommand};
-use core::tfmcp::TfMcp;
+use clap::{Parser, Subcommand, ValueEnum};
+use core::tfmcp::{AnalysisGates, TfMcp};
 use mcp::server::{TfMcpServer, ToolFilter};
 use shared::logging;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{fmt::format::FmtSpan, layer::SubscriberExt, util::SubscriberInitExt};
 
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Exit code of `analyze` when the report fails one of its gates
+const EXIT_GATE_FAILED: i32 = 2;
+
 #[derive(Parser)]
 #[command(
     name = "tfmcp",
@@ -56,10 +59,38 @@ enum Commands {
         /// Individual tools to enable (comma-separated, overrides --toolsets)
         #[arg(long, value_delimiter = ',')]
         tools: Option<Vec<String>>,
+        /// Transport to serve MCP over
+        #[arg(long, value_enum, default_value_t = Transport::Stdio)]
+        transport: Transport,
+        /// Address to listen on when using the HTTP transport
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        listen: std::net::SocketAddr,
     },
 
     #[command(name = "analyze", about = "Analyze Terraform configurations")]
-    Analyze,
+    Analyze {
+        /// Print the analysis, guideline checks and module health as one JSON
+        /// report and exit with status 2 when it has critical issues
+        #[arg(long)]
+        json: bool,
+        /// Exit with status 2 when the module health score is below SCORE
+        #[arg(long, value_name = "SCORE", value_parser = clap::value_parser!(u8).range(0..=100))]
+        fail_under: Option<u8>,
+        /// Exit with status 2 when the module cohesion score is below SCORE
+        #[arg(long, value_name = "SCORE", value_parser = clap::value_parser!(u8).range(0..=100))]
+        min_cohesion: Option<u8>,
+        /// Exit with status 2 when the security scan finds any hardcoded secret
+        #[arg(long)]
+        fail_on_secrets: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Transport {
+    /// JSON-RPC over stdin/stdout
+    Stdio,
+    /// Streamable HTTP with SSE, served at /mcp
+    Http,
 }
 
 #[tokio::main]
@@ -76,12 +107,23 @@ async fn main() {
 
     match &cli.command {
         Some(cmd) => match cmd {
-            Commands::Mcp { toolsets, tools } => {
+            Commands::Mcp {
+                toolsets,
+                tools,
+                transport,
+                listen,
+            } => {
                 logging::info("Starting tfmcp in MCP server mode");
                 let tool_filter = ToolFilter::from_cli(toolsets, tools.as_deref());
                 match init_tfmcp(&cli).await {
                     Ok(tfmcp) => {
-                        if let Err(err) = TfMcpServer::serve_stdio(tfmcp, tool_filter).await {
+                        let result = match transport {
+                            Transport::Stdio => TfMcpServer::serve_stdio(tfmcp, tool_filter).await,
+                            Transport::Http => {
+                                TfMcpServer::serve_http(tfmcp, tool_filter, *listen).await
+                            }
+                        };
+                        if let Err(err) = result {
                             logging::error(&format!("Error launching MCP server: {:?}", err));
                             std::process::exit(1);
                         }
@@ -92,15 +134,33 @@ async fn main() {
                     }
                 }
             }
-            Commands::Analyze => {
+            Commands::Analyze {
+                json,
+                fail_under,
+                min_cohesion,
+                fail_on_secrets,
+            } => {
                 logging::info("Starting Terraform configuration analysis");
+                let gates = AnalysisGates {
+                    fail_on_critical: *json,
+                    fail_under: *fail_under,
+                    min_cohesion: *min_cohesion,
+                    fail_on_secrets: *fail_on_secrets,
+                };
                 match init_tfmcp(&cli).await {
-                    Ok(mut tfmcp) => {
-                        if let Err(err) = tfmcp.analyze_terraform().await {
+                    Ok(mut tfmcp) => match run_analyze(&mut tfmcp, *json, &gates).await {
+                        Ok(failures) if !failures.is_empty() => {
+                            for failure in &failures {
+                                logging::error(failure);
+                            }
+                            std::process::exit(EXIT_GATE_FAILED);
+                        }
+                        Ok(_) => {}
+                        Err(err) => {
                             logging::error(&format!("Error analyzing Terraform: {:?}", err));
                             std::process::exit(1);
                         }
-                    }
+                    },
                     Err(e) => {
                         logging::error(&format!("Failed to initialize tfmcp: {}", e));
                         std::process::exit(1);
@@ -115,6 +175,25 @@ async fn main() {
     };
 }
 
+/// Print the analysis and return the gates it fails
+async fn run_analyze(
+    tfmcp: &mut TfMcp,
+    json: bool,
+    gates: &AnalysisGates,
+) -> anyhow::Result<Vec<String>> {
+    if !json {
+        tfmcp.analyze_terraform().await?;
+        if gates.is_empty() {
+            return Ok(Vec::new());
+        }
+    }
+    let report = tfmcp.analysis_report().await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+    Ok(gates.failures(&report))
+}
+
 async fn init_tfmcp(cli: &Cli) -> anyhow::Result<TfMcp> {
     let config_path = cli.config.clone();
     let dir_path = cli.dir.clone();
@@ -145,6 +224,12 @@ fn init_logging() {
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| format!("tfmcp={},reqwest=warn,hyper=warn", filter).into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(
+            // stdout carries the stdio transport; tool_call spans log their
+            // fields and timing when they close
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_span_events(FmtSpan::CLOSE),
+        )
         .init();
 }
@@ -1,3 +1,5 @@
+pub mod output_validation;
 pub mod resources;
 pub mod server;
 pub mod types;
+pub mod watcher;
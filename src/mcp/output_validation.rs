@@ -0,0 +1,285 @@
+//! Debug check of tool responses against their declared `outputSchema`.
+//!
+//! Enabled with `TFMCP_VALIDATE_OUTPUT=true`. Each successful response of a
+//! tool that declares an output schema is checked and every mismatch is
+//! reported as a warning, so schema drift shows up in test runs instead of
+//! in clients. Tools returning raw terraform output or ad-hoc JSON declare no
+//! schema and pass unchecked. Only the schema keywords the tools use are understood:
+//! `type`, `enum`, `required`, `properties`, `additionalProperties: false`,
+//! `items`, `anyOf`/`oneOf` and local `$ref`s into `$defs`/`definitions`.
+
+use rmcp::model::{CallToolResult, Tool};
+use serde_json::Value;
+
+/// Environment variable enabling output validation
+pub const VALIDATE_OUTPUT_ENV: &str = "TFMCP_VALIDATE_OUTPUT";
+
+pub fn output_validation_enabled() -> bool {
+    std::env::var(VALIDATE_OUTPUT_ENV).is_ok_and(|val| val.to_lowercase() == "true")
+}
+
+/// Check `result` against the output schema of `tool`, passing one message
+/// per mismatch to `warn`. Returns the number of mismatches; tools without
+/// an output schema and error results are not checked.
+pub fn check_tool_output(
+    tool: &Tool,
+    result: &CallToolResult,
+    mut warn: impl FnMut(&str),
+) -> usize {
+    let Some(schema) = &tool.output_schema else {
+        return 0;
+    };
+    if result.is_error == Some(true) {
+        return 0;
+    }
+
+    let schema = Value::Object(schema.as_ref().clone());
+    let mismatches = match response_payload(result) {
+        Some(payload) => schema_mismatches(&schema, &payload),
+        None => vec!["$: response has no JSON payload".to_string()],
+    };
+    for mismatch in &mismatches {
+        warn(&format!(
+            "Tool '{}' response does not match its outputSchema: {}",
+            tool.name, mismatch
+        ));
+    }
+    mismatches.len()
+}
+
+/// Structured content, or else the first text content parsed as JSON
+fn response_payload(result: &CallToolResult) -> Option<Value> {
+    if let Some(structured) = &result.structured_content {
+        return Some(structured.clone());
+    }
+    let text = result.content.first()?.raw.as_text()?;
+    serde_json::from_str(&text.text).ok()
+}
+
+/// Describe each way `value` fails `schema`, as `<json path>: <problem>`
+pub fn schema_mismatches(schema: &Value, value: &Value) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    check(schema, schema, value, "$", &mut mismatches);
+    mismatches
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, path: &str, out: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve_ref(root, reference) {
+            Some(target) => check(root, target, value, path, out),
+            None => out.push(format!("{}: unresolvable $ref '{}'", path, reference)),
+        }
+        return;
+    }
+
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(branches) = schema.get(keyword).and_then(Value::as_array) {
+            let matches_any = branches.iter().any(|branch| {
+                let mut branch_errors = Vec::new();
+                check(root, branch, value, path, &mut branch_errors);
+                branch_errors.is_empty()
+            });
+            if !matches_any {
+                out.push(format!("{}: matches no {} branch", path, keyword));
+            }
+        }
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| has_type(value, name)) {
+            out.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            out.push(format!(
+                "{}: {} is not one of the allowed values",
+                path, value
+            ));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for field in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(field) {
+                    out.push(format!("{}: missing required field '{}'", path, field));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, field_value) in object {
+            match properties.and_then(|props| props.get(key)) {
+                Some(field_schema) => check(
+                    root,
+                    field_schema,
+                    field_value,
+                    &format!("{}.{}", path, key),
+                    out,
+                ),
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    out.push(format!("{}: unexpected field '{}'", path, key));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            check(root, items, item, &format!("{}[{}]", path, index), out);
+        }
+    }
+}
+
+/// Resolve `#/$defs/Name` or `#/definitions/Name` within the root schema
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn tool_with_output_schema(schema: Value) -> Tool {
+        let Value::Object(schema) = schema else {
+            panic!("schema must be an object");
+        };
+        Tool::new("get_output", "test tool", Arc::new(serde_json::Map::new()))
+            .with_raw_output_schema(Arc::new(schema))
+    }
+
+    fn output_schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["output"],
+            "properties": {
+                "output": {"type": "string"},
+                "changes": {"type": "array", "items": {"$ref": "#/$defs/Change"}}
+            },
+            "$defs": {
+                "Change": {
+                    "type": "object",
+                    "required": ["action"],
+                    "properties": {"action": {"enum": ["create", "update", "delete"]}}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_conforming_response_has_no_warnings() {
+        let tool = tool_with_output_schema(output_schema());
+        let result = CallToolResult::success(vec![Content::text(
+            json!({"output": "ok", "changes": [{"action": "create"}]}).to_string(),
+        )]);
+
+        let mut warnings = Vec::new();
+        let count = check_tool_output(&tool, &result, |w| warnings.push(w.to_string()));
+
+        assert_eq!(count, 0);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_non_conforming_response_warns() {
+        let tool = tool_with_output_schema(output_schema());
+        // `result` instead of the declared `output`, and an unknown action
+        let result = CallToolResult::success(vec![Content::text(
+            json!({"result": "ok", "changes": [{"action": "replace"}]}).to_string(),
+        )]);
+
+        let mut warnings = Vec::new();
+        let count = check_tool_output(&tool, &result, |w| warnings.push(w.to_string()));
+
+        assert_eq!(count, 2);
+        assert!(warnings[0].contains("get_output"));
+        assert!(warnings[0].contains("$: missing required field 'output'"));
+        assert!(warnings[1].contains("$.changes[0].action"));
+    }
+
+    #[test]
+    fn test_non_json_response_warns() {
+        let tool = tool_with_output_schema(output_schema());
+        let result = CallToolResult::success(vec![Content::text("plain text")]);
+
+        let mut warnings = Vec::new();
+        check_tool_output(&tool, &result, |w| warnings.push(w.to_string()));
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("no JSON payload"));
+    }
+
+    #[test]
+    fn test_tools_without_output_schema_are_not_checked() {
+        let tool = Tool::new("plain", "no schema", Arc::new(serde_json::Map::new()));
+        let result = CallToolResult::success(vec![Content::text("anything")]);
+        assert_eq!(
+            check_tool_output(&tool, &result, |_| panic!("no warning")),
+            0
+        );
+    }
+
+    #[test]
+    fn test_schema_mismatches_types() {
+        let schema = json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "count": {"type": "integer"},
+                "note": {"type": ["string", "null"]}
+            }
+        });
+        assert!(schema_mismatches(&schema, &json!({"count": 3, "note": null})).is_empty());
+        assert_eq!(
+            schema_mismatches(&schema, &json!({"count": "3", "extra": true})),
+            vec![
+                "$.count: expected integer, got string".to_string(),
+                "$: unexpected field 'extra'".to_string(),
+            ]
+        );
+    }
+}
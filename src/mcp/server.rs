@@ -1,60 +1,216 @@
 //! RMCP-based MCP server implementation for tfmcp.
 
+use crate::config::McpConfig;
 use crate::core::tfmcp::TfMcp;
+use crate::mcp::output_validation::{check_tool_output, output_validation_enabled};
 use crate::mcp::types::*;
+use crate::prompts::builder::{DOCUMENT_MODULE_PROMPT, build_document_module_prompt};
+use crate::registry::client::{RegistryError, set_mcp_client};
+use crate::registry::doc_search::DEFAULT_DOC_SEARCH_LIMIT;
 use crate::registry::fallback::RegistryClientWithFallback;
 use crate::registry::policy::PolicyClient;
 use crate::registry::provider::ProviderResolver;
 use crate::shared::logging;
+use crate::shared::process::{ProcessTracker, SHUTDOWN_GRACE_PERIOD};
+use crate::terraform::analyzer::{subgraph_to_mermaid, suggest_provider_pin};
+use crate::terraform::error::TerraformError;
+use crate::terraform::graph::dot_to_dependency_graph;
+use crate::terraform::model::{
+    DefinitionLookup, DetailedValidationResult, ImpactAnalysis, LooseProviderConstraint,
+    MultiDirectoryAnalysis, ResourceDependencyGraph, SubgraphFormat,
+};
+use crate::terraform::module_source::{ModuleSourceCheck, ModuleSourceReport};
+use crate::terraform::plan_analyzer::{DestroyPreview, NoiseFilter, PlanAnalysis, PreflightResult};
+use crate::terraform::provider_resources::list_from_docs;
+use crate::terraform::resource_list::ResourceListing;
+use crate::terraform::scaffold::{ResourceScaffold, scaffold_from_docs};
 use rmcp::{
     ErrorData as McpError, ServerHandler,
-    handler::server::{tool::ToolRouter, wrapper::Parameters},
+    handler::server::{common::schema_for_output, tool::ToolRouter, wrapper::Parameters},
     model::{
-        Annotated, CallToolRequestParams, CallToolResult, Content, Implementation,
-        ListResourceTemplatesResult, ListResourcesResult, ListToolsResult, PaginatedRequestParams,
-        RawResource, RawResourceTemplate, ReadResourceRequestParams, ReadResourceResult,
-        ResourceContents, ServerCapabilities, ServerInfo,
+        Annotated, CallToolRequestParams, CallToolResult, Content, ErrorCode,
+        GetPromptRequestParams, GetPromptResult, Implementation, InitializeRequestParams,
+        InitializeResult, JsonObject, ListPromptsResult, ListResourceTemplatesResult,
+        ListResourcesResult, ListToolsResult, LoggingLevel, LoggingMessageNotificationParam, Meta,
+        PaginatedRequestParams, ProgressNotificationParam, Prompt, PromptMessage,
+        PromptMessageRole, RawResource, RawResourceTemplate, ReadResourceRequestParams,
+        ReadResourceResult, ResourceContents, ResourceUpdatedNotificationParam, ServerCapabilities,
+        ServerInfo, SetLevelRequestParams, SubscribeRequestParams, Tool, UnsubscribeRequestParams,
     },
-    service::{RequestContext, RoleServer, ServiceExt},
+    service::{Peer, RequestContext, RoleServer, ServiceExt},
     tool, tool_router,
 };
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+use tracing::Instrument;
+
+use super::watcher::{DEFAULT_DEBOUNCE, PROJECT_FILES_URI, ProjectFileWatcher};
 
 // Resource content for MCP resources
 use super::resources::{
     SERVER_INSTRUCTIONS, TERRAFORM_BEST_PRACTICES, get_module_dev_content, get_style_guide_content,
 };
 
+/// Path of the streamable HTTP endpoint served by `serve_http`
+pub const HTTP_ENDPOINT_PATH: &str = "/mcp";
+
+/// Resolve when the process receives SIGINT or (on Unix) SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Kill running terraform commands and give in-flight operations time to
+/// record their audit entries before the process exits.
+async fn shutdown_operations(processes: &ProcessTracker) {
+    if processes.active_operations() > 0 {
+        logging::info("Cancelling in-flight terraform operations");
+    }
+    if !processes.shutdown(SHUTDOWN_GRACE_PERIOD).await {
+        logging::warn("Timed out waiting for in-flight operations to finish");
+    }
+}
+
 /// Serialize a value to pretty JSON, returning an McpError on failure.
 fn to_json(value: &impl serde::Serialize) -> Result<String, McpError> {
     serde_json::to_string_pretty(value)
         .map_err(|e| McpError::internal_error(format!("JSON serialization failed: {e}"), None))
 }
 
+/// `outputSchema` of a tool whose result is `T`, checked by
+/// `TFMCP_VALIDATE_OUTPUT`
+fn output_schema<T: schemars::JsonSchema + 'static>() -> Arc<JsonObject> {
+    schema_for_output::<T>().unwrap_or_else(|e| panic!("invalid output schema: {e}"))
+}
+
+/// Success result for a tool declaring an `outputSchema`: the pretty JSON text
+/// plus the same value as structured content
+fn structured_success(value: &impl serde::Serialize) -> Result<CallToolResult, McpError> {
+    let mut result = CallToolResult::success(vec![Content::text(to_json(value)?)]);
+    result.structured_content =
+        Some(serde_json::to_value(value).map_err(|e| {
+            McpError::internal_error(format!("JSON serialization failed: {e}"), None)
+        })?);
+    Ok(result)
+}
+
+/// Report a failed tool call. Typed Terraform failures the client has to act
+/// on (init, a held lock, policy, a missing binary) become JSON-RPC errors with
+/// the variant in `data.kind`. Failures of the configuration itself, such as
+/// validation or plan errors, stay tool error results prefixed with `context`,
+/// followed by any JSON diagnostics terraform printed, so the model can read
+/// the diagnostics and fix them.
+fn tool_error(context: &str, error: impl Into<anyhow::Error>) -> Result<CallToolResult, McpError> {
+    let error = error.into();
+    let Some(tf_error) = error.downcast_ref::<TerraformError>() else {
+        return Ok(CallToolResult::error(vec![Content::text(format!(
+            "{}: {}",
+            context, error
+        ))]));
+    };
+
+    let code = match tf_error {
+        TerraformError::InitRequired
+        | TerraformError::StateLocked { .. }
+        | TerraformError::PolicyBlocked(_)
+        | TerraformError::PlanFileChanged { .. }
+        | TerraformError::ApprovalRejected(_) => ErrorCode::INVALID_REQUEST,
+        TerraformError::BinaryNotFound { .. } | TerraformError::Cancelled => {
+            ErrorCode::INTERNAL_ERROR
+        }
+        TerraformError::ValidationFailed(_)
+        | TerraformError::ExecFailed { .. }
+        | TerraformError::Io(_) => {
+            let mut content = vec![Content::text(format!("{}: {}", context, error))];
+            if let TerraformError::ExecFailed { diagnostics, .. } = tf_error
+                && !diagnostics.is_empty()
+            {
+                content.push(Content::text(to_json(
+                    &serde_json::json!({ "diagnostics": diagnostics }),
+                )?));
+            }
+            return Ok(CallToolResult::error(content));
+        }
+    };
+    let mut data = serde_json::json!({
+        "kind": tf_error.kind(),
+        "retryable": tf_error.is_retryable(),
+    });
+    if let TerraformError::StateLocked {
+        lock_id: Some(lock_id),
+        ..
+    } = tf_error
+    {
+        data["lock_id"] = serde_json::json!(lock_id);
+    }
+    Err(McpError::new(
+        code,
+        format!("{}: {}", context, tf_error),
+        Some(data),
+    ))
+}
+
 /// Tool filtering configuration.
 #[derive(Clone, Debug)]
 pub struct ToolFilter {
     enabled_tools: Option<HashSet<String>>,
+    /// Allowlist from the config file; `None` allows every tool
+    allowed_by_policy: Option<HashSet<String>>,
+    /// Denylist from the config file, applied after everything else
+    denied_by_policy: HashSet<String>,
 }
 
 impl ToolFilter {
     /// Create a filter that enables all tools.
     pub fn all() -> Self {
+        Self::from_enabled(None)
+    }
+
+    fn from_enabled(enabled_tools: Option<HashSet<String>>) -> Self {
         Self {
-            enabled_tools: None,
+            enabled_tools,
+            allowed_by_policy: None,
+            denied_by_policy: HashSet::new(),
         }
     }
 
+    /// Apply the `mcp.allowed_tools` / `mcp.denied_tools` lists of the config file.
+    pub fn with_policy(mut self, config: &McpConfig) -> Self {
+        self.allowed_by_policy = config
+            .allowed_tools
+            .as_ref()
+            .map(|tools| tools.iter().cloned().collect());
+        self.denied_by_policy = config.denied_tools.iter().cloned().collect();
+        self
+    }
+
     /// Create a filter from toolset categories and optional individual tool list.
     pub fn from_cli(toolsets: &[String], tools: Option<&[String]>) -> Self {
         // If individual tools specified, use those exclusively
         if let Some(tool_list) = tools {
-            return Self {
-                enabled_tools: Some(tool_list.iter().map(|s| s.to_string()).collect()),
-            };
+            return Self::from_enabled(Some(tool_list.iter().map(|s| s.to_string()).collect()));
         }
 
         let mut enabled = HashSet::new();
@@ -85,36 +241,89 @@ impl ToolFilter {
         if enabled.is_empty() {
             Self::all()
         } else {
-            Self {
-                enabled_tools: Some(enabled),
-            }
+            Self::from_enabled(Some(enabled))
         }
     }
 
     /// Check if a tool is enabled.
     pub fn is_enabled(&self, tool_name: &str) -> bool {
-        match &self.enabled_tools {
+        let selected = match &self.enabled_tools {
             None => true,
             Some(set) => set.contains(tool_name),
+        };
+        selected && !self.is_disabled_by_policy(tool_name)
+    }
+
+    /// Check if the config file's allow/deny lists disable a tool.
+    pub fn is_disabled_by_policy(&self, tool_name: &str) -> bool {
+        self.denied_by_policy.contains(tool_name)
+            || self
+                .allowed_by_policy
+                .as_ref()
+                .is_some_and(|allowed| !allowed.contains(tool_name))
+    }
+}
+
+/// A tool's metadata as returned by `describe_tools`. The hints are `None`
+/// when the tool does not declare them, in which case the MCP defaults
+/// apply (not read-only, destructive, not idempotent, open world).
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolDescription {
+    pub name: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub input_schema: JsonObject,
+    pub read_only: Option<bool>,
+    pub destructive: Option<bool>,
+    pub idempotent: Option<bool>,
+    pub open_world: Option<bool>,
+}
+
+impl From<Tool> for ToolDescription {
+    fn from(tool: Tool) -> Self {
+        let annotations = tool.annotations.unwrap_or_default();
+        Self {
+            name: tool.name.into_owned(),
+            title: tool.title.or(annotations.title),
+            description: tool.description.map(|d| d.into_owned()),
+            input_schema: (*tool.input_schema).clone(),
+            read_only: annotations.read_only_hint,
+            destructive: annotations.destructive_hint,
+            idempotent: annotations.idempotent_hint,
+            open_world: annotations.open_world_hint,
         }
     }
 }
 
 const TOOLSET_TERRAFORM: &[&str] = &[
     "init_terraform",
+    "get_terraform_version",
+    "get_cli_config",
+    "get_backend_config",
     "get_terraform_plan",
+    "plan_destroy",
+    "preflight",
     "apply_terraform",
+    "save_plan",
+    "apply_saved_plan",
+    "propose_apply",
+    "confirm_apply",
     "destroy_terraform",
     "validate_terraform",
     "validate_terraform_detailed",
     "get_terraform_state",
+    "pull_state",
     "list_terraform_resources",
     "set_terraform_directory",
     "terraform_workspace",
     "terraform_fmt",
+    "generate_tfvars",
     "terraform_graph",
     "terraform_output",
     "terraform_providers",
+    "verify_provider_lock",
+    "get_resource_schema",
+    "warm_provider_schemas",
     "terraform_import",
     "terraform_taint",
     "terraform_refresh",
@@ -124,9 +333,15 @@ const TOOLSET_REGISTRY: &[&str] = &[
     "search_terraform_providers",
     "get_provider_info",
     "get_provider_docs",
+    "search_provider_docs",
+    "list_provider_resources",
+    "scaffold_resource",
     "get_provider_capabilities",
     "search_terraform_modules",
     "get_module_details",
+    "list_module_submodules",
+    "list_module_examples",
+    "get_module_readme",
     "get_latest_module_version",
     "get_latest_provider_version",
     "search_policies",
@@ -135,14 +350,127 @@ const TOOLSET_REGISTRY: &[&str] = &[
 
 const TOOLSET_ANALYSIS: &[&str] = &[
     "analyze_terraform",
+    "analyze_all",
     "analyze_module_health",
+    "get_health_trend",
     "get_resource_dependency_graph",
+    "impact_analysis",
+    "resource_subgraph",
+    "locate_definition",
     "suggest_module_refactoring",
     "get_security_status",
+    "verify_module_source",
     "analyze_plan",
+    "analyze_plan_file",
+    "format_plan_diff",
+    "pre_apply_report",
+    "identify_cost_drivers",
+    "save_plan_snapshot",
+    "diff_plans",
+    "evaluate_policies",
     "analyze_state",
+    "scan_state_for_secrets",
+    "find_orphaned_resources",
+    "describe_tools",
 ];
 
+/// Resource listing recent audit log entries; `?count=N` selects how many
+const AUDIT_LOG_URI: &str = "terraform://audit-log";
+
+/// Audit entries returned when the resource URI has no `count`
+const DEFAULT_AUDIT_LOG_ENTRIES: usize = 50;
+
+/// Upper bound on `count` for the audit log resource
+const MAX_AUDIT_LOG_ENTRIES: usize = 1000;
+
+/// `count` from the query of an audit log URI, e.g. `?count=20`
+fn audit_log_count(query: &str) -> Result<usize, McpError> {
+    let Some(value) = query
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("count="))
+    else {
+        return Ok(DEFAULT_AUDIT_LOG_ENTRIES);
+    };
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count.min(MAX_AUDIT_LOG_ENTRIES)),
+        _ => Err(McpError::invalid_params(
+            format!(
+                "Invalid audit log count '{}': expected a positive number",
+                value
+            ),
+            None,
+        )),
+    }
+}
+
+/// Logger name on the notifications carrying streamed `plan -json` messages
+const PLAN_LOGGER: &str = "terraform.plan";
+
+/// Run a plan, forwarding each `plan -json` message to the client as it is
+/// emitted, then return the plan summary
+async fn stream_plan(
+    tfmcp: &TfMcp,
+    auto_init: bool,
+    extra_args: &[String],
+    peer: Peer<RoleServer>,
+    meta: Meta,
+) -> Result<CallToolResult, McpError> {
+    let (messages, mut received) = tokio::sync::mpsc::unbounded_channel::<serde_json::Value>();
+    let progress_token = meta.get_progress_token();
+    let forwarder = tokio::spawn(async move {
+        let mut count = 0u32;
+        while let Some(message) = received.recv().await {
+            count += 1;
+            if let Some(token) = &progress_token {
+                let mut progress = ProgressNotificationParam::new(token.clone(), count as f64);
+                if let Some(text) = message.get("@message").and_then(|m| m.as_str()) {
+                    progress = progress.with_message(text);
+                }
+                if let Err(e) = peer.notify_progress(progress).await {
+                    logging::warn(&format!("Failed to send plan progress: {}", e));
+                }
+            }
+            let log = LoggingMessageNotificationParam::new(LoggingLevel::Info, message)
+                .with_logger(PLAN_LOGGER);
+            if let Err(e) = peer.notify_logging_message(log).await {
+                logging::warn(&format!("Failed to send plan message: {}", e));
+            }
+        }
+        count
+    });
+
+    let result = {
+        let mut on_message = move |message| {
+            let _ = messages.send(message);
+        };
+        tfmcp
+            .get_terraform_plan_streamed(auto_init, extra_args, &mut on_message)
+            .await
+    };
+    // The sender is gone, so this returns once every message has been sent
+    let streamed = forwarder.await.unwrap_or_default();
+
+    match result {
+        Ok(analysis) => {
+            let changes: Vec<serde_json::Value> = analysis
+                .resource_changes
+                .iter()
+                .filter(|change| change.action != "no-op")
+                .map(|change| serde_json::json!({"address": change.address, "action": change.action}))
+                .collect();
+            let json = to_json(&serde_json::json!({
+                "summary": analysis.summary,
+                "risk_assessment": analysis.risk_assessment,
+                "resource_changes": changes,
+                "messages_streamed": streamed
+            }))?;
+            Ok(CallToolResult::success(vec![Content::text(json)]))
+        }
+        Err(e) => tool_error("Failed to get plan", e),
+    }
+}
+
 /// RMCP-based MCP server for Terraform operations.
 #[derive(Clone)]
 pub struct TfMcpServer {
@@ -150,6 +478,8 @@ pub struct TfMcpServer {
     registry_client: Arc<RegistryClientWithFallback>,
     provider_resolver: Arc<ProviderResolver>,
     policy_client: Arc<PolicyClient>,
+    project_watcher: Arc<Mutex<Option<ProjectFileWatcher>>>,
+    processes: ProcessTracker,
     tool_filter: ToolFilter,
     tool_router: ToolRouter<Self>,
 }
@@ -158,28 +488,193 @@ pub struct TfMcpServer {
 impl TfMcpServer {
     /// Create a new TfMcpServer instance.
     pub fn new(tfmcp: TfMcp, tool_filter: ToolFilter) -> Self {
+        // All registry clients share one rate limiter
+        let registry_client = RegistryClientWithFallback::new();
+        let provider_resolver = ProviderResolver::with_client(registry_client.primary.clone());
+        let policy_client =
+            PolicyClient::new().with_rate_limiter(registry_client.rate_limiter.clone());
+        let tool_filter = tool_filter.with_policy(tfmcp.mcp_config());
         Self {
+            processes: tfmcp.process_tracker(),
             tfmcp: Arc::new(RwLock::new(tfmcp)),
-            registry_client: Arc::new(RegistryClientWithFallback::new()),
-            provider_resolver: Arc::new(ProviderResolver::new()),
-            policy_client: Arc::new(PolicyClient::new()),
+            registry_client: Arc::new(registry_client),
+            provider_resolver: Arc::new(provider_resolver),
+            policy_client: Arc::new(policy_client),
+            project_watcher: Arc::new(Mutex::new(None)),
             tool_filter,
             tool_router: Self::tool_router(),
         }
     }
 
+    /// Copy of the current TfMcp state taken under a short-held read lock.
+    ///
+    /// Tools run against the snapshot so long terraform commands never hold
+    /// the lock; a concurrent `set_terraform_directory` applies to later calls.
+    async fn snapshot(&self) -> TfMcp {
+        self.tfmcp.read().await.clone()
+    }
+
+    /// Fill in the latest registry version and a `~>` pin for each loosely
+    /// constrained provider; providers the registry can't resolve keep no suggestion
+    async fn suggest_provider_pins(&self, loose: &mut [LooseProviderConstraint]) {
+        for constraint in loose {
+            match self
+                .registry_client
+                .get_provider_version(&constraint.provider, None)
+                .await
+            {
+                Ok((version, _)) => {
+                    constraint.suggested_constraint = Some(suggest_provider_pin(&version));
+                    constraint.latest_version = Some(version);
+                }
+                Err(e) => logging::debug(&format!(
+                    "No latest version for provider {}: {}",
+                    constraint.provider, e
+                )),
+            }
+        }
+    }
+
+    /// Look up whether each public registry module's namespace is a verified
+    /// publisher; modules the registry can't resolve stay unconfirmed
+    async fn verify_publishers(&self, checks: &mut [ModuleSourceCheck]) {
+        let mut verified: HashMap<String, Option<bool>> = HashMap::new();
+        for check in checks {
+            let Some((namespace, name, provider)) = check.public_registry_module() else {
+                continue;
+            };
+            let key = format!("{}/{}/{}", namespace, name, provider);
+            let result = match verified.get(&key) {
+                Some(result) => *result,
+                None => {
+                    let result = match self
+                        .registry_client
+                        .primary
+                        .get_module_details(namespace, name, provider, None)
+                        .await
+                    {
+                        Ok(details) => Some(details.verified),
+                        Err(e) => {
+                            logging::debug(&format!("No registry details for {}: {}", key, e));
+                            None
+                        }
+                    };
+                    verified.insert(key, result);
+                    result
+                }
+            };
+            check.set_verified_publisher(result);
+        }
+    }
+
+    /// Scaffold `resource_type` from its registry docs, for projects without
+    /// a local provider schema
+    async fn scaffold_from_docs(
+        &self,
+        resource_type: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<Option<ResourceScaffold>, RegistryError> {
+        let Some((provider, slug)) = resource_type.split_once('_') else {
+            return Ok(None);
+        };
+        let docs = self
+            .provider_resolver
+            .resolve_provider_doc_id(provider, namespace, slug, Some("resources"))
+            .await?;
+        let Some(doc) = docs
+            .iter()
+            .find(|doc| {
+                doc.title == slug || doc.title == resource_type || doc.slug.as_deref() == Some(slug)
+            })
+            .or_else(|| docs.first())
+        else {
+            return Ok(None);
+        };
+        let content = self.provider_resolver.get_provider_docs(&doc.id).await?;
+        Ok(scaffold_from_docs(resource_type, name, &content))
+    }
+
     /// Serve the MCP server over stdio with optional tool filtering.
     pub async fn serve_stdio(tfmcp: TfMcp, tool_filter: ToolFilter) -> anyhow::Result<()> {
         use tokio::io::{stdin, stdout};
 
         let server = Self::new(tfmcp, tool_filter);
-        let transport = (stdin(), stdout());
-
         logging::info("Starting tfmcp MCP server via stdio...");
+        Self::serve_until_closed(server, (stdin(), stdout())).await
+    }
+
+    /// Serve on `transport` until the client disconnects or SIGTERM/SIGINT
+    /// arrives, then cancel running terraform commands before returning.
+    pub async fn serve_until_closed<T, E, A>(server: Self, transport: T) -> anyhow::Result<()>
+    where
+        T: rmcp::transport::IntoTransport<RoleServer, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let processes = server.processes.clone();
         let service = server.serve(transport).await?;
 
+        let cancel = service.cancellation_token();
+        let signal_task = tokio::spawn(async move {
+            shutdown_signal().await;
+            logging::info("Shutdown signal received, closing MCP transport");
+            cancel.cancel();
+        });
+
         // Wait for the server to finish (keep it alive)
-        service.waiting().await?;
+        let result = service.waiting().await;
+        signal_task.abort();
+        shutdown_operations(&processes).await;
+
+        result?;
+        Ok(())
+    }
+
+    /// Serve the MCP server over streamable HTTP (with SSE) on `addr`.
+    pub async fn serve_http(
+        tfmcp: TfMcp,
+        tool_filter: ToolFilter,
+        addr: SocketAddr,
+    ) -> anyhow::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        Self::new(tfmcp, tool_filter).serve_http_on(listener).await
+    }
+
+    /// Serve over streamable HTTP on an already bound listener.
+    ///
+    /// Every HTTP session gets a clone of this server, so all sessions share
+    /// the same Terraform project state.
+    pub async fn serve_http_on(self, listener: tokio::net::TcpListener) -> anyhow::Result<()> {
+        use rmcp::transport::streamable_http_server::{
+            StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+        };
+
+        let processes = self.processes.clone();
+        let config = StreamableHttpServerConfig::default();
+        // Cancelling this ends open SSE sessions so graceful shutdown can complete
+        let sessions = config.cancellation_token.clone();
+        let processes_on_signal = processes.clone();
+        let service = StreamableHttpService::new(
+            move || Ok(self.clone()),
+            Arc::new(LocalSessionManager::default()),
+            config,
+        );
+        let router = axum::Router::new().nest_service(HTTP_ENDPOINT_PATH, service);
+
+        logging::info(&format!(
+            "Starting tfmcp MCP server via HTTP on http://{}{}",
+            listener.local_addr()?,
+            HTTP_ENDPOINT_PATH
+        ));
+        axum::serve(listener, router)
+            .with_graceful_shutdown(async move {
+                shutdown_signal().await;
+                logging::info("Shutdown signal received, closing HTTP sessions");
+                processes_on_signal.cancel();
+                sessions.cancel();
+            })
+            .await?;
+        shutdown_operations(&processes).await;
 
         Ok(())
     }
@@ -187,66 +682,193 @@ impl TfMcpServer {
     // ============ Core Terraform Operations ============
 
     #[tool(
-        description = "List all resources defined in the Terraform project",
-        annotations(title = "List Terraform Resources", read_only_hint = true)
+        description = "List all resources in the Terraform state, with instance counts by resource type and by module path",
+        annotations(title = "List Terraform Resources", read_only_hint = true),
+        output_schema = output_schema::<ResourceListing>()
     )]
     async fn list_terraform_resources(&self) -> Result<CallToolResult, McpError> {
         logging::info("Executing list_terraform_resources tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp.list_resources().await {
-            Ok(resources) => {
-                let json = to_json(&serde_json::json!({ "resources": resources }))?;
-                Ok(CallToolResult::success(vec![Content::text(json)]))
-            }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to list resources: {}",
-                e
-            ))])),
+            Ok(resources) => structured_success(&ResourceListing::new(resources)),
+            Err(e) => tool_error("Failed to list resources", e),
         }
     }
 
     #[tool(
-        description = "Execute 'terraform plan' and return the output",
+        description = "Execute 'terraform plan' and return the output. Set stream to receive each plan message as a logging notification (and progress notification when a progress token is given) while the plan runs; the result is then the plan summary",
         annotations(title = "Get Terraform Plan", read_only_hint = true)
     )]
-    async fn get_terraform_plan(&self) -> Result<CallToolResult, McpError> {
+    async fn get_terraform_plan(
+        &self,
+        params: Parameters<PlanInput>,
+        peer: Peer<RoleServer>,
+        meta: Meta,
+    ) -> Result<CallToolResult, McpError> {
         logging::info("Executing get_terraform_plan tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.get_terraform_plan().await {
+        let tfmcp = self.snapshot().await;
+        if params.0.stream {
+            return stream_plan(&tfmcp, params.0.auto_init, &params.0.extra_args, peer, meta).await;
+        }
+        match tfmcp
+            .get_terraform_plan(params.0.auto_init, &params.0.extra_args)
+            .await
+        {
             Ok(output) => {
                 let json = to_json(&serde_json::json!({
                     "plan": output
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get plan: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to get plan", e),
+        }
+    }
+
+    #[tool(
+        description = "Preview what 'terraform destroy' would remove by running 'terraform plan -destroy' (PREVIEW ONLY: nothing is destroyed)",
+        annotations(title = "Plan Destroy (Preview)", read_only_hint = true),
+        output_schema = output_schema::<DestroyPreview>()
+    )]
+    async fn plan_destroy(
+        &self,
+        params: Parameters<InitRetryInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing plan_destroy tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.plan_destroy(params.0.auto_init).await {
+            Ok(preview) => structured_success(&preview),
+            Err(e) => tool_error("Destroy preview failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Validate the configuration and, only if it is valid, run a plan. Returns validation diagnostics plus the plan summary in one call",
+        annotations(title = "Preflight (Validate + Plan)", read_only_hint = true),
+        output_schema = output_schema::<PreflightResult>()
+    )]
+    async fn preflight(
+        &self,
+        params: Parameters<InitRetryInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing preflight tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.preflight(params.0.auto_init).await {
+            Ok(result) => structured_success(&result),
+            Err(e) => tool_error("Preflight failed", e),
         }
     }
 
     #[tool(
-        description = "Apply Terraform configuration (WARNING: Makes actual infrastructure changes)",
+        description = "Apply Terraform configuration (WARNING: Makes actual infrastructure changes). When TFMCP_MAX_APPLY_DESTROYS is set, a plan destroying or replacing more resources than that is refused, listing their addresses, unless confirm_destructive is true",
         annotations(title = "Apply Terraform", destructive_hint = true)
     )]
     async fn apply_terraform(
         &self,
-        params: Parameters<AutoApproveInput>,
+        params: Parameters<ApplyInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing apply_terraform tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.apply_terraform(params.0.auto_approve).await {
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .apply_terraform(
+                params.0.auto_approve,
+                params.0.auto_init,
+                &params.0.extra_args,
+                params.0.confirm_destructive,
+            )
+            .await
+        {
             Ok(output) => {
                 let json = to_json(&serde_json::json!({
                     "output": output
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to apply: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to apply", e),
+        }
+    }
+
+    #[tool(
+        description = "Run a plan and write it to a plan file in the project, recording the file's SHA-256 so apply_saved_plan can verify it was not changed",
+        annotations(title = "Save Plan")
+    )]
+    async fn save_plan(
+        &self,
+        params: Parameters<SavePlanInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing save_plan tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .save_plan(params.0.plan_file.as_deref(), params.0.auto_init)
+            .await
+        {
+            Ok(saved) => {
+                let json = to_json(&saved)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to save plan", e),
+        }
+    }
+
+    #[tool(
+        description = "Apply a plan file written by save_plan, refusing if the file's hash changed since it was saved, or if it destroys or replaces more resources than TFMCP_MAX_APPLY_DESTROYS unless confirm_destructive is true (WARNING: Makes actual infrastructure changes; requires TFMCP_ALLOW_DANGEROUS_OPS=true)",
+        annotations(title = "Apply Saved Plan", destructive_hint = true)
+    )]
+    async fn apply_saved_plan(
+        &self,
+        params: Parameters<ApplySavedPlanInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing apply_saved_plan tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .apply_saved_plan(params.0.plan_file.as_deref(), params.0.confirm_destructive)
+            .await
+        {
+            Ok(result) => {
+                let json = to_json(&result)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to apply saved plan", e),
+        }
+    }
+
+    #[tool(
+        description = "Save a plan for review and return its summary with a single-use approval token; pass the token to confirm_apply before it expires (default: 5 minutes) to apply exactly that plan. Requires TFMCP_ALLOW_DANGEROUS_OPS=true",
+        annotations(title = "Propose Apply")
+    )]
+    async fn propose_apply(
+        &self,
+        params: Parameters<InitRetryInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing propose_apply tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.propose_apply(params.0.auto_init).await {
+            Ok(proposal) => {
+                let json = to_json(&proposal)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to propose apply", e),
+        }
+    }
+
+    #[tool(
+        description = "Apply the plan from propose_apply. The token is single-use and rejected once expired, already used, or if the plan file changed. A plan destroying or replacing more resources than TFMCP_MAX_APPLY_DESTROYS is refused unless confirm_destructive is true (WARNING: Makes actual infrastructure changes; requires TFMCP_ALLOW_DANGEROUS_OPS=true)",
+        annotations(title = "Confirm Apply", destructive_hint = true)
+    )]
+    async fn confirm_apply(
+        &self,
+        params: Parameters<ConfirmApplyInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing confirm_apply tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .confirm_apply(&params.0.token, params.0.confirm_destructive)
+            .await
+        {
+            Ok(result) => {
+                let json = to_json(&result)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to confirm apply", e),
         }
     }
 
@@ -259,7 +881,7 @@ impl TfMcpServer {
         params: Parameters<AutoApproveInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing destroy_terraform tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp.destroy_terraform(params.0.auto_approve).await {
             Ok(output) => {
                 let json = to_json(&serde_json::json!({
@@ -267,10 +889,7 @@ impl TfMcpServer {
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to destroy: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to destroy", e),
         }
     }
 
@@ -282,126 +901,213 @@ impl TfMcpServer {
             idempotent_hint = true
         )
     )]
-    async fn init_terraform(&self) -> Result<CallToolResult, McpError> {
+    async fn init_terraform(
+        &self,
+        params: Parameters<InitInput>,
+    ) -> Result<CallToolResult, McpError> {
         logging::info("Executing init_terraform tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.init_terraform().await {
+        let tfmcp = self.snapshot().await;
+        match tfmcp.init_terraform(&params.0.extra_args).await {
             Ok(output) => {
                 let json = to_json(&serde_json::json!({
                     "output": output
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to init: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to init", e),
+        }
+    }
+
+    #[tool(
+        description = "Get the installed Terraform/OpenTofu version and check it against the configuration's required_version constraint",
+        annotations(title = "Get Terraform Version", read_only_hint = true)
+    )]
+    async fn get_terraform_version(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_terraform_version tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.get_version_info().await {
+            Ok(info) => {
+                let json = to_json(&info)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to get version", e),
+        }
+    }
+
+    #[tool(
+        description = "Report the Terraform CLI config file (TF_CLI_CONFIG_FILE / .terraformrc) tfmcp runs terraform with, and whether it installs providers from a network or filesystem mirror",
+        annotations(title = "Get CLI Config", read_only_hint = true)
+    )]
+    async fn get_cli_config(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_cli_config tool");
+        let tfmcp = self.snapshot().await;
+        let json = to_json(&tfmcp.get_cli_config())?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(
+        description = "Report where state is stored: the backend type (or cloud block) and its settings such as bucket, key, region and workspace, with credential-like values redacted; local when no backend is configured",
+        annotations(title = "Get Backend Config", read_only_hint = true)
+    )]
+    async fn get_backend_config(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_backend_config tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.get_backend_config() {
+            Ok(config) => {
+                let json = to_json(&config)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to read backend configuration", e),
         }
     }
 
     #[tool(
-        description = "Validate Terraform configuration files",
+        description = "Validate Terraform configuration files in the project or a subdirectory",
         annotations(title = "Validate Terraform", read_only_hint = true)
     )]
-    async fn validate_terraform(&self) -> Result<CallToolResult, McpError> {
+    async fn validate_terraform(
+        &self,
+        params: Parameters<ValidateInput>,
+    ) -> Result<CallToolResult, McpError> {
         logging::info("Executing validate_terraform tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.validate_configuration().await {
-            Ok(result) => {
-                let valid = !result.contains("Error:");
+        let input = params.0;
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .validate_configuration(input.path.as_deref(), input.auto_init)
+            .await
+        {
+            Ok((validated_path, validation)) => {
                 let json = to_json(&serde_json::json!({
-                    "valid": valid,
-                    "message": result
+                    "valid": validation.valid,
+                    "validated_path": validated_path,
+                    "error_count": validation.error_count,
+                    "warning_count": validation.warning_count,
+                    "diagnostics": validation.diagnostics,
+                    "message": validation.message()
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Validation failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Validation failed", e),
         }
     }
 
     #[tool(
-        description = "Perform detailed validation with diagnostics and best practice checks",
-        annotations(title = "Validate Terraform (Detailed)", read_only_hint = true)
+        description = "Perform detailed validation with diagnostics and best practice checks; diagnostics_by_file groups the diagnostics with per-file error and warning counts",
+        annotations(title = "Validate Terraform (Detailed)", read_only_hint = true),
+        output_schema = output_schema::<DetailedValidationResult>()
     )]
-    async fn validate_terraform_detailed(&self) -> Result<CallToolResult, McpError> {
+    async fn validate_terraform_detailed(
+        &self,
+        params: Parameters<ValidateDetailedInput>,
+    ) -> Result<CallToolResult, McpError> {
         logging::info("Executing validate_terraform_detailed tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.validate_configuration_detailed().await {
-            Ok(result) => {
-                let json = to_json(&result)?;
-                Ok(CallToolResult::success(vec![Content::text(json)]))
-            }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Detailed validation failed: {}",
-                e
-            ))])),
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .validate_configuration_detailed(params.0.path.as_deref())
+            .await
+        {
+            Ok(result) => structured_success(&result),
+            Err(e) => tool_error("Detailed validation failed", e),
         }
     }
 
     #[tool(
-        description = "Get the current Terraform state",
+        description = "Get the Terraform state of the current workspace, or of the given workspace",
         annotations(title = "Get Terraform State", read_only_hint = true)
     )]
-    async fn get_terraform_state(&self) -> Result<CallToolResult, McpError> {
+    async fn get_terraform_state(
+        &self,
+        params: Parameters<StateInput>,
+    ) -> Result<CallToolResult, McpError> {
         logging::info("Executing get_terraform_state tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.get_state().await {
-            Ok(state) => {
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .get_state(params.0.auto_init, params.0.workspace.as_deref())
+            .await
+        {
+            Ok((workspace, state)) => {
                 let json = to_json(&serde_json::json!({
+                    "workspace": workspace,
                     "state": state
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get state: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to get state", e),
         }
     }
 
     // ============ Configuration & Analysis ============
 
     #[tool(
-        description = "Analyze Terraform configuration and return detailed analysis including provider version checks",
-        annotations(title = "Analyze Terraform", read_only_hint = true)
+        description = "Analyze Terraform configuration and return detailed analysis including provider version checks and the effective value of each variable (auto-loaded tfvars, TF_VAR_ environment as source \"env\" or default; sensitive and environment values masked)",
+        annotations(title = "Analyze Terraform", read_only_hint = true),
+        output_schema = output_schema::<AnalysisReport>()
     )]
     async fn analyze_terraform(&self) -> Result<CallToolResult, McpError> {
         logging::info("Executing analyze_terraform tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp.get_terraform_analysis().await {
             Ok(analysis) => {
                 // Run guideline checks for additional provider version info
                 let guideline_summary = match tfmcp.run_security_scan().await {
-                    Ok(checks) => {
-                        serde_json::json!({
-                            "compliance_score": checks.compliance_score,
-                            "providers_missing_version": checks.providers_missing_version,
-                            "variables_missing_type": checks.variables_missing_type.len(),
-                            "variables_missing_description": checks.variables_missing_description.len(),
-                            "outputs_missing_description": checks.outputs_missing_description.len()
+                    Ok(mut checks) => {
+                        self.suggest_provider_pins(&mut checks.loose_provider_constraints)
+                            .await;
+                        for loose in &checks.loose_provider_constraints {
+                            logging::warn(&format!(
+                                "Provider '{}' has a loose version constraint: {}{}",
+                                loose.provider,
+                                loose.reason,
+                                loose
+                                    .suggested_constraint
+                                    .as_ref()
+                                    .map(|pin| format!(" (suggested: {})", pin))
+                                    .unwrap_or_default()
+                            ));
+                        }
+                        Some(GuidelineSummary {
+                            compliance_score: checks.compliance_score,
+                            providers_missing_version: checks.providers_missing_version,
+                            loose_provider_constraints: checks.loose_provider_constraints,
+                            variables_missing_type: checks.variables_missing_type.len(),
+                            variables_missing_description: checks
+                                .variables_missing_description
+                                .len(),
+                            outputs_missing_description: checks.outputs_missing_description.len(),
                         })
                     }
-                    Err(_) => serde_json::json!(null),
+                    Err(_) => None,
                 };
 
-                let json = to_json(&serde_json::json!({
-                    "project_directory": analysis.project_directory,
-                    "file_count": analysis.file_count,
-                    "resources": analysis.resources,
-                    "variables": analysis.variables,
-                    "outputs": analysis.outputs,
-                    "providers": analysis.providers,
-                    "guideline_summary": guideline_summary
-                }))?;
-                Ok(CallToolResult::success(vec![Content::text(json)]))
+                structured_success(&AnalysisReport {
+                    analysis,
+                    guideline_summary,
+                })
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Analysis failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Analysis failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Analyze every directory under the project root that contains .tf files and matches a glob (e.g. 'environments/*/'), returning a summary per directory and an aggregate. Blocked directories are skipped and at most 50 directories are analyzed.",
+        annotations(title = "Analyze All Roots", read_only_hint = true),
+        output_schema = output_schema::<MultiDirectoryAnalysis>()
+    )]
+    async fn analyze_all(
+        &self,
+        params: Parameters<AnalyzeAllInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info(&format!(
+            "Executing analyze_all tool with pattern '{}'",
+            params.0.pattern
+        ));
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .analyze_all(&params.0.pattern, params.0.max_directories)
+            .await
+        {
+            Ok(analysis) => structured_success(&analysis),
+            Err(e) => tool_error("Multi-directory analysis failed", e),
         }
     }
 
@@ -414,7 +1120,7 @@ impl TfMcpServer {
         params: Parameters<DirectoryInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing set_terraform_directory tool");
-        // This is the only tool that needs a write lock
+        // The only write lock; held just for the directory switch itself
         let mut tfmcp = self.tfmcp.write().await;
         match tfmcp.change_project_directory(params.0.directory.clone()) {
             Ok(()) => {
@@ -426,16 +1132,14 @@ impl TfMcpServer {
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to change directory: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to change directory", e),
         }
     }
 
     #[tool(
         description = "Get the current security status, policy information, and secret detection scan results",
-        annotations(title = "Get Security Status", read_only_hint = true)
+        annotations(title = "Get Security Status", read_only_hint = true),
+        output_schema = output_schema::<SecurityStatus>()
     )]
     async fn get_security_status(&self) -> Result<CallToolResult, McpError> {
         logging::info("Executing get_security_status tool");
@@ -449,132 +1153,216 @@ impl TfMcpServer {
             .unwrap_or(false);
 
         // Run security scan for secret detection and compliance
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         let scan_result = tfmcp.run_security_scan().await;
 
         let (secrets_detected, compliance_score, scan_status) = match scan_result {
-            Ok(checks) => {
-                let secrets: Vec<_> = checks
-                    .hardcoded_secrets
-                    .iter()
-                    .map(|s| {
-                        serde_json::json!({
-                            "file": s.file,
-                            "line": s.line,
-                            "pattern": s.pattern,
-                            "severity": s.severity
-                        })
-                    })
-                    .collect();
-                (secrets, checks.compliance_score, "completed")
-            }
+            Ok(checks) => (
+                checks.hardcoded_secrets,
+                checks.compliance_score,
+                "completed",
+            ),
             Err(e) => {
                 logging::error(&format!("Security scan failed: {}", e));
                 (vec![], 0, "failed")
             }
         };
 
-        let json = to_json(&serde_json::json!({
-            "policy": {
-                "allow_dangerous_operations": allow_dangerous,
-                "allow_auto_approve": allow_auto_approve
+        structured_success(&SecurityStatus {
+            policy: SecurityPolicySummary {
+                allow_dangerous_operations: allow_dangerous,
+                allow_auto_approve,
             },
-            "permissions": {
-                "apply": allow_dangerous,
-                "destroy": allow_dangerous,
-                "init": true,
-                "plan": true,
-                "validate": true
+            permissions: CommandPermissions {
+                apply: allow_dangerous,
+                destroy: allow_dangerous,
+                init: true,
+                plan: true,
+                validate: true,
             },
-            "audit_enabled": true,
-            "security_scan": {
-                "status": scan_status,
-                "secrets_detected": secrets_detected,
-                "secrets_count": secrets_detected.len(),
-                "compliance_score": compliance_score
-            }
-        }))?;
-        Ok(CallToolResult::success(vec![Content::text(json)]))
+            audit_enabled: true,
+            security_scan: SecurityScanSummary {
+                status: scan_status.to_string(),
+                secrets_count: secrets_detected.len(),
+                secrets_detected,
+                compliance_score,
+            },
+        })
     }
 
     #[tool(
         description = "Analyze module health with cohesion, coupling metrics, and variable quality checks",
-        annotations(title = "Analyze Module Health", read_only_hint = true)
+        annotations(title = "Analyze Module Health", read_only_hint = true),
+        output_schema = output_schema::<ModuleHealthReport>()
     )]
     async fn analyze_module_health(&self) -> Result<CallToolResult, McpError> {
         logging::info("Executing analyze_module_health tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp.analyze_module_health().await {
             Ok(health) => {
+                if let Err(e) = tfmcp.record_module_health(&health) {
+                    logging::warn(&format!("Failed to record module health history: {}", e));
+                }
+
                 // Run guideline checks for variable quality info
                 let variable_quality = match tfmcp.run_security_scan().await {
-                    Ok(checks) => {
-                        serde_json::json!({
-                            "variables_missing_type": checks.variables_missing_type,
-                            "variables_missing_description": checks.variables_missing_description,
-                            "any_type_usage": checks.any_type_usage,
-                            "outputs_missing_description": checks.outputs_missing_description
-                        })
-                    }
-                    Err(_) => serde_json::json!(null),
+                    Ok(checks) => Some(VariableQuality {
+                        variables_missing_type: checks.variables_missing_type,
+                        variables_missing_description: checks.variables_missing_description,
+                        any_type_usage: checks.any_type_usage,
+                        outputs_missing_description: checks.outputs_missing_description,
+                    }),
+                    Err(_) => None,
                 };
 
-                let json = to_json(&serde_json::json!({
-                    "module_path": health.module_path,
-                    "health_score": health.health_score,
-                    "metrics": health.metrics,
-                    "issues": health.issues,
-                    "recommendations": health.recommendations,
-                    "cohesion_analysis": health.cohesion_analysis,
-                    "coupling_analysis": health.coupling_analysis,
-                    "variable_quality": variable_quality
-                }))?;
+                structured_success(&ModuleHealthReport {
+                    health,
+                    variable_quality,
+                })
+            }
+            Err(e) => tool_error("Module health analysis failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Module health scores recorded by previous analyze_module_health runs for this project, with the change since the last run",
+        annotations(title = "Get Health Trend", read_only_hint = true)
+    )]
+    async fn get_health_trend(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_health_trend tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.get_health_trend() {
+            Ok(trend) => {
+                let json = to_json(&trend)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Module health analysis failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to read health history", e),
         }
     }
 
     #[tool(
         description = "Get the resource dependency graph",
-        annotations(title = "Get Resource Dependency Graph", read_only_hint = true)
+        annotations(title = "Get Resource Dependency Graph", read_only_hint = true),
+        output_schema = output_schema::<ResourceDependencyGraph>()
     )]
     async fn get_resource_dependency_graph(&self) -> Result<CallToolResult, McpError> {
         logging::info("Executing get_resource_dependency_graph tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp.get_dependency_graph().await {
-            Ok(graph) => {
-                let json = to_json(&graph)?;
-                Ok(CallToolResult::success(vec![Content::text(json)]))
-            }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get dependency graph: {}",
-                e
-            ))])),
+            Ok(graph) => structured_success(&graph),
+            Err(e) => tool_error("Failed to get dependency graph", e),
         }
     }
 
     #[tool(
-        description = "Get module refactoring suggestions",
-        annotations(title = "Suggest Module Refactoring", read_only_hint = true)
+        description = "List the resources affected by changing a resource: its direct dependents and every resource depending on it transitively through the dependency graph, nearest first",
+        annotations(title = "Impact Analysis", read_only_hint = true),
+        output_schema = output_schema::<ImpactAnalysis>()
     )]
-    async fn suggest_module_refactoring(&self) -> Result<CallToolResult, McpError> {
-        logging::info("Executing suggest_module_refactoring tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.suggest_refactoring().await {
+    async fn impact_analysis(
+        &self,
+        params: Parameters<ImpactAnalysisInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing impact_analysis tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.impact_analysis(&params.0.address).await {
+            Ok(impact) => structured_success(&impact),
+            Err(e) => tool_error("Impact analysis failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Show one resource's part of the dependency graph: the resource, everything it depends on and everything depending on it, transitively, as JSON or a Mermaid flowchart",
+        annotations(title = "Resource Subgraph", read_only_hint = true)
+    )]
+    async fn resource_subgraph(
+        &self,
+        params: Parameters<ResourceSubgraphInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing resource_subgraph tool");
+        let format = match params
+            .0
+            .format
+            .as_deref()
+            .unwrap_or("json")
+            .parse::<SubgraphFormat>()
+        {
+            Ok(format) => format,
+            Err(e) => return tool_error("Resource subgraph failed", e),
+        };
+        let tfmcp = self.snapshot().await;
+        match tfmcp.resource_subgraph(&params.0.address).await {
+            Ok(subgraph) => {
+                let text = match format {
+                    SubgraphFormat::Json => to_json(&subgraph)?,
+                    SubgraphFormat::Mermaid => subgraph_to_mermaid(&subgraph),
+                };
+                Ok(CallToolResult::success(vec![Content::text(text)]))
+            }
+            Err(e) => tool_error("Resource subgraph failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Find the file and line declaring a resource address (e.g. aws_instance.web; instance keys are ignored), a variable (var.name), an output (output.name) or a bare variable/output name, across the project and its nested modules. Every declaration is returned when one is duplicated",
+        annotations(title = "Locate Definition", read_only_hint = true),
+        output_schema = output_schema::<DefinitionLookup>()
+    )]
+    async fn locate_definition(
+        &self,
+        params: Parameters<LocateDefinitionInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing locate_definition tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.locate_definition(&params.0.name).await {
+            Ok(lookup) => structured_success(&lookup),
+            Err(e) => tool_error("Failed to locate definition", e),
+        }
+    }
+
+    #[tool(
+        description = "Verify module sources for supply-chain safety: git sources must pin ?ref= to a full commit SHA (branches, tags and short SHAs are flagged as mutable), registry modules are checked for an exact version and a verified publisher namespace, and archives for a ?checksum=. Returns a low/medium/high risk per module and overall. Checks one `source`, or every module call in the project when omitted",
+        annotations(
+            title = "Verify Module Source",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn verify_module_source(
+        &self,
+        params: Parameters<VerifyModuleSourceInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing verify_module_source tool");
+        let mut checks = match &params.0.source {
+            Some(source) => vec![ModuleSourceCheck::new(source, params.0.version.as_deref())],
+            None => {
+                let tfmcp = self.snapshot().await;
+                match tfmcp.check_module_sources().await {
+                    Ok(checks) => checks,
+                    Err(e) => return tool_error("Failed to read module sources", e),
+                }
+            }
+        };
+        self.verify_publishers(&mut checks).await;
+        let json = to_json(&ModuleSourceReport::new(checks))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(
+        description = "Get module refactoring suggestions",
+        annotations(title = "Suggest Module Refactoring", read_only_hint = true)
+    )]
+    async fn suggest_module_refactoring(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing suggest_module_refactoring tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.suggest_refactoring().await {
             Ok(suggestions) => {
                 let json = to_json(&serde_json::json!({
                     "suggestions": suggestions
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get refactoring suggestions: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to get refactoring suggestions", e),
         }
     }
 
@@ -604,10 +1392,7 @@ impl TfMcpServer {
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Provider search failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Provider search failed", e),
         }
     }
 
@@ -635,15 +1420,12 @@ impl TfMcpServer {
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get provider info: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to get provider info", e),
         }
     }
 
     #[tool(
-        description = "Get documentation for a specific provider resource or data source",
+        description = "Get documentation for a specific provider resource or data source. Each matching doc includes its content, or a content_error when that doc could not be fetched",
         annotations(
             title = "Get Provider Docs",
             read_only_hint = true,
@@ -669,18 +1451,139 @@ impl TfMcpServer {
             .await
         {
             Ok(docs) => {
+                let docs = self.registry_client.primary.fetch_doc_contents(docs).await;
                 let json = to_json(&serde_json::json!({
                     "documentation": docs
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get provider docs: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to get provider docs", e),
         }
     }
 
+    #[tool(
+        description = "Search a provider's resource and data source documentation by free text, returning ranked matches with doc ids to fetch with get_provider_docs",
+        annotations(
+            title = "Search Provider Docs",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn search_provider_docs(
+        &self,
+        params: Parameters<SearchProviderDocsInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing search_provider_docs tool");
+        let namespace = params.0.namespace.as_deref().unwrap_or("hashicorp");
+        match self
+            .provider_resolver
+            .search_provider_docs(
+                &params.0.provider_name,
+                namespace,
+                params.0.version.as_deref(),
+                &params.0.query,
+                params.0.limit.unwrap_or(DEFAULT_DOC_SEARCH_LIMIT),
+            )
+            .await
+        {
+            Ok(search) => Ok(CallToolResult::success(vec![Content::text(to_json(
+                &search,
+            )?)])),
+            Err(e) => tool_error("Provider docs search failed", e),
+        }
+    }
+
+    #[tool(
+        description = "List the resource and data source type names a provider offers, with a count of each, optionally filtered by a case-insensitive substring. Uses the installed provider schema when the project is initialized and no version is given, otherwise the registry docs",
+        annotations(
+            title = "List Provider Resources",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn list_provider_resources(
+        &self,
+        params: Parameters<ListProviderResourcesInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing list_provider_resources tool");
+        let name = params.0.provider_name.as_str();
+        let namespace = params.0.namespace.as_deref().unwrap_or("hashicorp");
+        let filter = params.0.filter.as_deref();
+
+        if params.0.version.is_none() {
+            let tfmcp = self.snapshot().await;
+            match tfmcp.list_provider_resources(namespace, name, filter).await {
+                Ok(Some(list)) => {
+                    return Ok(CallToolResult::success(vec![Content::text(to_json(
+                        &list,
+                    )?)]));
+                }
+                Ok(None) => {}
+                Err(e) => logging::warn(&format!("Local provider schema unavailable: {}", e)),
+            }
+        }
+
+        match self
+            .provider_resolver
+            .provider_doc_index(name, namespace, params.0.version.as_deref())
+            .await
+        {
+            Ok((version, docs)) => {
+                let list = list_from_docs(&docs, namespace, name, &version, filter);
+                Ok(CallToolResult::success(vec![Content::text(to_json(
+                    &list,
+                )?)]))
+            }
+            Err(e) => tool_error("Failed to list provider resources", e),
+        }
+    }
+
+    #[tool(
+        description = "Generate a starting HCL block for a resource type with required arguments as placeholders and optional ones listed in a comment. Uses the provider schema when the project is initialized, otherwise the registry docs",
+        annotations(
+            title = "Scaffold Resource",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn scaffold_resource(
+        &self,
+        params: Parameters<ScaffoldResourceInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing scaffold_resource tool");
+        let resource_type = params.0.resource_type.as_str();
+        let name = params.0.name.as_deref().unwrap_or("this");
+        let namespace = params.0.namespace.as_deref().unwrap_or("hashicorp");
+
+        let tfmcp = self.snapshot().await;
+        let local = match tfmcp.scaffold_resource(resource_type, name).await {
+            Ok(local) => local,
+            Err(e) => {
+                logging::warn(&format!("Local provider schema unavailable: {}", e));
+                None
+            }
+        };
+        let scaffold = match local {
+            Some(scaffold) => scaffold,
+            None => match self
+                .scaffold_from_docs(resource_type, name, namespace)
+                .await
+            {
+                Ok(Some(scaffold)) => scaffold,
+                Ok(None) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "No provider schema or documentation found for resource type '{}'",
+                        resource_type
+                    ))]));
+                }
+                Err(e) => return tool_error("Failed to scaffold resource", e),
+            },
+        };
+        Ok(CallToolResult::success(vec![Content::text(to_json(
+            &scaffold,
+        )?)]))
+    }
+
     #[tool(
         description = "Search for Terraform modules in the registry",
         annotations(
@@ -706,10 +1609,7 @@ impl TfMcpServer {
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Module search failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Module search failed", e),
         }
     }
 
@@ -721,127 +1621,384 @@ impl TfMcpServer {
             open_world_hint = true
         )
     )]
-    async fn get_module_details(
+    async fn get_module_details(
+        &self,
+        params: Parameters<ModuleInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_module_details tool");
+        match self
+            .registry_client
+            .primary
+            .get_module_details(
+                &params.0.namespace,
+                &params.0.name,
+                &params.0.provider,
+                params.0.version.as_deref(),
+            )
+            .await
+        {
+            Ok(details) => {
+                let json = to_json(&serde_json::json!({
+                    "module": details
+                }))?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to get module details", e),
+        }
+    }
+
+    #[tool(
+        description = "List a module's submodules with their path, input/output counts and resources, to decide whether to call a submodule directly",
+        annotations(
+            title = "List Module Submodules",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn list_module_submodules(
+        &self,
+        params: Parameters<ModuleInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing list_module_submodules tool");
+        match self
+            .registry_client
+            .primary
+            .list_module_submodules(
+                &params.0.namespace,
+                &params.0.name,
+                &params.0.provider,
+                params.0.version.as_deref(),
+            )
+            .await
+        {
+            Ok(submodules) => {
+                let json = to_json(&submodules)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to list module submodules", e),
+        }
+    }
+
+    #[tool(
+        description = "List a module's published examples with their README and main.tf, to copy a working configuration",
+        annotations(
+            title = "List Module Examples",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn list_module_examples(
+        &self,
+        params: Parameters<ModuleInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing list_module_examples tool");
+        match self
+            .registry_client
+            .primary
+            .list_module_examples(
+                &params.0.namespace,
+                &params.0.name,
+                &params.0.provider,
+                params.0.version.as_deref(),
+            )
+            .await
+        {
+            Ok(examples) => {
+                let json = to_json(&examples)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to list module examples", e),
+        }
+    }
+
+    #[tool(
+        description = "Get the README of a module or one of its submodules, falling back to the source repository when the registry copy is empty",
+        annotations(
+            title = "Get Module README",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn get_module_readme(
+        &self,
+        params: Parameters<ModuleReadmeInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_module_readme tool");
+        match self
+            .registry_client
+            .primary
+            .get_module_readme(
+                &params.0.namespace,
+                &params.0.name,
+                &params.0.provider,
+                params.0.version.as_deref(),
+                params.0.submodule.as_deref(),
+            )
+            .await
+        {
+            Ok(readme) => {
+                let json = to_json(&readme)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to get module README", e),
+        }
+    }
+
+    #[tool(
+        description = "Get the latest version of a module",
+        annotations(
+            title = "Get Latest Module Version",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn get_latest_module_version(
+        &self,
+        params: Parameters<ModuleVersionInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_latest_module_version tool");
+        match self
+            .registry_client
+            .primary
+            .get_latest_module_version(&params.0.namespace, &params.0.name, &params.0.provider)
+            .await
+        {
+            Ok(version) => {
+                let json = to_json(&serde_json::json!({
+                    "version": version,
+                    "module_id": format!("{}/{}/{}", params.0.namespace, params.0.name, params.0.provider)
+                }))?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to get latest module version", e),
+        }
+    }
+
+    #[tool(
+        description = "Get the latest version of a provider",
+        annotations(
+            title = "Get Latest Provider Version",
+            read_only_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn get_latest_provider_version(
+        &self,
+        params: Parameters<ProviderInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_latest_provider_version tool");
+        match self
+            .registry_client
+            .get_provider_version(&params.0.provider_name, params.0.namespace.as_deref())
+            .await
+        {
+            Ok((version, namespace)) => {
+                let json = to_json(&serde_json::json!({
+                    "version": version,
+                    "namespace": namespace,
+                    "provider_id": format!("{}/{}", namespace, params.0.provider_name)
+                }))?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to get latest provider version", e),
+        }
+    }
+
+    // ============ v0.1.9 New Tools ============
+
+    #[tool(
+        description = "Analyze terraform plan with risk scoring and recommendations",
+        annotations(title = "Analyze Plan", read_only_hint = true),
+        output_schema = output_schema::<PlanAnalysis>()
+    )]
+    async fn analyze_plan(
+        &self,
+        params: Parameters<AnalyzePlanInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing analyze_plan tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .analyze_plan(params.0.include_risk, params.0.auto_init)
+            .await
+        {
+            Ok(mut analysis) => {
+                analysis.filter_noise(NoiseFilter {
+                    hide_no_op: params.0.hide_no_op,
+                    collapse_tag_only: params.0.collapse_tag_only,
+                });
+                structured_success(&analysis)
+            }
+            Err(e) => tool_error("Plan analysis failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Analyze an existing plan instead of running a new one: a `terraform show -json` document or a binary plan file inside the project, with the same risk scoring and summary as analyze_plan",
+        annotations(title = "Analyze Plan File", read_only_hint = true),
+        output_schema = output_schema::<PlanAnalysis>()
+    )]
+    async fn analyze_plan_file(
+        &self,
+        params: Parameters<AnalyzePlanFileInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing analyze_plan_file tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .analyze_plan_file(&params.0.plan_file, params.0.include_risk)
+            .await
+        {
+            Ok(mut analysis) => {
+                analysis.filter_noise(NoiseFilter {
+                    hide_no_op: params.0.hide_no_op,
+                    collapse_tag_only: params.0.collapse_tag_only,
+                });
+                structured_success(&analysis)
+            }
+            Err(e) => tool_error("Plan file analysis failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Evaluate the current plan against Rego policies with conftest (policy directory from TFMCP_POLICY_DIR or terraform.policy_dir)",
+        annotations(title = "Evaluate Policies", read_only_hint = true)
+    )]
+    async fn evaluate_policies(
+        &self,
+        params: Parameters<InitRetryInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing evaluate_policies tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.evaluate_policies(params.0.auto_init).await {
+            Ok(evaluation) => {
+                let json = to_json(&evaluation)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Policy evaluation failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Run a plan and render it as a git-style diff grouped by action: changed attributes per resource (before → after), sensitive values shown as (sensitive) and long values truncated",
+        annotations(title = "Format Plan Diff", read_only_hint = true)
+    )]
+    async fn format_plan_diff(
+        &self,
+        params: Parameters<InitRetryInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing format_plan_diff tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.format_plan_diff(params.0.auto_init).await {
+            Ok(diff) => Ok(CallToolResult::success(vec![Content::text(diff)])),
+            Err(e) => tool_error("Failed to render plan diff", e),
+        }
+    }
+
+    #[tool(
+        description = "Build a pre-apply review report: plan summary, risk score and, when infracost is installed, the monthly cost delta, with an overall recommendation (proceed/caution/block) from the risk level and number of deletes/replacements",
+        annotations(title = "Pre-Apply Report", read_only_hint = true)
+    )]
+    async fn pre_apply_report(
+        &self,
+        params: Parameters<InitRetryInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing pre_apply_report tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.pre_apply_report(params.0.auto_init).await {
+            Ok(report) => {
+                let json = to_json(&report)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to build pre-apply report", e),
+        }
+    }
+
+    #[tool(
+        description = "Run a plan and store its resource actions as a snapshot (keyed by id) for later comparison with diff_plans",
+        annotations(title = "Save Plan Snapshot", read_only_hint = true)
+    )]
+    async fn save_plan_snapshot(
         &self,
-        params: Parameters<ModuleInput>,
+        params: Parameters<PlanSnapshotInput>,
     ) -> Result<CallToolResult, McpError> {
-        logging::info("Executing get_module_details tool");
-        match self
-            .registry_client
-            .primary
-            .get_module_details(
-                &params.0.namespace,
-                &params.0.name,
-                &params.0.provider,
-                params.0.version.as_deref(),
-            )
+        logging::info("Executing save_plan_snapshot tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .save_plan_snapshot(params.0.id.as_deref(), params.0.auto_init)
             .await
         {
-            Ok(details) => {
-                let json = to_json(&serde_json::json!({
-                    "module": details
-                }))?;
+            Ok(snapshot) => {
+                let json = to_json(&snapshot)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get module details: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Failed to save plan snapshot", e),
         }
     }
 
     #[tool(
-        description = "Get the latest version of a module",
-        annotations(
-            title = "Get Latest Module Version",
-            read_only_hint = true,
-            open_world_hint = true
-        )
+        description = "Compare a stored plan snapshot against a fresh plan, reporting which resource actions appeared or disappeared",
+        annotations(title = "Diff Plans", read_only_hint = true)
     )]
-    async fn get_latest_module_version(
+    async fn diff_plans(
         &self,
-        params: Parameters<ModuleVersionInput>,
+        params: Parameters<DiffPlansInput>,
     ) -> Result<CallToolResult, McpError> {
-        logging::info("Executing get_latest_module_version tool");
-        match self
-            .registry_client
-            .primary
-            .get_latest_module_version(&params.0.namespace, &params.0.name, &params.0.provider)
+        logging::info("Executing diff_plans tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .diff_plans(&params.0.snapshot_id, params.0.auto_init)
             .await
         {
-            Ok(version) => {
-                let json = to_json(&serde_json::json!({
-                    "version": version,
-                    "module_id": format!("{}/{}/{}", params.0.namespace, params.0.name, params.0.provider)
-                }))?;
+            Ok(diff) => {
+                let json = to_json(&diff)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get latest module version: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Plan diff failed", e),
         }
     }
 
     #[tool(
-        description = "Get the latest version of a provider",
-        annotations(
-            title = "Get Latest Provider Version",
-            read_only_hint = true,
-            open_world_hint = true
-        )
+        description = "Scan terraform state for secrets: reports resource addresses and attribute paths whose values match secret patterns or are marked sensitive, never the values themselves",
+        annotations(title = "Scan State For Secrets", read_only_hint = true)
     )]
-    async fn get_latest_provider_version(
+    async fn scan_state_for_secrets(
         &self,
-        params: Parameters<ProviderInput>,
+        params: Parameters<InitRetryInput>,
     ) -> Result<CallToolResult, McpError> {
-        logging::info("Executing get_latest_provider_version tool");
-        match self
-            .registry_client
-            .get_provider_version(&params.0.provider_name, params.0.namespace.as_deref())
-            .await
-        {
-            Ok((version, namespace)) => {
-                let json = to_json(&serde_json::json!({
-                    "version": version,
-                    "namespace": namespace,
-                    "provider_id": format!("{}/{}", namespace, params.0.provider_name)
-                }))?;
+        logging::info("Executing scan_state_for_secrets tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.scan_state_for_secrets(params.0.auto_init).await {
+            Ok(scan) => {
+                let json = to_json(&scan)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to get latest provider version: {}",
-                e
-            ))])),
+            Err(e) => tool_error("State secret scan failed", e),
         }
     }
 
-    // ============ v0.1.9 New Tools ============
-
     #[tool(
-        description = "Analyze terraform plan with risk scoring and recommendations",
-        annotations(title = "Analyze Plan", read_only_hint = true)
+        description = "Return the full state JSON from 'terraform state pull' (read-only). State holds potentially sensitive data: outputs and attributes marked sensitive or matching secret patterns are masked, and resources beyond max_bytes are omitted",
+        annotations(title = "Pull State", read_only_hint = true)
     )]
-    async fn analyze_plan(
+    async fn pull_state(
         &self,
-        params: Parameters<AnalyzePlanInput>,
+        params: Parameters<PullStateInput>,
     ) -> Result<CallToolResult, McpError> {
-        logging::info("Executing analyze_plan tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.analyze_plan(params.0.include_risk).await {
-            Ok(analysis) => {
-                let json = to_json(&analysis)?;
+        logging::info("Executing pull_state tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .pull_state(params.0.auto_init, params.0.max_bytes)
+            .await
+        {
+            Ok(state) => {
+                let json = to_json(&state)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Plan analysis failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("State pull failed", e),
         }
     }
 
     #[tool(
-        description = "Analyze terraform state with optional drift detection",
+        description = "Analyze terraform state of the current or given workspace with optional drift detection",
         annotations(title = "Analyze State", read_only_hint = true)
     )]
     async fn analyze_state(
@@ -849,24 +2006,26 @@ impl TfMcpServer {
         params: Parameters<AnalyzeStateInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing analyze_state tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp
-            .analyze_state(params.0.resource_type.as_deref(), params.0.detect_drift)
+            .analyze_state(
+                params.0.resource_type.as_deref(),
+                params.0.detect_drift,
+                params.0.auto_init,
+                params.0.workspace.as_deref(),
+            )
             .await
         {
             Ok(analysis) => {
                 let json = to_json(&analysis)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "State analysis failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("State analysis failed", e),
         }
     }
 
     #[tool(
-        description = "Manage terraform workspaces (list, show, new, select, delete)",
+        description = "Manage terraform workspaces (list, show, new, select, delete). list with with_counts also reports each workspace's state resource count",
         annotations(title = "Terraform Workspace", idempotent_hint = true)
     )]
     async fn terraform_workspace(
@@ -874,19 +2033,20 @@ impl TfMcpServer {
         params: Parameters<WorkspaceInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing terraform_workspace tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp
-            .workspace(&params.0.action, params.0.name.as_deref())
+            .workspace(
+                &params.0.action,
+                params.0.name.as_deref(),
+                params.0.with_counts,
+            )
             .await
         {
             Ok(result) => {
                 let json = to_json(&result)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Workspace operation failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Workspace operation failed", e),
         }
     }
 
@@ -899,7 +2059,7 @@ impl TfMcpServer {
         params: Parameters<ImportInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing terraform_import tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp
             .import_resource(
                 &params.0.resource_type,
@@ -913,10 +2073,33 @@ impl TfMcpServer {
                 let json = to_json(&result)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Import failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Import failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Generate a starter .tfvars file: every variable without a default set to a placeholder for its type, with its description as a comment, and optionally defaulted variables commented out. Returned as text; written to write_to only when given, and an existing file is only overwritten with force",
+        annotations(title = "Generate tfvars")
+    )]
+    async fn generate_tfvars(
+        &self,
+        params: Parameters<GenerateTfvarsInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing generate_tfvars tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .generate_tfvars(
+                params.0.include_defaults,
+                params.0.write_to.as_deref(),
+                params.0.force,
+            )
+            .await
+        {
+            Ok(skeleton) => {
+                let json = to_json(&skeleton)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to generate tfvars", e),
         }
     }
 
@@ -929,7 +2112,7 @@ impl TfMcpServer {
         params: Parameters<FmtInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing terraform_fmt tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp
             .fmt(params.0.check, params.0.diff, params.0.file.as_deref())
             .await
@@ -938,15 +2121,12 @@ impl TfMcpServer {
                 let json = to_json(&result)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Format failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Format failed", e),
         }
     }
 
     #[tool(
-        description = "Generate Terraform dependency graph in DOT format",
+        description = "Generate Terraform dependency graph in DOT format, or as structured nodes/edges",
         annotations(title = "Terraform Graph", read_only_hint = true)
     )]
     async fn terraform_graph(
@@ -954,21 +2134,29 @@ impl TfMcpServer {
         params: Parameters<GraphInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing terraform_graph tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.graph(params.0.graph_type.as_deref()).await {
+        let input = params.0;
+        let tfmcp = self.snapshot().await;
+        match tfmcp.graph(input.graph_type.as_deref()).await {
+            Ok(graph) if input.structured => {
+                let deps = dot_to_dependency_graph(&graph.dot_output);
+                let json = to_json(&serde_json::json!({
+                    "nodes": deps.nodes,
+                    "edges": deps.edges,
+                    "module_boundaries": deps.module_boundaries,
+                    "dot_output": input.include_dot.then_some(graph.dot_output),
+                }))?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
             Ok(graph) => {
                 let json = to_json(&graph)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Graph generation failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Graph generation failed", e),
         }
     }
 
     #[tool(
-        description = "Get Terraform output values",
+        description = "Get Terraform output values (sensitive values are masked unless reveal_sensitive is set)",
         annotations(title = "Terraform Output", read_only_hint = true)
     )]
     async fn terraform_output(
@@ -976,16 +2164,17 @@ impl TfMcpServer {
         params: Parameters<OutputInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing terraform_output tool");
-        let tfmcp = self.tfmcp.read().await;
-        match tfmcp.output(params.0.name.as_deref()).await {
+        let input = params.0;
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .output(input.name.as_deref(), input.reveal_sensitive)
+            .await
+        {
             Ok(result) => {
                 let json = to_json(&result)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Output retrieval failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Output retrieval failed", e),
         }
     }
 
@@ -998,16 +2187,13 @@ impl TfMcpServer {
         params: Parameters<TaintInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing terraform_taint tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp.taint(&params.0.action, &params.0.address).await {
             Ok(result) => {
                 let json = to_json(&result)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Taint operation failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Taint operation failed", e),
         }
     }
 
@@ -1020,16 +2206,13 @@ impl TfMcpServer {
         params: Parameters<RefreshInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing terraform_refresh tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp.refresh_state(params.0.target.as_deref()).await {
             Ok(result) => {
                 let json = to_json(&result)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Refresh failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Refresh failed", e),
         }
     }
 
@@ -1042,16 +2225,109 @@ impl TfMcpServer {
         params: Parameters<ProvidersInput>,
     ) -> Result<CallToolResult, McpError> {
         logging::info("Executing terraform_providers tool");
-        let tfmcp = self.tfmcp.read().await;
+        let tfmcp = self.snapshot().await;
         match tfmcp.get_providers(params.0.include_lock).await {
             Ok(result) => {
                 let json = to_json(&result)?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Provider info failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Provider info failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Find resources still in state whose resource blocks were removed from the root module configuration (candidates for a removed block, state rm or destroy). count/for_each instances are grouped under their block; data sources and module resources are not compared",
+        annotations(title = "Find Orphaned Resources", read_only_hint = true)
+    )]
+    async fn find_orphaned_resources(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing find_orphaned_resources tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.find_orphaned_resources().await {
+            Ok(report) => {
+                let json = to_json(&report)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to find orphaned resources", e),
+        }
+    }
+
+    #[tool(
+        description = "Flag resources commonly associated with high cost (NAT gateways, large or GPU instance types, provisioned IOPS volumes, load balancers, inter-region replication) from the configuration, with guidance for each. Heuristic and offline: nothing is priced; use pre_apply_report with Infracost for a monthly estimate",
+        annotations(title = "Identify Cost Drivers", read_only_hint = true)
+    )]
+    async fn identify_cost_drivers(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing identify_cost_drivers tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.identify_cost_drivers().await {
+            Ok(report) => {
+                let json = to_json(&report)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to identify cost drivers", e),
+        }
+    }
+
+    #[tool(
+        description = "Verify .terraform.lock.hcl: flag providers locked outside their configured constraints, missing from the lock, or missing hashes for the current platform",
+        annotations(title = "Verify Provider Lock", read_only_hint = true)
+    )]
+    async fn verify_provider_lock(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing verify_provider_lock tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.verify_provider_lock().await {
+            Ok(verification) => {
+                let json = to_json(&verification)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Lock file verification failed", e),
+        }
+    }
+
+    #[tool(
+        description = "Get the schema (attributes with type, required, optional, computed, sensitive, and nested blocks) of a resource or data source type from the providers installed in the project. Requires terraform init",
+        annotations(title = "Get Resource Schema", read_only_hint = true)
+    )]
+    async fn get_resource_schema(
+        &self,
+        params: Parameters<ResourceSchemaInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing get_resource_schema tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp
+            .get_resource_schema(&params.0.resource_type, params.0.data_source)
+            .await
+        {
+            Ok(schema) => {
+                let json = to_json(&schema)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to get resource schema", e),
+        }
+    }
+
+    #[tool(
+        description = "Fetch the provider schemas of the project (terraform providers schema -json) and cache them, so validate_terraform_detailed also flags resource and data source arguments the schema does not define. Set background to return before the fetch completes. Requires terraform init",
+        annotations(title = "Warm Provider Schemas", read_only_hint = true)
+    )]
+    async fn warm_provider_schemas(
+        &self,
+        params: Parameters<WarmProviderSchemasInput>,
+    ) -> Result<CallToolResult, McpError> {
+        logging::info("Executing warm_provider_schemas tool");
+        let tfmcp = self.snapshot().await;
+        match tfmcp.warm_provider_schemas(params.0.background).await {
+            Ok(Some(warmed)) => {
+                let json = to_json(&warmed)?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Ok(None) => {
+                let json = to_json(&serde_json::json!({
+                    "status": "warming",
+                    "message": "Provider schemas are being fetched in the background; validate_terraform_detailed uses them once they are cached"
+                }))?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => tool_error("Failed to warm provider schemas", e),
         }
     }
 
@@ -1079,10 +2355,7 @@ impl TfMcpServer {
                 let json = to_json(&serde_json::json!({ "policies": policies }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Policy search failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Policy search failed", e),
         }
     }
 
@@ -1108,10 +2381,7 @@ impl TfMcpServer {
                 let json = to_json(&serde_json::json!({ "policy": policy }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Policy details failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Policy details failed", e),
         }
     }
 
@@ -1187,12 +2457,29 @@ impl TfMcpServer {
                 }))?;
                 Ok(CallToolResult::success(vec![Content::text(json)]))
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Provider capabilities failed: {}",
-                e
-            ))])),
+            Err(e) => tool_error("Provider capabilities failed", e),
         }
     }
+
+    // ============ Server Introspection ============
+
+    #[tool(
+        description = "List the enabled tools with their descriptions, input schemas and annotation hints (read-only, destructive, idempotent, open-world), so clients can gate destructive tools behind confirmation",
+        annotations(title = "Describe Tools", read_only_hint = true)
+    )]
+    async fn describe_tools(&self) -> Result<CallToolResult, McpError> {
+        logging::info("Executing describe_tools tool");
+        let mut tools: Vec<ToolDescription> = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .filter(|t| self.tool_filter.is_enabled(t.name.as_ref()))
+            .map(ToolDescription::from)
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        let json = to_json(&serde_json::json!({ "tools": tools }))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
 }
 
 // The ServerHandler trait requires this specific impl Future pattern
@@ -1202,7 +2489,9 @@ impl ServerHandler for TfMcpServer {
         let capabilities = ServerCapabilities::builder()
             .enable_tools()
             .enable_resources()
+            .enable_resources_subscribe()
             .enable_prompts()
+            .enable_logging()
             .build();
         let server_info = Implementation::new("tfmcp", env!("CARGO_PKG_VERSION"));
         ServerInfo::new(capabilities)
@@ -1216,7 +2505,7 @@ impl ServerHandler for TfMcpServer {
         _context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<ListResourcesResult, McpError>> + Send + '_ {
         async move {
-            Ok(ListResourcesResult::with_all_items(vec![
+            let mut resources = vec![
                 Annotated::new(
                     RawResource::new("terraform://style-guide", "Terraform Style Guide")
                         .with_description("Best practices for HCL formatting and code style")
@@ -1235,7 +2524,42 @@ impl ServerHandler for TfMcpServer {
                         .with_mime_type("text/markdown"),
                     None,
                 ),
-            ]))
+                Annotated::new(
+                    RawResource::new("terraform://tfmcp-config", "tfmcp Configuration")
+                        .with_description(
+                            "Effective tfmcp configuration (config file + environment) with secrets redacted",
+                        )
+                        .with_mime_type("application/json"),
+                    None,
+                ),
+                Annotated::new(
+                    RawResource::new("terraform://files", "Project File Listing")
+                        .with_description(
+                            "Project .tf/.tfvars files with sizes and a preview of their declarations",
+                        )
+                        .with_mime_type("application/json"),
+                    None,
+                ),
+                Annotated::new(
+                    RawResource::new(PROJECT_FILES_URI, "Project Terraform Files")
+                        .with_description(
+                            "Terraform files in the project directory (subscribe for change notifications)",
+                        )
+                        .with_mime_type("application/json"),
+                    None,
+                ),
+            ];
+            if self.snapshot().await.audit_log_enabled() {
+                resources.push(Annotated::new(
+                    RawResource::new(AUDIT_LOG_URI, "Audit Log")
+                        .with_description(
+                            "Most recent audit log entries (?count=N, default 50) with secrets in command arguments redacted",
+                        )
+                        .with_mime_type("application/json"),
+                    None,
+                ));
+            }
+            Ok(ListResourcesResult::with_all_items(resources))
         }
     }
 
@@ -1260,6 +2584,51 @@ impl ServerHandler for TfMcpServer {
         }
     }
 
+    fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListPromptsResult, McpError>> + Send + '_ {
+        async move {
+            Ok(ListPromptsResult::with_all_items(vec![Prompt::new(
+                DOCUMENT_MODULE_PROMPT,
+                Some(
+                    "Write a README for the current module, with inputs/outputs tables built from its variables and outputs",
+                ),
+                None,
+            )]))
+        }
+    }
+
+    fn get_prompt(
+        &self,
+        request: GetPromptRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<GetPromptResult, McpError>> + Send + '_ {
+        async move {
+            if request.name != DOCUMENT_MODULE_PROMPT {
+                return Err(McpError::invalid_params(
+                    format!("Unknown prompt: {}", request.name),
+                    None,
+                ));
+            }
+
+            let analysis = self
+                .snapshot()
+                .await
+                .get_terraform_analysis()
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            Ok(GetPromptResult::new(vec![PromptMessage::new_text(
+                PromptMessageRole::User,
+                build_document_module_prompt(&analysis),
+            )])
+            .with_description(format!(
+                "Document the module in {}",
+                analysis.project_directory
+            )))
+        }
+    }
     fn read_resource(
         &self,
         request: ReadResourceRequestParams,
@@ -1324,6 +2693,70 @@ impl ServerHandler for TfMcpServer {
                 }
             }
 
+            if let Some(query) = request
+                .uri
+                .strip_prefix(AUDIT_LOG_URI)
+                .filter(|rest| rest.is_empty() || rest.starts_with('?'))
+            {
+                let count = audit_log_count(query)?;
+                let entries = self
+                    .snapshot()
+                    .await
+                    .recent_audit_entries(count)
+                    .map_err(|e| McpError::resource_not_found(e.to_string(), None))?;
+                let json = to_json(&serde_json::json!({
+                    "count": entries.len(),
+                    "entries": entries
+                }))?;
+                return Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    json,
+                    request.uri,
+                )]));
+            }
+
+            if request.uri == "terraform://tfmcp-config" {
+                let json = to_json(&self.snapshot().await.effective_config().await)?;
+                return Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    json,
+                    request.uri,
+                )]));
+            }
+
+            if request.uri == "terraform://files" {
+                let tfmcp = self.snapshot().await;
+                let files = tfmcp
+                    .list_project_files()
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                let json = to_json(&serde_json::json!({
+                    "project_directory": tfmcp.get_project_directory(),
+                    "files": files
+                }))?;
+                return Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    json,
+                    request.uri,
+                )]));
+            }
+
+            if request.uri == PROJECT_FILES_URI {
+                let project_dir = self.tfmcp.read().await.get_project_directory();
+                let mut files: Vec<String> = std::fs::read_dir(&project_dir)
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "tf"))
+                    .filter_map(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+                    .collect();
+                files.sort();
+                let json = to_json(&serde_json::json!({
+                    "project_directory": project_dir,
+                    "files": files
+                }))?;
+                return Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    json,
+                    request.uri,
+                )]));
+            }
+
             // Static resources with live fetch fallback
             let content = match request.uri.as_str() {
                 "terraform://style-guide" => get_style_guide_content().await,
@@ -1344,6 +2777,85 @@ impl ServerHandler for TfMcpServer {
         }
     }
 
+    fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        async move {
+            if request.uri != PROJECT_FILES_URI {
+                return Err(McpError::resource_not_found(
+                    format!("Resource does not support subscription: {}", request.uri),
+                    None,
+                ));
+            }
+
+            let project_dir = self.tfmcp.read().await.get_project_directory();
+            let (watcher, mut changes) = ProjectFileWatcher::start(&project_dir, DEFAULT_DEBOUNCE)
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to watch project: {}", e), None)
+                })?;
+
+            // Replacing an existing watcher drops it, which ends its forwarding task
+            *self.project_watcher.lock().await = Some(watcher);
+            logging::info(&format!(
+                "Watching {} for Terraform file changes",
+                project_dir.display()
+            ));
+
+            let peer = context.peer.clone();
+            tokio::spawn(async move {
+                while changes.recv().await.is_some() {
+                    let param = ResourceUpdatedNotificationParam {
+                        uri: PROJECT_FILES_URI.to_string(),
+                    };
+                    if let Err(e) = peer.notify_resource_updated(param).await {
+                        logging::error(&format!("Failed to send resource update: {}", e));
+                        break;
+                    }
+                }
+            });
+
+            Ok(())
+        }
+    }
+
+    fn initialize(
+        &self,
+        request: InitializeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<InitializeResult, McpError>> + Send + '_ {
+        set_mcp_client(&request.client_info.name, &request.client_info.version);
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        std::future::ready(Ok(self.get_info()))
+    }
+
+    fn set_level(
+        &self,
+        _request: SetLevelRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        // Log notifications are only sent for plans the client asked to
+        // stream, so every level is accepted without filtering
+        async move { Ok(()) }
+    }
+
+    fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        async move {
+            if request.uri == PROJECT_FILES_URI {
+                *self.project_watcher.lock().await = None;
+                logging::info("Stopped watching project for Terraform file changes");
+            }
+            Ok(())
+        }
+    }
+
     fn list_tools(
         &self,
         _request: Option<PaginatedRequestParams>,
@@ -1368,6 +2880,15 @@ impl ServerHandler for TfMcpServer {
         context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
         async move {
+            if self.tool_filter.is_disabled_by_policy(&request.name) {
+                return Err(McpError::invalid_request(
+                    format!(
+                        "Tool '{}' is disabled by policy (mcp.allowed_tools / mcp.denied_tools).",
+                        request.name
+                    ),
+                    None,
+                ));
+            }
             if !self.tool_filter.is_enabled(&request.name) {
                 return Err(McpError::invalid_request(
                     format!(
@@ -1377,9 +2898,160 @@ impl ServerHandler for TfMcpServer {
                     None,
                 ));
             }
+            // Everything logged while the tool runs is attributed to this span;
+            // elapsed_ms is recorded just before it closes
+            let span = tracing::info_span!(
+                "tool_call",
+                request_id = %context.id,
+                tool = %request.name,
+                elapsed_ms = tracing::field::Empty,
+            );
+            let started = std::time::Instant::now();
+            let tool_name = request.name.clone();
             let tool_context =
                 rmcp::handler::server::tool::ToolCallContext::new(self, request, context);
-            self.tool_router.call(tool_context).await
+            let result = self
+                .tool_router
+                .call(tool_context)
+                .instrument(span.clone())
+                .await;
+            span.record("elapsed_ms", started.elapsed().as_millis() as u64);
+
+            if let (Ok(output), Some(tool)) = (&result, self.tool_router.get(&tool_name)) {
+                if output_validation_enabled() {
+                    check_tool_output(tool, output, logging::warn);
+                }
+            }
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routed_tool(name: &str) -> Tool {
+        TfMcpServer::tool_router()
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| panic!("tool {name} is not routed"))
+    }
+
+    #[test]
+    fn test_structured_tools_declare_output_schema() {
+        for name in [
+            "list_terraform_resources",
+            "plan_destroy",
+            "preflight",
+            "validate_terraform_detailed",
+            "analyze_terraform",
+            "analyze_all",
+            "get_security_status",
+            "analyze_module_health",
+            "get_resource_dependency_graph",
+            "impact_analysis",
+            "locate_definition",
+            "analyze_plan",
+            "analyze_plan_file",
+        ] {
+            let schema = routed_tool(name).output_schema.expect("output schema");
+            assert_eq!(schema["type"], "object", "{name}");
         }
     }
+
+    #[test]
+    fn test_routed_output_schema_reports_mismatch() {
+        let tool = routed_tool("list_terraform_resources");
+
+        let listing = ResourceListing::new(vec!["aws_instance.web".to_string()]);
+        let conforming = structured_success(&listing).expect("result");
+        assert_eq!(check_tool_output(&tool, &conforming, |_| {}), 0);
+
+        // `total` as a string and the per-type counts missing
+        let drifted = CallToolResult::success(vec![Content::text(
+            serde_json::json!({
+                "resources": ["aws_instance.web"],
+                "total": "1",
+                "by_module": {"root": 1}
+            })
+            .to_string(),
+        )]);
+        let mut warnings = Vec::new();
+        let count = check_tool_output(&tool, &drifted, |w| warnings.push(w.to_string()));
+
+        assert_eq!(count, 2);
+        assert!(
+            warnings
+                .iter()
+                .all(|w| w.contains("list_terraform_resources"))
+        );
+        assert!(warnings.iter().any(|w| w.contains("'by_type'")));
+        assert!(warnings.iter().any(|w| w.contains("$.total")));
+    }
+
+    #[tokio::test]
+    async fn test_analysis_reports_match_their_output_schema() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(
+            project.path().join("main.tf"),
+            "variable \"region\" {}\nresource \"aws_db_instance\" \"main\" {\n  password = \"hunter2hunter2\"\n}\n",
+        )
+        .unwrap();
+        let service = crate::terraform::service::TerraformService::new(
+            std::path::PathBuf::from("terraform"),
+            project.path().to_path_buf(),
+        );
+        let checks = service.run_security_scan().await.unwrap();
+        assert!(!checks.hardcoded_secrets.is_empty());
+        let assert_conforms = |name: &str, result: CallToolResult| {
+            let mut warnings = Vec::new();
+            check_tool_output(&routed_tool(name), &result, |w| {
+                warnings.push(w.to_string())
+            });
+            assert!(warnings.is_empty(), "{warnings:?}");
+        };
+
+        let report = AnalysisReport {
+            analysis: service.analyze_configurations().await.unwrap(),
+            guideline_summary: None,
+        };
+        assert_conforms("analyze_terraform", structured_success(&report).unwrap());
+
+        let report = ModuleHealthReport {
+            health: service.analyze_module_health().await.unwrap(),
+            variable_quality: Some(VariableQuality {
+                variables_missing_type: checks.variables_missing_type.clone(),
+                variables_missing_description: checks.variables_missing_description.clone(),
+                any_type_usage: Vec::new(),
+                outputs_missing_description: Vec::new(),
+            }),
+        };
+        assert_conforms(
+            "analyze_module_health",
+            structured_success(&report).unwrap(),
+        );
+
+        let status = SecurityStatus {
+            policy: SecurityPolicySummary {
+                allow_dangerous_operations: false,
+                allow_auto_approve: false,
+            },
+            permissions: CommandPermissions {
+                apply: false,
+                destroy: false,
+                init: true,
+                plan: true,
+                validate: true,
+            },
+            audit_enabled: true,
+            security_scan: SecurityScanSummary {
+                status: "completed".to_string(),
+                secrets_count: checks.hardcoded_secrets.len(),
+                secrets_detected: checks.hardcoded_secrets,
+                compliance_score: checks.compliance_score,
+            },
+        };
+        assert_conforms("get_security_status", structured_success(&status).unwrap());
+    }
 }
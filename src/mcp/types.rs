@@ -1,7 +1,10 @@
 //! Input/output types for RMCP tools with automatic JSON Schema generation.
 
+use crate::terraform::model::{
+    LooseProviderConstraint, ModuleHealthAnalysis, SecretDetection, TerraformAnalysis,
+};
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Input for setting Terraform directory
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -10,7 +13,7 @@ pub struct DirectoryInput {
     pub directory: String,
 }
 
-/// Input for apply/destroy operations
+/// Input for destroy operation
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct AutoApproveInput {
     /// Whether to automatically approve the operation (default: false)
@@ -18,6 +21,83 @@ pub struct AutoApproveInput {
     pub auto_approve: bool,
 }
 
+/// Input for apply_terraform operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ApplyInput {
+    /// Whether to automatically approve the operation (default: false)
+    #[serde(default)]
+    pub auto_approve: bool,
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+    /// Extra flags appended to 'terraform apply', e.g. ["-parallelism=4"]; requires TFMCP_ALLOW_EXTRA_ARGS=true
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// Apply even when the plan destroys or replaces more resources than TFMCP_MAX_APPLY_DESTROYS (default: false)
+    #[serde(default)]
+    pub confirm_destructive: bool,
+}
+
+/// Input for init_terraform operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct InitInput {
+    /// Extra flags appended to 'terraform init', e.g. ["-upgrade"]; requires TFMCP_ALLOW_EXTRA_ARGS=true
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+/// Input for commands that may require 'terraform init' (plan, validate, state)
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct InitRetryInput {
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+}
+
+/// Input for get_terraform_plan operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanInput {
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+    /// Forward each 'plan -json' message as a logging (and, with a progress token,
+    /// progress) notification while the plan runs, then return the plan summary (default: false)
+    #[serde(default)]
+    pub stream: bool,
+    /// Extra flags appended to 'terraform plan', e.g. ["-refresh=false"]; requires TFMCP_ALLOW_EXTRA_ARGS=true
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+/// Input for get_terraform_state operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StateInput {
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+    /// Workspace to read, passed to terraform as TF_WORKSPACE so the selected workspace is unchanged (default: current)
+    pub workspace: Option<String>,
+}
+
+/// Input for validate_terraform operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ValidateInput {
+    /// Subdirectory (or file, whose directory is used) to validate, relative to the project root
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Run 'terraform init' and retry if the directory is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+}
+
+/// Input for validate_terraform_detailed operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ValidateDetailedInput {
+    /// Subdirectory (or file, whose directory is used) to validate, relative to the project root
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
 /// Input for analyze_terraform operation
 #[derive(Debug, Deserialize, JsonSchema)]
 #[allow(dead_code)]
@@ -55,6 +135,76 @@ pub struct ProviderDocsInput {
     pub data_type: Option<String>,
 }
 
+/// Input for free-text provider documentation search
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchProviderDocsInput {
+    /// Name of the provider (e.g., "aws")
+    pub provider_name: String,
+    /// Words to look for in resource and data source titles and descriptions (e.g., "s3 bucket policy")
+    pub query: String,
+    /// Provider namespace (optional, defaults to "hashicorp")
+    pub namespace: Option<String>,
+    /// Provider version (optional, defaults to latest)
+    pub version: Option<String>,
+    /// Maximum number of matches to return (optional, defaults to 20)
+    pub limit: Option<usize>,
+}
+
+/// Input for generate_tfvars operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateTfvarsInput {
+    /// Also list variables that have defaults, commented out (default: false)
+    #[serde(default)]
+    pub include_defaults: bool,
+    /// Write the skeleton to this .tfvars file, relative to the project root (e.g. "terraform.tfvars"); when unset the content is only returned
+    pub write_to: Option<String>,
+    /// Overwrite write_to if it already exists (default: false)
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Input for list_provider_resources operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListProviderResourcesInput {
+    /// Name of the provider (e.g., "aws")
+    pub provider_name: String,
+    /// Provider namespace (optional, defaults to "hashicorp")
+    pub namespace: Option<String>,
+    /// Provider version (optional); when set, the registry docs of that version are listed instead of the installed schema
+    pub version: Option<String>,
+    /// Case-insensitive substring the type names must contain (e.g., "s3_bucket")
+    pub filter: Option<String>,
+}
+
+/// Input for resource schema lookup
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResourceSchemaInput {
+    /// Resource or data source type (e.g., "aws_s3_bucket")
+    pub resource_type: String,
+    /// Look up a data source instead of a resource (default: false)
+    #[serde(default)]
+    pub data_source: bool,
+}
+
+/// Input for warming the provider schema cache
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WarmProviderSchemasInput {
+    /// Fetch the schemas in the background and return immediately (default: false)
+    #[serde(default)]
+    pub background: bool,
+}
+
+/// Input for resource scaffolding
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ScaffoldResourceInput {
+    /// Resource type to scaffold (e.g., "aws_s3_bucket")
+    pub resource_type: String,
+    /// Local name for the resource block (optional, defaults to "this")
+    pub name: Option<String>,
+    /// Provider namespace used for the docs fallback (optional, defaults to "hashicorp")
+    pub namespace: Option<String>,
+}
+
 /// Input for module details lookup
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ModuleInput {
@@ -68,6 +218,21 @@ pub struct ModuleInput {
     pub version: Option<String>,
 }
 
+/// Input for module README lookup
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModuleReadmeInput {
+    /// Module namespace (e.g., "terraform-aws-modules")
+    pub namespace: String,
+    /// Module name (e.g., "vpc")
+    pub name: String,
+    /// Provider name (e.g., "aws")
+    pub provider: String,
+    /// Specific version (optional, defaults to latest)
+    pub version: Option<String>,
+    /// Submodule name or path (e.g., "vpc-endpoints"); defaults to the root module
+    pub submodule: Option<String>,
+}
+
 /// Input for latest module version lookup
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ModuleVersionInput {
@@ -87,12 +252,97 @@ pub struct AnalyzePlanInput {
     /// Include risk assessment in the analysis (default: true)
     #[serde(default = "default_true")]
     pub include_risk: bool,
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+    /// Leave no-op and read changes out of resource_changes (default: false)
+    #[serde(default)]
+    pub hide_no_op: bool,
+    /// Leave updates that only change tags/tags_all out of resource_changes (default: false)
+    #[serde(default)]
+    pub collapse_tag_only: bool,
+}
+
+/// Input for analyze_plan_file operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnalyzePlanFileInput {
+    /// Plan to analyze, relative to the project root: `terraform show -json`
+    /// output or a binary plan file
+    pub plan_file: String,
+    /// Include risk assessment in the analysis (default: true)
+    #[serde(default = "default_true")]
+    pub include_risk: bool,
+    /// Leave no-op and read changes out of resource_changes (default: false)
+    #[serde(default)]
+    pub hide_no_op: bool,
+    /// Leave updates that only change tags/tags_all out of resource_changes (default: false)
+    #[serde(default)]
+    pub collapse_tag_only: bool,
 }
 
 fn default_true() -> bool {
     true
 }
 
+/// Input for save_plan operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SavePlanInput {
+    /// Plan file to write, relative to the project root (default: tfmcp.tfplan)
+    pub plan_file: Option<String>,
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+}
+
+/// Input for apply_saved_plan operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ApplySavedPlanInput {
+    /// Plan file written by save_plan, relative to the project root (default: tfmcp.tfplan)
+    pub plan_file: Option<String>,
+    /// Apply even when the plan destroys or replaces more resources than TFMCP_MAX_APPLY_DESTROYS (default: false)
+    #[serde(default)]
+    pub confirm_destructive: bool,
+}
+
+/// Input for confirm_apply operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ConfirmApplyInput {
+    /// Approval token returned by propose_apply
+    pub token: String,
+    /// Apply even when the plan destroys or replaces more resources than TFMCP_MAX_APPLY_DESTROYS (default: false)
+    #[serde(default)]
+    pub confirm_destructive: bool,
+}
+
+/// Input for analyze_all operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnalyzeAllInput {
+    /// Glob relative to the project root selecting directories, e.g. 'environments/*/' or '**'
+    pub pattern: String,
+    /// Maximum directories to analyze (default and upper bound: 50)
+    pub max_directories: Option<usize>,
+}
+
+/// Input for save_plan_snapshot operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanSnapshotInput {
+    /// Snapshot id; replaces an existing snapshot with the same id (default: generated)
+    pub id: Option<String>,
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+}
+
+/// Input for diff_plans operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DiffPlansInput {
+    /// Id of the snapshot to compare against, as returned by save_plan_snapshot
+    pub snapshot_id: String,
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+}
+
 /// Input for analyze_state operation
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct AnalyzeStateInput {
@@ -101,6 +351,22 @@ pub struct AnalyzeStateInput {
     /// Enable drift detection (default: false)
     #[serde(default)]
     pub detect_drift: bool,
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+    /// Workspace to analyze, passed to terraform as TF_WORKSPACE so the selected workspace is unchanged (default: current)
+    pub workspace: Option<String>,
+}
+
+/// Input for pull_state operation
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PullStateInput {
+    /// Run 'terraform init' and retry if the project is not initialized (default: false)
+    #[serde(default)]
+    pub auto_init: bool,
+    /// Largest serialized state to return; trailing resources beyond it are
+    /// omitted (default: 524288)
+    pub max_bytes: Option<usize>,
 }
 
 /// Input for workspace operations
@@ -110,6 +376,10 @@ pub struct WorkspaceInput {
     pub action: String,
     /// Workspace name (required for new, select, delete)
     pub name: Option<String>,
+    /// For list: report whether each workspace's state has resources and
+    /// how many, reading each with TF_WORKSPACE (slower; default: false)
+    #[serde(default)]
+    pub with_counts: bool,
 }
 
 /// Input for terraform import
@@ -144,6 +414,12 @@ pub struct FmtInput {
 pub struct GraphInput {
     /// Graph type: "plan" or "apply" (optional)
     pub graph_type: Option<String>,
+    /// Return the {nodes, edges} dependency graph structure used by get_resource_dependency_graph (default: false)
+    #[serde(default)]
+    pub structured: bool,
+    /// Include the raw DOT output alongside the structured graph (default: false)
+    #[serde(default)]
+    pub include_dot: bool,
 }
 
 /// Input for terraform output
@@ -151,6 +427,9 @@ pub struct GraphInput {
 pub struct OutputInput {
     /// Specific output name (optional, returns all if not specified)
     pub name: Option<String>,
+    /// Return sensitive values verbatim instead of "<sensitive>" (requires TFMCP_ALLOW_DANGEROUS_OPS=true)
+    #[serde(default)]
+    pub reveal_sensitive: bool,
 }
 
 /// Input for taint/untaint operations
@@ -205,3 +484,113 @@ pub struct ProviderCapabilitiesInput {
     /// Provider namespace (optional, defaults to "hashicorp")
     pub namespace: Option<String>,
 }
+
+/// Input for dependency impact analysis
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ImpactAnalysisInput {
+    /// Resource address as it appears in the dependency graph (e.g., "aws_vpc.main")
+    pub address: String,
+}
+
+/// Input for locate_definition
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LocateDefinitionInput {
+    /// Resource address (e.g., "aws_instance.web"), "var.<name>", "output.<name>", or a bare variable/output name
+    pub name: String,
+}
+
+/// Input for verify_module_source
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct VerifyModuleSourceInput {
+    /// Module source to check (e.g., "git::https://github.com/org/repo.git?ref=<sha>" or "terraform-aws-modules/vpc/aws"); omit to check every module call in the project
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Version constraint of a registry `source` (e.g., "5.8.1")
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// Input for resource_subgraph
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResourceSubgraphInput {
+    /// Resource address as it appears in the dependency graph (e.g., "aws_instance.web")
+    pub address: String,
+    /// Output format: "json" (default) or "mermaid"
+    pub format: Option<String>,
+}
+
+// ==================== Output Types ====================
+
+/// Output of analyze_terraform
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AnalysisReport {
+    #[serde(flatten)]
+    pub analysis: TerraformAnalysis,
+    /// Guideline check counts; null when the scan failed
+    pub guideline_summary: Option<GuidelineSummary>,
+}
+
+/// Provider version and documentation findings of the guideline checks
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GuidelineSummary {
+    pub compliance_score: u8,
+    pub providers_missing_version: Vec<String>,
+    pub loose_provider_constraints: Vec<LooseProviderConstraint>,
+    pub variables_missing_type: usize,
+    pub variables_missing_description: usize,
+    pub outputs_missing_description: usize,
+}
+
+/// Output of analyze_module_health
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ModuleHealthReport {
+    #[serde(flatten)]
+    pub health: ModuleHealthAnalysis,
+    /// Variable and output documentation findings; null when the scan failed
+    pub variable_quality: Option<VariableQuality>,
+}
+
+/// Variables and outputs the guideline checks flagged
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VariableQuality {
+    pub variables_missing_type: Vec<String>,
+    pub variables_missing_description: Vec<String>,
+    pub any_type_usage: Vec<String>,
+    pub outputs_missing_description: Vec<String>,
+}
+
+/// Output of get_security_status
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SecurityStatus {
+    pub policy: SecurityPolicySummary,
+    pub permissions: CommandPermissions,
+    pub audit_enabled: bool,
+    pub security_scan: SecurityScanSummary,
+}
+
+/// Policy switches set through the environment
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SecurityPolicySummary {
+    pub allow_dangerous_operations: bool,
+    pub allow_auto_approve: bool,
+}
+
+/// Whether each terraform command may run
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommandPermissions {
+    pub apply: bool,
+    pub destroy: bool,
+    pub init: bool,
+    pub plan: bool,
+    pub validate: bool,
+}
+
+/// Secret detection results of the security scan
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SecurityScanSummary {
+    /// "completed" or "failed"
+    pub status: String,
+    pub secrets_detected: Vec<SecretDetection>,
+    pub secrets_count: usize,
+    pub compliance_score: u8,
+}
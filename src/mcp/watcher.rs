@@ -0,0 +1,124 @@
+//! Project directory watcher backing `resources/subscribe` for Terraform files.
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// URI of the subscribable resource describing the project's `.tf` files
+pub const PROJECT_FILES_URI: &str = "terraform://project-files";
+
+/// Quiet period after the last change before a notification is emitted
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watches a project directory and emits one signal per burst of `.tf` changes.
+///
+/// Dropping the watcher stops the underlying file watch and closes the
+/// receiver returned by [`ProjectFileWatcher::start`].
+pub struct ProjectFileWatcher {
+    _watcher: RecommendedWatcher,
+    debounce_task: JoinHandle<()>,
+}
+
+impl ProjectFileWatcher {
+    /// Start watching `dir` recursively for `.tf` file changes
+    pub fn start(
+        dir: &Path,
+        debounce: Duration,
+    ) -> anyhow::Result<(Self, mpsc::UnboundedReceiver<()>)> {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let (change_tx, change_rx) = mpsc::unbounded_channel();
+
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                if let Ok(event) = result {
+                    if is_tf_change(&event) {
+                        let _ = event_tx.send(());
+                    }
+                }
+            })?;
+        watcher.watch(dir, RecursiveMode::Recursive)?;
+
+        let debounce_task = tokio::spawn(async move {
+            while event_rx.recv().await.is_some() {
+                // Swallow further events until the directory has been quiet
+                // for the debounce period
+                loop {
+                    match tokio::time::timeout(debounce, event_rx.recv()).await {
+                        Ok(Some(())) => continue,
+                        Ok(None) => return,
+                        Err(_) => break,
+                    }
+                }
+                if change_tx.send(()).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok((
+            Self {
+                _watcher: watcher,
+                debounce_task,
+            },
+            change_rx,
+        ))
+    }
+}
+
+impl Drop for ProjectFileWatcher {
+    fn drop(&mut self) {
+        self.debounce_task.abort();
+    }
+}
+
+/// Whether a filesystem event modifies a Terraform configuration file
+fn is_tf_change(event: &notify::Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event
+            .paths
+            .iter()
+            .any(|path| path.extension().is_some_and(|ext| ext == "tf"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_touching_tf_file_emits_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_watcher, mut changes) =
+            ProjectFileWatcher::start(dir.path(), Duration::from_millis(100)).unwrap();
+
+        std::fs::write(dir.path().join("main.tf"), "# changed").unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), changes.recv()).await;
+        assert_eq!(received.ok().flatten(), Some(()));
+    }
+
+    #[tokio::test]
+    async fn test_non_tf_file_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_watcher, mut changes) =
+            ProjectFileWatcher::start(dir.path(), Duration::from_millis(100)).unwrap();
+
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let received = tokio::time::timeout(Duration::from_millis(500), changes.recv()).await;
+        assert!(received.is_err(), "No change should be reported");
+    }
+
+    #[tokio::test]
+    async fn test_drop_closes_receiver() {
+        let dir = tempfile::tempdir().unwrap();
+        let (watcher, mut changes) =
+            ProjectFileWatcher::start(dir.path(), Duration::from_millis(100)).unwrap();
+
+        drop(watcher);
+
+        let received = tokio::time::timeout(Duration::from_secs(5), changes.recv()).await;
+        assert_eq!(received.ok().flatten(), None);
+    }
+}
@@ -1,5 +1,9 @@
+use crate::terraform::model::TerraformAnalysis;
 use std::collections::HashMap;
 
+/// Name of the prompt that asks the model to write a module README
+pub const DOCUMENT_MODULE_PROMPT: &str = "document_module";
+
 /// Builder for creating structured tool descriptions with usage guides and constraints
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
@@ -226,6 +230,84 @@ pub fn common_security_notes() -> Vec<String> {
     ]
 }
 
+/// Build the `document_module` prompt, embedding the project's variables,
+/// outputs, resources and providers so no separate analyze call is needed
+pub fn build_document_module_prompt(analysis: &TerraformAnalysis) -> String {
+    let mut prompt = format!(
+        "Write a README.md for the Terraform module in `{}`.\n\n\
+         Include a short overview of what the module manages, a usage example calling the \
+         module with its required inputs, and Markdown tables for inputs and outputs. \
+         The inputs table has the columns Name, Description, Type, Default and Required \
+         (an input is required when it has no default). The outputs table has the columns \
+         Name and Description. Use only the declarations below; where a description is \
+         missing, write \"TODO\" instead of inventing one.",
+        analysis.project_directory
+    );
+
+    prompt.push_str("\n\n## Variables");
+    if analysis.variables.is_empty() {
+        prompt.push_str("\n(none)");
+    }
+    for variable in &analysis.variables {
+        prompt.push_str(&format!(
+            "\n- `{}` | type: {} | default: {} | description: {}",
+            variable.name,
+            variable.type_.as_deref().unwrap_or("any"),
+            variable
+                .default
+                .as_ref()
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "(required)".to_string()),
+            variable.description.as_deref().unwrap_or("(missing)")
+        ));
+    }
+
+    prompt.push_str("\n\n## Outputs");
+    if analysis.outputs.is_empty() {
+        prompt.push_str("\n(none)");
+    }
+    for output in &analysis.outputs {
+        prompt.push_str(&format!(
+            "\n- `{}` | description: {}",
+            output.name,
+            output.description.as_deref().unwrap_or("(missing)")
+        ));
+    }
+
+    prompt.push_str("\n\n## Resources");
+    if analysis.resources.is_empty() {
+        prompt.push_str("\n(none)");
+    }
+    for resource in &analysis.resources {
+        prompt.push_str(&format!(
+            "\n- `{}.{}` ({})",
+            resource.resource_type, resource.name, resource.file
+        ));
+    }
+
+    if !analysis.providers.is_empty() {
+        prompt.push_str("\n\n## Providers");
+        for provider in &analysis.providers {
+            prompt.push_str(&format!(
+                "\n- `{}` {}",
+                provider.name,
+                provider
+                    .version
+                    .as_deref()
+                    .unwrap_or("(no version constraint)")
+            ));
+        }
+    }
+    if let Some(required_version) = &analysis.required_version {
+        prompt.push_str(&format!(
+            "\n\nRequired Terraform version: {}",
+            required_version
+        ));
+    }
+
+    prompt
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -285,4 +367,59 @@ mod tests {
         let security_notes = common_security_notes();
         assert!(!security_notes.is_empty());
     }
+
+    #[test]
+    fn test_document_module_prompt_embeds_declarations() {
+        use crate::terraform::model::{TerraformOutput, TerraformResource, TerraformVariable};
+
+        let analysis = TerraformAnalysis {
+            project_directory: "/work/modules/logs".to_string(),
+            file_count: 2,
+            resources: vec![TerraformResource {
+                resource_type: "aws_s3_bucket".to_string(),
+                name: "logs".to_string(),
+                file: "main.tf".to_string(),
+                provider: "aws".to_string(),
+                provider_alias: None,
+            }],
+            variables: vec![
+                TerraformVariable {
+                    name: "bucket_name".to_string(),
+                    description: Some("Name of the log bucket".to_string()),
+                    type_: Some("string".to_string()),
+                    default: None,
+                    sensitive: false,
+                    effective: None,
+                },
+                TerraformVariable {
+                    name: "retention_days".to_string(),
+                    description: None,
+                    type_: Some("number".to_string()),
+                    default: Some(serde_json::json!(30)),
+                    sensitive: false,
+                    effective: None,
+                },
+            ],
+            outputs: vec![TerraformOutput {
+                name: "bucket_arn".to_string(),
+                description: Some("ARN of the log bucket".to_string()),
+                value: None,
+            }],
+            providers: Vec::new(),
+            required_version: None,
+            version_compatibility: None,
+            remote_state_dependencies: Vec::new(),
+            state_encryption: None,
+        };
+
+        let prompt = build_document_module_prompt(&analysis);
+        assert!(prompt.contains("/work/modules/logs"));
+        assert!(prompt.contains(
+            "- `bucket_name` | type: string | default: (required) | description: Name of the log bucket"
+        ));
+        assert!(prompt.contains("- `retention_days` | type: number | default: 30"));
+        assert!(prompt.contains("- `bucket_arn` | description: ARN of the log bucket"));
+        assert!(prompt.contains("- `aws_s3_bucket.logs` (main.tf)"));
+        assert!(!prompt.contains("## Providers"));
+    }
 }
@@ -12,6 +12,7 @@ pub struct BatchFetcher {
 }
 
 impl BatchFetcher {
+    #[allow(dead_code)]
     pub fn new(client: Arc<RegistryClient>, max_concurrent: usize) -> Self {
         Self {
             client,
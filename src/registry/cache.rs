@@ -101,12 +101,46 @@ impl<T: Clone> Default for SimpleCache<T> {
     }
 }
 
+/// Time-to-live for cached documentation content
+pub const DOCUMENTATION_CACHE_TTL: Duration = Duration::from_secs(1800);
+
+/// Time-to-live for cached provider information
+pub const PROVIDERS_CACHE_TTL: Duration = Duration::from_secs(600);
+
 /// Specialized cache for documentation content
 pub type DocumentationCache = SimpleCache<String>;
 
 /// Specialized cache for provider information
 pub type ProvidersCache = SimpleCache<String>;
 
+/// Version segment of keys for data that isn't tied to a provider version
+pub const LATEST_VERSION_KEY: &str = "latest";
+
+/// Canonical registry cache key: `endpoint:namespace:provider:version[:params]`.
+///
+/// Every part is always present, so entries for providers sharing a service
+/// slug (or a namespace-less lookup) never collide; `:` and `%` inside a part
+/// are percent-escaped to keep the separators unambiguous.
+pub fn registry_cache_key(
+    endpoint: &str,
+    namespace: &str,
+    provider: &str,
+    version: Option<&str>,
+    params: &[&str],
+) -> String {
+    [
+        endpoint,
+        namespace,
+        provider,
+        version.unwrap_or(LATEST_VERSION_KEY),
+    ]
+    .iter()
+    .chain(params)
+    .map(|part| part.replace('%', "%25").replace(':', "%3A"))
+    .collect::<Vec<_>>()
+    .join(":")
+}
+
 /// Cache manager that handles multiple cache types
 pub struct CacheManager {
     pub documentation_cache: DocumentationCache,
@@ -116,8 +150,8 @@ pub struct CacheManager {
 impl CacheManager {
     pub fn new() -> Self {
         Self {
-            documentation_cache: SimpleCache::new(Duration::from_secs(1800)), // 30 minutes for docs
-            providers_cache: SimpleCache::new(Duration::from_secs(600)), // 10 minutes for providers
+            documentation_cache: SimpleCache::new(DOCUMENTATION_CACHE_TTL),
+            providers_cache: SimpleCache::new(PROVIDERS_CACHE_TTL),
         }
     }
 
@@ -182,4 +216,100 @@ mod tests {
         sleep(Duration::from_millis(60)).await;
         assert_eq!(cache.get("key1").await, None);
     }
+
+    #[test]
+    fn test_registry_cache_key_includes_full_tuple() {
+        assert_eq!(
+            registry_cache_key(
+                "docids",
+                "hashicorp",
+                "aws",
+                None,
+                &["resources", "instance"]
+            ),
+            "docids:hashicorp:aws:latest:resources:instance"
+        );
+        assert_ne!(
+            registry_cache_key(
+                "docids",
+                "hashicorp",
+                "aws",
+                None,
+                &["resources", "instance"]
+            ),
+            registry_cache_key(
+                "docids",
+                "hashicorp",
+                "aws",
+                None,
+                &["data-sources", "instance"]
+            )
+        );
+        assert_ne!(
+            registry_cache_key("docindex", "hashicorp", "aws", Some("5.0.0"), &[]),
+            registry_cache_key("docindex", "hashicorp", "aws", Some("5.1.0"), &[])
+        );
+        assert_ne!(
+            registry_cache_key("info", "hashicorp", "aws", None, &[]),
+            registry_cache_key("docindex", "hashicorp", "aws", None, &[])
+        );
+        // Separators inside a part can't shift the boundaries between parts
+        assert_ne!(
+            registry_cache_key("info", "a:b", "c", None, &[]),
+            registry_cache_key("info", "a", "b:c", None, &[])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_providers_with_same_slug_get_distinct_entries() {
+        let cache = CacheManager::new();
+        let aws = registry_cache_key(
+            "docids",
+            "hashicorp",
+            "aws",
+            None,
+            &["resources", "instance"],
+        );
+        let google = registry_cache_key(
+            "docids",
+            "hashicorp",
+            "google",
+            None,
+            &["resources", "instance"],
+        );
+        let community = registry_cache_key(
+            "docids",
+            "community",
+            "aws",
+            None,
+            &["resources", "instance"],
+        );
+
+        cache
+            .documentation_cache
+            .set(aws.clone(), "aws docs".to_string())
+            .await;
+        cache
+            .documentation_cache
+            .set(google.clone(), "google docs".to_string())
+            .await;
+        cache
+            .documentation_cache
+            .set(community.clone(), "community docs".to_string())
+            .await;
+
+        assert_eq!(cache.documentation_cache.size().await, 3);
+        assert_eq!(
+            cache.documentation_cache.get(&aws).await.as_deref(),
+            Some("aws docs")
+        );
+        assert_eq!(
+            cache.documentation_cache.get(&google).await.as_deref(),
+            Some("google docs")
+        );
+        assert_eq!(
+            cache.documentation_cache.get(&community).await.as_deref(),
+            Some("community docs")
+        );
+    }
 }
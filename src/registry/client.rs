@@ -1,7 +1,11 @@
-use reqwest::Client;
+use crate::registry::rate_limit::RateLimiter;
+use futures::stream::{self, StreamExt};
+use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
 
@@ -66,6 +70,27 @@ pub enum RegistryError {
         "Module '{module}' exists but has no available versions. This may indicate a deprecated or invalid module."
     )]
     NoModuleVersionsAvailable { module: String },
+
+    #[error(
+        "Submodule '{submodule}' not found in module '{module}'. Use get_module_details to list the available submodules."
+    )]
+    SubmoduleNotFound { submodule: String, module: String },
+}
+
+impl RegistryError {
+    /// Whether the registry answered that the thing asked for does not exist,
+    /// so another namespace may still have it. Rate limits, timeouts and
+    /// other HTTP or parse failures would fail the same way elsewhere.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            RegistryError::ProviderNotFound { .. }
+                | RegistryError::NoVersionsAvailable { .. }
+                | RegistryError::ModuleNotFound { .. }
+                | RegistryError::ServiceNotFound { .. }
+                | RegistryError::DocumentationNotFound { .. }
+        )
+    }
 }
 
 impl From<reqwest::Error> for RegistryError {
@@ -135,6 +160,18 @@ pub struct DocIdResult {
     pub extra: HashMap<String, Value>,
 }
 
+/// A doc found by [`RegistryClient::search_docs`] with its content, or why
+/// the content could not be fetched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocWithContent {
+    #[serde(flatten)]
+    pub doc: DocIdResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderVersions {
     /// Registry returns versions as either `["1.0"]` or `[{"version":"1.0",...}]`
@@ -229,6 +266,12 @@ pub struct ModuleInfo {
     pub verified: bool,
     #[serde(default)]
     pub owner: String,
+    /// Latest published version, from the v2 search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_version: Option<String>,
+    /// Logo of the module's provider, from the v2 search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_logo_url: Option<String>,
     #[serde(flatten)]
     pub extra: HashMap<String, Value>,
 }
@@ -258,6 +301,9 @@ pub struct ModuleDetails {
     pub root: Option<ModuleRoot>,
     #[serde(default)]
     pub submodules: Vec<ModuleSubmodule>,
+    /// Examples under `examples/`, described like submodules
+    #[serde(default)]
+    pub examples: Vec<ModuleSubmodule>,
     #[serde(default)]
     pub versions: Vec<String>,
     #[serde(flatten)]
@@ -310,6 +356,224 @@ pub struct ModuleSubmodule {
     pub extra: HashMap<String, Value>,
 }
 
+impl ModuleDetails {
+    /// README of the root module, or of the submodule matched by name or path.
+    ///
+    /// Returns `Ok(None)` when the registry has no README content for it.
+    pub fn readme(&self, submodule: Option<&str>) -> Result<Option<&str>, RegistryError> {
+        let readme = match submodule {
+            None => self.root.as_ref().map(|root| root.readme.as_str()),
+            Some(wanted) => Some(
+                self.find_submodule(wanted)
+                    .ok_or_else(|| RegistryError::SubmoduleNotFound {
+                        submodule: wanted.to_string(),
+                        module: self.id.clone(),
+                    })?
+                    .readme
+                    .as_str(),
+            ),
+        };
+        Ok(readme.filter(|r| !r.trim().is_empty()))
+    }
+
+    /// Interface summary of each submodule, in registry order
+    pub fn submodule_summaries(&self) -> Vec<SubmoduleSummary> {
+        self.submodules
+            .iter()
+            .map(|s| SubmoduleSummary {
+                name: s.name.clone(),
+                path: s.path.clone(),
+                input_count: s.inputs.len(),
+                required_input_count: s.inputs.iter().filter(|i| i.required).count(),
+                output_count: s.outputs.len(),
+                resources: s
+                    .resources
+                    .iter()
+                    .map(|r| format!("{}.{}", r.resource_type, r.name))
+                    .collect(),
+                has_readme: !s.readme.trim().is_empty(),
+            })
+            .collect()
+    }
+
+    /// Name, path and registry README of each example, in registry order
+    pub fn example_listing(&self) -> Vec<ModuleExample> {
+        self.examples
+            .iter()
+            .map(|e| ModuleExample {
+                name: e.name.clone(),
+                path: e.path.clone(),
+                readme: Some(e.readme.clone()).filter(|r| !r.trim().is_empty()),
+                main_tf: None,
+            })
+            .collect()
+    }
+
+    /// Submodule matched by name, path, or path relative to `modules/`
+    fn find_submodule(&self, wanted: &str) -> Option<&ModuleSubmodule> {
+        let wanted = wanted.trim_matches('/');
+        self.submodules.iter().find(|s| {
+            let path = s.path.trim_matches('/');
+            s.name == wanted || path == wanted || path.strip_prefix("modules/") == Some(wanted)
+        })
+    }
+}
+
+/// Interface of one submodule, for deciding whether to call it directly
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubmoduleSummary {
+    pub name: String,
+    /// Path within the module, usable as `<source>//<path>`
+    pub path: String,
+    pub input_count: usize,
+    /// Inputs without a default
+    pub required_input_count: usize,
+    pub output_count: usize,
+    /// `type.name` of each resource the submodule declares
+    pub resources: Vec<String>,
+    pub has_readme: bool,
+}
+
+/// Submodules of a registry module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleSubmodules {
+    pub module_id: String,
+    pub version: String,
+    pub submodule_count: usize,
+    pub submodules: Vec<SubmoduleSummary>,
+}
+
+/// A published example of how to call a module
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleExample {
+    pub name: String,
+    /// Path within the module, e.g. `examples/complete`
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme: Option<String>,
+    /// The example's `main.tf` from the module's source repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_tf: Option<String>,
+}
+
+/// Examples of a registry module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleExamples {
+    pub module_id: String,
+    pub version: String,
+    pub example_count: usize,
+    pub examples: Vec<ModuleExample>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Modules from a v2 `modules` JSON:API listing; entries without a
+/// namespace, name or provider are skipped
+fn modules_from_v2(data: &[Value], included: Option<&Value>) -> Vec<ModuleInfo> {
+    let versions: HashMap<&str, &Value> = included
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|item| item.get("type").and_then(Value::as_str) == Some("module-versions"))
+        .filter_map(|item| Some((item.get("id")?.as_str()?, item.get("attributes")?)))
+        .collect();
+    let text = |attributes: &Value, key: &str| {
+        attributes
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string()
+    };
+
+    data.iter()
+        .filter_map(|module| {
+            let attributes = module.get("attributes")?;
+            let namespace = text(attributes, "namespace");
+            let name = text(attributes, "name");
+            let provider = text(attributes, "provider-name");
+            if namespace.is_empty() || name.is_empty() || provider.is_empty() {
+                return None;
+            }
+            let latest = module
+                .pointer("/relationships/latest-version/data/id")
+                .and_then(Value::as_str)
+                .and_then(|id| versions.get(id));
+            let latest_version = latest
+                .and_then(|v| v.get("version"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let version = latest_version.clone().unwrap_or_default();
+
+            Some(ModuleInfo {
+                id: format!("{}/{}/{}/{}", namespace, name, provider, version)
+                    .trim_end_matches('/')
+                    .to_string(),
+                description: text(attributes, "description"),
+                source: text(attributes, "source"),
+                published_at: latest
+                    .map(|v| text(v, "published-at"))
+                    .unwrap_or_else(|| text(attributes, "published-at")),
+                downloads: attributes
+                    .get("downloads")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0),
+                verified: attributes
+                    .get("verified")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                owner: text(attributes, "owner-name"),
+                provider_logo_url: attributes
+                    .get("provider-logo-url")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                latest_version,
+                namespace,
+                name,
+                provider,
+                version,
+                extra: HashMap::new(),
+            })
+        })
+        .collect()
+}
+
+/// Where a module README was found
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadmeSource {
+    Registry,
+    Repository,
+    NotFound,
+}
+
+/// README of a registry module or one of its submodules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleReadme {
+    pub module_id: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submodule: Option<String>,
+    pub source: ReadmeSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// `(owner, repo)` of a GitHub module source URL
+fn github_repository(source: &str) -> Option<(String, String)> {
+    let rest = source
+        .trim_start_matches("git::")
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .strip_prefix("github.com/")?;
+    let mut parts = rest.split('/');
+    let owner = parts.next().filter(|s| !s.is_empty())?;
+    let repo = parts.next().filter(|s| !s.is_empty())?;
+    let repo = repo.split(['?', '#']).next().unwrap_or(repo);
+    Some((owner.to_string(), repo.trim_end_matches(".git").to_string()))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ModuleInput {
     #[serde(default)]
@@ -460,9 +724,55 @@ pub struct ModuleVersionProvider {
     pub extra: HashMap<String, Value>,
 }
 
+/// Largest doc or README body returned to callers when `TFMCP_MAX_DOC_BYTES` is unset
+pub const DEFAULT_MAX_DOC_BYTES: usize = 256 * 1024;
+
+/// Total time allowed per registry request when `TFMCP_REGISTRY_TIMEOUT_SECS` is unset
+pub const DEFAULT_REGISTRY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most doc contents [`RegistryClient::fetch_doc_contents`] fetches at once
+pub const MAX_CONCURRENT_DOC_FETCHES: usize = 4;
+
+/// Time allowed for DNS and the TCP/TLS handshake, so an unreachable
+/// registry fails fast; never longer than the total timeout
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// MCP client (`name/version`) reported during `initialize`
+static MCP_CLIENT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Record the connected MCP client so registry requests identify it.
+/// Both parts come from the client, so only visible ASCII is kept for the
+/// User-Agent header.
+pub fn set_mcp_client(name: &str, version: &str) {
+    if let Ok(mut client) = MCP_CLIENT.write() {
+        *client = Some(format!(
+            "{}/{}",
+            visible_ascii(name),
+            visible_ascii(version)
+        ));
+    }
+}
+
+/// `text` without whitespace, control and non-ASCII characters
+fn visible_ascii(text: &str) -> String {
+    text.chars().filter(char::is_ascii_graphic).collect()
+}
+
+/// User-agent sent to registries: `tfmcp/<version>`, followed by the
+/// connected MCP client in parentheses once it is known
+pub fn user_agent() -> String {
+    let tfmcp = format!("tfmcp/{}", env!("CARGO_PKG_VERSION"));
+    match MCP_CLIENT.read().ok().and_then(|client| client.clone()) {
+        Some(client) => format!("{} ({})", tfmcp, client),
+        None => tfmcp,
+    }
+}
+
 pub struct RegistryClient {
     client: Client,
     base_url: String,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    max_doc_bytes: usize,
 }
 
 impl Default for RegistryClient {
@@ -473,13 +783,53 @@ impl Default for RegistryClient {
 
 impl RegistryClient {
     pub fn new() -> Self {
+        Self::with_timeout(registry_timeout_from_env())
+    }
+
+    /// Client whose requests fail with [`RegistryError::HttpError`] once
+    /// `timeout` has passed
+    pub fn with_timeout(timeout: Duration) -> Self {
         Self {
             client: Client::builder()
-                .user_agent("tfmcp/0.1.3")
-                .timeout(std::time::Duration::from_secs(30))
+                .user_agent(user_agent())
+                .connect_timeout(DEFAULT_CONNECT_TIMEOUT.min(timeout))
+                .timeout(timeout)
                 .build()
                 .unwrap_or_else(|_| Client::new()), // Fallback to default client
             base_url: "https://registry.terraform.io".to_string(),
+            rate_limiter: None,
+            max_doc_bytes: max_doc_bytes_from_env(),
+        }
+    }
+
+    /// Pace every request from this client through a shared limiter
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Send requests to another registry, e.g. a mirror or a test server
+    #[allow(dead_code)]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Truncate doc content and READMEs longer than `max_doc_bytes`
+    #[allow(dead_code)]
+    pub fn with_max_doc_bytes(mut self, max_doc_bytes: usize) -> Self {
+        self.max_doc_bytes = max_doc_bytes;
+        self
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        // The client may have been built before the MCP client connected
+        let request = request
+            .header(reqwest::header::USER_AGENT, user_agent())
+            .build()?;
+        match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.execute(&self.client, request).await,
+            None => self.client.execute(request).await,
         }
     }
 
@@ -488,7 +838,9 @@ impl RegistryClient {
         let url = format!("{}/v1/providers", self.base_url);
         debug!("Searching providers with query '{}' at URL: {}", query, url);
 
-        let response = self.client.get(&url).query(&[("q", query)]).send().await?;
+        let response = self
+            .send(self.client.get(&url).query(&[("q", query)]))
+            .await?;
         let status = response.status();
 
         debug!("Search response status: {}", status);
@@ -575,11 +927,9 @@ impl RegistryClient {
             .filter_map(|provider| {
                 let mut provider_info = ProviderInfo::default();
 
-                if let Some(name) = provider.get("name").and_then(|v| v.as_str()) {
-                    provider_info.name = name.to_string();
-                } else {
-                    return None; // Name is required
-                }
+                // Name is required
+                let name = provider.get("name").and_then(|v| v.as_str())?;
+                provider_info.name = name.to_string();
 
                 if let Some(namespace) = provider.get("namespace").and_then(|v| v.as_str()) {
                     provider_info.namespace = namespace.to_string();
@@ -615,7 +965,7 @@ impl RegistryClient {
 
         debug!("Fetching provider info from URL: {}", url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         let status = response.status();
 
         debug!("Response status: {}", status);
@@ -725,7 +1075,7 @@ impl RegistryClient {
 
         debug!("Fetching provider versions from URL: {}", url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         let status = response.status();
 
         debug!("Response status: {}", status);
@@ -889,7 +1239,7 @@ impl RegistryClient {
                     params
                 );
 
-                let response = self.client.get(url).query(params).send().await?;
+                let response = self.send(self.client.get(url).query(params)).await?;
                 let status = response.status();
 
                 debug!("Response status: {} for URL: {}", status, url);
@@ -1058,19 +1408,90 @@ impl RegistryClient {
             .collect()
     }
 
+    /// List every documentation entry (resources, data sources, guides, ...)
+    /// published for one provider version
+    pub async fn list_provider_docs(
+        &self,
+        provider_name: &str,
+        namespace: &str,
+        version: &str,
+    ) -> Result<Vec<DocIdResult>, RegistryError> {
+        let url = format!(
+            "{}/v1/providers/{}/{}/{}",
+            self.base_url, namespace, provider_name, version
+        );
+        debug!("Listing provider docs from URL: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+        let status = response.status();
+
+        if status == 404 {
+            warn!(
+                "Provider version not found: {}/{} {}",
+                namespace, provider_name, version
+            );
+            return Err(RegistryError::ProviderNotFound {
+                provider: provider_name.to_string(),
+                namespace: namespace.to_string(),
+            });
+        }
+
+        if status == 429 {
+            warn!("Rate limit exceeded for provider docs listing");
+            return Err(RegistryError::RateLimited);
+        }
+
+        if !status.is_success() {
+            error!("HTTP error {} for provider docs URL: {}", status, url);
+            return Err(RegistryError::HttpError(format!("HTTP {}", status)));
+        }
+
+        let json_value: Value = response.json().await?;
+        let docs = json_value
+            .get("docs")
+            .and_then(|v| v.as_array())
+            .map(|docs| self.extract_docs_from_array(docs))
+            .ok_or(RegistryError::InvalidResponse)?;
+
+        info!(
+            "Listed {} docs for {}/{} {}",
+            docs.len(),
+            namespace,
+            provider_name,
+            version
+        );
+        Ok(docs)
+    }
+
     /// Get provider documentation content by ID with multiple endpoint patterns
     pub async fn get_doc_content(&self, doc_id: &str) -> Result<String, RegistryError> {
         debug!("Fetching documentation content for ID: {}", doc_id);
 
         // Try multiple URL patterns for documentation content
         let url_patterns = [
-            format!("{}/v1/docs/{}", self.base_url, doc_id),
-            format!("{}/v2/docs/{}", self.base_url, doc_id),
-            format!("{}/docs/{}", self.base_url, doc_id),
-            format!("{}/documentation/{}", self.base_url, doc_id),
+            (
+                format!("{}/v2/provider-docs/{}", self.base_url, doc_id),
+                DocBody::JsonApi,
+            ),
+            (
+                format!("{}/v1/docs/{}", self.base_url, doc_id),
+                DocBody::Markdown,
+            ),
+            (
+                format!("{}/v2/docs/{}", self.base_url, doc_id),
+                DocBody::Markdown,
+            ),
+            (
+                format!("{}/docs/{}", self.base_url, doc_id),
+                DocBody::Markdown,
+            ),
+            (
+                format!("{}/documentation/{}", self.base_url, doc_id),
+                DocBody::Markdown,
+            ),
         ];
 
-        for (idx, url) in url_patterns.iter().enumerate() {
+        for (idx, (url, body)) in url_patterns.iter().enumerate() {
             debug!(
                 "Trying documentation URL pattern {}/{}: {}",
                 idx + 1,
@@ -1078,7 +1499,7 @@ impl RegistryClient {
                 url
             );
 
-            let response = self.client.get(url).send().await?;
+            let response = self.send(self.client.get(url)).await?;
             let status = response.status();
 
             debug!("Response status: {} for docs URL: {}", status, url);
@@ -1102,7 +1523,17 @@ impl RegistryClient {
                 continue;
             }
 
-            let content = response.text().await?;
+            let text = response.text().await?;
+            let content = match body {
+                DocBody::Markdown => text,
+                DocBody::JsonApi => match doc_content_from_json(&text) {
+                    Some(content) => content,
+                    None => {
+                        debug!("No data.attributes.content in response from {}", url);
+                        continue;
+                    }
+                },
+            };
             debug!(
                 "Retrieved documentation content ({} chars) for ID: {}",
                 content.len(),
@@ -1114,7 +1545,7 @@ impl RegistryClient {
                     "Successfully retrieved documentation content for ID: {}",
                     doc_id
                 );
-                return Ok(content);
+                return Ok(truncate_body(content, self.max_doc_bytes));
             }
         }
 
@@ -1127,18 +1558,91 @@ impl RegistryClient {
         })
     }
 
+    /// Fetch the content of each of `docs` concurrently (at most
+    /// [`MAX_CONCURRENT_DOC_FETCHES`] at once), keeping their order. A doc
+    /// whose content can't be fetched carries `content_error` instead of
+    /// failing the whole batch.
+    pub async fn fetch_doc_contents(&self, docs: Vec<DocIdResult>) -> Vec<DocWithContent> {
+        stream::iter(docs)
+            .map(|doc| async move {
+                match self.get_doc_content(&doc.id).await {
+                    Ok(content) => DocWithContent {
+                        doc,
+                        content: Some(content),
+                        content_error: None,
+                    },
+                    Err(e) => {
+                        warn!("Failed to fetch content for doc {}: {}", doc.id, e);
+                        DocWithContent {
+                            doc,
+                            content: None,
+                            content_error: Some(e.to_string()),
+                        }
+                    }
+                }
+            })
+            .buffered(MAX_CONCURRENT_DOC_FETCHES)
+            .collect()
+            .await
+    }
+
     // ==================== Module API Methods ====================
 
     /// Search for modules in the Terraform Registry
+    /// Search modules with the v2 API for its richer metadata, falling back
+    /// to the v1 search when v2 fails or finds nothing
     pub async fn search_modules(&self, query: &str) -> Result<Vec<ModuleInfo>, RegistryError> {
+        match self.search_modules_v2(query).await {
+            Ok(modules) if !modules.is_empty() => {
+                info!("Found {} modules for query: {} (v2)", modules.len(), query);
+                return Ok(modules);
+            }
+            Ok(_) => debug!("v2 module search found nothing for '{}', trying v1", query),
+            Err(e) => warn!("v2 module search failed, falling back to v1: {}", e),
+        }
+        self.search_modules_v1(query).await
+    }
+
+    /// Search modules with `/v2/modules`, reading each module's latest
+    /// version from the included `module-versions`
+    async fn search_modules_v2(&self, query: &str) -> Result<Vec<ModuleInfo>, RegistryError> {
+        let url = format!("{}/v2/modules", self.base_url);
+        debug!("Searching modules with query '{}' at URL: {}", query, url);
+
+        let response = self
+            .send(self.client.get(&url).query(&[
+                ("filter[query]", query),
+                ("page[size]", "20"),
+                ("include", "latest-version"),
+            ]))
+            .await?;
+        let status = response.status();
+        if status == 429 {
+            return Err(RegistryError::RateLimited);
+        }
+        if !status.is_success() {
+            return Err(RegistryError::HttpError(format!("HTTP {}", status)));
+        }
+
+        let json: Value = serde_json::from_str(&response.text().await?)
+            .map_err(|e| RegistryError::JsonError(format!("Invalid JSON response: {}", e)))?;
+        let data = json
+            .get("data")
+            .and_then(Value::as_array)
+            .ok_or_else(|| RegistryError::JsonError("v2 module search has no data".to_string()))?;
+        Ok(modules_from_v2(data, json.get("included")))
+    }
+
+    async fn search_modules_v1(&self, query: &str) -> Result<Vec<ModuleInfo>, RegistryError> {
         let url = format!("{}/v1/modules/search", self.base_url);
         debug!("Searching modules with query '{}' at URL: {}", query, url);
 
         let response = self
-            .client
-            .get(&url)
-            .query(&[("q", query), ("limit", "20")])
-            .send()
+            .send(
+                self.client
+                    .get(&url)
+                    .query(&[("q", query), ("limit", "20")]),
+            )
             .await?;
         let status = response.status();
 
@@ -1276,6 +1780,8 @@ impl RegistryClient {
                         .and_then(|v| v.as_str())
                         .unwrap_or("")
                         .to_string(),
+                    latest_version: None,
+                    provider_logo_url: None,
                     extra: HashMap::new(),
                 })
             })
@@ -1303,7 +1809,7 @@ impl RegistryClient {
 
         debug!("Fetching module details from URL: {}", url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         let status = response.status();
 
         debug!("Module details response status: {}", status);
@@ -1398,6 +1904,7 @@ impl RegistryClient {
                                 .unwrap_or(false),
                             root: None,
                             submodules: vec![],
+                            examples: vec![],
                             versions: vec![],
                             extra: HashMap::new(),
                         };
@@ -1431,7 +1938,7 @@ impl RegistryClient {
 
         debug!("Fetching module versions from URL: {}", url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         let status = response.status();
 
         debug!("Module versions response status: {}", status);
@@ -1552,4 +2059,720 @@ impl RegistryClient {
             Ok(details.version)
         }
     }
+
+    /// Path, interface counts and resources of each submodule of a module
+    pub async fn list_module_submodules(
+        &self,
+        namespace: &str,
+        name: &str,
+        provider: &str,
+        version: Option<&str>,
+    ) -> Result<ModuleSubmodules, RegistryError> {
+        let details = self
+            .get_module_details(namespace, name, provider, version)
+            .await?;
+        let submodules = details.submodule_summaries();
+        Ok(ModuleSubmodules {
+            module_id: format!("{}/{}/{}", namespace, name, provider),
+            version: details.version,
+            submodule_count: submodules.len(),
+            submodules,
+        })
+    }
+
+    /// Examples of a module with their README and, for GitHub sources, their
+    /// `main.tf`; a failed `main.tf` fetch leaves it out rather than failing
+    pub async fn list_module_examples(
+        &self,
+        namespace: &str,
+        name: &str,
+        provider: &str,
+        version: Option<&str>,
+    ) -> Result<ModuleExamples, RegistryError> {
+        let details = self
+            .get_module_details(namespace, name, provider, version)
+            .await?;
+        let module_id = format!("{}/{}/{}", namespace, name, provider);
+        let mut examples = details.example_listing();
+        for example in &mut examples {
+            example.readme = example
+                .readme
+                .take()
+                .map(|readme| truncate_body(readme, self.max_doc_bytes));
+            let file = format!("{}/main.tf", example.path.trim_matches('/'));
+            match self
+                .get_repository_file(&details.source, &details.version, &file)
+                .await
+            {
+                Ok(main_tf) => {
+                    example.main_tf = main_tf.map(|body| truncate_body(body, self.max_doc_bytes))
+                }
+                Err(e) => warn!("Failed to fetch {} for {}: {}", file, module_id, e),
+            }
+        }
+
+        Ok(ModuleExamples {
+            message: examples
+                .is_empty()
+                .then(|| format!("{} publishes no examples", module_id)),
+            module_id,
+            version: details.version,
+            example_count: examples.len(),
+            examples,
+        })
+    }
+
+    /// README of a module (or a named submodule), falling back to the README in
+    /// the module's GitHub repository when the registry copy is empty
+    pub async fn get_module_readme(
+        &self,
+        namespace: &str,
+        name: &str,
+        provider: &str,
+        version: Option<&str>,
+        submodule: Option<&str>,
+    ) -> Result<ModuleReadme, RegistryError> {
+        let details = self
+            .get_module_details(namespace, name, provider, version)
+            .await?;
+        let module_id = format!("{}/{}/{}", namespace, name, provider);
+        let mut result = ModuleReadme {
+            module_id: module_id.clone(),
+            version: details.version.clone(),
+            submodule: submodule.map(str::to_string),
+            source: ReadmeSource::NotFound,
+            readme: None,
+            message: None,
+        };
+
+        if let Some(readme) = details.readme(submodule)? {
+            result.source = ReadmeSource::Registry;
+            result.readme = Some(truncate_body(readme.to_string(), self.max_doc_bytes));
+            return Ok(result);
+        }
+
+        let path = submodule
+            .and_then(|s| details.find_submodule(s))
+            .map(|s| s.path.trim_matches('/'));
+        match self
+            .get_repository_readme(&details.source, &details.version, path)
+            .await
+        {
+            Ok(Some(readme)) => {
+                result.source = ReadmeSource::Repository;
+                result.readme = Some(truncate_body(readme, self.max_doc_bytes));
+            }
+            Ok(None) => {
+                result.message = Some(format!(
+                    "No README found for {} in the registry or its source repository",
+                    module_id
+                ));
+            }
+            Err(e) => {
+                warn!("Failed to fetch repository README for {}: {}", module_id, e);
+                result.message = Some(format!(
+                    "The registry has no README for {} and fetching it from the source repository failed: {}",
+                    module_id, e
+                ));
+            }
+        }
+        Ok(result)
+    }
+
+    /// README from a GitHub module repository, trying the version tag before the default branch.
+    ///
+    /// Returns `Ok(None)` for non-GitHub sources or when no README exists.
+    async fn get_repository_readme(
+        &self,
+        source: &str,
+        version: &str,
+        path: Option<&str>,
+    ) -> Result<Option<String>, RegistryError> {
+        let file = match path {
+            Some(path) => format!("{}/README.md", path),
+            None => "README.md".to_string(),
+        };
+        self.get_repository_file(source, version, &file).await
+    }
+
+    /// A file from a GitHub module repository, trying the version tag before
+    /// the default branch.
+    ///
+    /// Returns `Ok(None)` for non-GitHub sources or when the file is missing or empty.
+    async fn get_repository_file(
+        &self,
+        source: &str,
+        version: &str,
+        file: &str,
+    ) -> Result<Option<String>, RegistryError> {
+        let Some((owner, repo)) = github_repository(source) else {
+            debug!("Module source '{}' is not a GitHub repository", source);
+            return Ok(None);
+        };
+
+        let mut refs = Vec::new();
+        if !version.is_empty() {
+            refs.push(format!("v{}", version));
+            refs.push(version.to_string());
+        }
+        refs.push("HEAD".to_string());
+
+        for git_ref in refs {
+            let url = format!(
+                "https://raw.githubusercontent.com/{}/{}/{}/{}",
+                owner, repo, git_ref, file
+            );
+            debug!("Fetching repository file from URL: {}", url);
+
+            let response = self.send(self.client.get(&url)).await?;
+            let status = response.status();
+            if status == 404 {
+                continue;
+            }
+            if status == 429 {
+                return Err(RegistryError::RateLimited);
+            }
+            if !status.is_success() {
+                return Err(RegistryError::HttpError(format!("HTTP {}", status)));
+            }
+
+            let body = response.text().await?;
+            if !body.trim().is_empty() {
+                return Ok(Some(body));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Read `TFMCP_MAX_DOC_BYTES`, falling back to [`DEFAULT_MAX_DOC_BYTES`]
+fn max_doc_bytes_from_env() -> usize {
+    match std::env::var("TFMCP_MAX_DOC_BYTES") {
+        Ok(value) => match value.trim().parse::<usize>() {
+            Ok(bytes) if bytes > 0 => bytes,
+            _ => {
+                warn!(
+                    "Invalid TFMCP_MAX_DOC_BYTES '{}', using {}",
+                    value, DEFAULT_MAX_DOC_BYTES
+                );
+                DEFAULT_MAX_DOC_BYTES
+            }
+        },
+        Err(_) => DEFAULT_MAX_DOC_BYTES,
+    }
+}
+
+/// Read `TFMCP_REGISTRY_TIMEOUT_SECS`, falling back to [`DEFAULT_REGISTRY_TIMEOUT`]
+fn registry_timeout_from_env() -> Duration {
+    match std::env::var("TFMCP_REGISTRY_TIMEOUT_SECS") {
+        Ok(value) => match value.trim().parse::<u64>() {
+            Ok(secs) if secs > 0 => Duration::from_secs(secs),
+            _ => {
+                warn!(
+                    "Invalid TFMCP_REGISTRY_TIMEOUT_SECS '{}', using {}s",
+                    value,
+                    DEFAULT_REGISTRY_TIMEOUT.as_secs()
+                );
+                DEFAULT_REGISTRY_TIMEOUT
+            }
+        },
+        Err(_) => DEFAULT_REGISTRY_TIMEOUT,
+    }
+}
+
+/// How a documentation URL returns its content
+#[derive(Debug, Clone, Copy)]
+enum DocBody {
+    /// The response body is the Markdown itself
+    Markdown,
+    /// A JSON:API document carrying the Markdown in `data.attributes.content`
+    JsonApi,
+}
+
+/// Markdown of a v2 `provider-docs` response; `None` when the field is missing or empty
+fn doc_content_from_json(body: &str) -> Option<String> {
+    let json: Value = serde_json::from_str(body).ok()?;
+    json.pointer("/data/attributes/content")
+        .and_then(Value::as_str)
+        .filter(|content| !content.trim().is_empty())
+        .map(str::to_string)
+}
+
+/// Cut `body` to at most `max_bytes` (on a char boundary) and append a marker
+/// with the original size, so callers can tell the content is incomplete
+pub fn truncate_body(mut body: String, max_bytes: usize) -> String {
+    if body.len() <= max_bytes {
+        return body;
+    }
+    let original = body.len();
+    let mut end = max_bytes;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    body.truncate(end);
+    debug!("Truncated response body from {} to {} bytes", original, end);
+    body.push_str(&format!(
+        "\n\n[truncated: showing {} of {} bytes]",
+        end, original
+    ));
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::test_server::serve;
+
+    fn synthetic_module() -> ModuleDetails {
+        ModuleDetails {
+            id: "terraform-aws-modules/vpc/aws/5.0.0".to_string(),
+            version: "5.0.0".to_string(),
+            source: "https://github.com/terraform-aws-modules/terraform-aws-vpc".to_string(),
+            root: Some(ModuleRoot {
+                readme: "# AWS VPC Terraform module\n".to_string(),
+                ..Default::default()
+            }),
+            submodules: vec![
+                ModuleSubmodule {
+                    path: "modules/vpc-endpoints".to_string(),
+                    name: "vpc-endpoints".to_string(),
+                    readme: "# VPC endpoints\n".to_string(),
+                    inputs: vec![
+                        ModuleInput {
+                            name: "vpc_id".to_string(),
+                            required: true,
+                            ..Default::default()
+                        },
+                        ModuleInput {
+                            name: "endpoints".to_string(),
+                            default: Some(serde_json::json!({})),
+                            ..Default::default()
+                        },
+                    ],
+                    outputs: vec![ModuleOutput {
+                        name: "endpoints".to_string(),
+                        ..Default::default()
+                    }],
+                    resources: vec![
+                        ModuleResource {
+                            name: "this".to_string(),
+                            resource_type: "aws_vpc_endpoint".to_string(),
+                            ..Default::default()
+                        },
+                        ModuleResource {
+                            name: "this".to_string(),
+                            resource_type: "aws_security_group".to_string(),
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                },
+                ModuleSubmodule {
+                    path: "modules/empty".to_string(),
+                    name: "empty".to_string(),
+                    readme: "  \n".to_string(),
+                    ..Default::default()
+                },
+            ],
+            examples: vec![
+                ModuleSubmodule {
+                    path: "examples/complete".to_string(),
+                    name: "complete".to_string(),
+                    readme: "# Complete VPC\n".to_string(),
+                    ..Default::default()
+                },
+                ModuleSubmodule {
+                    path: "examples/simple".to_string(),
+                    name: "simple".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_module_readme_from_details() {
+        let details = synthetic_module();
+
+        assert_eq!(
+            details.readme(None).unwrap(),
+            Some("# AWS VPC Terraform module\n")
+        );
+        assert_eq!(
+            details.readme(Some("vpc-endpoints")).unwrap(),
+            Some("# VPC endpoints\n")
+        );
+        assert_eq!(
+            details.readme(Some("modules/vpc-endpoints")).unwrap(),
+            Some("# VPC endpoints\n")
+        );
+        // Whitespace-only READMEs count as missing so the repository fallback kicks in
+        assert_eq!(details.readme(Some("empty")).unwrap(), None);
+        assert!(matches!(
+            details.readme(Some("nope")),
+            Err(RegistryError::SubmoduleNotFound { .. })
+        ));
+        assert_eq!(ModuleDetails::default().readme(None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_submodule_summaries() {
+        let summaries = synthetic_module().submodule_summaries();
+        assert_eq!(
+            summaries,
+            vec![
+                SubmoduleSummary {
+                    name: "vpc-endpoints".to_string(),
+                    path: "modules/vpc-endpoints".to_string(),
+                    input_count: 2,
+                    required_input_count: 1,
+                    output_count: 1,
+                    resources: vec![
+                        "aws_vpc_endpoint.this".to_string(),
+                        "aws_security_group.this".to_string(),
+                    ],
+                    has_readme: true,
+                },
+                SubmoduleSummary {
+                    name: "empty".to_string(),
+                    path: "modules/empty".to_string(),
+                    input_count: 0,
+                    required_input_count: 0,
+                    output_count: 0,
+                    resources: Vec::new(),
+                    has_readme: false,
+                },
+            ]
+        );
+        assert!(ModuleDetails::default().submodule_summaries().is_empty());
+    }
+
+    #[test]
+    fn test_example_listing() {
+        let examples = synthetic_module().example_listing();
+        assert_eq!(
+            examples,
+            vec![
+                ModuleExample {
+                    name: "complete".to_string(),
+                    path: "examples/complete".to_string(),
+                    readme: Some("# Complete VPC\n".to_string()),
+                    main_tf: None,
+                },
+                ModuleExample {
+                    name: "simple".to_string(),
+                    path: "examples/simple".to_string(),
+                    readme: None,
+                    main_tf: None,
+                },
+            ]
+        );
+        assert!(ModuleDetails::default().example_listing().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_module_examples_without_examples() {
+        let base_url = serve(|path| match path {
+            "/v1/modules/acme/network/aws" => (
+                200,
+                serde_json::json!({
+                    "id": "acme/network/aws/1.0.0",
+                    "namespace": "acme",
+                    "name": "network",
+                    "provider": "aws",
+                    "version": "1.0.0",
+                    "source": "https://gitlab.com/acme/network"
+                })
+                .to_string(),
+            ),
+            _ => (404, String::new()),
+        })
+        .await
+        .base_url;
+        let client = RegistryClient::new().with_base_url(base_url);
+
+        let examples = client
+            .list_module_examples("acme", "network", "aws", None)
+            .await
+            .unwrap();
+        assert_eq!(examples.example_count, 0);
+        assert_eq!(examples.version, "1.0.0");
+        assert_eq!(
+            examples.message.as_deref(),
+            Some("acme/network/aws publishes no examples")
+        );
+    }
+
+    #[test]
+    fn test_github_repository_from_source() {
+        assert_eq!(
+            github_repository("https://github.com/terraform-aws-modules/terraform-aws-vpc"),
+            Some((
+                "terraform-aws-modules".to_string(),
+                "terraform-aws-vpc".to_string()
+            ))
+        );
+        assert_eq!(
+            github_repository("git::https://github.com/example/network.git?ref=v1.0.0"),
+            Some(("example".to_string(), "network".to_string()))
+        );
+        assert_eq!(
+            github_repository("https://gitlab.com/example/network"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_truncate_oversized_body() {
+        let small = "# README\n".to_string();
+        assert_eq!(truncate_body(small.clone(), DEFAULT_MAX_DOC_BYTES), small);
+
+        // 300 KB of multi-byte text, cut inside a character
+        let body = "é".repeat(150 * 1024);
+        assert_eq!(body.len(), 300 * 1024);
+        let truncated = truncate_body(body, 1001);
+        let (kept, marker) = truncated.split_once("\n\n[truncated").unwrap();
+        assert_eq!(kept.len(), 1000);
+        assert_eq!(marker, ": showing 1000 of 307200 bytes]");
+
+        let truncated = truncate_body("x".repeat(DEFAULT_MAX_DOC_BYTES + 1), DEFAULT_MAX_DOC_BYTES);
+        assert!(truncated.starts_with(&"x".repeat(DEFAULT_MAX_DOC_BYTES)));
+        assert!(truncated.ends_with("[truncated: showing 262144 of 262145 bytes]"));
+    }
+
+    #[test]
+    fn test_visible_ascii_strips_header_breaking_characters() {
+        assert_eq!(visible_ascii("claude-desktop"), "claude-desktop");
+        assert_eq!(visible_ascii("evil\r\nX-Injected: 1"), "evilX-Injected:1");
+        assert_eq!(visible_ascii("クライアント 1.0\t"), "1.0");
+        assert_eq!(visible_ascii("\u{7f}"), "");
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_names_version_and_mcp_client() {
+        let tfmcp = format!("tfmcp/{}", env!("CARGO_PKG_VERSION"));
+        assert!(user_agent().starts_with(&tfmcp));
+
+        let server = serve(|_| (200, r#"{"providers":[]}"#.to_string())).await;
+
+        // Built before the MCP client connects, as the server does
+        let client = RegistryClient::new().with_base_url(&server.base_url);
+        set_mcp_client("claude-desktop", "1.2");
+        assert_eq!(user_agent(), format!("{} (claude-desktop/1.2)", tfmcp));
+
+        let _ = client.search_providers("aws").await;
+        let request = server.requests()[0].to_lowercase();
+        assert!(
+            request.contains(&format!("user-agent: {} (claude-desktop/1.2)", tfmcp)),
+            "unexpected request: {}",
+            request
+        );
+    }
+
+    #[tokio::test]
+    async fn test_doc_content_from_v2_provider_docs_json() {
+        let base_url = serve(|path| match path {
+            "/v2/provider-docs/8814952" => (
+                200,
+                serde_json::json!({
+                    "data": {
+                        "type": "provider-docs",
+                        "id": "8814952",
+                        "attributes": {
+                            "category": "resources",
+                            "slug": "s3_bucket",
+                            "content": "# Resource: aws_s3_bucket\n\nProvides a S3 bucket resource."
+                        }
+                    }
+                })
+                .to_string(),
+            ),
+            "/v2/provider-docs/empty" => {
+                (200, r#"{"data":{"attributes":{"content":""}}}"#.to_string())
+            }
+            _ => (404, String::new()),
+        })
+        .await
+        .base_url;
+        let client = RegistryClient::new().with_base_url(base_url);
+
+        let content = client.get_doc_content("8814952").await.unwrap();
+        assert_eq!(
+            content,
+            "# Resource: aws_s3_bucket\n\nProvides a S3 bucket resource."
+        );
+
+        let missing = client.get_doc_content("empty").await;
+        assert!(
+            matches!(missing, Err(RegistryError::DocumentationNotFound { .. })),
+            "expected DocumentationNotFound, got {:?}",
+            missing
+        );
+    }
+
+    #[tokio::test]
+    async fn test_doc_contents_keep_order_and_per_doc_errors() {
+        let base_url = serve(|path| match path.split('?').next().unwrap_or(path) {
+            "/v1/providers/hashicorp/aws/docs" => (
+                200,
+                serde_json::json!({
+                    "data": [
+                        {"id": "101", "title": "s3_bucket", "category": "resources"},
+                        {"id": "102", "title": "s3_bucket_policy", "category": "resources"},
+                        {"id": "103", "title": "s3_bucket_acl", "category": "resources"}
+                    ]
+                })
+                .to_string(),
+            ),
+            "/v1/docs/101" => (200, "# aws_s3_bucket".to_string()),
+            "/v1/docs/103" => (200, "# aws_s3_bucket_acl".to_string()),
+            _ => (404, String::new()),
+        })
+        .await
+        .base_url;
+        let client = RegistryClient::new().with_base_url(base_url);
+
+        let docs = client
+            .search_docs("aws", "hashicorp", "s3_bucket", "resources")
+            .await
+            .unwrap();
+        let docs = client.fetch_doc_contents(docs).await;
+        let ids: Vec<_> = docs.iter().map(|d| d.doc.id.as_str()).collect();
+        assert_eq!(ids, ["101", "102", "103"]);
+        assert_eq!(docs[0].content.as_deref(), Some("# aws_s3_bucket"));
+        assert!(docs[0].content_error.is_none());
+        assert!(docs[1].content.is_none());
+        assert!(
+            docs[1]
+                .content_error
+                .as_deref()
+                .is_some_and(|e| e.contains("102"))
+        );
+        assert_eq!(docs[2].content.as_deref(), Some("# aws_s3_bucket_acl"));
+
+        let json = serde_json::to_value(&docs[1]).unwrap();
+        assert_eq!(json["title"], "s3_bucket_policy");
+        assert!(json.get("content").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_module_search_uses_v2_fields() {
+        let base_url = serve(|path| match path.split('?').next().unwrap_or(path) {
+            "/v2/modules" => (
+                200,
+                serde_json::json!({
+                    "data": [{
+                        "type": "modules",
+                        "id": "1",
+                        "attributes": {
+                            "namespace": "terraform-aws-modules",
+                            "name": "vpc",
+                            "provider-name": "aws",
+                            "description": "Terraform module to create AWS VPC resources",
+                            "source": "https://github.com/terraform-aws-modules/terraform-aws-vpc",
+                            "downloads": 123456,
+                            "verified": true,
+                            "owner-name": "antonbabenko",
+                            "provider-logo-url": "/images/providers/aws.png"
+                        },
+                        "relationships": {
+                            "latest-version": {"data": {"type": "module-versions", "id": "99"}}
+                        }
+                    }],
+                    "included": [{
+                        "type": "module-versions",
+                        "id": "99",
+                        "attributes": {"version": "5.8.1", "published-at": "2024-05-01T00:00:00Z"}
+                    }]
+                })
+                .to_string(),
+            ),
+            _ => (404, String::new()),
+        })
+        .await
+        .base_url;
+        let client = RegistryClient::new().with_base_url(base_url);
+
+        let modules = client.search_modules("vpc").await.unwrap();
+        assert_eq!(modules.len(), 1);
+        let vpc = &modules[0];
+        assert_eq!(vpc.id, "terraform-aws-modules/vpc/aws/5.8.1");
+        assert_eq!(vpc.provider, "aws");
+        assert_eq!(vpc.version, "5.8.1");
+        assert_eq!(vpc.latest_version.as_deref(), Some("5.8.1"));
+        assert_eq!(vpc.published_at, "2024-05-01T00:00:00Z");
+        assert_eq!(
+            vpc.provider_logo_url.as_deref(),
+            Some("/images/providers/aws.png")
+        );
+        assert!(vpc.verified);
+        assert_eq!(vpc.downloads, 123456);
+    }
+
+    #[tokio::test]
+    async fn test_module_search_falls_back_to_v1() {
+        let base_url = serve(|path| match path.split('?').next().unwrap_or(path) {
+            "/v2/modules" => (500, String::new()),
+            "/v1/modules/search" => (
+                200,
+                serde_json::json!({
+                    "meta": {"limit": 20, "current_offset": 0},
+                    "modules": [{
+                        "id": "terraform-aws-modules/vpc/aws/5.8.1",
+                        "namespace": "terraform-aws-modules",
+                        "name": "vpc",
+                        "provider": "aws",
+                        "version": "5.8.1",
+                        "verified": true,
+                        "downloads": 42
+                    }]
+                })
+                .to_string(),
+            ),
+            _ => (404, String::new()),
+        })
+        .await
+        .base_url;
+        let client = RegistryClient::new().with_base_url(base_url);
+
+        let modules = client.search_modules("vpc").await.unwrap();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].id, "terraform-aws-modules/vpc/aws/5.8.1");
+        assert_eq!(modules[0].downloads, 42);
+        assert!(modules[0].latest_version.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slow_registry_times_out() {
+        // Accepts connections and reads the request but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf).await;
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                });
+            }
+        });
+
+        let client = RegistryClient::with_timeout(Duration::from_millis(200))
+            .with_base_url(format!("http://{}", addr));
+        let started = std::time::Instant::now();
+        let result = client.search_providers("aws").await;
+
+        assert!(
+            matches!(result, Err(RegistryError::HttpError(_))),
+            "expected HttpError, got {:?}",
+            result.map(|providers| providers.len())
+        );
+        assert!(
+            started.elapsed() < Duration::from_secs(2),
+            "timed out after {:?}",
+            started.elapsed()
+        );
+    }
 }
@@ -0,0 +1,192 @@
+//! Free-text search over a provider's documentation index.
+//!
+//! The registry only looks docs up by exact slug, so users who don't know a
+//! resource's name can search titles and descriptions here and then fetch the
+//! matching doc by id.
+
+use crate::registry::client::DocIdResult;
+use serde::{Deserialize, Serialize};
+
+/// Doc categories included in the search index
+pub const SEARCHABLE_DOC_CATEGORIES: &[&str] = &["resources", "data-sources"];
+
+/// Matches returned when the caller does not set a limit
+pub const DEFAULT_DOC_SEARCH_LIMIT: usize = 20;
+
+/// A doc entry matching a search query, with its relevance score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocSearchMatch {
+    pub id: String,
+    pub title: String,
+    pub category: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subcategory: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub score: u32,
+}
+
+/// Result of searching one provider version's docs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderDocSearch {
+    pub namespace: String,
+    pub provider: String,
+    pub version: String,
+    pub query: String,
+    /// Resource and data source docs searched
+    pub indexed_docs: usize,
+    pub matches: Vec<DocSearchMatch>,
+}
+
+/// Keep only the resource and data source entries of a provider's docs
+pub fn build_doc_index(docs: Vec<DocIdResult>) -> Vec<DocIdResult> {
+    docs.into_iter()
+        .filter(|doc| SEARCHABLE_DOC_CATEGORIES.contains(&doc.category.as_str()))
+        .collect()
+}
+
+/// Lowercase and treat `_`/`-` as word separators, so "s3 bucket" matches "aws_s3_bucket"
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c == '_' || c == '-' { ' ' } else { c })
+        .collect()
+}
+
+/// Relevance of `doc` for the normalized query; 0 when a term matches nowhere
+fn score_doc(doc: &DocIdResult, query: &str, terms: &[&str]) -> u32 {
+    let title = normalize(&doc.title);
+    let slug = doc.slug.as_deref().map(normalize).unwrap_or_default();
+    let subcategory = doc
+        .subcategory
+        .as_deref()
+        .map(normalize)
+        .unwrap_or_default();
+    let description = normalize(&doc.description);
+
+    let mut score = 0;
+    for term in terms {
+        let mut term_score = 0;
+        if title.split_whitespace().any(|word| word == *term) {
+            term_score += 10;
+        } else if title.contains(term) {
+            term_score += 6;
+        }
+        if subcategory.contains(term) {
+            term_score += 4;
+        }
+        if description.contains(term) {
+            term_score += 2;
+        }
+        if term_score == 0 {
+            return 0;
+        }
+        score += term_score;
+    }
+
+    if title == query || slug == query {
+        score += 100;
+    } else if title.ends_with(query) || slug.ends_with(query) {
+        // "bucket" for "aws_s3_bucket" style titles
+        score += 50;
+    } else if title.contains(query) {
+        score += 25;
+    }
+    score
+}
+
+/// Docs matching every term of `query`, best first (ties by title), at most `limit`
+pub fn rank_docs(docs: &[DocIdResult], query: &str, limit: usize) -> Vec<DocSearchMatch> {
+    let query = normalize(query);
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let query = terms.join(" ");
+
+    let mut matches: Vec<DocSearchMatch> = docs
+        .iter()
+        .filter_map(|doc| {
+            let score = score_doc(doc, &query, &terms);
+            (score > 0).then(|| DocSearchMatch {
+                id: doc.id.clone(),
+                title: doc.title.clone(),
+                category: doc.category.clone(),
+                subcategory: doc.subcategory.clone(),
+                description: doc.description.clone(),
+                score,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+    matches.truncate(limit);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn doc(id: &str, title: &str, category: &str, subcategory: &str) -> DocIdResult {
+        DocIdResult {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: String::new(),
+            category: category.to_string(),
+            slug: Some(title.to_string()),
+            path: None,
+            subcategory: Some(subcategory.to_string()),
+            extra: HashMap::new(),
+        }
+    }
+
+    fn synthetic_docs() -> Vec<DocIdResult> {
+        vec![
+            doc("1", "s3_bucket_policy", "resources", "S3 (Simple Storage)"),
+            doc("2", "s3_bucket", "resources", "S3 (Simple Storage)"),
+            doc("3", "s3_bucket", "data-sources", "S3 (Simple Storage)"),
+            doc("4", "instance", "resources", "EC2 (Elastic Compute Cloud)"),
+            doc("5", "bucket_notification_guide", "guides", "Guides"),
+            doc(
+                "6",
+                "s3_bucket_versioning",
+                "resources",
+                "S3 (Simple Storage)",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_exact_title_ranks_above_partial_matches() {
+        let index = build_doc_index(synthetic_docs());
+        assert_eq!(index.len(), 5, "guides are not indexed");
+
+        let matches = rank_docs(&index, "s3 bucket", 10);
+        let ids: Vec<&str> = matches.iter().map(|m| m.id.as_str()).collect();
+
+        // Exact matches first (resource and data source), then longer titles by name
+        assert_eq!(ids.len(), 4);
+        assert!(ids[..2].contains(&"2") && ids[..2].contains(&"3"));
+        assert_eq!(&ids[2..], &["1", "6"]);
+        assert!(matches[0].score > matches[2].score);
+    }
+
+    #[test]
+    fn test_all_terms_must_match_and_limit_applies() {
+        let index = build_doc_index(synthetic_docs());
+
+        let matches = rank_docs(&index, "bucket versioning", 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, "6");
+
+        // Subcategory text is searchable too
+        let matches = rank_docs(&index, "elastic compute", 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].title, "instance");
+
+        assert_eq!(rank_docs(&index, "bucket", 2).len(), 2);
+        assert!(rank_docs(&index, "   ", 10).is_empty());
+    }
+}
@@ -1,4 +1,6 @@
+use crate::registry::cache::{PROVIDERS_CACHE_TTL, SimpleCache, registry_cache_key};
 use crate::registry::client::{ProviderInfo, RegistryClient, RegistryError};
+use crate::registry::rate_limit::RateLimiter;
 use crate::shared::logging;
 use std::sync::Arc;
 use thiserror::Error;
@@ -15,16 +17,27 @@ pub enum FallbackError {
     RegistryError(#[from] RegistryError),
 }
 
-/// Registry client with intelligent fallback capabilities
+/// Registry client with intelligent fallback capabilities.
+///
+/// Only not-found answers ([`RegistryError::is_not_found`]) move on to the
+/// next namespace; rate limits, timeouts and other HTTP errors are returned
+/// at once, as every namespace is served by the same registry.
 pub struct RegistryClientWithFallback {
     pub primary: Arc<RegistryClient>,
     pub fallback_namespaces: Vec<String>,
+    /// Paces all registry requests; share it with other registry clients
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Latest `(version, namespace)` per requested provider and namespace
+    version_cache: SimpleCache<(String, String)>,
 }
 
 impl RegistryClientWithFallback {
     pub fn new() -> Self {
+        let rate_limiter = Arc::new(RateLimiter::from_env());
         Self {
-            primary: Arc::new(RegistryClient::new()),
+            primary: Arc::new(RegistryClient::new().with_rate_limiter(rate_limiter.clone())),
+            rate_limiter,
+            version_cache: SimpleCache::new(PROVIDERS_CACHE_TTL),
             fallback_namespaces: vec![
                 "hashicorp".to_string(),
                 "terraform-providers".to_string(),
@@ -34,11 +47,27 @@ impl RegistryClientWithFallback {
     }
 
     /// Get provider version with intelligent fallback
-    /// Tries the specified namespace first, then falls back to common namespaces
+    /// Tries the specified namespace first, then falls back to common namespaces.
+    /// Results are cached for [`PROVIDERS_CACHE_TTL`].
     pub async fn get_provider_version(
         &self,
         provider: &str,
         namespace: Option<&str>,
+    ) -> Result<(String, String), FallbackError> {
+        let cache_key =
+            registry_cache_key("version", namespace.unwrap_or("*"), provider, None, &[]);
+        if let Some(cached) = self.version_cache.get(&cache_key).await {
+            return Ok(cached);
+        }
+        let found = self.lookup_provider_version(provider, namespace).await?;
+        self.version_cache.set(cache_key, found.clone()).await;
+        Ok(found)
+    }
+
+    async fn lookup_provider_version(
+        &self,
+        provider: &str,
+        namespace: Option<&str>,
     ) -> Result<(String, String), FallbackError> {
         let mut searched_namespaces = Vec::new();
 
@@ -53,7 +82,7 @@ impl RegistryClientWithFallback {
                     ));
                     return Ok((version, ns.to_string()));
                 }
-                Err(RegistryError::ProviderNotFound { .. }) => {
+                Err(e) if e.is_not_found() => {
                     logging::debug(&format!(
                         "Provider {} not found in specified namespace {}, trying fallbacks",
                         provider, ns
@@ -79,7 +108,7 @@ impl RegistryClientWithFallback {
                     ));
                     return Ok((version, fallback_ns.clone()));
                 }
-                Err(RegistryError::ProviderNotFound { .. }) => {
+                Err(e) if e.is_not_found() => {
                     logging::debug(&format!(
                         "Provider {} not found in fallback namespace {}",
                         provider, fallback_ns
@@ -115,7 +144,7 @@ impl RegistryClientWithFallback {
                     ));
                     return Ok(info);
                 }
-                Err(RegistryError::ProviderNotFound { .. }) => {
+                Err(e) if e.is_not_found() => {
                     logging::debug(&format!(
                         "Provider {} not found in specified namespace {}, trying fallbacks",
                         provider, ns
@@ -141,7 +170,7 @@ impl RegistryClientWithFallback {
                     ));
                     return Ok(info);
                 }
-                Err(RegistryError::ProviderNotFound { .. }) => {
+                Err(e) if e.is_not_found() => {
                     logging::debug(&format!(
                         "Provider {} not found in fallback namespace {}",
                         provider, fallback_ns
@@ -184,13 +213,13 @@ impl RegistryClientWithFallback {
                     ));
                     return Ok((docs, ns.to_string()));
                 }
-                Ok(_) => {
+                Err(e) if !e.is_not_found() => return Err(FallbackError::RegistryError(e)),
+                _ => {
                     logging::debug(&format!(
                         "No documentation found for {} in specified namespace {}, trying fallbacks",
                         provider, ns
                     ));
                 }
-                Err(e) => return Err(FallbackError::RegistryError(e)),
             }
         }
 
@@ -214,14 +243,14 @@ impl RegistryClientWithFallback {
                     ));
                     return Ok((docs, fallback_ns.clone()));
                 }
-                Ok(_) => {
+                Err(e) if !e.is_not_found() => return Err(FallbackError::RegistryError(e)),
+                _ => {
                     logging::debug(&format!(
                         "No documentation found for {} in fallback namespace {}",
                         provider, fallback_ns
                     ));
                     continue;
                 }
-                Err(e) => return Err(FallbackError::RegistryError(e)),
             }
         }
 
@@ -243,6 +272,7 @@ impl Default for RegistryClientWithFallback {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::registry::test_server::serve;
 
     #[tokio::test]
     async fn test_fallback_client_creation() {
@@ -250,4 +280,138 @@ mod tests {
         // Just test that client creates successfully
         assert_eq!(client.fallback_namespaces.len(), 3);
     }
+
+    fn client_for(base_url: &str) -> RegistryClientWithFallback {
+        RegistryClientWithFallback {
+            primary: Arc::new(RegistryClient::new().with_base_url(base_url)),
+            ..RegistryClientWithFallback::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_not_found_falls_back_to_next_namespace() {
+        let server = serve(|path| match path {
+            "/v1/providers/community/widget/versions" => {
+                (200, r#"{"versions":[{"version":"1.2.0"}]}"#.to_string())
+            }
+            _ => (404, String::new()),
+        })
+        .await;
+        let client = client_for(&server.base_url);
+
+        let found = client
+            .get_provider_version("widget", Some("acme"))
+            .await
+            .unwrap();
+        assert_eq!(found, ("1.2.0".to_string(), "community".to_string()));
+        assert_eq!(
+            server.paths(),
+            [
+                "/v1/providers/acme/widget/versions",
+                "/v1/providers/hashicorp/widget/versions",
+                "/v1/providers/terraform-providers/widget/versions",
+                "/v1/providers/community/widget/versions",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_is_not_retried_across_namespaces() {
+        let server = serve(|_| (429, String::new())).await;
+        let client = client_for(&server.base_url);
+
+        let error = client
+            .get_provider_version("widget", Some("acme"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            FallbackError::RegistryError(RegistryError::RateLimited)
+        ));
+        let error = client.get_provider_info("widget", None).await.unwrap_err();
+        assert!(matches!(
+            error,
+            FallbackError::RegistryError(RegistryError::RateLimited)
+        ));
+        assert_eq!(
+            server.paths(),
+            [
+                "/v1/providers/acme/widget/versions",
+                "/v1/providers/hashicorp/widget",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_delays_next_request_to_host() {
+        let server = serve(|path| match path {
+            "/v1/providers/acme/widget/versions" => (429, String::new()),
+            _ => (200, r#"{"versions":[{"version":"1.2.0"}]}"#.to_string()),
+        })
+        .await;
+        let rate_limiter = Arc::new(RateLimiter::new(1000.0));
+        let client = RegistryClientWithFallback {
+            primary: Arc::new(
+                RegistryClient::new()
+                    .with_base_url(&server.base_url)
+                    .with_rate_limiter(rate_limiter.clone()),
+            ),
+            rate_limiter,
+            ..RegistryClientWithFallback::new()
+        };
+
+        let error = client
+            .get_provider_version("widget", Some("acme"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            FallbackError::RegistryError(RegistryError::RateLimited)
+        ));
+
+        // The 429 asked for a one second pause before the host is tried again
+        let start = std::time::Instant::now();
+        let found = client
+            .get_provider_version("widget", Some("hashicorp"))
+            .await
+            .unwrap();
+        assert_eq!(found.1, "hashicorp");
+        assert!(start.elapsed() >= std::time::Duration::from_millis(900));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_server_error_is_not_retried_across_namespaces() {
+        let server = serve(|_| (503, String::new())).await;
+        let client = client_for(&server.base_url);
+
+        let error = client
+            .get_provider_version("widget", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            FallbackError::RegistryError(RegistryError::HttpError(_))
+        ));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_not_found_classification() {
+        let not_found = RegistryError::ProviderNotFound {
+            provider: "widget".to_string(),
+            namespace: "acme".to_string(),
+        };
+        assert!(not_found.is_not_found());
+        assert!(
+            RegistryError::NoVersionsAvailable {
+                provider: "widget".to_string(),
+                namespace: "acme".to_string(),
+            }
+            .is_not_found()
+        );
+        assert!(!RegistryError::RateLimited.is_not_found());
+        assert!(!RegistryError::HttpError("operation timed out".to_string()).is_not_found());
+        assert!(!RegistryError::InvalidResponse.is_not_found());
+    }
 }
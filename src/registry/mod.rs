@@ -1,6 +1,10 @@
 pub mod batch;
 pub mod cache;
 pub mod client;
+pub mod doc_search;
 pub mod fallback;
 pub mod policy;
 pub mod provider;
+pub mod rate_limit;
+#[cfg(test)]
+mod test_server;
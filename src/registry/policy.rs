@@ -3,9 +3,12 @@
 //! Provides search and detail lookup for Sentinel/OPA policy libraries
 //! from the public Terraform Registry.
 
-use reqwest::Client;
+use crate::registry::client::user_agent;
+use crate::registry::rate_limit::RateLimiter;
+use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, warn};
 
@@ -29,6 +32,7 @@ pub struct PolicyInfo {
 /// Client for the Terraform Registry Policy API.
 pub struct PolicyClient {
     client: Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl Default for PolicyClient {
@@ -40,10 +44,30 @@ impl Default for PolicyClient {
 impl PolicyClient {
     pub fn new() -> Self {
         let client = Client::builder()
+            .user_agent(user_agent())
             .timeout(REQUEST_TIMEOUT)
             .build()
             .unwrap_or_default();
-        Self { client }
+        Self {
+            client,
+            rate_limiter: None,
+        }
+    }
+
+    /// Pace every request from this client through a shared limiter
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let request = request
+            .header(reqwest::header::USER_AGENT, user_agent())
+            .build()?;
+        match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.execute(&self.client, request).await,
+            None => self.client.execute(request).await,
+        }
     }
 
     /// Search for policy libraries in the public registry.
@@ -59,7 +83,7 @@ impl PolicyClient {
 
         debug!("Searching policies: {}", url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         if !response.status().is_success() {
             warn!("Policy search failed: HTTP {}", response.status());
             return Ok(Vec::new());
@@ -109,7 +133,7 @@ impl PolicyClient {
         let url = format!("{}/v2/policies/{}/{}", REGISTRY_BASE, namespace, name);
         debug!("Fetching policy details: {}", url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
                 "Policy not found: {}/{} (HTTP {})",
@@ -1,5 +1,6 @@
-use crate::registry::cache::CacheManager;
+use crate::registry::cache::{CacheManager, registry_cache_key};
 use crate::registry::client::{DocIdResult, ProviderInfo, RegistryClient, RegistryError};
+use crate::registry::doc_search::{ProviderDocSearch, build_doc_index, rank_docs};
 use crate::shared::logging;
 use std::sync::Arc;
 
@@ -18,8 +19,15 @@ impl ProviderResolver {
         }
     }
 
+    /// Resolver issuing requests through an existing (e.g. rate-limited) client
+    pub fn with_client(client: Arc<RegistryClient>) -> Self {
+        Self {
+            client,
+            cache: Arc::new(CacheManager::new()),
+        }
+    }
+
     /// Stage 1: Resolve provider documentation IDs
-    #[allow(dead_code)]
     pub async fn resolve_provider_doc_id(
         &self,
         provider_name: &str,
@@ -27,9 +35,12 @@ impl ProviderResolver {
         service_slug: &str,
         data_type: Option<&str>,
     ) -> Result<Vec<DocIdResult>, RegistryError> {
-        let cache_key = format!(
-            "docids:{}:{}:{}",
-            provider_namespace, provider_name, service_slug
+        let cache_key = registry_cache_key(
+            "docids",
+            provider_namespace,
+            provider_name,
+            None,
+            &[data_type.unwrap_or("resources"), service_slug],
         );
 
         logging::debug(&format!(
@@ -76,9 +87,8 @@ impl ProviderResolver {
     }
 
     /// Stage 2: Get provider documentation content by ID
-    #[allow(dead_code)]
     pub async fn get_provider_docs(&self, doc_id: &str) -> Result<String, RegistryError> {
-        let cache_key = format!("doc:{}", doc_id);
+        let cache_key = registry_cache_key("doc", "", "", None, &[doc_id]);
 
         logging::debug(&format!(
             "Fetching documentation content for ID: {}",
@@ -111,7 +121,7 @@ impl ProviderResolver {
 
     /// Search providers with intelligent caching
     pub async fn search_providers(&self, query: &str) -> Result<Vec<ProviderInfo>, RegistryError> {
-        let cache_key = format!("search:{}", query);
+        let cache_key = registry_cache_key("search", "", "", None, &[query]);
 
         logging::debug(&format!("Searching providers with query: {}", query));
 
@@ -147,7 +157,7 @@ impl ProviderResolver {
         provider_name: &str,
         namespace: &str,
     ) -> Result<ProviderInfo, RegistryError> {
-        let cache_key = format!("info:{}:{}", namespace, provider_name);
+        let cache_key = registry_cache_key("info", namespace, provider_name, None, &[]);
 
         logging::debug(&format!(
             "Getting provider info for {}/{}",
@@ -183,6 +193,80 @@ impl ProviderResolver {
 
         Ok(info)
     }
+
+    /// Resource and data source doc entries of a provider version (latest
+    /// when `version` is `None`), with the resolved version. The index is
+    /// cached per provider version.
+    pub async fn provider_doc_index(
+        &self,
+        provider_name: &str,
+        namespace: &str,
+        version: Option<&str>,
+    ) -> Result<(String, Vec<DocIdResult>), RegistryError> {
+        let version = match version {
+            Some(version) => version.to_string(),
+            None => {
+                self.client
+                    .get_latest_version(provider_name, namespace)
+                    .await?
+            }
+        };
+        let cache_key =
+            registry_cache_key("docindex", namespace, provider_name, Some(&version), &[]);
+
+        let cached = match self.cache.documentation_cache.get(&cache_key).await {
+            Some(cached) => serde_json::from_str::<Vec<DocIdResult>>(&cached).ok(),
+            None => None,
+        };
+        if let Some(index) = cached {
+            logging::debug(&format!("Found cached doc index for {}", cache_key));
+            return Ok((version, index));
+        }
+
+        let index = build_doc_index(
+            self.client
+                .list_provider_docs(provider_name, namespace, &version)
+                .await?,
+        );
+        logging::info(&format!(
+            "Indexed {} resource and data source docs for {}/{} {}",
+            index.len(),
+            namespace,
+            provider_name,
+            version
+        ));
+        if let Ok(serialized) = serde_json::to_string(&index) {
+            self.cache
+                .documentation_cache
+                .set(cache_key, serialized)
+                .await;
+        }
+        Ok((version, index))
+    }
+
+    /// Search the resource and data source docs of a provider version (latest
+    /// when `version` is `None`)
+    pub async fn search_provider_docs(
+        &self,
+        provider_name: &str,
+        namespace: &str,
+        version: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<ProviderDocSearch, RegistryError> {
+        let (version, index) = self
+            .provider_doc_index(provider_name, namespace, version)
+            .await?;
+
+        Ok(ProviderDocSearch {
+            namespace: namespace.to_string(),
+            provider: provider_name.to_string(),
+            version,
+            query: query.to_string(),
+            indexed_docs: index.len(),
+            matches: rank_docs(&index, query, limit),
+        })
+    }
 }
 
 impl Default for ProviderResolver {
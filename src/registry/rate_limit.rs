@@ -0,0 +1,250 @@
+//! Shared pacing of outgoing Terraform Registry requests.
+//!
+//! Every registry client created by the server draws from one limiter, so a
+//! burst of tool calls is spread out over time instead of tripping the
+//! registry's rate limit (HTTP 429). When a host does answer 429, further
+//! requests to it wait out its `Retry-After` instead of piling on.
+
+use crate::shared::logging;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Request, Response, StatusCode, Url};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Requests per second allowed when `TFMCP_REGISTRY_RPS` is unset
+pub const DEFAULT_REGISTRY_RPS: f64 = 10.0;
+
+/// Slowest rate honored: one request every 100 seconds. Smaller rates would
+/// make the interval too long to represent.
+pub const MIN_REGISTRY_RPS: f64 = 0.01;
+
+/// Cooldown after a 429 without a usable `Retry-After` header
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(2);
+
+/// Longest cooldown honored, whatever `Retry-After` asks for
+pub const MAX_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Token bucket holding a single token, refilled at a fixed rate.
+///
+/// Callers reserve the next free slot under the lock and sleep outside it, so
+/// concurrent requests queue up one interval apart.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+    /// Per host, the time before which no request is sent after a 429
+    cooldowns: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    /// Limiter allowing `requests_per_second` requests; non-positive rates fall
+    /// back to the default and tiny ones are raised to [`MIN_REGISTRY_RPS`]
+    pub fn new(requests_per_second: f64) -> Self {
+        let rps = if requests_per_second.is_finite() && requests_per_second > 0.0 {
+            requests_per_second.max(MIN_REGISTRY_RPS)
+        } else {
+            DEFAULT_REGISTRY_RPS
+        };
+        Self {
+            interval: Duration::from_secs_f64(1.0 / rps),
+            next_slot: Mutex::new(None),
+            cooldowns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limiter configured from `TFMCP_REGISTRY_RPS`
+    pub fn from_env() -> Self {
+        let rps = match std::env::var("TFMCP_REGISTRY_RPS") {
+            Ok(value) => value.trim().parse::<f64>().unwrap_or_else(|_| {
+                logging::warn(&format!(
+                    "Invalid TFMCP_REGISTRY_RPS '{}', using {}",
+                    value, DEFAULT_REGISTRY_RPS
+                ));
+                DEFAULT_REGISTRY_RPS
+            }),
+            Err(_) => DEFAULT_REGISTRY_RPS,
+        };
+        Self::new(rps)
+    }
+
+    /// Minimum spacing between two requests
+    #[allow(dead_code)]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Wait until the caller may send its next request
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let now = Instant::now();
+            let slot = next_slot.map_or(now, |next| next.max(now));
+            *next_slot = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Wait out any cooldown on `host`, then for the next free slot
+    pub async fn acquire_for(&self, host: &str) {
+        let until = {
+            let mut cooldowns = self.cooldowns.lock().await;
+            match cooldowns.get(host) {
+                Some(until) if *until > Instant::now() => Some(*until),
+                Some(_) => {
+                    cooldowns.remove(host);
+                    None
+                }
+                None => None,
+            }
+        };
+        if let Some(until) = until {
+            logging::info(&format!(
+                "Registry host {} is rate limited, waiting {:.1}s",
+                host,
+                (until - Instant::now()).as_secs_f64()
+            ));
+            tokio::time::sleep_until(until).await;
+        }
+        self.acquire().await;
+    }
+
+    /// Hold back requests to `host` for `retry_after`, at most [`MAX_COOLDOWN`]
+    pub async fn cool_down(&self, host: &str, retry_after: Duration) {
+        let until = Instant::now() + retry_after.min(MAX_COOLDOWN);
+        let mut cooldowns = self.cooldowns.lock().await;
+        let entry = cooldowns.entry(host.to_string()).or_insert(until);
+        *entry = (*entry).max(until);
+    }
+
+    /// Send `request` once its host is out of cooldown and a slot is free,
+    /// starting a cooldown if the host answers 429
+    pub async fn execute(&self, client: &Client, request: Request) -> reqwest::Result<Response> {
+        let host = host_key(request.url());
+        self.acquire_for(&host).await;
+        let response = client.execute(request).await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = retry_after(response.headers());
+            logging::warn(&format!(
+                "Registry host {} returned 429, pausing requests for {}s",
+                host,
+                retry_after.min(MAX_COOLDOWN).as_secs()
+            ));
+            self.cool_down(&host, retry_after).await;
+        }
+        Ok(response)
+    }
+}
+
+/// Cooldown key of a URL: host and port
+fn host_key(url: &Url) -> String {
+    format!(
+        "{}:{}",
+        url.host_str().unwrap_or_default(),
+        url.port_or_known_default().unwrap_or_default()
+    )
+}
+
+/// Delay asked for by a `Retry-After` header in seconds, or [`DEFAULT_COOLDOWN`]
+fn retry_after(headers: &HeaderMap) -> Duration {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map_or(DEFAULT_COOLDOWN, Duration::from_secs)
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_REGISTRY_RPS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_requests_are_spaced_by_configured_rate() {
+        let limiter = Arc::new(RateLimiter::new(20.0));
+        assert_eq!(limiter.interval(), Duration::from_millis(50));
+
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..5)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move {
+                    limiter.acquire().await;
+                    Instant::now()
+                })
+            })
+            .collect();
+
+        let mut times = Vec::new();
+        for task in tasks {
+            times.push(task.await.unwrap());
+        }
+        times.sort();
+
+        // The first request goes out immediately, the rest one interval apart
+        assert!(times[0] - start < Duration::from_millis(40));
+        for pair in times.windows(2) {
+            assert!(pair[1] - pair[0] >= Duration::from_millis(45));
+        }
+        assert!(times[4] - start >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_applies_per_host() {
+        let limiter = RateLimiter::new(1000.0);
+        limiter
+            .cool_down("registry.example:443", Duration::from_millis(150))
+            .await;
+
+        let start = Instant::now();
+        limiter.acquire_for("other.example:443").await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+        limiter.acquire_for("registry.example:443").await;
+        assert!(start.elapsed() >= Duration::from_millis(140));
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), DEFAULT_COOLDOWN);
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Duration::from_secs(7));
+        // HTTP dates are not parsed
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), DEFAULT_COOLDOWN);
+    }
+
+    #[test]
+    fn test_invalid_rate_uses_default() {
+        assert_eq!(
+            RateLimiter::new(0.0).interval(),
+            Duration::from_secs_f64(1.0 / DEFAULT_REGISTRY_RPS)
+        );
+        assert_eq!(
+            RateLimiter::new(-3.0).interval(),
+            RateLimiter::default().interval()
+        );
+    }
+
+    #[test]
+    fn test_tiny_rate_is_clamped() {
+        let slowest = Duration::from_secs_f64(1.0 / MIN_REGISTRY_RPS);
+        assert_eq!(RateLimiter::new(1e-300).interval(), slowest);
+        assert_eq!(RateLimiter::new(f64::MIN_POSITIVE).interval(), slowest);
+        assert_eq!(
+            RateLimiter::new(0.5).interval(),
+            Duration::from_secs_f64(2.0)
+        );
+    }
+}
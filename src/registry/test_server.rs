@@ -0,0 +1,68 @@
+//! Local HTTP server standing in for a registry in unit tests.
+
+use std::sync::{Arc, Mutex};
+
+/// A running test server and the requests it has received
+pub struct TestServer {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServer {
+    /// Request heads received so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Paths requested so far, in order
+    pub fn paths(&self) -> Vec<String> {
+        self.requests()
+            .iter()
+            .map(|request| request_path(request).to_string())
+            .collect()
+    }
+}
+
+fn request_path(request: &str) -> &str {
+    request.split_whitespace().nth(1).unwrap_or("/")
+}
+
+/// Serve `respond(path)` as `(status, body)` for every request on a local
+/// port. 429 responses carry `retry-after: 1`.
+pub async fn serve(respond: fn(&str) -> (u16, String)) -> TestServer {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let recorded = recorded.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                let n = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                    .await
+                    .unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let (status, body) = respond(request_path(&request));
+                recorded.lock().unwrap().push(request);
+                let retry_after = if status == 429 {
+                    "retry-after: 1\r\n"
+                } else {
+                    ""
+                };
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\n{}connection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    retry_after,
+                    body
+                );
+                let _ = tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes()).await;
+            });
+        }
+    });
+    TestServer {
+        base_url: format!("http://{}", addr),
+        requests,
+    }
+}
@@ -1,3 +1,4 @@
 pub mod logging;
+pub mod process;
 pub mod security;
 pub mod utils;
@@ -0,0 +1,334 @@
+//! Tracking of Terraform child processes so they can be cancelled on shutdown.
+//!
+//! Commands run through [`ProcessTracker::output`] are interrupted, then killed
+//! and reaped when the tracker is cancelled, instead of being orphaned when the
+//! MCP client disconnects or the server receives SIGTERM. Terraform handles
+//! SIGINT like Ctrl-C: it stops gracefully, persists state and releases the
+//! state lock, which SIGKILL would leave behind.
+
+use std::process::{Output, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// How long shutdown waits for in-flight operations to wind down
+pub const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// How long an interrupted command gets to exit before it is killed; shorter
+/// than [`SHUTDOWN_GRACE_PERIOD`] so the kill still happens during shutdown
+pub const INTERRUPT_GRACE_PERIOD: Duration = Duration::from_secs(8);
+
+struct TrackerState {
+    token: CancellationToken,
+    active: AtomicUsize,
+    idle: Notify,
+    interrupt_grace: Duration,
+}
+
+impl Default for TrackerState {
+    fn default() -> Self {
+        Self {
+            token: CancellationToken::new(),
+            active: AtomicUsize::new(0),
+            idle: Notify::new(),
+            interrupt_grace: INTERRUPT_GRACE_PERIOD,
+        }
+    }
+}
+
+/// Shared handle over running operations and their child processes
+#[derive(Clone, Default)]
+pub struct ProcessTracker {
+    state: Arc<TrackerState>,
+}
+
+/// Marks an operation as in flight until dropped
+pub struct OperationGuard {
+    state: Arc<TrackerState>,
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        if self.state.active.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.state.idle.notify_waiters();
+        }
+    }
+}
+
+impl ProcessTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracker that waits `grace` after interrupting a command before killing it
+    #[cfg(test)]
+    pub fn with_interrupt_grace(grace: Duration) -> Self {
+        Self {
+            state: Arc::new(TrackerState {
+                interrupt_grace: grace,
+                ..TrackerState::default()
+            }),
+        }
+    }
+
+    /// Register an in-flight operation; shutdown waits for it to finish
+    pub fn begin_operation(&self) -> OperationGuard {
+        self.state.active.fetch_add(1, Ordering::SeqCst);
+        OperationGuard {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Number of operations currently in flight
+    pub fn active_operations(&self) -> usize {
+        self.state.active.load(Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.token.is_cancelled()
+    }
+
+    /// Run a command to completion, interrupting and reaping it if the tracker
+    /// is cancelled
+    pub async fn output(&self, command: &mut Command) -> std::io::Result<Output> {
+        self.output_streaming(command, &mut |_| {}).await
+    }
+
+    /// Like [`output`](Self::output), but also passes each stdout line (without
+    /// its newline) to `on_line` as soon as the command writes it
+    pub async fn output_streaming(
+        &self,
+        command: &mut Command,
+        on_line: &mut (dyn FnMut(&str) + Send),
+    ) -> std::io::Result<Output> {
+        if self.is_cancelled() {
+            return Err(cancelled_error());
+        }
+        let _guard = self.begin_operation();
+
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let mut stdout_pipe = child.stdout.take().map(BufReader::new);
+        let mut stderr_pipe = child.stderr.take();
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let completed = tokio::select! {
+            result = async {
+                let read_stdout = async {
+                    let Some(pipe) = stdout_pipe.as_mut() else {
+                        return Ok::<_, std::io::Error>(());
+                    };
+                    loop {
+                        let start = stdout.len();
+                        if pipe.read_until(b'\n', &mut stdout).await? == 0 {
+                            return Ok(());
+                        }
+                        let line = String::from_utf8_lossy(&stdout[start..]);
+                        on_line(line.trim_end_matches(['\r', '\n']));
+                    }
+                };
+                let read_stderr = async {
+                    match stderr_pipe.as_mut() {
+                        Some(pipe) => pipe.read_to_end(&mut stderr).await.map(|_| ()),
+                        None => Ok(()),
+                    }
+                };
+                let (status, out, err) = tokio::join!(child.wait(), read_stdout, read_stderr);
+                out?;
+                err?;
+                status
+            } => Some(result),
+            _ = self.state.token.cancelled() => None,
+        };
+
+        match completed {
+            Some(status) => Ok(Output {
+                status: status?,
+                stdout,
+                stderr,
+            }),
+            None => {
+                interrupt(&mut child, self.state.interrupt_grace).await?;
+                Err(cancelled_error())
+            }
+        }
+    }
+
+    /// Interrupt running commands and refuse new ones
+    pub fn cancel(&self) {
+        self.state.token.cancel();
+    }
+
+    /// Cancel running commands and wait up to `grace` for in-flight operations to finish
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        self.cancel();
+
+        let deadline = tokio::time::Instant::now() + grace;
+        loop {
+            let idle = self.state.idle.notified();
+            if self.active_operations() == 0 {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, idle).await.is_err() {
+                return self.active_operations() == 0;
+            }
+        }
+    }
+}
+
+/// Send SIGINT and wait up to `grace` for the child to exit, then kill it;
+/// either way the child is reaped before returning
+#[cfg_attr(not(unix), allow(unused_variables))]
+async fn interrupt(child: &mut Child, grace: Duration) -> std::io::Result<()> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: kill(2) has no memory effects; the pid is our unreaped
+        // child, so it can't have been reused by another process
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGINT) } == 0 {
+            if let Ok(status) = tokio::time::timeout(grace, child.wait()).await {
+                return status.map(|_| ());
+            }
+        }
+    }
+    // kill() also waits, so the child is reaped before returning
+    child.kill().await
+}
+
+fn cancelled_error() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Interrupted,
+        "operation cancelled: tfmcp is shutting down",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn test_output_collects_stdout_and_status() {
+        let tracker = ProcessTracker::new();
+        let output = tracker
+            .output(Command::new("sh").args(["-c", "echo hello; echo oops >&2; exit 3"]))
+            .await
+            .unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "oops\n");
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(tracker.active_operations(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_output_streaming_passes_lines_as_written() {
+        let tracker = ProcessTracker::new();
+        let mut lines = Vec::new();
+        let output = tracker
+            .output_streaming(
+                Command::new("sh").args(["-c", "echo one; printf 'two\\nthree'"]),
+                &mut |line| lines.push(line.to_string()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(lines, ["one", "two", "three"]);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "one\ntwo\nthree");
+        assert!(output.status.success());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_shutdown_kills_and_reaps_running_child() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("pid");
+        let tracker = ProcessTracker::new();
+
+        let task = {
+            let tracker = tracker.clone();
+            let script = format!("echo $$ > {}; exec sleep 30", pid_file.display());
+            tokio::spawn(async move {
+                tracker
+                    .output(Command::new("sh").args(["-c", &script]))
+                    .await
+            })
+        };
+
+        let pid = loop {
+            if let Ok(pid) = std::fs::read_to_string(&pid_file) {
+                if !pid.trim().is_empty() {
+                    break pid.trim().to_string();
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        };
+
+        assert!(tracker.shutdown(Duration::from_secs(5)).await);
+        let err = task.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+        assert!(!std::path::Path::new(&format!("/proc/{}", pid)).exists());
+
+        // New commands are refused once shut down
+        assert!(tracker.output(&mut Command::new("true")).await.is_err());
+    }
+
+    /// Run `script` under `tracker` until it has written `ready`, then shut down
+    #[cfg(unix)]
+    async fn shut_down_while_running(tracker: &ProcessTracker, script: String, ready: &Path) {
+        let task = {
+            let tracker = tracker.clone();
+            tokio::spawn(async move {
+                tracker
+                    .output(Command::new("sh").args(["-c", &script]))
+                    .await
+            })
+        };
+        while !ready.exists() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(tracker.shutdown(Duration::from_secs(5)).await);
+        let err = task.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_interrupts_child_before_killing() {
+        let dir = tempfile::tempdir().unwrap();
+        let ready = dir.path().join("ready");
+        let interrupted = dir.path().join("interrupted");
+        let script = format!(
+            "trap 'touch {}; exit 130' INT; touch {}; while :; do sleep 0.05; done",
+            interrupted.display(),
+            ready.display()
+        );
+
+        shut_down_while_running(&ProcessTracker::new(), script, &ready).await;
+        // The INT handler ran, as terraform's would to release its state lock
+        assert!(interrupted.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_kills_child_ignoring_interrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let ready = dir.path().join("ready");
+        let script = format!("trap '' INT; touch {}; exec sleep 30", ready.display());
+        let tracker = ProcessTracker::with_interrupt_grace(Duration::from_millis(200));
+
+        let started = std::time::Instant::now();
+        shut_down_while_running(&tracker, script, &ready).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -1,7 +1,6 @@
 use dirs::home_dir;
 use std::path::{Path, PathBuf};
 
-#[allow(dead_code)]
 pub fn expand_tilde(path: &str) -> PathBuf {
     if !path.starts_with('~') {
         return PathBuf::from(path);
@@ -0,0 +1,140 @@
+//! Project-wide analysis results keyed by a hash of the configuration.
+//!
+//! Module health, the dependency graph and refactoring suggestions are
+//! derived from every file in the project, so they are cached as a whole:
+//! the key hashes the project directory with each file's path and content,
+//! and any edit produces a new key. Only the latest result of each kind is
+//! kept.
+
+use crate::terraform::model::{
+    ModuleHealthAnalysis, RefactoringSuggestion, ResourceDependencyGraph,
+};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Hash of the project directory and its file contents, independent of the
+/// map's iteration order; `extra` covers inputs read outside the project's
+/// files, such as the README, local modules elsewhere in the project or the
+/// resource category mapping
+pub fn content_key(
+    project_directory: &Path,
+    file_contents: &HashMap<String, String>,
+    extra: impl Hash,
+) -> u64 {
+    let mut files: Vec<_> = file_contents.iter().collect();
+    files.sort();
+    let mut hasher = DefaultHasher::new();
+    project_directory.hash(&mut hasher);
+    files.hash(&mut hasher);
+    extra.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Latest result of one kind with the key it was computed for
+struct Slot<T>(Mutex<Option<(u64, T)>>);
+
+impl<T: Clone> Slot<T> {
+    fn get(&self, key: u64) -> Option<T> {
+        let slot = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        slot.as_ref()
+            .filter(|(cached, _)| *cached == key)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn store(&self, key: u64, value: T) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some((key, value));
+    }
+
+    fn clear(&self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self(Mutex::new(None))
+    }
+}
+
+/// Cached project analyses, shared by clones of the service
+#[derive(Default)]
+pub struct AnalysisCache {
+    module_health: Slot<ModuleHealthAnalysis>,
+    dependency_graph: Slot<ResourceDependencyGraph>,
+    refactoring: Slot<Vec<RefactoringSuggestion>>,
+    computed: AtomicUsize,
+}
+
+impl AnalysisCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn module_health(&self, key: u64) -> Option<ModuleHealthAnalysis> {
+        self.module_health.get(key)
+    }
+
+    pub fn store_module_health(&self, key: u64, health: ModuleHealthAnalysis) {
+        self.computed.fetch_add(1, Ordering::Relaxed);
+        self.module_health.store(key, health);
+    }
+
+    pub fn dependency_graph(&self, key: u64) -> Option<ResourceDependencyGraph> {
+        self.dependency_graph.get(key)
+    }
+
+    pub fn store_dependency_graph(&self, key: u64, graph: ResourceDependencyGraph) {
+        self.computed.fetch_add(1, Ordering::Relaxed);
+        self.dependency_graph.store(key, graph);
+    }
+
+    pub fn refactoring(&self, key: u64) -> Option<Vec<RefactoringSuggestion>> {
+        self.refactoring.get(key)
+    }
+
+    pub fn store_refactoring(&self, key: u64, suggestions: Vec<RefactoringSuggestion>) {
+        self.computed.fetch_add(1, Ordering::Relaxed);
+        self.refactoring.store(key, suggestions);
+    }
+
+    /// Number of results computed and stored since the cache was created
+    #[allow(dead_code)]
+    pub fn computed_count(&self) -> usize {
+        self.computed.load(Ordering::Relaxed)
+    }
+
+    /// Drop all cached results
+    pub fn clear(&self) {
+        self.module_health.clear();
+        self.dependency_graph.clear();
+        self.refactoring.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_key_tracks_contents_not_order() {
+        let dir = Path::new("/project");
+        let mut a = HashMap::new();
+        a.insert("main.tf".to_string(), "resource \"a\" \"b\" {}".to_string());
+        a.insert("variables.tf".to_string(), String::new());
+        let mut b = HashMap::new();
+        b.insert("variables.tf".to_string(), String::new());
+        b.insert("main.tf".to_string(), "resource \"a\" \"b\" {}".to_string());
+
+        assert_eq!(content_key(dir, &a, ()), content_key(dir, &b, ()));
+        assert_ne!(content_key(dir, &a, ()), content_key(dir, &a, "# README"));
+        assert_ne!(
+            content_key(dir, &a, ()),
+            content_key(Path::new("/other"), &a, ())
+        );
+        b.insert("main.tf".to_string(), "resource \"a\" \"c\" {}".to_string());
+        assert_ne!(content_key(dir, &a, ()), content_key(dir, &b, ()));
+    }
+}
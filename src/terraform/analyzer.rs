@@ -6,15 +6,22 @@
 //! Reference: Infrastructure code requires whitebox understanding - detailed visibility
 //! into internal structure is essential, unlike application code abstraction.
 
+use crate::terraform::categories;
 use crate::terraform::model::{
     CohesionAnalysis, CohesionType, CountUsageWarning, CouplingAnalysis, CouplingType,
-    DependencyType, GuidelineCheckResult, IssueCategory, IssueSeverity, ModuleBoundary,
-    ModuleDependency, ModuleHealthAnalysis, ModuleIssue, ModuleMetrics, ProposedModuleStructure,
-    RefactoringSuggestion, RefactoringType, ResourceDependencyGraph, ResourceEdge, ResourceNode,
-    ResourceTypeGroup, SecretDetection, TerraformAnalysis,
+    DefinitionLocation, DefinitionLookup, DefinitionMatch, DependencyType, DeprecatedSyntax,
+    DuplicateDefinition, GuidelineCheckResult, HardcodedValue, ImpactAnalysis, ImpactedResource,
+    IssueCategory, IssueSeverity, LooseProviderConstraint, ModuleBoundary, ModuleDependency,
+    ModuleHealthAnalysis, ModuleIssue, ModuleMetrics, ModuleSourceRef, ModuleWiringIssue,
+    ModuleWiringIssueKind, ProposedModuleStructure, ReadmeQuality, RefactoringSuggestion,
+    RefactoringType, ResourceDependencyGraph, ResourceEdge, ResourceNode, ResourceSubgraph,
+    ResourceTypeGroup, SecretDetection, TerraformAnalysis, TerraformProvider, UndeclaredProvider,
+    UnpinnedModuleSource,
 };
+use crate::terraform::parser::TerraformParser;
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::sync::LazyLock;
 
 // Regex patterns for extended parsing
@@ -55,6 +62,84 @@ static COUNT_VALUE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
         .expect("Invalid count value regex")
 });
 
+static RESOURCE_HEADER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*resource\s+"([^"]+)"\s+"([^"]+)""#).expect("Invalid resource header regex")
+});
+
+static LITERAL_ASSIGNMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*[A-Za-z_][A-Za-z0-9_-]*\s*=\s*("[^"]*"|-?\d+(?:\.\d+)?)\s*(?:#.*|//.*)?$"#)
+        .expect("Invalid literal assignment regex")
+});
+
+static MODULE_HEADER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*module\s+"([^"]+)""#).expect("Invalid module header regex"));
+
+static DEFINITION_HEADER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(resource|variable|output)\s+"([^"]+)"(?:\s+"([^"]+)")?"#)
+        .expect("Invalid definition header regex")
+});
+
+static ARGUMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*([A-Za-z_][A-Za-z0-9_-]*)\s*=[^=]"#).expect("Invalid argument regex")
+});
+
+static INLINE_DEFAULT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\{.*\bdefault\s*=[^=]"#).expect("Invalid inline default regex"));
+
+static MODULE_OUTPUT_REF_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\bmodule\.([A-Za-z_][A-Za-z0-9_-]*)(?:\[[^\]]*\])*\.([A-Za-z_][A-Za-z0-9_-]*)"#)
+        .expect("Invalid module output reference regex")
+});
+
+/// Module block arguments that are not input variables
+const MODULE_META_ARGUMENTS: &[&str] = &[
+    "source",
+    "version",
+    "count",
+    "for_each",
+    "providers",
+    "depends_on",
+];
+
+static VERSION_ATTRIBUTE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*version\s*=\s*(.*?)\s*$"#).expect("Invalid version attribute regex")
+});
+
+static INTERPOLATION_ONLY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""\$\{([^"{}]+)\}""#).expect("Invalid interpolation-only regex"));
+
+static TERRAFORM_ENV_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bterraform\.env\b"#).expect("Invalid terraform.env regex"));
+
+static QUOTED_TYPE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*type\s*=\s*"(string|number|bool|list|map)""#)
+        .expect("Invalid quoted type regex")
+});
+
+static BARE_COLLECTION_TYPE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*type\s*=\s*(list|map|set)\s*(?:#.*|//.*)?$"#)
+        .expect("Invalid bare collection type regex")
+});
+
+static COLLECTION_CALL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\b(list|map)\(\s*([A-Za-z_]*)"#).expect("Invalid collection call regex")
+});
+
+static HEREDOC_START_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<<-?\s*([A-Za-z_]+)\s*$"#).expect("Invalid heredoc regex"));
+
+/// Keywords that make `list(...)`/`map(...)` a type constraint rather than
+/// the removed function
+const TYPE_KEYWORDS: &[&str] = &[
+    "string", "number", "bool", "any", "list", "map", "set", "object", "tuple",
+];
+
+/// Literals too structural to be worth extracting into variables
+const STRUCTURAL_LITERALS: &[&str] = &["0", "1", "-1", "\"\"", "\"*\""];
+
+/// Minimum number of distinct resources sharing a literal before it is reported
+const MIN_HARDCODED_RESOURCES: usize = 2;
+
 static DEFAULT_TAGS_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"default_tags\s*\{"#).expect("Invalid default_tags regex"));
 
@@ -120,133 +205,37 @@ const MAX_RESOURCE_TYPES: usize = 5;
 const MAX_HIERARCHY_DEPTH: usize = 2;
 const MIN_DESCRIPTION_RATIO: f64 = 0.8;
 
+/// README sections expected of a module, in README order
+const README_SECTIONS: &[&str] = &["Usage", "Inputs", "Outputs"];
+
 /// Resource type categories for cohesion analysis
 fn get_resource_category(resource_type: &str) -> &'static str {
-    let type_lower = resource_type.to_lowercase();
-
-    // AWS categories
-    if type_lower.contains("vpc")
-        || type_lower.contains("subnet")
-        || type_lower.contains("route")
-        || type_lower.contains("internet_gateway")
-        || type_lower.contains("nat_gateway")
-        || type_lower.contains("network_acl")
-    {
-        return "networking-core";
-    }
-    if type_lower.contains("security_group") {
-        return "networking-security";
-    }
-    if type_lower.contains("vpn") || type_lower.contains("transit") {
-        return "networking-connectivity";
-    }
-    if type_lower.contains("flow_log") {
-        return "networking-monitoring";
-    }
-    if type_lower.contains("lb")
-        || type_lower.contains("load_balancer")
-        || type_lower.contains("target_group")
-        || type_lower.contains("listener")
-    {
-        return "load-balancing";
-    }
-    if type_lower.contains("instance")
-        || type_lower.contains("launch_template")
-        || type_lower.contains("autoscaling")
-    {
-        return "compute";
-    }
-    if type_lower.contains("rds")
-        || type_lower.contains("db_")
-        || type_lower.contains("dynamodb")
-        || type_lower.contains("elasticache")
-    {
-        return "database";
-    }
-    if type_lower.contains("s3") || type_lower.contains("bucket") {
-        return "storage";
-    }
-    if type_lower.contains("iam")
-        || type_lower.contains("role")
-        || type_lower.contains("policy")
-        || type_lower.contains("kms")
-    {
-        return "security";
-    }
-    if type_lower.contains("lambda") || type_lower.contains("function") {
-        return "serverless";
-    }
-    if type_lower.contains("eks")
-        || type_lower.contains("ecs")
-        || type_lower.contains("kubernetes")
-        || type_lower.contains("container")
-    {
-        return "containers";
-    }
-    if type_lower.contains("cloudwatch")
-        || type_lower.contains("log_group")
-        || type_lower.contains("alarm")
-        || type_lower.contains("metric")
-    {
-        return "monitoring";
-    }
-    if type_lower.contains("sns")
-        || type_lower.contains("sqs")
-        || type_lower.contains("eventbridge")
-    {
-        return "messaging";
-    }
-    if type_lower.contains("route53")
-        || type_lower.contains("dns")
-        || type_lower.contains("hosted_zone")
-    {
-        return "dns";
-    }
-    if type_lower.contains("acm") || type_lower.contains("certificate") {
-        return "certificates";
-    }
-    if type_lower.contains("cloudfront") || type_lower.contains("cdn") {
-        return "cdn";
-    }
-    if type_lower.contains("api_gateway") || type_lower.contains("apigateway") {
-        return "api";
-    }
-
-    // Azure categories
-    if type_lower.contains("azurerm_virtual_network")
-        || type_lower.contains("azurerm_subnet")
-        || type_lower.contains("azurerm_network")
-    {
-        return "networking-core";
-    }
-    if type_lower.contains("azurerm_vm") || type_lower.contains("azurerm_virtual_machine") {
-        return "compute";
-    }
-
-    // GCP categories
-    if type_lower.contains("google_compute_network")
-        || type_lower.contains("google_compute_subnetwork")
-    {
-        return "networking-core";
-    }
-    if type_lower.contains("google_compute_instance") {
-        return "compute";
-    }
-
-    "other"
+    categories::active().categorize(resource_type)
 }
 
-/// Analyze module health
+/// Analyze module health; `module_wiring` comes from [`find_module_wiring_issues`]
+/// and `readme` is the content of the module's README.md, if it has one
 pub fn analyze_module_health(
     analysis: &TerraformAnalysis,
     file_contents: &HashMap<String, String>,
+    module_wiring: &[ModuleWiringIssue],
+    readme: Option<&str>,
 ) -> ModuleHealthAnalysis {
     let metrics = calculate_metrics(analysis, file_contents);
     let cohesion = analyze_cohesion(analysis);
     let coupling = analyze_coupling(analysis, file_contents);
-    let issues = detect_issues(analysis, &metrics, &cohesion, &coupling, file_contents);
-    let recommendations = generate_recommendations(&issues, &metrics, &cohesion);
-    let health_score = calculate_health_score(&metrics, &cohesion, &coupling, &issues);
+    let readme = assess_readme(readme);
+    let issues = detect_issues(
+        analysis,
+        &metrics,
+        &cohesion,
+        &coupling,
+        file_contents,
+        module_wiring,
+        &readme,
+    );
+    let recommendations = generate_recommendations(&issues, &metrics, &cohesion, &readme);
+    let health_score = calculate_health_score(&metrics, &cohesion, &coupling, &issues, &readme);
 
     ModuleHealthAnalysis {
         module_path: analysis.project_directory.clone(),
@@ -256,6 +245,52 @@ pub fn analyze_module_health(
         recommendations,
         cohesion_analysis: cohesion,
         coupling_analysis: coupling,
+        readme,
+    }
+}
+
+/// Score a README by presence (40) and its Usage, Inputs and Outputs
+/// sections (20 each), recognized by heading text
+pub fn assess_readme(content: Option<&str>) -> ReadmeQuality {
+    let Some(content) = content else {
+        return ReadmeQuality {
+            missing_sections: README_SECTIONS.iter().map(|s| s.to_string()).collect(),
+            ..ReadmeQuality::default()
+        };
+    };
+
+    let headings: Vec<String> = content
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix('#'))
+        .map(|heading| heading.trim_start_matches('#').trim().to_lowercase())
+        .collect();
+    let has_heading = |words: &[&str]| {
+        headings
+            .iter()
+            .any(|heading| words.iter().any(|word| heading.contains(word)))
+    };
+
+    let has_usage = has_heading(&["usage", "example"]);
+    let has_inputs = has_heading(&["input", "variable"]);
+    let has_outputs = has_heading(&["output"]);
+    let missing_sections = README_SECTIONS
+        .iter()
+        .zip([has_usage, has_inputs, has_outputs])
+        .filter(|(_, present)| !present)
+        .map(|(section, _)| section.to_string())
+        .collect();
+    let sections = [has_usage, has_inputs, has_outputs]
+        .iter()
+        .filter(|present| **present)
+        .count() as u8;
+
+    ReadmeQuality {
+        present: true,
+        has_usage,
+        has_inputs,
+        has_outputs,
+        missing_sections,
+        score: 40 + 20 * sections,
     }
 }
 
@@ -340,17 +375,20 @@ fn analyze_cohesion(analysis: &TerraformAnalysis) -> CohesionAnalysis {
             .push(resource.resource_type.clone());
     }
 
-    let resource_type_groups: Vec<ResourceTypeGroup> = category_counts
+    let mut resource_type_groups: Vec<ResourceTypeGroup> = category_counts
         .into_iter()
         .map(|(name, types)| {
-            let unique_types: HashSet<_> = types.iter().cloned().collect();
+            let mut unique_types: Vec<String> = types.to_vec();
+            unique_types.sort();
+            unique_types.dedup();
             ResourceTypeGroup {
                 name: name.to_string(),
-                resource_types: unique_types.into_iter().collect(),
+                resource_types: unique_types,
                 resource_count: types.len(),
             }
         })
         .collect();
+    resource_type_groups.sort_by(|a, b| a.name.cmp(&b.name));
 
     let num_categories = resource_type_groups.len();
     let total_resources = analysis.resources.len();
@@ -460,7 +498,7 @@ fn analyze_coupling(
     let mut control_coupling_count = 0;
     let mut module_sources: Vec<String> = Vec::new();
 
-    for content in file_contents.values() {
+    for (_, content) in sorted_files(file_contents) {
         // Count control coupling (count/for_each based on variables)
         control_coupling_count += COUNT_REGEX.captures_iter(content).count();
         control_coupling_count += FOR_EACH_REGEX.captures_iter(content).count();
@@ -551,6 +589,8 @@ fn detect_issues(
     cohesion: &CohesionAnalysis,
     coupling: &CouplingAnalysis,
     file_contents: &HashMap<String, String>,
+    module_wiring: &[ModuleWiringIssue],
+    readme: &ReadmeQuality,
 ) -> Vec<ModuleIssue> {
     let mut issues = Vec::new();
 
@@ -630,6 +670,28 @@ fn detect_issues(
         });
     }
 
+    if !readme.present {
+        issues.push(ModuleIssue {
+            severity: IssueSeverity::Warning,
+            category: IssueCategory::MissingDocumentation,
+            message: "Module has no README.md. Users need it to learn how to call the module."
+                .to_string(),
+            file: None,
+            line: None,
+        });
+    } else if !readme.missing_sections.is_empty() {
+        issues.push(ModuleIssue {
+            severity: IssueSeverity::Info,
+            category: IssueCategory::MissingDocumentation,
+            message: format!(
+                "README.md has no {} section(s)",
+                readme.missing_sections.join(", ")
+            ),
+            file: Some("README.md".to_string()),
+            line: None,
+        });
+    }
+
     // Check for public module usage
     for dep in &coupling.dependencies {
         if dep.dependency_type == "public-registry" {
@@ -647,7 +709,7 @@ fn detect_issues(
     }
 
     // Check for control coupling patterns
-    for (filename, content) in file_contents {
+    for (filename, content) in sorted_files(file_contents) {
         let count_occurrences = COUNT_REGEX.captures_iter(content).count();
         let for_each_occurrences = FOR_EACH_REGEX.captures_iter(content).count();
 
@@ -667,7 +729,7 @@ fn detect_issues(
     }
 
     // Check naming conventions
-    for filename in file_contents.keys() {
+    for (filename, _) in sorted_files(file_contents) {
         if filename == "main.tf" && metrics.resource_count > 5 {
             issues.push(ModuleIssue {
                 severity: IssueSeverity::Info,
@@ -679,6 +741,104 @@ fn detect_issues(
         }
     }
 
+    // Check for literals repeated across resources
+    for hardcoded in find_hardcoded_values(file_contents) {
+        issues.push(ModuleIssue {
+            severity: IssueSeverity::Info,
+            category: IssueCategory::HardcodedValue,
+            message: format!(
+                "Literal {} appears {} times across {} resources ({}). Consider extracting it into a variable.",
+                hardcoded.value,
+                hardcoded.occurrences,
+                hardcoded.resources.len(),
+                hardcoded.files.join(", ")
+            ),
+            file: hardcoded.files.first().cloned(),
+            line: None,
+        });
+    }
+
+    // Check for module sources that are not pinned
+    for unpinned in find_unpinned_module_sources(file_contents) {
+        issues.push(ModuleIssue {
+            severity: IssueSeverity::Warning,
+            category: IssueCategory::UnpinnedModuleSource,
+            message: format!(
+                "Module '{}' uses unpinned source '{}': {}. Upstream changes will be pulled in on the next init.",
+                unpinned.module, unpinned.source, unpinned.reason
+            ),
+            file: Some(unpinned.file),
+            line: Some(unpinned.line),
+        });
+    }
+
+    // Check for addresses Terraform will reject as declared twice
+    for duplicate in find_duplicate_definitions(file_contents) {
+        let locations: Vec<String> = duplicate
+            .locations
+            .iter()
+            .map(|l| format!("{}:{}", l.file, l.line))
+            .collect();
+        issues.push(ModuleIssue {
+            severity: IssueSeverity::Critical,
+            category: IssueCategory::DuplicateDefinition,
+            message: format!(
+                "Duplicate {} '{}' declared {} times ({}). Terraform rejects duplicate declarations; remove or rename all but one.",
+                duplicate.kind,
+                duplicate.address,
+                duplicate.locations.len(),
+                locations.join(", ")
+            ),
+            file: duplicate.locations.first().map(|l| l.file.clone()),
+            line: duplicate.locations.first().map(|l| l.line),
+        });
+    }
+
+    // Check local module calls against the module's variables and outputs
+    for wiring in module_wiring {
+        let problem = match wiring.kind {
+            ModuleWiringIssueKind::MissingRequiredInput => {
+                format!("does not pass required input '{}'", wiring.key)
+            }
+            ModuleWiringIssueKind::UnknownInput => format!(
+                "passes '{}', which the module does not declare as a variable",
+                wiring.key
+            ),
+            ModuleWiringIssueKind::UnknownOutput => format!(
+                "output '{}' is referenced but not declared by the module",
+                wiring.key
+            ),
+        };
+        issues.push(ModuleIssue {
+            severity: IssueSeverity::Critical,
+            category: IssueCategory::ModuleWiring,
+            message: format!(
+                "Module '{}' ({}): {}. Terraform will reject this configuration.",
+                wiring.module, wiring.source, problem
+            ),
+            file: Some(wiring.file.clone()),
+            line: Some(wiring.line),
+        });
+    }
+
+    // Check for constructs carried over from pre-0.12 configurations
+    for deprecated in find_deprecated_syntax(file_contents) {
+        issues.push(ModuleIssue {
+            severity: IssueSeverity::Info,
+            category: IssueCategory::DeprecatedSyntax,
+            message: format!(
+                "Deprecated {} `{}` at {}:{}. Use `{}` instead.",
+                deprecated.pattern,
+                deprecated.found,
+                deprecated.file,
+                deprecated.line,
+                deprecated.suggestion
+            ),
+            file: Some(deprecated.file),
+            line: Some(deprecated.line),
+        });
+    }
+
     // Cohesion-based issues
     if cohesion.score < 50 {
         issues.push(ModuleIssue {
@@ -690,14 +850,24 @@ fn detect_issues(
         });
     }
 
+    // Most severe first; the sort is stable, so detection order is kept otherwise
+    issues.sort_by(|a, b| a.severity.cmp(&b.severity));
     issues
 }
 
+/// `file_contents` in file name order, so output does not depend on hash order
+fn sorted_files(file_contents: &HashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut files: Vec<_> = file_contents.iter().collect();
+    files.sort_by(|a, b| a.0.cmp(b.0));
+    files
+}
+
 /// Generate recommendations based on detected issues
 fn generate_recommendations(
     issues: &[ModuleIssue],
     metrics: &ModuleMetrics,
     cohesion: &CohesionAnalysis,
+    readme: &ReadmeQuality,
 ) -> Vec<String> {
     let mut recommendations = Vec::new();
 
@@ -729,6 +899,16 @@ fn generate_recommendations(
             metrics.variables_without_description
         ));
     }
+    if !readme.present {
+        recommendations.push(
+            "📝 Add a README.md with Usage, Inputs and Outputs sections. terraform-docs can generate the Inputs and Outputs tables.".to_string(),
+        );
+    } else if !readme.missing_sections.is_empty() {
+        recommendations.push(format!(
+            "📝 Add the missing README.md sections: {}.",
+            readme.missing_sections.join(", ")
+        ));
+    }
 
     // Hierarchy recommendations
     if metrics.hierarchy_depth > MAX_HIERARCHY_DEPTH {
@@ -748,6 +928,46 @@ fn generate_recommendations(
         );
     }
 
+    // Module source pinning recommendations
+    if issues
+        .iter()
+        .any(|i| matches!(i.category, IssueCategory::UnpinnedModuleSource))
+    {
+        recommendations.push(
+            "📌 Pin module sources: Add a `version` constraint to registry modules and a `?ref=` tag or commit to git sources so upgrades are deliberate and reviewable.".to_string()
+        );
+    }
+
+    // Duplicate declaration recommendations
+    if issues
+        .iter()
+        .any(|i| matches!(i.category, IssueCategory::DuplicateDefinition))
+    {
+        recommendations.push(
+            "🚫 Remove duplicate declarations: Each resource address, variable and output must be declared once per module. Check for blocks copied between files.".to_string()
+        );
+    }
+
+    // Module wiring recommendations
+    if issues
+        .iter()
+        .any(|i| matches!(i.category, IssueCategory::ModuleWiring))
+    {
+        recommendations.push(
+            "🔌 Fix module wiring: Pass every variable without a default, drop arguments the module does not declare, and only reference outputs the module defines.".to_string()
+        );
+    }
+
+    // Deprecated syntax recommendations
+    if issues
+        .iter()
+        .any(|i| matches!(i.category, IssueCategory::DeprecatedSyntax))
+    {
+        recommendations.push(
+            "🧹 Modernize deprecated syntax: Write references without \"${...}\" wrappers, use terraform.workspace, unquoted type constraints and tolist()/tomap().".to_string()
+        );
+    }
+
     // General best practices
     if issues.is_empty() {
         recommendations.push(
@@ -764,6 +984,7 @@ fn calculate_health_score(
     cohesion: &CohesionAnalysis,
     coupling: &CouplingAnalysis,
     issues: &[ModuleIssue],
+    readme: &ReadmeQuality,
 ) -> u8 {
     let mut score: i32 = 100;
 
@@ -796,6 +1017,9 @@ fn calculate_health_score(
         score -= 10;
     }
 
+    // Deduct for README documentation, up to 10 when there is none
+    score -= (100 - readme.score as i32) / 10;
+
     // Ensure score is within bounds
     score.clamp(0, 100) as u8
 }
@@ -808,14 +1032,9 @@ pub fn build_dependency_graph(
     let mut nodes = Vec::new();
     let mut edges = Vec::new();
 
-    // Build resource lookup map
-    let mut resource_map: HashMap<String, usize> = HashMap::new();
-
     // Create nodes for each resource
     for resource in &analysis.resources {
         let id = format!("{}.{}", resource.resource_type, resource.name);
-        resource_map.insert(id.clone(), nodes.len());
-
         nodes.push(ResourceNode {
             id: id.clone(),
             resource_type: resource.resource_type.clone(),
@@ -825,9 +1044,17 @@ pub fn build_dependency_graph(
             provider: resource.provider.clone(),
         });
     }
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    // Build resource lookup map
+    let resource_map: HashMap<String, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id.clone(), index))
+        .collect();
 
     // Find dependencies by scanning file contents
-    for (filename, content) in file_contents {
+    for (filename, content) in sorted_files(file_contents) {
         // Find explicit depends_on
         for cap in DEPENDS_ON_REGEX.captures_iter(content) {
             let deps_str = &cap[1];
@@ -885,6 +1112,15 @@ pub fn build_dependency_graph(
         }
     }
 
+    edges.sort_by(|a, b| {
+        (&a.source, &a.target, &a.dependency_type, &a.attribute).cmp(&(
+            &b.source,
+            &b.target,
+            &b.dependency_type,
+            &b.attribute,
+        ))
+    });
+
     // Create module boundary
     let module_boundaries = vec![ModuleBoundary {
         module_path: analysis.project_directory.clone(),
@@ -898,6 +1134,177 @@ pub fn build_dependency_graph(
     }
 }
 
+/// Resources that depend on `address` in `graph`, directly and transitively.
+/// Edges point from the dependent resource to its dependency.
+pub fn impact_analysis(
+    graph: &ResourceDependencyGraph,
+    address: &str,
+) -> anyhow::Result<ImpactAnalysis> {
+    if !graph.nodes.iter().any(|node| node.id == address) {
+        return Err(anyhow::anyhow!(
+            "Resource '{}' is not in the dependency graph",
+            address
+        ));
+    }
+
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        dependents
+            .entry(edge.target.as_str())
+            .or_default()
+            .push(edge.source.as_str());
+    }
+
+    let mut direct: Vec<String> = dependents
+        .get(address)
+        .into_iter()
+        .flatten()
+        .map(|source| source.to_string())
+        .collect();
+    direct.sort();
+    direct.dedup();
+
+    // Breadth-first, so each resource is reported at its shortest distance;
+    // the visited set stops cycles from being walked again
+    let mut visited: HashSet<&str> = HashSet::from([address]);
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(address, 0)]);
+    let mut transitive = Vec::new();
+    let mut in_cycle = false;
+    while let Some((current, distance)) = queue.pop_front() {
+        for &dependent in dependents.get(current).into_iter().flatten() {
+            if dependent == address {
+                in_cycle = true;
+            }
+            if visited.insert(dependent) {
+                transitive.push(ImpactedResource {
+                    address: dependent.to_string(),
+                    distance: distance + 1,
+                });
+                queue.push_back((dependent, distance + 1));
+            }
+        }
+    }
+    transitive.sort_by(|a, b| (a.distance, &a.address).cmp(&(b.distance, &b.address)));
+
+    Ok(ImpactAnalysis {
+        resource: address.to_string(),
+        direct_dependents: direct,
+        transitive_dependents: transitive,
+        in_cycle,
+    })
+}
+
+/// Cut the subgraph around `address`: the resource, its transitive
+/// dependencies and dependents, and the edges between them
+pub fn resource_subgraph(
+    graph: &ResourceDependencyGraph,
+    address: &str,
+) -> anyhow::Result<ResourceSubgraph> {
+    if !graph.nodes.iter().any(|node| node.id == address) {
+        return Err(anyhow::anyhow!(
+            "Resource '{}' is not in the dependency graph",
+            address
+        ));
+    }
+
+    let mut dependencies: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        dependencies
+            .entry(edge.source.as_str())
+            .or_default()
+            .push(edge.target.as_str());
+        dependents
+            .entry(edge.target.as_str())
+            .or_default()
+            .push(edge.source.as_str());
+    }
+    let dependencies = reachable_from(address, &dependencies);
+    let dependents = reachable_from(address, &dependents);
+
+    let members: HashSet<&str> = std::iter::once(address)
+        .chain(dependencies.iter().map(|r| r.address.as_str()))
+        .chain(dependents.iter().map(|r| r.address.as_str()))
+        .collect();
+    let nodes = graph
+        .nodes
+        .iter()
+        .filter(|node| members.contains(node.id.as_str()))
+        .cloned()
+        .collect();
+    let edges = graph
+        .edges
+        .iter()
+        .filter(|edge| {
+            members.contains(edge.source.as_str()) && members.contains(edge.target.as_str())
+        })
+        .cloned()
+        .collect();
+
+    Ok(ResourceSubgraph {
+        resource: address.to_string(),
+        dependencies,
+        dependents,
+        nodes,
+        edges,
+    })
+}
+
+/// Resources reachable from `start` through `adjacency`, breadth-first and
+/// nearest first; `start` itself is left out even when a cycle reaches it
+fn reachable_from(start: &str, adjacency: &HashMap<&str, Vec<&str>>) -> Vec<ImpactedResource> {
+    let mut visited: HashSet<&str> = HashSet::from([start]);
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(start, 0)]);
+    let mut reached = Vec::new();
+    while let Some((current, distance)) = queue.pop_front() {
+        for &next in adjacency.get(current).into_iter().flatten() {
+            if visited.insert(next) {
+                reached.push(ImpactedResource {
+                    address: next.to_string(),
+                    distance: distance + 1,
+                });
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    reached.sort_by(|a, b| (a.distance, &a.address).cmp(&(b.distance, &b.address)));
+    reached
+}
+
+/// Render a subgraph as a Mermaid flowchart, edges pointing from a resource
+/// to what it depends on and the selected resource highlighted
+pub fn subgraph_to_mermaid(subgraph: &ResourceSubgraph) -> String {
+    let ids: HashMap<&str, String> = subgraph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id.as_str(), format!("n{}", index)))
+        .collect();
+
+    let mut lines = vec!["graph LR".to_string()];
+    for node in &subgraph.nodes {
+        lines.push(format!("    {}[\"{}\"]", ids[node.id.as_str()], node.id));
+    }
+    for edge in &subgraph.edges {
+        let (Some(source), Some(target)) =
+            (ids.get(edge.source.as_str()), ids.get(edge.target.as_str()))
+        else {
+            continue;
+        };
+        match edge.dependency_type {
+            DependencyType::Explicit => {
+                lines.push(format!("    {} -->|depends_on| {}", source, target))
+            }
+            _ => lines.push(format!("    {} --> {}", source, target)),
+        }
+    }
+    if let Some(id) = ids.get(subgraph.resource.as_str()) {
+        lines.push("    classDef selected stroke-width:3px".to_string());
+        lines.push(format!("    class {} selected", id));
+    }
+    lines.join("\n")
+}
+
 /// Generate refactoring suggestions
 pub fn suggest_refactoring(
     analysis: &TerraformAnalysis,
@@ -917,6 +1324,8 @@ pub fn suggest_refactoring(
                     .collect();
 
                 if !affected_resources.is_empty() {
+                    let module_call = group.name.replace('-', "_");
+                    let generated_hcl = generate_moved_blocks(&module_call, &affected_resources);
                     suggestions.push(RefactoringSuggestion {
                         suggestion_type: RefactoringType::ExtractSubmodule,
                         priority: IssueSeverity::Warning,
@@ -926,7 +1335,7 @@ pub fn suggest_refactoring(
                         ),
                         affected_resources: affected_resources.clone(),
                         proposed_structure: Some(ProposedModuleStructure {
-                            module_name: format!("modules/{}", group.name.replace('-', "_")),
+                            module_name: format!("modules/{}", module_call),
                             resources: affected_resources,
                             variables: Vec::new(),
                             outputs: Vec::new(),
@@ -936,9 +1345,13 @@ pub fn suggest_refactoring(
                             "2. Move related resources to new module".to_string(),
                             "3. Create variables.tf for required inputs".to_string(),
                             "4. Create outputs.tf for values needed by other resources".to_string(),
-                            "5. Add 'moved' blocks to preserve state".to_string(),
+                            format!(
+                                "5. Call the module as module \"{}\" and add the generated 'moved' blocks to preserve state",
+                                module_call
+                            ),
                             "6. Run terraform plan to verify no changes".to_string(),
                         ],
+                        generated_hcl: Some(generated_hcl),
                     });
                 }
             }
@@ -975,6 +1388,7 @@ pub fn suggest_refactoring(
                     "4. Forward only necessary outputs".to_string(),
                     "5. Update callers to use wrapper module".to_string(),
                 ],
+                generated_hcl: None,
             });
         }
     }
@@ -1009,6 +1423,7 @@ pub fn suggest_refactoring(
                 "3. Include example values where helpful".to_string(),
                 "4. Run terraform-docs to generate documentation".to_string(),
             ],
+            generated_hcl: None,
         });
     }
 
@@ -1029,12 +1444,27 @@ pub fn suggest_refactoring(
                 "3. Use module composition instead of nesting".to_string(),
                 "4. Maintain visibility of resource details".to_string(),
             ],
+            generated_hcl: None,
         });
     }
 
     suggestions
 }
 
+/// Generate `moved` blocks that relocate root resources into a module call
+pub fn generate_moved_blocks(module_call: &str, resources: &[String]) -> String {
+    resources
+        .iter()
+        .map(|address| {
+            format!(
+                "moved {{\n  from = {}\n  to   = module.{}.{}\n}}\n",
+                address, module_call, address
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 // ==================== Future Architect Guideline Checks ====================
 
 /// Check Terraform configurations against Future Architect guidelines
@@ -1082,6 +1512,8 @@ pub fn check_guidelines(
             result.providers_missing_version.push(provider.name.clone());
         }
     }
+    result.loose_provider_constraints = find_loose_provider_constraints(&analysis.providers);
+    result.undeclared_providers = find_undeclared_providers(file_contents);
 
     // Check file contents for patterns
     let mut has_aws_provider = false;
@@ -1189,30 +1621,850 @@ fn check_count_usage(content: &str, filename: &str, warnings: &mut Vec<CountUsag
     }
 }
 
-/// Check for hardcoded secrets in content
-fn check_secrets(content: &str, filename: &str, detections: &mut Vec<SecretDetection>) {
-    for (line_num, line) in content.lines().enumerate() {
-        // Skip comments
-        let trimmed = line.trim();
-        if trimmed.starts_with('#') || trimmed.starts_with("//") {
-            continue;
-        }
+/// Find literal values (strings and numbers) assigned in several resources.
+///
+/// Booleans, `null`, `0`/`1`, and interpolated strings are never reported.
+/// Results are sorted by descending occurrence count, then value.
+pub fn find_hardcoded_values(file_contents: &HashMap<String, String>) -> Vec<HardcodedValue> {
+    // literal -> (occurrences, resources, files)
+    let mut literals: HashMap<String, (usize, HashSet<String>, HashSet<String>)> = HashMap::new();
 
-        for (pattern_name, regex) in SECRET_PATTERNS.iter() {
-            if regex.is_match(line) {
-                detections.push(SecretDetection {
-                    file: filename.to_string(),
-                    line: line_num + 1,
-                    pattern: pattern_name.to_string(),
-                    severity: if *pattern_name == "Private Key" || pattern_name.contains("Secret") {
-                        "critical".to_string()
-                    } else {
-                        "high".to_string()
-                    },
-                });
+    for (filename, content) in file_contents {
+        let mut current_resource: Option<String> = None;
+        for line in hcl_lines(content) {
+            if line.depth == 0 {
+                current_resource = RESOURCE_HEADER_REGEX
+                    .captures(line.text)
+                    .map(|cap| format!("{}.{}", &cap[1], &cap[2]));
+            } else if let Some(resource) = &current_resource {
+                if let Some(cap) = LITERAL_ASSIGNMENT_REGEX.captures(line.text) {
+                    let literal = &cap[1];
+                    if !STRUCTURAL_LITERALS.contains(&literal) && !literal.contains("${") {
+                        let entry = literals.entry(literal.to_string()).or_default();
+                        entry.0 += 1;
+                        entry.1.insert(resource.clone());
+                        entry.2.insert(filename.clone());
+                    }
+                }
+            }
+
+            if line.depth_after == 0 {
+                current_resource = None;
             }
         }
     }
+
+    let mut values: Vec<HardcodedValue> = literals
+        .into_iter()
+        .filter(|(_, (_, resources, _))| resources.len() >= MIN_HARDCODED_RESOURCES)
+        .map(|(value, (occurrences, resources, files))| {
+            let mut resources: Vec<String> = resources.into_iter().collect();
+            resources.sort();
+            let mut files: Vec<String> = files.into_iter().collect();
+            files.sort();
+            HardcodedValue {
+                value,
+                occurrences,
+                resources,
+                files,
+            }
+        })
+        .collect();
+    values.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then_with(|| a.value.cmp(&b.value))
+    });
+    values
+}
+
+/// Every `module` block with a `source`, sorted by file, then line
+pub fn collect_module_sources(file_contents: &HashMap<String, String>) -> Vec<ModuleSourceRef> {
+    let mut sources = Vec::new();
+
+    for (filename, content) in file_contents {
+        // (module name, header line, source, version)
+        let mut current: Option<(String, usize, Option<String>, Option<String>)> = None;
+        for line in hcl_lines(content) {
+            if line.depth == 0 {
+                current = MODULE_HEADER_REGEX
+                    .captures(line.text)
+                    .map(|cap| (cap[1].to_string(), line.number, None, None));
+            } else if line.depth == 1 {
+                if let Some((_, _, source, version)) = current.as_mut() {
+                    if let Some(cap) = MODULE_SOURCE_REGEX.captures(line.text) {
+                        *source = Some(cap[1].to_string());
+                    }
+                    if let Some(cap) = VERSION_ATTRIBUTE_REGEX.captures(line.text) {
+                        *version = Some(cap[1].trim_matches('"').to_string());
+                    }
+                }
+            }
+
+            if line.depth_after == 0 {
+                if let Some((module, line, Some(source), version)) = current.take() {
+                    sources.push(ModuleSourceRef {
+                        module,
+                        source,
+                        version,
+                        file: filename.clone(),
+                        line,
+                    });
+                }
+            }
+        }
+    }
+
+    sources.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line)));
+    sources
+}
+
+/// Find registry module calls without a `version` and git module sources without a `?ref=` pin.
+///
+/// Local paths and other source types (archives, buckets) are not reported.
+/// Results are sorted by file, then line.
+pub fn find_unpinned_module_sources(
+    file_contents: &HashMap<String, String>,
+) -> Vec<UnpinnedModuleSource> {
+    collect_module_sources(file_contents)
+        .into_iter()
+        .filter_map(|module| {
+            let reason = module_source_pin_issue(&module.source, module.version.is_some())?;
+            Some(UnpinnedModuleSource {
+                module: module.module,
+                source: module.source,
+                file: module.file,
+                line: module.line,
+                reason: reason.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Find pre-0.12 constructs that Terraform still accepts but has replaced:
+/// interpolation-only strings, `terraform.env`, quoted or bare collection
+/// type constraints and the `list()`/`map()` functions.
+///
+/// Heredoc bodies and comments are skipped. Results are sorted by file, then line.
+pub fn find_deprecated_syntax(file_contents: &HashMap<String, String>) -> Vec<DeprecatedSyntax> {
+    let mut found = Vec::new();
+
+    for (filename, content) in sorted_files(file_contents) {
+        for HclLine {
+            number, text: line, ..
+        } in hcl_lines(content)
+        {
+            let mut push = |pattern: &str, text: &str, suggestion: String| {
+                found.push(DeprecatedSyntax {
+                    file: filename.clone(),
+                    line: number,
+                    pattern: pattern.to_string(),
+                    found: text.to_string(),
+                    suggestion,
+                });
+            };
+
+            for cap in INTERPOLATION_ONLY_REGEX.captures_iter(line) {
+                push(
+                    "interpolation-only expression",
+                    &cap[0],
+                    cap[1].trim().to_string(),
+                );
+            }
+
+            if TERRAFORM_ENV_REGEX.is_match(line) {
+                push(
+                    "terraform.env",
+                    "terraform.env",
+                    "terraform.workspace".to_string(),
+                );
+            }
+
+            if let Some(cap) = QUOTED_TYPE_REGEX.captures(line) {
+                let suggestion = match &cap[1] {
+                    "list" | "map" => format!("{}(string)", &cap[1]),
+                    other => other.to_string(),
+                };
+                push(
+                    "quoted type constraint",
+                    &format!("\"{}\"", &cap[1]),
+                    suggestion,
+                );
+            } else if let Some(cap) = BARE_COLLECTION_TYPE_REGEX.captures(line) {
+                push("bare collection type", &cap[1], format!("{}(any)", &cap[1]));
+            }
+
+            for cap in COLLECTION_CALL_REGEX.captures_iter(&strip_strings(line)) {
+                if TYPE_KEYWORDS.contains(&&cap[2]) {
+                    continue;
+                }
+                let suggestion = match &cap[1] {
+                    "list" => "tolist([...])",
+                    _ => "tomap({...})",
+                };
+                push(
+                    "collection function",
+                    &format!("{}(...)", &cap[1]),
+                    suggestion.to_string(),
+                );
+            }
+        }
+    }
+
+    found
+}
+
+/// `line` with the contents of its quoted strings removed
+fn strip_strings(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in line.chars() {
+        if in_string {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => {
+                    in_string = false;
+                    stripped.push(c);
+                }
+                _ => escaped = false,
+            }
+        } else {
+            if c == '"' {
+                in_string = true;
+            }
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// A line of HCL outside comments and heredoc bodies
+pub(crate) struct HclLine<'a> {
+    /// 1-based line number
+    pub number: usize,
+    pub text: &'a str,
+    /// Block depth at the start of the line
+    pub depth: usize,
+    /// Block depth after the line's braces
+    pub depth_after: usize,
+}
+
+/// Iterator over the [`HclLine`]s of a file, see [`hcl_lines`]
+pub(crate) struct HclLines<'a> {
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    depth: usize,
+    heredoc: Option<String>,
+}
+
+impl<'a> Iterator for HclLines<'a> {
+    type Item = HclLine<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, text) in self.lines.by_ref() {
+            let trimmed = text.trim();
+            if let Some(marker) = &self.heredoc {
+                if trimmed == marker {
+                    self.heredoc = None;
+                }
+                continue;
+            }
+            if trimmed.starts_with('#') || trimmed.starts_with("//") {
+                continue;
+            }
+
+            let depth = self.depth;
+            let (opens, closes) = block_braces(text);
+            self.depth = (depth + opens).saturating_sub(closes);
+            self.heredoc = HEREDOC_START_REGEX
+                .captures(text)
+                .map(|cap| cap[1].to_string());
+            return Some(HclLine {
+                number: index + 1,
+                text,
+                depth,
+                depth_after: self.depth,
+            });
+        }
+        None
+    }
+}
+
+/// The lines of `content` with their block depth. Comment lines and heredoc
+/// bodies are skipped, and braces inside quoted strings, `${...}`/`%{...}`
+/// templates and trailing comments are not counted, so an interpolation or
+/// an inline JSON policy does not shift block boundaries.
+pub(crate) fn hcl_lines(content: &str) -> HclLines<'_> {
+    HclLines {
+        lines: content.lines().enumerate(),
+        depth: 0,
+        heredoc: None,
+    }
+}
+
+/// Block braces opened and closed on `line`, ignoring strings and comments
+fn block_braces(line: &str) -> (usize, usize) {
+    let (mut opens, mut closes) = (0, 0);
+    let mut in_string = false;
+    // Unclosed braces of each template interpolation the scan is inside
+    let mut templates: Vec<usize> = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                '$' | '%' if chars.peek() == Some(&c) => {
+                    chars.next();
+                }
+                '$' | '%' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    templates.push(0);
+                    in_string = false;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '#' if templates.is_empty() => break,
+            '/' if templates.is_empty() && chars.peek() == Some(&'/') => break,
+            '{' => match templates.last_mut() {
+                Some(open) => *open += 1,
+                None => opens += 1,
+            },
+            '}' => match templates.last_mut() {
+                Some(0) => {
+                    templates.pop();
+                    in_string = true;
+                }
+                Some(open) => *open -= 1,
+                None => closes += 1,
+            },
+            _ => {}
+        }
+    }
+    (opens, closes)
+}
+
+/// Find resource addresses, variables and outputs declared more than once
+/// across the module's files.
+///
+/// Results are sorted by kind, then address; locations by file, then line.
+pub fn find_duplicate_definitions(
+    file_contents: &HashMap<String, String>,
+) -> Vec<DuplicateDefinition> {
+    let mut duplicates: Vec<DuplicateDefinition> = collect_definitions(file_contents)
+        .into_iter()
+        .flat_map(|((kind, address), locations)| {
+            // Each module directory is its own namespace: modules may each
+            // declare the same variable or output
+            let mut by_module: BTreeMap<&Path, Vec<DefinitionLocation>> = BTreeMap::new();
+            for location in locations {
+                let module = file_contents
+                    .get_key_value(&location.file)
+                    .and_then(|(file, _)| Path::new(file).parent())
+                    .unwrap_or(Path::new(""));
+                by_module.entry(module).or_default().push(location);
+            }
+            by_module
+                .into_values()
+                .filter(|locations| locations.len() > 1)
+                .map(move |locations| DuplicateDefinition {
+                    kind: kind.clone(),
+                    address: address.clone(),
+                    locations,
+                })
+        })
+        .collect();
+    duplicates.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then_with(|| a.address.cmp(&b.address))
+            .then_with(|| a.locations[0].file.cmp(&b.locations[0].file))
+    });
+    duplicates
+}
+
+/// Find where `query` is declared across the module's files.
+///
+/// `query` is a resource address (`aws_instance.web`; instance keys such as
+/// `[0]` are ignored), `var.<name>`, `output.<name>`, or a bare name, which
+/// matches both variables and outputs.
+pub fn locate_definition(file_contents: &HashMap<String, String>, query: &str) -> DefinitionLookup {
+    let query = query.trim();
+    let wanted: Vec<(&str, String)> = if let Some(name) = query.strip_prefix("var.") {
+        vec![("variable", name.to_string())]
+    } else if let Some(name) = query.strip_prefix("output.") {
+        vec![("output", name.to_string())]
+    } else if query.contains('.') {
+        let address = crate::terraform::resource_list::address_segments(query).join(".");
+        vec![("resource", address)]
+    } else {
+        vec![
+            ("variable", query.to_string()),
+            ("output", query.to_string()),
+        ]
+    };
+
+    let mut definitions = collect_definitions(file_contents);
+    let mut matches: Vec<DefinitionMatch> = wanted
+        .into_iter()
+        .flat_map(|(kind, address)| {
+            let locations = definitions
+                .remove(&(kind.to_string(), address.clone()))
+                .unwrap_or_default();
+            locations.into_iter().map(move |location| DefinitionMatch {
+                kind: kind.to_string(),
+                address: address.clone(),
+                file: location.file,
+                line: location.line,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+
+    DefinitionLookup {
+        query: query.to_string(),
+        definitions: matches,
+    }
+}
+
+/// Header locations of every resource, variable and output block, keyed by
+/// (kind, address) and sorted by file, then line
+fn collect_definitions(
+    file_contents: &HashMap<String, String>,
+) -> HashMap<(String, String), Vec<DefinitionLocation>> {
+    // (kind, address) -> locations
+    let mut definitions: HashMap<(String, String), Vec<DefinitionLocation>> = HashMap::new();
+
+    for (filename, content) in file_contents {
+        for line in hcl_lines(content) {
+            if line.depth == 0 {
+                if let Some(cap) = DEFINITION_HEADER_REGEX.captures(line.text) {
+                    let kind = &cap[1];
+                    let address = match (kind, cap.get(3)) {
+                        ("resource", Some(name)) => format!("{}.{}", &cap[2], name.as_str()),
+                        ("resource", None) => String::new(),
+                        _ => cap[2].to_string(),
+                    };
+                    if !address.is_empty() {
+                        definitions
+                            .entry((kind.to_string(), address))
+                            .or_default()
+                            .push(DefinitionLocation {
+                                file: filename.clone(),
+                                line: line.number,
+                            });
+                    }
+                }
+            }
+        }
+    }
+
+    for locations in definitions.values_mut() {
+        locations.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line)));
+    }
+    definitions
+}
+
+/// A `module` block calling a local source
+struct LocalModuleCall {
+    name: String,
+    source: String,
+    file: String,
+    line: usize,
+    /// Arguments passed to the module, with their lines
+    arguments: Vec<(String, usize)>,
+}
+
+/// Variables (and whether they have a default) and outputs a module declares
+#[derive(Default)]
+struct ModuleInterface {
+    variables: HashMap<String, bool>,
+    outputs: HashSet<String>,
+}
+
+/// Check every local module call (`source = "./..."` or `"../..."`) against
+/// the variables and outputs the module declares.
+///
+/// `load_module` receives the module directory relative to the project root
+/// and returns its `.tf` file contents, or `None` when it can't be read, in
+/// which case the call is skipped. Results are sorted by file, then line.
+pub fn find_module_wiring_issues(
+    file_contents: &HashMap<String, String>,
+    load_module: impl Fn(&str) -> Option<HashMap<String, String>>,
+) -> Vec<ModuleWiringIssue> {
+    let mut issues = Vec::new();
+
+    // Module names and output references are scoped to the caller's directory
+    let mut directories: HashMap<&str, Vec<(&String, &String)>> = HashMap::new();
+    for (filename, content) in file_contents {
+        let directory = filename.rsplit_once('/').map_or("", |(dir, _)| dir);
+        directories
+            .entry(directory)
+            .or_default()
+            .push((filename, content));
+    }
+
+    for (directory, files) in directories {
+        let mut interfaces: HashMap<String, (String, ModuleInterface)> = HashMap::new();
+
+        for call in files
+            .iter()
+            .flat_map(|(filename, content)| local_module_calls(filename, content))
+        {
+            let module_dir = resolve_module_path(directory, &call.source);
+            let Some(module_files) = load_module(&module_dir) else {
+                continue;
+            };
+            let interface = module_interface(&module_files);
+            let issue = |kind, key: &str, line| ModuleWiringIssue {
+                module: call.name.clone(),
+                source: call.source.clone(),
+                kind,
+                key: key.to_string(),
+                file: call.file.clone(),
+                line,
+            };
+
+            for (argument, line) in &call.arguments {
+                if !interface.variables.contains_key(argument) {
+                    issues.push(issue(ModuleWiringIssueKind::UnknownInput, argument, *line));
+                }
+            }
+            let mut missing: Vec<&String> = interface
+                .variables
+                .iter()
+                .filter(|(name, has_default)| {
+                    !**has_default && !call.arguments.iter().any(|(arg, _)| arg == *name)
+                })
+                .map(|(name, _)| name)
+                .collect();
+            missing.sort();
+            for name in missing {
+                issues.push(issue(
+                    ModuleWiringIssueKind::MissingRequiredInput,
+                    name,
+                    call.line,
+                ));
+            }
+
+            interfaces.insert(call.name.clone(), (call.source.clone(), interface));
+        }
+
+        for (filename, content) in &files {
+            for line in hcl_lines(content) {
+                for cap in MODULE_OUTPUT_REF_REGEX.captures_iter(line.text) {
+                    let Some((source, interface)) = interfaces.get(&cap[1]) else {
+                        continue;
+                    };
+                    if !interface.outputs.contains(&cap[2]) {
+                        issues.push(ModuleWiringIssue {
+                            module: cap[1].to_string(),
+                            source: source.clone(),
+                            kind: ModuleWiringIssueKind::UnknownOutput,
+                            key: cap[2].to_string(),
+                            file: filename.to_string(),
+                            line: line.number,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    issues
+}
+
+/// `module` blocks in `content` whose source is a local path
+fn local_module_calls(filename: &str, content: &str) -> Vec<LocalModuleCall> {
+    let mut calls = Vec::new();
+    // Source stays empty until the block's `source` line is seen
+    let mut current: Option<LocalModuleCall> = None;
+    for line in hcl_lines(content) {
+        if line.depth == 0 {
+            current = MODULE_HEADER_REGEX
+                .captures(line.text)
+                .map(|cap| LocalModuleCall {
+                    name: cap[1].to_string(),
+                    source: String::new(),
+                    file: filename.to_string(),
+                    line: line.number,
+                    arguments: Vec::new(),
+                });
+        } else if line.depth == 1 {
+            if let Some(call) = current.as_mut() {
+                if let Some(cap) = MODULE_SOURCE_REGEX.captures(line.text) {
+                    call.source = cap[1].to_string();
+                }
+                if let Some(cap) = ARGUMENT_REGEX.captures(line.text) {
+                    if !MODULE_META_ARGUMENTS.contains(&&cap[1]) {
+                        call.arguments.push((cap[1].to_string(), line.number));
+                    }
+                }
+            }
+        }
+
+        if line.depth_after == 0 {
+            if let Some(call) = current.take() {
+                if call.source.starts_with("./") || call.source.starts_with("../") {
+                    calls.push(call);
+                }
+            }
+        }
+    }
+    calls
+}
+
+/// Variables and outputs declared across a module's files
+fn module_interface(module_files: &HashMap<String, String>) -> ModuleInterface {
+    let mut interface = ModuleInterface::default();
+
+    for content in module_files.values() {
+        // (variable name, has default)
+        let mut variable: Option<(String, bool)> = None;
+        for line in hcl_lines(content) {
+            if line.depth == 0 {
+                if let Some(cap) = DEFINITION_HEADER_REGEX.captures(line.text) {
+                    match &cap[1] {
+                        "variable" => {
+                            variable =
+                                Some((cap[2].to_string(), INLINE_DEFAULT_REGEX.is_match(line.text)))
+                        }
+                        "output" => {
+                            interface.outputs.insert(cap[2].to_string());
+                        }
+                        _ => {}
+                    }
+                }
+            } else if line.depth == 1 {
+                if let Some((_, has_default)) = variable.as_mut() {
+                    if ARGUMENT_REGEX
+                        .captures(line.text)
+                        .is_some_and(|cap| &cap[1] == "default")
+                    {
+                        *has_default = true;
+                    }
+                }
+            }
+
+            if line.depth_after == 0 {
+                if let Some((name, has_default)) = variable.take() {
+                    interface.variables.insert(name, has_default);
+                }
+            }
+        }
+    }
+    interface
+}
+
+/// Join `source` onto the caller's directory, resolving `.` and `..`
+/// lexically; `..` past the project root is kept
+fn resolve_module_path(directory: &str, source: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in directory.split('/').chain(source.split('/')) {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Why a module source is unpinned, or `None` when it is pinned or not pinnable
+fn module_source_pin_issue(source: &str, has_version: bool) -> Option<&'static str> {
+    if source.starts_with("./") || source.starts_with("../") {
+        return None;
+    }
+
+    let is_git = source.starts_with("git::")
+        || source.starts_with("git@")
+        || source.starts_with("github.com/")
+        || source.starts_with("bitbucket.org/");
+    if is_git {
+        if source.contains("?ref=") || source.contains("&ref=") {
+            return None;
+        }
+        return Some("git source has no ?ref= pin");
+    }
+
+    // Registry addresses are <namespace>/<name>/<provider>, optionally prefixed
+    // by a hostname and followed by a //subdirectory
+    let address = source.split("//").next().unwrap_or(source);
+    let is_registry = !source.contains("::")
+        && !source.contains("://")
+        && matches!(address.split('/').count(), 3 | 4);
+    if is_registry && !has_version {
+        return Some("registry module has no version constraint");
+    }
+
+    None
+}
+
+/// Find providers with no version constraint or one without an upper bound,
+/// e.g. `>= 0` or `>= 4.0`. Suggested pins are left for the caller to fill
+/// in from the registry; results are sorted by provider name.
+pub fn find_loose_provider_constraints(
+    providers: &[TerraformProvider],
+) -> Vec<LooseProviderConstraint> {
+    let mut loose: Vec<LooseProviderConstraint> = providers
+        .iter()
+        .filter_map(|provider| {
+            let reason = provider_constraint_issue(provider.version.as_deref())?;
+            Some(LooseProviderConstraint {
+                provider: provider.name.clone(),
+                constraint: provider.version.clone(),
+                reason: reason.to_string(),
+                latest_version: None,
+                suggested_constraint: None,
+            })
+        })
+        .collect();
+    loose.sort_by(|a, b| a.provider.cmp(&b.provider));
+    loose
+}
+
+/// Find providers that a module's resources and data sources rely on but
+/// its `required_providers` blocks don't declare. Each module directory is
+/// checked against its own declarations; the built-in `terraform` provider
+/// needs none. Results are sorted by module, then provider.
+pub fn find_undeclared_providers(
+    file_contents: &HashMap<String, String>,
+) -> Vec<UndeclaredProvider> {
+    let mut declared: HashMap<&str, HashSet<String>> = HashMap::new();
+    // (module directory, provider) -> resources using it
+    let mut used: BTreeMap<(&str, String), Vec<String>> = BTreeMap::new();
+
+    for (filename, content) in sorted_files(file_contents) {
+        let module = filename.rsplit_once('/').map_or(".", |(dir, _)| dir);
+        let parser = TerraformParser::new(content.clone());
+        declared
+            .entry(module)
+            .or_default()
+            .extend(parser.parse_required_provider_names());
+
+        for resource in parser.parse_resources(filename) {
+            used.entry((module, resource.provider))
+                .or_default()
+                .push(format!("{}.{}", resource.resource_type, resource.name));
+        }
+        for cap in DATA_SOURCE_REGEX.captures_iter(content) {
+            let provider = cap[1].split('_').next().unwrap_or_default().to_string();
+            used.entry((module, provider))
+                .or_default()
+                .push(format!("data.{}.{}", &cap[1], &cap[2]));
+        }
+    }
+
+    used.into_iter()
+        .filter(|((module, provider), _)| {
+            provider != "terraform"
+                && !declared
+                    .get(module)
+                    .is_some_and(|names| names.contains(provider))
+        })
+        .map(|((module, provider), resources)| UndeclaredProvider {
+            provider,
+            module: module.to_string(),
+            resources,
+        })
+        .collect()
+}
+
+/// Why a provider version constraint is too loose, or `None` when it is bounded
+fn provider_constraint_issue(constraint: Option<&str>) -> Option<&'static str> {
+    let parts: Vec<&str> = constraint
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.is_empty() {
+        return Some("no version constraint");
+    }
+
+    // `~>`, `<`, `<=`, `=` and bare versions all cap the version
+    let has_upper_bound = parts
+        .iter()
+        .any(|part| !(part.starts_with(">") || part.starts_with("!=")));
+    if has_upper_bound {
+        return None;
+    }
+
+    let major_zero_floor = parts.iter().any(|part| {
+        part.trim_start_matches(['>', '=', ' '])
+            .split('.')
+            .next()
+            .is_some_and(|major| major.trim() == "0")
+    });
+    if major_zero_floor {
+        Some("lower bound of major version 0 accepts every release")
+    } else {
+        Some("no upper bound; a new major version is picked up without review")
+    }
+}
+
+/// `~> major.minor` pin for the given release, e.g. `5.31.0` -> `~> 5.31`
+pub fn suggest_provider_pin(latest_version: &str) -> String {
+    let mut parts = latest_version.trim_start_matches('v').split('.');
+    let major = parts.next().unwrap_or("0");
+    let minor = parts.next().unwrap_or("0");
+    format!("~> {}.{}", major, minor)
+}
+
+/// Check for hardcoded secrets in content
+fn check_secrets(content: &str, filename: &str, detections: &mut Vec<SecretDetection>) {
+    for (line_num, line) in content.lines().enumerate() {
+        // Skip comments
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || trimmed.starts_with("//") {
+            continue;
+        }
+
+        for pattern_name in matching_secret_patterns(line) {
+            detections.push(SecretDetection {
+                file: filename.to_string(),
+                line: line_num + 1,
+                pattern: pattern_name.to_string(),
+                severity: secret_severity(pattern_name).to_string(),
+            });
+        }
+    }
+}
+
+/// Names of the secret patterns matching `line` (an HCL `key = "value"` line)
+pub fn matching_secret_patterns(line: &str) -> impl Iterator<Item = &'static str> + '_ {
+    SECRET_PATTERNS
+        .iter()
+        .filter(move |(_, regex)| regex.is_match(line))
+        .map(|(pattern_name, _)| *pattern_name)
+}
+
+/// Severity reported for a match of the named secret pattern
+pub fn secret_severity(pattern_name: &str) -> &'static str {
+    if pattern_name == "Private Key" || pattern_name.contains("Secret") {
+        "critical"
+    } else {
+        "high"
+    }
 }
 
 /// Calculate compliance score based on guideline violations
@@ -1262,7 +2514,72 @@ fn calculate_compliance_score(result: &GuidelineCheckResult, analysis: &Terrafor
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::terraform::model::{TerraformOutput, TerraformProvider, TerraformVariable};
+    use crate::terraform::model::{TerraformOutput, TerraformVariable};
+
+    #[test]
+    fn test_find_undeclared_providers() {
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "versions.tf".to_string(),
+            r#"
+terraform {
+  required_providers {
+    aws = {
+      source  = "hashicorp/aws"
+      version = "~> 5.0"
+    }
+  }
+}
+"#
+            .to_string(),
+        );
+        file_contents.insert(
+            "main.tf".to_string(),
+            r#"
+resource "aws_s3_bucket" "logs" {
+  bucket = "logs"
+}
+
+resource "google_compute_instance" "vm" {
+  name = "vm"
+}
+
+resource "google_storage_bucket" "assets" {
+  name = "assets"
+}
+
+data "google_project" "current" {}
+
+resource "terraform_data" "marker" {}
+"#
+            .to_string(),
+        );
+
+        let undeclared = find_undeclared_providers(&file_contents);
+        assert_eq!(
+            undeclared,
+            vec![UndeclaredProvider {
+                provider: "google".to_string(),
+                module: ".".to_string(),
+                resources: vec![
+                    "google_compute_instance.vm".to_string(),
+                    "google_storage_bucket.assets".to_string(),
+                    "data.google_project.current".to_string(),
+                ],
+            }]
+        );
+
+        // Each module is checked against its own required_providers
+        file_contents.insert(
+            "modules/network/main.tf".to_string(),
+            "resource \"aws_vpc\" \"this\" {}\n".to_string(),
+        );
+        let undeclared = find_undeclared_providers(&file_contents);
+        assert_eq!(undeclared.len(), 2);
+        assert_eq!(undeclared[1].module, "modules/network");
+        assert_eq!(undeclared[1].provider, "aws");
+        assert_eq!(undeclared[1].resources, vec!["aws_vpc.this"]);
+    }
 
     #[test]
     fn test_check_guidelines_missing_type() {
@@ -1276,16 +2593,24 @@ mod tests {
                     description: Some("Has type".to_string()),
                     type_: Some("string".to_string()),
                     default: None,
+                    sensitive: false,
+                    effective: None,
                 },
                 TerraformVariable {
                     name: "var_without_type".to_string(),
                     description: Some("No type".to_string()),
                     type_: None,
                     default: None,
+                    sensitive: false,
+                    effective: None,
                 },
             ],
             outputs: vec![],
             providers: vec![],
+            required_version: None,
+            version_compatibility: None,
+            remote_state_dependencies: Vec::new(),
+            state_encryption: None,
         };
         let file_contents = HashMap::new();
         let result = check_guidelines(&analysis, &file_contents);
@@ -1306,12 +2631,16 @@ mod tests {
                     description: Some("Has description".to_string()),
                     type_: Some("string".to_string()),
                     default: None,
+                    sensitive: false,
+                    effective: None,
                 },
                 TerraformVariable {
                     name: "var_without_desc".to_string(),
                     description: None,
                     type_: Some("string".to_string()),
                     default: None,
+                    sensitive: false,
+                    effective: None,
                 },
             ],
             outputs: vec![
@@ -1327,6 +2656,10 @@ mod tests {
                 },
             ],
             providers: vec![],
+            required_version: None,
+            version_compatibility: None,
+            remote_state_dependencies: Vec::new(),
+            state_encryption: None,
         };
         let file_contents = HashMap::new();
         let result = check_guidelines(&analysis, &file_contents);
@@ -1349,12 +2682,18 @@ mod tests {
                 TerraformProvider {
                     name: "aws".to_string(),
                     version: Some("~> 5.0".to_string()),
+                    aliases: vec![],
                 },
                 TerraformProvider {
                     name: "random".to_string(),
                     version: None,
+                    aliases: vec![],
                 },
             ],
+            required_version: None,
+            version_compatibility: None,
+            remote_state_dependencies: Vec::new(),
+            state_encryption: None,
         };
         let file_contents = HashMap::new();
         let result = check_guidelines(&analysis, &file_contents);
@@ -1364,16 +2703,64 @@ mod tests {
     }
 
     #[test]
-    fn test_compliance_score_calculation() {
-        let analysis = TerraformAnalysis {
-            project_directory: "/test".to_string(),
-            file_count: 1,
-            resources: vec![],
-            variables: vec![TerraformVariable {
+    fn test_find_loose_provider_constraints() {
+        let provider = |name: &str, version: Option<&str>| TerraformProvider {
+            name: name.to_string(),
+            version: version.map(str::to_string),
+            aliases: vec![],
+        };
+        let providers = vec![
+            provider("aws", Some("~> 5.0")),
+            provider("random", Some(">= 0")),
+            provider("google", Some(">= 4.0")),
+            provider("azurerm", Some(">= 3.0, < 4.0")),
+            provider("null", None),
+            provider("tls", Some("4.0.5")),
+        ];
+
+        let loose = find_loose_provider_constraints(&providers);
+        let flagged: Vec<(&str, &str)> = loose
+            .iter()
+            .map(|c| (c.provider.as_str(), c.reason.as_str()))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (
+                    "google",
+                    "no upper bound; a new major version is picked up without review"
+                ),
+                ("null", "no version constraint"),
+                (
+                    "random",
+                    "lower bound of major version 0 accepts every release"
+                ),
+            ]
+        );
+        assert_eq!(loose[0].constraint.as_deref(), Some(">= 4.0"));
+        assert!(loose.iter().all(|c| c.suggested_constraint.is_none()));
+    }
+
+    #[test]
+    fn test_suggest_provider_pin() {
+        assert_eq!(suggest_provider_pin("5.31.0"), "~> 5.31");
+        assert_eq!(suggest_provider_pin("v3.6.2"), "~> 3.6");
+        assert_eq!(suggest_provider_pin("2"), "~> 2.0");
+    }
+
+    #[test]
+    fn test_compliance_score_calculation() {
+        let analysis = TerraformAnalysis {
+            project_directory: "/test".to_string(),
+            file_count: 1,
+            resources: vec![],
+            variables: vec![TerraformVariable {
                 name: "good_var".to_string(),
                 description: Some("Good variable".to_string()),
                 type_: Some("string".to_string()),
                 default: None,
+                sensitive: false,
+                effective: None,
             }],
             outputs: vec![TerraformOutput {
                 name: "good_output".to_string(),
@@ -1383,7 +2770,12 @@ mod tests {
             providers: vec![TerraformProvider {
                 name: "aws".to_string(),
                 version: Some("~> 5.0".to_string()),
+                aliases: vec![],
             }],
+            required_version: None,
+            version_compatibility: None,
+            remote_state_dependencies: Vec::new(),
+            state_encryption: None,
         };
         let file_contents = HashMap::new();
         let result = check_guidelines(&analysis, &file_contents);
@@ -1402,18 +2794,21 @@ mod tests {
                     name: "main".to_string(),
                     file: "vpc.tf".to_string(),
                     provider: "aws".to_string(),
+                    provider_alias: None,
                 },
                 crate::terraform::model::TerraformResource {
                     resource_type: "aws_subnet".to_string(),
                     name: "public".to_string(),
                     file: "vpc.tf".to_string(),
                     provider: "aws".to_string(),
+                    provider_alias: None,
                 },
                 crate::terraform::model::TerraformResource {
                     resource_type: "aws_instance".to_string(),
                     name: "web".to_string(),
                     file: "compute.tf".to_string(),
                     provider: "aws".to_string(),
+                    provider_alias: None,
                 },
             ],
             variables: vec![
@@ -1422,12 +2817,16 @@ mod tests {
                     description: Some("VPC CIDR block".to_string()),
                     type_: Some("string".to_string()),
                     default: None,
+                    sensitive: false,
+                    effective: None,
                 },
                 TerraformVariable {
                     name: "instance_type".to_string(),
                     description: None,
                     type_: Some("string".to_string()),
                     default: Some(serde_json::json!("t3.micro")),
+                    sensitive: false,
+                    effective: None,
                 },
             ],
             outputs: vec![TerraformOutput {
@@ -1438,7 +2837,12 @@ mod tests {
             providers: vec![TerraformProvider {
                 name: "aws".to_string(),
                 version: Some("~> 5.0".to_string()),
+                aliases: vec![],
             }],
+            required_version: None,
+            version_compatibility: None,
+            remote_state_dependencies: Vec::new(),
+            state_encryption: None,
         }
     }
 
@@ -1487,12 +2891,990 @@ mod tests {
         assert!(!cohesion.resource_type_groups.is_empty());
     }
 
+    #[test]
+    fn test_analysis_output_order_is_deterministic() {
+        // Fresh maps get fresh hash seeds, so each run iterates files differently
+        let file_contents = || -> HashMap<String, String> {
+            [
+                (
+                    "vpc.tf",
+                    "resource \"aws_vpc\" \"main\" {}\nresource \"aws_subnet\" \"public\" {\n  vpc_id = aws_vpc.main.id\n  count  = 2\n}\n",
+                ),
+                (
+                    "compute.tf",
+                    "resource \"aws_instance\" \"web\" {\n  subnet_id = aws_subnet.public[0].id\n  depends_on = [aws_vpc.main]\n}\n",
+                ),
+                ("variables.tf", "variable \"vpc_cidr\" {}\n"),
+                ("outputs.tf", "output \"vpc_id\" {\n  value = aws_vpc.main.id\n}\n"),
+                (
+                    "modules.tf",
+                    "module \"b\" {\n  source = \"terraform-aws-modules/vpc/aws\"\n}\nmodule \"a\" {\n  source = \"terraform-aws-modules/eks/aws\"\n}\n",
+                ),
+            ]
+            .into_iter()
+            .map(|(name, content)| (name.to_string(), content.to_string()))
+            .collect()
+        };
+
+        let run = |reverse: bool| {
+            let mut analysis = create_test_analysis();
+            if reverse {
+                analysis.resources.reverse();
+            }
+            let files = file_contents();
+            let health = analyze_module_health(&analysis, &files, &[], None);
+            let graph = build_dependency_graph(&analysis, &files);
+            (
+                serde_json::to_string(&health).unwrap(),
+                serde_json::to_string(&graph).unwrap(),
+            )
+        };
+
+        let (first_health, first_graph) = run(false);
+        for _ in 0..5 {
+            assert_eq!(run(true), (first_health.clone(), first_graph.clone()));
+        }
+
+        let analysis = create_test_analysis();
+        let graph = build_dependency_graph(&analysis, &file_contents());
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["aws_instance.web", "aws_subnet.public", "aws_vpc.main"]
+        );
+        let groups = analyze_cohesion(&analysis).resource_type_groups;
+        assert!(groups.windows(2).all(|pair| pair[0].name <= pair[1].name));
+    }
+
+    #[test]
+    fn test_find_hardcoded_values() {
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "compute.tf".to_string(),
+            r#"
+resource "aws_instance" "web" {
+  availability_zone = "us-east-1a"
+  instance_type     = "t3.micro"
+  monitoring        = true
+  count             = 1
+}
+
+resource "aws_instance" "worker" {
+  availability_zone = "us-east-1a"
+  instance_type     = "m5.large"
+  monitoring        = true
+  count             = 1
+}
+"#
+            .to_string(),
+        );
+        file_contents.insert(
+            "storage.tf".to_string(),
+            r#"
+resource "aws_ebs_volume" "data" {
+  availability_zone = "us-east-1a"
+  size              = 40
+  name              = "${var.prefix}-data"
+}
+"#
+            .to_string(),
+        );
+
+        let values = find_hardcoded_values(&file_contents);
+
+        assert_eq!(values.len(), 1);
+        let region = &values[0];
+        assert_eq!(region.value, "\"us-east-1a\"");
+        assert_eq!(region.occurrences, 3);
+        assert_eq!(
+            region.resources,
+            vec![
+                "aws_ebs_volume.data",
+                "aws_instance.web",
+                "aws_instance.worker"
+            ]
+        );
+        assert_eq!(region.files, vec!["compute.tf", "storage.tf"]);
+    }
+
+    #[test]
+    fn test_hardcoded_values_reported_as_issues() {
+        let analysis = create_test_analysis();
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "network.tf".to_string(),
+            r#"
+resource "aws_subnet" "a" {
+  cidr_block = "10.0.0.0/16"
+}
+
+resource "aws_subnet" "b" {
+  cidr_block = "10.0.0.0/16"
+}
+"#
+            .to_string(),
+        );
+
+        let health = analyze_module_health(&analysis, &file_contents, &[], None);
+        let hardcoded: Vec<_> = health
+            .issues
+            .iter()
+            .filter(|i| matches!(i.category, IssueCategory::HardcodedValue))
+            .collect();
+        assert_eq!(hardcoded.len(), 1);
+        assert!(hardcoded[0].message.contains("10.0.0.0/16"));
+        assert_eq!(hardcoded[0].file.as_deref(), Some("network.tf"));
+    }
+
+    #[test]
+    fn test_find_unpinned_module_sources() {
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "modules.tf".to_string(),
+            r#"
+module "vpc" {
+  source = "terraform-aws-modules/vpc/aws"
+}
+
+module "vpc_pinned" {
+  source  = "terraform-aws-modules/vpc/aws"
+  version = "~> 5.0"
+}
+
+module "network" {
+  source = "git::https://github.com/example/network.git"
+}
+
+module "network_pinned" {
+  source = "git::https://github.com/example/network.git?ref=v1.2.0"
+}
+
+module "local" {
+  source = "./modules/local"
+}
+"#
+            .to_string(),
+        );
+
+        let unpinned = find_unpinned_module_sources(&file_contents);
+
+        let modules: Vec<_> = unpinned.iter().map(|u| u.module.as_str()).collect();
+        assert_eq!(modules, vec!["vpc", "network"]);
+        assert_eq!(unpinned[0].file, "modules.tf");
+        assert_eq!(unpinned[0].line, 2);
+        assert!(unpinned[0].reason.contains("version"));
+        assert!(unpinned[1].reason.contains("?ref="));
+    }
+
+    #[test]
+    fn test_unpinned_module_sources_reported_as_issues() {
+        let analysis = create_test_analysis();
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "main.tf".to_string(),
+            r#"
+module "iam" {
+  source = "terraform-aws-modules/iam/aws//modules/iam-role"
+}
+"#
+            .to_string(),
+        );
+
+        let health = analyze_module_health(&analysis, &file_contents, &[], None);
+        let unpinned: Vec<_> = health
+            .issues
+            .iter()
+            .filter(|i| matches!(i.category, IssueCategory::UnpinnedModuleSource))
+            .collect();
+        assert_eq!(unpinned.len(), 1);
+        assert!(unpinned[0].message.contains("'iam'"));
+        assert_eq!(unpinned[0].file.as_deref(), Some("main.tf"));
+        assert!(
+            health
+                .recommendations
+                .iter()
+                .any(|r| r.contains("Pin module sources"))
+        );
+    }
+
+    #[test]
+    fn test_find_deprecated_syntax() {
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "main.tf".to_string(),
+            r#"
+variable "names" {
+  type = "list"
+}
+
+variable "zones" {
+  type = list
+}
+
+variable "settings" {
+  type = object({
+    tags = map(string)
+  })
+}
+
+resource "aws_instance" "web" {
+  ami   = "${var.ami}"
+  name  = "${var.prefix}-web"
+  tags  = map("Env", terraform.env)
+  zones = list("a", "b")
+  # subnet = "${var.subnet}"
+  user_data = <<EOF
+{"ami": "${var.ami}"}
+EOF
+  description = "list(s) of zones"
+}
+"#
+            .to_string(),
+        );
+
+        let deprecated = find_deprecated_syntax(&file_contents);
+        let found: Vec<_> = deprecated
+            .iter()
+            .map(|d| (d.line, d.found.as_str(), d.suggestion.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, "\"list\"", "list(string)"),
+                (7, "list", "list(any)"),
+                (17, "\"${var.ami}\"", "var.ami"),
+                (19, "terraform.env", "terraform.workspace"),
+                (19, "map(...)", "tomap({...})"),
+                (20, "list(...)", "tolist([...])"),
+            ]
+        );
+        assert_eq!(deprecated[2].pattern, "interpolation-only expression");
+        assert_eq!(deprecated[2].file, "main.tf");
+    }
+
+    #[test]
+    fn test_deprecated_syntax_reported_as_issues() {
+        let analysis = create_test_analysis();
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "outputs.tf".to_string(),
+            r#"
+output "vpc_id" {
+  value = "${aws_vpc.main.id}"
+}
+"#
+            .to_string(),
+        );
+
+        let health = analyze_module_health(&analysis, &file_contents, &[], None);
+        let deprecated: Vec<_> = health
+            .issues
+            .iter()
+            .filter(|i| matches!(i.category, IssueCategory::DeprecatedSyntax))
+            .collect();
+        assert_eq!(deprecated.len(), 1);
+        assert_eq!(deprecated[0].severity, IssueSeverity::Info);
+        assert_eq!(deprecated[0].file.as_deref(), Some("outputs.tf"));
+        assert_eq!(deprecated[0].line, Some(3));
+        assert!(
+            deprecated[0]
+                .message
+                .contains("Use `aws_vpc.main.id` instead")
+        );
+        assert!(
+            health
+                .recommendations
+                .iter()
+                .any(|r| r.contains("Modernize deprecated syntax"))
+        );
+    }
+
+    fn network_module() -> HashMap<String, String> {
+        let mut module = HashMap::new();
+        module.insert(
+            "variables.tf".to_string(),
+            r#"
+variable "cidr_block" {
+  type = string
+}
+
+variable "name" {
+  type = string
+  validation {
+    condition     = length(var.name) > 0
+    error_message = "name must not be empty"
+  }
+}
+
+variable "tags" {
+  type    = map(string)
+  default = {}
+}
+
+variable "enable_nat" { default = false }
+"#
+            .to_string(),
+        );
+        module.insert(
+            "outputs.tf".to_string(),
+            r#"
+output "vpc_id" {
+  value = aws_vpc.this.id
+}
+"#
+            .to_string(),
+        );
+        module
+    }
+
+    #[test]
+    fn test_find_module_wiring_issues() {
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "main.tf".to_string(),
+            r#"
+module "network" {
+  source     = "./modules/network"
+  cidr_block = "10.0.0.0/16"
+  cidr       = "10.1.0.0/16"
+  tags = {
+    Name = "main"
+  }
+}
+
+module "registry" {
+  source  = "terraform-aws-modules/vpc/aws"
+  version = "5.0.0"
+  anything = true
+}
+
+resource "aws_subnet" "a" {
+  vpc_id = module.network.vpc_id
+  # module.network.commented_out
+}
+
+output "subnet_ids" {
+  value = module.network.subnet_ids
+}
+"#
+            .to_string(),
+        );
+
+        let issues = find_module_wiring_issues(&file_contents, |module_dir| {
+            (module_dir == "modules/network").then(network_module)
+        });
+        let found: Vec<(ModuleWiringIssueKind, &str, usize)> = issues
+            .iter()
+            .map(|i| (i.kind, i.key.as_str(), i.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (ModuleWiringIssueKind::MissingRequiredInput, "name", 2),
+                (ModuleWiringIssueKind::UnknownInput, "cidr", 5),
+                (ModuleWiringIssueKind::UnknownOutput, "subnet_ids", 23),
+            ]
+        );
+        assert!(issues.iter().all(|i| i.module == "network"));
+        assert_eq!(issues[0].source, "./modules/network");
+        assert_eq!(issues[0].file, "main.tf");
+    }
+
+    #[test]
+    fn test_hcl_lines_ignore_braces_in_strings_and_heredocs() {
+        let content = r#"resource "aws_iam_policy" "p" {
+  name   = "${lookup(var.names, "policy")}-{"
+  path   = "$${literal}/"
+  policy = <<EOF
+{"Statement": [{
+EOF
+  tags = { Name = "x" } # closes }
+}
+# resource "ignored" "x" {
+variable "after" {}
+"#;
+        let lines: Vec<_> = hcl_lines(content)
+            .map(|line| (line.number, line.depth, line.depth_after))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (1, 0, 1),
+                (2, 1, 1),
+                (3, 1, 1),
+                (4, 1, 1),
+                (7, 1, 1),
+                (8, 1, 0),
+                (10, 0, 0)
+            ]
+        );
+
+        let mut file_contents = HashMap::new();
+        file_contents.insert("main.tf".to_string(), content.to_string());
+        assert_eq!(
+            locate_definition(&file_contents, "var.after")
+                .definitions
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_module_wiring_resolves_paths_from_caller_directory() {
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "modules/app/main.tf".to_string(),
+            r#"
+module "network" {
+  source     = "../network"
+  cidr_block = "10.0.0.0/16"
+  name       = "app"
+}
+"#
+            .to_string(),
+        );
+
+        let issues = find_module_wiring_issues(&file_contents, |module_dir| {
+            (module_dir == "modules/network").then(network_module)
+        });
+        assert!(issues.is_empty(), "unexpected issues: {:?}", issues);
+        assert_eq!(resolve_module_path("", "./modules/vpc"), "modules/vpc");
+        assert_eq!(resolve_module_path("envs/prod", "../../shared"), "shared");
+        assert_eq!(resolve_module_path("", "../shared"), "../shared");
+    }
+
+    #[test]
+    fn test_module_wiring_reported_as_issues() {
+        let analysis = create_test_analysis();
+        let wiring = vec![ModuleWiringIssue {
+            module: "network".to_string(),
+            source: "./modules/network".to_string(),
+            kind: ModuleWiringIssueKind::UnknownInput,
+            key: "cidr".to_string(),
+            file: "main.tf".to_string(),
+            line: 5,
+        }];
+
+        let health = analyze_module_health(&analysis, &HashMap::new(), &wiring, None);
+        let issue = health
+            .issues
+            .iter()
+            .find(|i| matches!(i.category, IssueCategory::ModuleWiring))
+            .expect("wiring issue should be reported");
+        assert!(matches!(issue.severity, IssueSeverity::Critical));
+        assert!(issue.message.contains("Module 'network'"));
+        assert!(issue.message.contains("'cidr'"));
+        assert_eq!(issue.line, Some(5));
+        assert!(
+            health
+                .recommendations
+                .iter()
+                .any(|r| r.contains("Fix module wiring"))
+        );
+    }
+
+    #[test]
+    fn test_find_duplicate_definitions() {
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "main.tf".to_string(),
+            r#"
+resource "aws_s3_bucket" "logs" {
+  bucket = "app-logs"
+}
+
+variable "region" {
+  type = string
+}
+
+output "bucket" {
+  value = aws_s3_bucket.logs.id
+}
+"#
+            .to_string(),
+        );
+        file_contents.insert(
+            "storage.tf".to_string(),
+            r#"
+resource "aws_s3_bucket" "logs" {
+  bucket = "app-logs-copy"
+}
+
+# resource "aws_s3_bucket" "logs" {}
+
+resource "aws_s3_bucket" "data" {
+  bucket = "app-data"
+}
+
+variable "region" {
+  type = string
+}
+
+output "data_bucket" {
+  value = aws_s3_bucket.data.id
+}
+"#
+            .to_string(),
+        );
+
+        let duplicates = find_duplicate_definitions(&file_contents);
+
+        let found: Vec<_> = duplicates
+            .iter()
+            .map(|d| (d.kind.as_str(), d.address.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("resource", "aws_s3_bucket.logs"), ("variable", "region")]
+        );
+        let files: Vec<_> = duplicates[0]
+            .locations
+            .iter()
+            .map(|l| (l.file.as_str(), l.line))
+            .collect();
+        assert_eq!(files, vec![("main.tf", 2), ("storage.tf", 2)]);
+
+        // Modules are separate namespaces: the same variable in each is fine,
+        // a duplicate within one module is not
+        file_contents.insert(
+            "modules/vpc/variables.tf".to_string(),
+            "variable \"region\" {}\noutput \"id\" {\n  value = 1\n}\n".to_string(),
+        );
+        file_contents.insert(
+            "modules/db/variables.tf".to_string(),
+            "variable \"region\" {}\noutput \"id\" {\n  value = 2\n}\n".to_string(),
+        );
+        file_contents.insert(
+            "modules/db/outputs.tf".to_string(),
+            "output \"id\" {\n  value = 3\n}\n".to_string(),
+        );
+        let found: Vec<_> = find_duplicate_definitions(&file_contents)
+            .iter()
+            .map(|d| {
+                (
+                    d.kind.clone(),
+                    d.address.clone(),
+                    d.locations[0].file.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "output".to_string(),
+                    "id".to_string(),
+                    "modules/db/outputs.tf".to_string()
+                ),
+                (
+                    "resource".to_string(),
+                    "aws_s3_bucket.logs".to_string(),
+                    "main.tf".to_string()
+                ),
+                (
+                    "variable".to_string(),
+                    "region".to_string(),
+                    "main.tf".to_string()
+                ),
+            ]
+        );
+        file_contents.retain(|file, _| !file.starts_with("modules/"));
+
+        let analysis = create_test_analysis();
+        let health = analyze_module_health(&analysis, &file_contents, &[], None);
+        let issues: Vec<_> = health
+            .issues
+            .iter()
+            .filter(|i| matches!(i.category, IssueCategory::DuplicateDefinition))
+            .collect();
+        assert_eq!(issues.len(), 2);
+        assert!(matches!(issues[0].severity, IssueSeverity::Critical));
+        assert!(issues[0].message.contains("main.tf:2, storage.tf:2"));
+    }
+
+    #[test]
+    fn test_locate_definition() {
+        let mut file_contents = HashMap::new();
+        file_contents.insert(
+            "main.tf".to_string(),
+            r#"resource "aws_instance" "web" {
+  ami = var.ami
+}
+"#
+            .to_string(),
+        );
+        file_contents.insert(
+            "variables.tf".to_string(),
+            r#"variable "region" {
+  type = string
+}
+
+variable "ami" {
+  type = string
+}
+"#
+            .to_string(),
+        );
+        file_contents.insert(
+            "outputs.tf".to_string(),
+            r#"output "ami" {
+  value = var.ami
+}
+"#
+            .to_string(),
+        );
+        file_contents.insert(
+            "modules/app/main.tf".to_string(),
+            "\n\nresource \"aws_instance\" \"web\" {}\n".to_string(),
+        );
+
+        let found = |query: &str| -> Vec<(String, String, usize)> {
+            locate_definition(&file_contents, query)
+                .definitions
+                .into_iter()
+                .map(|d| (d.kind, d.file, d.line))
+                .collect()
+        };
+
+        // Duplicated across files: every declaration is returned
+        assert_eq!(
+            found("aws_instance.web[0]"),
+            vec![
+                ("resource".to_string(), "main.tf".to_string(), 1),
+                ("resource".to_string(), "modules/app/main.tf".to_string(), 3),
+            ]
+        );
+        assert_eq!(
+            found("var.ami"),
+            vec![("variable".to_string(), "variables.tf".to_string(), 5)]
+        );
+        assert_eq!(
+            found("ami"),
+            vec![
+                ("output".to_string(), "outputs.tf".to_string(), 1),
+                ("variable".to_string(), "variables.tf".to_string(), 5),
+            ]
+        );
+        assert!(found("aws_instance.db").is_empty());
+        assert_eq!(
+            locate_definition(&file_contents, "var.region").definitions[0].address,
+            "region"
+        );
+    }
+
+    #[test]
+    fn test_generate_moved_blocks() {
+        let hcl = generate_moved_blocks(
+            "networking",
+            &["aws_vpc.main".to_string(), "aws_subnet.public".to_string()],
+        );
+
+        assert_eq!(
+            hcl,
+            "moved {\n  from = aws_vpc.main\n  to   = module.networking.aws_vpc.main\n}\n\n\
+             moved {\n  from = aws_subnet.public\n  to   = module.networking.aws_subnet.public\n}\n"
+        );
+        assert_eq!(hcl.matches('{').count(), hcl.matches('}').count());
+    }
+
+    #[test]
+    fn test_extract_submodule_suggestions_include_moved_blocks() {
+        let analysis = create_test_analysis();
+        let file_contents = HashMap::new();
+        let mut health = analyze_module_health(&analysis, &file_contents, &[], None);
+        let group = |name: &str, types: &[&str], count: usize| ResourceTypeGroup {
+            name: name.to_string(),
+            resource_types: types.iter().map(|t| t.to_string()).collect(),
+            resource_count: count,
+        };
+        health.cohesion_analysis.resource_type_groups = vec![
+            group("network-core", &["aws_vpc", "aws_subnet"], 2),
+            group("compute", &["aws_instance"], 1),
+            group("storage", &[], 0),
+        ];
+
+        let suggestions = suggest_refactoring(&analysis, &health);
+        let extract: Vec<_> = suggestions
+            .iter()
+            .filter(|s| matches!(s.suggestion_type, RefactoringType::ExtractSubmodule))
+            .collect();
+        assert_eq!(extract.len(), 1);
+        assert_eq!(
+            extract[0].affected_resources,
+            vec!["aws_vpc.main", "aws_subnet.public"]
+        );
+
+        for suggestion in extract {
+            let hcl = suggestion.generated_hcl.as_deref().unwrap();
+            assert_eq!(
+                hcl.matches("moved {").count(),
+                suggestion.affected_resources.len()
+            );
+            let module_call = "network_core";
+            for address in &suggestion.affected_resources {
+                assert!(hcl.contains(&format!("from = {}\n", address)));
+                assert!(hcl.contains(&format!("to   = module.{}.{}\n", module_call, address)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_readme_quality_factors_into_health() {
+        let full = r#"# terraform-aws-network
+
+Creates a VPC with public and private subnets.
+
+## Usage
+
+```hcl
+module "network" {
+  source = "./modules/network"
+}
+```
+
+<!-- BEGIN_TF_DOCS -->
+## Inputs
+
+| Name | Description |
+|------|-------------|
+| cidr_block | VPC CIDR |
+
+## Outputs
+
+| Name | Description |
+|------|-------------|
+| vpc_id | VPC ID |
+<!-- END_TF_DOCS -->
+"#;
+        let stub = "# network
+
+TODO: document this module
+";
+        let analysis = create_test_analysis();
+        let file_contents = HashMap::new();
+        let health = |readme| analyze_module_health(&analysis, &file_contents, &[], readme);
+        let readme_issues = |health: &ModuleHealthAnalysis| {
+            health
+                .issues
+                .iter()
+                .filter(|i| i.message.contains("README"))
+                .map(|i| i.message.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let documented = health(Some(full));
+        assert_eq!(documented.readme.score, 100);
+        assert!(documented.readme.missing_sections.is_empty());
+        assert!(readme_issues(&documented).is_empty());
+
+        let stubbed = health(Some(stub));
+        assert!(stubbed.readme.present);
+        assert_eq!(stubbed.readme.score, 40);
+        assert_eq!(
+            stubbed.readme.missing_sections,
+            vec!["Usage", "Inputs", "Outputs"]
+        );
+        assert_eq!(
+            readme_issues(&stubbed),
+            vec!["README.md has no Usage, Inputs, Outputs section(s)"]
+        );
+        assert!(
+            stubbed
+                .recommendations
+                .iter()
+                .any(|r| r.contains("missing README.md sections: Usage, Inputs, Outputs"))
+        );
+
+        let undocumented = health(None);
+        assert!(!undocumented.readme.present);
+        assert_eq!(undocumented.readme.score, 0);
+        assert_eq!(readme_issues(&undocumented).len(), 1);
+        assert!(
+            undocumented
+                .recommendations
+                .iter()
+                .any(|r| r.contains("Add a README.md"))
+        );
+
+        assert!(documented.health_score > stubbed.health_score);
+        assert!(stubbed.health_score > undocumented.health_score);
+    }
+
     #[test]
     fn test_health_score_bounds() {
         let analysis = create_test_analysis();
         let file_contents = HashMap::new();
-        let health = analyze_module_health(&analysis, &file_contents);
+        let health = analyze_module_health(&analysis, &file_contents, &[], None);
 
         assert!(health.health_score <= 100);
     }
+
+    #[test]
+    fn test_impact_analysis_follows_transitive_chain() {
+        let node = |id: &str| ResourceNode {
+            id: id.to_string(),
+            resource_type: id.split('.').next().unwrap().to_string(),
+            resource_name: id.split('.').nth(1).unwrap().to_string(),
+            module_path: ".".to_string(),
+            file: "main.tf".to_string(),
+            provider: "aws".to_string(),
+        };
+        let edge = |source: &str, target: &str| ResourceEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            dependency_type: DependencyType::Implicit,
+            attribute: None,
+        };
+        // subnet -> vpc, instance -> subnet, eip -> instance, and an
+        // unrelated bucket; sg and rule depend on each other
+        let graph = ResourceDependencyGraph {
+            nodes: [
+                "aws_vpc.main",
+                "aws_subnet.a",
+                "aws_instance.web",
+                "aws_eip.web",
+                "aws_s3_bucket.logs",
+                "aws_security_group.web",
+                "aws_security_group_rule.web",
+            ]
+            .into_iter()
+            .map(node)
+            .collect(),
+            edges: vec![
+                edge("aws_subnet.a", "aws_vpc.main"),
+                edge("aws_instance.web", "aws_subnet.a"),
+                edge("aws_instance.web", "aws_vpc.main"),
+                edge("aws_eip.web", "aws_instance.web"),
+                edge("aws_security_group.web", "aws_vpc.main"),
+                edge("aws_security_group_rule.web", "aws_security_group.web"),
+                edge("aws_security_group.web", "aws_security_group_rule.web"),
+            ],
+            module_boundaries: vec![],
+        };
+
+        let impact = impact_analysis(&graph, "aws_vpc.main").unwrap();
+        assert_eq!(
+            impact.direct_dependents,
+            vec!["aws_instance.web", "aws_security_group.web", "aws_subnet.a"]
+        );
+        let transitive: Vec<(&str, usize)> = impact
+            .transitive_dependents
+            .iter()
+            .map(|r| (r.address.as_str(), r.distance))
+            .collect();
+        assert_eq!(
+            transitive,
+            vec![
+                ("aws_instance.web", 1),
+                ("aws_security_group.web", 1),
+                ("aws_subnet.a", 1),
+                ("aws_eip.web", 2),
+                ("aws_security_group_rule.web", 2),
+            ]
+        );
+        assert!(!impact.in_cycle);
+
+        // The cycle terminates and is reported
+        let impact = impact_analysis(&graph, "aws_security_group.web").unwrap();
+        assert!(impact.in_cycle);
+        assert_eq!(impact.transitive_dependents.len(), 1);
+
+        let impact = impact_analysis(&graph, "aws_s3_bucket.logs").unwrap();
+        assert!(impact.direct_dependents.is_empty() && impact.transitive_dependents.is_empty());
+
+        assert!(impact_analysis(&graph, "aws_vpc.missing").is_err());
+    }
+
+    #[test]
+    fn test_resource_subgraph_extracts_dependencies_and_dependents() {
+        let node = |id: &str| ResourceNode {
+            id: id.to_string(),
+            resource_type: id.split('.').next().unwrap().to_string(),
+            resource_name: id.split('.').nth(1).unwrap().to_string(),
+            module_path: ".".to_string(),
+            file: "main.tf".to_string(),
+            provider: "aws".to_string(),
+        };
+        let edge = |source: &str, target: &str, dependency_type| ResourceEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            dependency_type,
+            attribute: None,
+        };
+        // vpc <- subnet <- instance <- eip, instance -> sg; the bucket and
+        // the role depending on it are outside the instance's subtree
+        let graph = ResourceDependencyGraph {
+            nodes: [
+                "aws_vpc.main",
+                "aws_subnet.a",
+                "aws_instance.web",
+                "aws_eip.web",
+                "aws_security_group.web",
+                "aws_s3_bucket.logs",
+                "aws_iam_role.reader",
+            ]
+            .into_iter()
+            .map(node)
+            .collect(),
+            edges: vec![
+                edge("aws_subnet.a", "aws_vpc.main", DependencyType::Implicit),
+                edge("aws_instance.web", "aws_subnet.a", DependencyType::Implicit),
+                edge(
+                    "aws_instance.web",
+                    "aws_security_group.web",
+                    DependencyType::Explicit,
+                ),
+                edge("aws_eip.web", "aws_instance.web", DependencyType::Implicit),
+                edge(
+                    "aws_iam_role.reader",
+                    "aws_s3_bucket.logs",
+                    DependencyType::Implicit,
+                ),
+            ],
+            module_boundaries: vec![],
+        };
+
+        let subgraph = resource_subgraph(&graph, "aws_instance.web").unwrap();
+        let reached = |resources: &[ImpactedResource]| {
+            resources
+                .iter()
+                .map(|r| (r.address.clone(), r.distance))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            reached(&subgraph.dependencies),
+            vec![
+                ("aws_security_group.web".to_string(), 1),
+                ("aws_subnet.a".to_string(), 1),
+                ("aws_vpc.main".to_string(), 2),
+            ]
+        );
+        assert_eq!(
+            reached(&subgraph.dependents),
+            vec![("aws_eip.web".to_string(), 1)]
+        );
+        let nodes: Vec<_> = subgraph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(
+            nodes,
+            vec![
+                "aws_vpc.main",
+                "aws_subnet.a",
+                "aws_instance.web",
+                "aws_eip.web",
+                "aws_security_group.web",
+            ]
+        );
+        assert_eq!(subgraph.edges.len(), 4);
+
+        let mermaid = subgraph_to_mermaid(&subgraph);
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("    n2[\"aws_instance.web\"]"));
+        assert!(mermaid.contains("    n2 --> n1"));
+        assert!(mermaid.contains("    n2 -->|depends_on| n4"));
+        assert!(mermaid.contains("    class n2 selected"));
+        assert!(!mermaid.contains("aws_s3_bucket.logs"));
+
+        let err = resource_subgraph(&graph, "aws_vpc.missing").unwrap_err();
+        assert!(err.to_string().contains("not in the dependency graph"));
+    }
 }
@@ -0,0 +1,224 @@
+//! Single-use approval tokens for the two-phase apply.
+//!
+//! `propose_apply` saves a plan and issues a token bound to that plan file and
+//! its SHA-256; `confirm_apply` redeems the token to apply exactly that plan.
+//! Tokens are kept in memory only, expire after a TTL, and are consumed by
+//! the first redeem whether or not the apply then succeeds.
+
+use crate::terraform::plan_analyzer::ChangeSummary;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Time a token stays valid when `TFMCP_APPROVAL_TTL_SECS` is unset
+pub const DEFAULT_APPROVAL_TTL: Duration = Duration::from_secs(300);
+
+/// Prefix of the plan files written for proposals
+pub const PROPOSAL_PLAN_PREFIX: &str = "tfmcp-proposal-";
+
+/// The plan a token approves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Approval {
+    /// Project the plan was saved in
+    pub project_directory: PathBuf,
+    /// Plan file, relative to the project root
+    pub plan_file: String,
+    /// Hex SHA-256 of the plan file when it was proposed
+    pub sha256: String,
+}
+
+/// A saved plan awaiting `confirm_apply`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyProposal {
+    /// Pass to `confirm_apply` to apply this plan
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub plan_file: String,
+    pub sha256: String,
+    pub summary: ChangeSummary,
+}
+
+/// Why a token was not accepted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalError {
+    /// Never issued, or already redeemed
+    Unknown,
+    /// Issued more than the TTL ago; the approval it held is returned so its
+    /// plan file can be cleaned up
+    Expired(Approval),
+}
+
+impl std::fmt::Display for ApprovalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown => write!(
+                f,
+                "Approval token is unknown or has already been used. Call propose_apply and review the new plan."
+            ),
+            Self::Expired(_) => write!(
+                f,
+                "Approval token has expired. Call propose_apply and review the new plan."
+            ),
+        }
+    }
+}
+
+struct PendingApproval {
+    approval: Approval,
+    issued_at: Instant,
+}
+
+/// Session-wide store of outstanding approval tokens
+pub struct ApprovalStore {
+    ttl: Duration,
+    pending: Mutex<HashMap<String, PendingApproval>>,
+    issued: AtomicU64,
+}
+
+impl Default for ApprovalStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_APPROVAL_TTL)
+    }
+}
+
+impl ApprovalStore {
+    /// Store whose tokens expire `ttl` after they are issued
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            pending: Mutex::new(HashMap::new()),
+            issued: AtomicU64::new(0),
+        }
+    }
+
+    /// Store with the TTL from `TFMCP_APPROVAL_TTL_SECS`
+    pub fn from_env() -> Self {
+        let ttl = match std::env::var("TFMCP_APPROVAL_TTL_SECS") {
+            Ok(value) => match value.trim().parse::<u64>() {
+                Ok(secs) if secs > 0 => Duration::from_secs(secs),
+                _ => {
+                    eprintln!(
+                        "[WARN] Invalid TFMCP_APPROVAL_TTL_SECS '{}', using {}s",
+                        value,
+                        DEFAULT_APPROVAL_TTL.as_secs()
+                    );
+                    DEFAULT_APPROVAL_TTL
+                }
+            },
+            Err(_) => DEFAULT_APPROVAL_TTL,
+        };
+        Self::new(ttl)
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Issue a token approving `approval`
+    pub fn issue(&self, approval: Approval) -> String {
+        let token = self.new_token(&approval.sha256);
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.insert(
+            token.clone(),
+            PendingApproval {
+                approval,
+                issued_at: Instant::now(),
+            },
+        );
+        token
+    }
+
+    /// Consume `token`, returning the approval it was issued for
+    pub fn redeem(&self, token: &str) -> Result<Approval, ApprovalError> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = pending.remove(token.trim()).ok_or(ApprovalError::Unknown)?;
+        if entry.issued_at.elapsed() > self.ttl {
+            return Err(ApprovalError::Expired(entry.approval));
+        }
+        Ok(entry.approval)
+    }
+
+    /// Drop expired tokens, returning their approvals for cleanup
+    pub fn prune_expired(&self) -> Vec<Approval> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let expired: Vec<String> = pending
+            .iter()
+            .filter(|(_, entry)| entry.issued_at.elapsed() > self.ttl)
+            .map(|(token, _)| token.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|token| pending.remove(&token))
+            .map(|entry| entry.approval)
+            .collect()
+    }
+
+    /// 128-bit hex token from the process's random hasher keys, a counter,
+    /// the time and the plan hash
+    fn new_token(&self, sha256: &str) -> String {
+        let sequence = self.issued.fetch_add(1, Ordering::Relaxed);
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let random = RandomState::new();
+        let mut hasher = Sha256::new();
+        hasher.update(random.hash_one(sequence).to_le_bytes());
+        hasher.update(random.hash_one(nanos).to_le_bytes());
+        hasher.update(sequence.to_le_bytes());
+        hasher.update(nanos.to_le_bytes());
+        hasher.update(sha256.as_bytes());
+        hasher.finalize()[..16]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approval() -> Approval {
+        Approval {
+            project_directory: PathBuf::from("/work/network"),
+            plan_file: format!("{}1.tfplan", PROPOSAL_PLAN_PREFIX),
+            sha256: "ab".repeat(32),
+        }
+    }
+
+    #[test]
+    fn test_token_redeems_once() {
+        let store = ApprovalStore::default();
+        let token = store.issue(approval());
+        assert_eq!(token.len(), 32);
+        assert_ne!(store.issue(approval()), token);
+
+        assert_eq!(store.redeem(&token), Ok(approval()));
+        assert_eq!(store.redeem(&token), Err(ApprovalError::Unknown));
+        assert_eq!(store.redeem("not-a-token"), Err(ApprovalError::Unknown));
+    }
+
+    #[test]
+    fn test_expired_token_is_rejected() {
+        let store = ApprovalStore::new(Duration::from_millis(20));
+        let token = store.issue(approval());
+        let pruned = store.issue(approval());
+        std::thread::sleep(Duration::from_millis(40));
+
+        assert_eq!(
+            store.redeem(&token),
+            Err(ApprovalError::Expired(approval()))
+        );
+        // Expired tokens are gone, not merely refused
+        assert_eq!(store.redeem(&token), Err(ApprovalError::Unknown));
+        assert_eq!(store.prune_expired(), vec![approval()]);
+        assert_eq!(store.redeem(&pruned), Err(ApprovalError::Unknown));
+    }
+}
@@ -0,0 +1,150 @@
+//! Resource type → category mapping used by the module health analyzer.
+//!
+//! The default rules ship as embedded JSON (`resource_categories.json`). A
+//! user mapping file in the same format can be supplied with
+//! `TFMCP_RESOURCE_CATEGORIES` or `terraform.resource_categories_file`; its
+//! rules are checked before the defaults, so it can add provider prefixes or
+//! move a type into a different category.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Default mapping, kept in order: the first matching rule wins
+const EMBEDDED_CATEGORIES: &str = include_str!("resource_categories.json");
+
+/// Category of resource types no rule matches
+pub const UNCATEGORIZED: &str = "other";
+
+static ACTIVE_CATEGORIES: OnceLock<ResourceCategories> = OnceLock::new();
+
+/// One category and the resource type patterns that belong to it
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub category: String,
+    /// Resource type prefixes, e.g. `digitalocean_` for a whole provider
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+    /// Substrings anywhere in the resource type
+    #[serde(default)]
+    pub contains: Vec<String>,
+}
+
+impl CategoryRule {
+    fn matches(&self, type_lower: &str) -> bool {
+        self.prefixes
+            .iter()
+            .any(|prefix| type_lower.starts_with(&prefix.to_lowercase()))
+            || self
+                .contains
+                .iter()
+                .any(|pattern| type_lower.contains(&pattern.to_lowercase()))
+    }
+}
+
+/// Ordered list of category rules
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+pub struct ResourceCategories {
+    pub rules: Vec<CategoryRule>,
+}
+
+impl ResourceCategories {
+    /// The built-in mapping
+    pub fn embedded() -> Self {
+        serde_json::from_str(EMBEDDED_CATEGORIES).expect("Invalid embedded resource categories")
+    }
+
+    /// Load a mapping file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read resource categories {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to parse resource categories {}: {}",
+                path.display(),
+                e
+            )
+        })
+    }
+
+    /// The embedded mapping with `path`'s rules checked first
+    pub fn with_override_file(path: &Path) -> anyhow::Result<Self> {
+        let mut categories = Self::load(path)?;
+        categories.rules.extend(Self::embedded().rules);
+        Ok(categories)
+    }
+
+    /// Category of `resource_type`, or [`UNCATEGORIZED`]
+    pub fn categorize(&self, resource_type: &str) -> &str {
+        let type_lower = resource_type.to_lowercase();
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(&type_lower))
+            .map_or(UNCATEGORIZED, |rule| rule.category.as_str())
+    }
+}
+
+/// Make `categories` the mapping used by the analyzer. Only the first call
+/// takes effect; returns `false` if a mapping was already in use.
+pub fn install(categories: ResourceCategories) -> bool {
+    ACTIVE_CATEGORIES.set(categories).is_ok()
+}
+
+/// The mapping in use: the installed one, or the embedded defaults
+pub fn active() -> &'static ResourceCategories {
+    ACTIVE_CATEGORIES.get_or_init(ResourceCategories::embedded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_categories() {
+        let categories = ResourceCategories::embedded();
+        assert_eq!(categories.categorize("aws_vpc"), "networking-core");
+        assert_eq!(categories.categorize("AWS_S3_BUCKET"), "storage");
+        assert_eq!(
+            categories.categorize("google_compute_subnetwork"),
+            "networking-core"
+        );
+        assert_eq!(categories.categorize("digitalocean_droplet"), UNCATEGORIZED);
+    }
+
+    #[test]
+    fn test_override_file_adds_provider_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("categories.json");
+        std::fs::write(
+            &path,
+            r#"{"rules": [
+                {"category": "compute", "prefixes": ["digitalocean_droplet"]},
+                {"category": "digitalocean", "prefixes": ["digitalocean_"]},
+                {"category": "storage", "contains": ["aws_db_snapshot"]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let categories = ResourceCategories::with_override_file(&path).unwrap();
+        assert_eq!(categories.categorize("digitalocean_droplet"), "compute");
+        assert_eq!(
+            categories.categorize("digitalocean_spaces_bucket"),
+            "digitalocean"
+        );
+        // Override rules win over the defaults, which still apply otherwise
+        assert_eq!(categories.categorize("aws_db_snapshot"), "storage");
+        assert_eq!(categories.categorize("aws_dynamodb_table"), "database");
+
+        std::fs::write(&path, "not json").unwrap();
+        let err = ResourceCategories::with_override_file(&path).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Failed to parse resource categories")
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Terraform CLI configuration (`.terraformrc` / `TF_CLI_CONFIG_FILE`).
+//!
+//! A project can name its own CLI config file with `terraform.cli_config_file`;
+//! tfmcp passes it to terraform as `TF_CLI_CONFIG_FILE`. The summary here
+//! reports which file is in effect and whether it installs providers from a
+//! mirror, without returning its contents (it may hold registry credentials).
+
+use crate::terraform::analyzer::hcl_lines;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Environment variable terraform reads the CLI config path from
+pub const CLI_CONFIG_ENV: &str = "TF_CLI_CONFIG_FILE";
+
+static MIRROR_BLOCK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(network_mirror|filesystem_mirror)\s*\{"#).expect("Invalid mirror regex")
+});
+
+static URL_ATTRIBUTE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*url\s*=\s*"([^"]+)""#).expect("Invalid url regex"));
+
+static PLUGIN_CACHE_DIR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*plugin_cache_dir\s*=\s*"([^"]+)""#).expect("Invalid plugin_cache_dir regex")
+});
+
+/// Where the effective CLI config path came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CliConfigSource {
+    /// `terraform.cli_config_file` in the tfmcp config
+    TfmcpConfig,
+    /// `TF_CLI_CONFIG_FILE` in tfmcp's environment
+    Environment,
+    /// `~/.terraformrc`
+    Default,
+    /// No CLI config file is used
+    None,
+}
+
+/// Settings of interest from a CLI config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CliConfigSummary {
+    /// A `provider_installation` block is present
+    pub provider_installation: bool,
+    pub network_mirror: bool,
+    pub filesystem_mirror: bool,
+    /// `url` of each network mirror
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub network_mirror_urls: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin_cache_dir: Option<String>,
+}
+
+/// The CLI config terraform will use when run by tfmcp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliConfigInfo {
+    pub path: Option<String>,
+    pub source: CliConfigSource,
+    pub exists: bool,
+    #[serde(flatten)]
+    pub summary: CliConfigSummary,
+}
+
+/// Resolve a configured CLI config path against the project directory and
+/// check that it exists
+pub fn resolve_cli_config_file(project_directory: &Path, path: &str) -> anyhow::Result<PathBuf> {
+    let expanded = crate::shared::utils::path::expand_tilde(path);
+    let resolved = if expanded.is_absolute() {
+        expanded
+    } else {
+        project_directory.join(expanded)
+    };
+    if !resolved.is_file() {
+        return Err(anyhow::anyhow!(
+            "Terraform CLI config file {} does not exist (terraform.cli_config_file)",
+            resolved.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Summarize the mirror and cache settings of CLI config HCL
+pub fn parse_cli_config(content: &str) -> CliConfigSummary {
+    let mut summary = CliConfigSummary::default();
+    // Depth at which the current network_mirror block was opened
+    let mut network_mirror_depth: Option<usize> = None;
+
+    for line in hcl_lines(content) {
+        if line.text.trim().starts_with("provider_installation") {
+            summary.provider_installation = true;
+        }
+        if let Some(cap) = MIRROR_BLOCK_REGEX.captures(line.text) {
+            if &cap[1] == "network_mirror" {
+                summary.network_mirror = true;
+                network_mirror_depth = Some(line.depth);
+            } else {
+                summary.filesystem_mirror = true;
+            }
+        } else if network_mirror_depth.is_some() {
+            if let Some(cap) = URL_ATTRIBUTE_REGEX.captures(line.text) {
+                summary.network_mirror_urls.push(cap[1].to_string());
+            }
+        }
+        if line.depth == 0 {
+            if let Some(cap) = PLUGIN_CACHE_DIR_REGEX.captures(line.text) {
+                summary.plugin_cache_dir = Some(cap[1].to_string());
+            }
+        }
+
+        if network_mirror_depth.is_some_and(|open| line.depth_after <= open) {
+            network_mirror_depth = None;
+        }
+    }
+    summary
+}
+
+/// Effective CLI config: the project setting, then `TF_CLI_CONFIG_FILE`,
+/// then `~/.terraformrc` when it exists
+pub fn effective_cli_config(
+    configured: Option<&Path>,
+    env_override: Option<&str>,
+) -> (Option<PathBuf>, CliConfigSource) {
+    if let Some(path) = configured {
+        return (Some(path.to_path_buf()), CliConfigSource::TfmcpConfig);
+    }
+    if let Some(path) = env_override.filter(|p| !p.trim().is_empty()) {
+        return (Some(PathBuf::from(path)), CliConfigSource::Environment);
+    }
+    match dirs::home_dir().map(|home| home.join(".terraformrc")) {
+        Some(path) if path.is_file() => (Some(path), CliConfigSource::Default),
+        _ => (None, CliConfigSource::None),
+    }
+}
+
+/// Describe the CLI config in effect for `configured`
+pub fn describe_cli_config(configured: Option<&Path>) -> CliConfigInfo {
+    let (path, source) =
+        effective_cli_config(configured, std::env::var(CLI_CONFIG_ENV).ok().as_deref());
+    let content = path
+        .as_deref()
+        .and_then(|p| std::fs::read_to_string(p).ok());
+    CliConfigInfo {
+        path: path.map(|p| p.display().to_string()),
+        source,
+        exists: content.is_some(),
+        summary: content.as_deref().map(parse_cli_config).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cli_config_mirrors() {
+        let summary = parse_cli_config(
+            r#"
+plugin_cache_dir = "$HOME/.terraform.d/plugin-cache"
+
+credentials "app.terraform.io" {
+  token = "xxxxxx.atlasv1.zzzzzzzzzzzzz"
+}
+
+provider_installation {
+  network_mirror {
+    url     = "https://mirror.example.com/providers/"
+    include = ["registry.terraform.io/hashicorp/*"]
+  }
+  # filesystem_mirror {
+  #   path = "/usr/share/terraform/providers"
+  # }
+  direct {
+    exclude = ["registry.terraform.io/hashicorp/*"]
+  }
+}
+"#,
+        );
+        assert!(summary.provider_installation);
+        assert!(summary.network_mirror);
+        assert!(!summary.filesystem_mirror);
+        assert_eq!(
+            summary.network_mirror_urls,
+            vec!["https://mirror.example.com/providers/"]
+        );
+        assert_eq!(
+            summary.plugin_cache_dir.as_deref(),
+            Some("$HOME/.terraform.d/plugin-cache")
+        );
+
+        assert_eq!(parse_cli_config(""), CliConfigSummary::default());
+    }
+
+    #[test]
+    fn test_effective_cli_config_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let rc = dir.path().join("project.tfrc");
+        std::fs::write(&rc, "").unwrap();
+
+        let resolved = resolve_cli_config_file(dir.path(), "project.tfrc").unwrap();
+        assert_eq!(resolved, rc);
+        assert!(resolve_cli_config_file(dir.path(), "missing.tfrc").is_err());
+
+        assert_eq!(
+            effective_cli_config(Some(&rc), Some("/etc/terraformrc")),
+            (Some(rc.clone()), CliConfigSource::TfmcpConfig)
+        );
+        assert_eq!(
+            effective_cli_config(None, Some("/etc/terraformrc")),
+            (
+                Some(PathBuf::from("/etc/terraformrc")),
+                CliConfigSource::Environment
+            )
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Policy-as-code evaluation of plans with Conftest (Rego policies).
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Outcome of a policy evaluation run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluationStatus {
+    Evaluated,
+    NotConfigured,
+    ConftestNotFound,
+}
+
+/// Pass/fail result for a single policy namespace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyResult {
+    pub namespace: String,
+    pub passed: bool,
+    pub successes: u32,
+    pub failures: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Result of evaluating the current plan against a policy directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyEvaluation {
+    pub status: EvaluationStatus,
+    pub policy_dir: Option<String>,
+    pub passed: bool,
+    pub results: Vec<PolicyResult>,
+    pub message: String,
+}
+
+impl PolicyEvaluation {
+    /// An evaluation that could not run, e.g. because nothing is configured
+    pub fn skipped(status: EvaluationStatus, policy_dir: Option<&Path>, message: &str) -> Self {
+        Self {
+            status,
+            policy_dir: policy_dir.map(|p| p.display().to_string()),
+            passed: false,
+            results: vec![],
+            message: message.to_string(),
+        }
+    }
+}
+
+/// One entry of `conftest test --output json`
+#[derive(Debug, Deserialize)]
+struct ConftestFileResult {
+    #[serde(default)]
+    namespace: String,
+    #[serde(default)]
+    successes: u32,
+    #[serde(default)]
+    failures: Option<Vec<ConftestMessage>>,
+    #[serde(default)]
+    warnings: Option<Vec<ConftestMessage>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConftestMessage {
+    msg: String,
+}
+
+/// Locate the `conftest` binary on PATH
+pub fn find_conftest() -> Option<PathBuf> {
+    which::which("conftest").ok()
+}
+
+/// Run `conftest test` on a plan JSON document
+pub fn run_conftest(
+    conftest_path: &Path,
+    policy_dir: &Path,
+    plan_json: &str,
+) -> anyhow::Result<PolicyEvaluation> {
+    let mut plan_file = tempfile::Builder::new()
+        .prefix("tfmcp-plan-")
+        .suffix(".json")
+        .tempfile()?;
+    plan_file.write_all(plan_json.as_bytes())?;
+
+    let output = Command::new(conftest_path)
+        .arg("test")
+        .arg("--no-color")
+        .arg("--output")
+        .arg("json")
+        .arg("--all-namespaces")
+        .arg("--policy")
+        .arg(policy_dir)
+        .arg(plan_file.path())
+        .output()?;
+
+    // conftest exits non-zero when policies fail, so judge by stdout instead
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Err(anyhow::anyhow!(
+            "conftest failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let mut evaluation = parse_conftest_output(&stdout)?;
+    evaluation.policy_dir = Some(policy_dir.display().to_string());
+    Ok(evaluation)
+}
+
+/// Parse `conftest test --output json` into per-namespace results
+pub fn parse_conftest_output(json: &str) -> anyhow::Result<PolicyEvaluation> {
+    let files: Vec<ConftestFileResult> = serde_json::from_str(json)
+        .map_err(|e| anyhow::anyhow!("Failed to parse conftest output: {}", e))?;
+
+    let mut by_namespace: BTreeMap<String, PolicyResult> = BTreeMap::new();
+    for file in files {
+        let namespace = if file.namespace.is_empty() {
+            "main".to_string()
+        } else {
+            file.namespace
+        };
+        let result = by_namespace
+            .entry(namespace.clone())
+            .or_insert_with(|| PolicyResult {
+                namespace,
+                passed: true,
+                successes: 0,
+                failures: vec![],
+                warnings: vec![],
+            });
+        result.successes += file.successes;
+        result
+            .failures
+            .extend(file.failures.unwrap_or_default().into_iter().map(|m| m.msg));
+        result
+            .warnings
+            .extend(file.warnings.unwrap_or_default().into_iter().map(|m| m.msg));
+        result.passed = result.failures.is_empty();
+    }
+
+    let results: Vec<PolicyResult> = by_namespace.into_values().collect();
+    let failed = results.iter().filter(|r| !r.passed).count();
+    let passed = failed == 0;
+    let message = if passed {
+        format!("All {} policy namespaces passed", results.len())
+    } else {
+        format!("{} of {} policy namespaces failed", failed, results.len())
+    };
+
+    Ok(PolicyEvaluation {
+        status: EvaluationStatus::Evaluated,
+        policy_dir: None,
+        passed,
+        results,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Captured from `conftest test --output json --all-namespaces`
+    const SAMPLE_OUTPUT: &str = r#"[
+  {
+    "filename": "/tmp/tfmcp-plan-abc.json",
+    "namespace": "terraform.s3",
+    "successes": 2,
+    "failures": [
+      {"msg": "S3 bucket 'aws_s3_bucket.logs' must enable versioning", "metadata": {"query": "data.terraform.s3.deny"}}
+    ],
+    "warnings": [
+      {"msg": "S3 bucket 'aws_s3_bucket.logs' has no lifecycle rule"}
+    ]
+  },
+  {
+    "filename": "/tmp/tfmcp-plan-abc.json",
+    "namespace": "terraform.tags",
+    "successes": 3
+  }
+]"#;
+
+    #[test]
+    fn test_parse_conftest_output() {
+        let evaluation = parse_conftest_output(SAMPLE_OUTPUT).unwrap();
+
+        assert_eq!(evaluation.status, EvaluationStatus::Evaluated);
+        assert!(!evaluation.passed);
+        assert_eq!(evaluation.results.len(), 2);
+
+        let s3 = &evaluation.results[0];
+        assert_eq!(s3.namespace, "terraform.s3");
+        assert!(!s3.passed);
+        assert_eq!(s3.successes, 2);
+        assert_eq!(
+            s3.failures,
+            vec!["S3 bucket 'aws_s3_bucket.logs' must enable versioning"]
+        );
+        assert_eq!(s3.warnings.len(), 1);
+
+        let tags = &evaluation.results[1];
+        assert_eq!(tags.namespace, "terraform.tags");
+        assert!(tags.passed);
+        assert!(tags.failures.is_empty());
+        assert!(evaluation.message.contains("1 of 2"));
+    }
+
+    #[test]
+    fn test_parse_conftest_output_all_passing() {
+        let evaluation = parse_conftest_output(
+            r#"[{"filename": "plan.json", "namespace": "main", "successes": 4, "failures": null}]"#,
+        )
+        .unwrap();
+        assert!(evaluation.passed);
+        assert_eq!(evaluation.results[0].successes, 4);
+    }
+
+    #[test]
+    fn test_parse_conftest_output_invalid() {
+        assert!(parse_conftest_output("not json").is_err());
+    }
+}
@@ -0,0 +1,462 @@
+//! Resources commonly behind large cloud bills, found from the configuration
+//! alone.
+//!
+//! This is a qualitative complement to the Infracost estimate in
+//! `pre_apply_report`: no pricing API is called and nothing is priced. Only
+//! literal attribute values are inspected, so an instance type passed in
+//! through a variable is not judged.
+
+use crate::terraform::analyzer::hcl_lines;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// vCPUs from which an instance type counts as large
+pub const LARGE_INSTANCE_VCPUS: u32 = 16;
+
+static RESOURCE_HEADER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*resource\s+"([^"]+)"\s+"([^"]+)""#).expect("Invalid resource header regex")
+});
+
+static STRING_ATTRIBUTE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*([A-Za-z_][A-Za-z0-9_-]*)\s*=\s*"([^"$]*)""#)
+        .expect("Invalid string attribute regex")
+});
+
+/// `<n>xlarge` sizes of AWS instance types, e.g. `m5.4xlarge`
+static AWS_SIZE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+)xlarge$").expect("Invalid AWS size regex"));
+
+/// AWS families with GPUs or ML accelerators, e.g. `p4d`, `g5`, `inf2`
+static AWS_ACCELERATED_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(p\d|g\d|inf\d|trn\d|dl\d)").expect("Invalid AWS accelerated family regex")
+});
+
+/// GCP machine types ending in their vCPU count, e.g. `n2-standard-32`
+static GCP_MACHINE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([a-z0-9]+)-[a-z]+-(\d+)").expect("Invalid GCP machine type regex")
+});
+
+/// Azure VM sizes, e.g. `Standard_D32s_v5`; the digits are the vCPU count
+static AZURE_SIZE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^Standard_([A-Z]+)(\d+)").expect("Invalid Azure VM size regex"));
+
+/// Attributes naming an instance, node or VM size
+const SIZE_ATTRIBUTES: &[&str] = &[
+    "instance_type",
+    "instance_class",
+    "node_type",
+    "machine_type",
+    "vm_size",
+    "size",
+];
+
+/// Attributes naming a disk or volume type
+const VOLUME_TYPE_ATTRIBUTES: &[&str] = &[
+    "type",
+    "volume_type",
+    "storage_type",
+    "storage_account_type",
+];
+
+/// Volume types billed for provisioned IOPS
+const PROVISIONED_IOPS_TYPES: &[&str] = &[
+    "io1",
+    "io2",
+    "pd-extreme",
+    "hyperdisk-extreme",
+    "UltraSSD_LRS",
+];
+
+const NAT_GATEWAY_TYPES: &[&str] = &[
+    "aws_nat_gateway",
+    "azurerm_nat_gateway",
+    "google_compute_router_nat",
+];
+
+const LOAD_BALANCER_TYPES: &[&str] = &[
+    "aws_lb",
+    "aws_alb",
+    "aws_elb",
+    "azurerm_lb",
+    "azurerm_application_gateway",
+    "google_compute_forwarding_rule",
+    "google_compute_global_forwarding_rule",
+];
+
+/// Resources that copy data between regions
+const CROSS_REGION_TYPES: &[&str] = &[
+    "aws_s3_bucket_replication_configuration",
+    "aws_dynamodb_table_replica",
+    "aws_dynamodb_global_table",
+    "aws_rds_global_cluster",
+    "aws_ec2_transit_gateway_peering_attachment",
+];
+
+/// Attributes that point a resource at another region
+const CROSS_REGION_ATTRIBUTES: &[&str] = &["peer_region", "destination_region", "source_region"];
+
+/// Kind of cost a driver incurs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CostCategory {
+    NatGateway,
+    LargeInstance,
+    ProvisionedIops,
+    LoadBalancer,
+    InterRegionTransfer,
+}
+
+/// How much a driver typically adds to the bill
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum CostImpact {
+    High,
+    Medium,
+}
+
+/// A resource likely to be expensive, with what to check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostDriver {
+    /// `<type>.<name>` of the resource block
+    pub address: String,
+    pub file: String,
+    pub line: usize,
+    pub category: CostCategory,
+    pub impact: CostImpact,
+    /// What in the configuration triggered the flag
+    pub reason: String,
+    pub guidance: String,
+}
+
+/// Cost drivers found in the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostDriverReport {
+    /// `resource` blocks inspected
+    pub resources_scanned: usize,
+    /// High impact first, then by file and line
+    pub drivers: Vec<CostDriver>,
+    pub message: String,
+}
+
+/// A `resource` block being read, with its literal string attributes
+struct ResourceBlock {
+    resource_type: String,
+    name: String,
+    line: usize,
+    attributes: Vec<(String, String)>,
+}
+
+/// Flag the resources in `file_contents` commonly associated with high cost
+pub fn identify_cost_drivers(file_contents: &HashMap<String, String>) -> CostDriverReport {
+    let mut files: Vec<_> = file_contents.iter().collect();
+    files.sort();
+
+    let mut resources_scanned = 0;
+    let mut drivers = Vec::new();
+    for (filename, content) in files {
+        let mut current: Option<ResourceBlock> = None;
+        for line in hcl_lines(content) {
+            if line.depth == 0 {
+                current = RESOURCE_HEADER_REGEX
+                    .captures(line.text)
+                    .map(|cap| ResourceBlock {
+                        resource_type: cap[1].to_string(),
+                        name: cap[2].to_string(),
+                        line: line.number,
+                        attributes: Vec::new(),
+                    });
+            } else if let Some(block) = current.as_mut() {
+                if let Some(cap) = STRING_ATTRIBUTE_REGEX.captures(line.text) {
+                    block
+                        .attributes
+                        .push((cap[1].to_string(), cap[2].to_string()));
+                }
+            }
+
+            if line.depth_after == 0 {
+                if let Some(block) = current.take() {
+                    resources_scanned += 1;
+                    let address = format!("{}.{}", block.resource_type, block.name);
+                    for (category, reason) in
+                        resource_drivers(&block.resource_type, &block.attributes)
+                    {
+                        drivers.push(CostDriver {
+                            address: address.clone(),
+                            file: filename.clone(),
+                            line: block.line,
+                            category,
+                            impact: impact(category),
+                            reason,
+                            guidance: guidance(category).to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    drivers.sort_by(|a, b| {
+        a.impact
+            .cmp(&b.impact)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    let message = if drivers.is_empty() {
+        format!(
+            "No common cost drivers found in {} resources",
+            resources_scanned
+        )
+    } else {
+        format!(
+            "{} potential cost driver(s) in {} resources. These are heuristics from the \
+             configuration; use pre_apply_report with Infracost installed for a priced estimate.",
+            drivers.len(),
+            resources_scanned
+        )
+    };
+
+    CostDriverReport {
+        resources_scanned,
+        drivers,
+        message,
+    }
+}
+
+/// Categories a resource falls into, with the reason for each
+fn resource_drivers(
+    resource_type: &str,
+    attributes: &[(String, String)],
+) -> Vec<(CostCategory, String)> {
+    let mut found = Vec::new();
+
+    if NAT_GATEWAY_TYPES.contains(&resource_type) {
+        found.push((
+            CostCategory::NatGateway,
+            format!("{} bills hourly plus per GB processed", resource_type),
+        ));
+    }
+    if LOAD_BALANCER_TYPES.contains(&resource_type) {
+        found.push((
+            CostCategory::LoadBalancer,
+            format!("{} bills hourly plus per unit of traffic", resource_type),
+        ));
+    }
+
+    for (key, value) in attributes {
+        if SIZE_ATTRIBUTES.contains(&key.as_str())
+            && (key != "size" || resource_type.starts_with("azurerm_"))
+        {
+            if let Some(reason) = large_instance(value) {
+                found.push((
+                    CostCategory::LargeInstance,
+                    format!("{} = \"{}\" is {}", key, value, reason),
+                ));
+            }
+        }
+        if VOLUME_TYPE_ATTRIBUTES.contains(&key.as_str())
+            && PROVISIONED_IOPS_TYPES.contains(&value.as_str())
+        {
+            found.push((
+                CostCategory::ProvisionedIops,
+                format!("{} = \"{}\" bills for provisioned IOPS", key, value),
+            ));
+        }
+        if CROSS_REGION_ATTRIBUTES.contains(&key.as_str()) {
+            found.push((
+                CostCategory::InterRegionTransfer,
+                format!("{} = \"{}\" sends traffic to another region", key, value),
+            ));
+        }
+    }
+
+    if CROSS_REGION_TYPES.contains(&resource_type)
+        && !found
+            .iter()
+            .any(|(category, _)| *category == CostCategory::InterRegionTransfer)
+    {
+        found.push((
+            CostCategory::InterRegionTransfer,
+            format!("{} copies data between regions", resource_type),
+        ));
+    }
+
+    found
+}
+
+/// Why an instance, node or VM size is large, if it is
+fn large_instance(value: &str) -> Option<String> {
+    // AWS: `m5.4xlarge`, `db.r6g.8xlarge`, `cache.p4d.24xlarge`
+    let segments: Vec<&str> = value.split('.').collect();
+    if segments.len() >= 2 {
+        let size = segments[segments.len() - 1];
+        let family = segments[segments.len() - 2];
+        if AWS_ACCELERATED_REGEX.is_match(family) {
+            return Some("a GPU/accelerator instance type".to_string());
+        }
+        if size == "metal" {
+            return Some("a bare-metal instance type".to_string());
+        }
+        let multiplier: u32 = AWS_SIZE_REGEX.captures(size)?[1].parse().ok()?;
+        // Each xlarge has 4 vCPUs
+        let vcpus = multiplier * 4;
+        return (vcpus >= LARGE_INSTANCE_VCPUS).then(|| format!("{} vCPUs", vcpus));
+    }
+
+    // GCP: `n2-standard-32`, `a2-highgpu-1g`
+    if let Some(cap) = GCP_MACHINE_REGEX.captures(value) {
+        if matches!(&cap[1], "a2" | "a3" | "g2") {
+            return Some("a GPU machine type".to_string());
+        }
+        let vcpus: u32 = cap[2].parse().ok()?;
+        return (vcpus >= LARGE_INSTANCE_VCPUS).then(|| format!("{} vCPUs", vcpus));
+    }
+
+    // Azure: `Standard_D32s_v5`, `Standard_NC6s_v3`
+    if let Some(cap) = AZURE_SIZE_REGEX.captures(value) {
+        if cap[1].starts_with('N') {
+            return Some("a GPU VM size".to_string());
+        }
+        let vcpus: u32 = cap[2].parse().ok()?;
+        return (vcpus >= LARGE_INSTANCE_VCPUS).then(|| format!("{} vCPUs", vcpus));
+    }
+
+    None
+}
+
+fn impact(category: CostCategory) -> CostImpact {
+    match category {
+        CostCategory::NatGateway | CostCategory::LargeInstance | CostCategory::ProvisionedIops => {
+            CostImpact::High
+        }
+        CostCategory::LoadBalancer | CostCategory::InterRegionTransfer => CostImpact::Medium,
+    }
+}
+
+fn guidance(category: CostCategory) -> &'static str {
+    match category {
+        CostCategory::NatGateway => {
+            "Share NAT gateways across subnets where availability allows, and route S3/DynamoDB or other AWS API traffic through VPC endpoints to avoid per-GB processing charges"
+        }
+        CostCategory::LargeInstance => {
+            "Confirm the size matches measured load; consider a smaller type with autoscaling, Graviton/ARM families, or reserved/spot capacity for steady or interruptible workloads"
+        }
+        CostCategory::ProvisionedIops => {
+            "Provisioned IOPS bill whether used or not; gp3 (or pd-balanced/Premium SSD) with tuned throughput is usually enough outside latency-critical databases"
+        }
+        CostCategory::LoadBalancer => {
+            "Each load balancer has a fixed hourly charge; consolidate services behind one with host or path based routing where possible"
+        }
+        CostCategory::InterRegionTransfer => {
+            "Data leaving a region is billed per GB; replicate only what recovery or latency requires and check the expected transfer volume"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_CONFIG: &str = r#"
+resource "aws_nat_gateway" "main" {
+  allocation_id = aws_eip.nat.id
+  subnet_id     = aws_subnet.public.id
+}
+
+resource "aws_instance" "analytics" {
+  ami           = "ami-12345678"
+  instance_type = "r5.8xlarge"
+}
+
+resource "aws_instance" "bastion" {
+  ami           = "ami-12345678"
+  instance_type = "t3.micro"
+}
+
+resource "aws_instance" "worker" {
+  instance_type = var.worker_type
+}
+
+resource "aws_ebs_volume" "db" {
+  availability_zone = "us-east-1a"
+  type              = "io2"
+  iops              = 10000
+}
+
+resource "aws_lb" "web" {
+  name = "web"
+}
+"#;
+
+    fn report_for(content: &str) -> CostDriverReport {
+        let mut files = HashMap::new();
+        files.insert("main.tf".to_string(), content.to_string());
+        identify_cost_drivers(&files)
+    }
+
+    #[test]
+    fn test_flags_nat_gateway_and_large_instance() {
+        let report = report_for(SAMPLE_CONFIG);
+        assert_eq!(report.resources_scanned, 6);
+
+        let flagged: Vec<_> = report
+            .drivers
+            .iter()
+            .map(|d| (d.address.as_str(), d.category, d.impact))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (
+                    "aws_nat_gateway.main",
+                    CostCategory::NatGateway,
+                    CostImpact::High
+                ),
+                (
+                    "aws_instance.analytics",
+                    CostCategory::LargeInstance,
+                    CostImpact::High
+                ),
+                (
+                    "aws_ebs_volume.db",
+                    CostCategory::ProvisionedIops,
+                    CostImpact::High
+                ),
+                ("aws_lb.web", CostCategory::LoadBalancer, CostImpact::Medium),
+            ]
+        );
+
+        let nat = &report.drivers[0];
+        assert_eq!((nat.file.as_str(), nat.line), ("main.tf", 2));
+        assert!(nat.guidance.contains("VPC endpoints"));
+        assert_eq!(
+            report.drivers[1].reason,
+            "instance_type = \"r5.8xlarge\" is 32 vCPUs"
+        );
+    }
+
+    #[test]
+    fn test_instance_sizes_across_providers() {
+        assert!(large_instance("m5.4xlarge").is_some());
+        assert!(large_instance("m5.2xlarge").is_none());
+        assert!(large_instance("db.r6g.large").is_none());
+        assert!(large_instance("p4d.24xlarge").unwrap().contains("GPU"));
+        assert!(large_instance("g5.xlarge").unwrap().contains("GPU"));
+        assert!(large_instance("c5.metal").is_some());
+        assert!(large_instance("n2-standard-32").is_some());
+        assert!(large_instance("e2-medium").is_none());
+        assert!(large_instance("a2-highgpu-1g").unwrap().contains("GPU"));
+        assert!(large_instance("Standard_D32s_v5").is_some());
+        assert!(large_instance("Standard_B2s").is_none());
+        assert!(large_instance("Standard_NC6s_v3").unwrap().contains("GPU"));
+    }
+
+    #[test]
+    fn test_no_drivers_in_small_config() {
+        let report = report_for("resource \"aws_s3_bucket\" \"logs\" {\n  bucket = \"logs\"\n}\n");
+        assert_eq!(report.resources_scanned, 1);
+        assert!(report.drivers.is_empty());
+        assert!(report.message.starts_with("No common cost drivers"));
+    }
+}
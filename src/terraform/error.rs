@@ -0,0 +1,317 @@
+//! Typed errors for Terraform command failures.
+//!
+//! Service methods return `anyhow::Result`, but failures of the terraform
+//! binary itself are raised as [`TerraformError`] so callers can recover the
+//! variant with `downcast_ref` and react to it (e.g. map it to a JSON-RPC
+//! error code) instead of matching on message text.
+
+use crate::terraform::model::TerraformDiagnostic;
+use std::path::PathBuf;
+use std::process::Output;
+use thiserror::Error;
+
+/// Stderr fragments Terraform emits when the working directory needs `terraform init`.
+const INIT_REQUIRED_SIGNATURES: &[&str] = &[
+    "terraform init",
+    "Backend initialization required",
+    "Module not installed",
+    "Required plugins are not installed",
+    "missing or corrupted provider plugins",
+    "Inconsistent dependency lock file",
+];
+
+/// Stderr fragments Terraform emits when another process holds the state lock.
+const STATE_LOCKED_SIGNATURES: &[&str] = &[
+    "Error acquiring the state lock",
+    "state lock is already held",
+    "ConditionalCheckFailedException",
+];
+
+/// Diagnostics from the `"type": "diagnostic"` messages a command run with
+/// `-json` streams to stdout; other lines are ignored
+pub fn json_diagnostics(stdout: &str) -> Vec<TerraformDiagnostic> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let message: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+            if message.get("type")?.as_str()? != "diagnostic" {
+                return None;
+            }
+            serde_json::from_value(message.get("diagnostic")?.clone()).ok()
+        })
+        .collect()
+}
+
+/// One line per diagnostic, e.g. `Error: Invalid AMI (aws_instance.web): ...`
+fn describe_diagnostics(diagnostics: &[TerraformDiagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut line = match diagnostic.severity.as_str() {
+                "warning" => format!("Warning: {}", diagnostic.summary),
+                _ => format!("Error: {}", diagnostic.summary),
+            };
+            if let Some(address) = &diagnostic.address {
+                line.push_str(&format!(" ({})", address));
+            }
+            if let Some(detail) = diagnostic.detail.as_deref().filter(|d| !d.is_empty()) {
+                line.push_str(&format!(": {}", detail));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns true when Terraform's stderr indicates the project has not been initialized.
+pub fn is_init_required(stderr: &str) -> bool {
+    INIT_REQUIRED_SIGNATURES
+        .iter()
+        .any(|signature| stderr.contains(signature))
+}
+
+#[derive(Error, Debug)]
+pub enum TerraformError {
+    #[error(
+        "Terraform binary not found at '{}'. Install Terraform or set TFMCP_TERRAFORM_BIN to its path.",
+        path.display()
+    )]
+    BinaryNotFound { path: PathBuf },
+
+    #[error(
+        "Terraform initialization required. Please run 'terraform init' first, or retry with auto_init enabled."
+    )]
+    InitRequired,
+
+    #[error(
+        "Terraform state is locked{}. Wait for the other operation to finish, or run 'terraform force-unlock' if it is stale. {stderr}",
+        lock_id.as_deref().map(|id| format!(" (lock ID {})", id)).unwrap_or_default()
+    )]
+    StateLocked {
+        lock_id: Option<String>,
+        stderr: String,
+    },
+
+    #[error("Terraform validate failed: {0}")]
+    ValidationFailed(String),
+
+    #[error("{0}")]
+    PolicyBlocked(String),
+
+    #[error(
+        "Plan file '{plan_file}' changed since it was saved (expected sha256 {expected}, found {actual}). Save and review the plan again before applying."
+    )]
+    PlanFileChanged {
+        plan_file: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("{0}")]
+    ApprovalRejected(String),
+
+    #[error("Operation cancelled: tfmcp is shutting down")]
+    Cancelled,
+
+    #[error("Terraform {command} failed: {stderr}")]
+    ExecFailed {
+        command: String,
+        stderr: String,
+        /// Diagnostics the command printed as JSON on stdout
+        diagnostics: Vec<TerraformDiagnostic>,
+    },
+
+    #[error("Failed to run terraform: {0}")]
+    Io(#[source] std::io::Error),
+}
+
+impl TerraformError {
+    /// Classify a failed `terraform <command>` by its stderr
+    pub fn from_stderr(command: &str, stderr: &str) -> Self {
+        // `terraform init` failures mention init too, but retrying init won't help
+        if command != "init" && is_init_required(stderr) {
+            return Self::InitRequired;
+        }
+        if STATE_LOCKED_SIGNATURES
+            .iter()
+            .any(|signature| stderr.contains(signature))
+        {
+            return Self::StateLocked {
+                lock_id: lock_id(stderr),
+                stderr: stderr.trim().to_string(),
+            };
+        }
+        if command == "validate" {
+            return Self::ValidationFailed(stderr.to_string());
+        }
+        Self::ExecFailed {
+            command: command.to_string(),
+            stderr: stderr.to_string(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Classify the output of a failed `terraform <command>`. Commands run
+    /// with `-json` report errors as diagnostics on stdout; these are parsed
+    /// and reported alongside stderr. Other stdout is used when stderr is empty.
+    pub fn from_output(command: &str, output: &Output) -> Self {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let diagnostics = json_diagnostics(&stdout);
+
+        let message = match (stderr.trim().is_empty(), diagnostics.is_empty()) {
+            (true, true) => stdout.to_string(),
+            (true, false) => describe_diagnostics(&diagnostics),
+            (false, true) => stderr.to_string(),
+            (false, false) => format!(
+                "{}\n{}",
+                stderr.trim_end(),
+                describe_diagnostics(&diagnostics)
+            ),
+        };
+        let mut error = Self::from_stderr(command, &message);
+        if let Self::ExecFailed {
+            diagnostics: parsed,
+            ..
+        } = &mut error
+        {
+            *parsed = diagnostics;
+        }
+        error
+    }
+
+    /// Classify an error spawning or waiting on the terraform binary at `path`
+    pub fn from_io(path: PathBuf, error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => Self::BinaryNotFound { path },
+            std::io::ErrorKind::Interrupted => Self::Cancelled,
+            _ => Self::Io(error),
+        }
+    }
+
+    /// Stable snake_case name of the variant, for structured error data
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::BinaryNotFound { .. } => "binary_not_found",
+            Self::InitRequired => "init_required",
+            Self::StateLocked { .. } => "state_locked",
+            Self::ValidationFailed(_) => "validation_failed",
+            Self::PolicyBlocked(_) => "policy_blocked",
+            Self::PlanFileChanged { .. } => "plan_file_changed",
+            Self::ApprovalRejected(_) => "approval_rejected",
+            Self::Cancelled => "cancelled",
+            Self::ExecFailed { .. } => "exec_failed",
+            Self::Io(_) => "io",
+        }
+    }
+
+    /// Whether retrying the same request later may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::StateLocked { .. } | Self::Cancelled)
+    }
+}
+
+/// Lock ID from the "Lock Info" block Terraform prints when the state is locked
+fn lock_id(stderr: &str) -> Option<String> {
+    stderr.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("ID:")?;
+        Some(rest.trim().to_string()).filter(|id| !id.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_required_signature() {
+        let err = TerraformError::from_stderr(
+            "plan",
+            "Error: Required plugins are not installed\n\nRun \"terraform init\".",
+        );
+        assert!(matches!(err, TerraformError::InitRequired));
+        assert_eq!(err.kind(), "init_required");
+
+        // A failing init is reported as such, not as "run init"
+        assert!(matches!(
+            TerraformError::from_stderr(
+                "init",
+                "Error: Failed to query providers. Run \"terraform init -upgrade\"."
+            ),
+            TerraformError::ExecFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_state_locked_signature_extracts_lock_id() {
+        let stderr = r#"
+Error: Error acquiring the state lock
+
+Error message: ConditionalCheckFailedException: The conditional request failed
+Lock Info:
+  ID:        0f3c9a1e-2b4d-4c7e-9f10-123456789abc
+  Path:      tfstate/terraform.tfstate
+  Operation: OperationTypeApply
+"#;
+        let err = TerraformError::from_stderr("apply", stderr);
+        match &err {
+            TerraformError::StateLocked { lock_id, .. } => assert_eq!(
+                lock_id.as_deref(),
+                Some("0f3c9a1e-2b4d-4c7e-9f10-123456789abc")
+            ),
+            other => panic!("expected StateLocked, got {:?}", other),
+        }
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("force-unlock"));
+    }
+
+    #[test]
+    fn test_validate_and_generic_failures() {
+        assert!(matches!(
+            TerraformError::from_stderr("validate", "Error: Unsupported argument"),
+            TerraformError::ValidationFailed(_)
+        ));
+        let err = TerraformError::from_stderr("plan", "Error: Invalid reference");
+        assert!(matches!(err, TerraformError::ExecFailed { .. }));
+        assert_eq!(
+            err.to_string(),
+            "Terraform plan failed: Error: Invalid reference"
+        );
+    }
+
+    #[test]
+    fn test_json_diagnostics_from_streamed_plan() {
+        // Captured from a failing `terraform plan -json` (Terraform 1.9)
+        let stdout = r#"{"@level":"info","@message":"Terraform 1.9.5","terraform":"1.9.5","type":"version","ui":"1.2"}
+{"@level":"error","@message":"Error: Invalid AMI","diagnostic":{"severity":"error","summary":"Invalid AMI","detail":"The image id 'ami-123' does not exist","address":"aws_instance.web","range":{"filename":"main.tf","start":{"line":1,"column":1,"byte":0},"end":{"line":1,"column":30,"byte":29}}},"type":"diagnostic"}
+{"@level":"warn","@message":"Warning: Deprecated attribute","diagnostic":{"severity":"warning","summary":"Deprecated attribute","detail":""},"type":"diagnostic"}"#;
+
+        let diagnostics = json_diagnostics(stdout);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].address.as_deref(), Some("aws_instance.web"));
+        assert_eq!(
+            diagnostics[0].range.as_ref().map(|r| r.filename.as_str()),
+            Some("main.tf")
+        );
+        assert_eq!(diagnostics[1].severity, "warning");
+        assert_eq!(
+            describe_diagnostics(&diagnostics),
+            "Error: Invalid AMI (aws_instance.web): The image id 'ami-123' does not exist\nWarning: Deprecated attribute"
+        );
+        assert!(json_diagnostics("Error: not JSON\n").is_empty());
+    }
+
+    #[test]
+    fn test_io_errors() {
+        let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        assert!(matches!(
+            TerraformError::from_io(PathBuf::from("/nope/terraform"), missing),
+            TerraformError::BinaryNotFound { .. }
+        ));
+        let interrupted = std::io::Error::new(std::io::ErrorKind::Interrupted, "cancelled");
+        assert!(matches!(
+            TerraformError::from_io(PathBuf::from("terraform"), interrupted),
+            TerraformError::Cancelled
+        ));
+    }
+}
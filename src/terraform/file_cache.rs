@@ -0,0 +1,338 @@
+//! Per-file content and parse cache keyed by path and modification time.
+//!
+//! Analysis tools re-read every `.tf` file on each call; the cache lets
+//! unchanged files skip both the disk read and the parser within a session.
+//! [`FileCache::read_all`] loads many files concurrently with `tokio::fs`, so
+//! large modules don't block the runtime one file at a time.
+
+use crate::terraform::model::{
+    RemoteStateDependency, StateEncryption, TerraformOutput, TerraformProvider, TerraformResource,
+    TerraformVariable,
+};
+use crate::terraform::parser::TerraformParser;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::task::JoinSet;
+
+/// Most files [`FileCache::read_all`] reads at the same time
+pub const MAX_CONCURRENT_READS: usize = 16;
+
+/// Declarations parsed from a single file
+#[derive(Debug, Clone)]
+pub struct ParsedFile {
+    pub resources: Vec<TerraformResource>,
+    pub variables: Vec<TerraformVariable>,
+    pub outputs: Vec<TerraformOutput>,
+    pub providers: Vec<TerraformProvider>,
+    pub required_version: Option<String>,
+    pub remote_states: Vec<RemoteStateDependency>,
+    /// `(data source name, output name)` pairs read from remote state
+    pub remote_state_references: Vec<(String, String)>,
+    pub state_encryption: Option<StateEncryption>,
+}
+
+impl ParsedFile {
+    fn parse(content: &str, file_name: &str) -> Self {
+        if super::json_config::is_json_config(file_name) {
+            return super::json_config::parse(content, file_name);
+        }
+        let parser = TerraformParser::new(content.to_string());
+        Self {
+            resources: parser.parse_resources(file_name),
+            variables: parser.parse_variables(),
+            outputs: parser.parse_outputs(),
+            providers: parser.parse_providers(),
+            required_version: parser.parse_required_version(),
+            remote_states: parser.parse_remote_states(file_name),
+            remote_state_references: parser.parse_remote_state_references(),
+            state_encryption: parser.parse_state_encryption(file_name),
+        }
+    }
+}
+
+struct CachedFile {
+    modified: SystemTime,
+    len: u64,
+    content: Arc<String>,
+    parsed: Option<Arc<ParsedFile>>,
+}
+
+/// Cache of file contents and parse results, invalidated per file when its
+/// modification time or size changes
+#[derive(Default)]
+pub struct FileCache {
+    entries: Mutex<HashMap<PathBuf, CachedFile>>,
+    parse_count: AtomicUsize,
+}
+
+impl FileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Contents of `path`, re-read only when the file changed since the last call
+    pub fn read(&self, path: &Path) -> std::io::Result<Arc<String>> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata.modified()?;
+        let len = metadata.len();
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = entries.get(path) {
+            if cached.modified == modified && cached.len == len {
+                return Ok(cached.content.clone());
+            }
+        }
+
+        let content = Arc::new(std::fs::read_to_string(path)?);
+        entries.insert(
+            path.to_path_buf(),
+            CachedFile {
+                modified,
+                len,
+                content: content.clone(),
+                parsed: None,
+            },
+        );
+        Ok(content)
+    }
+
+    /// Contents of each of `paths`, in the same order, reading those that
+    /// changed since the last call concurrently (at most
+    /// [`MAX_CONCURRENT_READS`] at once)
+    pub async fn read_all(&self, paths: &[PathBuf]) -> Vec<std::io::Result<Arc<String>>> {
+        let known: Vec<Option<(SystemTime, u64)>> = {
+            let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            paths
+                .iter()
+                .map(|path| entries.get(path).map(|c| (c.modified, c.len)))
+                .collect()
+        };
+
+        let mut results: Vec<Option<std::io::Result<Arc<String>>>> =
+            paths.iter().map(|_| None).collect();
+        let mut reads = JoinSet::new();
+        for (index, (path, known)) in paths.iter().cloned().zip(known).enumerate() {
+            if reads.len() >= MAX_CONCURRENT_READS {
+                if let Some(Ok((index, path, read))) = reads.join_next().await {
+                    results[index] = Some(self.store(path, read));
+                }
+            }
+            reads.spawn(async move {
+                let read = read_if_changed(&path, known).await;
+                (index, path, read)
+            });
+        }
+        while let Some(joined) = reads.join_next().await {
+            if let Ok((index, path, read)) = joined {
+                results[index] = Some(self.store(path, read));
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| Err(std::io::Error::other("file read task failed")))
+            })
+            .collect()
+    }
+
+    /// Cache the outcome of [`read_if_changed`] for `path`
+    fn store(
+        &self,
+        path: PathBuf,
+        read: std::io::Result<FileRead>,
+    ) -> std::io::Result<Arc<String>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match read? {
+            FileRead::Unchanged => match entries.get(&path) {
+                Some(cached) => Ok(cached.content.clone()),
+                // Entry cleared while reading; read it again
+                None => {
+                    drop(entries);
+                    self.read(&path)
+                }
+            },
+            FileRead::Changed {
+                modified,
+                len,
+                content,
+            } => {
+                let content = Arc::new(content);
+                entries.insert(
+                    path,
+                    CachedFile {
+                        modified,
+                        len,
+                        content: content.clone(),
+                        parsed: None,
+                    },
+                );
+                Ok(content)
+            }
+        }
+    }
+
+    /// Parsed declarations of each of `paths`, in the same order; files are
+    /// read as in [`FileCache::read_all`]
+    pub async fn parse_all(&self, paths: &[PathBuf]) -> Vec<std::io::Result<Arc<ParsedFile>>> {
+        let contents = self.read_all(paths).await;
+        paths
+            .iter()
+            .zip(contents)
+            .map(|(path, content)| Ok(self.parse_read(path, content?)))
+            .collect()
+    }
+
+    /// Parsed declarations of `path`, re-parsed only when the file changed
+    #[allow(dead_code)]
+    pub fn parse(&self, path: &Path) -> std::io::Result<Arc<ParsedFile>> {
+        let content = self.read(path)?;
+        Ok(self.parse_read(path, content))
+    }
+
+    /// Parse `content` just read for `path`, reusing the cached parse when
+    /// the cache still holds that same content
+    fn parse_read(&self, path: &Path, content: Arc<String>) -> Arc<ParsedFile> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(cached) = entries.get_mut(path) else {
+            // Entry evicted between read and parse; parse without caching
+            return Arc::new(self.parse_content(path, &content));
+        };
+        if !Arc::ptr_eq(&cached.content, &content) {
+            return Arc::new(self.parse_content(path, &content));
+        }
+        if let Some(parsed) = &cached.parsed {
+            return parsed.clone();
+        }
+
+        let parsed = Arc::new(self.parse_content(path, &content));
+        cached.parsed = Some(parsed.clone());
+        parsed
+    }
+
+    fn parse_content(&self, path: &Path, content: &str) -> ParsedFile {
+        self.parse_count.fetch_add(1, Ordering::Relaxed);
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        ParsedFile::parse(content, &file_name)
+    }
+
+    /// Number of times a file has been parsed since the cache was created
+    #[allow(dead_code)]
+    pub fn parse_count(&self) -> usize {
+        self.parse_count.load(Ordering::Relaxed)
+    }
+
+    /// Drop all cached entries
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+/// Result of reading a file the cache may already hold
+enum FileRead {
+    /// Modification time and size match the cached entry
+    Unchanged,
+    Changed {
+        modified: SystemTime,
+        len: u64,
+        content: String,
+    },
+}
+
+/// Read `path` with `tokio::fs` unless its modification time and size
+/// still match `known`
+async fn read_if_changed(
+    path: &Path,
+    known: Option<(SystemTime, u64)>,
+) -> std::io::Result<FileRead> {
+    let metadata = tokio::fs::metadata(path).await?;
+    let modified = metadata.modified()?;
+    let len = metadata.len();
+    if known == Some((modified, len)) {
+        return Ok(FileRead::Unchanged);
+    }
+    Ok(FileRead::Changed {
+        modified,
+        len,
+        content: tokio::fs::read_to_string(path).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_unchanged_file_is_served_from_cache() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("main.tf");
+        fs::write(&path, "resource \"aws_vpc\" \"main\" {}\n").unwrap();
+
+        let cache = FileCache::new();
+        let first = cache.parse(&path).unwrap();
+        let second = cache.parse(&path).unwrap();
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.parse_count(), 1);
+        assert_eq!(first.resources[0].resource_type, "aws_vpc");
+    }
+
+    #[test]
+    fn test_modified_file_is_reparsed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("main.tf");
+        fs::write(&path, "resource \"aws_vpc\" \"main\" {}\n").unwrap();
+
+        let cache = FileCache::new();
+        cache.parse(&path).unwrap();
+
+        fs::write(&path, "resource \"aws_subnet\" \"main\" {}\n").unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+
+        let parsed = cache.parse(&path).unwrap();
+        assert_eq!(cache.parse_count(), 2);
+        assert_eq!(parsed.resources[0].resource_type, "aws_subnet");
+    }
+
+    #[tokio::test]
+    async fn test_read_all_keeps_input_order() {
+        let dir = tempdir().unwrap();
+        let mut paths: Vec<PathBuf> = (0..(MAX_CONCURRENT_READS * 3))
+            .map(|i| {
+                let path = dir.path().join(format!("file{:02}.tf", i));
+                fs::write(&path, format!("resource \"aws_vpc\" \"v{}\" {{}}\n", i)).unwrap();
+                path
+            })
+            .collect();
+        paths.push(dir.path().join("missing.tf"));
+
+        let cache = FileCache::new();
+        let contents = cache.read_all(&paths).await;
+        assert_eq!(contents.len(), paths.len());
+        for (i, content) in contents[..paths.len() - 1].iter().enumerate() {
+            assert!(content.as_ref().unwrap().contains(&format!("\"v{}\"", i)));
+        }
+        assert!(contents.last().unwrap().is_err());
+
+        // A second pass serves unchanged files from the cache, parsed once
+        let parsed = cache.parse_all(&paths[..3]).await;
+        let again = cache.parse_all(&paths[..3]).await;
+        assert!(Arc::ptr_eq(
+            parsed[2].as_ref().unwrap(),
+            again[2].as_ref().unwrap()
+        ));
+        assert_eq!(cache.parse_count(), 3);
+        assert_eq!(again[1].as_ref().unwrap().resources[0].name, "v1");
+    }
+}
@@ -0,0 +1,353 @@
+//! Project file listing backing the `terraform://files` resource, and
+//! discovery of Terraform root directories for `analyze_all`.
+
+use crate::shared::security::wildcard_match;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Maximum number of declarations included in a file preview
+const MAX_PREVIEW_DECLARATIONS: usize = 5;
+
+/// Upper bound on the directories one `analyze_all` call analyzes
+pub const MAX_ANALYZE_DIRECTORIES: usize = 50;
+
+/// Top-level block keywords surfaced in file previews
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "resource", "data", "module", "variable", "output", "provider", "locals",
+];
+
+/// A Terraform or tfvars file found in the project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectFile {
+    /// Path relative to the project directory, using '/' separators
+    pub path: String,
+    pub size: u64,
+    /// First declarations in the file, e.g. `resource "aws_vpc" "main"`
+    pub preview: Vec<String>,
+}
+
+/// Recursively list `.tf` and `.tfvars` files under `root`, sorted by path.
+///
+/// Hidden directories such as `.terraform` are skipped, as is any file for
+/// which `is_blocked` returns true.
+pub fn list_project_files(
+    root: &Path,
+    is_blocked: impl Fn(&Path) -> bool,
+) -> anyhow::Result<Vec<ProjectFile>> {
+    let mut files = Vec::new();
+    collect_files(root, root, &is_blocked, &mut files)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    is_blocked: &impl Fn(&Path) -> bool,
+    files: &mut Vec<ProjectFile>,
+) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if path.is_dir() {
+            if !name.starts_with('.') {
+                collect_files(root, &path, is_blocked, files)?;
+            }
+            continue;
+        }
+
+        if !is_terraform_file(&name) || is_blocked(&path) {
+            continue;
+        }
+
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        files.push(ProjectFile {
+            path: relative,
+            size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            preview: preview_declarations(&content),
+        });
+    }
+    Ok(())
+}
+
+/// Directories under `root` that directly contain `.tf` files and whose
+/// path relative to `root` matches the glob `pattern`, sorted by path.
+///
+/// Pattern segments are separated by '/'; `*` matches within one segment and
+/// a `**` segment matches any number of them. "." (or an empty pattern) is
+/// the root itself. Hidden directories and those for which `is_blocked`
+/// returns true are not entered. At most `max_directories` are returned; the
+/// flag reports whether more matched.
+pub fn find_terraform_directories(
+    root: &Path,
+    pattern: &str,
+    is_blocked: impl Fn(&Path) -> bool,
+    max_directories: usize,
+) -> anyhow::Result<(Vec<String>, bool)> {
+    let segments = glob_segments(pattern)?;
+    let mut found = Vec::new();
+    let truncated = collect_directories(
+        root,
+        &mut Vec::new(),
+        &segments,
+        &is_blocked,
+        max_directories,
+        &mut found,
+    )?;
+    Ok((found, truncated))
+}
+
+/// Split a glob into path segments, rejecting patterns that leave the root
+fn glob_segments(pattern: &str) -> anyhow::Result<Vec<&str>> {
+    let trimmed = pattern.trim();
+    if trimmed.starts_with('/') || trimmed.starts_with('~') {
+        return Err(anyhow::anyhow!(
+            "Pattern '{}' must be relative to the project directory",
+            pattern
+        ));
+    }
+    let segments: Vec<&str> = trimmed
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect();
+    if segments.contains(&"..") {
+        return Err(anyhow::anyhow!(
+            "Pattern '{}' must not leave the project directory",
+            pattern
+        ));
+    }
+    Ok(segments)
+}
+
+/// Match path segments against glob segments
+fn glob_match(pattern: &[&str], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_match(rest, &path[skip..])),
+        Some((segment, rest)) => {
+            !path.is_empty() && wildcard_match(segment, &path[0]) && glob_match(rest, &path[1..])
+        }
+    }
+}
+
+/// Depth-first walk in name order; returns true once more than `max` matched
+fn collect_directories(
+    dir: &Path,
+    relative: &mut Vec<String>,
+    pattern: &[&str],
+    is_blocked: &impl Fn(&Path) -> bool,
+    max: usize,
+    found: &mut Vec<String>,
+) -> anyhow::Result<bool> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let has_tf = entries.iter().any(|entry| {
+        let path = entry.path();
+        path.is_file() && path.extension().is_some_and(|ext| ext == "tf")
+    });
+    if has_tf && glob_match(pattern, relative) {
+        if found.len() == max {
+            return Ok(true);
+        }
+        found.push(if relative.is_empty() {
+            ".".to_string()
+        } else {
+            relative.join("/")
+        });
+    }
+
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Symlinked directories are not followed: a link back up the tree or
+        // to `/` would otherwise be walked without bound
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        if !is_dir || name.starts_with('.') || is_blocked(&path) {
+            continue;
+        }
+        relative.push(name);
+        let truncated = collect_directories(&path, relative, pattern, is_blocked, max, found)?;
+        relative.pop();
+        if truncated {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn is_terraform_file(name: &str) -> bool {
+    name.ends_with(".tf") || name.ends_with(".tfvars")
+}
+
+/// Extract the headers of the first top-level declarations in a file
+pub fn preview_declarations(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(|line| {
+            let keyword = line.split_whitespace().next()?;
+            if !DECLARATION_KEYWORDS.contains(&keyword) {
+                return None;
+            }
+            let header = line.split('{').next().unwrap_or(line);
+            Some(header.trim_end().to_string())
+        })
+        .take(MAX_PREVIEW_DECLARATIONS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_preview_declarations() {
+        let content = r#"
+terraform {
+  required_version = ">= 1.5.0"
+}
+
+variable "region" {
+  default = "us-east-1"
+}
+
+resource "aws_vpc" "main" {
+  cidr_block = "10.0.0.0/16"
+  tags = {
+    resource = "nested"
+  }
+}
+"#;
+        assert_eq!(
+            preview_declarations(content),
+            vec![
+                r#"variable "region""#.to_string(),
+                r#"resource "aws_vpc" "main""#.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preview_is_truncated() {
+        let content: String = (0..10)
+            .map(|i| format!("variable \"v{}\" {{}}\n", i))
+            .collect();
+        assert_eq!(
+            preview_declarations(&content).len(),
+            MAX_PREVIEW_DECLARATIONS
+        );
+    }
+
+    #[test]
+    fn test_list_project_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let main_tf = "resource \"null_resource\" \"a\" {}\n";
+        fs::write(root.join("main.tf"), main_tf).unwrap();
+        fs::write(root.join("terraform.tfvars"), "region = \"us-east-1\"\n").unwrap();
+        fs::write(root.join("README.md"), "# docs").unwrap();
+        fs::write(root.join("blocked.tf"), "variable \"x\" {}\n").unwrap();
+        fs::create_dir_all(root.join("modules/vpc")).unwrap();
+        fs::write(root.join("modules/vpc/main.tf"), "variable \"cidr\" {}\n").unwrap();
+        fs::create_dir_all(root.join(".terraform/modules")).unwrap();
+        fs::write(root.join(".terraform/modules/cached.tf"), "").unwrap();
+
+        let files = list_project_files(root, |path| path.ends_with("blocked.tf")).unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+
+        assert_eq!(
+            paths,
+            vec!["main.tf", "modules/vpc/main.tf", "terraform.tfvars"]
+        );
+        assert_eq!(files[0].size, main_tf.len() as u64);
+        assert_eq!(files[0].preview, vec![r#"resource "null_resource" "a""#]);
+        assert_eq!(files[1].preview, vec![r#"variable "cidr""#]);
+        assert!(files[2].preview.is_empty());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        let path = |p: &str| p.split('/').map(String::from).collect::<Vec<_>>();
+        assert!(glob_match(
+            &["environments", "*"],
+            &path("environments/dev")
+        ));
+        assert!(!glob_match(
+            &["environments", "*"],
+            &path("environments/dev/app")
+        ));
+        assert!(glob_match(&["**"], &[]));
+        assert!(glob_match(&["**", "stack-*"], &path("a/b/stack-1")));
+        assert!(glob_match(&["env*", "**"], &path("envs/prod/app")));
+        assert!(!glob_match(&["modules", "*"], &path("environments/dev")));
+    }
+
+    #[test]
+    fn test_glob_segments_stay_in_root() {
+        assert_eq!(
+            glob_segments("environments/*/").unwrap(),
+            vec!["environments", "*"]
+        );
+        assert!(glob_segments(".").unwrap().is_empty());
+        assert!(glob_segments("../other/*").is_err());
+        assert!(glob_segments("/etc/*").is_err());
+    }
+
+    #[test]
+    fn test_find_terraform_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for env in ["dev", "staging", "blocked", "empty"] {
+            fs::create_dir_all(root.join("environments").join(env)).unwrap();
+        }
+        for env in ["dev", "staging", "blocked"] {
+            fs::write(root.join("environments").join(env).join("main.tf"), "").unwrap();
+        }
+        fs::write(root.join("environments/empty/README.md"), "").unwrap();
+        fs::create_dir_all(root.join("environments/dev/.terraform")).unwrap();
+        fs::write(root.join("environments/dev/.terraform/cached.tf"), "").unwrap();
+        fs::write(root.join("main.tf"), "").unwrap();
+
+        let is_blocked = |path: &Path| path.ends_with("blocked");
+        let (found, truncated) =
+            find_terraform_directories(root, "environments/*/", is_blocked, 10).unwrap();
+        assert_eq!(found, vec!["environments/dev", "environments/staging"]);
+        assert!(!truncated);
+
+        let (found, _) = find_terraform_directories(root, "**", is_blocked, 10).unwrap();
+        assert_eq!(found, vec![".", "environments/dev", "environments/staging"]);
+
+        let (found, truncated) = find_terraform_directories(root, "**", is_blocked, 2).unwrap();
+        assert_eq!(found, vec![".", "environments/dev"]);
+        assert!(truncated);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_terraform_directories_skips_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("modules/vpc")).unwrap();
+        fs::write(root.join("modules/vpc/main.tf"), "").unwrap();
+        // A loop back to the root and a link out of the project
+        std::os::unix::fs::symlink(root, root.join("modules/vpc/loop")).unwrap();
+        std::os::unix::fs::symlink("/", root.join("rootfs")).unwrap();
+
+        let (found, truncated) = find_terraform_directories(root, "**", |_| false, 10).unwrap();
+        assert_eq!(found, vec!["modules/vpc"]);
+        assert!(!truncated);
+    }
+}
@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::path::Path;
-use std::process::Command;
+use std::process::Output;
 
 /// Format check result for a single file
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,67 +23,40 @@ pub struct FormatResult {
     pub message: String,
 }
 
-/// Check formatting without making changes
-pub fn check_format(
-    terraform_path: &Path,
-    project_dir: &Path,
-    file: Option<&str>,
-) -> anyhow::Result<FormatResult> {
-    format_internal(terraform_path, project_dir, file, true, false)
-}
-
-/// Format files and show diff
-pub fn format_with_diff(
-    terraform_path: &Path,
-    project_dir: &Path,
-    file: Option<&str>,
-) -> anyhow::Result<FormatResult> {
-    format_internal(terraform_path, project_dir, file, false, true)
-}
-
-/// Format files in place
-pub fn format_files(
-    terraform_path: &Path,
-    project_dir: &Path,
-    file: Option<&str>,
-) -> anyhow::Result<FormatResult> {
-    format_internal(terraform_path, project_dir, file, false, false)
-}
-
-/// Internal format implementation
-fn format_internal(
-    terraform_path: &Path,
-    project_dir: &Path,
-    file: Option<&str>,
-    check_only: bool,
-    show_diff: bool,
-) -> anyhow::Result<FormatResult> {
-    let mut cmd = Command::new(terraform_path);
-    cmd.arg("fmt");
+/// Arguments of `terraform fmt`: a check without changes (`check_only`),
+/// formatting with a diff (`show_diff`), or formatting in place
+pub fn format_args(file: Option<&str>, check_only: bool, show_diff: bool) -> Vec<String> {
+    let mut args = vec!["fmt".to_string()];
 
     if check_only {
-        cmd.arg("-check");
+        args.push("-check".to_string());
     }
 
     if show_diff {
-        cmd.arg("-diff");
+        args.push("-diff".to_string());
     }
 
     // List files that would be formatted
-    cmd.arg("-list=true");
+    args.push("-list=true".to_string());
 
     // Recursive formatting
-    cmd.arg("-recursive");
+    args.push("-recursive".to_string());
 
     // If a specific file is provided, use it
     if let Some(file_path) = file {
-        cmd.arg(file_path);
+        args.push(file_path.to_string());
     }
 
-    cmd.current_dir(project_dir);
-
-    let output = cmd.output()?;
+    args
+}
 
+/// Result of a run with [`format_args`] in `project_dir`
+pub fn format_result(
+    output: &Output,
+    project_dir: &Path,
+    check_only: bool,
+    show_diff: bool,
+) -> FormatResult {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
@@ -133,14 +106,14 @@ fn format_internal(
         "No files needed formatting".to_string()
     };
 
-    Ok(FormatResult {
+    FormatResult {
         success,
         files_checked: all_tf_files as i32,
         files_formatted: files_formatted as i32,
         files_unchanged: files_unchanged as i32,
         file_results,
         message,
-    })
+    }
 }
 
 /// Count .tf files in a directory (recursive)
@@ -1,9 +1,11 @@
 //! Terraform graph output for dependency visualization.
 
+use crate::terraform::model::{
+    DependencyType, ModuleBoundary, ResourceDependencyGraph, ResourceEdge, ResourceNode,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
-use std::process::Command;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::process::Output;
 
 /// Graph node representing a resource or module
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,25 +90,19 @@ impl std::str::FromStr for GraphType {
     }
 }
 
-/// Generate terraform graph
-pub fn generate_graph(
-    terraform_path: &Path,
-    project_dir: &Path,
-    graph_type: Option<GraphType>,
-) -> anyhow::Result<TerraformGraph> {
-    let mut cmd = Command::new(terraform_path);
-    cmd.arg("graph");
-
-    // Add type filter if specified
-    if let Some(gt) = &graph_type {
-        match gt {
-            GraphType::Plan => cmd.arg("-type=plan"),
-            GraphType::Apply => cmd.arg("-type=apply"),
-        };
+/// Arguments of `terraform graph`, with the type filter if specified
+pub fn graph_args(graph_type: Option<GraphType>) -> Vec<String> {
+    let mut args = vec!["graph".to_string()];
+    match graph_type {
+        Some(GraphType::Plan) => args.push("-type=plan".to_string()),
+        Some(GraphType::Apply) => args.push("-type=apply".to_string()),
+        None => {}
     }
+    args
+}
 
-    let output = cmd.current_dir(project_dir).output()?;
-
+/// Graph from a run with [`graph_args`]
+pub fn graph_result(output: &Output) -> anyhow::Result<TerraformGraph> {
     if !output.status.success() {
         return Err(anyhow::anyhow!(
             "Failed to generate graph: {}",
@@ -320,6 +316,196 @@ fn determine_edge_type(from: &str, to: &str) -> GraphEdgeType {
     GraphEdgeType::Reference
 }
 
+/// Module path used for resources in the root module
+const ROOT_MODULE_PATH: &str = "root";
+
+/// Convert `terraform graph` DOT output into the resource dependency graph
+/// structure produced by the internal analyzer.
+///
+/// Only resources and data sources become nodes. Dependencies routed through
+/// meta-nodes (`[root]`, variables, locals, outputs, module expansions) are
+/// collapsed into direct edges, and provider nodes are used solely to
+/// attribute each resource to its provider.
+pub fn dot_to_dependency_graph(dot: &str) -> ResourceDependencyGraph {
+    let mut node_ids: BTreeSet<String> = BTreeSet::new();
+    let mut adjacency: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for line in dot.lines() {
+        let quoted = quoted_strings(line);
+        let Some(first) = quoted.first() else {
+            continue;
+        };
+        let from = normalize_dot_id(first);
+
+        let is_edge = quoted.len() >= 2
+            && line
+                .find("->")
+                .is_some_and(|arrow| arrow > line.find('"').unwrap_or(0));
+        if is_edge {
+            let to = normalize_dot_id(&quoted[1]);
+            node_ids.insert(from.clone());
+            node_ids.insert(to.clone());
+            adjacency.entry(from).or_default().insert(to);
+        } else if !line.trim_start().starts_with("subgraph") {
+            node_ids.insert(from);
+        }
+    }
+
+    let mut nodes = Vec::new();
+    for id in node_ids.iter().filter(|id| is_resource_address(id)) {
+        let (module_path, resource_type, resource_name) = split_resource_address(id);
+        let provider = adjacency
+            .get(id)
+            .and_then(|targets| targets.iter().find_map(|t| provider_from_id(t)))
+            .unwrap_or_else(|| resource_type.split('_').next().unwrap_or("").to_string());
+
+        nodes.push(ResourceNode {
+            id: id.clone(),
+            resource_type,
+            resource_name,
+            module_path,
+            file: String::new(),
+            provider,
+        });
+    }
+
+    let mut edges = Vec::new();
+    for node in &nodes {
+        for target in reachable_resources(&node.id, &adjacency) {
+            let dependency_type = if target.split('.').any(|part| part == "data") {
+                DependencyType::DataSource
+            } else {
+                DependencyType::Implicit
+            };
+            edges.push(ResourceEdge {
+                source: node.id.clone(),
+                target,
+                dependency_type,
+                attribute: None,
+            });
+        }
+    }
+
+    let mut boundaries: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for node in &nodes {
+        boundaries
+            .entry(node.module_path.clone())
+            .or_default()
+            .push(node.id.clone());
+    }
+    let module_boundaries = boundaries
+        .into_iter()
+        .map(|(module_path, resource_ids)| ModuleBoundary {
+            module_path,
+            resource_ids,
+        })
+        .collect();
+
+    ResourceDependencyGraph {
+        nodes,
+        edges,
+        module_boundaries,
+    }
+}
+
+/// Extract the double-quoted strings on a DOT line, unescaping `\"`
+fn quoted_strings(line: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (&mut current, c) {
+            (None, '"') => current = Some(String::new()),
+            (Some(s), '\\') => {
+                if let Some(escaped) = chars.next() {
+                    s.push(escaped);
+                }
+            }
+            (Some(_), '"') => strings.extend(current.take()),
+            (Some(s), c) => s.push(c),
+            (None, _) => {}
+        }
+    }
+    strings
+}
+
+/// Strip the `[root] ` prefix and ` (expand)`/` (close)` style suffixes
+fn normalize_dot_id(id: &str) -> String {
+    let id = id.strip_prefix("[root] ").unwrap_or(id);
+    match id.rfind(" (") {
+        Some(pos) if id.ends_with(')') => id[..pos].to_string(),
+        _ => id.to_string(),
+    }
+}
+
+/// Whether a normalized node id addresses a resource or data source
+fn is_resource_address(id: &str) -> bool {
+    let mut parts: Vec<&str> = id.split('.').collect();
+    while parts.len() >= 2 && parts[0] == "module" {
+        parts.drain(..2);
+    }
+    match parts.as_slice() {
+        ["data", resource_type, _] => resource_type.contains('_'),
+        [resource_type, _] => resource_type.contains('_') && !resource_type.contains('['),
+        _ => false,
+    }
+}
+
+/// Split an address into (module path, resource type, resource name)
+fn split_resource_address(id: &str) -> (String, String, String) {
+    let parts: Vec<&str> = id.split('.').collect();
+    let module_len = parts
+        .chunks(2)
+        .take_while(|pair| pair.len() == 2 && pair[0] == "module")
+        .count()
+        * 2;
+    let module_path = if module_len == 0 {
+        ROOT_MODULE_PATH.to_string()
+    } else {
+        parts[..module_len].join(".")
+    };
+    let rest = &parts[module_len..];
+    let resource_name = rest.last().copied().unwrap_or_default().to_string();
+    let resource_type = rest[..rest.len().saturating_sub(1)].join(".");
+    (module_path, resource_type, resource_name)
+}
+
+/// Provider short name from a `provider["registry.terraform.io/hashicorp/aws"]` node
+fn provider_from_id(id: &str) -> Option<String> {
+    let source = id.strip_prefix("provider[")?.split(']').next()?;
+    source
+        .trim_matches('"')
+        .rsplit('/')
+        .next()
+        .map(|name| name.to_string())
+}
+
+/// Resources reachable from `start`, following edges through meta-nodes but
+/// not through other resources or providers
+fn reachable_resources(start: &str, adjacency: &BTreeMap<String, BTreeSet<String>>) -> Vec<String> {
+    let mut found = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut stack: Vec<&str> = adjacency
+        .get(start)
+        .map(|targets| targets.iter().map(String::as_str).collect())
+        .unwrap_or_default();
+
+    while let Some(id) = stack.pop() {
+        if id == start || !visited.insert(id) || id.starts_with("provider") {
+            continue;
+        }
+        if is_resource_address(id) {
+            found.insert(id.to_string());
+            continue;
+        }
+        if let Some(targets) = adjacency.get(id) {
+            stack.extend(targets.iter().map(String::as_str));
+        }
+    }
+    found.into_iter().collect()
+}
+
 /// Calculate graph statistics
 fn calculate_statistics(nodes: &[GraphNode], edges: &[GraphEdge]) -> GraphStatistics {
     let mut resource_count = 0;
@@ -421,6 +607,110 @@ mod tests {
         assert_eq!(node_type, GraphNodeType::Root);
     }
 
+    /// Captured from `terraform graph -type=plan` (Terraform 1.11)
+    const LEGACY_DOT: &str = r#"digraph {
+	compound = "true"
+	newrank = "true"
+	subgraph "root" {
+		"[root] provider[\"registry.terraform.io/hashicorp/aws\"]" [label = "provider[\"registry.terraform.io/hashicorp/aws\"]", shape = "diamond"]
+		"[root] aws_instance.web (expand)" [label = "aws_instance.web", shape = "box"]
+		"[root] aws_vpc.main (expand)" [label = "aws_vpc.main", shape = "box"]
+		"[root] data.aws_ami.ubuntu (expand)" [label = "data.aws_ami.ubuntu", shape = "box"]
+		"[root] module.db.aws_db_instance.this (expand)" [label = "module.db.aws_db_instance.this", shape = "box"]
+		"[root] var.name" [label = "var.name", shape = "note"]
+		"[root] aws_instance.web (expand)" -> "[root] local.subnet (expand)"
+		"[root] aws_instance.web (expand)" -> "[root] data.aws_ami.ubuntu (expand)"
+		"[root] aws_instance.web (expand)" -> "[root] provider[\"registry.terraform.io/hashicorp/aws\"]"
+		"[root] aws_vpc.main (expand)" -> "[root] provider[\"registry.terraform.io/hashicorp/aws\"]"
+		"[root] local.subnet (expand)" -> "[root] aws_vpc.main (expand)"
+		"[root] local.subnet (expand)" -> "[root] var.name"
+		"[root] module.db.aws_db_instance.this (expand)" -> "[root] module.db (expand)"
+		"[root] module.db (expand)" -> "[root] aws_vpc.main (expand)"
+		"[root] provider[\"registry.terraform.io/hashicorp/aws\"] (close)" -> "[root] aws_instance.web (expand)"
+		"[root] root" -> "[root] provider[\"registry.terraform.io/hashicorp/aws\"] (close)"
+	}
+}
+"#;
+
+    /// Captured from `terraform graph` (Terraform 1.11)
+    const MODERN_DOT: &str = r#"digraph G {
+  rankdir = "RL";
+  node [shape = rect, fontname = "sans-serif"];
+  "terraform_data.a" [label="terraform_data.a"];
+  "terraform_data.b" [label="terraform_data.b"];
+  "terraform_data.b" -> "terraform_data.a";
+}
+"#;
+
+    #[test]
+    fn test_dot_to_dependency_graph_legacy_format() {
+        let graph = dot_to_dependency_graph(LEGACY_DOT);
+
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "aws_instance.web",
+                "aws_vpc.main",
+                "data.aws_ami.ubuntu",
+                "module.db.aws_db_instance.this"
+            ]
+        );
+
+        let web = &graph.nodes[0];
+        assert_eq!(web.resource_type, "aws_instance");
+        assert_eq!(web.resource_name, "web");
+        assert_eq!(web.module_path, "root");
+        assert_eq!(web.provider, "aws");
+
+        let db = &graph.nodes[3];
+        assert_eq!(db.module_path, "module.db");
+        assert_eq!(db.resource_type, "aws_db_instance");
+
+        let edges: Vec<(&str, &str)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("aws_instance.web", "aws_vpc.main"),
+                ("aws_instance.web", "data.aws_ami.ubuntu"),
+                ("module.db.aws_db_instance.this", "aws_vpc.main"),
+            ]
+        );
+        assert!(matches!(
+            graph.edges[1].dependency_type,
+            DependencyType::DataSource
+        ));
+        assert_eq!(graph.module_boundaries.len(), 2);
+    }
+
+    #[test]
+    fn test_dot_to_dependency_graph_modern_format() {
+        let graph = dot_to_dependency_graph(MODERN_DOT);
+
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[0].provider, "terraform");
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].source, "terraform_data.b");
+        assert_eq!(graph.edges[0].target, "terraform_data.a");
+    }
+
+    #[test]
+    fn test_normalize_dot_id() {
+        assert_eq!(
+            normalize_dot_id("[root] aws_vpc.main (expand)"),
+            "aws_vpc.main"
+        );
+        assert_eq!(
+            normalize_dot_id(r#"[root] provider["registry.terraform.io/hashicorp/aws"] (close)"#),
+            r#"provider["registry.terraform.io/hashicorp/aws"]"#
+        );
+        assert_eq!(normalize_dot_id("var.name"), "var.name");
+    }
+
     #[test]
     fn test_extract_provider() {
         assert_eq!(
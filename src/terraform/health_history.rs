@@ -0,0 +1,186 @@
+//! Persisted module health scores for tracking a project over time.
+//!
+//! Each `analyze_module_health` result is appended as one JSON line to
+//! `<history dir>/<project hash>.jsonl`, where the hash is taken from the
+//! project directory path. The trend reports the stored scores and the
+//! change between the last two runs.
+
+use crate::terraform::model::ModuleHealthAnalysis;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One recorded health analysis
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HealthHistoryEntry {
+    pub recorded_at: DateTime<Utc>,
+    pub health_score: u8,
+    pub issue_count: usize,
+}
+
+/// Score history of a project, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthTrend {
+    pub project_directory: String,
+    pub history: Vec<HealthHistoryEntry>,
+    pub latest_score: Option<u8>,
+    pub previous_score: Option<u8>,
+    /// Latest score minus the previous one; `None` with fewer than two runs
+    pub delta: Option<i16>,
+}
+
+impl HealthTrend {
+    pub fn from_history(project_directory: String, history: Vec<HealthHistoryEntry>) -> Self {
+        let latest_score = history.last().map(|entry| entry.health_score);
+        let previous_score = history
+            .len()
+            .checked_sub(2)
+            .map(|index| history[index].health_score);
+        let delta = latest_score
+            .zip(previous_score)
+            .map(|(latest, previous)| i16::from(latest) - i16::from(previous));
+        Self {
+            project_directory,
+            history,
+            latest_score,
+            previous_score,
+            delta,
+        }
+    }
+}
+
+/// JSONL store of health results, one file per project directory
+pub struct HealthHistory {
+    directory: PathBuf,
+}
+
+impl HealthHistory {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    /// `~/.tfmcp/history`, if the home directory is known
+    pub fn default_directory() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".tfmcp").join("history"))
+    }
+
+    /// History file for `project_directory`
+    pub fn path_for(&self, project_directory: &Path) -> PathBuf {
+        let digest = Sha256::digest(project_directory.to_string_lossy().as_bytes());
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        self.directory.join(format!("{}.jsonl", &hex[..16]))
+    }
+
+    /// Append a health result for `project_directory`
+    pub fn record(
+        &self,
+        project_directory: &Path,
+        health: &ModuleHealthAnalysis,
+    ) -> anyhow::Result<HealthHistoryEntry> {
+        let entry = HealthHistoryEntry {
+            recorded_at: Utc::now(),
+            health_score: health.health_score,
+            issue_count: health.issues.len(),
+        };
+        self.append(project_directory, &entry)?;
+        Ok(entry)
+    }
+
+    fn append(&self, project_directory: &Path, entry: &HealthHistoryEntry) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.directory).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to create history directory {}: {}",
+                self.directory.display(),
+                e
+            )
+        })?;
+        let path = self.path_for(project_directory);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// Recorded entries for `project_directory`, skipping unreadable lines
+    pub fn load(&self, project_directory: &Path) -> anyhow::Result<Vec<HealthHistoryEntry>> {
+        let path = self.path_for(project_directory);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    pub fn trend(&self, project_directory: &Path) -> anyhow::Result<HealthTrend> {
+        let history = self.load(project_directory)?;
+        Ok(HealthTrend::from_history(
+            project_directory.display().to_string(),
+            history,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(score: u8) -> HealthHistoryEntry {
+        HealthHistoryEntry {
+            recorded_at: Utc::now(),
+            health_score: score,
+            issue_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_trend_delta_between_last_two_entries() {
+        let dir = TempDir::new().unwrap();
+        let history = HealthHistory::new(dir.path().to_path_buf());
+        let project = Path::new("/work/infra");
+
+        history.append(project, &entry(70)).unwrap();
+        history.append(project, &entry(82)).unwrap();
+
+        let trend = history.trend(project).unwrap();
+        assert_eq!(trend.history.len(), 2);
+        assert_eq!(trend.previous_score, Some(70));
+        assert_eq!(trend.latest_score, Some(82));
+        assert_eq!(trend.delta, Some(12));
+    }
+
+    #[test]
+    fn test_trend_negative_delta_and_single_entry() {
+        assert_eq!(
+            HealthTrend::from_history(String::new(), vec![entry(60)]).delta,
+            None
+        );
+        let trend = HealthTrend::from_history(String::new(), vec![entry(90), entry(55)]);
+        assert_eq!(trend.delta, Some(-35));
+    }
+
+    #[test]
+    fn test_history_keyed_by_project_directory() {
+        let dir = TempDir::new().unwrap();
+        let history = HealthHistory::new(dir.path().to_path_buf());
+
+        history.append(Path::new("/a"), &entry(50)).unwrap();
+        assert_ne!(
+            history.path_for(Path::new("/a")),
+            history.path_for(Path::new("/b"))
+        );
+        assert!(history.load(Path::new("/b")).unwrap().is_empty());
+        assert_eq!(history.load(Path::new("/a")).unwrap().len(), 1);
+    }
+}
@@ -1,8 +1,7 @@
 //! Terraform import helper for importing existing resources.
 
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::process::Command;
+use std::process::Output;
 
 /// Import preview information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,35 +44,35 @@ pub fn preview_import(
     })
 }
 
-/// Execute the import
-pub fn execute_import(
-    terraform_path: &Path,
-    project_dir: &Path,
+/// Arguments of `terraform import` for the resource
+pub fn import_args(resource_type: &str, resource_id: &str, name: &str) -> Vec<String> {
+    vec![
+        "import".to_string(),
+        format!("{}.{}", resource_type, name),
+        resource_id.to_string(),
+    ]
+}
+
+/// Result of an import run with [`import_args`]
+pub fn import_result(
     resource_type: &str,
     resource_id: &str,
     name: &str,
-) -> anyhow::Result<ImportResult> {
+    output: &Output,
+) -> ImportResult {
     let resource_address = format!("{}.{}", resource_type, name);
 
-    // Run terraform import
-    let output = Command::new(terraform_path)
-        .arg("import")
-        .arg(&resource_address)
-        .arg(resource_id)
-        .current_dir(project_dir)
-        .output()?;
-
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
     if output.status.success() {
-        Ok(ImportResult {
+        ImportResult {
             success: true,
             resource_address,
             resource_id: resource_id.to_string(),
             message: "Resource imported successfully".to_string(),
             output: Some(stdout.to_string()),
-        })
+        }
     } else {
         // Parse common error messages
         let message = if stderr.contains("Cannot import non-existent remote object") {
@@ -95,13 +94,13 @@ pub fn execute_import(
             format!("Import failed: {}", stderr)
         };
 
-        Ok(ImportResult {
+        ImportResult {
             success: false,
             resource_address,
             resource_id: resource_id.to_string(),
             message,
             output: Some(stderr.to_string()),
-        })
+        }
     }
 }
 
@@ -0,0 +1,314 @@
+//! Declarations from Terraform's JSON configuration syntax (`.tf.json`).
+//!
+//! JSON files mirror HCL blocks as nested objects: `resource.<type>.<name>`,
+//! `variable.<name>`, `output.<name>`, `provider.<name>` and `terraform`. Any
+//! level may also be an array of such objects, and `"//"` keys are comments.
+
+use crate::terraform::file_cache::ParsedFile;
+use crate::terraform::model::{
+    RemoteStateDependency, TerraformOutput, TerraformProvider, TerraformResource, TerraformVariable,
+};
+use crate::terraform::parser::TerraformParser;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Suffix of files written in the JSON configuration syntax
+pub const JSON_CONFIG_SUFFIX: &str = ".tf.json";
+
+/// Whether `file_name` is a JSON configuration file
+pub fn is_json_config(file_name: &str) -> bool {
+    file_name.ends_with(JSON_CONFIG_SUFFIX)
+}
+
+/// Parse a `.tf.json` file; content that isn't a JSON object declares nothing
+pub fn parse(content: &str, file_name: &str) -> ParsedFile {
+    let root = match serde_json::from_str::<Value>(content) {
+        Ok(Value::Object(root)) => root,
+        Ok(_) => {
+            eprintln!("[WARN] {} is not a JSON object", file_name);
+            Map::new()
+        }
+        Err(e) => {
+            eprintln!("[WARN] Failed to parse {}: {}", file_name, e);
+            Map::new()
+        }
+    };
+
+    let terraform: Vec<&Map<String, Value>> = blocks(&root, "terraform").collect();
+    ParsedFile {
+        resources: parse_resources(&root, file_name),
+        variables: parse_variables(&root),
+        outputs: parse_outputs(&root),
+        providers: parse_providers(&root, &terraform),
+        required_version: terraform
+            .iter()
+            .find_map(|t| t.get("required_version")?.as_str())
+            .map(str::to_string),
+        remote_states: parse_remote_states(&root, file_name),
+        // References are `${...}` strings, which the HCL pattern still finds
+        remote_state_references: TerraformParser::new(content.to_string())
+            .parse_remote_state_references(),
+        state_encryption: None,
+    }
+}
+
+fn parse_resources(root: &Map<String, Value>, file_name: &str) -> Vec<TerraformResource> {
+    labelled_pairs(root, "resource")
+        .map(|(resource_type, name, body)| {
+            let mut provider = resource_type
+                .split('_')
+                .next()
+                .unwrap_or("unknown")
+                .to_string();
+            // An explicit `"provider": "aws.west"` selects an aliased configuration
+            let provider_alias = body
+                .get("provider")
+                .and_then(Value::as_str)
+                .and_then(|p| p.split_once('.'))
+                .map(|(name, alias)| {
+                    provider = name.to_string();
+                    alias.to_string()
+                });
+            TerraformResource {
+                resource_type: resource_type.to_string(),
+                name: name.to_string(),
+                file: file_name.to_string(),
+                provider,
+                provider_alias,
+            }
+        })
+        .collect()
+}
+
+fn parse_variables(root: &Map<String, Value>) -> Vec<TerraformVariable> {
+    labelled(root, "variable")
+        .map(|(name, body)| TerraformVariable {
+            name: name.to_string(),
+            description: string_field(body, "description"),
+            type_: string_field(body, "type"),
+            default: body.get("default").cloned(),
+            sensitive: body.get("sensitive").and_then(Value::as_bool) == Some(true),
+            effective: None,
+        })
+        .collect()
+}
+
+fn parse_outputs(root: &Map<String, Value>) -> Vec<TerraformOutput> {
+    labelled(root, "output")
+        .map(|(name, body)| TerraformOutput {
+            name: name.to_string(),
+            description: string_field(body, "description"),
+            value: None,
+        })
+        .collect()
+}
+
+fn parse_providers(
+    root: &Map<String, Value>,
+    terraform: &[&Map<String, Value>],
+) -> Vec<TerraformProvider> {
+    // Versions from required_providers, as `{"source": ..., "version": ...}`
+    // or the legacy bare version string
+    let versions: BTreeMap<&str, Option<String>> = terraform
+        .iter()
+        .flat_map(|t| blocks(t, "required_providers"))
+        .flat_map(|required| required.iter())
+        .filter(|(name, _)| name.as_str() != "//")
+        .map(|(name, requirement)| {
+            let version = match requirement {
+                Value::String(version) => Some(version.clone()),
+                Value::Object(requirement) => string_field(requirement, "version"),
+                _ => None,
+            };
+            (name.as_str(), version)
+        })
+        .collect();
+
+    let mut providers: BTreeMap<&str, TerraformProvider> = BTreeMap::new();
+    for (name, body) in labelled(root, "provider") {
+        let provider = providers.entry(name).or_insert_with(|| TerraformProvider {
+            name: name.to_string(),
+            version: versions.get(name).cloned().flatten(),
+            aliases: Vec::new(),
+        });
+        if let Some(alias) = string_field(body, "alias") {
+            if !provider.aliases.contains(&alias) {
+                provider.aliases.push(alias);
+            }
+        }
+    }
+    for (name, version) in versions {
+        providers.entry(name).or_insert_with(|| TerraformProvider {
+            name: name.to_string(),
+            version,
+            aliases: Vec::new(),
+        });
+    }
+
+    providers
+        .into_values()
+        .map(|mut provider| {
+            provider.aliases.sort();
+            provider
+        })
+        .collect()
+}
+
+fn parse_remote_states(root: &Map<String, Value>, file_name: &str) -> Vec<RemoteStateDependency> {
+    labelled_pairs(root, "data")
+        .filter(|(data_type, _, _)| *data_type == "terraform_remote_state")
+        .map(|(_, name, body)| RemoteStateDependency {
+            name: name.to_string(),
+            backend: string_field(body, "backend"),
+            file: file_name.to_string(),
+            outputs: Vec::new(),
+        })
+        .collect()
+}
+
+/// Objects under `key`, which may be a single object or an array of them
+fn blocks<'a>(
+    object: &'a Map<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = &'a Map<String, Value>> {
+    object.get(key).into_iter().flat_map(objects)
+}
+
+/// `value` as objects: itself, or the objects of an array
+fn objects(value: &Value) -> Vec<&Map<String, Value>> {
+    match value {
+        Value::Object(object) => vec![object],
+        Value::Array(items) => items.iter().filter_map(Value::as_object).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `(label, body)` of single-label blocks such as `variable.<name>`
+fn labelled<'a>(
+    root: &'a Map<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = (&'a str, &'a Map<String, Value>)> {
+    blocks(root, key).flat_map(|labels| {
+        labels
+            .iter()
+            .filter(|(label, _)| label.as_str() != "//")
+            .flat_map(|(label, body)| objects(body).into_iter().map(move |b| (label.as_str(), b)))
+    })
+}
+
+/// `(first label, second label, body)` of two-label blocks such as
+/// `resource.<type>.<name>`
+fn labelled_pairs<'a>(
+    root: &'a Map<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = (&'a str, &'a str, &'a Map<String, Value>)> {
+    labelled(root, key).flat_map(|(first, names)| {
+        names
+            .iter()
+            .filter(|(name, _)| name.as_str() != "//")
+            .flat_map(move |(name, body)| {
+                objects(body)
+                    .into_iter()
+                    .map(move |b| (first, name.as_str(), b))
+            })
+    })
+}
+
+fn string_field(body: &Map<String, Value>, field: &str) -> Option<String> {
+    body.get(field).and_then(Value::as_str).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN_TF_JSON: &str = r#"{
+  "//": "Generated by cdktf",
+  "terraform": {
+    "required_version": ">= 1.5.0",
+    "required_providers": {
+      "aws": {"source": "hashicorp/aws", "version": "~> 5.0"}
+    }
+  },
+  "provider": {
+    "aws": [
+      {"region": "us-east-1"},
+      {"region": "us-west-2", "alias": "west"}
+    ]
+  },
+  "variable": {
+    "instance_type": {
+      "type": "string",
+      "description": "EC2 instance type",
+      "default": "t3.micro"
+    }
+  },
+  "resource": {
+    "aws_instance": {
+      "web": {"instance_type": "${var.instance_type}"},
+      "replica": {"provider": "aws.west", "instance_type": "${var.instance_type}"}
+    }
+  },
+  "data": {
+    "terraform_remote_state": {
+      "network": {"backend": "s3", "config": {"bucket": "state"}}
+    }
+  },
+  "output": {
+    "subnet": {"value": "${data.terraform_remote_state.network.outputs.subnet_id}"}
+  }
+}"#;
+
+    #[test]
+    fn test_parse_json_configuration() {
+        let parsed = parse(MAIN_TF_JSON, "main.tf.json");
+
+        let mut resources: Vec<_> = parsed
+            .resources
+            .iter()
+            .map(|r| {
+                (
+                    r.resource_type.as_str(),
+                    r.name.as_str(),
+                    r.provider_alias.as_deref(),
+                )
+            })
+            .collect();
+        resources.sort();
+        assert_eq!(
+            resources,
+            vec![
+                ("aws_instance", "replica", Some("west")),
+                ("aws_instance", "web", None),
+            ]
+        );
+        assert_eq!(parsed.resources[0].file, "main.tf.json");
+        assert_eq!(parsed.resources[0].provider, "aws");
+
+        assert_eq!(parsed.variables.len(), 1);
+        let variable = &parsed.variables[0];
+        assert_eq!(variable.name, "instance_type");
+        assert_eq!(variable.type_.as_deref(), Some("string"));
+        assert_eq!(variable.description.as_deref(), Some("EC2 instance type"));
+        assert_eq!(variable.default, Some(Value::from("t3.micro")));
+
+        assert_eq!(parsed.outputs[0].name, "subnet");
+        assert_eq!(parsed.providers.len(), 1);
+        assert_eq!(parsed.providers[0].version.as_deref(), Some("~> 5.0"));
+        assert_eq!(parsed.providers[0].aliases, ["west"]);
+        assert_eq!(parsed.required_version.as_deref(), Some(">= 1.5.0"));
+        assert_eq!(parsed.remote_states[0].backend.as_deref(), Some("s3"));
+        assert_eq!(
+            parsed.remote_state_references,
+            [("network".to_string(), "subnet_id".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_invalid_json_declares_nothing() {
+        let parsed = parse("resource \"aws_instance\" \"web\" {}", "main.tf.json");
+        assert!(parsed.resources.is_empty());
+        assert!(is_json_config("main.tf.json"));
+        assert!(!is_json_config("main.tf"));
+    }
+}
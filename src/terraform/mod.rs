@@ -1,14 +1,41 @@
+pub mod analysis_cache;
 pub mod analyzer;
+pub mod approval;
+pub mod backend;
+pub mod categories;
+pub mod cli_config;
+pub mod conftest;
+pub mod cost_drivers;
+pub mod error;
+pub mod file_cache;
+pub mod files;
 pub mod fmt;
 pub mod graph;
+pub mod health_history;
 pub mod import_helper;
+pub mod json_config;
 pub mod model;
+pub mod module_source;
+pub mod orphans;
 pub mod output;
 pub mod parser;
 pub mod plan_analyzer;
+pub mod plan_diff;
+pub mod plan_snapshot;
+pub mod pre_apply;
+pub mod provider_resources;
 pub mod providers;
 pub mod refresh;
+pub mod resource_list;
+pub mod saved_plan;
+pub mod scaffold;
+pub mod schema;
+pub mod schema_hints;
 pub mod service;
 pub mod state_analyzer;
+pub mod state_pull;
+pub mod state_secrets;
 pub mod taint;
+pub mod tfvars;
+pub mod version;
 pub mod workspace;
@@ -1,6 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct TerraformAnalysis {
     pub project_directory: String,
     pub file_count: usize,
@@ -8,24 +10,165 @@ pub struct TerraformAnalysis {
     pub variables: Vec<TerraformVariable>,
     pub outputs: Vec<TerraformOutput>,
     pub providers: Vec<TerraformProvider>,
+    #[serde(default)]
+    pub required_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_compatibility: Option<VersionCompatibility>,
+    /// `terraform_remote_state` data sources and the outputs read from them
+    #[serde(default)]
+    pub remote_state_dependencies: Vec<RemoteStateDependency>,
+    /// OpenTofu `encryption` block of the terraform block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_encryption: Option<StateEncryption>,
+}
+
+/// An OpenTofu `terraform { encryption { ... } }` block. Terraform rejects
+/// this block, so its presence means the configuration targets OpenTofu.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct StateEncryption {
+    pub file: String,
+    /// Whether a `state` block selects a method, i.e. state files are encrypted
+    pub state_encrypted: bool,
+    /// Whether a `plan` block selects a method, i.e. saved plans are encrypted
+    pub plan_encrypted: bool,
+    /// `enforced = true` in the `state` block: unencrypted state is refused
+    pub enforced: bool,
+    /// Key providers as `type.name`, e.g. `pbkdf2.passphrase`
+    pub key_providers: Vec<String>,
+    /// Methods as `type.name`, e.g. `aes_gcm.default`
+    pub methods: Vec<String>,
+}
+
+/// A `data "terraform_remote_state"` block, i.e. a dependency on another
+/// project's state
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct RemoteStateDependency {
+    pub name: String,
+    /// Backend type, e.g. `s3` or `remote`; `None` when not a literal
+    pub backend: Option<String>,
+    pub file: String,
+    /// Outputs referenced as `data.terraform_remote_state.<name>.outputs.<output>`, sorted
+    pub outputs: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Analysis of every Terraform root matching a glob, from `analyze_all`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MultiDirectoryAnalysis {
+    pub pattern: String,
+    pub directories: Vec<DirectoryAnalysisSummary>,
+    pub aggregate: AggregateAnalysis,
+    /// More directories matched than the cap allowed; the rest were skipped
+    pub truncated: bool,
+}
+
+/// Counts from one directory's `TerraformAnalysis`
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DirectoryAnalysisSummary {
+    /// Directory relative to the project root ("." for the root)
+    pub path: String,
+    pub file_count: usize,
+    pub resource_count: usize,
+    pub variable_count: usize,
+    pub output_count: usize,
+    pub providers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_version: Option<String>,
+    /// Set when the directory could not be analyzed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DirectoryAnalysisSummary {
+    pub fn from_analysis(path: String, analysis: &TerraformAnalysis) -> Self {
+        let mut providers: Vec<String> =
+            analysis.providers.iter().map(|p| p.name.clone()).collect();
+        providers.sort();
+        providers.dedup();
+        Self {
+            path,
+            file_count: analysis.file_count,
+            resource_count: analysis.resources.len(),
+            variable_count: analysis.variables.len(),
+            output_count: analysis.outputs.len(),
+            providers,
+            required_version: analysis.required_version.clone(),
+            error: None,
+        }
+    }
+
+    pub fn failed(path: String, error: String) -> Self {
+        Self {
+            path,
+            error: Some(error),
+            ..Default::default()
+        }
+    }
+}
+
+/// Totals across the directories of a `MultiDirectoryAnalysis`
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct AggregateAnalysis {
+    pub directory_count: usize,
+    pub failed_count: usize,
+    pub file_count: usize,
+    pub resource_count: usize,
+    pub variable_count: usize,
+    pub output_count: usize,
+    /// Distinct providers used by any directory
+    pub providers: Vec<String>,
+}
+
+impl AggregateAnalysis {
+    pub fn from_directories(directories: &[DirectoryAnalysisSummary]) -> Self {
+        let mut aggregate = Self {
+            directory_count: directories.len(),
+            ..Default::default()
+        };
+        for dir in directories {
+            if dir.error.is_some() {
+                aggregate.failed_count += 1;
+            }
+            aggregate.file_count += dir.file_count;
+            aggregate.resource_count += dir.resource_count;
+            aggregate.variable_count += dir.variable_count;
+            aggregate.output_count += dir.output_count;
+            aggregate.providers.extend(dir.providers.iter().cloned());
+        }
+        aggregate.providers.sort();
+        aggregate.providers.dedup();
+        aggregate
+    }
+}
+
+/// Whether the installed binary satisfies the configuration's `required_version`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VersionCompatibility {
+    pub constraint: String,
+    pub installed_version: Option<String>,
+    /// `None` when the installed version or constraint could not be evaluated
+    pub satisfied: Option<bool>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TerraformResource {
     pub resource_type: String,
     pub name: String,
     pub file: String,
     pub provider: String,
+    /// Provider alias selected via `provider = <name>.<alias>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_alias: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[allow(dead_code)]
 pub struct TerraformPlan {
     pub changes: TerraformChanges,
     pub raw_output: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[allow(dead_code)]
 pub struct TerraformChanges {
     pub add: usize,
@@ -33,14 +176,14 @@ pub struct TerraformChanges {
     pub destroy: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[allow(dead_code)]
 pub struct TerraformState {
     pub resources: Vec<TerraformStateResource>,
     pub version: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[allow(dead_code)]
 pub struct TerraformStateResource {
     pub name: String,
@@ -49,14 +192,14 @@ pub struct TerraformStateResource {
     pub instances: Vec<TerraformResourceInstance>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[allow(dead_code)]
 pub struct TerraformResourceInstance {
     pub id: String,
     pub attributes: serde_json::Value,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct TerraformValidateOutput {
     pub valid: bool,
     pub error_count: i32,
@@ -64,43 +207,109 @@ pub struct TerraformValidateOutput {
     pub diagnostics: Vec<TerraformDiagnostic>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl TerraformValidateOutput {
+    /// One-line outcome, e.g. "Configuration is valid with 1 warning(s)"
+    pub fn message(&self) -> String {
+        match (self.valid, self.warning_count) {
+            (true, 0) => "Configuration is valid".to_string(),
+            (true, warnings) => format!("Configuration is valid with {} warning(s)", warnings),
+            (false, warnings) => format!(
+                "Configuration is invalid: {} error(s), {} warning(s)",
+                self.error_count, warnings
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct TerraformDiagnostic {
     pub severity: String,
     pub summary: String,
     pub detail: Option<String>,
+    /// Resource the diagnostic is about, when terraform reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
     pub range: Option<DiagnosticRange>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Group label for diagnostics reported without a source range
+pub const NO_FILE_DIAGNOSTICS: &str = "(no file)";
+
+/// Error and warning counts for the diagnostics reported against one file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct FileDiagnostics {
+    pub file: String,
+    pub error_count: usize,
+    pub warning_count: usize,
+    /// `<line>: <summary>` of each diagnostic, in reported order
+    pub summaries: Vec<String>,
+}
+
+impl FileDiagnostics {
+    /// Group `diagnostics` by `range.filename`, sorted by file; those
+    /// without a range are grouped under [`NO_FILE_DIAGNOSTICS`]
+    pub fn group(diagnostics: &[TerraformDiagnostic]) -> Vec<Self> {
+        let mut files: BTreeMap<&str, FileDiagnostics> = BTreeMap::new();
+        for diagnostic in diagnostics {
+            let file = diagnostic
+                .range
+                .as_ref()
+                .map_or(NO_FILE_DIAGNOSTICS, |range| range.filename.as_str());
+            let entry = files.entry(file).or_insert_with(|| FileDiagnostics {
+                file: file.to_string(),
+                error_count: 0,
+                warning_count: 0,
+                summaries: Vec::new(),
+            });
+            match diagnostic.severity.as_str() {
+                "error" => entry.error_count += 1,
+                "warning" => entry.warning_count += 1,
+                _ => {}
+            }
+            entry.summaries.push(match &diagnostic.range {
+                Some(range) => format!("{}: {}", range.start.line, diagnostic.summary),
+                None => diagnostic.summary.clone(),
+            });
+        }
+        files.into_values().collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct DiagnosticRange {
     pub filename: String,
     pub start: Position,
     pub end: Position,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Position {
     pub line: i32,
     pub column: i32,
     pub byte: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DetailedValidationResult {
     pub valid: bool,
     pub error_count: i32,
     pub warning_count: i32,
     pub diagnostics: Vec<TerraformDiagnostic>,
+    /// `diagnostics` grouped by file with per-file error and warning counts
+    #[serde(default)]
+    pub diagnostics_by_file: Vec<FileDiagnostics>,
     pub additional_warnings: Vec<String>,
     pub suggestions: Vec<String>,
     pub checked_files: usize,
     /// Future Architect guideline compliance checks
     pub guideline_checks: Option<GuidelineCheckResult>,
+    /// Directory that was validated, relative to the project root ("." for the root)
+    #[serde(default)]
+    pub validated_path: String,
 }
 
 /// Results from Future Architect Terraform guideline compliance checks
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 pub struct GuidelineCheckResult {
     /// Overall compliance score (0-100)
     pub compliance_score: u8,
@@ -116,6 +325,12 @@ pub struct GuidelineCheckResult {
     pub any_type_usage: Vec<String>,
     /// Providers missing version constraint
     pub providers_missing_version: Vec<String>,
+    /// Providers whose constraint is missing or has no upper bound
+    #[serde(default)]
+    pub loose_provider_constraints: Vec<LooseProviderConstraint>,
+    /// Providers used by resources but missing from `required_providers`
+    #[serde(default)]
+    pub undeclared_providers: Vec<UndeclaredProvider>,
     /// Resources missing default_tags (AWS)
     pub missing_default_tags: bool,
     /// Detected hardcoded secrets
@@ -124,8 +339,33 @@ pub struct GuidelineCheckResult {
     pub missing_lifecycle_protection: Vec<String>,
 }
 
+/// Provider whose version constraint lets any future major version in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct LooseProviderConstraint {
+    pub provider: String,
+    pub constraint: Option<String>,
+    pub reason: String,
+    /// Latest registry version, filled in when the registry is reachable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_version: Option<String>,
+    /// `~>` pin based on `latest_version`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_constraint: Option<String>,
+}
+
+/// Provider a module's resources rely on without declaring it in
+/// `required_providers`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct UndeclaredProvider {
+    pub provider: String,
+    /// Directory of the module, relative to the project root ("." for the root)
+    pub module: String,
+    /// `type.name` of each resource and `data.type.name` of each data source using it
+    pub resources: Vec<String>,
+}
+
 /// Warning for count usage that should be for_each
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CountUsageWarning {
     pub resource_name: String,
     pub resource_type: String,
@@ -133,7 +373,7 @@ pub struct CountUsageWarning {
 }
 
 /// Detected potential secret in code
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SecretDetection {
     pub file: String,
     pub line: usize,
@@ -141,33 +381,181 @@ pub struct SecretDetection {
     pub severity: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Literal value repeated across resources that could be extracted into a variable
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HardcodedValue {
+    pub value: String,
+    pub occurrences: usize,
+    pub resources: Vec<String>,
+    pub files: Vec<String>,
+}
+
+/// Module call with its source and version constraint as written
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModuleSourceRef {
+    pub module: String,
+    pub source: String,
+    /// `version` argument without quotes, or the expression if not a literal
+    pub version: Option<String>,
+    pub file: String,
+    pub line: usize,
+}
+
+/// Module call whose registry or git source is not pinned to a version
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UnpinnedModuleSource {
+    pub module: String,
+    pub source: String,
+    pub file: String,
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Pre-0.12 construct that still parses but has a modern replacement
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DeprecatedSyntax {
+    pub file: String,
+    pub line: usize,
+    /// Kind of deprecation, e.g. "interpolation-only expression"
+    pub pattern: String,
+    /// Offending text as written
+    pub found: String,
+    /// Modernized replacement for `found`
+    pub suggestion: String,
+}
+
+/// How a local module call disagrees with the module's declared interface
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleWiringIssueKind {
+    /// Variable without a default that the call doesn't pass
+    MissingRequiredInput,
+    /// Argument the module declares no variable for
+    UnknownInput,
+    /// `module.<name>.<output>` reference to an output the module doesn't declare
+    UnknownOutput,
+}
+
+/// Local module call whose arguments or output references don't match the module
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct ModuleWiringIssue {
+    pub module: String,
+    pub source: String,
+    pub kind: ModuleWiringIssueKind,
+    /// Offending variable or output name
+    pub key: String,
+    pub file: String,
+    pub line: usize,
+}
+
+/// Resource address, variable or output declared more than once
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicateDefinition {
+    /// "resource", "variable" or "output"
+    pub kind: String,
+    /// `type.name` for resources, the name otherwise
+    pub address: String,
+    pub locations: Vec<DefinitionLocation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DefinitionLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+/// Where a resource, variable or output is declared
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DefinitionLookup {
+    /// Name or address as given
+    pub query: String,
+    /// Every matching declaration, sorted by kind, file, then line; more
+    /// than one per address means it is duplicated
+    pub definitions: Vec<DefinitionMatch>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DefinitionMatch {
+    /// "resource", "variable" or "output"
+    pub kind: String,
+    /// `type.name` for resources, the name otherwise
+    pub address: String,
+    pub file: String,
+    /// Line of the block header
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TerraformVariable {
     pub name: String,
     pub description: Option<String>,
     pub type_: Option<String>,
     pub default: Option<serde_json::Value>,
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Value terraform would use, from auto-loaded tfvars, the environment or the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective: Option<EffectiveValue>,
+}
+
+/// Where a variable's effective value comes from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueSource {
+    /// An auto-loaded tfvars file
+    File,
+    /// A `TF_VAR_<name>` environment variable
+    #[serde(rename = "env", alias = "environment")]
+    Environment,
+    Default,
+    /// No value; terraform would prompt for it
+    Unset,
+}
+
+/// Resolved value of a variable
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct EffectiveValue {
+    pub source: ValueSource,
+    /// tfvars file that sets the value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Masked for sensitive variables and environment values; absent for unset values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TerraformOutput {
     pub name: String,
     pub description: Option<String>,
     pub value: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TerraformProvider {
     pub name: String,
     pub version: Option<String>,
+    /// Aliases declared by `provider` blocks with an `alias` argument
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
 
 // ==================== Module Health Analysis Models ====================
 // Based on the whitebox approach to Infrastructure as Code
 // Reference: "インフラコードはホワイトボックス的利用が必要"
 
+/// Configuration analysis, guideline checks and module health, as printed by `tfmcp analyze --json`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AnalysisReport {
+    pub analysis: TerraformAnalysis,
+    pub guidelines: GuidelineCheckResult,
+    pub module_health: ModuleHealthAnalysis,
+    /// Critical module health issues plus critical hardcoded secrets
+    pub critical_issues: usize,
+}
+
 /// Module health analysis result
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ModuleHealthAnalysis {
     pub module_path: String,
     pub metrics: ModuleMetrics,
@@ -176,10 +564,25 @@ pub struct ModuleHealthAnalysis {
     pub recommendations: Vec<String>,
     pub cohesion_analysis: CohesionAnalysis,
     pub coupling_analysis: CouplingAnalysis,
+    #[serde(default)]
+    pub readme: ReadmeQuality,
+}
+
+/// Presence and completeness of the module's README.md
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct ReadmeQuality {
+    pub present: bool,
+    /// Has a usage or example section
+    pub has_usage: bool,
+    pub has_inputs: bool,
+    pub has_outputs: bool,
+    /// Sections to add, in README order
+    pub missing_sections: Vec<String>,
+    pub score: u8, // 0-100
 }
 
 /// Quantitative metrics for module analysis
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ModuleMetrics {
     pub variable_count: usize,
     pub output_count: usize,
@@ -196,8 +599,8 @@ pub struct ModuleMetrics {
     pub variables_without_description: usize,
 }
 
-/// Issue severity levels
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Issue severity levels, most severe first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
 pub enum IssueSeverity {
     Critical,
     Warning,
@@ -205,7 +608,7 @@ pub enum IssueSeverity {
 }
 
 /// Module issue detected during analysis
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ModuleIssue {
     pub severity: IssueSeverity,
     pub category: IssueCategory,
@@ -215,7 +618,7 @@ pub struct ModuleIssue {
 }
 
 /// Categories of module issues
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum IssueCategory {
     LogicalCohesion,      // Too many unrelated resource types
     ExcessiveVariables,   // Too many variables exposed
@@ -225,10 +628,15 @@ pub enum IssueCategory {
     ModelCoupling,        // Internal model exposed through variables
     NamingConvention,     // Poor file/resource naming
     PublicModuleRisk,     // Using public registry modules without wrappers
+    HardcodedValue,       // Literal repeated across resources instead of a variable
+    UnpinnedModuleSource, // Registry/git module source without a version pin
+    DuplicateDefinition,  // Same resource address, variable or output declared twice
+    ModuleWiring,         // Local module call that doesn't match the module's variables/outputs
+    DeprecatedSyntax,     // Pre-0.12 syntax such as "${var.x}" or terraform.env
 }
 
 /// Cohesion type analysis (based on software engineering principles)
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CohesionAnalysis {
     pub cohesion_type: CohesionType,
     pub score: u8, // 0-100, higher is better
@@ -237,7 +645,7 @@ pub struct CohesionAnalysis {
 }
 
 /// Types of module cohesion
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum CohesionType {
     Functional,      // Best: Single, well-defined purpose
     Sequential,      // Good: Output of one feeds into another
@@ -249,7 +657,7 @@ pub enum CohesionType {
 }
 
 /// Group of related resource types
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ResourceTypeGroup {
     pub name: String,
     pub resource_types: Vec<String>,
@@ -257,7 +665,7 @@ pub struct ResourceTypeGroup {
 }
 
 /// Coupling analysis between modules
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CouplingAnalysis {
     pub coupling_type: CouplingType,
     pub score: u8, // 0-100, lower coupling is better
@@ -266,7 +674,7 @@ pub struct CouplingAnalysis {
 }
 
 /// Types of module coupling
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum CouplingType {
     Data,    // Best: Only data passed between modules
     Stamp,   // OK: Structured data passed
@@ -276,7 +684,7 @@ pub enum CouplingType {
 }
 
 /// Dependency information between modules
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ModuleDependency {
     pub source_module: String,
     pub target_module: String,
@@ -285,7 +693,7 @@ pub struct ModuleDependency {
 }
 
 /// Resource dependency graph for visualization
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ResourceDependencyGraph {
     pub nodes: Vec<ResourceNode>,
     pub edges: Vec<ResourceEdge>,
@@ -293,7 +701,7 @@ pub struct ResourceDependencyGraph {
 }
 
 /// A node in the resource dependency graph
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ResourceNode {
     pub id: String,
     pub resource_type: String,
@@ -304,7 +712,7 @@ pub struct ResourceNode {
 }
 
 /// An edge in the resource dependency graph
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ResourceEdge {
     pub source: String,
     pub target: String,
@@ -313,7 +721,7 @@ pub struct ResourceEdge {
 }
 
 /// Types of resource dependencies
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
 pub enum DependencyType {
     Explicit,     // depends_on
     Implicit,     // Reference to another resource
@@ -321,15 +729,70 @@ pub enum DependencyType {
     ModuleOutput, // Reference to module output
 }
 
+/// Resources affected by a change to one resource
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImpactAnalysis {
+    pub resource: String,
+    /// Resources that reference or depend_on the resource
+    pub direct_dependents: Vec<String>,
+    /// All resources depending on it directly or through others, nearest first
+    pub transitive_dependents: Vec<ImpactedResource>,
+    /// The resource depends on itself through a cycle
+    pub in_cycle: bool,
+}
+
+/// A downstream resource and how many edges away it is
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct ImpactedResource {
+    pub address: String,
+    pub distance: usize,
+}
+
+/// One resource with everything it depends on and everything depending on
+/// it, cut out of the dependency graph
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceSubgraph {
+    pub resource: String,
+    /// Resources it depends on directly or through others, nearest first
+    pub dependencies: Vec<ImpactedResource>,
+    /// Resources depending on it directly or through others, nearest first
+    pub dependents: Vec<ImpactedResource>,
+    pub nodes: Vec<ResourceNode>,
+    /// Edges of the full graph between nodes of the subgraph
+    pub edges: Vec<ResourceEdge>,
+}
+
+/// Output format of the `resource_subgraph` tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubgraphFormat {
+    Json,
+    Mermaid,
+}
+
+impl std::str::FromStr for SubgraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SubgraphFormat::Json),
+            "mermaid" => Ok(SubgraphFormat::Mermaid),
+            _ => Err(anyhow::anyhow!(
+                "Unknown subgraph format: {}. Valid formats: json, mermaid",
+                s
+            )),
+        }
+    }
+}
+
 /// Module boundary for visualization
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ModuleBoundary {
     pub module_path: String,
     pub resource_ids: Vec<String>,
 }
 
 /// Refactoring suggestion
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RefactoringSuggestion {
     pub suggestion_type: RefactoringType,
     pub priority: IssueSeverity,
@@ -337,10 +800,13 @@ pub struct RefactoringSuggestion {
     pub affected_resources: Vec<String>,
     pub proposed_structure: Option<ProposedModuleStructure>,
     pub migration_steps: Vec<String>,
+    /// Ready-to-use HCL for the refactoring, e.g. `moved` blocks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_hcl: Option<String>,
 }
 
 /// Types of refactoring suggestions
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum RefactoringType {
     SplitModule,           // Extract resources to new module
     MergeModules,          // Combine small modules
@@ -352,7 +818,7 @@ pub enum RefactoringType {
 }
 
 /// Proposed new module structure
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProposedModuleStructure {
     pub module_name: String,
     pub resources: Vec<String>,
@@ -0,0 +1,383 @@
+//! Supply-chain checks on module sources.
+//!
+//! Git sources are safe to reuse only when `?ref=` names a full commit SHA:
+//! branches and tags can be moved to different code after review. Registry
+//! modules are judged by their version constraint and, for the public
+//! registry, whether the namespace is a verified publisher; the caller fills
+//! that in from the registry with [`ModuleSourceCheck::set_verified_publisher`].
+
+use crate::terraform::model::ModuleSourceRef;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Hostname of the public registry, the only one with verified publishers
+pub const PUBLIC_REGISTRY_HOST: &str = "registry.terraform.io";
+
+/// Full SHA-1 or SHA-256 commit id
+static FULL_SHA_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:[0-9a-f]{40}|[0-9a-f]{64})$").expect("Invalid full SHA regex")
+});
+
+/// Abbreviated commit id
+static SHORT_SHA_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[0-9a-f]{7,39}$").expect("Invalid short SHA regex"));
+
+/// Tag-like ref such as `v1.2.0` or `1.4`
+static VERSION_TAG_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^v?\d+(?:\.\d+)*").expect("Invalid version tag regex"));
+
+/// Exact registry version such as `5.8.1` or `= 5.8.1`
+static EXACT_VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^=?\s*v?\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?$").expect("Invalid exact version regex")
+});
+
+/// Where a module is fetched from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleSourceKind {
+    Local,
+    Git,
+    Registry,
+    /// HTTP archives, buckets and other go-getter sources
+    Other,
+}
+
+/// How exposed a source is to upstream changes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceRisk {
+    Low,
+    Medium,
+    High,
+}
+
+/// Verification result for one module source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleSourceCheck {
+    /// Module block name when checked from the configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub kind: ModuleSourceKind,
+    /// `?ref=` of a git source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// Whether `git_ref` is a full commit SHA
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_to_commit: Option<bool>,
+    /// `namespace/name/provider` of a registry source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry_module: Option<String>,
+    /// Whether the namespace is a verified publisher; `None` when unknown
+    /// or not a public registry module
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_publisher: Option<bool>,
+    pub risk: SourceRisk,
+    /// Why the risk is above low, most severe first
+    pub findings: Vec<String>,
+}
+
+impl ModuleSourceCheck {
+    /// Classify `source` with its `version` constraint
+    pub fn new(source: &str, version: Option<&str>) -> Self {
+        let mut check = Self {
+            module: None,
+            source: source.to_string(),
+            version: version.map(str::to_string),
+            file: None,
+            line: None,
+            kind: source_kind(source),
+            git_ref: None,
+            pinned_to_commit: None,
+            registry_module: None,
+            verified_publisher: None,
+            risk: SourceRisk::Low,
+            findings: Vec::new(),
+        };
+        match check.kind {
+            ModuleSourceKind::Git => {
+                check.git_ref = query_param(source, "ref");
+                check.pinned_to_commit = Some(
+                    check
+                        .git_ref
+                        .as_deref()
+                        .is_some_and(|r| FULL_SHA_REGEX.is_match(r)),
+                );
+            }
+            ModuleSourceKind::Registry => {
+                check.registry_module = registry_address(source).map(|(_, module)| module);
+            }
+            ModuleSourceKind::Local | ModuleSourceKind::Other => {}
+        }
+        check.assess();
+        check
+    }
+
+    /// Classify a module call found in the configuration
+    pub fn from_module_call(call: &ModuleSourceRef) -> Self {
+        let mut check = Self::new(&call.source, call.version.as_deref());
+        check.module = Some(call.module.clone());
+        check.file = Some(call.file.clone());
+        check.line = Some(call.line);
+        check
+    }
+
+    /// `(namespace, name, provider)` to look up when this is a public
+    /// registry module
+    pub fn public_registry_module(&self) -> Option<(&str, &str, &str)> {
+        let (host, _) = registry_address(&self.source)?;
+        if host.is_some_and(|host| host != PUBLIC_REGISTRY_HOST) {
+            return None;
+        }
+        let mut parts = self.registry_module.as_deref()?.split('/');
+        Some((parts.next()?, parts.next()?, parts.next()?))
+    }
+
+    /// Record the registry's verified flag for the namespace (`None` when
+    /// the lookup failed) and re-rate the source
+    pub fn set_verified_publisher(&mut self, verified: Option<bool>) {
+        self.verified_publisher = verified;
+        self.assess();
+    }
+
+    fn assess(&mut self) {
+        // (risk, finding)
+        let mut findings: Vec<(SourceRisk, String)> = Vec::new();
+        match self.kind {
+            ModuleSourceKind::Local => {}
+            ModuleSourceKind::Git => match self.git_ref.as_deref() {
+                None => findings.push((
+                    SourceRisk::High,
+                    "git source has no ?ref= pin and follows the default branch".to_string(),
+                )),
+                Some(r) if FULL_SHA_REGEX.is_match(r) => {}
+                Some(r) if SHORT_SHA_REGEX.is_match(r) => findings.push((
+                    SourceRisk::Medium,
+                    format!(
+                        "ref '{}' is an abbreviated SHA; pin the full 40-character commit",
+                        r
+                    ),
+                )),
+                Some(r) if VERSION_TAG_REGEX.is_match(r) => findings.push((
+                    SourceRisk::Medium,
+                    format!(
+                        "ref '{}' is a tag, which can be moved to other commits; pin the commit SHA",
+                        r
+                    ),
+                )),
+                Some(r) => findings.push((
+                    SourceRisk::High,
+                    format!(
+                        "ref '{}' looks like a branch, which changes with every push; pin the commit SHA",
+                        r
+                    ),
+                )),
+            },
+            ModuleSourceKind::Registry => {
+                match self.version.as_deref() {
+                    None => findings.push((
+                        SourceRisk::High,
+                        "registry module has no version constraint".to_string(),
+                    )),
+                    Some(v) if EXACT_VERSION_REGEX.is_match(v) => {}
+                    Some(v) => findings.push((
+                        SourceRisk::Medium,
+                        format!(
+                            "version '{}' allows newer releases; pin an exact version",
+                            v
+                        ),
+                    )),
+                }
+                if self.public_registry_module().is_some() {
+                    match self.verified_publisher {
+                        Some(true) => {}
+                        Some(false) => findings.push((
+                            SourceRisk::Medium,
+                            "namespace is not a verified publisher".to_string(),
+                        )),
+                        None => findings.push((
+                            SourceRisk::Medium,
+                            "publisher verification could not be confirmed".to_string(),
+                        )),
+                    }
+                }
+            }
+            ModuleSourceKind::Other => {
+                if query_param(&self.source, "checksum").is_none() {
+                    findings.push((
+                        SourceRisk::Medium,
+                        "archive source has no ?checksum= to verify its contents".to_string(),
+                    ));
+                }
+            }
+        }
+
+        findings.sort_by_key(|(risk, _)| std::cmp::Reverse(*risk));
+        // Two medium findings compound into a high risk
+        let mediums = findings
+            .iter()
+            .filter(|(risk, _)| *risk == SourceRisk::Medium)
+            .count();
+        self.risk = match findings.first() {
+            None => SourceRisk::Low,
+            Some(_) if mediums >= 2 => SourceRisk::High,
+            Some((risk, _)) => *risk,
+        };
+        self.findings = findings.into_iter().map(|(_, finding)| finding).collect();
+    }
+}
+
+/// Sources checked together with the highest risk among them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleSourceReport {
+    pub risk: SourceRisk,
+    pub modules: Vec<ModuleSourceCheck>,
+}
+
+impl ModuleSourceReport {
+    pub fn new(modules: Vec<ModuleSourceCheck>) -> Self {
+        Self {
+            risk: modules
+                .iter()
+                .map(|m| m.risk)
+                .max()
+                .unwrap_or(SourceRisk::Low),
+            modules,
+        }
+    }
+}
+
+fn source_kind(source: &str) -> ModuleSourceKind {
+    if source.starts_with("./") || source.starts_with("../") {
+        ModuleSourceKind::Local
+    } else if source.starts_with("git::")
+        || source.starts_with("git@")
+        || source.starts_with("github.com/")
+        || source.starts_with("bitbucket.org/")
+    {
+        ModuleSourceKind::Git
+    } else if registry_address(source).is_some() {
+        ModuleSourceKind::Registry
+    } else {
+        ModuleSourceKind::Other
+    }
+}
+
+/// Optional hostname and `namespace/name/provider` of a registry address,
+/// ignoring any `//subdirectory`
+fn registry_address(source: &str) -> Option<(Option<&str>, String)> {
+    if source.contains("::") || source.contains("://") || source.contains('?') {
+        return None;
+    }
+    let address = source.split("//").next().unwrap_or(source);
+    let parts: Vec<&str> = address.split('/').collect();
+    match parts.as_slice() {
+        [namespace, name, provider] => Some((None, format!("{}/{}/{}", namespace, name, provider))),
+        [host, namespace, name, provider] if host.contains('.') => {
+            Some((Some(host), format!("{}/{}/{}", namespace, name, provider)))
+        }
+        _ => None,
+    }
+}
+
+/// Value of `key` in the query string of a go-getter source
+fn query_param(source: &str, key: &str) -> Option<String> {
+    let (_, query) = source.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| value.split("//").next().unwrap_or(value).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA: &str = "6b1b1f4c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f70";
+
+    #[test]
+    fn test_sha_pinned_git_source_is_low_risk() {
+        let check = ModuleSourceCheck::new(
+            &format!("git::https://github.com/example/network.git?ref={}", SHA),
+            None,
+        );
+        assert_eq!(check.kind, ModuleSourceKind::Git);
+        assert_eq!(check.git_ref.as_deref(), Some(SHA));
+        assert_eq!(check.pinned_to_commit, Some(true));
+        assert_eq!(check.risk, SourceRisk::Low);
+        assert!(check.findings.is_empty());
+    }
+
+    #[test]
+    fn test_branch_pinned_git_source_is_high_risk() {
+        let check = ModuleSourceCheck::new(
+            "git::https://github.com/example/network.git//modules/vpc?ref=main",
+            None,
+        );
+        assert_eq!(check.git_ref.as_deref(), Some("main"));
+        assert_eq!(check.pinned_to_commit, Some(false));
+        assert_eq!(check.risk, SourceRisk::High);
+        assert!(check.findings[0].contains("branch"));
+
+        let tag = ModuleSourceCheck::new("github.com/example/network?ref=v1.2.0", None);
+        assert_eq!(tag.risk, SourceRisk::Medium);
+        assert!(tag.findings[0].contains("tag"));
+
+        let short = ModuleSourceCheck::new("git@github.com:example/network.git?ref=6b1b1f4", None);
+        assert_eq!(short.risk, SourceRisk::Medium);
+
+        let unpinned = ModuleSourceCheck::new("git::https://github.com/example/network.git", None);
+        assert_eq!(unpinned.risk, SourceRisk::High);
+    }
+
+    #[test]
+    fn test_registry_source_rating() {
+        let mut check = ModuleSourceCheck::new("terraform-aws-modules/vpc/aws", Some("5.8.1"));
+        assert_eq!(check.kind, ModuleSourceKind::Registry);
+        assert_eq!(
+            check.public_registry_module(),
+            Some(("terraform-aws-modules", "vpc", "aws"))
+        );
+        // Unknown until the registry is asked
+        assert_eq!(check.risk, SourceRisk::Medium);
+        check.set_verified_publisher(Some(true));
+        assert_eq!(check.risk, SourceRisk::Low);
+
+        let mut loose = ModuleSourceCheck::new("example/vpc/aws//modules/subnets", Some("~> 5.0"));
+        loose.set_verified_publisher(Some(false));
+        assert_eq!(loose.risk, SourceRisk::High);
+        assert_eq!(loose.findings.len(), 2);
+
+        let private = ModuleSourceCheck::new("app.terraform.io/acme/vpc/aws", Some("1.0.0"));
+        assert_eq!(private.public_registry_module(), None);
+        assert_eq!(private.risk, SourceRisk::Low);
+
+        let unversioned = ModuleSourceCheck::new("terraform-aws-modules/vpc/aws", None);
+        assert_eq!(unversioned.risk, SourceRisk::High);
+    }
+
+    #[test]
+    fn test_other_sources() {
+        assert_eq!(
+            ModuleSourceCheck::new("./modules/vpc", None).risk,
+            SourceRisk::Low
+        );
+        let archive = ModuleSourceCheck::new("https://example.com/vpc.zip", None);
+        assert_eq!(archive.kind, ModuleSourceKind::Other);
+        assert_eq!(archive.risk, SourceRisk::Medium);
+        let checked =
+            ModuleSourceCheck::new("https://example.com/vpc.zip?checksum=sha256:abc", None);
+        assert_eq!(checked.risk, SourceRisk::Low);
+
+        let report = ModuleSourceReport::new(vec![archive, checked]);
+        assert_eq!(report.risk, SourceRisk::Medium);
+    }
+}
@@ -0,0 +1,145 @@
+//! Resources left in state after their blocks were removed from the
+//! configuration.
+//!
+//! `terraform state list` addresses are compared against the root module's
+//! `resource` blocks with instance keys (`[0]`, `["a"]`) dropped, so every
+//! `count`/`for_each` instance maps back to its block. Data sources are read
+//! again on every run and resources inside module calls are declared in the
+//! module's own files, so neither is reported.
+
+use crate::terraform::model::TerraformResource;
+use crate::terraform::resource_list::address_segments;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// A resource block present in state but not in the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedResource {
+    /// Resource address without instance keys, e.g. `aws_instance.web`
+    pub address: String,
+    /// State addresses of its instances, e.g. `aws_instance.web[0]`
+    pub instances: Vec<String>,
+}
+
+/// State resources compared against the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanReport {
+    /// Addresses listed by `terraform state list`
+    pub state_resource_count: usize,
+    /// `resource` blocks in the root module configuration
+    pub config_resource_count: usize,
+    /// Candidates for removal from state or destroy, sorted by address
+    pub orphaned: Vec<OrphanedResource>,
+    /// State addresses inside module calls, not compared
+    pub skipped_module_instances: usize,
+    pub message: String,
+}
+
+/// Compare `state_addresses` with the root module `resources`
+pub fn find_orphaned_resources(
+    state_addresses: &[String],
+    resources: &[TerraformResource],
+) -> OrphanReport {
+    let declared: HashSet<String> = resources
+        .iter()
+        .map(|r| format!("{}.{}", r.resource_type, r.name))
+        .collect();
+
+    let mut orphaned: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut skipped_module_instances = 0;
+    for address in state_addresses {
+        let segments = address_segments(address);
+        match segments.first().map(String::as_str) {
+            Some("module") => skipped_module_instances += 1,
+            Some("data") => {}
+            _ => {
+                let resource = segments.join(".");
+                if !declared.contains(&resource) {
+                    orphaned.entry(resource).or_default().push(address.clone());
+                }
+            }
+        }
+    }
+
+    let orphaned: Vec<OrphanedResource> = orphaned
+        .into_iter()
+        .map(|(address, instances)| OrphanedResource { address, instances })
+        .collect();
+    let message = if orphaned.is_empty() {
+        "Every root module resource in state is declared in the configuration".to_string()
+    } else {
+        format!(
+            "{} resource(s) in state are no longer declared in the configuration. \
+             The next apply will destroy them; use a `removed` block or \
+             `terraform state rm` to keep the real infrastructure.",
+            orphaned.len()
+        )
+    };
+
+    OrphanReport {
+        state_resource_count: state_addresses.len(),
+        config_resource_count: declared.len(),
+        orphaned,
+        skipped_module_instances,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(resource_type: &str, name: &str) -> TerraformResource {
+        TerraformResource {
+            resource_type: resource_type.to_string(),
+            name: name.to_string(),
+            file: "main.tf".to_string(),
+            provider: "aws".to_string(),
+            provider_alias: None,
+        }
+    }
+
+    #[test]
+    fn test_reports_resource_missing_from_config() {
+        let state: Vec<String> = [
+            "aws_instance.web[0]",
+            "aws_instance.web[1]",
+            "aws_s3_bucket.assets[\"logs.example\"]",
+            "aws_s3_bucket.old",
+            "data.aws_ami.ubuntu",
+            "module.vpc.aws_vpc.this[0]",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        let config = [
+            resource("aws_instance", "web"),
+            resource("aws_s3_bucket", "assets"),
+        ];
+
+        let report = find_orphaned_resources(&state, &config);
+        assert_eq!(report.state_resource_count, 6);
+        assert_eq!(report.config_resource_count, 2);
+        assert_eq!(report.skipped_module_instances, 1);
+        assert_eq!(report.orphaned.len(), 1);
+        assert_eq!(report.orphaned[0].address, "aws_s3_bucket.old");
+        assert_eq!(report.orphaned[0].instances, ["aws_s3_bucket.old"]);
+    }
+
+    #[test]
+    fn test_groups_instances_of_removed_block() {
+        let state: Vec<String> = ["aws_instance.worker[0]", "aws_instance.worker[1]"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+
+        let report = find_orphaned_resources(&state, &[]);
+        assert_eq!(report.orphaned.len(), 1);
+        assert_eq!(report.orphaned[0].address, "aws_instance.worker");
+        assert_eq!(report.orphaned[0].instances.len(), 2);
+
+        let clean = find_orphaned_resources(&[], &[resource("aws_instance", "web")]);
+        assert!(clean.orphaned.is_empty());
+        assert!(clean.message.starts_with("Every root module resource"));
+    }
+}
@@ -1,8 +1,7 @@
 //! Terraform output value retrieval.
 
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::process::Command;
+use std::process::Output;
 
 /// A single output value
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,22 +21,22 @@ pub struct OutputResult {
     pub message: String,
 }
 
-/// Get all terraform outputs or a specific one
-pub fn get_outputs(
-    terraform_path: &Path,
-    project_dir: &Path,
-    name: Option<&str>,
-) -> anyhow::Result<OutputResult> {
-    let mut cmd = Command::new(terraform_path);
-    cmd.arg("output").arg("-json");
-
-    // If a specific output is requested
-    if let Some(output_name) = name {
-        cmd.arg(output_name);
-    }
+/// Placeholder substituted for the value of a sensitive output
+pub const SENSITIVE_PLACEHOLDER: &str = "<sensitive>";
 
-    let output = cmd.current_dir(project_dir).output()?;
+/// Arguments fetching outputs. Always the full map: `output -json <name>`
+/// omits the sensitive flag.
+pub const OUTPUT_ARGS: &[&str] = &["output", "-json"];
 
+/// All terraform outputs or a specific one, from a run with [`OUTPUT_ARGS`].
+///
+/// Values of outputs marked `sensitive` are replaced with
+/// [`SENSITIVE_PLACEHOLDER`] unless `reveal_sensitive` is set.
+pub fn outputs_result(
+    output: &Output,
+    name: Option<&str>,
+    reveal_sensitive: bool,
+) -> anyhow::Result<OutputResult> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
@@ -51,78 +50,32 @@ pub fn get_outputs(
             });
         }
 
-        if stderr.contains("output") && stderr.contains("not found") {
-            return Err(anyhow::anyhow!(
-                "Output '{}' not found",
-                name.unwrap_or("unknown")
-            ));
-        }
-
         return Err(anyhow::anyhow!("Failed to get outputs: {}", stderr));
     }
 
-    // Parse JSON output
-    let outputs = if let Some(output_name) = name {
-        // Single output: the JSON is the value itself
-        if stdout.trim().is_empty() {
-            vec![]
-        } else {
-            match serde_json::from_str::<serde_json::Value>(&stdout) {
-                Ok(value) => {
-                    vec![OutputValue {
-                        name: output_name.to_string(),
-                        value: value.clone(),
-                        value_type: get_value_type(&value),
-                        sensitive: false, // Can't determine from single output
-                        description: None,
-                    }]
-                }
-                Err(e) => return Err(anyhow::anyhow!("Failed to parse output JSON: {}", e)),
-            }
+    let mut outputs = parse_outputs_json(&stdout)?;
+
+    if let Some(output_name) = name {
+        outputs.retain(|o| o.name == output_name);
+        if outputs.is_empty() {
+            return Err(anyhow::anyhow!("Output '{}' not found", output_name));
         }
+    }
+
+    let masked = if reveal_sensitive {
+        0
     } else {
-        // All outputs: JSON is a map of output names to output objects
-        if stdout.trim().is_empty() || stdout.trim() == "{}" {
-            vec![]
-        } else {
-            match serde_json::from_str::<serde_json::Value>(&stdout) {
-                Ok(serde_json::Value::Object(map)) => {
-                    let mut outputs = Vec::new();
-                    for (name, output_obj) in map {
-                        let value = output_obj
-                            .get("value")
-                            .cloned()
-                            .unwrap_or(serde_json::Value::Null);
-                        let sensitive = output_obj
-                            .get("sensitive")
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(false);
-                        let value_type = output_obj
-                            .get("type")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|| get_value_type(&value));
-
-                        outputs.push(OutputValue {
-                            name,
-                            value,
-                            value_type,
-                            sensitive,
-                            description: None,
-                        });
-                    }
-                    outputs
-                }
-                Ok(_) => {
-                    return Err(anyhow::anyhow!("Unexpected output format"));
-                }
-                Err(e) => return Err(anyhow::anyhow!("Failed to parse outputs JSON: {}", e)),
-            }
-        }
+        mask_sensitive_outputs(&mut outputs)
     };
 
     let message = if outputs.is_empty() {
         "No outputs found".to_string()
+    } else if masked > 0 {
+        format!(
+            "Found {} outputs ({} sensitive values masked)",
+            outputs.len(),
+            masked
+        )
     } else {
         format!("Found {} outputs", outputs.len())
     };
@@ -134,6 +87,60 @@ pub fn get_outputs(
     })
 }
 
+/// Parse the map produced by `terraform output -json`, sorted by name
+pub fn parse_outputs_json(stdout: &str) -> anyhow::Result<Vec<OutputValue>> {
+    if stdout.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let map = match serde_json::from_str::<serde_json::Value>(stdout) {
+        Ok(serde_json::Value::Object(map)) => map,
+        Ok(_) => return Err(anyhow::anyhow!("Unexpected output format")),
+        Err(e) => return Err(anyhow::anyhow!("Failed to parse outputs JSON: {}", e)),
+    };
+
+    let mut outputs = Vec::new();
+    for (name, output_obj) in map {
+        let value = output_obj
+            .get("value")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        let sensitive = output_obj
+            .get("sensitive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let value_type = output_obj
+            .get("type")
+            .filter(|v| !v.is_null())
+            .map(|v| match v.as_str() {
+                Some(s) => s.to_string(),
+                None => v.to_string(),
+            })
+            .unwrap_or_else(|| get_value_type(&value));
+
+        outputs.push(OutputValue {
+            name,
+            value,
+            value_type,
+            sensitive,
+            description: None,
+        });
+    }
+    outputs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(outputs)
+}
+
+/// Replace sensitive output values with a placeholder, returning how many were masked
+pub fn mask_sensitive_outputs(outputs: &mut [OutputValue]) -> usize {
+    let mut masked = 0;
+    for output in outputs.iter_mut().filter(|o| o.sensitive) {
+        output.value = serde_json::Value::String(SENSITIVE_PLACEHOLDER.to_string());
+        masked += 1;
+    }
+    masked
+}
+
 /// Determine the type of a JSON value
 fn get_value_type(value: &serde_json::Value) -> String {
     match value {
@@ -152,17 +159,9 @@ fn get_value_type(value: &serde_json::Value) -> String {
     }
 }
 
-/// Get outputs in a simple key-value format (non-JSON)
+/// Outputs in a simple key-value format, from a plain (non-JSON) `terraform output`
 #[allow(dead_code)]
-pub fn get_outputs_simple(
-    terraform_path: &Path,
-    project_dir: &Path,
-) -> anyhow::Result<Vec<(String, String)>> {
-    let output = Command::new(terraform_path)
-        .arg("output")
-        .current_dir(project_dir)
-        .output()?;
-
+pub fn parse_outputs_simple(output: &Output) -> anyhow::Result<Vec<(String, String)>> {
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         if stderr.contains("No outputs found") {
@@ -200,6 +199,26 @@ mod tests {
         assert_eq!(get_value_type(&serde_json::json!({})), "object");
     }
 
+    #[test]
+    fn test_sensitive_outputs_masked() {
+        let stdout = r#"{
+            "db_password": {"sensitive": true, "type": "string", "value": "hunter2"},
+            "vpc_id": {"sensitive": false, "type": "string", "value": "vpc-123"},
+            "subnet_ids": {"sensitive": false, "type": ["list", "string"], "value": ["a", "b"]}
+        }"#;
+        let mut outputs = parse_outputs_json(stdout).unwrap();
+        let masked = mask_sensitive_outputs(&mut outputs);
+
+        assert_eq!(masked, 1);
+        let names: Vec<&str> = outputs.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec!["db_password", "subnet_ids", "vpc_id"]);
+        assert_eq!(outputs[0].value, serde_json::json!(SENSITIVE_PLACEHOLDER));
+        assert!(outputs[0].sensitive);
+        assert_eq!(outputs[1].value, serde_json::json!(["a", "b"]));
+        assert_eq!(outputs[1].value_type, r#"["list","string"]"#);
+        assert_eq!(outputs[2].value, serde_json::json!("vpc-123"));
+    }
+
     #[test]
     fn test_empty_outputs() {
         // Test that empty output is handled correctly
@@ -1,5 +1,7 @@
+use crate::terraform::analyzer::hcl_lines;
 use crate::terraform::model::{
-    TerraformOutput, TerraformProvider, TerraformResource, TerraformVariable,
+    RemoteStateDependency, StateEncryption, TerraformOutput, TerraformProvider, TerraformResource,
+    TerraformVariable,
 };
 use regex::Regex;
 use serde_json::Value;
@@ -17,8 +19,69 @@ static VARIABLE_REGEX: LazyLock<Regex> =
 static OUTPUT_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"output\s+"([^"]+)""#).expect("Invalid output regex"));
 
+// `\b` keeps OpenTofu's `key_provider "pbkdf2" "name"` from reading as a provider
 static PROVIDER_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"provider\s+"([^"]+)""#).expect("Invalid provider regex"));
+    LazyLock::new(|| Regex::new(r#"\bprovider\s+"([^"]+)""#).expect("Invalid provider regex"));
+
+static PROVIDER_ALIAS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^\s*alias\s*=\s*"([^"]+)""#).expect("Invalid provider alias regex")
+});
+
+static RESOURCE_PROVIDER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^\s*provider\s*=\s*([A-Za-z0-9_-]+)\.([A-Za-z0-9_-]+)"#)
+        .expect("Invalid resource provider regex")
+});
+
+static REQUIRED_VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"required_version\s*=\s*"([^"]+)""#).expect("Invalid required_version regex")
+});
+
+static REMOTE_STATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"data\s+"terraform_remote_state"\s+"([^"]+)""#)
+        .expect("Invalid remote state regex")
+});
+
+static BACKEND_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^\s*backend\s*=\s*"([^"]+)""#).expect("Invalid backend regex")
+});
+
+static TERRAFORM_BLOCK_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*terraform\s*\{").expect("Invalid terraform regex"));
+
+static ENCRYPTION_BLOCK_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*encryption\s*\{").expect("Invalid encryption regex"));
+
+static REQUIRED_PROVIDERS_BLOCK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*required_providers\s*\{").expect("Invalid required_providers regex")
+});
+
+static BLOCK_KEY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*([A-Za-z][A-Za-z0-9_-]*)\s*=").expect("Invalid block key regex")
+});
+
+/// `key_provider "type" "name"` or `method "type" "name"` inside an encryption block
+static ENCRYPTION_ITEM_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^\s*(key_provider|method)\s+"([^"]+)"\s+"([^"]+)""#)
+        .expect("Invalid encryption item regex")
+});
+
+static ENCRYPTION_TARGET_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(state|plan)\s*\{").expect("Invalid encryption target regex")
+});
+
+static METHOD_ASSIGNMENT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*method\s*=").expect("Invalid method regex"));
+
+static ENFORCED_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*enforced\s*=\s*true\b").expect("Invalid enforced regex"));
+
+/// `data.terraform_remote_state.<name>[index].outputs.<output>` or `.outputs["<output>"]`
+static REMOTE_STATE_OUTPUT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"data\.terraform_remote_state\.([A-Za-z0-9_-]+)(?:\[[^\]]*\])?\.outputs(?:\.([A-Za-z0-9_-]+)|\["([^"]+)"\])"#,
+    )
+    .expect("Invalid remote state output regex")
+});
 
 /// Parser for Terraform HCL files
 pub struct TerraformParser {
@@ -38,17 +101,25 @@ impl TerraformParser {
                 if captures.len() >= 3 {
                     let resource_type = captures[1].to_string();
                     let resource_name = captures[2].to_string();
-                    let provider = resource_type
+                    let mut provider = resource_type
                         .split('_')
                         .next()
                         .unwrap_or("unknown")
                         .to_string();
 
+                    // An explicit `provider = aws.west` selects an aliased configuration
+                    let body = self.block_body(captures.get(0)?.end());
+                    let provider_alias = RESOURCE_PROVIDER_REGEX.captures(body).map(|cap| {
+                        provider = cap[1].to_string();
+                        cap[2].to_string()
+                    });
+
                     Some(TerraformResource {
                         resource_type,
                         name: resource_name,
                         file: file_name.to_string(),
                         provider,
+                        provider_alias,
                     })
                 } else {
                     None
@@ -69,6 +140,11 @@ impl TerraformParser {
                         description: self.extract_field_value(&name, "variable", "description"),
                         type_: self.extract_field_type(&name),
                         default: self.extract_field_json(&name, "variable", "default"),
+                        sensitive: matches!(
+                            self.extract_field_json(&name, "variable", "sensitive"),
+                            Some(Value::Bool(true))
+                        ),
+                        effective: None,
                     })
                 } else {
                     None
@@ -104,21 +180,150 @@ impl TerraformParser {
         for captures in PROVIDER_REGEX.captures_iter(&self.content) {
             if captures.len() >= 2 {
                 let name = captures[1].to_string();
-                let version = self.extract_provider_version(&name);
-                providers.insert(name.clone(), TerraformProvider { name, version });
+                let alias = captures.get(0).and_then(|m| {
+                    PROVIDER_ALIAS_REGEX
+                        .captures(self.block_body(m.end()))
+                        .map(|cap| cap[1].to_string())
+                });
+                let provider = providers
+                    .entry(name.clone())
+                    .or_insert_with(|| TerraformProvider {
+                        version: self.extract_provider_version(&name),
+                        name,
+                        aliases: Vec::new(),
+                    });
+                if let Some(alias) = alias {
+                    if !provider.aliases.contains(&alias) {
+                        provider.aliases.push(alias);
+                    }
+                }
             }
         }
 
         // Also check required_providers block
         if let Some(required_providers) = self.extract_required_providers() {
             for (name, version) in required_providers {
-                providers
-                    .entry(name.clone())
-                    .or_insert(TerraformProvider { name, version });
+                providers.entry(name.clone()).or_insert(TerraformProvider {
+                    name,
+                    version,
+                    aliases: Vec::new(),
+                });
             }
         }
 
-        providers.into_values().collect()
+        providers
+            .into_values()
+            .map(|mut provider| {
+                provider.aliases.sort();
+                provider
+            })
+            .collect()
+    }
+
+    /// Parse `terraform_remote_state` data sources; their outputs are left
+    /// empty since references may live in other files
+    pub fn parse_remote_states(&self, file_name: &str) -> Vec<RemoteStateDependency> {
+        REMOTE_STATE_REGEX
+            .captures_iter(&self.content)
+            .filter_map(|captures| {
+                let body = self.block_body(captures.get(0)?.end());
+                Some(RemoteStateDependency {
+                    name: captures[1].to_string(),
+                    backend: BACKEND_REGEX.captures(body).map(|cap| cap[1].to_string()),
+                    file: file_name.to_string(),
+                    outputs: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    /// Parse `(data source name, output name)` pairs read from remote state
+    pub fn parse_remote_state_references(&self) -> Vec<(String, String)> {
+        REMOTE_STATE_OUTPUT_REGEX
+            .captures_iter(&self.content)
+            .filter_map(|captures| {
+                let output = captures.get(2).or_else(|| captures.get(3))?;
+                Some((captures[1].to_string(), output.as_str().to_string()))
+            })
+            .collect()
+    }
+
+    /// Parse the `required_version` constraint from the terraform block
+    pub fn parse_required_version(&self) -> Option<String> {
+        REQUIRED_VERSION_REGEX
+            .captures(&self.content)?
+            .get(1)
+            .map(|m| m.as_str().trim().to_string())
+    }
+
+    /// Local names declared in `required_providers` blocks of the terraform
+    /// block, in declaration order
+    pub fn parse_required_provider_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for terraform in TERRAFORM_BLOCK_REGEX
+            .find_iter(&self.content)
+            .map(|m| block_body(&self.content, m.start()))
+        {
+            for header in REQUIRED_PROVIDERS_BLOCK_REGEX.find_iter(terraform) {
+                // Only keys at the top level of the block name providers;
+                // `source` and `version` sit one level down
+                for line in hcl_lines(block_body(terraform, header.start())) {
+                    if line.depth == 0 {
+                        if let Some(cap) = BLOCK_KEY_REGEX.captures(line.text) {
+                            if !names.contains(&cap[1].to_string()) {
+                                names.push(cap[1].to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Parse OpenTofu's `encryption` block from the terraform block
+    pub fn parse_state_encryption(&self, file_name: &str) -> Option<StateEncryption> {
+        let body = TERRAFORM_BLOCK_REGEX
+            .find_iter(&self.content)
+            .map(|m| block_body(&self.content, m.start()))
+            .find_map(|terraform| {
+                let header = ENCRYPTION_BLOCK_REGEX.find(terraform)?;
+                Some(block_body(terraform, header.start()))
+            })?;
+
+        let mut encryption = StateEncryption {
+            file: file_name.to_string(),
+            state_encrypted: false,
+            plan_encrypted: false,
+            enforced: false,
+            key_providers: Vec::new(),
+            methods: Vec::new(),
+        };
+        for captures in ENCRYPTION_ITEM_REGEX.captures_iter(body) {
+            let item = format!("{}.{}", &captures[2], &captures[3]);
+            match &captures[1] {
+                "key_provider" => encryption.key_providers.push(item),
+                _ => encryption.methods.push(item),
+            }
+        }
+        for target in ENCRYPTION_TARGET_REGEX.captures_iter(body) {
+            let target_body = block_body(body, target.get(0).map_or(0, |m| m.start()));
+            if !METHOD_ASSIGNMENT_REGEX.is_match(target_body) {
+                continue;
+            }
+            if &target[1] == "state" {
+                encryption.state_encrypted = true;
+                encryption.enforced = ENFORCED_REGEX.is_match(target_body);
+            } else {
+                encryption.plan_encrypted = true;
+            }
+        }
+        Some(encryption)
+    }
+
+    /// [`block_body`] of this file's content
+    fn block_body(&self, offset: usize) -> &str {
+        block_body(&self.content, offset)
     }
 
     /// Extract type field specifically (handles unquoted values like 'string', 'number', etc.)
@@ -217,6 +422,29 @@ impl TerraformParser {
     }
 }
 
+/// Body of the block whose header ends at or after `offset` in `text`,
+/// excluding the outer braces. Empty if no opening brace follows.
+fn block_body(text: &str, offset: usize) -> &str {
+    let rest = &text[offset..];
+    let Some(open) = rest.find('{') else {
+        return "";
+    };
+    let mut depth = 0;
+    for (i, c) in rest[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &rest[open + 1..open + i];
+                }
+            }
+            _ => {}
+        }
+    }
+    &rest[open + 1..]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -337,4 +565,194 @@ provider "google" {
         let google_provider = providers.iter().find(|p| p.name == "google");
         assert!(google_provider.is_some());
     }
+
+    #[test]
+    fn test_parse_provider_aliases() {
+        let content = r#"
+provider "aws" {
+  region = "us-east-1"
+}
+
+provider "aws" {
+  alias  = "west"
+  region = "us-west-2"
+}
+
+provider "aws" {
+  alias  = "eu"
+  region = "eu-west-1"
+}
+
+resource "aws_s3_bucket" "primary" {
+  bucket = "primary"
+}
+
+resource "aws_s3_bucket" "replica" {
+  provider = aws.west
+  bucket   = "replica"
+}
+
+resource "aws_s3_bucket" "archive" {
+  provider = aws.eu
+  bucket   = "archive"
+}
+"#;
+        let parser = TerraformParser::new(content.to_string());
+
+        let providers = parser.parse_providers();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].name, "aws");
+        assert_eq!(providers[0].aliases, vec!["eu", "west"]);
+
+        let resources = parser.parse_resources("main.tf");
+        assert_eq!(resources.len(), 3);
+        assert_eq!(resources[0].provider_alias, None);
+        assert_eq!(resources[1].provider, "aws");
+        assert_eq!(resources[1].provider_alias.as_deref(), Some("west"));
+        assert_eq!(resources[2].provider_alias.as_deref(), Some("eu"));
+    }
+
+    #[test]
+    fn test_parse_required_version() {
+        let content = r#"
+terraform {
+  required_version = ">= 1.5.0, < 2.0.0"
+}
+"#;
+        let parser = TerraformParser::new(content.to_string());
+        assert_eq!(
+            parser.parse_required_version(),
+            Some(">= 1.5.0, < 2.0.0".to_string())
+        );
+
+        let parser = TerraformParser::new("provider \"aws\" {}".to_string());
+        assert_eq!(parser.parse_required_version(), None);
+    }
+
+    #[test]
+    fn test_parse_required_provider_names() {
+        let content = r#"
+terraform {
+  required_version = ">= 1.5.0"
+  required_providers {
+    aws = {
+      source  = "hashicorp/aws"
+      version = "~> 5.0"
+    }
+    random = {
+      source = "hashicorp/random"
+    }
+    legacy = "~> 1.0"
+  }
+}
+"#;
+        let parser = TerraformParser::new(content.to_string());
+        assert_eq!(
+            parser.parse_required_provider_names(),
+            vec!["aws", "random", "legacy"]
+        );
+
+        let parser = TerraformParser::new("provider \"aws\" {}".to_string());
+        assert!(parser.parse_required_provider_names().is_empty());
+    }
+
+    #[test]
+    fn test_parse_remote_states() {
+        let content = r#"
+data "terraform_remote_state" "network" {
+  backend = "s3"
+  config = {
+    bucket = "state"
+    key    = "network/terraform.tfstate"
+  }
+}
+
+data "terraform_remote_state" "shared" {
+  backend = "remote"
+  config = {
+    organization = "acme"
+  }
+}
+
+resource "aws_instance" "web" {
+  subnet_id = data.terraform_remote_state.network.outputs.subnet_id
+  tags = {
+    Zone = data.terraform_remote_state.shared.outputs["dns_zone"]
+  }
+}
+"#;
+        let parser = TerraformParser::new(content.to_string());
+
+        let remote_states = parser.parse_remote_states("data.tf");
+        assert_eq!(remote_states.len(), 2);
+        assert_eq!(remote_states[0].name, "network");
+        assert_eq!(remote_states[0].backend.as_deref(), Some("s3"));
+        assert_eq!(remote_states[0].file, "data.tf");
+        assert_eq!(remote_states[1].name, "shared");
+        assert_eq!(remote_states[1].backend.as_deref(), Some("remote"));
+
+        assert_eq!(
+            parser.parse_remote_state_references(),
+            vec![
+                ("network".to_string(), "subnet_id".to_string()),
+                ("shared".to_string(), "dns_zone".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_opentofu_encryption_block() {
+        let content = r#"
+terraform {
+  required_version = ">= 1.7.0"
+
+  encryption {
+    key_provider "pbkdf2" "passphrase" {
+      passphrase = var.state_passphrase
+    }
+
+    method "aes_gcm" "default" {
+      keys = key_provider.pbkdf2.passphrase
+    }
+
+    method "unencrypted" "migrate" {}
+
+    state {
+      method   = method.aes_gcm.default
+      enforced = true
+
+      fallback {
+        method = method.unencrypted.migrate
+      }
+    }
+  }
+}
+
+provider "aws" {
+  region = "eu-west-1"
+}
+"#;
+        let parser = TerraformParser::new(content.to_string());
+
+        let encryption = parser.parse_state_encryption("versions.tf").unwrap();
+        assert_eq!(encryption.file, "versions.tf");
+        assert!(encryption.state_encrypted);
+        assert!(encryption.enforced);
+        assert!(!encryption.plan_encrypted);
+        assert_eq!(encryption.key_providers, vec!["pbkdf2.passphrase"]);
+        assert_eq!(
+            encryption.methods,
+            vec!["aes_gcm.default", "unencrypted.migrate"]
+        );
+
+        // The key_provider label is not a provider
+        let providers = parser.parse_providers();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].name, "aws");
+        assert_eq!(parser.parse_required_version().as_deref(), Some(">= 1.7.0"));
+
+        let plain =
+            TerraformParser::new("terraform {\n  required_version = \">= 1.5\"\n}\n".to_string());
+        assert_eq!(plain.parse_state_encryption("main.tf"), None);
+    }
 }
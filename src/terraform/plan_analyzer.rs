@@ -1,10 +1,14 @@
 //! Plan analyzer for detailed terraform plan analysis with risk scoring.
 
+use crate::shared::security::wildcard_match;
+use crate::terraform::model::TerraformValidateOutput;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
 /// Risk level for plan changes
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum RiskLevel {
     Low,
@@ -14,7 +18,7 @@ pub enum RiskLevel {
 }
 
 /// A single resource change from terraform plan
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ResourceChange {
     pub address: String,
     pub resource_type: String,
@@ -23,10 +27,26 @@ pub struct ResourceChange {
     pub before: Option<serde_json::Value>,
     pub after: Option<serde_json::Value>,
     pub after_unknown: Option<serde_json::Value>,
+    /// Mirrors `before` with `true` at sensitive values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before_sensitive: Option<serde_json::Value>,
+    /// Mirrors `after` with `true` at sensitive values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_sensitive: Option<serde_json::Value>,
+}
+
+impl ResourceChange {
+    /// Whether the change deletes the resource, alone or as part of a replacement
+    pub fn is_destructive(&self) -> bool {
+        matches!(
+            self.action.as_str(),
+            "delete" | "replace" | "create_delete" | "delete_create"
+        )
+    }
 }
 
 /// Change summary statistics
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ChangeSummary {
     pub add: i32,
     pub change: i32,
@@ -36,16 +56,157 @@ pub struct ChangeSummary {
 }
 
 /// Risk assessment for the plan
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RiskAssessment {
     pub level: RiskLevel,
     pub score: i32,
     pub warnings: Vec<String>,
     pub recommendations: Vec<String>,
+    /// Highest-scoring individual changes, most risky first
+    #[serde(default)]
+    pub top_risks: Vec<ChangeRisk>,
+    /// Deletions and replacements of critical resource types
+    #[serde(default)]
+    pub critical_changes: Vec<CriticalChange>,
+}
+
+/// A destructive change to a resource type declared critical
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CriticalChange {
+    pub address: String,
+    pub resource_type: String,
+    pub action: String,
+    /// Critical resource type pattern the type matched
+    pub pattern: String,
+}
+
+/// Risk contribution of a single resource change
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChangeRisk {
+    pub address: String,
+    pub action: String,
+    pub score: i32,
+    /// Policy rule that determined the score, if any
+    pub matched_rule: Option<String>,
+}
+
+/// Weight assigned by a risk policy rule: a named level or explicit points
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(untagged)]
+pub enum RiskWeight {
+    Level(RiskLevel),
+    Points(i32),
+}
+
+impl RiskWeight {
+    pub fn points(&self) -> i32 {
+        match self {
+            RiskWeight::Level(RiskLevel::Low) => 2,
+            RiskWeight::Level(RiskLevel::Medium) => 10,
+            RiskWeight::Level(RiskLevel::High) => 30,
+            RiskWeight::Level(RiskLevel::Critical) => 80,
+            RiskWeight::Points(points) => *points,
+        }
+    }
+}
+
+/// A single risk policy rule, e.g. `*_database` + `delete` = `critical`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskRule {
+    /// Resource type pattern; `*` matches any run of characters
+    pub resource_type: String,
+    /// Plan action ("create", "update", "delete", "replace") or "*" for any
+    #[serde(default = "any_action")]
+    pub action: String,
+    pub weight: RiskWeight,
+}
+
+fn any_action() -> String {
+    "*".to_string()
+}
+
+impl RiskRule {
+    fn matches(&self, change: &ResourceChange) -> bool {
+        let action_matches = match self.action.as_str() {
+            "*" => true,
+            "replace" => matches!(
+                change.action.as_str(),
+                "replace" | "create_delete" | "delete_create"
+            ),
+            action => change.action == action,
+        };
+        action_matches && wildcard_match(&self.resource_type, &change.resource_type)
+    }
+
+    fn describe(&self) -> String {
+        format!("{} -> {}", self.resource_type, self.action)
+    }
+}
+
+/// User-supplied risk weights for `analyze_plan`.
+///
+/// The first matching rule replaces the built-in score for a change;
+/// changes matching no rule keep the built-in weights. Deleting or replacing
+/// a critical resource type adds [`CRITICAL_CHANGE_POINTS`] on top of either.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RiskPolicy {
+    #[serde(default)]
+    pub rules: Vec<RiskRule>,
+    /// Business-critical resource type patterns; `*` matches any run of
+    /// characters. [`DEFAULT_CRITICAL_RESOURCE_TYPES`] when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critical_resource_types: Option<Vec<String>>,
+}
+
+impl RiskPolicy {
+    /// Load a policy from a JSON file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read risk policy {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse risk policy {}: {}", path.display(), e))
+    }
+
+    fn find_rule(&self, change: &ResourceChange) -> Option<&RiskRule> {
+        self.rules.iter().find(|rule| rule.matches(change))
+    }
 }
 
+/// Resource types treated as critical when no list is configured
+pub const DEFAULT_CRITICAL_RESOURCE_TYPES: &[&str] = &[
+    "*_db_instance",
+    "*_rds_cluster",
+    "*_sql_database_instance",
+    "*_dynamodb_table",
+    "aws_iam_*",
+    "google_project_iam_*",
+    "azurerm_role_*",
+    "*_dns_zone",
+    "aws_route53_zone",
+    "google_dns_managed_zone",
+    "*_kms_key",
+    "azurerm_key_vault",
+];
+
+/// Points added for deleting or replacing a critical resource type
+pub const CRITICAL_CHANGE_POINTS: i32 = 100;
+
+/// First critical pattern matching a deletion or replacement
+fn critical_pattern<'a>(patterns: &[&'a str], change: &ResourceChange) -> Option<&'a str> {
+    if !change.is_destructive() {
+        return None;
+    }
+    patterns
+        .iter()
+        .copied()
+        .find(|pattern| wildcard_match(pattern, &change.resource_type))
+}
+
+/// Number of changes listed in `RiskAssessment::top_risks`
+const TOP_RISKS_LIMIT: usize = 5;
+
 /// Dependency impact analysis
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DependencyImpact {
     pub resource: String,
     pub affected_by: Vec<String>,
@@ -53,7 +214,7 @@ pub struct DependencyImpact {
 }
 
 /// Complete plan analysis result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlanAnalysis {
     pub summary: ChangeSummary,
     pub resource_changes: Vec<ResourceChange>,
@@ -61,6 +222,108 @@ pub struct PlanAnalysis {
     pub dependency_impacts: Vec<DependencyImpact>,
     pub terraform_version: Option<String>,
     pub format_version: Option<String>,
+    /// Changes dropped from `resource_changes` by a [`NoiseFilter`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filtered: Option<FilteredChanges>,
+}
+
+/// Low-signal changes to drop from `PlanAnalysis::resource_changes`; the
+/// summary still counts them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoiseFilter {
+    /// Drop no-op and read changes
+    pub hide_no_op: bool,
+    /// Drop updates that only touch `tags`/`tags_all`
+    pub collapse_tag_only: bool,
+}
+
+/// Changes removed by a [`NoiseFilter`]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct FilteredChanges {
+    pub no_op: usize,
+    pub tag_only: usize,
+    /// Addresses of the collapsed tag-only updates
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_only_addresses: Vec<String>,
+}
+
+/// Attributes whose changes `NoiseFilter::collapse_tag_only` ignores
+const TAG_ATTRIBUTES: &[&str] = &["tags", "tags_all"];
+
+impl PlanAnalysis {
+    /// Addresses the plan deletes or replaces, in plan order
+    pub fn destructive_addresses(&self) -> Vec<String> {
+        self.resource_changes
+            .iter()
+            .filter(|change| change.is_destructive())
+            .map(|change| change.address.clone())
+            .collect()
+    }
+
+    /// Drop the changes selected by `filter`, recording what was dropped in
+    /// `filtered`. Does nothing when no option is set.
+    pub fn filter_noise(&mut self, filter: NoiseFilter) {
+        if !filter.hide_no_op && !filter.collapse_tag_only {
+            return;
+        }
+        let mut filtered = FilteredChanges::default();
+        self.resource_changes.retain(|change| {
+            if filter.hide_no_op && matches!(change.action.as_str(), "no-op" | "read") {
+                filtered.no_op += 1;
+                false
+            } else if filter.collapse_tag_only && is_tag_only_update(change) {
+                filtered.tag_only += 1;
+                filtered.tag_only_addresses.push(change.address.clone());
+                false
+            } else {
+                true
+            }
+        });
+        self.filtered = Some(filtered);
+    }
+}
+
+/// Whether an update changes `tags`/`tags_all` and nothing else
+fn is_tag_only_update(change: &ResourceChange) -> bool {
+    use serde_json::Value;
+
+    if change.action != "update" {
+        return false;
+    }
+    let (Some(Value::Object(before)), Some(Value::Object(after))) = (&change.before, &change.after)
+    else {
+        return false;
+    };
+    let unknown = change.after_unknown.as_ref().and_then(Value::as_object);
+
+    let mut touches_tags = false;
+    for key in before
+        .keys()
+        .chain(after.keys())
+        .chain(unknown.into_iter().flat_map(|u| u.keys()))
+    {
+        let changed = before.get(key) != after.get(key)
+            || unknown
+                .and_then(|u| u.get(key))
+                .is_some_and(contains_unknown);
+        if changed {
+            if !TAG_ATTRIBUTES.contains(&key.as_str()) {
+                return false;
+            }
+            touches_tags = true;
+        }
+    }
+    touches_tags
+}
+
+/// Whether an `after_unknown` value marks anything as unknown
+fn contains_unknown(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Bool(unknown) => *unknown,
+        serde_json::Value::Array(items) => items.iter().any(contains_unknown),
+        serde_json::Value::Object(map) => map.values().any(contains_unknown),
+        _ => false,
+    }
 }
 
 /// Terraform plan JSON output structure
@@ -75,6 +338,66 @@ struct TerraformPlanJson {
     configuration: Option<serde_json::Value>,
 }
 
+/// Resources a `terraform plan -destroy` would remove; nothing is destroyed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DestroyPreview {
+    /// Always true: this output comes from a plan, not from a destroy
+    pub preview: bool,
+    pub message: String,
+    pub summary: ChangeSummary,
+    pub resources: Vec<DestroyedResource>,
+}
+
+/// Combined result of validating and, when valid, planning the configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PreflightResult {
+    pub valid: bool,
+    /// False when validation failed and planning was skipped
+    pub planned: bool,
+    pub validation: TerraformValidateOutput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<PlanAnalysis>,
+    pub message: String,
+}
+
+impl PreflightResult {
+    /// Validation failed, so no plan was run
+    pub fn invalid(validation: TerraformValidateOutput) -> Self {
+        Self {
+            valid: false,
+            planned: false,
+            message: format!(
+                "Validation failed with {} error(s); plan was skipped",
+                validation.error_count
+            ),
+            validation,
+            plan: None,
+        }
+    }
+
+    pub fn planned(validation: TerraformValidateOutput, plan: PlanAnalysis) -> Self {
+        let summary = &plan.summary;
+        Self {
+            valid: true,
+            planned: true,
+            message: format!(
+                "Configuration is valid. Plan: {} to add, {} to change, {} to destroy, {} to replace",
+                summary.add, summary.change, summary.destroy, summary.replace
+            ),
+            validation,
+            plan: Some(plan),
+        }
+    }
+}
+
+/// A resource that would be deleted by `terraform destroy`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DestroyedResource {
+    pub address: String,
+    pub resource_type: String,
+    pub provider: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct PlanResourceChange {
     address: String,
@@ -90,6 +413,10 @@ struct PlanChange {
     before: Option<serde_json::Value>,
     after: Option<serde_json::Value>,
     after_unknown: Option<serde_json::Value>,
+    #[serde(default)]
+    before_sensitive: Option<serde_json::Value>,
+    #[serde(default)]
+    after_sensitive: Option<serde_json::Value>,
 }
 
 /// High-risk resource types that require extra caution
@@ -121,7 +448,11 @@ const HIGH_RISK_RESOURCES: &[&str] = &[
 ];
 
 /// Analyze terraform plan JSON output
-pub fn analyze_plan(plan_json: &str, include_risk: bool) -> anyhow::Result<PlanAnalysis> {
+pub fn analyze_plan(
+    plan_json: &str,
+    include_risk: bool,
+    policy: Option<&RiskPolicy>,
+) -> anyhow::Result<PlanAnalysis> {
     // Try to parse as JSON array of lines (terraform plan -json outputs NDJSON)
     let plan = parse_plan_json(plan_json)?;
 
@@ -156,19 +487,29 @@ pub fn analyze_plan(plan_json: &str, include_risk: bool) -> anyhow::Result<PlanA
                 before: change.change.as_ref().and_then(|c| c.before.clone()),
                 after: change.change.as_ref().and_then(|c| c.after.clone()),
                 after_unknown: change.change.as_ref().and_then(|c| c.after_unknown.clone()),
+                before_sensitive: change
+                    .change
+                    .as_ref()
+                    .and_then(|c| c.before_sensitive.clone()),
+                after_sensitive: change
+                    .change
+                    .as_ref()
+                    .and_then(|c| c.after_sensitive.clone()),
             };
             resource_changes.push(rc);
         }
     }
 
     let risk_assessment = if include_risk {
-        assess_risk(&resource_changes, &summary)
+        assess_risk(&resource_changes, &summary, policy)
     } else {
         RiskAssessment {
             level: RiskLevel::Low,
             score: 0,
             warnings: vec![],
             recommendations: vec![],
+            top_risks: vec![],
+            critical_changes: vec![],
         }
     };
 
@@ -181,6 +522,74 @@ pub fn analyze_plan(plan_json: &str, include_risk: bool) -> anyhow::Result<PlanA
         dependency_impacts,
         terraform_version: plan.terraform_version,
         format_version: plan.format_version,
+        filtered: None,
+    })
+}
+
+/// Summarize `terraform plan -destroy -json` output as a destroy preview
+pub fn summarize_destroy_plan(plan_json: &str) -> anyhow::Result<DestroyPreview> {
+    let analysis = analyze_plan(plan_json, false, None)?;
+
+    let resources: Vec<DestroyedResource> = analysis
+        .resource_changes
+        .into_iter()
+        .filter(|c| c.action == "delete")
+        .map(|c| DestroyedResource {
+            address: c.address,
+            resource_type: c.resource_type,
+            provider: c.provider,
+        })
+        .collect();
+
+    let message = if resources.is_empty() {
+        "Preview only: destroy would not remove any resources".to_string()
+    } else {
+        format!(
+            "Preview only: destroy would remove {} resource(s). Nothing has been destroyed.",
+            resources.len()
+        )
+    };
+
+    Ok(DestroyPreview {
+        preview: true,
+        message,
+        summary: analysis.summary,
+        resources,
+    })
+}
+
+/// Convert a streamed `planned_change` message into a resource change.
+///
+/// `terraform plan -json` streams `{"resource": {"addr", ...}, "action": "delete"}`
+/// rather than the `resource_changes` shape of `terraform show -json`.
+fn streamed_change(change: &serde_json::Value) -> Option<PlanResourceChange> {
+    let resource = change.get("resource")?;
+    let action = change.get("action")?.as_str()?;
+    let actions = match action {
+        "replace" => vec!["delete".to_string(), "create".to_string()],
+        "noop" => vec!["no-op".to_string()],
+        other => vec![other.to_string()],
+    };
+
+    Some(PlanResourceChange {
+        address: resource.get("addr")?.as_str()?.to_string(),
+        resource_type: resource
+            .get("resource_type")
+            .and_then(|t| t.as_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        provider_name: resource
+            .get("implied_provider")
+            .and_then(|p| p.as_str())
+            .map(str::to_string),
+        change: Some(PlanChange {
+            actions,
+            before: None,
+            after: None,
+            after_unknown: None,
+            before_sensitive: None,
+            after_sensitive: None,
+        }),
     })
 }
 
@@ -218,6 +627,8 @@ fn parse_plan_json(json_str: &str) -> anyhow::Result<TerraformPlanJson> {
                         if let Ok(rc) = serde_json::from_value::<PlanResourceChange>(change.clone())
                         {
                             resource_changes.push(rc);
+                        } else if change_type == "planned_change" {
+                            resource_changes.extend(streamed_change(change));
                         }
                     }
                 }
@@ -255,75 +666,73 @@ fn actions_to_string(actions: &[String]) -> String {
 }
 
 /// Assess risk based on resource changes
-fn assess_risk(changes: &[ResourceChange], summary: &ChangeSummary) -> RiskAssessment {
+fn assess_risk(
+    changes: &[ResourceChange],
+    summary: &ChangeSummary,
+    policy: Option<&RiskPolicy>,
+) -> RiskAssessment {
     let mut score = 0;
     let mut warnings = Vec::new();
     let mut recommendations = Vec::new();
+    let mut change_risks = Vec::new();
+    let mut critical_changes = Vec::new();
+    let critical_types: Vec<&str> = match policy.and_then(|p| p.critical_resource_types.as_ref()) {
+        Some(types) => types.iter().map(String::as_str).collect(),
+        None => DEFAULT_CRITICAL_RESOURCE_TYPES.to_vec(),
+    };
 
-    // Base score from change counts
-    score += summary.destroy * 30;
-    score += summary.replace * 20;
-    score += summary.change * 5;
-    score += summary.add * 2;
-
-    // Check for high-risk resources
     for change in changes {
-        let is_high_risk = HIGH_RISK_RESOURCES
-            .iter()
-            .any(|&r| change.resource_type == r);
-
-        if is_high_risk {
-            match change.action.as_str() {
-                "delete" => {
-                    score += 50;
-                    warnings.push(format!(
-                        "CRITICAL: High-risk resource '{}' will be DESTROYED",
-                        change.address
-                    ));
-                }
-                "replace" | "create_delete" | "delete_create" => {
-                    score += 40;
-                    warnings.push(format!(
-                        "WARNING: High-risk resource '{}' will be REPLACED (data loss possible)",
-                        change.address
-                    ));
-                }
-                "update" => {
-                    score += 15;
+        let rule = policy.and_then(|p| p.find_rule(change));
+        let mut change_score = match rule {
+            Some(rule) => {
+                let points = rule.weight.points();
+                if points > 0 {
                     warnings.push(format!(
-                        "CAUTION: High-risk resource '{}' will be modified",
-                        change.address
+                        "Policy rule '{}' matched '{}' (+{})",
+                        rule.describe(),
+                        change.address,
+                        points
                     ));
                 }
-                _ => {}
+                points
             }
-        }
+            None => builtin_change_score(change, &mut warnings),
+        };
 
-        // Check for IAM/security changes
-        let is_security_resource = change.resource_type.contains("iam")
-            || change.resource_type.contains("security")
-            || change.resource_type.contains("firewall");
-        if is_security_resource && change.action != "no-op" && change.action != "read" {
-            score += 10;
+        if let Some(pattern) = critical_pattern(&critical_types, change) {
+            change_score += CRITICAL_CHANGE_POINTS;
             warnings.push(format!(
-                "Security-related resource '{}' will be modified",
-                change.address
+                "CRITICAL: '{}' matches critical resource type '{}' and will be {}",
+                change.address,
+                pattern,
+                if change.action == "delete" {
+                    "DESTROYED"
+                } else {
+                    "REPLACED"
+                }
             ));
+            critical_changes.push(CriticalChange {
+                address: change.address.clone(),
+                resource_type: change.resource_type.clone(),
+                action: change.action.clone(),
+                pattern: pattern.to_string(),
+            });
         }
 
-        // Check for network changes
-        let is_network_resource = change.resource_type.contains("vpc")
-            || change.resource_type.contains("network")
-            || change.resource_type.contains("subnet");
-        if is_network_resource && (change.action == "delete" || change.action.contains("replace")) {
-            score += 25;
-            warnings.push(format!(
-                "Network infrastructure '{}' change may cause connectivity issues",
-                change.address
-            ));
+        score += change_score;
+        if change_score > 0 {
+            change_risks.push(ChangeRisk {
+                address: change.address.clone(),
+                action: change.action.clone(),
+                score: change_score,
+                matched_rule: rule.map(RiskRule::describe),
+            });
         }
     }
 
+    change_risks.sort_by(|a, b| b.score.cmp(&a.score).then(a.address.cmp(&b.address)));
+    change_risks.truncate(TOP_RISKS_LIMIT);
+
     // Generate recommendations
     if summary.destroy > 0 {
         recommendations.push("Review all resources marked for destruction carefully".to_string());
@@ -335,6 +744,13 @@ fn assess_risk(changes: &[ResourceChange], summary: &ChangeSummary) -> RiskAsses
             .push("Resources being replaced may have brief downtime or data loss".to_string());
     }
 
+    if !critical_changes.is_empty() {
+        recommendations.push(
+            "Confirm each change listed in critical_changes is intended before applying"
+                .to_string(),
+        );
+    }
+
     if score > 50 {
         recommendations.push("Consider applying changes during a maintenance window".to_string());
         recommendations.push("Have a rollback plan ready".to_string());
@@ -352,7 +768,79 @@ fn assess_risk(changes: &[ResourceChange], summary: &ChangeSummary) -> RiskAsses
         score,
         warnings,
         recommendations,
+        top_risks: change_risks,
+        critical_changes,
+    }
+}
+
+/// Built-in score for a single change, used when no policy rule matches
+fn builtin_change_score(change: &ResourceChange, warnings: &mut Vec<String>) -> i32 {
+    // Base score from the action
+    let mut score = match change.action.as_str() {
+        "delete" => 30,
+        "replace" | "create_delete" | "delete_create" => 20,
+        "update" => 5,
+        "create" => 2,
+        _ => 0,
+    };
+
+    // Check for high-risk resources
+    let is_high_risk = HIGH_RISK_RESOURCES
+        .iter()
+        .any(|&r| change.resource_type == r);
+
+    if is_high_risk {
+        match change.action.as_str() {
+            "delete" => {
+                score += 50;
+                warnings.push(format!(
+                    "CRITICAL: High-risk resource '{}' will be DESTROYED",
+                    change.address
+                ));
+            }
+            "replace" | "create_delete" | "delete_create" => {
+                score += 40;
+                warnings.push(format!(
+                    "WARNING: High-risk resource '{}' will be REPLACED (data loss possible)",
+                    change.address
+                ));
+            }
+            "update" => {
+                score += 15;
+                warnings.push(format!(
+                    "CAUTION: High-risk resource '{}' will be modified",
+                    change.address
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    // Check for IAM/security changes
+    let is_security_resource = change.resource_type.contains("iam")
+        || change.resource_type.contains("security")
+        || change.resource_type.contains("firewall");
+    if is_security_resource && change.action != "no-op" && change.action != "read" {
+        score += 10;
+        warnings.push(format!(
+            "Security-related resource '{}' will be modified",
+            change.address
+        ));
     }
+
+    // Check for network changes
+    let is_network_resource = change.resource_type.contains("vpc")
+        || change.resource_type.contains("network")
+        || change.resource_type.contains("subnet");
+    if is_network_resource && (change.action == "delete" || change.action.contains("replace")) {
+        score += 25;
+        warnings.push(format!(
+            "Network infrastructure '{}' change may cause connectivity issues",
+            change.address
+        ));
+    }
+
+    score
 }
 
 /// Analyze dependencies between resources
@@ -473,11 +961,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_summarize_destroy_plan_from_streamed_json() {
+        // Captured from `terraform plan -destroy -json` (Terraform 1.11)
+        let ndjson = r#"{"@level":"info","@message":"Terraform 1.11.4","terraform":"1.11.4","type":"version","ui":"1.2"}
+{"@level":"info","@message":"terraform_data.a: Plan to delete","change":{"resource":{"addr":"terraform_data.a","module":"","resource":"terraform_data.a","implied_provider":"terraform","resource_type":"terraform_data","resource_name":"a","resource_key":null},"action":"delete"},"type":"planned_change"}
+{"@level":"info","@message":"module.db.aws_db_instance.main: Plan to delete","change":{"resource":{"addr":"module.db.aws_db_instance.main","module":"module.db","resource":"aws_db_instance.main","implied_provider":"aws","resource_type":"aws_db_instance","resource_name":"main","resource_key":null},"action":"delete"},"type":"planned_change"}
+{"@level":"info","@message":"Plan: 0 to add, 0 to change, 2 to destroy.","changes":{"add":0,"change":0,"import":0,"remove":2,"operation":"plan"},"type":"change_summary"}"#;
+
+        let preview = summarize_destroy_plan(ndjson).unwrap();
+        assert!(preview.preview);
+        assert_eq!(preview.summary.destroy, 2);
+        assert_eq!(preview.resources.len(), 2);
+        assert_eq!(preview.resources[0].address, "terraform_data.a");
+        assert_eq!(preview.resources[1].resource_type, "aws_db_instance");
+        assert_eq!(preview.resources[1].provider, "aws");
+        assert!(preview.message.contains("Nothing has been destroyed"));
+    }
+
+    fn noisy_plan() -> String {
+        serde_json::json!({
+            "format_version": "1.2",
+            "resource_changes": [
+                {
+                    "address": "aws_s3_bucket.logs",
+                    "type": "aws_s3_bucket",
+                    "provider_name": "registry.terraform.io/hashicorp/aws",
+                    "change": {
+                        "actions": ["update"],
+                        "before": {"bucket": "logs", "tags": {"Env": "dev"}, "tags_all": {"Env": "dev"}},
+                        "after": {"bucket": "logs", "tags": {"Env": "prod"}, "tags_all": {"Env": "prod"}},
+                        "after_unknown": {"tags": {}, "tags_all": {}}
+                    }
+                },
+                {
+                    "address": "aws_instance.web",
+                    "type": "aws_instance",
+                    "provider_name": "registry.terraform.io/hashicorp/aws",
+                    "change": {
+                        "actions": ["update"],
+                        "before": {"instance_type": "t3.micro", "tags": {"Env": "dev"}},
+                        "after": {"instance_type": "t3.small", "tags": {"Env": "prod"}},
+                        "after_unknown": {}
+                    }
+                },
+                {
+                    "address": "aws_vpc.main",
+                    "type": "aws_vpc",
+                    "provider_name": "registry.terraform.io/hashicorp/aws",
+                    "change": {
+                        "actions": ["no-op"],
+                        "before": {"cidr_block": "10.0.0.0/16"},
+                        "after": {"cidr_block": "10.0.0.0/16"},
+                        "after_unknown": {}
+                    }
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_noise_filter_drops_no_op_and_tag_only_changes() {
+        let mut analysis = analyze_plan(&noisy_plan(), false, None).unwrap();
+        analysis.filter_noise(NoiseFilter {
+            hide_no_op: true,
+            collapse_tag_only: true,
+        });
+
+        let addresses: Vec<_> = analysis
+            .resource_changes
+            .iter()
+            .map(|c| c.address.as_str())
+            .collect();
+        assert_eq!(addresses, vec!["aws_instance.web"]);
+        let filtered = analysis.filtered.unwrap();
+        assert_eq!(filtered.no_op, 1);
+        assert_eq!(filtered.tag_only, 1);
+        assert_eq!(filtered.tag_only_addresses, vec!["aws_s3_bucket.logs"]);
+        // The summary still counts the whole plan
+        assert_eq!(analysis.summary.change, 2);
+        assert_eq!(analysis.summary.no_op, 1);
+    }
+
+    #[test]
+    fn test_noise_filter_options() {
+        let mut analysis = analyze_plan(&noisy_plan(), false, None).unwrap();
+        analysis.filter_noise(NoiseFilter::default());
+        assert_eq!(analysis.resource_changes.len(), 3);
+        assert!(analysis.filtered.is_none());
+
+        analysis.filter_noise(NoiseFilter {
+            hide_no_op: true,
+            collapse_tag_only: false,
+        });
+        assert_eq!(analysis.resource_changes.len(), 2);
+        let filtered = analysis.filtered.unwrap();
+        assert_eq!((filtered.no_op, filtered.tag_only), (1, 0));
+    }
+
     #[test]
     fn test_risk_assessment_empty() {
         let changes = vec![];
         let summary = ChangeSummary::default();
-        let risk = assess_risk(&changes, &summary);
+        let risk = assess_risk(&changes, &summary, None);
         assert_eq!(risk.level, RiskLevel::Low);
         assert_eq!(risk.score, 0);
     }
@@ -492,12 +1079,14 @@ mod tests {
             before: None,
             after: None,
             after_unknown: None,
+            before_sensitive: None,
+            after_sensitive: None,
         }];
         let summary = ChangeSummary {
             destroy: 1,
             ..Default::default()
         };
-        let risk = assess_risk(&changes, &summary);
+        let risk = assess_risk(&changes, &summary, None);
         assert!(risk.score > 0);
     }
 
@@ -511,13 +1100,186 @@ mod tests {
             before: None,
             after: None,
             after_unknown: None,
+            before_sensitive: None,
+            after_sensitive: None,
         }];
         let summary = ChangeSummary {
             destroy: 1,
             ..Default::default()
         };
-        let risk = assess_risk(&changes, &summary);
+        let risk = assess_risk(&changes, &summary, None);
         assert_eq!(risk.level, RiskLevel::Critical);
         assert!(risk.warnings.iter().any(|w| w.contains("CRITICAL")));
     }
+
+    const MIXED_PLAN: &str = r#"{
+        "format_version": "1.2",
+        "resource_changes": [
+            {"address": "aws_iam_role.app", "type": "aws_iam_role",
+             "change": {"actions": ["update"]}},
+            {"address": "azurerm_mssql_database.main", "type": "azurerm_mssql_database",
+             "change": {"actions": ["delete"]}},
+            {"address": "aws_instance.web", "type": "aws_instance",
+             "change": {"actions": ["create"]}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_risk_policy_changes_score() {
+        let default = analyze_plan(MIXED_PLAN, true, None).unwrap();
+        // update iam_role: 5 + 15 + 10, delete database: 30, create instance: 2
+        assert_eq!(default.risk_assessment.score, 62);
+        let top: Vec<(&str, i32)> = default
+            .risk_assessment
+            .top_risks
+            .iter()
+            .map(|r| (r.address.as_str(), r.score))
+            .collect();
+        assert_eq!(
+            top,
+            vec![
+                ("aws_iam_role.app", 30),
+                ("azurerm_mssql_database.main", 30),
+                ("aws_instance.web", 2)
+            ]
+        );
+        assert!(default.risk_assessment.top_risks[0].matched_rule.is_none());
+
+        let policy: RiskPolicy = serde_json::from_str(
+            r#"{"rules": [
+                {"resource_type": "*_database", "action": "delete", "weight": "critical"},
+                {"resource_type": "aws_iam_*", "action": "update", "weight": "high"},
+                {"resource_type": "aws_instance", "weight": 0}
+            ]}"#,
+        )
+        .unwrap();
+        let custom = analyze_plan(MIXED_PLAN, true, Some(&policy)).unwrap();
+        let risk = &custom.risk_assessment;
+
+        assert_eq!(risk.score, 80 + 30);
+        assert_eq!(risk.level, RiskLevel::Critical);
+        assert_eq!(risk.top_risks.len(), 2);
+        assert_eq!(risk.top_risks[0].address, "azurerm_mssql_database.main");
+        assert_eq!(risk.top_risks[0].score, 80);
+        assert_eq!(
+            risk.top_risks[0].matched_rule.as_deref(),
+            Some("*_database -> delete")
+        );
+        assert_eq!(risk.top_risks[1].address, "aws_iam_role.app");
+    }
+
+    #[test]
+    fn test_risk_policy_unmatched_changes_use_builtin_weights() {
+        let policy = RiskPolicy {
+            rules: vec![RiskRule {
+                resource_type: "google_*".to_string(),
+                action: "*".to_string(),
+                weight: RiskWeight::Points(100),
+            }],
+            critical_resource_types: None,
+        };
+        let default = analyze_plan(MIXED_PLAN, true, None).unwrap();
+        let custom = analyze_plan(MIXED_PLAN, true, Some(&policy)).unwrap();
+        assert_eq!(custom.risk_assessment.score, default.risk_assessment.score);
+    }
+
+    const LEDGER_PLAN: &str = r#"{
+        "format_version": "1.2",
+        "resource_changes": [
+            {"address": "acme_ledger.main", "type": "acme_ledger",
+             "change": {"actions": ["delete"]}},
+            {"address": "acme_widget.main", "type": "acme_widget",
+             "change": {"actions": ["delete"]}},
+            {"address": "acme_ledger_replica.eu", "type": "acme_ledger_replica",
+             "change": {"actions": ["update"]}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_critical_resource_type_delete_scores_higher() {
+        let policy: RiskPolicy =
+            serde_json::from_str(r#"{"critical_resource_types": ["acme_ledger*"]}"#).unwrap();
+        let analysis = analyze_plan(LEDGER_PLAN, true, Some(&policy)).unwrap();
+        let risk = &analysis.risk_assessment;
+
+        let score = |address: &str| {
+            risk.top_risks
+                .iter()
+                .find(|r| r.address == address)
+                .map(|r| r.score)
+                .unwrap()
+        };
+        assert_eq!(score("acme_widget.main"), 30);
+        assert_eq!(score("acme_ledger.main"), 30 + CRITICAL_CHANGE_POINTS);
+        assert_eq!(risk.top_risks[0].address, "acme_ledger.main");
+
+        // Updates are not destructive, so the replica is not listed
+        assert_eq!(risk.critical_changes.len(), 1);
+        let critical = &risk.critical_changes[0];
+        assert_eq!(critical.address, "acme_ledger.main");
+        assert_eq!(critical.action, "delete");
+        assert_eq!(critical.pattern, "acme_ledger*");
+        assert!(risk.warnings.iter().any(|w| w.contains("acme_ledger*")));
+
+        // Not critical under the defaults
+        let default = analyze_plan(LEDGER_PLAN, true, None).unwrap();
+        assert!(default.risk_assessment.critical_changes.is_empty());
+        assert_eq!(
+            risk.score - default.risk_assessment.score,
+            CRITICAL_CHANGE_POINTS
+        );
+    }
+
+    #[test]
+    fn test_default_critical_resource_types() {
+        let plan = r#"{
+            "format_version": "1.2",
+            "resource_changes": [
+                {"address": "aws_route53_zone.public", "type": "aws_route53_zone",
+                 "change": {"actions": ["delete", "create"]}},
+                {"address": "aws_iam_role.app", "type": "aws_iam_role",
+                 "change": {"actions": ["update"]}}
+            ]
+        }"#;
+        let analysis = analyze_plan(plan, true, None).unwrap();
+        let critical: Vec<(&str, &str)> = analysis
+            .risk_assessment
+            .critical_changes
+            .iter()
+            .map(|c| (c.address.as_str(), c.pattern.as_str()))
+            .collect();
+        assert_eq!(
+            critical,
+            vec![("aws_route53_zone.public", "aws_route53_zone")]
+        );
+
+        // An empty list turns the bump off
+        let policy = RiskPolicy {
+            rules: vec![],
+            critical_resource_types: Some(vec![]),
+        };
+        let opted_out = analyze_plan(plan, true, Some(&policy)).unwrap();
+        assert!(opted_out.risk_assessment.critical_changes.is_empty());
+        assert_eq!(
+            analysis.risk_assessment.score - opted_out.risk_assessment.score,
+            CRITICAL_CHANGE_POINTS
+        );
+    }
+
+    #[test]
+    fn test_risk_policy_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("risk-policy.json");
+        std::fs::write(
+            &path,
+            r#"{"rules": [{"resource_type": "aws_s3_*", "action": "replace", "weight": 45}]}"#,
+        )
+        .unwrap();
+
+        let policy = RiskPolicy::load(&path).unwrap();
+        assert_eq!(policy.rules.len(), 1);
+        assert_eq!(policy.rules[0].weight, RiskWeight::Points(45));
+
+        assert!(RiskPolicy::load(&dir.path().join("missing.json")).is_err());
+    }
 }
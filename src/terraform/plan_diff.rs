@@ -0,0 +1,284 @@
+//! Plan rendering as a reviewable `+`/`-`/`~` diff.
+//!
+//! Resources are grouped by action; under each one the changed attributes
+//! are listed by dotted path with their old and new values. Values marked
+//! sensitive in the plan are shown as `(sensitive)` and long values are
+//! truncated.
+
+use crate::terraform::plan_analyzer::{PlanAnalysis, ResourceChange};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Rendered values longer than this many characters are truncated
+pub const MAX_DIFF_VALUE_LEN: usize = 80;
+
+const SENSITIVE: &str = "(sensitive)";
+const KNOWN_AFTER_APPLY: &str = "(known after apply)";
+
+/// Action groups in the order they are rendered, with their diff marker
+const ACTION_GROUPS: &[(&str, &str)] = &[
+    ("create", "+"),
+    ("update", "~"),
+    ("replace", "-/+"),
+    ("delete", "-"),
+];
+
+/// Render `plan` as a diff, one section per action
+pub fn render_plan_diff(plan: &PlanAnalysis) -> String {
+    let summary = &plan.summary;
+    let mut out = format!(
+        "# {} to add, {} to change, {} to replace, {} to destroy\n",
+        summary.add, summary.change, summary.replace, summary.destroy
+    );
+
+    for (action, marker) in ACTION_GROUPS {
+        let changes: Vec<&ResourceChange> = plan
+            .resource_changes
+            .iter()
+            .filter(|change| group_of(&change.action) == Some(*action))
+            .collect();
+        if changes.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("\n## {} ({})\n", action, changes.len()));
+        for change in changes {
+            out.push_str(&format!("{} {}\n", marker, change.address));
+            if *action != "delete" {
+                for line in attribute_lines(change) {
+                    out.push_str(&format!("    {}\n", line));
+                }
+            }
+        }
+    }
+
+    if summary.add + summary.change + summary.replace + summary.destroy == 0 {
+        out.push_str("\nNo changes.\n");
+    }
+    out
+}
+
+fn group_of(action: &str) -> Option<&'static str> {
+    match action {
+        "create" => Some("create"),
+        "update" => Some("update"),
+        "replace" | "create_delete" | "delete_create" => Some("replace"),
+        "delete" => Some("delete"),
+        _ => None,
+    }
+}
+
+/// Changed attributes of one resource, sorted by path
+fn attribute_lines(change: &ResourceChange) -> Vec<String> {
+    let before = flatten(change.before.as_ref());
+    let after = flatten(change.after.as_ref());
+    let unknown = flatten(change.after_unknown.as_ref());
+    let sensitive_paths: Vec<String> = [&change.before_sensitive, &change.after_sensitive]
+        .into_iter()
+        .flat_map(|marks| flatten(marks.as_ref()))
+        .filter(|(_, value)| *value == Value::Bool(true))
+        .map(|(path, _)| path)
+        .collect();
+    let is_sensitive = |path: &str| {
+        sensitive_paths
+            .iter()
+            .any(|marked| path == marked || is_child_path(path, marked))
+    };
+    let is_unknown = |path: &str| unknown.get(path) == Some(&Value::Bool(true));
+
+    let mut paths: Vec<&String> = before.keys().chain(after.keys()).collect();
+    paths.extend(unknown.keys().filter(|path| is_unknown(path)));
+    paths.sort();
+    paths.dedup();
+
+    let render = |path: &str, value: &Value| {
+        if is_sensitive(path) {
+            SENSITIVE.to_string()
+        } else {
+            render_value(value)
+        }
+    };
+
+    let mut lines = Vec::new();
+    for path in paths {
+        let old = before.get(path).filter(|v| !v.is_null());
+        let new = after.get(path).filter(|v| !v.is_null());
+        if is_unknown(path) {
+            match old {
+                Some(old) => lines.push(format!(
+                    "~ {}: {} → {}",
+                    path,
+                    render(path, old),
+                    KNOWN_AFTER_APPLY
+                )),
+                None => lines.push(format!("+ {}: {}", path, KNOWN_AFTER_APPLY)),
+            }
+            continue;
+        }
+        match (old, new) {
+            (Some(old), Some(new)) if old != new => lines.push(format!(
+                "~ {}: {} → {}",
+                path,
+                render(path, old),
+                render(path, new)
+            )),
+            (None, Some(new)) => lines.push(format!("+ {}: {}", path, render(path, new))),
+            (Some(old), None) => lines.push(format!("- {}: {}", path, render(path, old))),
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// `path` is nested under `parent`, e.g. `tags.env` under `tags` or `rule[0]` under `rule`
+fn is_child_path(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+}
+
+/// Leaf values keyed by dotted path (`tags.env`, `rule[0].port`). Empty
+/// objects and arrays are kept as leaves so their removal shows up.
+fn flatten(value: Option<&Value>) -> BTreeMap<String, Value> {
+    let mut leaves = BTreeMap::new();
+    if let Some(value) = value {
+        flatten_into(value, String::new(), &mut leaves);
+    }
+    leaves
+}
+
+fn flatten_into(value: &Value, path: String, leaves: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                flatten_into(child, child_path, leaves);
+            }
+        }
+        Value::Array(items) if !items.is_empty() && !path.is_empty() => {
+            for (index, child) in items.iter().enumerate() {
+                flatten_into(child, format!("{}[{}]", path, index), leaves);
+            }
+        }
+        _ if !path.is_empty() => {
+            leaves.insert(path, value.clone());
+        }
+        _ => {}
+    }
+}
+
+/// Compact JSON for `value`, truncated to `MAX_DIFF_VALUE_LEN` characters
+fn render_value(value: &Value) -> String {
+    let rendered = value.to_string();
+    let length = rendered.chars().count();
+    if length <= MAX_DIFF_VALUE_LEN {
+        return rendered;
+    }
+    let truncated: String = rendered.chars().take(MAX_DIFF_VALUE_LEN).collect();
+    format!("{}… ({} chars)", truncated, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terraform::plan_analyzer::analyze_plan;
+
+    const SAMPLE_PLAN: &str = r#"{
+        "format_version": "1.2",
+        "resource_changes": [
+            {
+                "address": "aws_db_instance.main",
+                "type": "aws_db_instance",
+                "change": {
+                    "actions": ["update"],
+                    "before": {"instance_class": "db.t3.micro", "password": "old-secret", "port": 5432, "tags": {"env": "dev"}},
+                    "after": {"instance_class": "db.t3.small", "password": "new-secret", "port": 5432, "tags": {"env": "dev"}},
+                    "after_unknown": {},
+                    "before_sensitive": {"password": true},
+                    "after_sensitive": {"password": true}
+                }
+            },
+            {
+                "address": "aws_s3_bucket.logs",
+                "type": "aws_s3_bucket",
+                "change": {
+                    "actions": ["create"],
+                    "before": null,
+                    "after": {"bucket": "logs", "tags": {"team": "infra"}},
+                    "after_unknown": {"arn": true}
+                }
+            },
+            {
+                "address": "aws_instance.old",
+                "type": "aws_instance",
+                "change": {"actions": ["delete"], "before": {"ami": "ami-1"}, "after": null}
+            },
+            {
+                "address": "aws_vpc.main",
+                "type": "aws_vpc",
+                "change": {"actions": ["no-op"], "before": {"cidr_block": "10.0.0.0/16"}, "after": {"cidr_block": "10.0.0.0/16"}}
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_update_diff_masks_sensitive_attribute() {
+        let plan = analyze_plan(SAMPLE_PLAN, false, None).unwrap();
+        let diff = render_plan_diff(&plan);
+
+        assert!(diff.starts_with("# 1 to add, 1 to change, 0 to replace, 1 to destroy\n"));
+        assert!(diff.contains(
+            "## update (1)\n~ aws_db_instance.main\n    ~ instance_class: \"db.t3.micro\" → \"db.t3.small\"\n"
+        ));
+        assert!(diff.contains("    ~ password: (sensitive) → (sensitive)\n"));
+        assert!(!diff.contains("secret"));
+        // Unchanged attributes and no-op resources are left out
+        assert!(!diff.contains("port"));
+        assert!(!diff.contains("aws_vpc.main"));
+    }
+
+    #[test]
+    fn test_diff_groups_by_action() {
+        let plan = analyze_plan(SAMPLE_PLAN, false, None).unwrap();
+        let diff = render_plan_diff(&plan);
+
+        let create = diff.find("## create (1)").unwrap();
+        let update = diff.find("## update (1)").unwrap();
+        let delete = diff.find("## delete (1)").unwrap();
+        assert!(create < update && update < delete);
+        assert!(diff.contains(
+            "+ aws_s3_bucket.logs\n    + arn: (known after apply)\n    + bucket: \"logs\"\n    + tags.team: \"infra\"\n"
+        ));
+        assert!(diff.ends_with("- aws_instance.old\n"));
+    }
+
+    #[test]
+    fn test_long_values_are_truncated() {
+        let long = Value::String("x".repeat(200));
+        let rendered = render_value(&long);
+        assert!(rendered.starts_with(&format!("\"{}", "x".repeat(MAX_DIFF_VALUE_LEN - 1))));
+        assert!(rendered.ends_with("… (202 chars)"));
+    }
+
+    #[test]
+    fn test_nested_sensitive_marks_cover_children() {
+        let change = ResourceChange {
+            address: "aws_instance.web".to_string(),
+            resource_type: "aws_instance".to_string(),
+            provider: "aws".to_string(),
+            action: "update".to_string(),
+            before: Some(serde_json::json!({"user_data": {"token": "a"}})),
+            after: Some(serde_json::json!({"user_data": {"token": "b"}})),
+            after_unknown: None,
+            before_sensitive: Some(serde_json::json!({"user_data": true})),
+            after_sensitive: None,
+        };
+        assert_eq!(
+            attribute_lines(&change),
+            vec!["~ user_data.token: (sensitive) → (sensitive)"]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! In-memory plan snapshots for comparing plans taken at different times.
+//!
+//! A snapshot keeps only the per-resource actions and summary of a plan, so
+//! a later plan can report which resource actions appeared or disappeared
+//! while the configuration was being edited.
+
+use crate::terraform::plan_analyzer::{ChangeSummary, PlanAnalysis};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Snapshots kept per session; the oldest is evicted beyond this
+pub const MAX_PLAN_SNAPSHOTS: usize = 50;
+
+/// Summary of a plan stored under an id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanSnapshot {
+    pub id: String,
+    pub taken_at: DateTime<Utc>,
+    pub project_directory: String,
+    pub summary: ChangeSummary,
+    /// Resource address -> planned action, excluding no-ops
+    pub actions: BTreeMap<String, String>,
+}
+
+impl PlanSnapshot {
+    pub fn from_analysis(id: String, project_directory: String, analysis: &PlanAnalysis) -> Self {
+        let actions = analysis
+            .resource_changes
+            .iter()
+            .filter(|change| change.action != "no-op")
+            .map(|change| (change.address.clone(), change.action.clone()))
+            .collect();
+        Self {
+            id,
+            taken_at: Utc::now(),
+            project_directory,
+            summary: analysis.summary.clone(),
+            actions,
+        }
+    }
+}
+
+/// A planned action on one resource
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResourceAction {
+    pub address: String,
+    pub action: String,
+}
+
+/// Difference between a stored snapshot and a newer plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDiff {
+    pub snapshot_id: String,
+    pub snapshot_taken_at: DateTime<Utc>,
+    pub before: ChangeSummary,
+    pub after: ChangeSummary,
+    /// Actions in the new plan that the snapshot did not have
+    pub appeared: Vec<ResourceAction>,
+    /// Actions in the snapshot that the new plan no longer has
+    pub disappeared: Vec<ResourceAction>,
+    /// Resources planned with the same action in both
+    pub unchanged: usize,
+}
+
+/// Compare two snapshots, sorted by resource address
+pub fn diff_snapshots(before: &PlanSnapshot, after: &PlanSnapshot) -> PlanDiff {
+    let mut appeared = Vec::new();
+    let mut disappeared = Vec::new();
+    let mut unchanged = 0;
+
+    for (address, action) in &after.actions {
+        match before.actions.get(address) {
+            Some(previous) if previous == action => unchanged += 1,
+            _ => appeared.push(ResourceAction {
+                address: address.clone(),
+                action: action.clone(),
+            }),
+        }
+    }
+    for (address, action) in &before.actions {
+        if after.actions.get(address) != Some(action) {
+            disappeared.push(ResourceAction {
+                address: address.clone(),
+                action: action.clone(),
+            });
+        }
+    }
+
+    PlanDiff {
+        snapshot_id: before.id.clone(),
+        snapshot_taken_at: before.taken_at,
+        before: before.summary.clone(),
+        after: after.summary.clone(),
+        appeared,
+        disappeared,
+        unchanged,
+    }
+}
+
+/// Session-wide store of plan snapshots keyed by id
+#[derive(Default)]
+pub struct PlanSnapshotStore {
+    snapshots: Mutex<HashMap<String, PlanSnapshot>>,
+}
+
+impl PlanSnapshotStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a snapshot, replacing any with the same id and evicting the oldest when full
+    pub fn insert(&self, snapshot: PlanSnapshot) {
+        let mut snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        if !snapshots.contains_key(&snapshot.id) && snapshots.len() >= MAX_PLAN_SNAPSHOTS {
+            if let Some(oldest) = snapshots
+                .values()
+                .min_by_key(|s| s.taken_at)
+                .map(|s| s.id.clone())
+            {
+                snapshots.remove(&oldest);
+            }
+        }
+        snapshots.insert(snapshot.id.clone(), snapshot);
+    }
+
+    pub fn get(&self, id: &str) -> Option<PlanSnapshot> {
+        self.snapshots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(id: &str, actions: &[(&str, &str)]) -> PlanSnapshot {
+        PlanSnapshot {
+            id: id.to_string(),
+            taken_at: Utc::now(),
+            project_directory: "/project".to_string(),
+            summary: ChangeSummary::default(),
+            actions: actions
+                .iter()
+                .map(|(address, action)| (address.to_string(), action.to_string()))
+                .collect(),
+        }
+    }
+
+    fn action(address: &str, action: &str) -> ResourceAction {
+        ResourceAction {
+            address: address.to_string(),
+            action: action.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_snapshots_reports_appeared_and_disappeared_actions() {
+        let before = snapshot(
+            "baseline",
+            &[
+                ("aws_instance.web", "create"),
+                ("aws_s3_bucket.logs", "update"),
+                ("aws_vpc.main", "create"),
+            ],
+        );
+        let after = snapshot(
+            "current",
+            &[
+                ("aws_instance.web", "create"),
+                ("aws_s3_bucket.logs", "replace"),
+                ("aws_subnet.private", "create"),
+            ],
+        );
+
+        let diff = diff_snapshots(&before, &after);
+
+        assert_eq!(diff.snapshot_id, "baseline");
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(
+            diff.appeared,
+            vec![
+                action("aws_s3_bucket.logs", "replace"),
+                action("aws_subnet.private", "create"),
+            ]
+        );
+        assert_eq!(
+            diff.disappeared,
+            vec![
+                action("aws_s3_bucket.logs", "update"),
+                action("aws_vpc.main", "create"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_store_replaces_snapshot_with_same_id() {
+        let store = PlanSnapshotStore::new();
+        store.insert(snapshot("baseline", &[("aws_vpc.main", "create")]));
+        store.insert(snapshot("baseline", &[("aws_vpc.main", "delete")]));
+
+        let stored = store.get("baseline").unwrap();
+        assert_eq!(stored.actions["aws_vpc.main"], "delete");
+        assert!(store.get("missing").is_none());
+    }
+}
@@ -0,0 +1,262 @@
+//! Pre-apply review report combining the plan summary, risk assessment and,
+//! when Infracost is installed, the monthly cost delta.
+
+use crate::terraform::plan_analyzer::{ChangeSummary, PlanAnalysis, RiskAssessment, RiskLevel};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Deletes plus replacements at which the report recommends blocking
+pub const BLOCK_DESTRUCTIVE_CHANGES: i32 = 5;
+
+/// Overall verdict of a pre-apply report
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Recommendation {
+    Proceed,
+    Caution,
+    Block,
+}
+
+/// Monthly cost of the plan as estimated by `infracost breakdown`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CostEstimate {
+    pub currency: String,
+    pub past_monthly_cost: Option<f64>,
+    pub monthly_cost: Option<f64>,
+    pub monthly_cost_delta: Option<f64>,
+}
+
+/// Everything a reviewer needs before approving an apply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreApplyReport {
+    pub recommendation: Recommendation,
+    /// Why the recommendation was made, most severe first
+    pub reasons: Vec<String>,
+    pub summary: ChangeSummary,
+    /// Deleted plus replaced resources
+    pub destructive_changes: i32,
+    pub risk: RiskAssessment,
+    /// Omitted when Infracost is not installed or could not price the plan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<CostEstimate>,
+}
+
+impl PreApplyReport {
+    pub fn new(plan: PlanAnalysis, cost: Option<CostEstimate>) -> Self {
+        let (recommendation, reasons) = recommend(&plan.risk_assessment, &plan.summary);
+        Self {
+            recommendation,
+            reasons,
+            destructive_changes: plan.summary.destroy + plan.summary.replace,
+            summary: plan.summary,
+            risk: plan.risk_assessment,
+            cost,
+        }
+    }
+}
+
+/// Derive the recommendation from the risk level and destructive-change count:
+/// critical risk or [`BLOCK_DESTRUCTIVE_CHANGES`] or more blocks; medium or
+/// high risk, or any delete or replacement, calls for caution.
+pub fn recommend(risk: &RiskAssessment, summary: &ChangeSummary) -> (Recommendation, Vec<String>) {
+    let destructive = summary.destroy + summary.replace;
+    let mut blockers = Vec::new();
+    let mut cautions = Vec::new();
+
+    match risk.level {
+        RiskLevel::Critical => {
+            blockers.push(format!("Risk level is critical (score {})", risk.score))
+        }
+        RiskLevel::High | RiskLevel::Medium => cautions.push(format!(
+            "Risk level is {} (score {})",
+            risk_level_name(&risk.level),
+            risk.score
+        )),
+        RiskLevel::Low => {}
+    }
+
+    if destructive >= BLOCK_DESTRUCTIVE_CHANGES {
+        blockers.push(format!(
+            "{} resources would be deleted or replaced",
+            destructive
+        ));
+    } else if destructive > 0 {
+        cautions.push(format!(
+            "{} resource(s) would be deleted or replaced",
+            destructive
+        ));
+    }
+
+    let recommendation = if !blockers.is_empty() {
+        Recommendation::Block
+    } else if !cautions.is_empty() {
+        Recommendation::Caution
+    } else {
+        Recommendation::Proceed
+    };
+    let mut reasons = blockers;
+    reasons.extend(cautions);
+    if reasons.is_empty() {
+        reasons.push("Low risk and no destructive changes".to_string());
+    }
+    (recommendation, reasons)
+}
+
+fn risk_level_name(level: &RiskLevel) -> &'static str {
+    match level {
+        RiskLevel::Low => "low",
+        RiskLevel::Medium => "medium",
+        RiskLevel::High => "high",
+        RiskLevel::Critical => "critical",
+    }
+}
+
+/// Locate the `infracost` binary on PATH
+pub fn find_infracost() -> Option<PathBuf> {
+    which::which("infracost").ok()
+}
+
+/// Price a `terraform show -json` plan document with `infracost breakdown`
+pub fn estimate_cost(infracost_path: &Path, plan_json: &str) -> anyhow::Result<CostEstimate> {
+    let mut plan_file = tempfile::Builder::new()
+        .prefix("tfmcp-plan-")
+        .suffix(".json")
+        .tempfile()?;
+    plan_file.write_all(plan_json.as_bytes())?;
+
+    let output = Command::new(infracost_path)
+        .arg("breakdown")
+        .arg("--path")
+        .arg(plan_file.path())
+        .arg("--format")
+        .arg("json")
+        .arg("--no-color")
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "infracost failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    parse_infracost_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Totals of `infracost breakdown --format json`; amounts are decimal strings
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InfracostBreakdown {
+    #[serde(default)]
+    currency: Option<String>,
+    past_total_monthly_cost: Option<String>,
+    total_monthly_cost: Option<String>,
+    diff_total_monthly_cost: Option<String>,
+}
+
+/// Parse the totals of `infracost breakdown --format json`
+pub fn parse_infracost_output(json: &str) -> anyhow::Result<CostEstimate> {
+    let breakdown: InfracostBreakdown = serde_json::from_str(json)
+        .map_err(|e| anyhow::anyhow!("Failed to parse infracost output: {}", e))?;
+    let amount = |value: Option<String>| value.and_then(|v| v.parse::<f64>().ok());
+    Ok(CostEstimate {
+        currency: breakdown.currency.unwrap_or_else(|| "USD".to_string()),
+        past_monthly_cost: amount(breakdown.past_total_monthly_cost),
+        monthly_cost: amount(breakdown.total_monthly_cost),
+        monthly_cost_delta: amount(breakdown.diff_total_monthly_cost),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn risk(level: RiskLevel, score: i32) -> RiskAssessment {
+        RiskAssessment {
+            level,
+            score,
+            warnings: vec![],
+            recommendations: vec![],
+            top_risks: vec![],
+            critical_changes: vec![],
+        }
+    }
+
+    fn summary(add: i32, destroy: i32, replace: i32) -> ChangeSummary {
+        ChangeSummary {
+            add,
+            destroy,
+            replace,
+            ..ChangeSummary::default()
+        }
+    }
+
+    #[test]
+    fn test_low_risk_without_deletes_proceeds() {
+        let (recommendation, reasons) = recommend(&risk(RiskLevel::Low, 4), &summary(3, 0, 0));
+        assert_eq!(recommendation, Recommendation::Proceed);
+        assert_eq!(reasons, vec!["Low risk and no destructive changes"]);
+    }
+
+    #[test]
+    fn test_medium_risk_or_any_delete_needs_caution() {
+        let (recommendation, _) = recommend(&risk(RiskLevel::Medium, 15), &summary(1, 0, 0));
+        assert_eq!(recommendation, Recommendation::Caution);
+
+        let (recommendation, reasons) = recommend(&risk(RiskLevel::Low, 5), &summary(0, 1, 0));
+        assert_eq!(recommendation, Recommendation::Caution);
+        assert_eq!(reasons, vec!["1 resource(s) would be deleted or replaced"]);
+
+        // One short of the block threshold, counting replacements
+        let (recommendation, _) = recommend(
+            &risk(RiskLevel::High, 50),
+            &summary(0, 2, BLOCK_DESTRUCTIVE_CHANGES - 3),
+        );
+        assert_eq!(recommendation, Recommendation::Caution);
+    }
+
+    #[test]
+    fn test_critical_risk_or_mass_deletes_block() {
+        let (recommendation, reasons) =
+            recommend(&risk(RiskLevel::Critical, 90), &summary(0, 1, 0));
+        assert_eq!(recommendation, Recommendation::Block);
+        assert_eq!(reasons[0], "Risk level is critical (score 90)");
+        assert_eq!(reasons[1], "1 resource(s) would be deleted or replaced");
+
+        let (recommendation, reasons) = recommend(
+            &risk(RiskLevel::Low, 0),
+            &summary(0, 3, BLOCK_DESTRUCTIVE_CHANGES - 3),
+        );
+        assert_eq!(recommendation, Recommendation::Block);
+        assert_eq!(reasons, vec!["5 resources would be deleted or replaced"]);
+    }
+
+    #[test]
+    fn test_parse_infracost_output() {
+        let cost = parse_infracost_output(
+            r#"{
+                "version": "0.2",
+                "currency": "EUR",
+                "projects": [],
+                "pastTotalMonthlyCost": "100.5",
+                "totalMonthlyCost": "142.25",
+                "diffTotalMonthlyCost": "41.75"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cost,
+            CostEstimate {
+                currency: "EUR".to_string(),
+                past_monthly_cost: Some(100.5),
+                monthly_cost: Some(142.25),
+                monthly_cost_delta: Some(41.75),
+            }
+        );
+
+        let unpriced = parse_infracost_output(r#"{"totalMonthlyCost": null}"#).unwrap();
+        assert_eq!(unpriced.currency, "USD");
+        assert_eq!(unpriced.monthly_cost, None);
+        assert!(parse_infracost_output("not json").is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! Resource and data source type names offered by a provider.
+//!
+//! Read from `terraform providers schema -json` when the provider is installed
+//! in the project, otherwise from the provider's registry doc index, whose
+//! entries are titled without the provider prefix (`s3_bucket`).
+
+use crate::registry::client::DocIdResult;
+use crate::terraform::schema::{ProviderSchema, ProviderSchemas};
+use serde::{Deserialize, Serialize};
+
+/// Where a listing was read from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceListSource {
+    /// The installed provider's schema
+    Schema,
+    /// The registry doc index
+    Registry,
+}
+
+/// Type names offered by one provider, sorted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderResourceList {
+    /// Provider address from the schema, or `namespace/name` for the registry
+    pub provider: String,
+    /// Registry version the doc index belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub source: ResourceListSource,
+    /// Case-insensitive substring the names were filtered by
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    pub resource_count: usize,
+    pub data_source_count: usize,
+    pub resources: Vec<String>,
+    pub data_sources: Vec<String>,
+}
+
+/// Listing for `namespace/name` from installed provider schemas; `None` when
+/// the provider is not installed
+pub fn list_from_schema(
+    schemas: &ProviderSchemas,
+    namespace: &str,
+    name: &str,
+    filter: Option<&str>,
+) -> Option<ProviderResourceList> {
+    let suffix = format!("/{}/{}", namespace, name).to_lowercase();
+    let (address, provider): (&String, &ProviderSchema) = schemas
+        .provider_schemas
+        .iter()
+        .find(|(address, _)| address.to_lowercase().ends_with(&suffix))?;
+
+    Some(build_list(
+        address.clone(),
+        None,
+        ResourceListSource::Schema,
+        filter,
+        provider.resource_schemas.keys().cloned().collect(),
+        provider.data_source_schemas.keys().cloned().collect(),
+    ))
+}
+
+/// Listing for `namespace/name` from the resource and data source entries of
+/// its registry doc index
+pub fn list_from_docs(
+    docs: &[DocIdResult],
+    namespace: &str,
+    name: &str,
+    version: &str,
+    filter: Option<&str>,
+) -> ProviderResourceList {
+    let mut resources = Vec::new();
+    let mut data_sources = Vec::new();
+    for doc in docs {
+        let slug = doc.slug.as_deref().unwrap_or(&doc.title);
+        if slug.is_empty() {
+            continue;
+        }
+        let type_name = if slug.starts_with(&format!("{}_", name)) {
+            slug.to_string()
+        } else {
+            format!("{}_{}", name, slug)
+        };
+        match doc.category.as_str() {
+            "resources" => resources.push(type_name),
+            "data-sources" => data_sources.push(type_name),
+            _ => {}
+        }
+    }
+
+    build_list(
+        format!("{}/{}", namespace, name),
+        Some(version.to_string()),
+        ResourceListSource::Registry,
+        filter,
+        resources,
+        data_sources,
+    )
+}
+
+fn build_list(
+    provider: String,
+    version: Option<String>,
+    source: ResourceListSource,
+    filter: Option<&str>,
+    resources: Vec<String>,
+    data_sources: Vec<String>,
+) -> ProviderResourceList {
+    let filter = filter.map(str::trim).filter(|f| !f.is_empty());
+    let needle = filter.map(str::to_lowercase);
+    let select = |mut names: Vec<String>| {
+        if let Some(needle) = &needle {
+            names.retain(|name| name.to_lowercase().contains(needle.as_str()));
+        }
+        names.sort();
+        names.dedup();
+        names
+    };
+    let resources = select(resources);
+    let data_sources = select(data_sources);
+
+    ProviderResourceList {
+        provider,
+        version,
+        source,
+        filter: filter.map(str::to_string),
+        resource_count: resources.len(),
+        data_source_count: data_sources.len(),
+        resources,
+        data_sources,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Block-less `providers schema -json` listing for two installed providers
+    const SAMPLE_SCHEMA: &str = r#"{
+  "format_version": "1.0",
+  "provider_schemas": {
+    "registry.terraform.io/hashicorp/aws": {
+      "resource_schemas": {
+        "aws_s3_bucket": {"version": 0, "block": {}},
+        "aws_s3_bucket_policy": {"version": 0, "block": {}},
+        "aws_instance": {"version": 1, "block": {}}
+      },
+      "data_source_schemas": {
+        "aws_s3_bucket": {"version": 0, "block": {}},
+        "aws_ami": {"version": 0, "block": {}}
+      }
+    },
+    "registry.terraform.io/hashicorp/random": {
+      "resource_schemas": {
+        "random_id": {"version": 0, "block": {}}
+      }
+    }
+  }
+}"#;
+
+    fn doc(slug: &str, category: &str) -> DocIdResult {
+        DocIdResult {
+            id: format!("{}-{}", category, slug),
+            title: slug.to_string(),
+            description: String::new(),
+            category: category.to_string(),
+            slug: Some(slug.to_string()),
+            path: None,
+            subcategory: None,
+            extra: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_list_from_schema() {
+        let schemas = ProviderSchemas::from_json(SAMPLE_SCHEMA).unwrap();
+
+        let aws = list_from_schema(&schemas, "hashicorp", "aws", None).unwrap();
+        assert_eq!(aws.provider, "registry.terraform.io/hashicorp/aws");
+        assert_eq!(aws.source, ResourceListSource::Schema);
+        assert_eq!(
+            aws.resources,
+            ["aws_instance", "aws_s3_bucket", "aws_s3_bucket_policy"]
+        );
+        assert_eq!(aws.data_sources, ["aws_ami", "aws_s3_bucket"]);
+        assert_eq!((aws.resource_count, aws.data_source_count), (3, 2));
+
+        let buckets = list_from_schema(&schemas, "hashicorp", "aws", Some("S3_Bucket")).unwrap();
+        assert_eq!(buckets.filter.as_deref(), Some("S3_Bucket"));
+        assert_eq!(buckets.resources, ["aws_s3_bucket", "aws_s3_bucket_policy"]);
+        assert_eq!(buckets.data_sources, ["aws_s3_bucket"]);
+        assert_eq!((buckets.resource_count, buckets.data_source_count), (2, 1));
+
+        let random = list_from_schema(&schemas, "hashicorp", "random", None).unwrap();
+        assert_eq!(random.resources, ["random_id"]);
+        assert_eq!(random.data_source_count, 0);
+
+        assert!(list_from_schema(&schemas, "hashicorp", "google", None).is_none());
+    }
+
+    #[test]
+    fn test_list_from_docs() {
+        let docs = vec![
+            doc("s3_bucket", "resources"),
+            doc("instance", "resources"),
+            doc("ami", "data-sources"),
+            doc("aws_caller_identity", "data-sources"),
+            doc("custom-service-endpoints", "guides"),
+        ];
+
+        let list = list_from_docs(&docs, "hashicorp", "aws", "5.100.0", None);
+        assert_eq!(list.provider, "hashicorp/aws");
+        assert_eq!(list.version.as_deref(), Some("5.100.0"));
+        assert_eq!(list.source, ResourceListSource::Registry);
+        assert_eq!(list.resources, ["aws_instance", "aws_s3_bucket"]);
+        assert_eq!(list.data_sources, ["aws_ami", "aws_caller_identity"]);
+        assert_eq!((list.resource_count, list.data_source_count), (2, 2));
+
+        let filtered = list_from_docs(&docs, "hashicorp", "aws", "5.100.0", Some("bucket"));
+        assert_eq!(filtered.resources, ["aws_s3_bucket"]);
+        assert!(filtered.data_sources.is_empty());
+    }
+}
@@ -1,10 +1,13 @@
 //! Terraform provider information retrieval.
 
+use crate::terraform::version::satisfies_constraint;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::Output;
 
 /// Provider information from terraform providers command
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,18 +37,276 @@ pub struct ProvidersResult {
     pub message: String,
 }
 
-/// Get provider information
-pub fn get_providers(
-    terraform_path: &Path,
+/// Kind of problem found while verifying the lock file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LockFindingKind {
+    /// No `.terraform.lock.hcl` in the project
+    LockFileMissing,
+    /// Provider is configured but has no lock entry
+    NotLocked,
+    /// Locked version does not satisfy the configured constraint
+    OutsideConstraint,
+    /// Constraints recorded in the lock differ from the configuration
+    ConstraintsChanged,
+    /// Lock entry has no hashes at all
+    NoHashes,
+    /// Installed package for this platform does not match any locked hash
+    MissingPlatformHash,
+}
+
+/// A single lock file problem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockFinding {
+    pub kind: LockFindingKind,
+    /// "error" or "warning"
+    pub severity: String,
+    pub provider: Option<String>,
+    pub message: String,
+}
+
+/// Result of checking `.terraform.lock.hcl` against the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockVerification {
+    /// True when no error-severity findings were reported
+    pub consistent: bool,
+    /// Current platform, e.g. "linux_amd64"
+    pub platform: String,
+    pub locks: Vec<ProviderLock>,
+    /// Providers whose installed package for this platform was hashed and checked
+    pub platform_verified: Vec<String>,
+    pub findings: Vec<LockFinding>,
+}
+
+/// Verify the lock file against configured provider constraints.
+///
+/// `constraints` maps provider local names (e.g. "aws") to their configured
+/// version constraint. Platform hashes are checked for providers installed
+/// under `.terraform/providers`.
+pub fn verify_provider_lock(
+    project_dir: &Path,
+    constraints: &HashMap<String, Option<String>>,
+) -> anyhow::Result<LockVerification> {
+    let platform = current_platform();
+    let lock_path = project_dir.join(".terraform.lock.hcl");
+    if !lock_path.exists() {
+        return Ok(LockVerification {
+            consistent: false,
+            platform,
+            locks: Vec::new(),
+            platform_verified: Vec::new(),
+            findings: vec![LockFinding {
+                kind: LockFindingKind::LockFileMissing,
+                severity: "error".to_string(),
+                provider: None,
+                message:
+                    "No .terraform.lock.hcl found. Run 'terraform init' and commit the lock file."
+                        .to_string(),
+            }],
+        });
+    }
+
+    let locks = parse_lock_hcl(&fs::read_to_string(&lock_path)?)?;
+    let mut findings = check_locks(&locks, constraints);
+    let mut platform_verified = Vec::new();
+
+    for lock in &locks {
+        let package_dir = project_dir
+            .join(".terraform/providers")
+            .join(&lock.name)
+            .join(&lock.version)
+            .join(&platform);
+        if !package_dir.is_dir() {
+            continue;
+        }
+        let hash = package_hash(&package_dir)?;
+        platform_verified.push(lock.name.clone());
+        if !lock.hashes.contains(&hash) {
+            findings.push(LockFinding {
+                kind: LockFindingKind::MissingPlatformHash,
+                severity: "error".to_string(),
+                provider: Some(lock.name.clone()),
+                message: format!(
+                    "Installed {} {} for {} hashes to {}, which is not in the lock file. Run 'terraform providers lock -platform={}'.",
+                    lock.name, lock.version, platform, hash, platform
+                ),
+            });
+        }
+    }
+
+    Ok(LockVerification {
+        consistent: !findings.iter().any(|f| f.severity == "error"),
+        platform,
+        locks,
+        platform_verified,
+        findings,
+    })
+}
+
+/// Compare lock entries with configured constraints
+fn check_locks(
+    locks: &[ProviderLock],
+    constraints: &HashMap<String, Option<String>>,
+) -> Vec<LockFinding> {
+    let mut findings = Vec::new();
+
+    for lock in locks {
+        let local_name = lock.name.rsplit('/').next().unwrap_or(&lock.name);
+
+        if lock.hashes.is_empty() {
+            findings.push(LockFinding {
+                kind: LockFindingKind::NoHashes,
+                severity: "error".to_string(),
+                provider: Some(lock.name.clone()),
+                message: format!(
+                    "{} has no recorded hashes, so its packages cannot be verified",
+                    lock.name
+                ),
+            });
+        }
+
+        let Some(Some(constraint)) = constraints.get(local_name) else {
+            continue;
+        };
+
+        match satisfies_constraint(&lock.version, constraint) {
+            Ok(false) => findings.push(LockFinding {
+                kind: LockFindingKind::OutsideConstraint,
+                severity: "error".to_string(),
+                provider: Some(lock.name.clone()),
+                message: format!(
+                    "{} is locked at {}, which does not satisfy the configured constraint \"{}\". Run 'terraform init -upgrade'.",
+                    lock.name, lock.version, constraint
+                ),
+            }),
+            Ok(true) => {}
+            Err(e) => findings.push(LockFinding {
+                kind: LockFindingKind::OutsideConstraint,
+                severity: "warning".to_string(),
+                provider: Some(lock.name.clone()),
+                message: format!("Could not evaluate constraint for {}: {}", lock.name, e),
+            }),
+        }
+
+        if lock
+            .constraints
+            .as_deref()
+            .is_some_and(|locked| normalize_constraint(locked) != normalize_constraint(constraint))
+        {
+            findings.push(LockFinding {
+                kind: LockFindingKind::ConstraintsChanged,
+                severity: "warning".to_string(),
+                provider: Some(lock.name.clone()),
+                message: format!(
+                    "Lock file records constraints \"{}\" for {} but the configuration requires \"{}\". Run 'terraform init' to refresh the lock file.",
+                    lock.constraints.as_deref().unwrap_or_default(),
+                    lock.name,
+                    constraint
+                ),
+            });
+        }
+    }
+
+    for (name, constraint) in constraints {
+        let locked = locks
+            .iter()
+            .any(|lock| lock.name.rsplit('/').next() == Some(name.as_str()));
+        if !locked {
+            findings.push(LockFinding {
+                kind: LockFindingKind::NotLocked,
+                severity: "error".to_string(),
+                provider: Some(name.clone()),
+                message: format!(
+                    "Provider '{}'{} is configured but missing from the lock file. Run 'terraform init'.",
+                    name,
+                    constraint
+                        .as_deref()
+                        .map(|c| format!(" ({})", c))
+                        .unwrap_or_default()
+                ),
+            });
+        }
+    }
+
+    findings.sort_by(|a, b| a.provider.cmp(&b.provider));
+    findings
+}
+
+fn normalize_constraint(constraint: &str) -> String {
+    constraint.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Platform string Terraform uses for provider packages, e.g. "darwin_arm64"
+fn current_platform() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        other => other,
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "386",
+        other => other,
+    };
+    format!("{}_{}", os, arch)
+}
+
+/// "h1:" hash of an unpacked provider package, as recorded in the lock file.
+///
+/// This is Go's dirhash Hash1: the SHA-256 of a manifest listing each file's
+/// SHA-256 and relative path, sorted by path.
+fn package_hash(package_dir: &Path) -> anyhow::Result<String> {
+    let mut files = Vec::new();
+    collect_files(package_dir, &mut files)?;
+
+    let mut entries: Vec<(String, PathBuf)> = files
+        .into_iter()
+        .map(|path| {
+            let name = path
+                .strip_prefix(package_dir)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            (name, path)
+        })
+        .collect();
+    entries.sort();
+
+    let mut manifest = String::new();
+    for (name, path) in entries {
+        let digest = Sha256::digest(fs::read(&path)?);
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        manifest.push_str(&format!("{}  {}\n", hex, name));
+    }
+
+    let summary = Sha256::digest(manifest.as_bytes());
+    Ok(format!(
+        "h1:{}",
+        base64::engine::general_purpose::STANDARD.encode(summary)
+    ))
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Provider information from `terraform providers` run in `project_dir`,
+/// with the lock file's entries if requested
+pub fn providers_result(
+    output: &Output,
     project_dir: &Path,
     include_lock: bool,
 ) -> anyhow::Result<ProvidersResult> {
-    // Run terraform providers command
-    let output = Command::new(terraform_path)
-        .arg("providers")
-        .current_dir(project_dir)
-        .output()?;
-
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
@@ -345,6 +606,106 @@ provider "registry.terraform.io/hashicorp/aws" {
         assert_eq!(locks[0].constraints, Some("~> 5.0".to_string()));
     }
 
+    const SAMPLE_LOCK: &str = r#"
+provider "registry.terraform.io/hashicorp/aws" {
+  version     = "4.67.0"
+  constraints = "~> 4.0"
+  hashes = [
+    "h1:abc123",
+  ]
+}
+
+provider "registry.terraform.io/hashicorp/random" {
+  version     = "3.6.0"
+  constraints = "~> 3.5"
+  hashes = [
+    "h1:def456",
+  ]
+}
+"#;
+
+    #[test]
+    fn test_check_locks_flags_version_outside_constraint() {
+        let locks = parse_lock_hcl(SAMPLE_LOCK).unwrap();
+        let constraints = HashMap::from([
+            ("aws".to_string(), Some("~> 5.0".to_string())),
+            ("random".to_string(), Some("~> 3.5".to_string())),
+            ("tls".to_string(), None),
+        ]);
+
+        let findings = check_locks(&locks, &constraints);
+        let kinds: Vec<_> = findings
+            .iter()
+            .map(|f| (f.provider.as_deref().unwrap(), f.kind))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    "registry.terraform.io/hashicorp/aws",
+                    LockFindingKind::OutsideConstraint
+                ),
+                (
+                    "registry.terraform.io/hashicorp/aws",
+                    LockFindingKind::ConstraintsChanged
+                ),
+                ("tls", LockFindingKind::NotLocked),
+            ]
+        );
+        assert!(findings[0].message.contains("4.67.0"));
+    }
+
+    #[test]
+    fn test_verify_provider_lock_checks_installed_platform_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let package_dir = dir
+            .path()
+            .join(".terraform/providers/registry.terraform.io/hashicorp/random/3.6.0")
+            .join(current_platform());
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(
+            package_dir.join("terraform-provider-random_v3.6.0"),
+            b"binary",
+        )
+        .unwrap();
+        fs::write(dir.path().join(".terraform.lock.hcl"), SAMPLE_LOCK).unwrap();
+
+        let verification = verify_provider_lock(dir.path(), &HashMap::new()).unwrap();
+        assert!(!verification.consistent);
+        assert_eq!(
+            verification.platform_verified,
+            vec!["registry.terraform.io/hashicorp/random"]
+        );
+        assert_eq!(verification.findings.len(), 1);
+        assert_eq!(
+            verification.findings[0].kind,
+            LockFindingKind::MissingPlatformHash
+        );
+
+        // Once the package hash is locked the platform check passes
+        let hash = package_hash(&package_dir).unwrap();
+        fs::write(
+            dir.path().join(".terraform.lock.hcl"),
+            SAMPLE_LOCK.replace("h1:def456", &hash),
+        )
+        .unwrap();
+        let verification = verify_provider_lock(dir.path(), &HashMap::new()).unwrap();
+        assert!(verification.consistent);
+        assert!(verification.findings.is_empty());
+    }
+
+    #[test]
+    fn test_verify_provider_lock_without_lock_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let verification = verify_provider_lock(dir.path(), &HashMap::new()).unwrap();
+        assert!(!verification.consistent);
+        assert_eq!(
+            verification.findings[0].kind,
+            LockFindingKind::LockFileMissing
+        );
+    }
+
     #[test]
     fn test_extract_provider_requirements() {
         let content = r#"
@@ -4,8 +4,7 @@
 //! `terraform apply -refresh-only` which is what this module implements internally.
 
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::process::Command;
+use std::process::Output;
 
 /// Result of refresh operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,25 +33,16 @@ pub enum RefreshChangeType {
     Unchanged,
 }
 
-/// Execute terraform refresh (via apply -refresh-only)
-pub fn execute_refresh(
-    terraform_path: &Path,
-    project_dir: &Path,
-    target: Option<&str>,
-) -> anyhow::Result<RefreshResult> {
-    let mut cmd = Command::new(terraform_path);
-    cmd.arg("apply")
-        .arg("-refresh-only")
-        .arg("-auto-approve")
-        .arg("-json");
-
-    // If targeting a specific resource
-    if let Some(target_addr) = target {
-        cmd.arg(format!("-target={}", target_addr));
-    }
-
-    let output = cmd.current_dir(project_dir).output()?;
+/// Arguments of a refresh, run as `apply -refresh-only`
+pub fn refresh_args(target: Option<&str>) -> Vec<String> {
+    with_target(
+        &["apply", "-refresh-only", "-auto-approve", "-json"],
+        target,
+    )
+}
 
+/// Result of a refresh run with [`refresh_args`]
+pub fn refresh_result(output: &Output) -> anyhow::Result<RefreshResult> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
@@ -82,23 +72,15 @@ pub fn execute_refresh(
     })
 }
 
-/// Execute refresh with plan preview (no auto-approve)
+/// Arguments of a refresh preview (plan -refresh-only, no auto-approve)
 #[allow(dead_code)]
-pub fn preview_refresh(
-    terraform_path: &Path,
-    project_dir: &Path,
-    target: Option<&str>,
-) -> anyhow::Result<RefreshResult> {
-    let mut cmd = Command::new(terraform_path);
-    cmd.arg("plan").arg("-refresh-only").arg("-json");
-
-    // If targeting a specific resource
-    if let Some(target_addr) = target {
-        cmd.arg(format!("-target={}", target_addr));
-    }
-
-    let output = cmd.current_dir(project_dir).output()?;
+pub fn preview_args(target: Option<&str>) -> Vec<String> {
+    with_target(&["plan", "-refresh-only", "-json"], target)
+}
 
+/// Result of a refresh preview run with [`preview_args`]
+#[allow(dead_code)]
+pub fn preview_result(output: &Output) -> anyhow::Result<RefreshResult> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
@@ -131,6 +113,15 @@ pub fn preview_refresh(
     })
 }
 
+/// `args`, limited to `target` when one is given
+fn with_target(args: &[&str], target: Option<&str>) -> Vec<String> {
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    if let Some(target_addr) = target {
+        args.push(format!("-target={}", target_addr));
+    }
+    args
+}
+
 /// Parse refresh output to extract changes
 fn parse_refresh_output(json_output: &str) -> Vec<RefreshChange> {
     let mut changes = Vec::new();
@@ -189,23 +180,15 @@ fn parse_refresh_output(json_output: &str) -> Vec<RefreshChange> {
     changes
 }
 
-/// Get a list of resources that might need refreshing
+/// Resources a refresh preview found drifted, i.e. that might need refreshing
 #[allow(dead_code)]
-pub fn get_stale_resources(
-    terraform_path: &Path,
-    project_dir: &Path,
-) -> anyhow::Result<Vec<String>> {
-    // Run a refresh-only plan to detect drift
-    let result = preview_refresh(terraform_path, project_dir, None)?;
-
-    let stale: Vec<String> = result
+pub fn stale_resources(preview: &RefreshResult) -> Vec<String> {
+    preview
         .changes
-        .into_iter()
+        .iter()
         .filter(|c| c.change_type == RefreshChangeType::Drifted)
-        .map(|c| c.resource_address)
-        .collect();
-
-    Ok(stale)
+        .map(|c| c.resource_address.clone())
+        .collect()
 }
 
 #[cfg(test)]
@@ -0,0 +1,129 @@
+//! Counts of `terraform state list` addresses by resource type and module.
+//!
+//! Instance keys (`[0]`, `["a.b"]`) are dropped before grouping, so every
+//! instance of a resource counts towards the same type and module.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Module path reported for resources of the root module
+pub const ROOT_MODULE_PATH: &str = "root";
+
+/// State addresses with per-type and per-module counts
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceListing {
+    /// Addresses as listed by `terraform state list`
+    pub resources: Vec<String>,
+    pub total: usize,
+    /// Instances per resource type; data sources are keyed `data.<type>`
+    pub by_type: BTreeMap<String, usize>,
+    /// Instances per module path, e.g. `module.vpc.module.subnets`
+    pub by_module: BTreeMap<String, usize>,
+}
+
+impl ResourceListing {
+    pub fn new(resources: Vec<String>) -> Self {
+        let mut by_type = BTreeMap::new();
+        let mut by_module = BTreeMap::new();
+        for address in &resources {
+            let (module_path, resource_type) = split_address(address);
+            *by_type.entry(resource_type).or_insert(0) += 1;
+            *by_module.entry(module_path).or_insert(0) += 1;
+        }
+        Self {
+            total: resources.len(),
+            resources,
+            by_type,
+            by_module,
+        }
+    }
+}
+
+/// (module path, resource type) of a state address
+fn split_address(address: &str) -> (String, String) {
+    let segments = address_segments(address);
+    let module_len = segments
+        .chunks(2)
+        .take_while(|pair| pair.len() == 2 && pair[0] == "module")
+        .count()
+        * 2;
+    let module_path = if module_len == 0 {
+        ROOT_MODULE_PATH.to_string()
+    } else {
+        segments[..module_len].join(".")
+    };
+    let rest = &segments[module_len..];
+    let resource_type = rest[..rest.len().saturating_sub(1)].join(".");
+    (module_path, resource_type)
+}
+
+/// Dot-separated segments of `address` with instance keys removed; dots
+/// inside `[...]` keys do not split
+pub(crate) fn address_segments(address: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    for c in address.chars() {
+        match c {
+            '"' if depth > 0 => in_string = !in_string,
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => depth -= 1,
+            '.' if depth == 0 => segments.push(std::mem::take(&mut current)),
+            _ if depth == 0 => current.push(c),
+            _ => {}
+        }
+    }
+    segments.push(current);
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breakdown_by_type_and_module() {
+        let addresses = [
+            "aws_instance.web[0]",
+            "aws_instance.web[1]",
+            "aws_s3_bucket.logs",
+            "data.aws_ami.ubuntu",
+            "module.vpc.aws_subnet.private[\"us-east-1.a\"]",
+            "module.vpc.aws_subnet.private[\"us-east-1.b\"]",
+            "module.vpc.aws_vpc.this[0]",
+            "module.apps[\"api\"].module.db.aws_db_instance.main",
+        ];
+        let listing = ResourceListing::new(addresses.iter().map(|a| a.to_string()).collect());
+
+        assert_eq!(listing.total, 8);
+        assert_eq!(listing.resources.len(), 8);
+        assert_eq!(
+            listing.by_type.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("aws_db_instance".to_string(), 1),
+                ("aws_instance".to_string(), 2),
+                ("aws_s3_bucket".to_string(), 1),
+                ("aws_subnet".to_string(), 2),
+                ("aws_vpc".to_string(), 1),
+                ("data.aws_ami".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            listing.by_module.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("module.apps.module.db".to_string(), 1),
+                ("module.vpc".to_string(), 3),
+                ("root".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_listing() {
+        let listing = ResourceListing::new(Vec::new());
+        assert_eq!(listing.total, 0);
+        assert!(listing.by_type.is_empty() && listing.by_module.is_empty());
+    }
+}
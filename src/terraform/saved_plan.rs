@@ -0,0 +1,209 @@
+//! Plan files saved for a later, verified apply.
+//!
+//! `save_plan` writes `terraform plan -out` to a file in the project and
+//! records the file's SHA-256 in a sidecar `<plan>.tfmcp.json`. Before
+//! `apply_saved_plan` runs, the file is hashed again and the apply is refused
+//! when it no longer matches, so what applies is exactly the plan that was
+//! reviewed.
+
+use crate::terraform::error::TerraformError;
+use crate::terraform::plan_analyzer::ChangeSummary;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Plan file used when the caller does not name one
+pub const DEFAULT_PLAN_FILE: &str = "tfmcp.tfplan";
+
+/// Appended to the plan file name for the metadata sidecar
+const METADATA_SUFFIX: &str = ".tfmcp.json";
+
+/// A plan file and the hash it had when it was saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPlan {
+    /// Plan file, relative to the project root
+    pub plan_file: String,
+    /// Hex SHA-256 of the plan file
+    pub sha256: String,
+    pub saved_at: DateTime<Utc>,
+    pub project_directory: String,
+    pub summary: ChangeSummary,
+}
+
+/// Result of applying a saved plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPlanApply {
+    pub plan_file: String,
+    pub sha256: String,
+    pub saved_at: DateTime<Utc>,
+    /// Configuration files modified after the plan was saved
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    pub output: String,
+}
+
+/// Resolve a plan file name relative to `project_directory`, refusing
+/// absolute paths and paths that climb out of the project
+pub fn resolve_plan_path(
+    project_directory: &Path,
+    plan_file: Option<&str>,
+) -> anyhow::Result<PathBuf> {
+    let plan_file = plan_file.unwrap_or(DEFAULT_PLAN_FILE);
+    let relative = Path::new(plan_file);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(anyhow::anyhow!(
+            "Plan file '{}' must be a relative path inside the project directory",
+            plan_file
+        ));
+    }
+    Ok(project_directory.join(relative))
+}
+
+fn metadata_path(plan_path: &Path) -> PathBuf {
+    let mut name = plan_path.as_os_str().to_os_string();
+    name.push(METADATA_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Hex SHA-256 of the file at `path`
+pub fn file_sha256(path: &Path) -> std::io::Result<String> {
+    let digest = Sha256::digest(std::fs::read(path)?);
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Write the sidecar recording `saved`
+pub fn record(plan_path: &Path, saved: &SavedPlan) -> anyhow::Result<()> {
+    let path = metadata_path(plan_path);
+    std::fs::write(&path, serde_json::to_string_pretty(saved)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write plan metadata {}: {}", path.display(), e))
+}
+
+/// Check the plan file against its sidecar, returning the recorded metadata
+/// when the file is unchanged
+pub fn verify(plan_path: &Path, plan_file: &str) -> anyhow::Result<SavedPlan> {
+    let path = metadata_path(plan_path);
+    let metadata = std::fs::read_to_string(&path).map_err(|_| {
+        anyhow::anyhow!(
+            "Plan file '{}' has no recorded hash. Create it with save_plan before applying it.",
+            plan_file
+        )
+    })?;
+    let saved: SavedPlan = serde_json::from_str(&metadata)
+        .map_err(|e| anyhow::anyhow!("Failed to parse plan metadata {}: {}", path.display(), e))?;
+
+    let actual = file_sha256(plan_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read plan file '{}': {}", plan_file, e))?;
+    if actual != saved.sha256 {
+        return Err(TerraformError::PlanFileChanged {
+            plan_file: plan_file.to_string(),
+            expected: saved.sha256,
+            actual,
+        }
+        .into());
+    }
+    Ok(saved)
+}
+
+/// Delete a plan file and its sidecar, ignoring files already gone
+pub fn remove(plan_path: &Path) {
+    let _ = std::fs::remove_file(plan_path);
+    let _ = std::fs::remove_file(metadata_path(plan_path));
+}
+
+/// Configuration files in `project_directory` modified after `since`
+pub fn config_modified_since(project_directory: &Path, since: SystemTime) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(project_directory) else {
+        return Vec::new();
+    };
+    let mut modified: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.ends_with(".tf") || name.ends_with(".tf.json") || name.ends_with(".tfvars")
+        })
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|mtime| mtime > since)
+        })
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    modified.sort();
+    modified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_plan(plan_path: &Path) -> SavedPlan {
+        SavedPlan {
+            plan_file: DEFAULT_PLAN_FILE.to_string(),
+            sha256: file_sha256(plan_path).unwrap(),
+            saved_at: Utc::now(),
+            project_directory: plan_path.parent().unwrap().display().to_string(),
+            summary: ChangeSummary::default(),
+        }
+    }
+
+    #[test]
+    fn test_verify_refuses_changed_plan_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan_path = resolve_plan_path(dir.path(), None).unwrap();
+        std::fs::write(&plan_path, b"reviewed plan").unwrap();
+        record(&plan_path, &saved_plan(&plan_path)).unwrap();
+
+        assert!(verify(&plan_path, DEFAULT_PLAN_FILE).is_ok());
+
+        std::fs::write(&plan_path, b"tampered plan").unwrap();
+        let err = verify(&plan_path, DEFAULT_PLAN_FILE).unwrap_err();
+        match err.downcast_ref::<TerraformError>() {
+            Some(TerraformError::PlanFileChanged {
+                expected, actual, ..
+            }) => {
+                assert_ne!(expected, actual);
+                assert_eq!(actual, &file_sha256(&plan_path).unwrap());
+            }
+            other => panic!("expected PlanFileChanged, got {:?}", other),
+        }
+
+        let unrecorded = dir.path().join("other.tfplan");
+        std::fs::write(&unrecorded, b"plan").unwrap();
+        assert!(
+            verify(&unrecorded, "other.tfplan")
+                .unwrap_err()
+                .to_string()
+                .contains("save_plan")
+        );
+    }
+
+    #[test]
+    fn test_plan_path_stays_in_project() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            resolve_plan_path(dir.path(), Some("plans/prod.tfplan")).unwrap(),
+            dir.path().join("plans/prod.tfplan")
+        );
+        assert!(resolve_plan_path(dir.path(), Some("../prod.tfplan")).is_err());
+        assert!(resolve_plan_path(dir.path(), Some("/tmp/prod.tfplan")).is_err());
+    }
+
+    #[test]
+    fn test_config_modified_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let before = SystemTime::now() - std::time::Duration::from_secs(60);
+        std::fs::write(dir.path().join("main.tf"), "").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "").unwrap();
+
+        assert_eq!(config_modified_since(dir.path(), before), vec!["main.tf"]);
+        let after = SystemTime::now() + std::time::Duration::from_secs(60);
+        assert!(config_modified_since(dir.path(), after).is_empty());
+    }
+}
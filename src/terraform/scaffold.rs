@@ -0,0 +1,360 @@
+//! Starter HCL blocks for a resource type.
+//!
+//! A scaffold sets every required argument (and required nested block) to a
+//! placeholder and lists the optional arguments in a comment. It is built
+//! from the local provider schema when available, or from the "Argument
+//! Reference" section of the resource's registry docs otherwise.
+
+use crate::terraform::schema::SchemaBlock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::LazyLock;
+
+/// Arguments listed in docs as "* `name` - (Required) ..." or "- `name` - (Optional) ..."
+static DOC_ARGUMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*[*-]\s+`([A-Za-z0-9_]+)`\s*[-–:]\s*\((Required|Optional)")
+        .expect("Invalid doc argument regex")
+});
+
+/// Placeholder for required string arguments
+const STRING_PLACEHOLDER: &str = "\"REPLACE_ME\"";
+
+/// Where the argument list of a scaffold came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaffoldSource {
+    /// `terraform providers schema -json` in the initialized project
+    ProviderSchema,
+    /// The resource's documentation on the Terraform Registry
+    ProviderDocs,
+}
+
+/// A generated starting block for one resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceScaffold {
+    pub resource_type: String,
+    pub name: String,
+    pub source: ScaffoldSource,
+    /// Required arguments; nested blocks as `block.argument`
+    pub required: Vec<String>,
+    /// Optional top-level arguments and blocks
+    pub optional: Vec<String>,
+    pub hcl: String,
+}
+
+/// Scaffold `resource_type` from its provider schema block
+pub fn scaffold_from_schema(
+    resource_type: &str,
+    name: &str,
+    block: &SchemaBlock,
+) -> ResourceScaffold {
+    let mut required = Vec::new();
+    let mut body = Vec::new();
+    render_required(block, "", 1, &mut required, &mut body);
+
+    let optional: Vec<String> = block
+        .attributes
+        .iter()
+        .filter(|(attr, schema)| schema.optional && !schema.deprecated && attr.as_str() != "id")
+        .map(|(attr, _)| attr.clone())
+        .chain(
+            block
+                .block_types
+                .iter()
+                .filter(|(_, nested)| nested.min_items == 0)
+                .map(|(block_name, _)| block_name.clone()),
+        )
+        .collect();
+
+    ResourceScaffold {
+        resource_type: resource_type.to_string(),
+        name: name.to_string(),
+        source: ScaffoldSource::ProviderSchema,
+        hcl: render_resource(resource_type, name, &body, &optional),
+        required,
+        optional,
+    }
+}
+
+/// Scaffold `resource_type` from its registry docs; `None` when the docs have
+/// no recognizable argument list
+pub fn scaffold_from_docs(
+    resource_type: &str,
+    name: &str,
+    doc_content: &str,
+) -> Option<ResourceScaffold> {
+    let markdown = doc_markdown(doc_content);
+    let mut required = Vec::new();
+    let mut optional = Vec::new();
+
+    let mut in_arguments = false;
+    for line in markdown.lines() {
+        if line.starts_with('#') {
+            // Nested block arguments follow under their own headings; only
+            // the top-level list directly below "Argument Reference" is used
+            if in_arguments {
+                break;
+            }
+            in_arguments = line.trim_start_matches('#').trim() == "Argument Reference";
+            continue;
+        }
+        if !in_arguments {
+            continue;
+        }
+        if let Some(caps) = DOC_ARGUMENT_REGEX.captures(line) {
+            let argument = caps[1].to_string();
+            match &caps[2] {
+                "Required" => required.push(argument),
+                _ => optional.push(argument),
+            }
+        }
+    }
+
+    if required.is_empty() && optional.is_empty() {
+        return None;
+    }
+
+    let body: Vec<String> = required
+        .iter()
+        .map(|argument| format!("  {} = {}", argument, STRING_PLACEHOLDER))
+        .collect();
+    Some(ResourceScaffold {
+        resource_type: resource_type.to_string(),
+        name: name.to_string(),
+        source: ScaffoldSource::ProviderDocs,
+        hcl: render_resource(resource_type, name, &body, &optional),
+        required,
+        optional,
+    })
+}
+
+/// Registry doc content is either raw markdown or a JSON:API document with
+/// the markdown under `data.attributes.content`
+fn doc_markdown(doc_content: &str) -> String {
+    serde_json::from_str::<Value>(doc_content)
+        .ok()
+        .and_then(|json| {
+            json.pointer("/data/attributes/content")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| doc_content.to_string())
+}
+
+/// Append placeholder lines for the required attributes and nested blocks of `block`
+fn render_required(
+    block: &SchemaBlock,
+    prefix: &str,
+    depth: usize,
+    required: &mut Vec<String>,
+    lines: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(depth);
+    for (attr, schema) in &block.attributes {
+        if schema.required {
+            required.push(format!("{}{}", prefix, attr));
+            lines.push(format!(
+                "{}{} = {}",
+                indent,
+                attr,
+                placeholder(schema.attribute_type.as_ref())
+            ));
+        }
+    }
+    for (block_name, nested) in &block.block_types {
+        if nested.min_items == 0 {
+            continue;
+        }
+        lines.push(String::new());
+        lines.push(format!("{}{} {{", indent, block_name));
+        render_required(
+            &nested.block,
+            &format!("{}{}.", prefix, block_name),
+            depth + 1,
+            required,
+            lines,
+        );
+        lines.push(format!("{}}}", indent));
+    }
+}
+
+/// Placeholder value for a cty type
+fn placeholder(attribute_type: Option<&Value>) -> &'static str {
+    let kind = match attribute_type {
+        Some(Value::String(primitive)) => primitive.as_str(),
+        Some(Value::Array(collection)) => collection.first().and_then(Value::as_str).unwrap_or(""),
+        _ => "",
+    };
+    match kind {
+        "number" => "0",
+        "bool" => "false",
+        "list" | "set" | "tuple" => "[]",
+        "map" | "object" => "{}",
+        _ => STRING_PLACEHOLDER,
+    }
+}
+
+fn render_resource(
+    resource_type: &str,
+    name: &str,
+    body: &[String],
+    optional: &[String],
+) -> String {
+    let mut hcl = format!("resource \"{}\" \"{}\" {{\n", resource_type, name);
+    for line in body.iter().skip_while(|line| line.is_empty()) {
+        hcl.push_str(line);
+        hcl.push('\n');
+    }
+    if !optional.is_empty() {
+        if !body.is_empty() {
+            hcl.push('\n');
+        }
+        hcl.push_str("  # Optional arguments:\n");
+        for argument in optional {
+            hcl.push_str(&format!("  #   {}\n", argument));
+        }
+    }
+    hcl.push_str("}\n");
+    hcl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terraform::schema::ProviderSchemas;
+
+    /// Trimmed from `terraform providers schema -json` for hashicorp/aws 5.100.0
+    const CAPTURED_SCHEMA: &str = r#"{
+  "format_version": "1.0",
+  "provider_schemas": {
+    "registry.terraform.io/hashicorp/aws": {
+      "resource_schemas": {
+        "aws_s3_bucket_versioning": {
+          "version": 0,
+          "block": {
+            "attributes": {
+              "bucket": {"type": "string", "description_kind": "plain", "required": true},
+              "expected_bucket_owner": {"type": "string", "description_kind": "plain", "optional": true},
+              "id": {"type": "string", "description_kind": "plain", "optional": true, "computed": true},
+              "mfa": {"type": "string", "description_kind": "plain", "optional": true}
+            },
+            "block_types": {
+              "versioning_configuration": {
+                "nesting_mode": "list",
+                "block": {
+                  "attributes": {
+                    "mfa_delete": {"type": "string", "description_kind": "plain", "optional": true, "computed": true},
+                    "status": {"type": "string", "description_kind": "plain", "required": true}
+                  },
+                  "description_kind": "plain"
+                },
+                "min_items": 1,
+                "max_items": 1
+              }
+            },
+            "description_kind": "plain"
+          }
+        },
+        "aws_security_group_rule": {
+          "version": 2,
+          "block": {
+            "attributes": {
+              "cidr_blocks": {"type": ["list", "string"], "optional": true},
+              "description": {"type": "string", "optional": true},
+              "from_port": {"type": "number", "required": true},
+              "id": {"type": "string", "optional": true, "computed": true},
+              "protocol": {"type": "string", "required": true},
+              "security_group_id": {"type": "string", "required": true},
+              "security_group_rule_id": {"type": "string", "computed": true},
+              "self": {"type": "bool", "optional": true},
+              "to_port": {"type": "number", "required": true},
+              "type": {"type": "string", "required": true}
+            },
+            "block_types": {
+              "timeouts": {"nesting_mode": "single", "block": {"attributes": {"create": {"type": "string", "optional": true}}}}
+            }
+          }
+        }
+      }
+    }
+  }
+}"#;
+
+    #[test]
+    fn test_scaffold_from_captured_schema() {
+        let schemas = ProviderSchemas::from_json(CAPTURED_SCHEMA).unwrap();
+        let (provider, schema) = schemas.resource("aws_s3_bucket_versioning").unwrap();
+        assert_eq!(provider, "registry.terraform.io/hashicorp/aws");
+
+        let scaffold = scaffold_from_schema("aws_s3_bucket_versioning", "this", &schema.block);
+        assert_eq!(scaffold.source, ScaffoldSource::ProviderSchema);
+        assert_eq!(
+            scaffold.required,
+            vec!["bucket", "versioning_configuration.status"]
+        );
+        assert_eq!(scaffold.optional, vec!["expected_bucket_owner", "mfa"]);
+        assert_eq!(
+            scaffold.hcl,
+            r#"resource "aws_s3_bucket_versioning" "this" {
+  bucket = "REPLACE_ME"
+
+  versioning_configuration {
+    status = "REPLACE_ME"
+  }
+
+  # Optional arguments:
+  #   expected_bucket_owner
+  #   mfa
+}
+"#
+        );
+
+        let (_, schema) = schemas.resource("aws_security_group_rule").unwrap();
+        let scaffold = scaffold_from_schema("aws_security_group_rule", "ingress", &schema.block);
+        assert!(scaffold.hcl.contains("  from_port = 0\n"));
+        assert!(scaffold.hcl.contains("  type = \"REPLACE_ME\"\n"));
+        assert!(
+            !scaffold
+                .required
+                .contains(&"security_group_rule_id".to_string())
+        );
+        assert!(scaffold.optional.contains(&"timeouts".to_string()));
+        assert!(schemas.resource("aws_instance").is_none());
+    }
+
+    #[test]
+    fn test_scaffold_from_docs_reads_top_level_arguments() {
+        let markdown = r#"# Resource: aws_s3_bucket_versioning
+
+## Example Usage
+
+* `not_an_argument` - (Required) Appears outside the argument reference.
+
+## Argument Reference
+
+This resource supports the following arguments:
+
+* `bucket` - (Required, Forces new resource) Name of the S3 bucket.
+* `versioning_configuration` - (Required) Configuration block for the versioning parameters.
+* `mfa` - (Optional) Concatenation of the authentication device's serial number.
+
+### versioning_configuration
+
+* `status` - (Required) Versioning state of the bucket.
+"#;
+        let content =
+            serde_json::json!({"data": {"attributes": {"content": markdown}}}).to_string();
+
+        let scaffold = scaffold_from_docs("aws_s3_bucket_versioning", "this", &content).unwrap();
+        assert_eq!(scaffold.source, ScaffoldSource::ProviderDocs);
+        assert_eq!(
+            scaffold.required,
+            vec!["bucket", "versioning_configuration"]
+        );
+        assert_eq!(scaffold.optional, vec!["mfa"]);
+        assert!(scaffold.hcl.contains("  bucket = \"REPLACE_ME\"\n"));
+
+        assert!(scaffold_from_docs("aws_thing", "this", "# No arguments here").is_none());
+    }
+}
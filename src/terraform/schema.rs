@@ -0,0 +1,203 @@
+//! Types for the output of `terraform providers schema -json`.
+//!
+//! Only the parts tfmcp uses are modelled; attribute types are kept as raw
+//! cty type JSON (`"string"`, `["list", "string"]`, ...).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Top-level `providers schema -json` document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderSchemas {
+    #[serde(default)]
+    pub format_version: String,
+    /// Provider source address (e.g. "registry.terraform.io/hashicorp/aws") -> schema
+    #[serde(default)]
+    pub provider_schemas: BTreeMap<String, ProviderSchema>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderSchema {
+    #[serde(default)]
+    pub provider: Option<Schema>,
+    #[serde(default)]
+    pub resource_schemas: BTreeMap<String, Schema>,
+    #[serde(default)]
+    pub data_source_schemas: BTreeMap<String, Schema>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schema {
+    #[serde(default)]
+    pub version: u64,
+    pub block: SchemaBlock,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaBlock {
+    #[serde(default)]
+    pub attributes: BTreeMap<String, SchemaAttribute>,
+    #[serde(default)]
+    pub block_types: BTreeMap<String, NestedBlock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaAttribute {
+    /// cty type of the attribute; absent for attributes with `nested_type`
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub attribute_type: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nested_type: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub computed: bool,
+    #[serde(default)]
+    pub sensitive: bool,
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NestedBlock {
+    /// "single", "list", "set", "map" or "group"
+    pub nesting_mode: String,
+    pub block: SchemaBlock,
+    #[serde(default)]
+    pub min_items: u64,
+    #[serde(default)]
+    pub max_items: u64,
+}
+
+impl ProviderSchemas {
+    /// Parse `terraform providers schema -json` output
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| anyhow::anyhow!("Failed to parse provider schemas: {}", e))
+    }
+
+    /// Schema of `resource_type` and the address of the provider defining it
+    pub fn resource(&self, resource_type: &str) -> Option<(&str, &Schema)> {
+        self.provider_schemas
+            .iter()
+            .find_map(|(address, provider)| {
+                provider
+                    .resource_schemas
+                    .get(resource_type)
+                    .map(|schema| (address.as_str(), schema))
+            })
+    }
+
+    /// Schema of the data source `data_source_type` and the address of its provider
+    pub fn data_source(&self, data_source_type: &str) -> Option<(&str, &Schema)> {
+        self.provider_schemas
+            .iter()
+            .find_map(|(address, provider)| {
+                provider
+                    .data_source_schemas
+                    .get(data_source_type)
+                    .map(|schema| (address.as_str(), schema))
+            })
+    }
+}
+
+/// Schema of one resource or data source type, as returned by `get_resource_schema`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceSchema {
+    pub resource_type: String,
+    pub data_source: bool,
+    /// Address of the provider defining the type
+    pub provider: String,
+    pub schema: Schema,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trimmed from `terraform providers schema -json` for hashicorp/aws 5.100.0
+    const SAMPLE_SCHEMA: &str = r#"{
+  "format_version": "1.0",
+  "provider_schemas": {
+    "registry.terraform.io/hashicorp/aws": {
+      "provider": {
+        "version": 0,
+        "block": {
+          "attributes": {
+            "region": {"type": "string", "description": "The region where AWS operations will take place.", "description_kind": "plain", "optional": true}
+          },
+          "description_kind": "plain"
+        }
+      },
+      "resource_schemas": {
+        "aws_db_instance": {
+          "version": 2,
+          "block": {
+            "attributes": {
+              "password": {"type": "string", "description_kind": "plain", "optional": true, "sensitive": true},
+              "tags": {"type": ["map", "string"], "description_kind": "plain", "optional": true},
+              "arn": {"type": "string", "description_kind": "plain", "computed": true}
+            },
+            "description_kind": "plain"
+          }
+        }
+      },
+      "data_source_schemas": {
+        "aws_s3_bucket": {
+          "version": 0,
+          "block": {
+            "attributes": {
+              "arn": {"type": "string", "description_kind": "plain", "computed": true},
+              "bucket": {"type": "string", "description_kind": "plain", "required": true}
+            },
+            "description_kind": "plain"
+          }
+        }
+      },
+      "ephemeral_resource_schemas": {},
+      "functions": {}
+    }
+  }
+}"#;
+
+    #[test]
+    fn test_deserialize_sample_schema() {
+        let schemas = ProviderSchemas::from_json(SAMPLE_SCHEMA).unwrap();
+        assert_eq!(schemas.format_version, "1.0");
+
+        let aws = &schemas.provider_schemas["registry.terraform.io/hashicorp/aws"];
+        let region = &aws.provider.as_ref().unwrap().block.attributes["region"];
+        assert!(region.optional && !region.required);
+
+        let (provider, db) = schemas.resource("aws_db_instance").unwrap();
+        assert_eq!(provider, "registry.terraform.io/hashicorp/aws");
+        assert_eq!(db.version, 2);
+        let password = &db.block.attributes["password"];
+        assert!(password.sensitive);
+        assert_eq!(
+            db.block.attributes["tags"].attribute_type,
+            Some(serde_json::json!(["map", "string"]))
+        );
+        assert!(db.block.attributes["arn"].computed);
+
+        let (_, bucket) = schemas.data_source("aws_s3_bucket").unwrap();
+        assert!(bucket.block.attributes["bucket"].required);
+        assert!(schemas.resource("aws_s3_bucket").is_none());
+        assert!(schemas.data_source("aws_db_instance").is_none());
+    }
+
+    #[test]
+    fn test_invalid_schema_json_is_an_error() {
+        let err = ProviderSchemas::from_json("not json").unwrap_err();
+        assert!(err.to_string().contains("Failed to parse provider schemas"));
+    }
+}
@@ -0,0 +1,337 @@
+//! Resource arguments checked against warmed provider schemas.
+//!
+//! `warm_provider_schemas` keeps the output of `terraform providers schema
+//! -json` for the project. Once warmed, `validate_terraform_detailed` reports
+//! top-level arguments and blocks of `resource` and `data` blocks that the
+//! schema does not define, with the closest defined name as a hint. Types
+//! from providers missing in the schemas are not checked.
+
+use crate::terraform::analyzer::hcl_lines;
+use crate::terraform::schema::{ProviderSchemas, SchemaBlock};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+static BLOCK_HEADER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(resource|data)\s+"([^"]+)"\s+"([^"]+)"\s*\{"#)
+        .expect("Invalid block header regex")
+});
+
+/// `name = ...` or `name {` at the start of a line
+static ARGUMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*([A-Za-z_][A-Za-z0-9_-]*)\s*(=|\{)"#).expect("Invalid argument regex")
+});
+
+static DYNAMIC_BLOCK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*dynamic\s+"([^"]+)"\s*\{"#).expect("Invalid dynamic block regex")
+});
+
+/// Meta-arguments Terraform accepts in every resource block
+const RESOURCE_META_ARGUMENTS: &[&str] = &[
+    "count",
+    "for_each",
+    "provider",
+    "depends_on",
+    "lifecycle",
+    "provisioner",
+    "connection",
+];
+
+/// Meta-arguments Terraform accepts in every data block
+const DATA_META_ARGUMENTS: &[&str] = &["count", "for_each", "provider", "depends_on", "lifecycle"];
+
+/// An argument or block the provider schema does not define
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnknownArgument {
+    /// `aws_s3_bucket.logs` or `data.aws_ami.ubuntu`
+    pub address: String,
+    pub argument: String,
+    pub file: String,
+    pub line: usize,
+    /// Closest argument the schema defines, when one is close enough
+    pub suggestion: Option<String>,
+}
+
+/// Summary returned by `warm_provider_schemas`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmedSchemas {
+    pub project_directory: String,
+    pub providers: Vec<String>,
+    pub resource_types: usize,
+    pub data_source_types: usize,
+    pub warmed_at: DateTime<Utc>,
+}
+
+impl WarmedSchemas {
+    fn new(project_directory: &Path, schemas: &ProviderSchemas) -> Self {
+        Self {
+            project_directory: project_directory.display().to_string(),
+            providers: schemas.provider_schemas.keys().cloned().collect(),
+            resource_types: schemas
+                .provider_schemas
+                .values()
+                .map(|p| p.resource_schemas.len())
+                .sum(),
+            data_source_types: schemas
+                .provider_schemas
+                .values()
+                .map(|p| p.data_source_schemas.len())
+                .sum(),
+            warmed_at: Utc::now(),
+        }
+    }
+}
+
+/// Session-wide provider schemas keyed by project directory
+#[derive(Default)]
+pub struct SchemaCache {
+    schemas: Mutex<HashMap<PathBuf, Arc<ProviderSchemas>>>,
+}
+
+impl SchemaCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store the schemas of `project_directory`, replacing earlier ones
+    pub fn insert(&self, project_directory: &Path, schemas: ProviderSchemas) -> WarmedSchemas {
+        let warmed = WarmedSchemas::new(project_directory, &schemas);
+        self.schemas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(project_directory.to_path_buf(), Arc::new(schemas));
+        warmed
+    }
+
+    pub fn get(&self, project_directory: &Path) -> Option<Arc<ProviderSchemas>> {
+        self.schemas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(project_directory)
+            .cloned()
+    }
+}
+
+/// Arguments of `resource` and `data` blocks in `file_contents` that their
+/// schema does not define
+pub fn find_unknown_arguments(
+    file_contents: &HashMap<String, String>,
+    schemas: &ProviderSchemas,
+) -> Vec<UnknownArgument> {
+    let mut files: Vec<_> = file_contents.iter().collect();
+    files.sort_by_key(|(file, _)| file.as_str());
+
+    let mut unknown = Vec::new();
+    for (file, content) in files {
+        // Address, schema and meta-arguments of the block being read
+        let mut current: Option<(String, &SchemaBlock, &[&str])> = None;
+        for line in hcl_lines(content) {
+            if line.depth == 0 {
+                current = BLOCK_HEADER_REGEX.captures(line.text).and_then(|cap| {
+                    let (kind, resource_type, name) = (&cap[1], &cap[2], &cap[3]);
+                    if kind == "data" {
+                        schemas.data_source(resource_type).map(|(_, schema)| {
+                            (
+                                format!("data.{}.{}", resource_type, name),
+                                &schema.block,
+                                DATA_META_ARGUMENTS,
+                            )
+                        })
+                    } else {
+                        schemas.resource(resource_type).map(|(_, schema)| {
+                            (
+                                format!("{}.{}", resource_type, name),
+                                &schema.block,
+                                RESOURCE_META_ARGUMENTS,
+                            )
+                        })
+                    }
+                });
+            } else if line.depth == 1 {
+                if let Some((address, block, meta)) = &current {
+                    let argument = DYNAMIC_BLOCK_REGEX
+                        .captures(line.text)
+                        .or_else(|| ARGUMENT_REGEX.captures(line.text))
+                        .map(|cap| cap[1].to_string());
+                    if let Some(argument) = argument {
+                        if !meta.contains(&argument.as_str()) && !defines(block, &argument) {
+                            unknown.push(UnknownArgument {
+                                address: address.clone(),
+                                suggestion: closest_argument(block, &argument),
+                                argument,
+                                file: file.clone(),
+                                line: line.number,
+                            });
+                        }
+                    }
+                }
+            }
+
+            if line.depth_after == 0 {
+                current = None;
+            }
+        }
+    }
+    unknown
+}
+
+/// Whether `block` defines `name` as an attribute or nested block; some
+/// providers accept list-of-object attributes written as blocks
+fn defines(block: &SchemaBlock, name: &str) -> bool {
+    block.attributes.contains_key(name) || block.block_types.contains_key(name)
+}
+
+fn closest_argument(block: &SchemaBlock, argument: &str) -> Option<String> {
+    let max_distance = (argument.len() / 3).max(2);
+    block
+        .attributes
+        .keys()
+        .chain(block.block_types.keys())
+        .map(|name| (edit_distance(name, argument), name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, name)| name.clone())
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            row.push(substitution.min(previous[j + 1] + 1).min(row[j] + 1));
+        }
+        previous = row;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trimmed from `terraform providers schema -json` for hashicorp/aws 5.100.0
+    const SAMPLE_SCHEMA: &str = r#"{
+  "format_version": "1.0",
+  "provider_schemas": {
+    "registry.terraform.io/hashicorp/aws": {
+      "resource_schemas": {
+        "aws_s3_bucket": {
+          "version": 0,
+          "block": {
+            "attributes": {
+              "arn": {"type": "string", "computed": true},
+              "bucket": {"type": "string", "optional": true, "computed": true},
+              "force_destroy": {"type": "bool", "optional": true},
+              "tags": {"type": ["map", "string"], "optional": true}
+            },
+            "block_types": {
+              "timeouts": {"nesting_mode": "single", "block": {"attributes": {"create": {"type": "string", "optional": true}}}}
+            }
+          }
+        }
+      },
+      "data_source_schemas": {
+        "aws_ami": {
+          "version": 0,
+          "block": {
+            "attributes": {
+              "most_recent": {"type": "bool", "optional": true},
+              "owners": {"type": ["list", "string"], "optional": true}
+            },
+            "block_types": {
+              "filter": {"nesting_mode": "set", "block": {"attributes": {"name": {"type": "string", "required": true}}}}
+            }
+          }
+        }
+      }
+    }
+  }
+}"#;
+
+    #[test]
+    fn test_flags_typoed_argument() {
+        let schemas = ProviderSchemas::from_json(SAMPLE_SCHEMA).unwrap();
+        let mut files = HashMap::new();
+        files.insert(
+            "main.tf".to_string(),
+            r#"
+resource "aws_s3_bucket" "logs" {
+  bukcet        = "logs"
+  force_destroy = true
+  count         = 1
+
+  tags = {
+    Team = "platform"
+  }
+
+  timeouts {
+    create = "5m"
+  }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+data "aws_ami" "ubuntu" {
+  most_recent = true
+  owner       = ["099720109477"]
+
+  filter {
+    name   = "name"
+    values = ["ubuntu/*"]
+  }
+}
+
+resource "google_storage_bucket" "unchecked" {
+  nmae = "not in the warmed schemas"
+}
+"#
+            .to_string(),
+        );
+
+        let unknown = find_unknown_arguments(&files, &schemas);
+        assert_eq!(
+            unknown,
+            vec![
+                UnknownArgument {
+                    address: "aws_s3_bucket.logs".to_string(),
+                    argument: "bukcet".to_string(),
+                    file: "main.tf".to_string(),
+                    line: 3,
+                    suggestion: Some("bucket".to_string()),
+                },
+                UnknownArgument {
+                    address: "data.aws_ami.ubuntu".to_string(),
+                    argument: "owner".to_string(),
+                    file: "main.tf".to_string(),
+                    line: 22,
+                    suggestion: Some("owners".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_cache_is_per_project() {
+        let cache = SchemaCache::new();
+        let schemas = ProviderSchemas::from_json(SAMPLE_SCHEMA).unwrap();
+        let warmed = cache.insert(Path::new("/work/app"), schemas);
+        assert_eq!(
+            warmed.providers,
+            vec!["registry.terraform.io/hashicorp/aws"]
+        );
+        assert_eq!((warmed.resource_types, warmed.data_source_types), (1, 1));
+
+        assert!(cache.get(Path::new("/work/app")).is_some());
+        assert!(cache.get(Path::new("/work/other")).is_none());
+        assert_eq!(edit_distance("bukcet", "bucket"), 2);
+        assert_eq!(edit_distance("owner", "owners"), 1);
+    }
+}
@@ -1,18 +1,54 @@
+use crate::shared::process::ProcessTracker;
 use crate::shared::security::SecurityManager;
+use crate::terraform::analysis_cache::{AnalysisCache, content_key};
 use crate::terraform::analyzer;
+use crate::terraform::approval::ApprovalStore;
+use crate::terraform::error::{TerraformError, is_init_required};
+use crate::terraform::file_cache::{FileCache, ParsedFile};
 use crate::terraform::model::{
-    DetailedValidationResult, GuidelineCheckResult, ModuleHealthAnalysis, RefactoringSuggestion,
-    ResourceDependencyGraph, TerraformAnalysis, TerraformValidateOutput,
+    AggregateAnalysis, DefinitionLookup, DetailedValidationResult, DirectoryAnalysisSummary,
+    FileDiagnostics, GuidelineCheckResult, ImpactAnalysis, ModuleHealthAnalysis,
+    MultiDirectoryAnalysis, RefactoringSuggestion, ResourceDependencyGraph, ResourceSubgraph,
+    TerraformAnalysis, TerraformValidateOutput,
 };
 use crate::terraform::parser::TerraformParser;
-use std::collections::HashMap;
+use crate::terraform::plan_snapshot::PlanSnapshotStore;
+use crate::terraform::schema_hints::{SchemaCache, WarmedSchemas};
+use crate::terraform::version::BinaryFlavor;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output};
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct TerraformService {
     terraform_path: PathBuf,
     project_directory: PathBuf,
     security_manager: SecurityManager,
+    file_cache: Arc<FileCache>,
+    /// Module health, dependency graph and refactoring results for the
+    /// current configuration contents
+    analysis_cache: Arc<AnalysisCache>,
+    processes: ProcessTracker,
+    plan_snapshots: Arc<PlanSnapshotStore>,
+    /// Provider schemas warmed by `warm_provider_schemas`
+    schema_cache: Arc<SchemaCache>,
+    /// Passed to terraform as `TF_CLI_CONFIG_FILE` when set
+    cli_config_file: Option<PathBuf>,
+    /// Held while a state-mutating command runs, so a second apply, destroy,
+    /// import, refresh or taint waits for the first instead of racing it
+    state_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Outstanding `propose_apply` tokens
+    approvals: Arc<ApprovalStore>,
+    /// Pass `<workspace>.tfvars` to plan and apply when it exists
+    auto_tfvars_by_workspace: bool,
+    /// Pass `-chdir=<project>` instead of running terraform in the project directory
+    use_chdir: bool,
+    /// Binary flavor and version, detected on the first command
+    binary: Arc<tokio::sync::OnceCell<Option<(BinaryFlavor, String)>>>,
+    /// Passed to terraform as `TF_WORKSPACE` when set, in place of the
+    /// workspace selected in the project
+    workspace: Option<String>,
 }
 
 impl TerraformService {
@@ -34,6 +70,18 @@ impl TerraformService {
             terraform_path,
             project_directory,
             security_manager,
+            file_cache: Arc::new(FileCache::new()),
+            analysis_cache: Arc::new(AnalysisCache::new()),
+            processes: ProcessTracker::new(),
+            plan_snapshots: Arc::new(PlanSnapshotStore::new()),
+            schema_cache: Arc::new(SchemaCache::new()),
+            cli_config_file: None,
+            state_lock: Arc::new(tokio::sync::Mutex::new(())),
+            approvals: Arc::new(ApprovalStore::from_env()),
+            auto_tfvars_by_workspace: false,
+            use_chdir: false,
+            binary: Arc::new(tokio::sync::OnceCell::new()),
+            workspace: None,
         }
     }
 
@@ -43,6 +91,8 @@ impl TerraformService {
             directory.display()
         );
         self.project_directory = directory;
+        self.file_cache.clear();
+        self.analysis_cache.clear();
         Ok(())
     }
 
@@ -50,11 +100,64 @@ impl TerraformService {
         &self.project_directory
     }
 
+    pub fn get_terraform_path(&self) -> &PathBuf {
+        &self.terraform_path
+    }
+
+    /// Use `path` as terraform's CLI config (`TF_CLI_CONFIG_FILE`) for commands run in the project
+    pub fn set_cli_config_file(&mut self, path: Option<PathBuf>) {
+        self.cli_config_file = path;
+    }
+
+    pub fn set_auto_tfvars_by_workspace(&mut self, enabled: bool) {
+        self.auto_tfvars_by_workspace = enabled;
+    }
+
+    /// Select the project with `terraform -chdir=<project>` rather than by
+    /// running terraform with the project as its working directory
+    pub fn set_use_chdir(&mut self, enabled: bool) {
+        self.use_chdir = enabled;
+    }
+
+    /// The CLI config terraform uses when run by this service
+    pub fn get_cli_config(&self) -> super::cli_config::CliConfigInfo {
+        super::cli_config::describe_cli_config(self.cli_config_file.as_deref())
+    }
+
+    /// `terraform` run in the project directory with the project's CLI config
+    /// and, when one is set, the service's workspace
+    fn project_command(&self) -> Command {
+        let mut cmd = Command::new(&self.terraform_path);
+        match self.chdir_arg() {
+            Some(chdir) => cmd.arg(chdir),
+            None => cmd.current_dir(&self.project_directory),
+        };
+        if let Some(path) = &self.cli_config_file {
+            cmd.env(super::cli_config::CLI_CONFIG_ENV, path);
+        }
+        if let Some(workspace) = &self.workspace {
+            cmd.env(super::workspace::WORKSPACE_ENV, workspace);
+        }
+        cmd
+    }
+
+    /// Tracker of running terraform commands, cancelled on server shutdown
+    pub fn process_tracker(&self) -> &ProcessTracker {
+        &self.processes
+    }
+
+    /// Cache of file contents and parse results shared by the analysis tools
+    #[allow(dead_code)]
+    pub fn file_cache(&self) -> &FileCache {
+        &self.file_cache
+    }
+
     pub async fn get_version(&self) -> anyhow::Result<String> {
         let output = Command::new(&self.terraform_path)
             .arg("version")
             .arg("-json")
-            .output()?;
+            .output()
+            .map_err(|e| TerraformError::from_io(self.terraform_path.clone(), e))?;
 
         let output_str = String::from_utf8_lossy(&output.stdout);
 
@@ -66,7 +169,10 @@ impl TerraformService {
         }
 
         // Fallback to non-JSON output
-        let output = Command::new(&self.terraform_path).arg("version").output()?;
+        let output = Command::new(&self.terraform_path)
+            .arg("version")
+            .output()
+            .map_err(|e| TerraformError::from_io(self.terraform_path.clone(), e))?;
 
         let version_output = String::from_utf8_lossy(&output.stdout);
         let version_line = version_output
@@ -77,57 +183,352 @@ impl TerraformService {
         Ok(version_line.to_string())
     }
 
-    pub async fn init(&self) -> anyhow::Result<String> {
-        let output = Command::new(&self.terraform_path)
+    /// Get the installed binary flavor/version and check it against the
+    /// configuration's `required_version` constraint
+    pub async fn get_version_info(&self) -> anyhow::Result<super::version::VersionInfo> {
+        eprintln!(
+            "[DEBUG] Checking terraform version for {}",
+            self.project_directory.display()
+        );
+
+        let (flavor, version) = super::version::detect_version(&self.terraform_path).await?;
+        let file_contents = hcl_only(self.read_file_contents().await?);
+
+        let mut files: Vec<_> = file_contents
+            .iter()
+            .filter(|(name, _)| !name.contains('/'))
+            .collect();
+        files.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut required_version = None;
+        let mut provider_constraints: Vec<super::version::ProviderConstraint> = Vec::new();
+        for (_, content) in files {
+            let parser = TerraformParser::new(content.clone());
+            if required_version.is_none() {
+                required_version = parser.parse_required_version();
+            }
+            for provider in parser.parse_providers() {
+                if !provider_constraints.iter().any(|p| p.name == provider.name) {
+                    provider_constraints.push(super::version::ProviderConstraint {
+                        name: provider.name,
+                        constraint: provider.version,
+                    });
+                }
+            }
+        }
+        provider_constraints.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut warnings = Vec::new();
+        let satisfies_required_version = required_version.as_deref().and_then(|constraint| {
+            let compat = super::version::check_compatibility(Some(&version), constraint);
+            if compat.satisfied != Some(true) {
+                warnings.push(compat.message);
+            }
+            compat.satisfied
+        });
+
+        Ok(super::version::VersionInfo {
+            flavor,
+            version,
+            required_version,
+            satisfies_required_version,
+            provider_constraints,
+            warnings,
+        })
+    }
+
+    /// Run `terraform init` with any `extra_args` the security policy allows
+    pub async fn init(&self, extra_args: &[String]) -> anyhow::Result<String> {
+        self.check_extra_args("init", extra_args)?;
+        let output = self
+            .project_command()
             .arg("init")
-            .current_dir(&self.project_directory)
-            .output()?;
+            .args(extra_args)
+            .output()
+            .map_err(|e| TerraformError::from_io(self.terraform_path.clone(), e))?;
+        self.audit_extra_args("init", extra_args, &output);
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Terraform init failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(TerraformError::from_output("init", &output).into())
         }
     }
 
-    pub async fn get_plan(&self) -> anyhow::Result<String> {
-        let output = Command::new(&self.terraform_path)
-            .arg("plan")
-            .arg("-json")
-            .current_dir(&self.project_directory)
-            .output()?;
+    /// Resolve `path` relative to the project root, refusing anything that
+    /// escapes it. A file resolves to its containing directory; `None`
+    /// resolves to the project root itself.
+    pub fn resolve_project_path(&self, path: Option<&str>) -> anyhow::Result<PathBuf> {
+        let Some(path) = path else {
+            return Ok(self.project_directory.clone());
+        };
+
+        let resolved = self.resolve_in_project(path)?;
+        if !resolved.exists() {
+            return Err(anyhow::anyhow!("Invalid path '{}': not found", path));
+        }
+
+        if resolved.is_file() {
+            Ok(resolved.parent().map(Path::to_path_buf).unwrap_or(resolved))
+        } else {
+            Ok(resolved)
+        }
+    }
+
+    /// Resolve a file path inside the project root, refusing `..`, absolute
+    /// and symlinked paths that escape it
+    fn resolve_in_project(&self, path: &str) -> anyhow::Result<PathBuf> {
+        self.security_manager
+            .resolve_in_project(&self.project_directory, path)
+            .map_err(|e| TerraformError::PolicyBlocked(e.to_string()).into())
+    }
+
+    /// Display form of a directory relative to the project root ("." for the root)
+    fn relative_path(&self, dir: &Path) -> String {
+        let root = self
+            .project_directory
+            .canonicalize()
+            .unwrap_or_else(|_| self.project_directory.clone());
+        match dir.strip_prefix(&root) {
+            Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+            Ok(relative) => relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => ".".to_string(),
+        }
+    }
+
+    /// A service rooted at `directory` sharing this service's binary and security policy
+    fn scoped_to(&self, directory: PathBuf) -> Self {
+        Self {
+            terraform_path: self.terraform_path.clone(),
+            project_directory: directory,
+            security_manager: SecurityManager {
+                policy: self.security_manager.get_policy().clone(),
+                audit_log: None,
+            },
+            file_cache: self.file_cache.clone(),
+            analysis_cache: self.analysis_cache.clone(),
+            processes: self.processes.clone(),
+            plan_snapshots: self.plan_snapshots.clone(),
+            schema_cache: self.schema_cache.clone(),
+            cli_config_file: self.cli_config_file.clone(),
+            state_lock: self.state_lock.clone(),
+            approvals: self.approvals.clone(),
+            auto_tfvars_by_workspace: self.auto_tfvars_by_workspace,
+            use_chdir: self.use_chdir,
+            binary: self.binary.clone(),
+            workspace: self.workspace.clone(),
+        }
+    }
+
+    /// Run a terraform subcommand in the project directory, handling the
+    /// "init required" case. When `auto_init` is set, `terraform init` is run
+    /// once and the command retried; otherwise a friendly error is returned.
+    async fn run_with_init_check(&self, args: &[&str], auto_init: bool) -> anyhow::Result<Output> {
+        let output = self.run_tracked(args).await?;
+
+        if output.status.success() || !is_init_required(&String::from_utf8_lossy(&output.stderr)) {
+            return Ok(output);
+        }
+
+        if !auto_init {
+            return Err(TerraformError::InitRequired.into());
+        }
+
+        eprintln!(
+            "[INFO] Terraform initialization required, running 'terraform init' in {}",
+            self.project_directory.display()
+        );
+        let init_output = self.run_tracked(&["init", "-input=false"]).await?;
+
+        if !init_output.status.success() {
+            return Err(TerraformError::from_output("init", &init_output).into());
+        }
+
+        self.run_tracked(args).await
+    }
+
+    /// Run a terraform subcommand in the project directory; the child is
+    /// killed and reaped if the server shuts down while it is running
+    async fn run_tracked(&self, args: &[&str]) -> anyhow::Result<Output> {
+        self.processes
+            .output(&mut self.tracked_command(args).await)
+            .await
+            .map_err(|e| TerraformError::from_io(self.terraform_path.clone(), e).into())
+    }
+
+    /// [`run_tracked`](Self::run_tracked) with owned arguments, as the
+    /// helper modules build them
+    async fn run_tracked_args(&self, args: &[String]) -> anyhow::Result<Output> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.run_tracked(&args).await
+    }
+
+    /// [`run_tracked`](Self::run_tracked), passing each stdout line to `on_line` as it is written
+    async fn run_tracked_streaming(
+        &self,
+        args: &[&str],
+        on_line: &mut (dyn FnMut(&str) + Send),
+    ) -> anyhow::Result<Output> {
+        self.processes
+            .output_streaming(&mut self.tracked_command(args).await, on_line)
+            .await
+            .map_err(|e| TerraformError::from_io(self.terraform_path.clone(), e).into())
+    }
+
+    async fn tracked_command(&self, args: &[&str]) -> tokio::process::Command {
+        let mut args = super::version::flavor_args(self.binary().await, args);
+        if self.auto_tfvars_by_workspace {
+            let workspace = self
+                .workspace
+                .clone()
+                .unwrap_or_else(|| super::workspace::selected_workspace(&self.project_directory));
+            if let Some(file) =
+                super::workspace::workspace_var_file(&self.project_directory, &workspace, &args)
+            {
+                eprintln!(
+                    "[INFO] Using {} for workspace '{}' (auto_tfvars_by_workspace)",
+                    file, workspace
+                );
+                // Options go before a positional plan file
+                args.insert(1, format!("-var-file={}", file));
+            }
+        }
+        let mut cmd = tokio::process::Command::from(self.project_command());
+        cmd.args(args);
+        cmd
+    }
+
+    /// `-chdir=<project>` when `use_chdir` is set. It is always the project
+    /// directory (or a directory resolved inside it), exactly as the working
+    /// directory would otherwise be, so neither mode reaches outside the root.
+    fn chdir_arg(&self) -> Option<String> {
+        self.use_chdir
+            .then(|| format!("-chdir={}", self.project_directory.display()))
+    }
+
+    /// Flavor and version of the binary, or `None` if `version` output was not recognized
+    async fn binary(&self) -> Option<&(BinaryFlavor, String)> {
+        self.binary
+            .get_or_init(|| async {
+                super::version::detect_version(&self.terraform_path)
+                    .await
+                    .map_err(|e| eprintln!("[WARN] Failed to detect terraform version: {}", e))
+                    .ok()
+            })
+            .await
+            .as_ref()
+    }
+
+    /// Run `terraform plan -json` with any `extra_args` the security policy allows
+    pub async fn get_plan(&self, auto_init: bool, extra_args: &[String]) -> anyhow::Result<String> {
+        self.check_extra_args("plan", extra_args)?;
+        let mut args = vec!["plan", "-json"];
+        args.extend(extra_args.iter().map(String::as_str));
+        let output = self.run_with_init_check(&args, auto_init).await?;
+        self.audit_extra_args("plan", extra_args, &output);
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            if stderr.contains("terraform init") {
-                Err(anyhow::anyhow!(
-                    "Terraform initialization required. Please run 'terraform init' first."
-                ))
-            } else {
-                Err(anyhow::anyhow!("Terraform plan failed: {}", stderr))
+            Err(TerraformError::from_output("plan", &output).into())
+        }
+    }
+
+    /// Run `terraform plan -json`, passing each JSON message to `on_message`
+    /// as terraform emits it, and return the analysis of the whole plan
+    pub async fn get_plan_streamed(
+        &self,
+        auto_init: bool,
+        extra_args: &[String],
+        policy: Option<&super::plan_analyzer::RiskPolicy>,
+        on_message: &mut (dyn FnMut(serde_json::Value) + Send),
+    ) -> anyhow::Result<super::plan_analyzer::PlanAnalysis> {
+        self.check_extra_args("plan", extra_args)?;
+        let mut args = vec!["plan", "-json", "-input=false"];
+        args.extend(extra_args.iter().map(String::as_str));
+        let mut forward = |line: &str| {
+            if let Ok(message) = serde_json::from_str(line) {
+                on_message(message);
+            }
+        };
+
+        let mut output = self.run_tracked_streaming(&args, &mut forward).await?;
+        if !output.status.success() && is_init_required(&String::from_utf8_lossy(&output.stderr)) {
+            if !auto_init {
+                return Err(TerraformError::InitRequired.into());
+            }
+            let init_output = self.run_tracked(&["init", "-input=false"]).await?;
+            if !init_output.status.success() {
+                return Err(TerraformError::from_output("init", &init_output).into());
             }
+            output = self.run_tracked_streaming(&args, &mut forward).await?;
         }
+        self.audit_extra_args("plan", extra_args, &output);
+        if !output.status.success() {
+            return Err(TerraformError::from_output("plan", &output).into());
+        }
+
+        super::plan_analyzer::analyze_plan(&String::from_utf8_lossy(&output.stdout), true, policy)
     }
 
-    pub async fn apply(&self, auto_approve: bool) -> anyhow::Result<String> {
+    /// Preview what `terraform destroy` would remove via `plan -destroy`.
+    /// Read-only, so it is not gated by the dangerous-operations policy.
+    pub async fn plan_destroy(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<super::plan_analyzer::DestroyPreview> {
+        eprintln!(
+            "[DEBUG] Previewing terraform destroy in {}",
+            self.project_directory.display()
+        );
+
+        let output = self
+            .run_with_init_check(&["plan", "-destroy", "-json", "-input=false"], auto_init)
+            .await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("plan -destroy", &output).into());
+        }
+
+        super::plan_analyzer::summarize_destroy_plan(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Run `terraform apply` with any `extra_args` the security policy allows.
+    /// When `TFMCP_MAX_APPLY_DESTROYS` is set and `confirm_destructive` is
+    /// not, the plan is written to a file under the state lock and refused if
+    /// it deletes or replaces more resources than that; otherwise exactly that
+    /// plan file is applied, so the apply cannot differ from what was counted.
+    /// `extra_args` then go to the plan. Without `auto_approve`, terraform
+    /// asks to confirm its own plan as before, once the check passes.
+    pub async fn apply(
+        &self,
+        auto_approve: bool,
+        auto_init: bool,
+        extra_args: &[String],
+        confirm_destructive: bool,
+    ) -> anyhow::Result<String> {
         // Security checks
         if !self.security_manager.is_command_allowed("apply") {
-            return Err(anyhow::anyhow!(
+            return Err(TerraformError::PolicyBlocked(
                 "Apply operation blocked by security policy. Set TFMCP_ALLOW_DANGEROUS_OPS=true to enable."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
         if auto_approve && !self.security_manager.is_auto_approve_allowed("apply") {
-            return Err(anyhow::anyhow!(
+            return Err(TerraformError::PolicyBlocked(
                 "Auto-approve for apply operation blocked by security policy. Set TFMCP_ALLOW_AUTO_APPROVE=true to enable."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
+        self.check_extra_args("apply", extra_args)?;
+
         // Validate directory security
         self.security_manager
             .validate_directory(&self.project_directory)?;
@@ -138,15 +539,39 @@ impl TerraformService {
                 .check_resource_limit(resources.len())?;
         }
 
-        let mut cmd = Command::new(&self.terraform_path);
-        cmd.arg("apply");
+        let mut command_args = vec!["terraform".to_string(), "apply".to_string()];
+        command_args.extend(extra_args.iter().cloned());
+        let _state_guard = self.state_lock.lock().await;
+        let _operation = self.processes.begin_operation();
 
-        if auto_approve {
-            cmd.arg("-auto-approve");
-        }
-
-        let command_args = vec!["terraform".to_string(), "apply".to_string()];
-        let output = cmd.current_dir(&self.project_directory).output()?;
+        let checked_plan = if self.destroy_limit_applies(confirm_destructive) {
+            Some(self.plan_checked_apply(auto_init, extra_args).await?)
+        } else {
+            None
+        };
+        let plan_arg = checked_plan
+            .as_ref()
+            .map(|plan| plan.path.to_string_lossy().to_string());
+        let args: Vec<&str> = match &plan_arg {
+            Some(plan_arg) if auto_approve => vec!["apply", "-input=false", plan_arg],
+            _ => {
+                let mut args = vec!["apply"];
+                if auto_approve {
+                    args.push("-auto-approve");
+                }
+                args.extend(extra_args.iter().map(String::as_str));
+                args
+            }
+        };
+        let result = self.run_with_init_check(&args, auto_init).await;
+        drop(checked_plan);
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                self.log_interrupted("apply", &command_args, &e);
+                return Err(e);
+            }
+        };
         let success = output.status.success();
 
         // Log audit entry
@@ -178,19 +603,364 @@ impl TerraformService {
         if success {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Terraform apply failed: {}",
-                String::from_utf8_lossy(&output.stderr)
+            Err(TerraformError::from_output("apply", &output).into())
+        }
+    }
+
+    /// Whether `TFMCP_MAX_APPLY_DESTROYS` has to be checked before applying
+    fn destroy_limit_applies(&self, confirm_destructive: bool) -> bool {
+        self.security_manager
+            .get_policy()
+            .max_apply_destroys
+            .is_some()
+            && !confirm_destructive
+    }
+
+    /// Refuse applying the plan file at `plan_arg` when it deletes or
+    /// replaces more resources than `TFMCP_MAX_APPLY_DESTROYS`
+    async fn check_plan_file_destroys(&self, plan_arg: &str) -> anyhow::Result<()> {
+        let output = self.run_tracked(&["show", "-json", plan_arg]).await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("show", &output).into());
+        }
+        let destroyed = super::plan_analyzer::analyze_plan(
+            &String::from_utf8_lossy(&output.stdout),
+            false,
+            None,
+        )?
+        .destructive_addresses();
+        self.security_manager
+            .check_apply_destroys(&destroyed, false)
+            .map_err(|e| TerraformError::PolicyBlocked(e.to_string()).into())
+    }
+
+    /// Write the plan for [`apply`](Self::apply) to a temporary plan file and
+    /// check its destroys. The caller holds the state lock.
+    async fn plan_checked_apply(
+        &self,
+        auto_init: bool,
+        extra_args: &[String],
+    ) -> anyhow::Result<TemporaryPlan> {
+        let plan = TemporaryPlan {
+            path: self.resolve_in_project(&format!(
+                "{}{}.tfplan",
+                APPLY_PLAN_PREFIX,
+                chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+            ))?,
+        };
+        let out_arg = format!("-out={}", plan.path.to_string_lossy());
+        let mut args = vec!["plan", "-input=false", &out_arg];
+        args.extend(extra_args.iter().map(String::as_str));
+        let output = self.run_with_init_check(&args, auto_init).await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("plan", &output).into());
+        }
+        self.check_plan_file_destroys(&plan.path.to_string_lossy())
+            .await?;
+        Ok(plan)
+    }
+
+    /// Run `terraform plan -out` into `plan_file` and record the file's hash
+    /// so [`apply_saved_plan`](Self::apply_saved_plan) can verify it
+    pub async fn save_plan(
+        &self,
+        plan_file: Option<&str>,
+        auto_init: bool,
+    ) -> anyhow::Result<super::saved_plan::SavedPlan> {
+        let plan_path = self.resolve_plan_path(plan_file)?;
+        let plan_file = plan_file.unwrap_or(super::saved_plan::DEFAULT_PLAN_FILE);
+        if let Some(parent) = plan_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        eprintln!(
+            "[DEBUG] Saving plan to {} in {}",
+            plan_file,
+            self.project_directory.display()
+        );
+
+        let out_arg = format!("-out={}", plan_path.to_string_lossy());
+        let output = self
+            .run_with_init_check(&["plan", "-input=false", &out_arg], auto_init)
+            .await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("plan", &output).into());
+        }
+
+        let plan_arg = plan_path.to_string_lossy().to_string();
+        let output = self.run_tracked(&["show", "-json", &plan_arg]).await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("show", &output).into());
+        }
+        let analysis = super::plan_analyzer::analyze_plan(
+            &String::from_utf8_lossy(&output.stdout),
+            false,
+            None,
+        )?;
+
+        let saved = super::saved_plan::SavedPlan {
+            plan_file: plan_file.to_string(),
+            sha256: super::saved_plan::file_sha256(&plan_path)?,
+            saved_at: chrono::Utc::now(),
+            project_directory: self.project_directory.to_string_lossy().to_string(),
+            summary: analysis.summary,
+        };
+        super::saved_plan::record(&plan_path, &saved)?;
+        eprintln!("[INFO] Saved plan {} (sha256 {})", plan_file, saved.sha256);
+        Ok(saved)
+    }
+
+    /// Apply a plan file written by [`save_plan`](Self::save_plan), refusing
+    /// when its hash no longer matches the recorded one or, as for
+    /// [`apply`](Self::apply), when it destroys more than
+    /// `TFMCP_MAX_APPLY_DESTROYS` without `confirm_destructive`
+    pub async fn apply_saved_plan(
+        &self,
+        plan_file: Option<&str>,
+        confirm_destructive: bool,
+    ) -> anyhow::Result<super::saved_plan::SavedPlanApply> {
+        let plan_path = self.resolve_plan_path(plan_file)?;
+        let plan_file = plan_file.unwrap_or(super::saved_plan::DEFAULT_PLAN_FILE);
+
+        // Verify first: a changed plan is refused whatever the security policy says
+        let saved = super::saved_plan::verify(&plan_path, plan_file)?;
+
+        if !self.security_manager.is_command_allowed("apply") {
+            return Err(TerraformError::PolicyBlocked(
+                "Apply operation blocked by security policy. Set TFMCP_ALLOW_DANGEROUS_OPS=true to enable."
+                    .to_string(),
+            )
+            .into());
+        }
+        self.security_manager
+            .validate_directory(&self.project_directory)?;
+
+        let warnings: Vec<String> = super::saved_plan::config_modified_since(
+            &self.project_directory,
+            saved.saved_at.into(),
+        )
+        .into_iter()
+        .map(|file| {
+            format!(
+                "{} was modified after the plan was saved; the applied plan does not include those changes",
+                file
+            )
+        })
+        .collect();
+        for warning in &warnings {
+            eprintln!("[WARN] {}", warning);
+        }
+
+        let plan_arg = plan_path.to_string_lossy().to_string();
+        let command_args = vec![
+            "terraform".to_string(),
+            "apply".to_string(),
+            plan_file.to_string(),
+        ];
+        let _state_guard = self.state_lock.lock().await;
+        let _operation = self.processes.begin_operation();
+        if self.destroy_limit_applies(confirm_destructive) {
+            self.check_plan_file_destroys(&plan_arg).await?;
+        }
+        let output = match self
+            .run_with_init_check(&["apply", "-input=false", &plan_arg], false)
+            .await
+        {
+            Ok(output) => output,
+            Err(e) => {
+                self.log_interrupted("apply", &command_args, &e);
+                return Err(e);
+            }
+        };
+        let success = output.status.success();
+
+        let audit_entry = self.security_manager.create_audit_entry(
+            "apply",
+            &self.project_directory.to_string_lossy(),
+            &command_args,
+            success,
+            (!success).then(|| String::from_utf8_lossy(&output.stderr).to_string()),
+            None,
+        );
+        if let Err(e) = self.security_manager.log_audit_entry(audit_entry) {
+            eprintln!("[WARN] Failed to log audit entry: {}", e);
+        }
+
+        if !success {
+            return Err(TerraformError::from_output("apply", &output).into());
+        }
+        Ok(super::saved_plan::SavedPlanApply {
+            plan_file: saved.plan_file,
+            sha256: saved.sha256,
+            saved_at: saved.saved_at,
+            warnings,
+            output: String::from_utf8_lossy(&output.stdout).to_string(),
+        })
+    }
+
+    /// Save a plan for review and issue a single-use token that
+    /// [`confirm_apply`](Self::confirm_apply) must present to apply it
+    pub async fn propose_apply(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<super::approval::ApplyProposal> {
+        // Refuse before planning: a proposal that can never be confirmed wastes a review
+        if !self.security_manager.is_command_allowed("apply") {
+            return Err(TerraformError::PolicyBlocked(
+                "Apply operation blocked by security policy. Set TFMCP_ALLOW_DANGEROUS_OPS=true to enable."
+                    .to_string(),
+            )
+            .into());
+        }
+        for expired in self.approvals.prune_expired() {
+            self.remove_proposal(&expired);
+        }
+
+        let plan_file = format!(
+            "{}{}.tfplan",
+            super::approval::PROPOSAL_PLAN_PREFIX,
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        );
+        let saved = self.save_plan(Some(&plan_file), auto_init).await?;
+        let token = self.approvals.issue(super::approval::Approval {
+            project_directory: self.project_directory.clone(),
+            plan_file: saved.plan_file.clone(),
+            sha256: saved.sha256.clone(),
+        });
+        Ok(super::approval::ApplyProposal {
+            token,
+            expires_at: saved.saved_at + chrono::Duration::from_std(self.approvals.ttl())?,
+            plan_file: saved.plan_file,
+            sha256: saved.sha256,
+            summary: saved.summary,
+        })
+    }
+
+    /// Apply the plan saved by [`propose_apply`](Self::propose_apply) for
+    /// `token`. The token is consumed and the plan file removed either way.
+    pub async fn confirm_apply(
+        &self,
+        token: &str,
+        confirm_destructive: bool,
+    ) -> anyhow::Result<super::saved_plan::SavedPlanApply> {
+        let approval = match self.approvals.redeem(token) {
+            Ok(approval) => approval,
+            Err(e) => {
+                if let super::approval::ApprovalError::Expired(approval) = &e {
+                    self.remove_proposal(approval);
+                }
+                return Err(TerraformError::ApprovalRejected(e.to_string()).into());
+            }
+        };
+        if approval.project_directory != self.project_directory {
+            return Err(TerraformError::ApprovalRejected(format!(
+                "Approval token was issued for {}, but the project directory is now {}",
+                approval.project_directory.display(),
+                self.project_directory.display()
             ))
+            .into());
         }
+
+        let plan_path = self.resolve_plan_path(Some(&approval.plan_file))?;
+        let result = match super::saved_plan::file_sha256(&plan_path) {
+            Ok(actual) if actual != approval.sha256 => Err(TerraformError::PlanFileChanged {
+                plan_file: approval.plan_file.clone(),
+                expected: approval.sha256.clone(),
+                actual,
+            }
+            .into()),
+            Ok(_) => {
+                self.apply_saved_plan(Some(&approval.plan_file), confirm_destructive)
+                    .await
+            }
+            Err(e) => Err(anyhow::anyhow!(
+                "Failed to read plan file '{}': {}",
+                approval.plan_file,
+                e
+            )),
+        };
+        self.remove_proposal(&approval);
+        result
     }
 
-    pub async fn get_state(&self) -> anyhow::Result<String> {
-        let output = Command::new(&self.terraform_path)
-            .arg("state")
-            .arg("list")
-            .current_dir(&self.project_directory)
-            .output()?;
+    /// Plan file path inside the project root: relative, without `..`, and
+    /// not reached through a symlink that leaves the project
+    fn resolve_plan_path(&self, plan_file: Option<&str>) -> anyhow::Result<PathBuf> {
+        super::saved_plan::resolve_plan_path(&self.project_directory, plan_file)?;
+        self.resolve_in_project(plan_file.unwrap_or(super::saved_plan::DEFAULT_PLAN_FILE))
+    }
+
+    /// Delete the plan file and sidecar written for a proposal
+    fn remove_proposal(&self, approval: &super::approval::Approval) {
+        if let Ok(plan_path) = super::saved_plan::resolve_plan_path(
+            &approval.project_directory,
+            Some(&approval.plan_file),
+        ) {
+            super::saved_plan::remove(&plan_path);
+        }
+    }
+
+    /// Refuse `extra_args` the security policy does not allow for `command`
+    fn check_extra_args(&self, command: &str, extra_args: &[String]) -> anyhow::Result<()> {
+        self.security_manager
+            .check_extra_args(&self.project_directory, command, extra_args)
+            .map_err(|e| TerraformError::PolicyBlocked(e.to_string()).into())
+    }
+
+    /// Record a run given caller-supplied `extra_args` in the audit log
+    fn audit_extra_args(&self, command: &str, extra_args: &[String], output: &Output) {
+        if extra_args.is_empty() {
+            return;
+        }
+        let mut command_args = vec!["terraform".to_string(), command.to_string()];
+        command_args.extend(extra_args.iter().cloned());
+        let success = output.status.success();
+        let audit_entry = self.security_manager.create_audit_entry(
+            command,
+            &self.project_directory.to_string_lossy(),
+            &command_args,
+            success,
+            (!success).then(|| String::from_utf8_lossy(&output.stderr).to_string()),
+            None,
+        );
+        if let Err(e) = self.security_manager.log_audit_entry(audit_entry) {
+            eprintln!("[WARN] Failed to log audit entry: {}", e);
+        }
+    }
+
+    /// Record a dangerous operation that failed before terraform completed,
+    /// e.g. because the server shut down mid-run
+    fn log_interrupted(&self, operation: &str, command_args: &[String], error: &anyhow::Error) {
+        let audit_entry = self.security_manager.create_audit_entry(
+            operation,
+            &self.project_directory.to_string_lossy(),
+            command_args,
+            false,
+            Some(error.to_string()),
+            None,
+        );
+        if let Err(e) = self.security_manager.log_audit_entry(audit_entry) {
+            eprintln!("[WARN] Failed to log audit entry: {}", e);
+        }
+    }
+
+    /// List resources in the state of `workspace` (or the current workspace),
+    /// returning the workspace name alongside the listing
+    pub async fn get_state(
+        &self,
+        auto_init: bool,
+        workspace: Option<&str>,
+    ) -> anyhow::Result<(Option<String>, String)> {
+        self.in_workspace(workspace, |service| async move {
+            service.state_list(auto_init).await
+        })
+        .await
+    }
+
+    /// `terraform state list` output, or `(no state)` when there is no state
+    async fn state_list(&self, auto_init: bool) -> anyhow::Result<String> {
+        let output = self
+            .run_with_init_check(&["state", "list"], auto_init)
+            .await?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -199,31 +969,76 @@ impl TerraformService {
             if stderr.contains("No state file") || stderr.contains("no state") {
                 return Ok("(no state)".to_string());
             }
-            Err(anyhow::anyhow!("Failed to get Terraform state: {}", stderr))
+            Err(TerraformError::from_stderr("state list", &stderr).into())
+        }
+    }
+
+    /// Run `op` on a copy of the service whose commands use `workspace`, or
+    /// the current workspace when none is given.
+    ///
+    /// The workspace reaches terraform as `TF_WORKSPACE` rather than being
+    /// selected, so the project's selection never changes, even briefly, and
+    /// concurrent tools keep seeing it. Returns the name of the workspace `op`
+    /// ran in; without an explicit workspace the current one is reported when
+    /// it can be determined.
+    async fn in_workspace<T, F, Fut>(
+        &self,
+        workspace: Option<&str>,
+        op: F,
+    ) -> anyhow::Result<(Option<String>, T)>
+    where
+        F: FnOnce(Self) -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<T>>,
+    {
+        let mut service = self.scoped_to(self.project_directory.clone());
+        let Some(target) = workspace else {
+            let current = self.current_workspace().await;
+            return Ok((current.ok(), op(service).await?));
+        };
+
+        let output = self.run_tracked(&["workspace", "list"]).await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("workspace list", &output).into());
+        }
+        let workspaces =
+            super::workspace::parse_workspace_list(&String::from_utf8_lossy(&output.stdout));
+        if !workspaces.iter().any(|w| w.name == target) {
+            return Err(anyhow::anyhow!("Workspace '{}' does not exist", target));
+        }
+
+        service.workspace = Some(target.to_string());
+        Ok((Some(target.to_string()), op(service).await?))
+    }
+
+    /// Workspace the service's commands run in
+    async fn current_workspace(&self) -> anyhow::Result<String> {
+        let output = self.run_tracked(&["workspace", "show"]).await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("workspace show", &output).into());
         }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 
     #[allow(dead_code)]
     pub async fn refresh(&self) -> anyhow::Result<String> {
-        let output = Command::new(&self.terraform_path)
+        let _state_guard = self.state_lock.lock().await;
+        let output = self
+            .project_command()
             .arg("refresh")
-            .current_dir(&self.project_directory)
-            .output()?;
+            .output()
+            .map_err(|e| TerraformError::from_io(self.terraform_path.clone(), e))?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Terraform refresh failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(TerraformError::from_output("refresh", &output).into())
         }
     }
 
     #[allow(dead_code)]
     pub async fn create_terraform_configuration(&self, content: &str) -> anyhow::Result<String> {
         // Write the content to a main.tf file in the project directory
-        let file_path = self.project_directory.join("main.tf");
+        let file_path = self.resolve_in_project("main.tf")?;
         std::fs::write(&file_path, content)?;
 
         Ok(format!(
@@ -234,7 +1049,14 @@ impl TerraformService {
 
     #[allow(dead_code)]
     pub async fn read_terraform_file(&self, filename: &str) -> anyhow::Result<String> {
-        let file_path = self.project_directory.join(filename);
+        let file_path = self.resolve_in_project(filename)?;
+        if self.security_manager.is_file_blocked(&file_path) {
+            return Err(TerraformError::PolicyBlocked(format!(
+                "Reading {} is blocked by security policy",
+                filename
+            ))
+            .into());
+        }
         match std::fs::read_to_string(&file_path) {
             Ok(content) => Ok(content),
             Err(e) => Err(anyhow::anyhow!(
@@ -246,11 +1068,7 @@ impl TerraformService {
     }
 
     pub async fn list_resources(&self) -> anyhow::Result<Vec<String>> {
-        let output = Command::new(&self.terraform_path)
-            .arg("state")
-            .arg("list")
-            .current_dir(&self.project_directory)
-            .output()?;
+        let output = self.run_with_init_check(&["state", "list"], false).await?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -258,7 +1076,7 @@ impl TerraformService {
             if stderr.contains("No state file") || stderr.contains("no state") {
                 return Ok(Vec::new());
             }
-            return Err(anyhow::anyhow!("Failed to list resources: {}", stderr));
+            return Err(TerraformError::from_stderr("state list", &stderr).into());
         }
 
         let resources = String::from_utf8_lossy(&output.stdout)
@@ -270,27 +1088,97 @@ impl TerraformService {
         Ok(resources)
     }
 
-    pub async fn validate(&self) -> anyhow::Result<String> {
-        let output = Command::new(&self.terraform_path)
-            .arg("validate")
-            .arg("-json")
-            .current_dir(&self.project_directory)
-            .output()?;
+    #[allow(dead_code)]
+    pub async fn validate(&self, auto_init: bool) -> anyhow::Result<String> {
+        let output = self
+            .run_with_init_check(&["validate", "-json"], auto_init)
+            .await?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(TerraformError::from_output("validate", &output).into())
+        }
+    }
+
+    /// Run `terraform validate -json`, returning the diagnostics even when the
+    /// configuration is invalid (validate exits non-zero in that case)
+    async fn validate_diagnostics(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<TerraformValidateOutput> {
+        let output = self
+            .run_with_init_check(&["validate", "-json"], auto_init)
+            .await?;
+
+        match serde_json::from_slice::<TerraformValidateOutput>(&output.stdout) {
+            Ok(validation) => Ok(validation),
+            Err(e) if output.status.success() => Err(e.into()),
+            Err(_) => Err(TerraformError::from_output("validate", &output).into()),
+        }
+    }
+
+    /// Validate the configuration and, only if it is valid, plan it
+    pub async fn preflight(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<super::plan_analyzer::PreflightResult> {
+        use super::plan_analyzer::PreflightResult;
+
+        eprintln!(
+            "[DEBUG] Running preflight in {}",
+            self.project_directory.display()
+        );
+
+        let validation = self.validate_diagnostics(auto_init).await?;
+        if !validation.valid {
+            return Ok(PreflightResult::invalid(validation));
+        }
+
+        // validate already ran init if it was needed
+        let plan_json = self.get_plan(false, &[]).await?;
+        let plan = super::plan_analyzer::analyze_plan(&plan_json, false, None)?;
+        Ok(PreflightResult::planned(validation, plan))
+    }
+
+    /// Run `terraform validate -json` in `path` (or the project root),
+    /// returning the validated directory relative to the root along with the
+    /// parsed result; an invalid configuration is a result, not an error
+    pub async fn validate_path(
+        &self,
+        path: Option<&str>,
+        auto_init: bool,
+    ) -> anyhow::Result<(String, TerraformValidateOutput)> {
+        if path.is_none() {
+            return Ok((".".to_string(), self.validate_diagnostics(auto_init).await?));
+        }
+        let target = self.resolve_project_path(path)?;
+        let validation = self
+            .scoped_to(target.clone())
+            .validate_diagnostics(auto_init)
+            .await?;
+        Ok((self.relative_path(&target), validation))
+    }
 
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            Err(anyhow::anyhow!(
-                "Terraform validate failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+    pub async fn validate_detailed(
+        &self,
+        path: Option<&str>,
+    ) -> anyhow::Result<DetailedValidationResult> {
+        if path.is_none() {
+            return self.validate_detailed_in_place().await;
         }
+        let target = self.resolve_project_path(path)?;
+        let mut result = self
+            .scoped_to(target.clone())
+            .validate_detailed_in_place()
+            .await?;
+        result.validated_path = self.relative_path(&target);
+        Ok(result)
     }
 
-    pub async fn validate_detailed(&self) -> anyhow::Result<DetailedValidationResult> {
+    async fn validate_detailed_in_place(&self) -> anyhow::Result<DetailedValidationResult> {
         // Run terraform validate with JSON output
-        let validate_json = self.validate().await?;
-        let validate_output: TerraformValidateOutput = serde_json::from_str(&validate_json)?;
+        let validate_output = self.validate_diagnostics(false).await?;
 
         // Additional validation checks
         let mut warnings = Vec::new();
@@ -306,7 +1194,7 @@ impl TerraformService {
         // Analyze configuration for best practices
         if !tf_files.is_empty() {
             let analysis = self.analyze_configurations().await?;
-            let file_contents = self.read_file_contents().await?;
+            let file_contents = hcl_only(self.read_file_contents().await?);
 
             // Run Future Architect guideline checks
             let checks = analyzer::check_guidelines(&analysis, &file_contents);
@@ -354,6 +1242,15 @@ impl TerraformService {
                 ));
             }
 
+            for undeclared in &checks.undeclared_providers {
+                warnings.push(format!(
+                    "[Providers] Provider '{}' is used by {} in {} but not declared in required_providers",
+                    undeclared.provider,
+                    undeclared.resources.join(", "),
+                    undeclared.module
+                ));
+            }
+
             if checks.missing_default_tags {
                 warnings.push(
                     "[Guideline] AWS provider is missing default_tags configuration".to_string(),
@@ -374,6 +1271,21 @@ impl TerraformService {
                 ));
             }
 
+            // Cross-check arguments when the provider schemas have been warmed
+            if let Some(schemas) = self.schema_cache.get(&self.project_directory) {
+                for unknown in super::schema_hints::find_unknown_arguments(&file_contents, &schemas)
+                {
+                    let hint = unknown
+                        .suggestion
+                        .map(|name| format!(" (did you mean '{}'?)", name))
+                        .unwrap_or_default();
+                    warnings.push(format!(
+                        "[Schema] {} has no argument '{}' at {}:{}{}",
+                        unknown.address, unknown.argument, unknown.file, unknown.line, hint
+                    ));
+                }
+            }
+
             // Check for hardcoded values that should be variables
             for resource in &analysis.resources {
                 if resource.provider == "aws" && resource.resource_type.contains("instance") {
@@ -391,11 +1303,13 @@ impl TerraformService {
             valid: validate_output.valid,
             error_count: validate_output.error_count,
             warning_count: validate_output.warning_count + warnings.len() as i32,
+            diagnostics_by_file: FileDiagnostics::group(&validate_output.diagnostics),
             diagnostics: validate_output.diagnostics,
             additional_warnings: warnings,
             suggestions,
             checked_files: tf_files.len(),
             guideline_checks,
+            validated_path: ".".to_string(),
         })
     }
 
@@ -406,13 +1320,9 @@ impl TerraformService {
         for entry in entries {
             let entry = entry?;
             let path = entry.path();
-            if path.is_file() {
-                if let Some(ext) = path.extension() {
-                    if ext == "tf" || ext == "tf.json" {
-                        if let Some(name) = path.file_name() {
-                            tf_files.push(name.to_string_lossy().to_string());
-                        }
-                    }
+            if path.is_file() && is_config_file(&path) {
+                if let Some(name) = path.file_name() {
+                    tf_files.push(name.to_string_lossy().to_string());
                 }
             }
         }
@@ -420,33 +1330,50 @@ impl TerraformService {
         Ok(tf_files)
     }
 
+    /// List project `.tf`/`.tfvars` files, omitting paths blocked by the security policy
+    pub fn list_project_files(&self) -> anyhow::Result<Vec<super::files::ProjectFile>> {
+        super::files::list_project_files(&self.project_directory, |path| {
+            self.security_manager.is_file_blocked(path)
+        })
+    }
+
     pub async fn destroy(&self, auto_approve: bool) -> anyhow::Result<String> {
         // Security checks
         if !self.security_manager.is_command_allowed("destroy") {
-            return Err(anyhow::anyhow!(
+            return Err(TerraformError::PolicyBlocked(
                 "Destroy operation blocked by security policy. Set TFMCP_ALLOW_DANGEROUS_OPS=true to enable."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
         if auto_approve && !self.security_manager.is_auto_approve_allowed("destroy") {
-            return Err(anyhow::anyhow!(
+            return Err(TerraformError::PolicyBlocked(
                 "Auto-approve for destroy operation blocked by security policy. Set TFMCP_ALLOW_AUTO_APPROVE=true to enable."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
         // Validate directory security
         self.security_manager
             .validate_directory(&self.project_directory)?;
 
-        let mut cmd = Command::new(&self.terraform_path);
-        cmd.arg("destroy");
-
+        let mut args = vec!["destroy"];
         if auto_approve {
-            cmd.arg("-auto-approve");
+            args.push("-auto-approve");
         }
 
         let command_args = vec!["terraform".to_string(), "destroy".to_string()];
-        let output = cmd.current_dir(&self.project_directory).output()?;
+        let _state_guard = self.state_lock.lock().await;
+        let _operation = self.processes.begin_operation();
+        let output = match self.run_tracked(&args).await {
+            Ok(output) => output,
+            Err(e) => {
+                self.log_interrupted("destroy", &command_args, &e);
+                return Err(e);
+            }
+        };
         let success = output.status.success();
 
         // Log audit entry
@@ -472,10 +1399,7 @@ impl TerraformService {
         if success {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Terraform destroy failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(TerraformError::from_output("destroy", &output).into())
         }
     }
 
@@ -492,16 +1416,20 @@ impl TerraformService {
             ));
         }
 
-        // Find all .tf files in the project directory
-        let entries = std::fs::read_dir(&self.project_directory)?;
-        let mut tf_files = Vec::new();
-
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() && path.extension().is_some_and(|ext| ext == "tf") {
-                eprintln!("[DEBUG] Found Terraform file: {}", path.display());
-                tf_files.push(path);
-            }
+        // Find all .tf files in the project directory. Files that resolve
+        // outside the project root, such as a symlinked `main.tf`, are skipped
+        let mut tf_files = tf_files_in(&self.project_directory).await?;
+        tf_files.retain(
+            |path| match self.resolve_in_project(&path.to_string_lossy()) {
+                Ok(_) => true,
+                Err(e) => {
+                    eprintln!("[WARN] Skipping {}: {}", path.display(), e);
+                    false
+                }
+            },
+        );
+        for path in &tf_files {
+            eprintln!("[DEBUG] Found Terraform file: {}", path.display());
         }
 
         if tf_files.is_empty() {
@@ -522,16 +1450,81 @@ impl TerraformService {
             variables: Vec::new(),
             outputs: Vec::new(),
             providers: Vec::new(),
+            required_version: None,
+            version_compatibility: None,
+            remote_state_dependencies: Vec::new(),
+            state_encryption: None,
         };
 
-        // Parse each file to identify resources, variables, outputs
-        for file_path in tf_files {
-            eprintln!("[DEBUG] Analyzing file: {}", file_path.display());
-            match self.analyze_file(&file_path, &mut analysis) {
-                Ok(_) => eprintln!("[DEBUG] Successfully analyzed {}", file_path.display()),
-                Err(e) => eprintln!("[ERROR] Failed to analyze {}: {}", file_path.display(), e),
+        // Read all files concurrently, then merge their declarations in file
+        // order so the result doesn't depend on which read finished first
+        let parsed_files: Vec<_> = self
+            .file_cache
+            .parse_all(&tf_files)
+            .await
+            .into_iter()
+            .zip(&tf_files)
+            .filter_map(|(parsed, file_path)| match parsed {
+                Ok(parsed) => Some(parsed),
+                Err(e) => {
+                    eprintln!("[ERROR] Failed to analyze {}: {}", file_path.display(), e);
+                    None
+                }
+            })
+            .collect();
+        for parsed in &parsed_files {
+            self.analyze_file(parsed, &mut analysis);
+        }
+
+        // Remote state outputs may be read from any file, so link them once all
+        // data sources are known
+        for parsed in &parsed_files {
+            for (name, output) in &parsed.remote_state_references {
+                if let Some(dependency) = analysis
+                    .remote_state_dependencies
+                    .iter_mut()
+                    .find(|d| &d.name == name)
+                {
+                    if !dependency.outputs.contains(output) {
+                        dependency.outputs.push(output.clone());
+                    }
+                }
+            }
+        }
+        for dependency in &mut analysis.remote_state_dependencies {
+            dependency.outputs.sort();
+        }
+
+        if let Some(constraint) = &analysis.required_version {
+            let installed = super::version::detect_version(&self.terraform_path)
+                .await
+                .map_err(|e| eprintln!("[WARN] Failed to detect terraform version: {}", e))
+                .ok()
+                .map(|(_, version)| version);
+            let compat = super::version::check_compatibility(installed.as_deref(), constraint);
+            if compat.satisfied == Some(false) {
+                eprintln!("[WARN] {}", compat.message);
+            }
+            analysis.version_compatibility = Some(compat);
+        }
+
+        // Effective variable values from auto-loaded tfvars files
+        let mut tfvars = Vec::new();
+        for path in super::tfvars::auto_loaded_files(&self.project_directory) {
+            let parsed = self
+                .read_project_file(&path)
+                .and_then(|content| super::tfvars::parse_tfvars_file(&path, &content));
+            match parsed {
+                Ok(values) => {
+                    let file = path.file_name().unwrap_or_default().to_string_lossy();
+                    tfvars.push((file.to_string(), values));
+                }
+                Err(e) => eprintln!("[WARN] {}", e),
             }
         }
+        super::tfvars::resolve_effective_values(&mut analysis.variables, &tfvars, |name| {
+            std::env::var_os(format!("TF_VAR_{}", name)).is_some()
+        });
 
         eprintln!(
             "[INFO] Terraform analysis complete: found {} resources, {} variables, {} outputs, {} providers",
@@ -544,67 +1537,67 @@ impl TerraformService {
         Ok(analysis)
     }
 
-    fn analyze_file(
-        &self,
-        file_path: &Path,
-        analysis: &mut TerraformAnalysis,
-    ) -> anyhow::Result<()> {
-        eprintln!("[DEBUG] Reading file: {}", file_path.display());
-        let content = match std::fs::read_to_string(file_path) {
-            Ok(content) => content,
-            Err(e) => {
-                eprintln!("[ERROR] Failed to read file {}: {}", file_path.display(), e);
-                return Err(anyhow::anyhow!("Failed to read file: {}", e));
-            }
-        };
-
-        let file_name = file_path.file_name().unwrap_or_default().to_string_lossy();
-        let parser = TerraformParser::new(content);
-
-        // Parse resources
-        eprintln!("[DEBUG] Parsing resources in {}", file_path.display());
-        let resources = parser.parse_resources(&file_name);
-        for resource in &resources {
+    /// Merge the declarations of one parsed file into `analysis`
+    fn analyze_file(&self, parsed: &ParsedFile, analysis: &mut TerraformAnalysis) {
+        for resource in &parsed.resources {
             eprintln!(
                 "[DEBUG] Found resource: {} ({})",
                 resource.name, resource.resource_type
             );
         }
-        analysis.resources.extend(resources);
+        analysis.resources.extend(parsed.resources.iter().cloned());
 
-        // Parse variables
-        eprintln!("[DEBUG] Parsing variables in {}", file_path.display());
-        let variables = parser.parse_variables();
-        for variable in &variables {
+        for variable in &parsed.variables {
             eprintln!("[DEBUG] Found variable: {}", variable.name);
         }
-        analysis.variables.extend(variables);
+        analysis.variables.extend(parsed.variables.iter().cloned());
 
-        // Parse outputs
-        eprintln!("[DEBUG] Parsing outputs in {}", file_path.display());
-        let outputs = parser.parse_outputs();
-        for output in &outputs {
+        for output in &parsed.outputs {
             eprintln!("[DEBUG] Found output: {}", output.name);
         }
-        analysis.outputs.extend(outputs);
+        analysis.outputs.extend(parsed.outputs.iter().cloned());
+
+        for remote_state in &parsed.remote_states {
+            eprintln!("[DEBUG] Found remote state: {}", remote_state.name);
+        }
+        analysis
+            .remote_state_dependencies
+            .extend(parsed.remote_states.iter().cloned());
+
+        if analysis.required_version.is_none() {
+            analysis.required_version = parsed.required_version.clone();
+        }
+        if analysis.state_encryption.is_none() {
+            if let Some(encryption) = &parsed.state_encryption {
+                eprintln!(
+                    "[DEBUG] Found OpenTofu encryption block (state encrypted: {})",
+                    encryption.state_encrypted
+                );
+                analysis.state_encryption = Some(encryption.clone());
+            }
+        }
 
-        // Parse providers
-        eprintln!("[DEBUG] Parsing providers in {}", file_path.display());
-        let providers = parser.parse_providers();
-        for provider in providers {
-            // Check if provider already exists
-            if !analysis.providers.iter().any(|p| p.name == provider.name) {
+        for provider in parsed.providers.iter().cloned() {
+            // Merge aliases declared across files into the existing provider entry
+            if let Some(existing) = analysis
+                .providers
+                .iter_mut()
+                .find(|p| p.name == provider.name)
+            {
+                for alias in provider.aliases {
+                    if !existing.aliases.contains(&alias) {
+                        existing.aliases.push(alias);
+                    }
+                }
+                existing.aliases.sort();
+            } else {
                 eprintln!("[DEBUG] Found provider: {}", provider.name);
                 analysis.providers.push(provider);
             }
         }
-
-        eprintln!("[DEBUG] Completed analysis of {}", file_path.display());
-        Ok(())
     }
 
     /// Get current security policy for debugging/reporting
-    #[allow(dead_code)]
     pub fn get_security_policy(&self) -> &crate::shared::security::SecurityPolicy {
         self.security_manager.get_policy()
     }
@@ -615,61 +1608,165 @@ impl TerraformService {
         self.security_manager.is_command_allowed(operation)
     }
 
+    /// The last `count` audit log entries, with secrets in arguments redacted
+    pub fn recent_audit_entries(
+        &self,
+        count: usize,
+    ) -> anyhow::Result<Vec<crate::shared::security::AuditLogEntry>> {
+        self.security_manager.recent_audit_entries(count)
+    }
+
     // ==================== Module Health Analysis Methods ====================
 
-    /// Read all Terraform file contents from the project directory
+    /// Read all Terraform file contents from the project directory and its
+    /// nested `modules/`, keyed by path relative to the project root
     async fn read_file_contents(&self) -> anyhow::Result<HashMap<String, String>> {
-        let mut file_contents = HashMap::new();
-        let entries = std::fs::read_dir(&self.project_directory)?;
+        let mut files: Vec<(String, PathBuf)> = tf_files_in(&self.project_directory)
+            .await?
+            .into_iter()
+            .filter_map(|path| Some((path.file_name()?.to_string_lossy().to_string(), path)))
+            .collect();
 
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() && path.extension().is_some_and(|ext| ext == "tf") {
-                if let Some(filename) = path.file_name() {
-                    let filename_str = filename.to_string_lossy().to_string();
-                    if let Ok(content) = std::fs::read_to_string(&path) {
-                        file_contents.insert(filename_str, content);
-                    }
+        // Also check for nested modules
+        let mut pending = vec![(
+            self.project_directory.join("modules"),
+            "modules".to_string(),
+        )];
+        while let Some((dir, prefix)) = pending.pop() {
+            let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                let Ok(metadata) = tokio::fs::metadata(&path).await else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    pending.push((path, format!("{}/{}", prefix, name)));
+                } else if metadata.is_file() && is_config_file(&path) {
+                    files.push((format!("{}/{}", prefix, name), path));
                 }
             }
         }
 
-        // Also check for nested modules
-        let modules_dir = self.project_directory.join("modules");
-        if modules_dir.exists() && modules_dir.is_dir() {
-            Self::read_nested_modules(&modules_dir, "modules", &mut file_contents)?;
-        }
-
-        Ok(file_contents)
+        // Files that resolve outside the project root, such as a symlinked
+        // `.tf` file, are skipped
+        let (keys, paths): (Vec<String>, Vec<PathBuf>) = files
+            .into_iter()
+            .filter_map(|(key, path)| {
+                let path = self.resolve_in_project(&path.to_string_lossy()).ok()?;
+                Some((key, path))
+            })
+            .unzip();
+        let contents = self.file_cache.read_all(&paths).await;
+
+        Ok(keys
+            .into_iter()
+            .zip(contents)
+            .filter_map(|(key, content)| Some((key, content.ok()?.to_string())))
+            .collect())
     }
 
-    /// Recursively read nested module contents
-    fn read_nested_modules(
-        dir: &Path,
-        prefix: &str,
-        file_contents: &mut HashMap<String, String>,
-    ) -> anyhow::Result<()> {
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    let submodule_name = path
+    /// Content of the project's README.md, matched case-insensitively
+    fn read_readme(&self) -> Option<String> {
+        std::fs::read_dir(&self.project_directory)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| {
+                path.is_file()
+                    && path
                         .file_name()
-                        .map(|n| n.to_string_lossy().to_string())
-                        .unwrap_or_default();
-                    let new_prefix = format!("{}/{}", prefix, submodule_name);
-                    Self::read_nested_modules(&path, &new_prefix, file_contents)?;
-                } else if path.is_file() && path.extension().is_some_and(|ext| ext == "tf") {
-                    if let Some(filename) = path.file_name() {
-                        let key = format!("{}/{}", prefix, filename.to_string_lossy());
-                        if let Ok(content) = std::fs::read_to_string(&path) {
-                            file_contents.insert(key, content);
-                        }
-                    }
-                }
-            }
+                        .is_some_and(|name| name.eq_ignore_ascii_case("readme.md"))
+            })
+            .filter(|path| !self.security_manager.is_file_blocked(path))
+            .and_then(|path| self.read_project_file(&path).ok())
+            .map(|content| content.to_string())
+    }
+
+    /// Cached content of a file found in the project, refusing one that
+    /// resolves outside the project root, such as a symlinked `.tf` file
+    fn read_project_file(&self, path: &Path) -> anyhow::Result<std::sync::Arc<String>> {
+        let path = self.resolve_in_project(&path.to_string_lossy())?;
+        Ok(self.file_cache.read(&path)?)
+    }
+
+    /// Check local module calls against the modules' declared variables and
+    /// outputs, returning the module files read along the way by path so
+    /// they can be part of the analysis cache key
+    fn find_module_wiring_issues(
+        &self,
+        file_contents: &HashMap<String, String>,
+    ) -> (
+        Vec<crate::terraform::model::ModuleWiringIssue>,
+        BTreeMap<PathBuf, String>,
+    ) {
+        let loaded = std::cell::RefCell::new(BTreeMap::new());
+        let issues = analyzer::find_module_wiring_issues(file_contents, |module_dir| {
+            let entries = std::fs::read_dir(self.project_directory.join(module_dir)).ok()?;
+            let module_files = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "tf"))
+                .filter(|path| !self.security_manager.is_file_blocked(path))
+                .filter_map(|path| {
+                    let content = self.read_project_file(&path).ok()?;
+                    let name = path.file_name()?.to_string_lossy().to_string();
+                    loaded.borrow_mut().insert(path, content.to_string());
+                    Some((name, content.to_string()))
+                })
+                .collect();
+            Some(module_files)
+        });
+        (issues, loaded.into_inner())
+    }
+
+    /// Analyze every directory under the project root that contains `.tf`
+    /// files and matches `pattern`, up to `max_directories`
+    pub async fn analyze_all(
+        &self,
+        pattern: &str,
+        max_directories: usize,
+    ) -> anyhow::Result<MultiDirectoryAnalysis> {
+        let root = self.project_directory.canonicalize()?;
+        let (paths, truncated) = super::files::find_terraform_directories(
+            &root,
+            pattern,
+            |path| self.security_manager.is_file_blocked(path),
+            max_directories,
+        )?;
+        if truncated {
+            eprintln!(
+                "[WARN] More than {} directories match '{}'; the rest are skipped",
+                max_directories, pattern
+            );
         }
-        Ok(())
+
+        let mut directories = Vec::new();
+        for path in paths {
+            let analysis = self
+                .scoped_to(root.join(&path))
+                .analyze_configurations()
+                .await;
+            directories.push(match analysis {
+                Ok(analysis) => DirectoryAnalysisSummary::from_analysis(path, &analysis),
+                Err(e) => DirectoryAnalysisSummary::failed(path, e.to_string()),
+            });
+        }
+
+        eprintln!(
+            "[INFO] Analyzed {} directories matching '{}'",
+            directories.len(),
+            pattern
+        );
+
+        Ok(MultiDirectoryAnalysis {
+            pattern: pattern.to_string(),
+            aggregate: AggregateAnalysis::from_directories(&directories),
+            directories,
+            truncated,
+        })
     }
 
     /// Analyze module health based on whitebox principles
@@ -680,10 +1777,32 @@ impl TerraformService {
             self.project_directory.display()
         );
 
-        let analysis = self.analyze_configurations().await?;
         let file_contents = self.read_file_contents().await?;
+        let readme = self.read_readme();
+        let hcl_files = hcl_only(file_contents.clone());
+        let (module_wiring, module_files) = self.find_module_wiring_issues(&hcl_files);
+        let key = content_key(
+            &self.project_directory,
+            &file_contents,
+            (
+                readme.as_deref(),
+                &module_files,
+                crate::terraform::categories::active(),
+            ),
+        );
+        if let Some(health) = self.analysis_cache.module_health(key) {
+            eprintln!("[DEBUG] Configuration unchanged, reusing module health analysis");
+            return Ok(health);
+        }
+        let file_contents = hcl_files;
 
-        let health = analyzer::analyze_module_health(&analysis, &file_contents);
+        let analysis = self.analyze_configurations().await?;
+        let health = analyzer::analyze_module_health(
+            &analysis,
+            &file_contents,
+            &module_wiring,
+            readme.as_deref(),
+        );
 
         eprintln!(
             "[INFO] Module health analysis complete: score={}, issues={}",
@@ -691,6 +1810,7 @@ impl TerraformService {
             health.issues.len()
         );
 
+        self.analysis_cache.store_module_health(key, health.clone());
         Ok(health)
     }
 
@@ -701,9 +1821,15 @@ impl TerraformService {
             self.project_directory.display()
         );
 
-        let analysis = self.analyze_configurations().await?;
         let file_contents = self.read_file_contents().await?;
+        let key = content_key(&self.project_directory, &file_contents, ());
+        if let Some(graph) = self.analysis_cache.dependency_graph(key) {
+            eprintln!("[DEBUG] Configuration unchanged, reusing dependency graph");
+            return Ok(graph);
+        }
+        let file_contents = hcl_only(file_contents);
 
+        let analysis = self.analyze_configurations().await?;
         let graph = analyzer::build_dependency_graph(&analysis, &file_contents);
 
         eprintln!(
@@ -712,9 +1838,49 @@ impl TerraformService {
             graph.edges.len()
         );
 
+        self.analysis_cache
+            .store_dependency_graph(key, graph.clone());
         Ok(graph)
     }
 
+    /// Resources downstream of `address` in the dependency graph
+    pub async fn impact_analysis(&self, address: &str) -> anyhow::Result<ImpactAnalysis> {
+        let graph = self.get_dependency_graph().await?;
+        let impact = analyzer::impact_analysis(&graph, address)?;
+        eprintln!(
+            "[INFO] Impact of {}: {} direct and {} transitive dependents",
+            address,
+            impact.direct_dependents.len(),
+            impact.transitive_dependents.len()
+        );
+        Ok(impact)
+    }
+
+    /// Files and lines declaring a resource address, variable or output
+    pub async fn locate_definition(&self, query: &str) -> anyhow::Result<DefinitionLookup> {
+        let file_contents = hcl_only(self.read_file_contents().await?);
+        let lookup = analyzer::locate_definition(&file_contents, query);
+        eprintln!(
+            "[INFO] Found {} definitions of {}",
+            lookup.definitions.len(),
+            query
+        );
+        Ok(lookup)
+    }
+
+    /// `address` with its transitive dependencies and dependents
+    pub async fn resource_subgraph(&self, address: &str) -> anyhow::Result<ResourceSubgraph> {
+        let graph = self.get_dependency_graph().await?;
+        let subgraph = analyzer::resource_subgraph(&graph, address)?;
+        eprintln!(
+            "[INFO] Subgraph of {}: {} dependencies, {} dependents",
+            address,
+            subgraph.dependencies.len(),
+            subgraph.dependents.len()
+        );
+        Ok(subgraph)
+    }
+
     /// Generate refactoring suggestions based on module health analysis
     pub async fn suggest_refactoring(&self) -> anyhow::Result<Vec<RefactoringSuggestion>> {
         eprintln!(
@@ -722,9 +1888,32 @@ impl TerraformService {
             self.project_directory.display()
         );
 
-        let analysis = self.analyze_configurations().await?;
         let file_contents = self.read_file_contents().await?;
-        let health = analyzer::analyze_module_health(&analysis, &file_contents);
+        let readme = self.read_readme();
+        let hcl_files = hcl_only(file_contents.clone());
+        let (module_wiring, module_files) = self.find_module_wiring_issues(&hcl_files);
+        let key = content_key(
+            &self.project_directory,
+            &file_contents,
+            (
+                readme.as_deref(),
+                &module_files,
+                crate::terraform::categories::active(),
+            ),
+        );
+        if let Some(suggestions) = self.analysis_cache.refactoring(key) {
+            eprintln!("[DEBUG] Configuration unchanged, reusing refactoring suggestions");
+            return Ok(suggestions);
+        }
+        let file_contents = hcl_files;
+
+        let analysis = self.analyze_configurations().await?;
+        let health = analyzer::analyze_module_health(
+            &analysis,
+            &file_contents,
+            &module_wiring,
+            readme.as_deref(),
+        );
 
         let suggestions = analyzer::suggest_refactoring(&analysis, &health);
 
@@ -733,91 +1922,395 @@ impl TerraformService {
             suggestions.len()
         );
 
+        self.analysis_cache
+            .store_refactoring(key, suggestions.clone());
         Ok(suggestions)
     }
 
-    /// Run security scan using guideline checks (secret detection, etc.)
-    pub async fn run_security_scan(&self) -> anyhow::Result<GuidelineCheckResult> {
+    /// Run security scan using guideline checks (secret detection, etc.)
+    pub async fn run_security_scan(&self) -> anyhow::Result<GuidelineCheckResult> {
+        eprintln!(
+            "[DEBUG] Running security scan in {}",
+            self.project_directory.display()
+        );
+
+        let tf_files = self.find_terraform_files().await?;
+        if tf_files.is_empty() {
+            return Ok(GuidelineCheckResult::default());
+        }
+
+        let analysis = self.analyze_configurations().await?;
+        let file_contents = hcl_only(self.read_file_contents().await?);
+
+        let checks = analyzer::check_guidelines(&analysis, &file_contents);
+
+        eprintln!(
+            "[INFO] Security scan complete: {} secrets found, compliance score: {}",
+            checks.hardcoded_secrets.len(),
+            checks.compliance_score
+        );
+
+        Ok(checks)
+    }
+
+    // ==================== New v0.1.9 Methods ====================
+
+    /// Analyze terraform plan output with risk scoring
+    pub async fn analyze_plan(
+        &self,
+        include_risk: bool,
+        auto_init: bool,
+        policy: Option<&super::plan_analyzer::RiskPolicy>,
+    ) -> anyhow::Result<super::plan_analyzer::PlanAnalysis> {
+        eprintln!(
+            "[DEBUG] Analyzing terraform plan in {}",
+            self.project_directory.display()
+        );
+
+        // Get plan JSON
+        let plan_json = self.get_plan(auto_init, &[]).await?;
+        super::plan_analyzer::analyze_plan(&plan_json, include_risk, policy)
+    }
+
+    /// Analyze a plan produced elsewhere, e.g. by an earlier CI step, without
+    /// planning again. `plan_file` is relative to the project root and holds
+    /// either JSON (`terraform show -json` or `plan -json` output) or a binary
+    /// plan file, which is rendered with `terraform show -json`.
+    pub async fn analyze_plan_file(
+        &self,
+        plan_file: &str,
+        include_risk: bool,
+        policy: Option<&super::plan_analyzer::RiskPolicy>,
+    ) -> anyhow::Result<super::plan_analyzer::PlanAnalysis> {
+        let path = self.resolve_plan_path(Some(plan_file))?;
+        if self.security_manager.is_file_blocked(&path) {
+            return Err(TerraformError::PolicyBlocked(format!(
+                "Reading {} is blocked by security policy",
+                plan_file
+            ))
+            .into());
+        }
+
+        eprintln!("[DEBUG] Analyzing plan file {}", path.display());
+        let content = std::fs::read(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read plan file '{}': {}", plan_file, e))?;
+        let plan_json = if content.trim_ascii_start().starts_with(b"{") {
+            String::from_utf8_lossy(&content).to_string()
+        } else {
+            self.show_plan_json(&path.to_string_lossy()).await?
+        };
+        super::plan_analyzer::analyze_plan(&plan_json, include_risk, policy)
+    }
+
+    /// Run a plan and store its per-resource actions under `id` (generated when omitted)
+    pub async fn save_plan_snapshot(
+        &self,
+        id: Option<&str>,
+        auto_init: bool,
+    ) -> anyhow::Result<super::plan_snapshot::PlanSnapshot> {
+        let plan = self.analyze_plan(false, auto_init, None).await?;
+        let id = id
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("plan-{}", chrono::Utc::now().format("%Y%m%dT%H%M%S%3f")));
+        let snapshot = super::plan_snapshot::PlanSnapshot::from_analysis(
+            id,
+            self.project_directory.to_string_lossy().to_string(),
+            &plan,
+        );
+        self.plan_snapshots.insert(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Compare a stored plan snapshot against a fresh plan
+    pub async fn diff_plan_snapshot(
+        &self,
+        id: &str,
+        auto_init: bool,
+    ) -> anyhow::Result<super::plan_snapshot::PlanDiff> {
+        let snapshot = self
+            .plan_snapshots
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("No plan snapshot with id '{}'", id))?;
+        let project_directory = self.project_directory.to_string_lossy().to_string();
+        if snapshot.project_directory != project_directory {
+            return Err(anyhow::anyhow!(
+                "Plan snapshot '{}' was taken in {}, not the current project {}",
+                id,
+                snapshot.project_directory,
+                project_directory
+            ));
+        }
+
+        let plan = self.analyze_plan(false, auto_init, None).await?;
+        let current = super::plan_snapshot::PlanSnapshot::from_analysis(
+            "current".to_string(),
+            project_directory,
+            &plan,
+        );
+        Ok(super::plan_snapshot::diff_snapshots(&snapshot, &current))
+    }
+
+    /// Evaluate a saved plan against Rego policies with conftest
+    pub async fn evaluate_policies(
+        &self,
+        policy_dir: Option<&Path>,
+        auto_init: bool,
+    ) -> anyhow::Result<super::conftest::PolicyEvaluation> {
+        use super::conftest::{EvaluationStatus, PolicyEvaluation};
+
+        let Some(policy_dir) = policy_dir else {
+            return Ok(PolicyEvaluation::skipped(
+                EvaluationStatus::NotConfigured,
+                None,
+                "No policy directory configured. Set TFMCP_POLICY_DIR or terraform.policy_dir in the config file.",
+            ));
+        };
+        if !policy_dir.is_dir() {
+            return Ok(PolicyEvaluation::skipped(
+                EvaluationStatus::NotConfigured,
+                Some(policy_dir),
+                "Configured policy directory does not exist",
+            ));
+        }
+        let Some(conftest_path) = super::conftest::find_conftest() else {
+            return Ok(PolicyEvaluation::skipped(
+                EvaluationStatus::ConftestNotFound,
+                Some(policy_dir),
+                "conftest was not found on PATH. Install it from https://www.conftest.dev/install/",
+            ));
+        };
+
+        eprintln!(
+            "[DEBUG] Evaluating policies in {} against plan in {}",
+            policy_dir.display(),
+            self.project_directory.display()
+        );
+
+        let plan_json = self.plan_document(auto_init).await?;
+        super::conftest::run_conftest(&conftest_path, policy_dir, &plan_json)
+    }
+
+    /// Plan into a temporary file and return its `terraform show -json`
+    /// document, which unlike `plan -json` carries attribute values
+    async fn plan_document(&self, auto_init: bool) -> anyhow::Result<String> {
+        let plan_file = tempfile::Builder::new()
+            .prefix("tfmcp-")
+            .suffix(".tfplan")
+            .tempfile()?;
+        let plan_path = plan_file.path().to_string_lossy().to_string();
+        let out_arg = format!("-out={}", plan_path);
+
+        let output = self
+            .run_with_init_check(&["plan", "-input=false", &out_arg], auto_init)
+            .await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("plan", &output).into());
+        }
+
+        self.show_plan_json(&plan_path).await
+    }
+
+    /// `terraform show -json` document of the binary plan file at `plan_path`
+    async fn show_plan_json(&self, plan_path: &str) -> anyhow::Result<String> {
+        let output = self.run_tracked(&["show", "-json", plan_path]).await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("show", &output).into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Render the plan's attribute changes as a reviewable diff
+    pub async fn format_plan_diff(&self, auto_init: bool) -> anyhow::Result<String> {
+        eprintln!(
+            "[DEBUG] Rendering plan diff in {}",
+            self.project_directory.display()
+        );
+        let plan_json = self.plan_document(auto_init).await?;
+        let plan = super::plan_analyzer::analyze_plan(&plan_json, false, None)?;
+        Ok(super::plan_diff::render_plan_diff(&plan))
+    }
+
+    /// Plan, score the risk and, when Infracost is installed, price the
+    /// change, combined into one report with an overall recommendation
+    pub async fn pre_apply_report(
+        &self,
+        auto_init: bool,
+        policy: Option<&super::plan_analyzer::RiskPolicy>,
+    ) -> anyhow::Result<super::pre_apply::PreApplyReport> {
+        eprintln!(
+            "[DEBUG] Building pre-apply report in {}",
+            self.project_directory.display()
+        );
+        let plan_json = self.plan_document(auto_init).await?;
+        let plan = super::plan_analyzer::analyze_plan(&plan_json, true, policy)?;
+
+        // Cost is optional: a missing or failing infracost leaves it out
+        let cost = super::pre_apply::find_infracost().and_then(|infracost| {
+            super::pre_apply::estimate_cost(&infracost, &plan_json)
+                .map_err(|e| eprintln!("[WARN] Cost estimation skipped: {}", e))
+                .ok()
+        });
+        Ok(super::pre_apply::PreApplyReport::new(plan, cost))
+    }
+
+    /// Analyze terraform state with optional drift detection
+    pub async fn analyze_state(
+        &self,
+        resource_type: Option<&str>,
+        detect_drift: bool,
+        auto_init: bool,
+        workspace: Option<&str>,
+    ) -> anyhow::Result<super::state_analyzer::StateAnalysis> {
+        eprintln!(
+            "[DEBUG] Analyzing terraform state in {}",
+            self.project_directory.display()
+        );
+
+        let (workspace, mut analysis) = self
+            .in_workspace(workspace, |service| async move {
+                // Get state JSON
+                let output = service
+                    .run_with_init_check(&["state", "pull"], auto_init)
+                    .await?;
+
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    if stderr.contains("No state file") || stderr.contains("no state") {
+                        return super::state_analyzer::analyze_state(
+                            "{}",
+                            resource_type,
+                            detect_drift,
+                        );
+                    }
+                    return Err(TerraformError::from_stderr("state pull", &stderr).into());
+                }
+
+                let state_json = String::from_utf8_lossy(&output.stdout);
+                // terraform state pull returns empty string when no state exists
+                if state_json.trim().is_empty() {
+                    return super::state_analyzer::analyze_state("{}", resource_type, detect_drift);
+                }
+                super::state_analyzer::analyze_state(&state_json, resource_type, detect_drift)
+            })
+            .await?;
+
+        analysis.workspace = workspace;
+        Ok(analysis)
+    }
+
+    /// Report which resource attributes in state hold secrets, without their values
+    pub async fn scan_state_for_secrets(
+        &self,
+        auto_init: bool,
+    ) -> anyhow::Result<super::state_secrets::StateSecretScan> {
         eprintln!(
-            "[DEBUG] Running security scan in {}",
+            "[DEBUG] Scanning terraform state for secrets in {}",
             self.project_directory.display()
         );
 
-        let tf_files = self.find_terraform_files().await?;
-        if tf_files.is_empty() {
-            return Ok(GuidelineCheckResult::default());
+        let output = self
+            .run_with_init_check(&["show", "-json"], auto_init)
+            .await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("show", &output).into());
         }
 
-        let analysis = self.analyze_configurations().await?;
-        let file_contents = self.read_file_contents().await?;
-
-        let checks = analyzer::check_guidelines(&analysis, &file_contents);
+        super::state_secrets::scan_state_json(&String::from_utf8_lossy(&output.stdout))
+    }
 
+    /// Resources in state whose blocks are no longer in the root module
+    /// configuration
+    pub async fn find_orphaned_resources(&self) -> anyhow::Result<super::orphans::OrphanReport> {
         eprintln!(
-            "[INFO] Security scan complete: {} secrets found, compliance score: {}",
-            checks.hardcoded_secrets.len(),
-            checks.compliance_score
+            "[DEBUG] Comparing state with configuration in {}",
+            self.project_directory.display()
         );
 
-        Ok(checks)
+        let state = self.list_resources().await?;
+        let analysis = self.analyze_configurations().await?;
+        Ok(super::orphans::find_orphaned_resources(
+            &state,
+            &analysis.resources,
+        ))
     }
 
-    // ==================== New v0.1.9 Methods ====================
+    /// Supply-chain checks on every module call's source, before any
+    /// registry lookup of publisher verification
+    pub async fn check_module_sources(
+        &self,
+    ) -> anyhow::Result<Vec<super::module_source::ModuleSourceCheck>> {
+        let file_contents = hcl_only(self.read_file_contents().await?);
+        Ok(analyzer::collect_module_sources(&file_contents)
+            .iter()
+            .map(super::module_source::ModuleSourceCheck::from_module_call)
+            .collect())
+    }
 
-    /// Analyze terraform plan output with risk scoring
-    pub async fn analyze_plan(
+    /// Resources commonly associated with high cost, judged from the
+    /// configuration without any pricing service
+    pub async fn identify_cost_drivers(
         &self,
-        include_risk: bool,
-    ) -> anyhow::Result<super::plan_analyzer::PlanAnalysis> {
+    ) -> anyhow::Result<super::cost_drivers::CostDriverReport> {
+        let file_contents = hcl_only(self.read_file_contents().await?);
+        let report = super::cost_drivers::identify_cost_drivers(&file_contents);
         eprintln!(
-            "[DEBUG] Analyzing terraform plan in {}",
-            self.project_directory.display()
+            "[INFO] Found {} cost drivers in {} resources",
+            report.drivers.len(),
+            report.resources_scanned
         );
-
-        // Get plan JSON
-        let plan_json = self.get_plan().await?;
-        super::plan_analyzer::analyze_plan(&plan_json, include_risk)
+        Ok(report)
     }
 
-    /// Analyze terraform state with optional drift detection
-    pub async fn analyze_state(
+    /// Pull the raw state with sensitive values masked, keeping only the
+    /// resources that fit in `max_bytes`
+    pub async fn pull_state(
         &self,
-        resource_type: Option<&str>,
-        detect_drift: bool,
-    ) -> anyhow::Result<super::state_analyzer::StateAnalysis> {
+        auto_init: bool,
+        max_bytes: Option<usize>,
+    ) -> anyhow::Result<super::state_pull::PulledState> {
         eprintln!(
-            "[DEBUG] Analyzing terraform state in {}",
+            "[DEBUG] Pulling terraform state in {}",
             self.project_directory.display()
         );
 
-        // Get state JSON
-        let output = Command::new(&self.terraform_path)
-            .arg("state")
-            .arg("pull")
-            .current_dir(&self.project_directory)
-            .output()?;
-
+        let output = self
+            .run_with_init_check(&["state", "pull"], auto_init)
+            .await?;
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            if stderr.contains("No state file") || stderr.contains("no state") {
-                return super::state_analyzer::analyze_state("{}", resource_type, detect_drift);
-            }
-            return Err(anyhow::anyhow!("Failed to get state: {}", stderr));
+            return Err(TerraformError::from_output("state pull", &output).into());
         }
 
-        let state_json = String::from_utf8_lossy(&output.stdout);
-        // terraform state pull returns empty string when no state exists
-        if state_json.trim().is_empty() {
-            return super::state_analyzer::analyze_state("{}", resource_type, detect_drift);
-        }
-        super::state_analyzer::analyze_state(&state_json, resource_type, detect_drift)
+        super::state_pull::pull_state_json(
+            &String::from_utf8_lossy(&output.stdout),
+            max_bytes.unwrap_or(super::state_pull::DEFAULT_MAX_STATE_BYTES),
+        )
+    }
+
+    /// Report the backend configured in the project's root `.tf` files,
+    /// with credential-like settings redacted
+    pub fn get_backend_config(&self) -> anyhow::Result<super::backend::BackendConfig> {
+        let files: Vec<(String, String)> = std::fs::read_dir(&self.project_directory)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "tf"))
+            .filter(|path| !self.security_manager.is_file_blocked(path))
+            .filter_map(|path| {
+                let content = self.read_project_file(&path).ok()?;
+                let name = path.file_name()?.to_string_lossy().to_string();
+                Some((name, content.to_string()))
+            })
+            .collect();
+        Ok(super::backend::find_backend_config(&files))
     }
 
-    /// Execute workspace operations
+    /// Execute workspace operations. With `with_counts`, `list` also counts
+    /// the resources in each workspace's state, running `state list` with
+    /// `TF_WORKSPACE` so the selected workspace never changes.
     pub async fn workspace(
         &self,
         action: &str,
         name: Option<&str>,
+        with_counts: bool,
     ) -> anyhow::Result<super::workspace::WorkspaceResult> {
         eprintln!(
             "[DEBUG] Executing workspace {} in {}",
@@ -825,13 +2318,45 @@ impl TerraformService {
             self.project_directory.display()
         );
 
-        let action = action.parse()?;
-        super::workspace::execute_workspace(
-            &self.terraform_path,
-            &self.project_directory,
-            action,
-            name,
-        )
+        let action: super::workspace::WorkspaceAction = action.parse()?;
+        let args = super::workspace::workspace_args(&action, name)?;
+        let current = match (&action, name) {
+            (super::workspace::WorkspaceAction::Delete, Some(name)) => {
+                let current = self.current_workspace().await?;
+                super::workspace::check_deletable(name, &current)?;
+                Some(current)
+            }
+            _ => None,
+        };
+        let output = self.run_tracked_args(&args).await?;
+        let mut result = super::workspace::workspace_result(&action, name, &output)?;
+        if current.is_some() {
+            result.current_workspace = current;
+        }
+        let count_resources = with_counts && action == super::workspace::WorkspaceAction::List;
+
+        if count_resources {
+            for workspace in result.workspaces.iter_mut().flatten() {
+                let mut service = self.scoped_to(self.project_directory.clone());
+                service.workspace = Some(workspace.name.clone());
+                match service.state_list(false).await {
+                    Ok(state) => {
+                        let count = state
+                            .lines()
+                            .filter(|line| !line.trim().is_empty() && *line != "(no state)")
+                            .count();
+                        workspace.has_state = Some(count > 0);
+                        workspace.resource_count = Some(count);
+                    }
+                    Err(e) => eprintln!(
+                        "[WARN] Failed to count resources in workspace '{}': {}",
+                        workspace.name, e
+                    ),
+                }
+            }
+        }
+
+        Ok(result)
     }
 
     /// Import a resource (preview or execute)
@@ -848,13 +2373,11 @@ impl TerraformService {
         );
 
         if execute {
-            let result = super::import_helper::execute_import(
-                &self.terraform_path,
-                &self.project_directory,
-                resource_type,
-                resource_id,
-                name,
-            )?;
+            let _state_guard = self.state_lock.lock().await;
+            let args = super::import_helper::import_args(resource_type, resource_id, name);
+            let output = self.run_tracked_args(&args).await?;
+            let result =
+                super::import_helper::import_result(resource_type, resource_id, name, &output);
             Ok(serde_json::to_value(result)?)
         } else {
             let preview = super::import_helper::preview_import(resource_type, resource_id, name)?;
@@ -874,13 +2397,15 @@ impl TerraformService {
             self.project_directory.display()
         );
 
-        if check {
-            super::fmt::check_format(&self.terraform_path, &self.project_directory, file)
-        } else if diff {
-            super::fmt::format_with_diff(&self.terraform_path, &self.project_directory, file)
-        } else {
-            super::fmt::format_files(&self.terraform_path, &self.project_directory, file)
-        }
+        let show_diff = !check && diff;
+        let args = super::fmt::format_args(file, check, show_diff);
+        let output = self.run_tracked_args(&args).await?;
+        Ok(super::fmt::format_result(
+            &output,
+            &self.project_directory,
+            check,
+            show_diff,
+        ))
     }
 
     /// Generate dependency graph
@@ -894,17 +2419,38 @@ impl TerraformService {
         );
 
         let graph_type = graph_type.map(|s| s.parse()).transpose()?;
-        super::graph::generate_graph(&self.terraform_path, &self.project_directory, graph_type)
+        let output = self
+            .run_tracked_args(&super::graph::graph_args(graph_type))
+            .await?;
+        super::graph::graph_result(&output)
     }
 
     /// Get terraform outputs
-    pub async fn output(&self, name: Option<&str>) -> anyhow::Result<super::output::OutputResult> {
+    pub async fn output(
+        &self,
+        name: Option<&str>,
+        reveal_sensitive: bool,
+    ) -> anyhow::Result<super::output::OutputResult> {
         eprintln!(
             "[DEBUG] Getting outputs in {}",
             self.project_directory.display()
         );
 
-        super::output::get_outputs(&self.terraform_path, &self.project_directory, name)
+        if reveal_sensitive
+            && !self
+                .security_manager
+                .get_policy()
+                .allow_dangerous_operations
+        {
+            return Err(TerraformError::PolicyBlocked(
+                "Revealing sensitive outputs blocked by security policy. Set TFMCP_ALLOW_DANGEROUS_OPS=true to enable."
+                    .to_string(),
+            )
+            .into());
+        }
+
+        let output = self.run_tracked(super::output::OUTPUT_ARGS).await?;
+        super::output::outputs_result(&output, name, reveal_sensitive)
     }
 
     /// Execute taint/untaint operation
@@ -920,13 +2466,13 @@ impl TerraformService {
             self.project_directory.display()
         );
 
-        let action = action.parse()?;
-        super::taint::execute_taint(
-            &self.terraform_path,
-            &self.project_directory,
-            action,
-            address,
-        )
+        let action: super::taint::TaintAction = action.parse()?;
+        let _state_guard = self.state_lock.lock().await;
+        let output = self.run_tracked(&[action.as_str(), address]).await?;
+        let version = self.binary().await.map(|(_, version)| version.as_str());
+        Ok(super::taint::taint_result(
+            action, address, &output, version,
+        ))
     }
 
     /// Refresh state
@@ -941,12 +2487,18 @@ impl TerraformService {
 
         // Security check - refresh can modify state
         if !self.security_manager.is_command_allowed("refresh") {
-            return Err(anyhow::anyhow!(
+            return Err(TerraformError::PolicyBlocked(
                 "Refresh operation blocked by security policy. Set TFMCP_ALLOW_DANGEROUS_OPS=true to enable."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
-        super::refresh::execute_refresh(&self.terraform_path, &self.project_directory, target)
+        let _state_guard = self.state_lock.lock().await;
+        let output = self
+            .run_tracked_args(&super::refresh::refresh_args(target))
+            .await?;
+        super::refresh::refresh_result(&output)
     }
 
     /// Get provider information
@@ -959,6 +2511,1023 @@ impl TerraformService {
             self.project_directory.display()
         );
 
-        super::providers::get_providers(&self.terraform_path, &self.project_directory, include_lock)
+        let output = self.run_tracked(&["providers"]).await?;
+        super::providers::providers_result(&output, &self.project_directory, include_lock)
+    }
+
+    /// Schemas of the providers installed in the project, from
+    /// `terraform providers schema -json`
+    pub async fn providers_schema(&self) -> anyhow::Result<super::schema::ProviderSchemas> {
+        eprintln!(
+            "[DEBUG] Reading provider schemas in {}",
+            self.project_directory.display()
+        );
+
+        // Without .terraform the command only reports an empty schema set
+        if !self.project_directory.join(".terraform").is_dir() {
+            return Err(TerraformError::InitRequired.into());
+        }
+
+        let output = self
+            .run_with_init_check(&["providers", "schema", "-json"], false)
+            .await?;
+        if !output.status.success() {
+            return Err(TerraformError::from_output("providers schema", &output).into());
+        }
+
+        super::schema::ProviderSchemas::from_json(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Fetch the provider schemas and keep them for argument checks in
+    /// `validate_detailed`
+    pub async fn warm_provider_schemas(&self) -> anyhow::Result<WarmedSchemas> {
+        let schemas = self.providers_schema().await?;
+        let warmed = self.schema_cache.insert(&self.project_directory, schemas);
+        eprintln!(
+            "[INFO] Warmed {} resource and {} data source schemas for {}",
+            warmed.resource_types,
+            warmed.data_source_types,
+            self.project_directory.display()
+        );
+        Ok(warmed)
+    }
+
+    /// Schema of one resource (or data source) type from the installed providers
+    pub async fn resource_schema(
+        &self,
+        resource_type: &str,
+        data_source: bool,
+    ) -> anyhow::Result<super::schema::ResourceSchema> {
+        let schemas = self.providers_schema().await?;
+        let found = if data_source {
+            schemas.data_source(resource_type)
+        } else {
+            schemas.resource(resource_type)
+        };
+        let Some((provider, schema)) = found else {
+            return Err(anyhow::anyhow!(
+                "{} '{}' is not defined by any provider installed in this project",
+                if data_source {
+                    "Data source"
+                } else {
+                    "Resource type"
+                },
+                resource_type
+            ));
+        };
+        Ok(super::schema::ResourceSchema {
+            resource_type: resource_type.to_string(),
+            data_source,
+            provider: provider.to_string(),
+            schema: schema.clone(),
+        })
+    }
+
+    /// Scaffold a resource block from the provider schema installed in the
+    /// project. Returns `None` when the project is not initialized or its
+    /// providers don't define `resource_type`, so callers can fall back to docs.
+    pub async fn scaffold_resource(
+        &self,
+        resource_type: &str,
+        name: &str,
+    ) -> anyhow::Result<Option<super::scaffold::ResourceScaffold>> {
+        let schemas = match self.providers_schema().await {
+            Ok(schemas) => schemas,
+            Err(e) => {
+                eprintln!("[DEBUG] No local schema for {}: {}", resource_type, e);
+                return Ok(None);
+            }
+        };
+        Ok(schemas.resource(resource_type).map(|(_, schema)| {
+            super::scaffold::scaffold_from_schema(resource_type, name, &schema.block)
+        }))
+    }
+
+    /// Starter tfvars for the root module's variables, written to `write_to`
+    /// (relative to the project root) when set. An existing file is only
+    /// overwritten with `force`.
+    pub async fn generate_tfvars(
+        &self,
+        include_defaults: bool,
+        write_to: Option<&str>,
+        force: bool,
+    ) -> anyhow::Result<super::tfvars::TfvarsSkeleton> {
+        let analysis = self.analyze_configurations().await?;
+        let mut skeleton = super::tfvars::generate_tfvars(&analysis.variables, include_defaults);
+        let Some(file) = write_to else {
+            return Ok(skeleton);
+        };
+
+        let relative = Path::new(file);
+        if !file.ends_with(".tfvars")
+            || relative
+                .components()
+                .any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            return Err(anyhow::anyhow!(
+                "tfvars file '{}' must be a relative path ending in .tfvars inside the project directory",
+                file
+            ));
+        }
+        let path = self.resolve_in_project(file)?;
+        if self.security_manager.is_file_blocked(&path) {
+            return Err(TerraformError::PolicyBlocked(format!(
+                "Writing {} is blocked by security policy",
+                file
+            ))
+            .into());
+        }
+        if path.exists() && !force {
+            return Err(anyhow::anyhow!(
+                "{} already exists; set force to overwrite it",
+                file
+            ));
+        }
+        std::fs::write(&path, &skeleton.content)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", file, e))?;
+        eprintln!("[INFO] Wrote tfvars skeleton to {}", path.display());
+        skeleton.written_to = Some(file.to_string());
+        Ok(skeleton)
+    }
+
+    /// Resource and data source types of the installed `namespace/name`
+    /// provider, or `None` when the project has no schema for it
+    pub async fn list_provider_resources(
+        &self,
+        namespace: &str,
+        name: &str,
+        filter: Option<&str>,
+    ) -> anyhow::Result<Option<super::provider_resources::ProviderResourceList>> {
+        let schemas = match self.providers_schema().await {
+            Ok(schemas) => schemas,
+            Err(e) => {
+                eprintln!("[DEBUG] No local schema for {}/{}: {}", namespace, name, e);
+                return Ok(None);
+            }
+        };
+        Ok(super::provider_resources::list_from_schema(
+            &schemas, namespace, name, filter,
+        ))
+    }
+
+    /// Check `.terraform.lock.hcl` against the configured provider constraints
+    pub async fn verify_provider_lock(&self) -> anyhow::Result<super::providers::LockVerification> {
+        eprintln!(
+            "[DEBUG] Verifying provider lock file in {}",
+            self.project_directory.display()
+        );
+
+        let analysis = self.analyze_configurations().await?;
+        let constraints = analysis
+            .providers
+            .into_iter()
+            .map(|provider| (provider.name, provider.version))
+            .collect();
+        super::providers::verify_provider_lock(&self.project_directory, &constraints)
+    }
+}
+
+/// Whether `path` is a `.tf` or `.tf.json` configuration file name
+fn is_config_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "tf")
+        || path
+            .file_name()
+            .is_some_and(|name| super::json_config::is_json_config(&name.to_string_lossy()))
+}
+
+/// Prefix of the plan files [`TerraformService::apply`] writes to check
+/// `TFMCP_MAX_APPLY_DESTROYS` against
+const APPLY_PLAN_PREFIX: &str = ".tfmcp-apply-";
+
+/// A plan file written for one apply, removed when dropped
+struct TemporaryPlan {
+    path: PathBuf,
+}
+
+impl Drop for TemporaryPlan {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// The HCL files of `file_contents`. The text-based analyzer passes read
+/// HCL syntax, so `.tf.json` files, which the parser already covers, are
+/// left out rather than matched as if they were HCL.
+fn hcl_only(mut file_contents: HashMap<String, String>) -> HashMap<String, String> {
+    file_contents.retain(|name, _| !super::json_config::is_json_config(name));
+    file_contents
+}
+
+/// `.tf` and `.tf.json` files directly inside `dir`, sorted by path
+async fn tf_files_in(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    let mut tf_files = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if is_config_file(&path)
+            && tokio::fs::metadata(&path)
+                .await
+                .is_ok_and(|metadata| metadata.is_file())
+        {
+            tf_files.push(path);
+        }
+    }
+    tf_files.sort();
+    Ok(tf_files)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::shared::security::{AuditConfig, SecurityPolicy};
+    use crate::terraform::model::NO_FILE_DIAGNOSTICS;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// A service for an empty project, run by a fake terraform executing
+    /// `script`. Both live in the returned directory, which must outlive the
+    /// service.
+    fn fake_service(script: &str) -> (TerraformService, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let fake_terraform = dir.path().join("fake-terraform");
+        std::fs::write(&fake_terraform, script).unwrap();
+        std::fs::set_permissions(&fake_terraform, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let project = project.canonicalize().unwrap();
+        (TerraformService::new(fake_terraform, project), dir)
+    }
+
+    /// Fake terraform whose `apply` logs its start and end around a pause
+    const SLOW_APPLY_SCRIPT: &str = r#"#!/bin/sh
+if [ "$1" = "apply" ]; then
+  echo start >> apply.log
+  sleep 0.3
+  echo end >> apply.log
+  echo "Apply complete!"
+fi
+exit 0
+"#;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_applies_run_sequentially() {
+        let (mut service, _dir) = fake_service(SLOW_APPLY_SCRIPT);
+        let project_dir = service.project_directory.clone();
+        service.security_manager = SecurityManager {
+            policy: SecurityPolicy {
+                allow_dangerous_operations: true,
+                allow_auto_approve: true,
+                ..SecurityPolicy::default()
+            },
+            audit_log: None,
+        };
+
+        let other = service.clone();
+        let (first, second) = tokio::join!(
+            service.apply(true, false, &[], false),
+            other.apply(true, false, &[], false)
+        );
+        first.unwrap();
+        second.unwrap();
+
+        let log = std::fs::read_to_string(project_dir.join("apply.log")).unwrap();
+        assert_eq!(
+            log.lines().collect::<Vec<_>>(),
+            ["start", "end", "start", "end"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_configuration_reuses_analysis() {
+        let project_dir = tempfile::tempdir().unwrap();
+        let main_tf = project_dir.path().join("main.tf");
+        std::fs::write(
+            &main_tf,
+            "resource \"aws_vpc\" \"main\" {}\nresource \"aws_subnet\" \"a\" {\n  vpc_id = aws_vpc.main.id\n}\n",
+        )
+        .unwrap();
+        let service =
+            TerraformService::new(PathBuf::from("terraform"), project_dir.path().to_path_buf());
+
+        let first = service.analyze_module_health().await.unwrap();
+        let parses = service.file_cache().parse_count();
+        let second = service.analyze_module_health().await.unwrap();
+        assert_eq!(service.analysis_cache.computed_count(), 1);
+        assert_eq!(service.file_cache().parse_count(), parses);
+        assert_eq!(first.health_score, second.health_score);
+
+        service.get_dependency_graph().await.unwrap();
+        let graph = service.get_dependency_graph().await.unwrap();
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(service.analysis_cache.computed_count(), 2);
+
+        // Any edit changes the key, so the graph is rebuilt
+        std::fs::write(
+            &main_tf,
+            "resource \"aws_vpc\" \"main\" {}\nresource \"aws_subnet\" \"a\" {}\n",
+        )
+        .unwrap();
+        let graph = service.get_dependency_graph().await.unwrap();
+        assert!(graph.edges.is_empty());
+        assert_eq!(service.analysis_cache.computed_count(), 3);
+        assert!(service.file_cache().parse_count() > parses);
+    }
+
+    #[tokio::test]
+    async fn test_module_edit_outside_modules_dir_invalidates_analysis() {
+        let project_dir = tempfile::tempdir().unwrap();
+        let module_dir = project_dir.path().join("shared/vpc");
+        std::fs::create_dir_all(&module_dir).unwrap();
+        std::fs::write(
+            project_dir.path().join("main.tf"),
+            "module \"vpc\" {\n  source = \"./shared/vpc\"\n  cidr   = \"10.0.0.0/16\"\n}\n",
+        )
+        .unwrap();
+        std::fs::write(module_dir.join("variables.tf"), "variable \"cidr\" {}\n").unwrap();
+        let service =
+            TerraformService::new(PathBuf::from("terraform"), project_dir.path().to_path_buf());
+
+        let wiring_issues = |health: &ModuleHealthAnalysis| {
+            health
+                .issues
+                .iter()
+                .filter(|issue| {
+                    matches!(
+                        issue.category,
+                        crate::terraform::model::IssueCategory::ModuleWiring
+                    )
+                })
+                .count()
+        };
+        let health = service.analyze_module_health().await.unwrap();
+        assert_eq!(wiring_issues(&health), 0);
+
+        std::fs::write(
+            module_dir.join("variables.tf"),
+            "variable \"cidr_block\" {}\n",
+        )
+        .unwrap();
+        let health = service.analyze_module_health().await.unwrap();
+        assert_eq!(service.analysis_cache.computed_count(), 2);
+        assert_eq!(wiring_issues(&health), 2);
+    }
+
+    #[tokio::test]
+    async fn test_many_files_are_read_in_stable_order() {
+        let project_dir = tempfile::tempdir().unwrap();
+        for i in 0..60 {
+            std::fs::write(
+                project_dir.path().join(format!("r{:02}.tf", i)),
+                format!(
+                    "resource \"aws_s3_bucket\" \"b{:02}\" {{}}\nvariable \"v{:02}\" {{}}\n",
+                    i, i
+                ),
+            )
+            .unwrap();
+        }
+        for module in ["network", "network/subnets", "storage"] {
+            let dir = project_dir.path().join("modules").join(module);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("main.tf"), format!("# {}\n", module)).unwrap();
+        }
+        let service =
+            TerraformService::new(PathBuf::from("terraform"), project_dir.path().to_path_buf());
+
+        let analysis = service.analyze_configurations().await.unwrap();
+        assert_eq!(analysis.file_count, 60);
+        let names: Vec<_> = analysis.resources.iter().map(|r| r.name.clone()).collect();
+        let expected: Vec<_> = (0..60).map(|i| format!("b{:02}", i)).collect();
+        assert_eq!(names, expected);
+        let variables: Vec<_> = analysis.variables.iter().map(|v| v.name.clone()).collect();
+        assert_eq!(variables[59], "v59");
+
+        // Repeated runs, served from the cache, keep the same order
+        let again = service.analyze_configurations().await.unwrap();
+        let again: Vec<_> = again.resources.iter().map(|r| r.name.clone()).collect();
+        assert_eq!(again, names);
+
+        let contents = service.read_file_contents().await.unwrap();
+        assert_eq!(contents.len(), 63);
+        assert!(contents["r07.tf"].contains("\"b07\""));
+        assert_eq!(
+            contents["modules/network/subnets/main.tf"],
+            "# network/subnets\n"
+        );
+        assert_eq!(contents["modules/storage/main.tf"], "# storage\n");
+    }
+
+    #[tokio::test]
+    async fn test_analysis_skips_files_linked_outside_project() {
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(
+            outside.path().join("secrets.tf"),
+            "resource \"aws_iam_user\" \"outside\" {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            outside.path().join("secrets.tfvars"),
+            "region = \"leaked\"\n",
+        )
+        .unwrap();
+        let project_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            project_dir.path().join("main.tf"),
+            "variable \"region\" {\n  default = \"eu-west-1\"\n}\nresource \"aws_vpc\" \"main\" {}\n",
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(
+            outside.path().join("secrets.tf"),
+            project_dir.path().join("linked.tf"),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(
+            outside.path().join("secrets.tfvars"),
+            project_dir.path().join("terraform.tfvars"),
+        )
+        .unwrap();
+        let service =
+            TerraformService::new(PathBuf::from("terraform"), project_dir.path().to_path_buf());
+
+        let analysis = service.analyze_configurations().await.unwrap();
+        assert_eq!(analysis.file_count, 1);
+        let resources: Vec<_> = analysis.resources.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(resources, ["main"]);
+        let effective = analysis.variables[0].effective.as_ref().unwrap();
+        assert_eq!(
+            effective.source,
+            crate::terraform::model::ValueSource::Default
+        );
+    }
+
+    /// Fake terraform whose `validate -json` reports a valid configuration
+    const VALID_CONFIG_SCRIPT: &str = r#"#!/bin/sh
+echo '{"format_version":"1.0","valid":true,"error_count":0,"warning_count":0,"diagnostics":[]}'
+exit 0
+"#;
+
+    #[tokio::test]
+    async fn test_json_configuration_files_are_analyzed() {
+        let (service, _dir) = fake_service(VALID_CONFIG_SCRIPT);
+        let project_dir = &service.project_directory;
+        std::fs::write(
+            project_dir.join("main.tf.json"),
+            r#"{
+  "variable": {"bucket_name": {"type": "string", "default": "logs"}},
+  "resource": {"aws_s3_bucket": {"logs": {"bucket": "${var.bucket_name}"}}}
+}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            project_dir.join("network.tf"),
+            "resource \"aws_vpc\" \"main\" {\n  cidr_block = \"10.0.0.0/16\"\n}\n",
+        )
+        .unwrap();
+
+        let analysis = service.analyze_configurations().await.unwrap();
+        assert_eq!(analysis.file_count, 2);
+        let resources: Vec<_> = analysis
+            .resources
+            .iter()
+            .map(|r| (r.resource_type.as_str(), r.file.as_str()))
+            .collect();
+        assert_eq!(
+            resources,
+            vec![("aws_s3_bucket", "main.tf.json"), ("aws_vpc", "network.tf")]
+        );
+        assert_eq!(analysis.variables.len(), 1);
+        assert_eq!(analysis.variables[0].name, "bucket_name");
+        assert_eq!(
+            analysis.variables[0].default,
+            Some(serde_json::Value::from("logs"))
+        );
+
+        let contents = service.read_file_contents().await.unwrap();
+        assert!(contents.contains_key("main.tf.json"));
+
+        // JSON interpolation is not flagged as pre-0.12 HCL syntax
+        let health = service.analyze_module_health().await.unwrap();
+        assert!(
+            !health
+                .issues
+                .iter()
+                .any(|issue| issue.file.as_deref() == Some("main.tf.json")),
+            "{:?}",
+            health.issues
+        );
+
+        // A JSON-only project still counts as configuration
+        std::fs::remove_file(project_dir.join("network.tf")).unwrap();
+        let validation = service.validate_detailed(None).await.unwrap();
+        assert_eq!(validation.checked_files, 1);
+        assert!(validation.guideline_checks.is_some());
+        assert!(
+            !validation
+                .additional_warnings
+                .iter()
+                .any(|warning| warning.contains("No Terraform configuration files")),
+            "{:?}",
+            validation.additional_warnings
+        );
+        let scan = service.run_security_scan().await.unwrap();
+        assert_eq!(scan.variables_missing_description, ["bucket_name"]);
+    }
+
+    /// Fake terraform printing its arguments and working directory
+    const INVOCATION_SCRIPT: &str = r#"#!/bin/sh
+echo "args: $*"
+echo "pwd: $(pwd -P)"
+exit 0
+"#;
+
+    #[tokio::test]
+    async fn test_use_chdir_selects_invocation_form() {
+        let (mut service, _dir) = fake_service(INVOCATION_SCRIPT);
+        let root = service.project_directory.clone();
+        std::fs::create_dir_all(root.join("envs/dev")).unwrap();
+
+        // Default: the project is the working directory
+        let output = service.init(&[]).await.unwrap();
+        assert!(output.contains("args: init\n"), "{}", output);
+        assert!(output.contains(&format!("pwd: {}\n", root.display())));
+
+        service.set_use_chdir(true);
+        let output = service.init(&[]).await.unwrap();
+        assert!(
+            output.contains(&format!("args: -chdir={} init\n", root.display())),
+            "{}",
+            output
+        );
+        assert!(!output.contains(&format!("pwd: {}\n", root.display())));
+        let output = service.run_tracked(&["plan", "-json"]).await.unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains(&format!("args: -chdir={} plan -json", root.display())),
+            "{}",
+            stdout
+        );
+
+        // Subdirectories are resolved inside the root in either mode
+        let scoped = service.scoped_to(service.resolve_project_path(Some("envs/dev")).unwrap());
+        let output = scoped.init(&[]).await.unwrap();
+        assert!(output.contains(&format!("-chdir={} init", root.join("envs/dev").display())));
+        assert!(service.resolve_project_path(Some("../")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_binary_plan_file_show_is_cancellable() {
+        let (service, _dir) =
+            fake_service("#!/bin/sh\n[ \"$1\" = \"show\" ] && exec sleep 30\nexit 0\n");
+        std::fs::write(service.project_directory.join("ci.tfplan"), b"PK\x03\x04").unwrap();
+        let processes = service.processes.clone();
+
+        let started = std::time::Instant::now();
+        let analysis =
+            tokio::spawn(async move { service.analyze_plan_file("ci.tfplan", false, None).await });
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        processes.cancel();
+
+        let err = analysis.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("cancelled"), "{err}");
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_helper_commands_use_project_command() {
+        const CLI_CONFIG_SCRIPT: &str = r#"#!/bin/sh
+echo "args: $*"
+echo "cli config: $TF_CLI_CONFIG_FILE"
+exit 0
+"#;
+        let (mut service, _dir) = fake_service(CLI_CONFIG_SCRIPT);
+        let root = service.project_directory.clone();
+        service.set_use_chdir(true);
+        service.set_cli_config_file(Some(PathBuf::from("/etc/tfmcp.tfrc")));
+
+        let graph = service.graph(Some("plan")).await.unwrap();
+        assert!(
+            graph.dot_output.contains(&format!(
+                "args: -chdir={} graph -type=plan\n",
+                root.display()
+            )),
+            "{}",
+            graph.dot_output
+        );
+        assert!(graph.dot_output.contains("cli config: /etc/tfmcp.tfrc\n"));
+
+        let formatted = service.fmt(true, false, None).await.unwrap();
+        let lines: Vec<_> = formatted
+            .file_results
+            .iter()
+            .map(|r| r.file.as_str())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                format!(
+                    "args: -chdir={} fmt -check -list=true -recursive",
+                    root.display()
+                )
+                .as_str(),
+                "cli config: /etc/tfmcp.tfrc",
+            ]
+        );
+    }
+
+    /// Fake terraform whose state holds a resource removed from the config
+    const ORPHAN_STATE_SCRIPT: &str = r#"#!/bin/sh
+if [ "$1" = "state" ] && [ "$2" = "list" ]; then
+  echo 'aws_instance.web[0]'
+  echo 'aws_instance.web[1]'
+  echo 'aws_s3_bucket.legacy'
+  echo 'data.aws_ami.ubuntu'
+fi
+exit 0
+"#;
+
+    #[tokio::test]
+    async fn test_find_orphaned_resources() {
+        let (service, _dir) = fake_service(ORPHAN_STATE_SCRIPT);
+        let project_dir = service.project_directory.clone();
+        std::fs::write(
+            project_dir.join("main.tf"),
+            "resource \"aws_instance\" \"web\" {\n  count = 2\n}\n",
+        )
+        .unwrap();
+
+        let report = service.find_orphaned_resources().await.unwrap();
+        assert_eq!(report.state_resource_count, 4);
+        assert_eq!(report.config_resource_count, 1);
+        assert_eq!(report.orphaned.len(), 1);
+        assert_eq!(report.orphaned[0].address, "aws_s3_bucket.legacy");
+    }
+
+    /// Fake terraform whose plan deletes two resources and replaces a third,
+    /// logging each command it runs
+    const DESTRUCTIVE_PLAN_SCRIPT: &str = r#"#!/bin/sh
+[ "$1" = version ] && exit 0
+echo "$@" >> commands.log
+case "$1" in
+  plan)
+    for arg in "$@"; do
+      case "$arg" in -out=*) echo plan > "${arg#-out=}" ;; esac
+    done ;;
+  show) cat <<'JSON'
+{"format_version":"1.2","resource_changes":[
+{"address":"aws_instance.web","type":"aws_instance","change":{"actions":["delete"]}},
+{"address":"aws_s3_bucket.logs","type":"aws_s3_bucket","change":{"actions":["delete"]}},
+{"address":"aws_db_instance.main","type":"aws_db_instance","change":{"actions":["delete","create"]}},
+{"address":"aws_vpc.main","type":"aws_vpc","change":{"actions":["create"]}}
+]}
+JSON
+    ;;
+  apply) echo "Apply complete!" ;;
+esac
+exit 0
+"#;
+
+    fn destructive_service(max_apply_destroys: usize) -> (TerraformService, tempfile::TempDir) {
+        let (mut service, dir) = fake_service(DESTRUCTIVE_PLAN_SCRIPT);
+        service.security_manager = SecurityManager {
+            policy: SecurityPolicy {
+                allow_dangerous_operations: true,
+                allow_auto_approve: true,
+                max_apply_destroys: Some(max_apply_destroys),
+                ..SecurityPolicy::default()
+            },
+            audit_log: None,
+        };
+        (service, dir)
+    }
+
+    /// Plan and apply commands the fake terraform ran, without the
+    /// `state list` of the resource limit check
+    fn logged_commands(service: &TerraformService) -> Vec<String> {
+        std::fs::read_to_string(service.project_directory.join("commands.log"))
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.starts_with("state list"))
+            .map(|line| {
+                line.replace(
+                    &service.project_directory.display().to_string(),
+                    "<project>",
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_apply_refuses_too_many_destroys() {
+        let (mut service, _dir) = destructive_service(2);
+
+        let err = service.apply(true, false, &[], false).await.unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("destroy or replace 3 resources"),
+            "{}",
+            message
+        );
+        for address in [
+            "aws_instance.web",
+            "aws_s3_bucket.logs",
+            "aws_db_instance.main",
+        ] {
+            assert!(message.contains(address), "{}", message);
+        }
+        assert!(!message.contains("aws_vpc.main"));
+        assert!(message.contains("confirm_destructive"));
+        let commands = logged_commands(&service);
+        assert_eq!(commands.len(), 2);
+        assert!(commands[0].starts_with("plan -input=false -out=<project>/.tfmcp-apply-"));
+        assert!(commands[1].starts_with("show -json <project>/.tfmcp-apply-"));
+
+        // Confirmed: applied without a separate plan
+        let output = service.apply(true, false, &[], true).await.unwrap();
+        assert!(output.contains("Apply complete!"));
+        assert_eq!(logged_commands(&service)[2..], ["apply -auto-approve"]);
+
+        // Within the limit: the counted plan file itself is applied, then removed
+        service.security_manager.policy.max_apply_destroys = Some(3);
+        std::fs::remove_file(service.project_directory.join("commands.log")).unwrap();
+        assert!(service.apply(true, false, &[], false).await.is_ok());
+        let commands = logged_commands(&service);
+        assert_eq!(commands.len(), 3, "{:?}", commands);
+        let plan_file = commands[0].strip_prefix("plan -input=false -out=").unwrap();
+        assert_eq!(commands[1], format!("show -json {}", plan_file));
+        assert_eq!(commands[2], format!("apply -input=false {}", plan_file));
+        let leftovers = std::fs::read_dir(&service.project_directory)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tfplan"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[tokio::test]
+    async fn test_apply_saved_plan_refuses_too_many_destroys() {
+        let (service, _dir) = destructive_service(2);
+        service.save_plan(None, false).await.unwrap();
+
+        let err = service.apply_saved_plan(None, false).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TerraformError>(),
+            Some(TerraformError::PolicyBlocked(_))
+        ));
+        assert!(err.to_string().contains("destroy or replace 3 resources"));
+        assert!(
+            !logged_commands(&service)
+                .iter()
+                .any(|command| command.starts_with("apply"))
+        );
+
+        let applied = service.apply_saved_plan(None, true).await.unwrap();
+        assert!(applied.output.contains("Apply complete!"));
+    }
+
+    /// Fake terraform whose validate passes with a warning mentioning "Error:"
+    const VALIDATE_WARNING_SCRIPT: &str = r#"#!/bin/sh
+cat <<'JSON'
+{"format_version":"1.0","valid":true,"error_count":0,"warning_count":1,"diagnostics":[{"severity":"warning","summary":"Deprecated attribute","detail":"Error: handling moved to the error_document block","range":{"filename":"main.tf","start":{"line":3,"column":3,"byte":40},"end":{"line":3,"column":9,"byte":46}}}]}
+JSON
+"#;
+
+    /// Fake terraform whose validate fails with one error
+    const VALIDATE_ERROR_SCRIPT: &str = r#"#!/bin/sh
+cat <<'JSON'
+{"format_version":"1.0","valid":false,"error_count":1,"warning_count":0,"diagnostics":[{"severity":"error","summary":"Unsupported argument","detail":"An argument named \"amii\" is not expected here.","range":null}]}
+JSON
+exit 1
+"#;
+
+    #[tokio::test]
+    async fn test_validate_path_parses_json_result() {
+        let (service, _dir) = fake_service(VALIDATE_WARNING_SCRIPT);
+
+        // A warning mentioning "Error:" does not make the configuration invalid
+        let (validated_path, validation) = service.validate_path(None, false).await.unwrap();
+        assert_eq!(validated_path, ".");
+        assert!(validation.valid);
+        assert_eq!(validation.error_count, 0);
+        assert_eq!(validation.warning_count, 1);
+        assert_eq!(validation.diagnostics[0].severity, "warning");
+        assert_eq!(
+            validation.message(),
+            "Configuration is valid with 1 warning(s)"
+        );
+
+        // Invalid configurations are reported through the result, not as an error
+        std::fs::write(&service.terraform_path, VALIDATE_ERROR_SCRIPT).unwrap();
+        let (_, validation) = service.validate_path(None, false).await.unwrap();
+        assert!(!validation.valid);
+        assert_eq!(validation.error_count, 1);
+        assert_eq!(validation.diagnostics[0].summary, "Unsupported argument");
+        assert_eq!(
+            validation.message(),
+            "Configuration is invalid: 1 error(s), 0 warning(s)"
+        );
+    }
+
+    /// Fake terraform whose validate reports diagnostics in two files and one
+    /// without a range
+    const VALIDATE_MULTI_FILE_SCRIPT: &str = r#"#!/bin/sh
+[ "$1" = validate ] || exit 0
+cat <<'JSON'
+{"format_version":"1.0","valid":false,"error_count":3,"warning_count":2,"diagnostics":[
+{"severity":"error","summary":"Unsupported argument","detail":"","range":{"filename":"main.tf","start":{"line":4,"column":3,"byte":60},"end":{"line":4,"column":7,"byte":64}}},
+{"severity":"warning","summary":"Deprecated attribute","detail":"","range":{"filename":"network.tf","start":{"line":12,"column":3,"byte":200},"end":{"line":12,"column":9,"byte":206}}},
+{"severity":"error","summary":"Missing required argument","detail":"","range":{"filename":"main.tf","start":{"line":9,"column":1,"byte":120},"end":{"line":9,"column":30,"byte":149}}},
+{"severity":"error","summary":"Reference to undeclared resource","detail":"","range":{"filename":"network.tf","start":{"line":20,"column":12,"byte":400},"end":{"line":20,"column":30,"byte":418}}},
+{"severity":"warning","summary":"Provider configuration not present","detail":"","range":null}
+]}
+JSON
+exit 1
+"#;
+
+    #[tokio::test]
+    async fn test_validate_detailed_groups_diagnostics_by_file() {
+        let (service, _dir) = fake_service(VALIDATE_MULTI_FILE_SCRIPT);
+
+        let result = service.validate_detailed(None).await.unwrap();
+        assert_eq!(result.diagnostics.len(), 5);
+        let counts: Vec<_> = result
+            .diagnostics_by_file
+            .iter()
+            .map(|f| (f.file.as_str(), f.error_count, f.warning_count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (NO_FILE_DIAGNOSTICS, 0, 1),
+                ("main.tf", 2, 0),
+                ("network.tf", 1, 1),
+            ]
+        );
+        assert_eq!(
+            result.diagnostics_by_file[1].summaries,
+            ["4: Unsupported argument", "9: Missing required argument"]
+        );
+    }
+
+    /// Fake terraform that writes plan files, shows an empty plan and logs
+    /// the plan file each apply is given
+    const PLAN_FILE_SCRIPT: &str = r#"#!/bin/sh
+case "$1" in
+  plan)
+    for arg in "$@"; do
+      case "$arg" in -out=*) echo plan > "${arg#-out=}" ;; esac
+    done ;;
+  show) echo '{"format_version":"1.2","resource_changes":[]}' ;;
+  apply) echo "$3" >> apply.log; echo "Apply complete!" ;;
+esac
+exit 0
+"#;
+
+    #[tokio::test]
+    async fn test_confirm_apply_applies_proposed_plan_once() {
+        let (mut service, _dir) = fake_service(PLAN_FILE_SCRIPT);
+        let project_dir = service.project_directory.clone();
+        service.security_manager = SecurityManager {
+            policy: SecurityPolicy {
+                allow_dangerous_operations: true,
+                ..SecurityPolicy::default()
+            },
+            audit_log: None,
+        };
+
+        let proposal = service.propose_apply(false).await.unwrap();
+        let plan_path = project_dir.join(&proposal.plan_file);
+        assert!(plan_path.exists());
+        assert_eq!(proposal.summary.add, 0);
+
+        let result = service.confirm_apply(&proposal.token, false).await.unwrap();
+        assert!(result.output.contains("Apply complete!"));
+        let log = std::fs::read_to_string(project_dir.join("apply.log")).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains(&proposal.plan_file));
+        assert!(!plan_path.exists());
+
+        let reused = service
+            .confirm_apply(&proposal.token, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            reused.downcast_ref::<TerraformError>(),
+            Some(TerraformError::ApprovalRejected(_))
+        ));
+        let log = std::fs::read_to_string(project_dir.join("apply.log")).unwrap();
+        assert_eq!(log.lines().count(), 1);
+    }
+
+    /// Fake terraform whose plan streams an error diagnostic on stdout, a
+    /// message on stderr, and exits non-zero
+    const FAILING_PLAN_SCRIPT: &str = r#"#!/bin/sh
+echo '{"@level":"info","@message":"Terraform 1.9.5","type":"version"}'
+echo '{"@level":"error","@message":"Error: Invalid AMI","diagnostic":{"severity":"error","summary":"Invalid AMI","detail":"ami-123 does not exist","address":"aws_instance.web"},"type":"diagnostic"}'
+echo "Operation failed" >&2
+exit 1
+"#;
+
+    #[tokio::test]
+    async fn test_failed_plan_reports_stdout_diagnostics() {
+        let (service, _dir) = fake_service(FAILING_PLAN_SCRIPT);
+
+        let err = service.get_plan(false, &[]).await.unwrap_err();
+        let Some(TerraformError::ExecFailed {
+            stderr,
+            diagnostics,
+            ..
+        }) = err.downcast_ref::<TerraformError>()
+        else {
+            panic!("expected ExecFailed, got {:?}", err);
+        };
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].summary, "Invalid AMI");
+        assert_eq!(diagnostics[0].address.as_deref(), Some("aws_instance.web"));
+        assert!(stderr.starts_with("Operation failed\n"));
+        assert!(err.to_string().contains("Invalid AMI (aws_instance.web)"));
+    }
+
+    /// Fake terraform that logs the arguments of each run; `version` runs
+    /// outside the project, so it isn't logged
+    const ARGS_LOG_SCRIPT: &str = r#"#!/bin/sh
+[ "$1" = version ] && exit 0
+echo "$@" >> args.log
+exit 0
+"#;
+
+    #[tokio::test]
+    async fn test_plan_uses_workspace_tfvars_when_enabled() {
+        let (mut service, _dir) = fake_service(ARGS_LOG_SCRIPT);
+        let project_dir = service.project_directory.clone();
+        std::fs::create_dir(project_dir.join(".terraform")).unwrap();
+        std::fs::write(project_dir.join(".terraform/environment"), "staging").unwrap();
+        let log_path = project_dir.join("args.log");
+        let last_plan = || {
+            let log = std::fs::read_to_string(&log_path).unwrap();
+            log.lines()
+                .rfind(|line| line.starts_with("plan"))
+                .unwrap()
+                .to_string()
+        };
+        service.set_auto_tfvars_by_workspace(true);
+
+        // Absent: plan runs without a var file
+        service.get_plan(false, &[]).await.unwrap();
+        assert_eq!(last_plan(), "plan -json");
+
+        // Present: included for the selected workspace
+        std::fs::write(project_dir.join("staging.tfvars"), "replicas = 2\n").unwrap();
+        service.get_plan(false, &[]).await.unwrap();
+        assert_eq!(last_plan(), "plan -var-file=staging.tfvars -json");
+
+        // Opt-in only
+        service.set_auto_tfvars_by_workspace(false);
+        service.get_plan(false, &[]).await.unwrap();
+        assert_eq!(last_plan(), "plan -json");
+    }
+
+    #[tokio::test]
+    async fn test_plan_appends_allowed_extra_args() {
+        let (mut service, dir) = fake_service(ARGS_LOG_SCRIPT);
+        let project_dir = service.project_directory.clone();
+        let audit_path = dir.path().join("audit.log");
+        let extra_args = vec!["-refresh=false".to_string(), "-parallelism=4".to_string()];
+
+        let denied = service.get_plan(false, &extra_args).await.unwrap_err();
+        assert!(matches!(
+            denied.downcast_ref::<TerraformError>(),
+            Some(TerraformError::PolicyBlocked(_))
+        ));
+        assert!(!project_dir.join("args.log").exists());
+
+        service.security_manager = SecurityManager {
+            policy: SecurityPolicy {
+                allow_extra_args: true,
+                audit_logging: AuditConfig {
+                    enabled: true,
+                    log_file: Some(audit_path.clone()),
+                    log_sensitive: false,
+                },
+                ..SecurityPolicy::default()
+            },
+            audit_log: Some(audit_path.clone()),
+        };
+        service.get_plan(false, &extra_args).await.unwrap();
+        let log = std::fs::read_to_string(project_dir.join("args.log")).unwrap();
+        assert_eq!(log.trim(), "plan -json -refresh=false -parallelism=4");
+        let audit = std::fs::read_to_string(&audit_path).unwrap();
+        assert!(audit.contains("-parallelism=4"));
+
+        let out = vec!["-out=../elsewhere.tfplan".to_string()];
+        let blocked = service.get_plan(false, &out).await.unwrap_err();
+        assert!(matches!(
+            blocked.downcast_ref::<TerraformError>(),
+            Some(TerraformError::PolicyBlocked(_))
+        ));
+        assert_eq!(
+            std::fs::read_to_string(project_dir.join("args.log"))
+                .unwrap()
+                .lines()
+                .count(),
+            1
+        );
     }
 }
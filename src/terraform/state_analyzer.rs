@@ -82,6 +82,9 @@ pub struct StateAnalysis {
     pub state_version: Option<i32>,
     pub terraform_version: Option<String>,
     pub serial: Option<i64>,
+    /// Workspace whose state was analyzed
+    #[serde(default)]
+    pub workspace: Option<String>,
 }
 
 /// Terraform state JSON structure
@@ -230,6 +233,7 @@ pub fn analyze_state(
         state_version: state.version,
         terraform_version: state.terraform_version,
         serial: state.serial,
+        workspace: None,
     })
 }
 
@@ -0,0 +1,320 @@
+//! Masked copy of the raw state from `terraform state pull`.
+//!
+//! Outputs marked sensitive, instance attributes listed in
+//! `sensitive_attributes`, and strings matching the analyzer's secret
+//! patterns are replaced with [`MASKED_VALUE`]. States larger than the size
+//! guard keep their leading resources and drop the rest, so the result is
+//! always valid JSON.
+
+use crate::terraform::analyzer::matching_secret_patterns;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Largest serialized state returned when the caller sets no limit
+pub const DEFAULT_MAX_STATE_BYTES: usize = 512 * 1024;
+
+/// Replacement for masked values
+pub const MASKED_VALUE: &str = "(sensitive value)";
+
+/// State from `terraform state pull` with secrets masked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PulledState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terraform_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lineage: Option<String>,
+    /// Resource blocks in the full state
+    pub resource_count: usize,
+    /// Values replaced with the mask
+    pub masked_values: usize,
+    /// Whether resources were dropped to fit the size guard
+    pub truncated: bool,
+    /// Resource blocks dropped from the end of `state`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub omitted_resources: usize,
+    /// Size of the serialized `state`, in bytes
+    pub size_bytes: usize,
+    pub state: Value,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Mask and size-guard the output of `terraform state pull`; empty output
+/// (no state yet) gives an empty state
+pub fn pull_state_json(raw: &str, max_bytes: usize) -> anyhow::Result<PulledState> {
+    let mut state: Value = if raw.trim().is_empty() {
+        Value::Object(Default::default())
+    } else {
+        serde_json::from_str(raw)
+            .map_err(|e| anyhow::anyhow!("Failed to parse state JSON: {}", e))?
+    };
+    let masked_values = mask_state(&mut state);
+
+    let resource_count = state
+        .get("resources")
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len);
+    let omitted_resources = fit_to_size(&mut state, max_bytes);
+    let size_bytes = serde_json::to_string(&state)?.len();
+
+    Ok(PulledState {
+        terraform_version: state
+            .get("terraform_version")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        serial: state.get("serial").and_then(Value::as_u64),
+        lineage: state
+            .get("lineage")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        resource_count,
+        masked_values,
+        truncated: omitted_resources > 0,
+        omitted_resources,
+        size_bytes,
+        state,
+    })
+}
+
+/// Mask sensitive values in a raw (format version 4) state, returning how
+/// many were replaced
+pub fn mask_state(state: &mut Value) -> usize {
+    let mut masked = 0;
+
+    if let Some(outputs) = state.get_mut("outputs").and_then(Value::as_object_mut) {
+        for output in outputs.values_mut() {
+            let sensitive = output.get("sensitive").and_then(Value::as_bool) == Some(true);
+            if let Some(value) = output.get_mut("value") {
+                if sensitive && !value.is_null() {
+                    *value = Value::String(MASKED_VALUE.to_string());
+                    masked += 1;
+                } else {
+                    masked += mask_secrets(value, "value");
+                }
+            }
+        }
+    }
+
+    for resource in state
+        .get_mut("resources")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+    {
+        for instance in resource
+            .get_mut("instances")
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten()
+        {
+            let paths = instance
+                .get("sensitive_attributes")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let Some(attributes) = instance.get_mut("attributes") else {
+                continue;
+            };
+            for path in &paths {
+                if let Some(steps) = path.as_array() {
+                    masked += mask_path(attributes, steps);
+                }
+            }
+            if let Some(map) = attributes.as_object_mut() {
+                for (key, value) in map.iter_mut() {
+                    masked += mask_secrets(value, key);
+                }
+            }
+        }
+    }
+
+    masked
+}
+
+/// Mask the value at a `sensitive_attributes` path such as
+/// `[{"type":"get_attr","value":"password"}]`
+fn mask_path(value: &mut Value, steps: &[Value]) -> usize {
+    let Some((step, rest)) = steps.split_first() else {
+        if value.is_null() || value.as_str() == Some(MASKED_VALUE) {
+            return 0;
+        }
+        *value = Value::String(MASKED_VALUE.to_string());
+        return 1;
+    };
+    let key = step.get("value");
+    let next = match (value, key) {
+        (Value::Object(map), Some(Value::String(name))) => map.get_mut(name),
+        // Index steps carry a typed value: {"value": 0, "type": "number"}
+        (Value::Object(map), Some(Value::Object(typed))) => typed
+            .get("value")
+            .and_then(Value::as_str)
+            .and_then(|name| map.get_mut(name)),
+        (Value::Array(items), Some(Value::Object(typed))) => typed
+            .get("value")
+            .and_then(Value::as_u64)
+            .and_then(|index| items.get_mut(index as usize)),
+        _ => None,
+    };
+    next.map_or(0, |next| mask_path(next, rest))
+}
+
+/// Mask strings under `key` that match a secret pattern
+fn mask_secrets(value: &mut Value, key: &str) -> usize {
+    match value {
+        Value::Object(map) => map
+            .iter_mut()
+            .map(|(key, nested)| mask_secrets(nested, key))
+            .sum(),
+        Value::Array(items) => items
+            .iter_mut()
+            .map(|nested| mask_secrets(nested, key))
+            .sum(),
+        Value::String(text) if text != MASKED_VALUE => {
+            // The patterns match HCL assignments, keyed by the attribute name
+            let line = format!("{} = {:?}", key, text);
+            if matching_secret_patterns(&line).next().is_some() {
+                *text = MASKED_VALUE.to_string();
+                1
+            } else {
+                0
+            }
+        }
+        _ => 0,
+    }
+}
+
+/// Drop trailing resources until the serialized state fits in `max_bytes`,
+/// returning how many were dropped
+fn fit_to_size(state: &mut Value, max_bytes: usize) -> usize {
+    let Some(resources) = state.get_mut("resources").and_then(Value::as_array_mut) else {
+        return 0;
+    };
+    let sizes: Vec<usize> = resources
+        .iter()
+        .map(|r| serde_json::to_string(r).map_or(0, |s| s.len()) + 1)
+        .collect();
+    let all = std::mem::take(resources);
+    let base = serde_json::to_string(state).map_or(0, |s| s.len());
+
+    let mut total = base;
+    let keep = sizes
+        .iter()
+        .take_while(|size| {
+            total += **size;
+            total <= max_bytes
+        })
+        .count();
+    let omitted = all.len() - keep;
+    if let Some(resources) = state.get_mut("resources").and_then(Value::as_array_mut) {
+        resources.extend(all.into_iter().take(keep));
+    }
+    omitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLANTED_SECRET: &str = "hunter2-very-secret-value";
+
+    fn synthetic_state() -> String {
+        serde_json::json!({
+            "version": 4,
+            "terraform_version": "1.9.5",
+            "serial": 12,
+            "lineage": "b7c1f0e2",
+            "outputs": {
+                "db_password": {"value": "s3cr3t!", "type": "string", "sensitive": true},
+                "endpoint": {"value": "db.internal:5432", "type": "string"}
+            },
+            "resources": [
+                {
+                    "mode": "managed",
+                    "type": "aws_db_instance",
+                    "name": "main",
+                    "instances": [{
+                        "attributes": {
+                            "identifier": "main",
+                            "password": "pw",
+                            "credentials": [{"user": "admin", "key": "k-1"}]
+                        },
+                        "sensitive_attributes": [
+                            [{"type": "get_attr", "value": "password"}],
+                            [
+                                {"type": "get_attr", "value": "credentials"},
+                                {"type": "index", "value": {"value": 0, "type": "number"}},
+                                {"type": "get_attr", "value": "key"}
+                            ]
+                        ]
+                    }]
+                },
+                {
+                    "mode": "managed",
+                    "type": "aws_ssm_parameter",
+                    "name": "config",
+                    "instances": [{
+                        "attributes": {
+                            "name": "/app/config",
+                            "settings": {"token": PLANTED_SECRET}
+                        },
+                        "sensitive_attributes": []
+                    }]
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_sensitive_values_are_masked() {
+        let pulled = pull_state_json(&synthetic_state(), DEFAULT_MAX_STATE_BYTES).unwrap();
+        assert_eq!(pulled.terraform_version.as_deref(), Some("1.9.5"));
+        assert_eq!(pulled.serial, Some(12));
+        assert_eq!(pulled.resource_count, 2);
+        assert_eq!(pulled.masked_values, 4);
+        assert!(!pulled.truncated);
+
+        let state = &pulled.state;
+        assert_eq!(state["outputs"]["db_password"]["value"], MASKED_VALUE);
+        assert_eq!(state["outputs"]["endpoint"]["value"], "db.internal:5432");
+        let db = &state["resources"][0]["instances"][0]["attributes"];
+        assert_eq!(db["password"], MASKED_VALUE);
+        assert_eq!(db["credentials"][0]["key"], MASKED_VALUE);
+        assert_eq!(db["credentials"][0]["user"], "admin");
+        assert_eq!(db["identifier"], "main");
+        let config = &state["resources"][1]["instances"][0]["attributes"];
+        assert_eq!(config["settings"]["token"], MASKED_VALUE);
+
+        let serialized = serde_json::to_string(&pulled).unwrap();
+        assert!(!serialized.contains(PLANTED_SECRET));
+        assert!(!serialized.contains("s3cr3t!"));
+        assert!(!serialized.contains("k-1"));
+    }
+
+    #[test]
+    fn test_large_state_drops_trailing_resources() {
+        let full = pull_state_json(&synthetic_state(), DEFAULT_MAX_STATE_BYTES).unwrap();
+        let limit = full.size_bytes - 1;
+
+        let pulled = pull_state_json(&synthetic_state(), limit).unwrap();
+        assert!(pulled.truncated);
+        assert_eq!(pulled.omitted_resources, 1);
+        assert_eq!(pulled.resource_count, 2);
+        assert!(pulled.size_bytes <= limit);
+        let kept = pulled.state["resources"].as_array().unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0]["type"], "aws_db_instance");
+    }
+
+    #[test]
+    fn test_empty_state() {
+        let pulled = pull_state_json("\n", DEFAULT_MAX_STATE_BYTES).unwrap();
+        assert_eq!(pulled.resource_count, 0);
+        assert!(pulled.serial.is_none());
+        assert_eq!(pulled.state, serde_json::json!({}));
+    }
+}
@@ -0,0 +1,276 @@
+//! Scan of terraform state for attributes holding secrets.
+//!
+//! Walks `terraform show -json` output and reports where secrets live
+//! (resource address and attribute path) without ever returning the values.
+//! An attribute is reported when its value matches one of the analyzer's
+//! secret patterns or when the provider marks it sensitive.
+
+use crate::terraform::analyzer::{matching_secret_patterns, secret_severity};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Severity of attributes only known to be secret because they are marked sensitive
+const MARKED_SENSITIVE_SEVERITY: &str = "medium";
+
+/// One attribute in state that holds a secret
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateSecretFinding {
+    /// Resource address, or `output.<name>` for root outputs
+    pub address: String,
+    /// Attribute path, e.g. `password` or `ingress[0].secret`
+    pub attribute: String,
+    /// Secret pattern the value matched, if any
+    pub pattern: Option<String>,
+    /// Whether the provider (or output) marks the value sensitive
+    pub marked_sensitive: bool,
+    pub severity: String,
+}
+
+/// Result of scanning state for secrets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSecretScan {
+    pub resources_scanned: usize,
+    pub findings: Vec<StateSecretFinding>,
+    pub message: String,
+}
+
+/// Scan the output of `terraform show -json`
+pub fn scan_state_json(show_json: &str) -> anyhow::Result<StateSecretScan> {
+    let state: Value = serde_json::from_str(show_json)
+        .map_err(|e| anyhow::anyhow!("Failed to parse state JSON: {}", e))?;
+
+    let mut scanner = Scanner::default();
+    if let Some(values) = state.get("values") {
+        if let Some(root_module) = values.get("root_module") {
+            scanner.scan_module(root_module);
+        }
+        if let Some(outputs) = values.get("outputs").and_then(Value::as_object) {
+            for (name, output) in outputs {
+                let marked = output.get("sensitive").and_then(Value::as_bool) == Some(true);
+                scanner.scan_values(
+                    &format!("output.{}", name),
+                    name,
+                    output.get("value").unwrap_or(&Value::Null),
+                    &Value::Bool(marked),
+                );
+            }
+        }
+    }
+
+    let findings: Vec<StateSecretFinding> = scanner.findings.into_values().collect();
+    let message = if findings.is_empty() {
+        format!(
+            "No secrets found in {} resources",
+            scanner.resources_scanned
+        )
+    } else {
+        format!(
+            "Found {} secret attributes across {} resources; values are not shown",
+            findings.len(),
+            scanner.resources_scanned
+        )
+    };
+    Ok(StateSecretScan {
+        resources_scanned: scanner.resources_scanned,
+        findings,
+        message,
+    })
+}
+
+#[derive(Default)]
+struct Scanner {
+    resources_scanned: usize,
+    /// Keyed by (address, attribute) so a value both marked and matched is reported once
+    findings: BTreeMap<(String, String), StateSecretFinding>,
+}
+
+impl Scanner {
+    fn scan_module(&mut self, module: &Value) {
+        for resource in module
+            .get("resources")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let Some(address) = resource.get("address").and_then(Value::as_str) else {
+                continue;
+            };
+            self.resources_scanned += 1;
+            if let Some(values) = resource.get("values").and_then(Value::as_object) {
+                let sensitive = resource.get("sensitive_values").unwrap_or(&Value::Null);
+                for (key, value) in values {
+                    self.scan_values(
+                        address,
+                        key,
+                        value,
+                        sensitive.get(key).unwrap_or(&Value::Null),
+                    );
+                }
+            }
+        }
+        for child in module
+            .get("child_modules")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            self.scan_module(child);
+        }
+    }
+
+    /// Walk `value` at attribute `path` alongside its `sensitive_values` mirror
+    fn scan_values(&mut self, address: &str, path: &str, value: &Value, sensitive: &Value) {
+        if sensitive == &Value::Bool(true) && !value.is_null() {
+            self.record(address, path, None, true);
+        }
+
+        match value {
+            Value::Object(map) => {
+                for (key, nested) in map {
+                    self.scan_values(
+                        address,
+                        &format!("{}.{}", path, key),
+                        nested,
+                        sensitive.get(key).unwrap_or(&Value::Null),
+                    );
+                }
+            }
+            Value::Array(items) => {
+                for (index, nested) in items.iter().enumerate() {
+                    self.scan_values(
+                        address,
+                        &format!("{}[{}]", path, index),
+                        nested,
+                        sensitive.get(index).unwrap_or(&Value::Null),
+                    );
+                }
+            }
+            Value::String(text) => {
+                // The patterns match HCL assignments, keyed by the attribute name
+                let key = path.rsplit(['.', '[']).next().unwrap_or(path);
+                let line = format!("{} = {:?}", key, text);
+                if let Some(pattern) = matching_secret_patterns(&line).next() {
+                    self.record(address, path, Some(pattern), false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record(&mut self, address: &str, path: &str, pattern: Option<&str>, marked: bool) {
+        let finding = self
+            .findings
+            .entry((address.to_string(), path.to_string()))
+            .or_insert_with(|| StateSecretFinding {
+                address: address.to_string(),
+                attribute: path.to_string(),
+                pattern: None,
+                marked_sensitive: false,
+                severity: MARKED_SENSITIVE_SEVERITY.to_string(),
+            });
+        finding.marked_sensitive |= marked;
+        if let Some(pattern) = pattern {
+            finding.pattern = Some(pattern.to_string());
+            finding.severity = secret_severity(pattern).to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLANTED_SECRET: &str = "hunter2-very-secret-value";
+
+    fn synthetic_state() -> String {
+        serde_json::json!({
+            "format_version": "1.0",
+            "values": {
+                "outputs": {
+                    "db_password": {"sensitive": true, "value": "s3cr3t!", "type": "string"},
+                    "endpoint": {"sensitive": false, "value": "db.internal:5432", "type": "string"}
+                },
+                "root_module": {
+                    "resources": [
+                        {
+                            "address": "aws_db_instance.main",
+                            "type": "aws_db_instance",
+                            "values": {
+                                "identifier": "main",
+                                "password": "correct-horse-battery",
+                                "tags": {"team": "data"}
+                            },
+                            "sensitive_values": {"password": true, "tags": {}}
+                        }
+                    ],
+                    "child_modules": [
+                        {
+                            "address": "module.app",
+                            "resources": [
+                                {
+                                    "address": "module.app.aws_ssm_parameter.config",
+                                    "type": "aws_ssm_parameter",
+                                    "values": {
+                                        "name": "/app/config",
+                                        "settings": [{"token": PLANTED_SECRET}]
+                                    },
+                                    "sensitive_values": {"settings": [{}]}
+                                }
+                            ]
+                        }
+                    ]
+                }
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_scan_reports_locations_without_values() {
+        let scan = scan_state_json(&synthetic_state()).unwrap();
+        assert_eq!(scan.resources_scanned, 2);
+
+        let located: Vec<(&str, &str)> = scan
+            .findings
+            .iter()
+            .map(|f| (f.address.as_str(), f.attribute.as_str()))
+            .collect();
+        assert_eq!(
+            located,
+            vec![
+                ("aws_db_instance.main", "password"),
+                ("module.app.aws_ssm_parameter.config", "settings[0].token"),
+                ("output.db_password", "db_password"),
+            ]
+        );
+
+        // Marked sensitive and matching a pattern is reported once, at pattern severity
+        let password = &scan.findings[0];
+        assert!(password.marked_sensitive);
+        assert_eq!(password.pattern.as_deref(), Some("Generic Secret"));
+        assert_eq!(password.severity, "critical");
+
+        // The planted secret is found by pattern alone
+        let token = &scan.findings[1];
+        assert!(!token.marked_sensitive);
+        assert_eq!(token.pattern.as_deref(), Some("Generic Secret"));
+
+        let output = &scan.findings[2];
+        assert!(output.marked_sensitive && output.pattern.is_none());
+        assert_eq!(output.severity, "medium");
+
+        let serialized = serde_json::to_string(&scan).unwrap();
+        assert!(!serialized.contains(PLANTED_SECRET));
+        assert!(!serialized.contains("correct-horse-battery"));
+        assert!(!serialized.contains("s3cr3t!"));
+    }
+
+    #[test]
+    fn test_scan_empty_state() {
+        let scan = scan_state_json(r#"{"format_version":"1.0"}"#).unwrap();
+        assert_eq!(scan.resources_scanned, 0);
+        assert!(scan.findings.is_empty());
+        assert!(scan.message.starts_with("No secrets found"));
+    }
+}
@@ -4,8 +4,7 @@
 //! The recommended approach is to use `terraform apply -replace=ADDRESS`.
 
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::process::Command;
+use std::process::Output;
 
 /// Taint action type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -30,6 +29,16 @@ impl std::str::FromStr for TaintAction {
     }
 }
 
+impl TaintAction {
+    /// The terraform subcommand
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaintAction::Taint => "taint",
+            TaintAction::Untaint => "untaint",
+        }
+    }
+}
+
 /// Result of taint/untaint operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaintResult {
@@ -40,26 +49,16 @@ pub struct TaintResult {
     pub deprecation_warning: Option<String>,
 }
 
-/// Execute taint or untaint operation
-pub fn execute_taint(
-    terraform_path: &Path,
-    project_dir: &Path,
+/// Result of `terraform taint|untaint <address>`; `terraform_version` is
+/// the binary's version, used for the deprecation warning
+pub fn taint_result(
     action: TaintAction,
     address: &str,
-) -> anyhow::Result<TaintResult> {
-    // Check terraform version for deprecation warning
-    let deprecation_warning = check_terraform_version_for_deprecation(terraform_path);
-
-    let action_str = match action {
-        TaintAction::Taint => "taint",
-        TaintAction::Untaint => "untaint",
-    };
-
-    let output = Command::new(terraform_path)
-        .arg(action_str)
-        .arg(address)
-        .current_dir(project_dir)
-        .output()?;
+    output: &Output,
+    terraform_version: Option<&str>,
+) -> TaintResult {
+    let deprecation_warning = terraform_version.and_then(deprecation_warning);
+    let action_str = action.as_str();
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
@@ -74,13 +73,13 @@ pub fn execute_taint(
             }
         };
 
-        Ok(TaintResult {
+        TaintResult {
             success: true,
             action: action_str.to_string(),
             resource_address: address.to_string(),
             message,
             deprecation_warning,
-        })
+        }
     } else {
         // Parse common error messages
         let message = if stderr.contains("No such resource instance") {
@@ -100,41 +99,29 @@ pub fn execute_taint(
             )
         };
 
-        Ok(TaintResult {
+        TaintResult {
             success: false,
             action: action_str.to_string(),
             resource_address: address.to_string(),
             message,
             deprecation_warning,
-        })
+        }
     }
 }
 
-/// Check if terraform version is 1.5+ and return deprecation warning
-fn check_terraform_version_for_deprecation(terraform_path: &Path) -> Option<String> {
-    let output = Command::new(terraform_path)
-        .arg("version")
-        .arg("-json")
-        .output()
-        .ok()?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&stdout) {
-        if let Some(version_str) = json.get("terraform_version").and_then(|v| v.as_str()) {
-            let parts: Vec<&str> = version_str.split('.').collect();
-            if let (Some(major), Some(minor)) = (
-                parts.first().and_then(|s| s.parse::<i32>().ok()),
-                parts.get(1).and_then(|s| s.parse::<i32>().ok()),
-            ) {
-                if major >= 1 && minor >= 5 {
-                    return Some(format!(
-                        "Note: 'terraform taint' and 'terraform untaint' are deprecated in Terraform {}. \
-                        Consider using 'terraform apply -replace={}' instead for new workflows.",
-                        version_str, "RESOURCE_ADDRESS"
-                    ));
-                }
-            }
+/// Deprecation warning when `version` is 1.5 or later
+fn deprecation_warning(version: &str) -> Option<String> {
+    let parts: Vec<&str> = version.split('.').collect();
+    if let (Some(major), Some(minor)) = (
+        parts.first().and_then(|s| s.parse::<i32>().ok()),
+        parts.get(1).and_then(|s| s.parse::<i32>().ok()),
+    ) {
+        if major >= 1 && minor >= 5 {
+            return Some(format!(
+                "Note: 'terraform taint' and 'terraform untaint' are deprecated in Terraform {}. \
+                Consider using 'terraform apply -replace={}' instead for new workflows.",
+                version, "RESOURCE_ADDRESS"
+            ));
         }
     }
 
@@ -147,27 +134,10 @@ pub fn get_replacement_command(address: &str) -> String {
     format!("terraform apply -replace='{}'", address)
 }
 
-/// Alternative: Plan with replace
+/// Alternative: arguments of a plan replacing `address`
 #[allow(dead_code)]
-pub fn plan_with_replace(
-    terraform_path: &Path,
-    project_dir: &Path,
-    address: &str,
-) -> anyhow::Result<String> {
-    let output = Command::new(terraform_path)
-        .arg("plan")
-        .arg(format!("-replace={}", address))
-        .current_dir(project_dir)
-        .output()?;
-
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
-    } else {
-        Err(anyhow::anyhow!(
-            "Failed to plan with replace: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ))
-    }
+pub fn plan_with_replace_args(address: &str) -> Vec<String> {
+    vec!["plan".to_string(), format!("-replace={}", address)]
 }
 
 #[cfg(test)]
@@ -184,6 +154,13 @@ mod tests {
         assert!("invalid".parse::<TaintAction>().is_err());
     }
 
+    #[test]
+    fn test_deprecation_warning() {
+        assert!(deprecation_warning("1.9.5").is_some());
+        assert!(deprecation_warning("1.4.7").is_none());
+        assert!(deprecation_warning("unknown").is_none());
+    }
+
     #[test]
     fn test_replacement_command() {
         let cmd = get_replacement_command("aws_instance.example");
@@ -0,0 +1,597 @@
+//! Variable values from auto-loaded tfvars files.
+//!
+//! Terraform loads `terraform.tfvars`, `terraform.tfvars.json` and then every
+//! `*.auto.tfvars` / `*.auto.tfvars.json` in lexical order, each overriding
+//! the ones before it; all of them override `TF_VAR_<name>` environment
+//! variables, which override variable defaults. Values that are expressions
+//! rather than literals are kept as their source text.
+
+use crate::terraform::model::{EffectiveValue, TerraformVariable, ValueSource};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Shown in place of the value of sensitive variables
+pub const MASKED_VALUE: &str = "(sensitive)";
+
+/// Placeholder for string and untyped variables in a generated tfvars file
+const STRING_PLACEHOLDER: &str = "\"REPLACE_ME\"";
+
+/// A starter tfvars file from `generate_tfvars`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TfvarsSkeleton {
+    pub content: String,
+    /// Variables without a default, set to placeholders
+    pub required: Vec<String>,
+    /// Variables with a default, listed commented out
+    pub defaulted: Vec<String>,
+    /// File the skeleton was written to, relative to the project root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub written_to: Option<String>,
+}
+
+/// Build a tfvars skeleton setting every variable without a default to a
+/// placeholder for its type, each preceded by its description. With
+/// `include_defaults`, defaulted variables follow commented out with their
+/// default value (a placeholder for sensitive or non-literal defaults).
+pub fn generate_tfvars(variables: &[TerraformVariable], include_defaults: bool) -> TfvarsSkeleton {
+    let (required, defaulted): (Vec<&TerraformVariable>, Vec<&TerraformVariable>) =
+        variables.iter().partition(|v| v.default.is_none());
+    let defaulted: Vec<&TerraformVariable> = if include_defaults {
+        defaulted
+    } else {
+        Vec::new()
+    };
+
+    let mut content = String::new();
+    if !required.is_empty() {
+        content.push_str("# Required variables\n");
+        for variable in &required {
+            content.push('\n');
+            push_comments(&mut content, variable);
+            content.push_str(&format!(
+                "{} = {}\n",
+                variable.name,
+                type_placeholder(variable.type_.as_deref())
+            ));
+        }
+    }
+    if !defaulted.is_empty() {
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str("# Variables with defaults; uncomment to override\n");
+        for variable in &defaulted {
+            content.push('\n');
+            push_comments(&mut content, variable);
+            content.push_str(&format!(
+                "# {} = {}\n",
+                variable.name,
+                default_literal(variable)
+            ));
+        }
+    }
+    if content.is_empty() {
+        content.push_str("# No variables to set\n");
+    }
+
+    TfvarsSkeleton {
+        content,
+        required: required.iter().map(|v| v.name.clone()).collect(),
+        defaulted: defaulted.iter().map(|v| v.name.clone()).collect(),
+        written_to: None,
+    }
+}
+
+fn push_comments(content: &mut String, variable: &TerraformVariable) {
+    if let Some(description) = &variable.description {
+        for line in description.lines() {
+            content.push_str(&format!("# {}\n", line.trim_end()));
+        }
+    }
+    if variable.sensitive {
+        content.push_str("# Sensitive: keep this file out of version control\n");
+    }
+}
+
+/// Placeholder for a type constraint such as `string` or `list(object({...}))`
+fn type_placeholder(type_: Option<&str>) -> &'static str {
+    let kind = type_.and_then(|t| t.split('(').next()).unwrap_or("").trim();
+    match kind {
+        "number" => "0",
+        "bool" => "false",
+        "list" | "set" | "tuple" => "[]",
+        "map" | "object" => "{}",
+        _ => STRING_PLACEHOLDER,
+    }
+}
+
+/// HCL literal of a variable's default. Defaults the parser kept as source
+/// text (multi-line blocks, function calls) get the type placeholder.
+fn default_literal(variable: &TerraformVariable) -> String {
+    let placeholder = || type_placeholder(variable.type_.as_deref()).to_string();
+    if variable.sensitive {
+        return placeholder();
+    }
+    match &variable.default {
+        Some(Value::String(text))
+            if text.starts_with(['{', '[', '"', '<']) || text.contains('(') =>
+        {
+            placeholder()
+        }
+        Some(value) => value.to_string(),
+        None => placeholder(),
+    }
+}
+
+/// The tfvars files terraform loads automatically from `project_directory`,
+/// in the order they are applied
+pub fn auto_loaded_files(project_directory: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = ["terraform.tfvars", "terraform.tfvars.json"]
+        .iter()
+        .map(|name| project_directory.join(name))
+        .filter(|path| path.is_file())
+        .collect();
+
+    let mut auto: Vec<PathBuf> = std::fs::read_dir(project_directory)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .filter(|path| {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy())
+                        .is_some_and(|name| {
+                            name.ends_with(".auto.tfvars") || name.ends_with(".auto.tfvars.json")
+                        })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    auto.sort();
+    files.extend(auto);
+    files
+}
+
+/// Top-level assignments of the tfvars file `path` with `content`; JSON
+/// when the name ends in `.json`
+pub fn parse_tfvars_file(path: &Path, content: &str) -> anyhow::Result<BTreeMap<String, Value>> {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        let value: Value = serde_json::from_str(content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        return Ok(match value {
+            Value::Object(map) => map.into_iter().collect(),
+            _ => BTreeMap::new(),
+        });
+    }
+    Ok(parse_tfvars(content))
+}
+
+/// Top-level `name = value` assignments of HCL tfvars content. Nested
+/// `name { ... }` blocks are read as objects.
+pub fn parse_tfvars(content: &str) -> BTreeMap<String, Value> {
+    let mut values = BTreeMap::new();
+    let mut parser = ValueParser::new(content);
+    loop {
+        parser.skip_trivia(true);
+        if parser.peek().is_none() {
+            break;
+        }
+        let Some(name) = parser.identifier() else {
+            parser.skip_line();
+            continue;
+        };
+        parser.skip_trivia(false);
+        if parser.peek() == Some('{') {
+            // Nested block, as found in backend configuration
+            match parser.object() {
+                Some(value) => {
+                    values.insert(name, value);
+                }
+                None => parser.skip_line(),
+            }
+            continue;
+        }
+        if !parser.eat('=') {
+            parser.skip_line();
+            continue;
+        }
+        parser.skip_trivia(false);
+        let start = parser.pos;
+        let literal = parser.value().filter(|_| {
+            parser.skip_trivia(false);
+            matches!(parser.peek(), None | Some('\n'))
+        });
+        let value = literal.unwrap_or_else(|| {
+            // Not a literal: keep the expression text up to the end of the line
+            parser.pos = start;
+            parser.skip_line();
+            Value::String(parser.text[start..parser.pos].trim().to_string())
+        });
+        values.insert(name, value);
+    }
+    values
+}
+
+/// Attach the effective value to each variable. `env_is_set` reports whether
+/// `TF_VAR_<name>` is set for a variable name; such values are never read,
+/// so they are reported masked.
+pub fn resolve_effective_values(
+    variables: &mut [TerraformVariable],
+    files: &[(String, BTreeMap<String, Value>)],
+    env_is_set: impl Fn(&str) -> bool,
+) {
+    for variable in variables {
+        let from_file = files
+            .iter()
+            .rev()
+            .find_map(|(file, values)| values.get(&variable.name).map(|value| (file, value)));
+
+        let mut effective = match from_file {
+            Some((file, value)) => EffectiveValue {
+                source: ValueSource::File,
+                file: Some(file.clone()),
+                value: Some(value.clone()),
+            },
+            None if env_is_set(&variable.name) => EffectiveValue {
+                source: ValueSource::Environment,
+                file: None,
+                value: Some(Value::String(MASKED_VALUE.to_string())),
+            },
+            None => match &variable.default {
+                Some(default) => EffectiveValue {
+                    source: ValueSource::Default,
+                    file: None,
+                    value: Some(default.clone()),
+                },
+                None => EffectiveValue {
+                    source: ValueSource::Unset,
+                    file: None,
+                    value: None,
+                },
+            },
+        };
+
+        if variable.sensitive {
+            let masked = Value::String(MASKED_VALUE.to_string());
+            if effective.value.is_some() {
+                effective.value = Some(masked.clone());
+            }
+            if variable.default.is_some() {
+                variable.default = Some(masked);
+            }
+        }
+        variable.effective = Some(effective);
+    }
+}
+
+/// Recursive-descent reader for HCL literal values
+struct ValueParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> ValueParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_line(&mut self) {
+        match self.text[self.pos..].find('\n') {
+            Some(offset) => self.pos += offset + 1,
+            None => self.pos = self.text.len(),
+        }
+    }
+
+    /// Skip whitespace and comments, and newlines too when `newlines` is set
+    fn skip_trivia(&mut self, newlines: bool) {
+        loop {
+            let rest = &self.text[self.pos..];
+            if rest.starts_with('#') || rest.starts_with("//") {
+                match rest.find('\n') {
+                    Some(offset) => self.pos += offset,
+                    None => self.pos = self.text.len(),
+                }
+            } else if rest.starts_with("/*") {
+                match rest.find("*/") {
+                    Some(offset) => self.pos += offset + 2,
+                    None => self.pos = self.text.len(),
+                }
+            } else if let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+                if c == '\n' && !newlines {
+                    return;
+                }
+                self.pos += c.len_utf8();
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(rest.len());
+        if len == 0 || rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        self.pos += len;
+        Some(rest[..len].to_string())
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        match self.peek()? {
+            '"' => self.string().map(Value::String),
+            '[' => self.list(),
+            '{' => self.object(),
+            _ => {
+                let rest = &self.text[self.pos..];
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || matches!(c, ',' | ']' | '}'))
+                    .unwrap_or(rest.len());
+                let token = &rest[..len];
+                let value = match token {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ => serde_json::from_str::<serde_json::Number>(token)
+                        .ok()
+                        .map(Value::Number)?,
+                };
+                self.pos += len;
+                Some(value)
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.eat('"');
+        let mut out = String::new();
+        let mut chars = self.text[self.pos..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += offset + 1;
+                    return Some(out);
+                }
+                '\\' => match chars.next()?.1 {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    other => out.push(other),
+                },
+                '\n' => return None,
+                _ => out.push(c),
+            }
+        }
+        None
+    }
+
+    fn list(&mut self) -> Option<Value> {
+        self.eat('[');
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia(true);
+            if self.eat(']') {
+                return Some(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_trivia(true);
+            self.eat(',');
+        }
+    }
+
+    fn object(&mut self) -> Option<Value> {
+        self.eat('{');
+        let mut map = Map::new();
+        loop {
+            self.skip_trivia(true);
+            if self.eat('}') {
+                return Some(Value::Object(map));
+            }
+            let key = if self.peek() == Some('"') {
+                self.string()?
+            } else {
+                self.identifier()?
+            };
+            self.skip_trivia(false);
+            let value = if self.peek() == Some('{') {
+                self.object()?
+            } else {
+                if !self.eat('=') && !self.eat(':') {
+                    return None;
+                }
+                self.skip_trivia(false);
+                self.value()?
+            };
+            map.insert(key, value);
+            self.skip_trivia(true);
+            self.eat(',');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_tfvars_literals() {
+        let values = parse_tfvars(
+            r#"
+# Production settings
+region         = "eu-west-1"
+instance_count = 3
+enable_logging = true
+azs            = ["eu-west-1a", "eu-west-1b"] // two zones
+tags = {
+  Team        = "platform"
+  "cost-center" = "1234"
+}
+ami = data.aws_ami.ubuntu.id
+disk_size = 10 * 2
+"#,
+        );
+        assert_eq!(values["region"], json!("eu-west-1"));
+        assert_eq!(values["instance_count"], json!(3));
+        assert_eq!(values["enable_logging"], json!(true));
+        assert_eq!(values["azs"], json!(["eu-west-1a", "eu-west-1b"]));
+        assert_eq!(
+            values["tags"],
+            json!({"Team": "platform", "cost-center": "1234"})
+        );
+        assert_eq!(values["ami"], json!("data.aws_ami.ubuntu.id"));
+        assert_eq!(values["disk_size"], json!("10 * 2"));
+    }
+
+    #[test]
+    fn test_auto_loaded_files_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "b.auto.tfvars",
+            "terraform.tfvars",
+            "a.auto.tfvars.json",
+            "prod.tfvars",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let names: Vec<String> = auto_loaded_files(dir.path())
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["terraform.tfvars", "a.auto.tfvars.json", "b.auto.tfvars"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_effective_values() {
+        let variable = |name: &str, default: Option<Value>, sensitive: bool| TerraformVariable {
+            name: name.to_string(),
+            description: None,
+            type_: None,
+            default,
+            sensitive,
+            effective: None,
+        };
+        let mut variables = vec![
+            variable("region", Some(json!("us-east-1")), false),
+            variable("instance_type", Some(json!("t3.micro")), false),
+            variable("db_password", None, true),
+            variable("api_token", None, false),
+            variable("subnet_id", None, false),
+        ];
+        let files = vec![
+            (
+                "terraform.tfvars".to_string(),
+                BTreeMap::from([
+                    ("region".to_string(), json!("eu-west-1")),
+                    ("db_password".to_string(), json!("hunter2")),
+                ]),
+            ),
+            (
+                "prod.auto.tfvars".to_string(),
+                BTreeMap::from([("region".to_string(), json!("eu-central-1"))]),
+            ),
+        ];
+
+        resolve_effective_values(&mut variables, &files, |name| name == "api_token");
+
+        let effective: Vec<&EffectiveValue> = variables
+            .iter()
+            .map(|v| v.effective.as_ref().unwrap())
+            .collect();
+        assert_eq!(effective[0].source, ValueSource::File);
+        assert_eq!(effective[0].file.as_deref(), Some("prod.auto.tfvars"));
+        assert_eq!(effective[0].value, Some(json!("eu-central-1")));
+        assert_eq!(effective[1].source, ValueSource::Default);
+        assert_eq!(effective[1].value, Some(json!("t3.micro")));
+        assert_eq!(effective[2].value, Some(json!(MASKED_VALUE)));
+        assert_eq!(effective[3].source, ValueSource::Environment);
+        assert_eq!(effective[3].value, Some(json!(MASKED_VALUE)));
+        assert_eq!(effective[4].source, ValueSource::Unset);
+    }
+
+    fn variable(name: &str, type_: &str, default: Option<Value>) -> TerraformVariable {
+        TerraformVariable {
+            name: name.to_string(),
+            description: Some(format!("The {}", name.replace('_', " "))),
+            type_: Some(type_.to_string()),
+            default,
+            sensitive: false,
+            effective: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_tfvars_required_and_defaulted() {
+        let mut password = variable("db_password", "string", None);
+        password.sensitive = true;
+        let variables = vec![
+            variable("region", "string", None),
+            variable("instance_count", "number", Some(json!(2))),
+            variable("subnet_ids", "list(string)", None),
+            variable("tags", "map(string)", Some(json!({"Team": "platform"}))),
+            variable("settings", "object({", Some(json!("{"))),
+            variable("enable_logging", "bool", None),
+            password,
+        ];
+
+        let skeleton = generate_tfvars(&variables, true);
+        assert_eq!(
+            skeleton.required,
+            ["region", "subnet_ids", "enable_logging", "db_password"]
+        );
+        assert_eq!(skeleton.defaulted, ["instance_count", "tags", "settings"]);
+
+        let lines: Vec<&str> = skeleton.content.lines().collect();
+        // Required variables are set, each after its description
+        for expected in [
+            "region = \"REPLACE_ME\"",
+            "subnet_ids = []",
+            "enable_logging = false",
+            "db_password = \"REPLACE_ME\"",
+        ] {
+            let at = lines.iter().position(|l| *l == expected).unwrap();
+            assert!(
+                lines[at - 1].starts_with("# "),
+                "{} has no comment",
+                expected
+            );
+        }
+        assert!(lines.contains(&"# The region"));
+        assert!(lines.contains(&"# Sensitive: keep this file out of version control"));
+        // Defaulted variables are commented out with their default
+        assert!(lines.contains(&"# instance_count = 2"));
+        assert!(lines.contains(&"# tags = {\"Team\":\"platform\"}"));
+        assert!(lines.contains(&"# settings = {}"));
+        assert!(!lines.iter().any(|l| l.starts_with("instance_count")));
+
+        // The generated file reads back as the placeholders
+        let values = parse_tfvars(&skeleton.content);
+        assert_eq!(values.len(), 4);
+        assert_eq!(values["region"], json!("REPLACE_ME"));
+        assert_eq!(values["subnet_ids"], json!([]));
+
+        let required_only = generate_tfvars(&variables, false);
+        assert!(required_only.defaulted.is_empty());
+        assert!(!required_only.content.contains("instance_count"));
+    }
+}
@@ -0,0 +1,391 @@
+//! Terraform/OpenTofu binary version detection and `required_version` constraint checks.
+
+use crate::terraform::model::VersionCompatibility;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// How long `<binary> version` may take before detection gives up
+pub const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Which Terraform-compatible binary is installed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryFlavor {
+    Terraform,
+    OpenTofu,
+}
+
+/// Version constraint declared for a provider in the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderConstraint {
+    pub name: String,
+    pub constraint: Option<String>,
+}
+
+/// Installed binary version and compatibility with the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub flavor: BinaryFlavor,
+    pub version: String,
+    pub required_version: Option<String>,
+    /// `None` when the configuration declares no `required_version`
+    pub satisfies_required_version: Option<bool>,
+    pub provider_constraints: Vec<ProviderConstraint>,
+    pub warnings: Vec<String>,
+}
+
+/// Run `<binary> version` and detect the flavor and version string. A
+/// binary that does not answer within [`VERSION_TIMEOUT`] is killed.
+pub async fn detect_version(terraform_path: &Path) -> anyhow::Result<(BinaryFlavor, String)> {
+    detect_version_within(terraform_path, VERSION_TIMEOUT).await
+}
+
+async fn detect_version_within(
+    terraform_path: &Path,
+    timeout: Duration,
+) -> anyhow::Result<(BinaryFlavor, String)> {
+    let output = tokio::time::timeout(
+        timeout,
+        Command::new(terraform_path)
+            .arg("version")
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| {
+        anyhow::anyhow!(
+            "'{} version' did not finish within {:?}",
+            terraform_path.display(),
+            timeout
+        )
+    })??;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Failed to get version: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find_map(parse_version_line)
+        .ok_or_else(|| anyhow::anyhow!("Unrecognized version output: {}", stdout.trim()))
+}
+
+/// Parse a line such as "Terraform v1.9.5" or "OpenTofu v1.8.0"
+pub fn parse_version_line(line: &str) -> Option<(BinaryFlavor, String)> {
+    let mut parts = line.split_whitespace();
+    let flavor = match parts.next()? {
+        "Terraform" => BinaryFlavor::Terraform,
+        "OpenTofu" => BinaryFlavor::OpenTofu,
+        _ => return None,
+    };
+    let version = parts.next()?.trim_start_matches('v').to_string();
+    Some((flavor, version))
+}
+
+/// Flags only one flavor accepts; the other fails on them as undefined
+const FLAVOR_ONLY_FLAGS: &[(&str, BinaryFlavor)] = &[
+    ("-consolidate-warnings", BinaryFlavor::OpenTofu),
+    ("-consolidate-errors", BinaryFlavor::OpenTofu),
+];
+
+/// First OpenTofu release with `-consolidate-warnings`
+const CONSOLIDATE_WARNINGS_SINCE: (u64, u64, u64) = (1, 8, 0);
+
+/// Arguments for running `args` on the detected binary.
+///
+/// Flags only the other flavor understands are dropped. OpenTofu folds
+/// repeated warnings of a human-readable plan or apply into one summary, so
+/// those runs get `-consolidate-warnings=false` to keep every warning with
+/// its resource address. With no detected binary, `args` pass through as is.
+pub fn flavor_args(binary: Option<&(BinaryFlavor, String)>, args: &[&str]) -> Vec<String> {
+    let Some((flavor, version)) = binary else {
+        return args.iter().map(|arg| arg.to_string()).collect();
+    };
+    let mut adapted: Vec<String> = args
+        .iter()
+        .filter(|arg| {
+            let name = arg.split('=').next().unwrap_or(arg);
+            FLAVOR_ONLY_FLAGS
+                .iter()
+                .all(|(flag, only)| *flag != name || only == flavor)
+        })
+        .map(|arg| arg.to_string())
+        .collect();
+
+    let human_readable_run =
+        matches!(args.first(), Some(&("plan" | "apply" | "destroy"))) && !args.contains(&"-json");
+    let supported = parse_version(version).is_some_and(|v| v >= CONSOLIDATE_WARNINGS_SINCE);
+    if *flavor == BinaryFlavor::OpenTofu
+        && human_readable_run
+        && supported
+        && !adapted
+            .iter()
+            .any(|arg| arg.starts_with("-consolidate-warnings"))
+    {
+        // Flags go before a positional plan file
+        adapted.insert(1, "-consolidate-warnings=false".to_string());
+    }
+    adapted
+}
+
+/// Check whether `version` satisfies a Terraform version constraint string
+/// such as ">= 1.5.0, < 2.0.0" or "~> 1.6".
+pub fn satisfies_constraint(version: &str, constraint: &str) -> anyhow::Result<bool> {
+    let installed =
+        parse_version(version).ok_or_else(|| anyhow::anyhow!("Invalid version: {}", version))?;
+
+    for part in constraint.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+
+        let (operator, required) = split_operator(part);
+        let required_parts = required.split('.').count();
+        let required = parse_version(required)
+            .ok_or_else(|| anyhow::anyhow!("Invalid version constraint: {}", part))?;
+
+        let ordering = installed.cmp(&required);
+        let satisfied = match operator {
+            "=" => ordering == Ordering::Equal,
+            "!=" => ordering != Ordering::Equal,
+            ">" => ordering == Ordering::Greater,
+            ">=" => ordering != Ordering::Less,
+            "<" => ordering == Ordering::Less,
+            "<=" => ordering != Ordering::Greater,
+            "~>" => {
+                ordering != Ordering::Less
+                    && installed < pessimistic_upper_bound(required, required_parts)
+            }
+            _ => return Err(anyhow::anyhow!("Unknown constraint operator: {}", operator)),
+        };
+
+        if !satisfied {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Evaluate the installed version against a `required_version` constraint
+pub fn check_compatibility(
+    installed_version: Option<&str>,
+    constraint: &str,
+) -> VersionCompatibility {
+    let Some(installed) = installed_version else {
+        return VersionCompatibility {
+            constraint: constraint.to_string(),
+            installed_version: None,
+            satisfied: None,
+            message: "Could not determine the installed version".to_string(),
+        };
+    };
+
+    let (satisfied, message) = match satisfies_constraint(installed, constraint) {
+        Ok(true) => (
+            Some(true),
+            format!(
+                "Installed version {} satisfies required_version \"{}\"",
+                installed, constraint
+            ),
+        ),
+        Ok(false) => (
+            Some(false),
+            format!(
+                "Installed version {} does not satisfy required_version \"{}\"",
+                installed, constraint
+            ),
+        ),
+        Err(e) => (None, format!("Could not evaluate required_version: {}", e)),
+    };
+
+    VersionCompatibility {
+        constraint: constraint.to_string(),
+        installed_version: Some(installed.to_string()),
+        satisfied,
+        message,
+    }
+}
+
+/// Split a single constraint into its operator and version (defaults to "=")
+fn split_operator(constraint: &str) -> (&str, &str) {
+    for operator in ["~>", ">=", "<=", "!=", ">", "<", "="] {
+        if let Some(rest) = constraint.strip_prefix(operator) {
+            return (operator, rest.trim());
+        }
+    }
+    ("=", constraint)
+}
+
+/// Parse "1.2.3", "1.2" or "v1.2.3-beta1" into (major, minor, patch)
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut numbers = core.split('.').map(|n| n.parse::<u64>());
+
+    let major = numbers.next()?.ok()?;
+    let minor = numbers.next().transpose().ok()?.unwrap_or(0);
+    let patch = numbers.next().transpose().ok()?.unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Upper bound for "~>": only the rightmost specified component may increase
+fn pessimistic_upper_bound(required: (u64, u64, u64), specified_parts: usize) -> (u64, u64, u64) {
+    match specified_parts {
+        0 | 1 => (u64::MAX, 0, 0),
+        2 => (required.0 + 1, 0, 0),
+        _ => (required.0, required.1 + 1, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    fn fake_binary(dir: &Path, script: &str) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("terraform");
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_detect_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = fake_binary(
+            dir.path(),
+            "#!/bin/sh\necho 'OpenTofu v1.8.3'\necho 'on linux_amd64'\n",
+        );
+        assert_eq!(
+            detect_version(&binary).await.unwrap(),
+            (BinaryFlavor::OpenTofu, "1.8.3".to_string())
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_detect_version_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = fake_binary(dir.path(), "#!/bin/sh\nexec sleep 30\n");
+        let started = std::time::Instant::now();
+        let error = detect_version_within(&binary, Duration::from_millis(200))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("did not finish"), "{error}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_parse_version_line() {
+        assert_eq!(
+            parse_version_line("Terraform v1.9.5"),
+            Some((BinaryFlavor::Terraform, "1.9.5".to_string()))
+        );
+        assert_eq!(
+            parse_version_line("OpenTofu v1.8.0"),
+            Some((BinaryFlavor::OpenTofu, "1.8.0".to_string()))
+        );
+        assert_eq!(parse_version_line("on linux_amd64"), None);
+    }
+
+    #[test]
+    fn test_satisfies_simple_constraints() {
+        assert!(satisfies_constraint("1.6.0", ">= 1.5.0").unwrap());
+        assert!(!satisfies_constraint("1.4.0", ">= 1.5.0").unwrap());
+        assert!(satisfies_constraint("1.5.0", "1.5.0").unwrap());
+        assert!(satisfies_constraint("1.5.0", "= 1.5").unwrap());
+        assert!(!satisfies_constraint("1.5.1", "!= 1.5.1").unwrap());
+        assert!(satisfies_constraint("1.9.9", "< 2.0").unwrap());
+        assert!(!satisfies_constraint("2.0.0", "< 2.0").unwrap());
+    }
+
+    #[test]
+    fn test_satisfies_combined_and_pessimistic_constraints() {
+        assert!(satisfies_constraint("1.7.3", ">= 1.5.0, < 2.0.0").unwrap());
+        assert!(!satisfies_constraint("2.1.0", ">= 1.5.0, < 2.0.0").unwrap());
+
+        assert!(satisfies_constraint("1.9.0", "~> 1.6").unwrap());
+        assert!(!satisfies_constraint("2.0.0", "~> 1.6").unwrap());
+        assert!(satisfies_constraint("1.6.9", "~> 1.6.2").unwrap());
+        assert!(!satisfies_constraint("1.7.0", "~> 1.6.2").unwrap());
+        assert!(!satisfies_constraint("1.6.1", "~> 1.6.2").unwrap());
+    }
+
+    #[test]
+    fn test_check_compatibility() {
+        let compat = check_compatibility(Some("1.6.0"), ">= 1.5.0");
+        assert_eq!(compat.satisfied, Some(true));
+
+        let compat = check_compatibility(Some("1.4.0"), ">= 1.5.0");
+        assert_eq!(compat.satisfied, Some(false));
+        assert!(compat.message.contains("does not satisfy"));
+
+        let compat = check_compatibility(None, ">= 1.5.0");
+        assert_eq!(compat.satisfied, None);
+        assert_eq!(compat.installed_version, None);
+    }
+
+    #[test]
+    fn test_prerelease_and_invalid_versions() {
+        assert!(satisfies_constraint("v1.10.0-beta1", ">= 1.10").unwrap());
+        assert!(satisfies_constraint("not-a-version", ">= 1.0").is_err());
+        assert!(satisfies_constraint("1.0.0", ">= latest").is_err());
+    }
+
+    #[test]
+    fn test_flavor_args() {
+        let tofu = (BinaryFlavor::OpenTofu, "1.8.3".to_string());
+        let terraform = (BinaryFlavor::Terraform, "1.9.5".to_string());
+
+        assert_eq!(
+            flavor_args(Some(&tofu), &["apply", "-input=false", "plan.tfplan"]),
+            [
+                "apply",
+                "-consolidate-warnings=false",
+                "-input=false",
+                "plan.tfplan"
+            ]
+        );
+        // JSON output and other subcommands are left alone
+        assert_eq!(
+            flavor_args(Some(&tofu), &["plan", "-json"]),
+            ["plan", "-json"]
+        );
+        assert_eq!(
+            flavor_args(Some(&tofu), &["validate", "-json"]),
+            ["validate", "-json"]
+        );
+        // Releases before the flag existed reject it
+        let old_tofu = (BinaryFlavor::OpenTofu, "1.7.2".to_string());
+        assert_eq!(flavor_args(Some(&old_tofu), &["apply"]), ["apply"]);
+
+        // Terraform never receives OpenTofu-only flags
+        assert_eq!(
+            flavor_args(
+                Some(&terraform),
+                &[
+                    "plan",
+                    "-consolidate-warnings=false",
+                    "-consolidate-errors",
+                    "-input=false"
+                ]
+            ),
+            ["plan", "-input=false"]
+        );
+        assert_eq!(
+            flavor_args(None, &["apply", "-input=false"]),
+            ["apply", "-input=false"]
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::path::Path;
-use std::process::Command;
+use std::process::Output;
 
 /// Workspace action to perform
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -33,11 +33,30 @@ impl std::str::FromStr for WorkspaceAction {
     }
 }
 
+impl WorkspaceAction {
+    /// The `terraform workspace` subcommand
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkspaceAction::List => "list",
+            WorkspaceAction::Show => "show",
+            WorkspaceAction::New => "new",
+            WorkspaceAction::Select => "select",
+            WorkspaceAction::Delete => "delete",
+        }
+    }
+}
+
 /// Workspace information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceInfo {
     pub name: String,
     pub current: bool,
+    /// Whether the workspace's state has any resources; only set by `list` with counts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_state: Option<bool>,
+    /// Resources in the workspace's state; only set by `list` with counts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_count: Option<usize>,
 }
 
 /// Result of workspace operations
@@ -50,226 +69,182 @@ pub struct WorkspaceResult {
     pub message: String,
 }
 
-/// Execute a workspace operation
-pub fn execute_workspace(
-    terraform_path: &Path,
-    project_dir: &Path,
-    action: WorkspaceAction,
+/// Arguments of the `terraform workspace` command performing `action`
+pub fn workspace_args(
+    action: &WorkspaceAction,
     workspace_name: Option<&str>,
-) -> anyhow::Result<WorkspaceResult> {
-    match action {
-        WorkspaceAction::List => list_workspaces(terraform_path, project_dir),
-        WorkspaceAction::Show => show_workspace(terraform_path, project_dir),
+) -> anyhow::Result<Vec<String>> {
+    let required = |action: &str| {
+        workspace_name
+            .ok_or_else(|| anyhow::anyhow!("Workspace name required for '{}' action", action))
+    };
+    let name = match action {
+        WorkspaceAction::List | WorkspaceAction::Show => None,
         WorkspaceAction::New => {
-            let name = workspace_name
-                .ok_or_else(|| anyhow::anyhow!("Workspace name required for 'new' action"))?;
-            new_workspace(terraform_path, project_dir, name)
-        }
-        WorkspaceAction::Select => {
-            let name = workspace_name
-                .ok_or_else(|| anyhow::anyhow!("Workspace name required for 'select' action"))?;
-            select_workspace(terraform_path, project_dir, name)
+            let name = required("new")?;
+            // Validate workspace name
+            if !is_valid_workspace_name(name) {
+                return Err(anyhow::anyhow!(
+                    "Invalid workspace name: '{}'. Names must be alphanumeric with hyphens or underscores",
+                    name
+                ));
+            }
+            Some(name)
         }
+        WorkspaceAction::Select => Some(required("select")?),
         WorkspaceAction::Delete => {
-            let name = workspace_name
-                .ok_or_else(|| anyhow::anyhow!("Workspace name required for 'delete' action"))?;
-            delete_workspace(terraform_path, project_dir, name)
+            let name = required("delete")?;
+            // Cannot delete the default workspace
+            if name == "default" {
+                return Err(anyhow::anyhow!("Cannot delete the 'default' workspace"));
+            }
+            Some(name)
         }
-    }
+    };
+    Ok(["workspace", action.as_str()]
+        .into_iter()
+        .chain(name)
+        .map(str::to_string)
+        .collect())
 }
 
-/// List all workspaces
-fn list_workspaces(terraform_path: &Path, project_dir: &Path) -> anyhow::Result<WorkspaceResult> {
-    let output = Command::new(terraform_path)
-        .arg("workspace")
-        .arg("list")
-        .current_dir(project_dir)
-        .output()?;
-
-    if !output.status.success() {
+/// Refuse to delete `name` while it is the `current` workspace
+pub fn check_deletable(name: &str, current: &str) -> anyhow::Result<()> {
+    if current == name {
         return Err(anyhow::anyhow!(
-            "Failed to list workspaces: {}",
-            String::from_utf8_lossy(&output.stderr)
+            "Cannot delete workspace '{}' because it is currently selected. Switch to another workspace first.",
+            name
         ));
     }
+    Ok(())
+}
 
+/// Result of a run with [`workspace_args`]. A deletion reports no current
+/// workspace; the caller knows it from [`check_deletable`].
+pub fn workspace_result(
+    action: &WorkspaceAction,
+    workspace_name: Option<&str>,
+    output: &Output,
+) -> anyhow::Result<WorkspaceResult> {
+    let name = workspace_name.unwrap_or_default();
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut workspaces = Vec::new();
-    let mut current_workspace = None;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let missing = stderr.contains("doesn't exist") || stderr.contains("does not exist");
 
-    for line in stdout.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
+    if !output.status.success() {
+        return Err(match action {
+            WorkspaceAction::List => anyhow::anyhow!("Failed to list workspaces: {}", stderr),
+            WorkspaceAction::Show => anyhow::anyhow!("Failed to show workspace: {}", stderr),
+            WorkspaceAction::New if stderr.contains("already exists") => {
+                anyhow::anyhow!("Workspace '{}' already exists", name)
+            }
+            WorkspaceAction::New => anyhow::anyhow!("Failed to create workspace: {}", stderr),
+            WorkspaceAction::Select | WorkspaceAction::Delete if missing => {
+                anyhow::anyhow!("Workspace '{}' does not exist", name)
+            }
+            WorkspaceAction::Select => anyhow::anyhow!("Failed to select workspace: {}", stderr),
+            WorkspaceAction::Delete if stderr.contains("is not empty") => anyhow::anyhow!(
+                "Workspace '{}' is not empty. Use 'terraform workspace delete -force {}' to force deletion.",
+                name,
+                name
+            ),
+            WorkspaceAction::Delete => anyhow::anyhow!("Failed to delete workspace: {}", stderr),
+        });
+    }
 
-        if let Some(name) = line.strip_prefix("* ") {
-            current_workspace = Some(name.to_string());
-            workspaces.push(WorkspaceInfo {
-                name: name.to_string(),
-                current: true,
-            });
-        } else {
-            workspaces.push(WorkspaceInfo {
-                name: line.to_string(),
-                current: false,
-            });
+    let (current_workspace, workspaces, message) = match action {
+        WorkspaceAction::List => {
+            let workspaces = parse_workspace_list(&stdout);
+            let current = workspaces
+                .iter()
+                .find(|w| w.current)
+                .map(|w| w.name.clone());
+            let message = format!("Found {} workspaces", workspaces.len());
+            (current, Some(workspaces), message)
         }
-    }
+        WorkspaceAction::Show => {
+            let current = stdout.trim().to_string();
+            let message = format!("Current workspace: {}", current);
+            (Some(current), None, message)
+        }
+        WorkspaceAction::New => (
+            Some(name.to_string()),
+            None,
+            format!("Created and switched to workspace '{}'", name),
+        ),
+        WorkspaceAction::Select => (
+            Some(name.to_string()),
+            None,
+            format!("Switched to workspace '{}'", name),
+        ),
+        WorkspaceAction::Delete => (None, None, format!("Deleted workspace '{}'", name)),
+    };
 
-    let message = format!("Found {} workspaces", workspaces.len());
     Ok(WorkspaceResult {
         success: true,
-        action: "list".to_string(),
+        action: action.as_str().to_string(),
         current_workspace,
-        workspaces: Some(workspaces),
+        workspaces,
         message,
     })
 }
 
-/// Show current workspace
-fn show_workspace(terraform_path: &Path, project_dir: &Path) -> anyhow::Result<WorkspaceResult> {
-    let output = Command::new(terraform_path)
-        .arg("workspace")
-        .arg("show")
-        .current_dir(project_dir)
-        .output()?;
-
-    if !output.status.success() {
-        return Err(anyhow::anyhow!(
-            "Failed to show workspace: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    let current = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-    Ok(WorkspaceResult {
-        success: true,
-        action: "show".to_string(),
-        current_workspace: Some(current.clone()),
-        workspaces: None,
-        message: format!("Current workspace: {}", current),
-    })
+/// Workspaces in `terraform workspace list` output, the current one marked with `*`
+pub fn parse_workspace_list(stdout: &str) -> Vec<WorkspaceInfo> {
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (name, current) = match line.strip_prefix("* ") {
+                Some(name) => (name, true),
+                None => (line, false),
+            };
+            WorkspaceInfo {
+                name: name.to_string(),
+                current,
+                has_state: None,
+                resource_count: None,
+            }
+        })
+        .collect()
 }
 
-/// Create a new workspace
-fn new_workspace(
-    terraform_path: &Path,
-    project_dir: &Path,
-    name: &str,
-) -> anyhow::Result<WorkspaceResult> {
-    // Validate workspace name
-    if !is_valid_workspace_name(name) {
-        return Err(anyhow::anyhow!(
-            "Invalid workspace name: '{}'. Names must be alphanumeric with hyphens or underscores",
-            name
-        ));
-    }
+/// Environment variable overriding the selected workspace for one command
+pub const WORKSPACE_ENV: &str = "TF_WORKSPACE";
 
-    let output = Command::new(terraform_path)
-        .arg("workspace")
-        .arg("new")
-        .arg(name)
-        .current_dir(project_dir)
-        .output()?;
+/// Subcommands given `<workspace>.tfvars` when `auto_tfvars_by_workspace` is on
+const VAR_FILE_SUBCOMMANDS: &[&str] = &["plan", "apply", "destroy"];
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("already exists") {
-            return Err(anyhow::anyhow!("Workspace '{}' already exists", name));
+/// Workspace terraform will use in `project_dir`, read the way terraform
+/// does: `TF_WORKSPACE`, then the data directory's `environment` file
+pub fn selected_workspace(project_dir: &Path) -> String {
+    if let Ok(workspace) = std::env::var(WORKSPACE_ENV) {
+        if !workspace.trim().is_empty() {
+            return workspace.trim().to_string();
         }
-        return Err(anyhow::anyhow!("Failed to create workspace: {}", stderr));
     }
-
-    Ok(WorkspaceResult {
-        success: true,
-        action: "new".to_string(),
-        current_workspace: Some(name.to_string()),
-        workspaces: None,
-        message: format!("Created and switched to workspace '{}'", name),
-    })
+    let data_dir = std::env::var("TF_DATA_DIR").unwrap_or_else(|_| ".terraform".to_string());
+    std::fs::read_to_string(project_dir.join(data_dir).join("environment"))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "default".to_string())
 }
 
-/// Select (switch to) a workspace
-fn select_workspace(
-    terraform_path: &Path,
-    project_dir: &Path,
-    name: &str,
-) -> anyhow::Result<WorkspaceResult> {
-    let output = Command::new(terraform_path)
-        .arg("workspace")
-        .arg("select")
-        .arg(name)
-        .current_dir(project_dir)
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("doesn't exist") || stderr.contains("does not exist") {
-            return Err(anyhow::anyhow!("Workspace '{}' does not exist", name));
-        }
-        return Err(anyhow::anyhow!("Failed to select workspace: {}", stderr));
+/// `<workspace>.tfvars` for a command with `args` when it exists in
+/// `project_dir`. Applying a saved plan file takes no variables, so such
+/// an `apply` gets none.
+pub fn workspace_var_file(project_dir: &Path, workspace: &str, args: &[String]) -> Option<String> {
+    let (subcommand, rest) = args.split_first()?;
+    if !VAR_FILE_SUBCOMMANDS.contains(&subcommand.as_str())
+        || (subcommand == "apply" && rest.iter().any(|arg| !arg.starts_with('-')))
+        || !is_valid_workspace_name(workspace)
+    {
+        return None;
     }
-
-    Ok(WorkspaceResult {
-        success: true,
-        action: "select".to_string(),
-        current_workspace: Some(name.to_string()),
-        workspaces: None,
-        message: format!("Switched to workspace '{}'", name),
-    })
-}
-
-/// Delete a workspace
-fn delete_workspace(
-    terraform_path: &Path,
-    project_dir: &Path,
-    name: &str,
-) -> anyhow::Result<WorkspaceResult> {
-    // Cannot delete the default workspace
-    if name == "default" {
-        return Err(anyhow::anyhow!("Cannot delete the 'default' workspace"));
-    }
-
-    // Check if trying to delete current workspace
-    let current = show_workspace(terraform_path, project_dir)?;
-    if current.current_workspace.as_deref() == Some(name) {
-        return Err(anyhow::anyhow!(
-            "Cannot delete workspace '{}' because it is currently selected. Switch to another workspace first.",
-            name
-        ));
-    }
-
-    let output = Command::new(terraform_path)
-        .arg("workspace")
-        .arg("delete")
-        .arg(name)
-        .current_dir(project_dir)
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("doesn't exist") || stderr.contains("does not exist") {
-            return Err(anyhow::anyhow!("Workspace '{}' does not exist", name));
-        }
-        if stderr.contains("is not empty") {
-            return Err(anyhow::anyhow!(
-                "Workspace '{}' is not empty. Use 'terraform workspace delete -force {}' to force deletion.",
-                name,
-                name
-            ));
-        }
-        return Err(anyhow::anyhow!("Failed to delete workspace: {}", stderr));
-    }
-
-    Ok(WorkspaceResult {
-        success: true,
-        action: "delete".to_string(),
-        current_workspace: current.current_workspace,
-        workspaces: None,
-        message: format!("Deleted workspace '{}'", name),
-    })
+    let file = format!("{}.tfvars", workspace);
+    project_dir.join(&file).is_file().then_some(file)
 }
 
 /// Validate workspace name
@@ -314,6 +289,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_workspace_list() {
+        let workspaces = parse_workspace_list("  default\n* staging\n\n  prod\n");
+        let names: Vec<_> = workspaces
+            .iter()
+            .map(|w| (w.name.as_str(), w.current))
+            .collect();
+        assert_eq!(
+            names,
+            [("default", false), ("staging", true), ("prod", false)]
+        );
+    }
+
     #[test]
     fn test_valid_workspace_name() {
         assert!(is_valid_workspace_name("dev"));
@@ -323,4 +311,43 @@ mod tests {
         assert!(!is_valid_workspace_name("name with spaces"));
         assert!(!is_valid_workspace_name("name/slash"));
     }
+
+    #[test]
+    fn test_workspace_var_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            workspace_var_file(dir.path(), "staging", &args(&["plan", "-json"])),
+            None
+        );
+
+        std::fs::write(dir.path().join("staging.tfvars"), "instance_count = 1\n").unwrap();
+        assert_eq!(
+            workspace_var_file(dir.path(), "staging", &args(&["plan", "-json"])).as_deref(),
+            Some("staging.tfvars")
+        );
+        assert_eq!(
+            workspace_var_file(dir.path(), "staging", &args(&["apply", "-auto-approve"]))
+                .as_deref(),
+            Some("staging.tfvars")
+        );
+        assert_eq!(
+            workspace_var_file(dir.path(), "prod", &args(&["plan"])),
+            None
+        );
+        // Saved plans carry their variables; other subcommands take none
+        assert_eq!(
+            workspace_var_file(
+                dir.path(),
+                "staging",
+                &args(&["apply", "-input=false", "tfmcp.tfplan"])
+            ),
+            None
+        );
+        assert_eq!(
+            workspace_var_file(dir.path(), "staging", &args(&["validate", "-json"])),
+            None
+        );
+    }
 }
@@ -5,9 +5,17 @@
 
 use rmcp::{
     ClientHandler, ServerHandler, ServiceExt,
-    model::{CallToolRequestParams, ClientInfo, ReadResourceRequestParams, ServerJsonRpcMessage},
+    model::{
+        CallToolRequestParams, ClientInfo, GetPromptRequestParams, LoggingMessageNotificationParam,
+        PromptMessageContent, ReadResourceRequestParams, ResourceContents,
+        ResourceUpdatedNotificationParam, ServerJsonRpcMessage, SubscribeRequestParams,
+        UnsubscribeRequestParams,
+    },
+    service::NotificationContext,
     transport::{IntoTransport, Transport},
 };
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
 use tfmcp::core::tfmcp::TfMcp;
 use tfmcp::mcp::server::TfMcpServer;
 
@@ -73,6 +81,46 @@ impl ClientHandler for TestClientHandler {
     }
 }
 
+/// Client handler that forwards resource update notifications to a channel
+#[derive(Debug, Clone)]
+struct SubscribingClientHandler {
+    updates: tokio::sync::mpsc::UnboundedSender<String>,
+}
+
+impl ClientHandler for SubscribingClientHandler {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo::default()
+    }
+
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<rmcp::RoleClient>,
+    ) {
+        let _ = self.updates.send(params.uri);
+    }
+}
+
+/// Client handler that forwards logging notifications to a channel
+#[derive(Debug, Clone)]
+struct LoggingClientHandler {
+    messages: tokio::sync::mpsc::UnboundedSender<LoggingMessageNotificationParam>,
+}
+
+impl ClientHandler for LoggingClientHandler {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo::default()
+    }
+
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<rmcp::RoleClient>,
+    ) {
+        let _ = self.messages.send(params);
+    }
+}
+
 // =============================================================================
 // ServerHandler trait tests (direct, no transport)
 // =============================================================================
@@ -90,6 +138,13 @@ async fn test_server_info_fields() {
     assert_eq!(info.server_info.version, env!("CARGO_PKG_VERSION"));
     assert!(info.capabilities.tools.is_some());
     assert!(info.capabilities.resources.is_some());
+    assert_eq!(
+        info.capabilities
+            .resources
+            .as_ref()
+            .and_then(|r| r.subscribe),
+        Some(true)
+    );
     assert!(info.capabilities.prompts.is_some());
     assert!(info.instructions.is_some());
     assert!(
@@ -172,7 +227,8 @@ async fn test_e2e_list_resources() {
 
     let resources = client.list_resources(None).await.expect("list_resources");
 
-    assert_eq!(resources.resources.len(), 3, "Should have 3 MCP resources");
+    // Audit logging is on by default, which adds the audit log resource
+    assert_eq!(resources.resources.len(), 7, "Should have 7 MCP resources");
 
     let uris: Vec<&str> = resources
         .resources
@@ -182,6 +238,10 @@ async fn test_e2e_list_resources() {
     assert!(uris.contains(&"terraform://style-guide"));
     assert!(uris.contains(&"terraform://module-development"));
     assert!(uris.contains(&"terraform://best-practices"));
+    assert!(uris.contains(&"terraform://project-files"));
+    assert!(uris.contains(&"terraform://tfmcp-config"));
+    assert!(uris.contains(&"terraform://files"));
+    assert!(uris.contains(&"terraform://audit-log"));
 }
 
 #[tokio::test]
@@ -206,6 +266,61 @@ async fn test_e2e_read_resource() {
     );
 }
 
+#[tokio::test]
+async fn test_e2e_read_tfmcp_config_resource() {
+    let Some((client, dir)) = start_e2e().await else {
+        eprintln!("skipping: terraform not available");
+        return;
+    };
+
+    let result = client
+        .read_resource(ReadResourceRequestParams::new("terraform://tfmcp-config"))
+        .await
+        .expect("read_resource");
+
+    assert_eq!(result.contents.len(), 1);
+    let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+        panic!("Expected text content");
+    };
+    let config: serde_json::Value = serde_json::from_str(text).expect("valid JSON");
+    let project_dir = config["project_directory"].as_str().unwrap_or_default();
+    assert!(project_dir.ends_with(&*dir.path().file_name().unwrap().to_string_lossy()));
+    assert!(config["terraform"]["binary_path"].is_string());
+    assert!(config["security"]["allowed_commands"].is_array());
+    assert!(config["cache"]["documentation_ttl_secs"].is_u64());
+}
+
+#[tokio::test]
+async fn test_tfmcp_config_redacts_secrets() {
+    let temp_dir = tempfile::tempdir().expect("temp dir");
+    let dir_str = temp_dir.path().to_string_lossy().to_string();
+    let Ok(tfmcp) = TfMcp::new(None, Some(dir_str)) else {
+        eprintln!("skipping: terraform not available");
+        return;
+    };
+
+    let env = vec![
+        (
+            "TF_TOKEN_app_terraform_io".to_string(),
+            "super-secret-token".to_string(),
+        ),
+        ("TF_VAR_db_password".to_string(), "hunter2".to_string()),
+        ("TFMCP_LOG_LEVEL".to_string(), "debug".to_string()),
+        ("HOME".to_string(), "/home/user".to_string()),
+    ];
+    let config = tfmcp.effective_config_from_env(env).await;
+    let environment = &config["environment"];
+
+    assert_eq!(environment["TF_TOKEN_app_terraform_io"], "[REDACTED]");
+    assert_eq!(environment["TF_VAR_db_password"], "[REDACTED]");
+    assert_eq!(environment["TFMCP_LOG_LEVEL"], "debug");
+    assert!(environment.get("HOME").is_none());
+
+    let serialized = config.to_string();
+    assert!(!serialized.contains("super-secret-token"));
+    assert!(!serialized.contains("hunter2"));
+}
+
 #[tokio::test]
 async fn test_e2e_read_resource_not_found() {
     let Some((client, _dir)) = start_e2e().await else {
@@ -220,6 +335,83 @@ async fn test_e2e_read_resource_not_found() {
     assert!(result.is_err(), "Reading unknown resource should fail");
 }
 
+#[tokio::test]
+async fn test_e2e_document_module_prompt() {
+    let Some((client, _dir)) = start_e2e().await else {
+        eprintln!("skipping: terraform not available");
+        return;
+    };
+
+    let prompts = client.list_prompts(None).await.expect("list_prompts");
+    let names: Vec<&str> = prompts.prompts.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["document_module"]);
+
+    let result = client
+        .get_prompt(GetPromptRequestParams::new("document_module"))
+        .await
+        .expect("get_prompt");
+    assert_eq!(result.messages.len(), 1);
+    let PromptMessageContent::Text { text } = &result.messages[0].content else {
+        panic!("expected a text prompt message");
+    };
+    assert!(text.contains("`greeting`"));
+    assert!(text.contains("A greeting message"));
+    assert!(text.contains("`file_path`"));
+    assert!(text.contains("`local_file.test`"));
+
+    let result = client
+        .get_prompt(GetPromptRequestParams::new("nonexistent"))
+        .await;
+    assert!(result.is_err(), "Getting unknown prompt should fail");
+}
+
+#[tokio::test]
+async fn test_e2e_describe_tools_annotations() {
+    let Some((client, _dir)) = start_e2e().await else {
+        eprintln!("skipping: terraform not available");
+        return;
+    };
+
+    let result = client
+        .call_tool(CallToolRequestParams::new("describe_tools"))
+        .await
+        .expect("call_tool describe_tools");
+    let text = result.content[0].raw.as_text().expect("text content");
+    let json: serde_json::Value = serde_json::from_str(&text.text).expect("valid JSON");
+    let tools = json["tools"].as_array().expect("tools array");
+
+    let listed = client.list_tools(None).await.expect("list_tools");
+    assert_eq!(tools.len(), listed.tools.len());
+
+    let tool = |name: &str| {
+        tools
+            .iter()
+            .find(|t| t["name"] == name)
+            .unwrap_or_else(|| panic!("{} should be described", name))
+    };
+    for name in [
+        "apply_terraform",
+        "apply_saved_plan",
+        "destroy_terraform",
+        "terraform_import",
+        "terraform_taint",
+        "terraform_refresh",
+    ] {
+        assert_eq!(tool(name)["destructive"], true, "{} is destructive", name);
+    }
+
+    let plan = tool("get_terraform_plan");
+    assert_eq!(plan["read_only"], true);
+    assert_ne!(plan["destructive"], true);
+    assert!(plan["description"].as_str().is_some_and(|d| !d.is_empty()));
+    assert_eq!(tool("describe_tools")["title"], "Describe Tools");
+    assert_eq!(tool("set_terraform_directory")["idempotent"], true);
+    assert_eq!(
+        tool("set_terraform_directory")["input_schema"]["type"],
+        "object"
+    );
+}
+
 #[tokio::test]
 async fn test_e2e_call_tool_list_resources() {
     let Some((client, _dir)) = start_e2e().await else {
@@ -249,10 +441,20 @@ async fn test_e2e_call_tool_validate() {
         return;
     };
 
-    let result = client
+    // An uninitialized project is reported as a JSON-RPC error telling the
+    // client to run init; any other outcome is a tool result
+    let result = match client
         .call_tool(CallToolRequestParams::new("validate_terraform"))
         .await
-        .expect("call_tool validate_terraform");
+    {
+        Ok(result) => result,
+        Err(rmcp::ServiceError::McpError(e)) => {
+            assert_eq!(e.code, rmcp::model::ErrorCode::INVALID_REQUEST);
+            assert_eq!(e.data.unwrap()["kind"], "init_required");
+            return;
+        }
+        Err(e) => panic!("call_tool validate_terraform: {:?}", e),
+    };
 
     assert!(
         !result.content.is_empty(),
@@ -299,6 +501,161 @@ async fn test_e2e_call_tool_get_security_status() {
     }
 }
 
+/// Span name and recorded fields
+type CapturedSpan = (String, BTreeMap<String, String>);
+
+/// Tracing layer that keeps the fields of each span once it closes
+#[derive(Clone, Default)]
+struct SpanCapture {
+    open: Arc<Mutex<HashMap<tracing::span::Id, CapturedSpan>>>,
+    closed: Arc<Mutex<Vec<CapturedSpan>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+
+impl tracing::field::Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanCapture {
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        self.open
+            .lock()
+            .unwrap()
+            .insert(id.clone(), (attrs.metadata().name().to_string(), fields));
+    }
+
+    fn on_record(
+        &self,
+        id: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if let Some((_, fields)) = self.open.lock().unwrap().get_mut(id) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+
+    fn on_close(&self, id: tracing::span::Id, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+        if let Some(span) = self.open.lock().unwrap().remove(&id) {
+            self.closed.lock().unwrap().push(span);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_tool_call_span_fields() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    // The current-thread test runtime runs the server task on this thread,
+    // so a thread-local subscriber sees its spans
+    let capture = SpanCapture::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let Some((client, _dir)) = start_e2e().await else {
+        eprintln!("skipping: terraform not available");
+        return;
+    };
+
+    client
+        .call_tool(CallToolRequestParams::new("get_security_status"))
+        .await
+        .expect("call_tool get_security_status");
+    client.cancel().await.expect("client cancel");
+
+    let closed = capture.closed.lock().unwrap();
+    let (_, fields) = closed
+        .iter()
+        .find(|(name, fields)| {
+            name == "tool_call"
+                && fields.get("tool").map(String::as_str) == Some("get_security_status")
+        })
+        .expect("tool_call span for get_security_status");
+    assert!(fields.contains_key("request_id"));
+    assert!(
+        fields["elapsed_ms"].parse::<u64>().is_ok(),
+        "elapsed_ms should be recorded: {:?}",
+        fields
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_denied_tool_is_hidden_and_rejected() {
+    if !terraform_available() {
+        eprintln!("skipping: terraform not available");
+        return;
+    }
+    let temp_dir = tempfile::tempdir().expect("temp dir");
+    std::fs::write(temp_dir.path().join("main.tf"), "").expect("main.tf");
+    let config_path = temp_dir.path().join("tfmcp.json");
+    std::fs::write(
+        &config_path,
+        serde_json::json!({
+            "terraform": {},
+            "mcp": { "tools": [], "denied_tools": ["apply_terraform", "destroy_terraform"] }
+        })
+        .to_string(),
+    )
+    .expect("config");
+    let tfmcp = TfMcp::new(
+        Some(config_path.to_string_lossy().to_string()),
+        Some(temp_dir.path().to_string_lossy().to_string()),
+    )
+    .expect("tfmcp");
+    let server = TfMcpServer::new(tfmcp, tfmcp::mcp::server::ToolFilter::all());
+
+    let (server_transport, client_transport) = tokio::io::duplex(65536);
+    tokio::spawn(async move {
+        let svc = server.serve(server_transport).await.expect("server serve");
+        svc.waiting().await.expect("server waiting");
+    });
+    let client = TestClientHandler
+        .serve(client_transport)
+        .await
+        .expect("client serve");
+
+    let tools = client.list_tools(None).await.expect("list_tools");
+    let names: Vec<&str> = tools.tools.iter().map(|t| t.name.as_ref()).collect();
+    assert!(!names.contains(&"apply_terraform"));
+    assert!(!names.contains(&"destroy_terraform"));
+    assert!(names.contains(&"get_terraform_plan"));
+
+    let err = client
+        .call_tool(CallToolRequestParams::new("apply_terraform"))
+        .await
+        .expect_err("denied tool should be rejected");
+    assert!(
+        err.to_string().contains("disabled by policy"),
+        "unexpected error: {}",
+        err
+    );
+
+    // An allowlist hides everything else; the denylist still wins over it
+    let filter = tfmcp::mcp::server::ToolFilter::all().with_policy(&tfmcp::config::McpConfig {
+        tools: Vec::new(),
+        allowed_tools: Some(vec!["get_terraform_plan".into(), "apply_terraform".into()]),
+        denied_tools: vec!["apply_terraform".into()],
+    });
+    assert!(filter.is_enabled("get_terraform_plan"));
+    assert!(!filter.is_enabled("analyze_terraform"));
+    assert!(filter.is_disabled_by_policy("apply_terraform"));
+}
+
 #[tokio::test]
 async fn test_e2e_call_tool_with_unknown_name() {
     let Some((client, _dir)) = start_e2e().await else {
@@ -445,7 +802,8 @@ async fn test_raw_protocol_initialize() {
             let json = serde_json::to_value(&r.result).unwrap();
             assert!(json["resources"].is_array());
             let resources = json["resources"].as_array().unwrap();
-            assert_eq!(resources.len(), 3, "Should have 3 resources");
+            // Includes the audit log resource, as audit logging is on by default
+            assert_eq!(resources.len(), 7, "Should have 7 resources");
         }
         other => panic!("Expected Response, got: {other:?}"),
     }
@@ -502,3 +860,325 @@ async fn test_raw_protocol_ping() {
         other => panic!("Expected Response to ping, got: {other:?}"),
     }
 }
+
+#[tokio::test]
+async fn test_e2e_subscribe_project_files() {
+    let Some((server, dir)) = setup_server().await else {
+        eprintln!("skipping: terraform not available");
+        return;
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(65536);
+    tokio::spawn(async move {
+        let svc = server.serve(server_transport).await.expect("server serve");
+        svc.waiting().await.expect("server waiting");
+    });
+
+    let (updates_tx, mut updates_rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = SubscribingClientHandler {
+        updates: updates_tx,
+    }
+    .serve(client_transport)
+    .await
+    .expect("client serve");
+
+    client
+        .subscribe(SubscribeRequestParams::new("terraform://project-files"))
+        .await
+        .expect("subscribe");
+
+    tokio::fs::write(dir.path().join("extra.tf"), "# touched")
+        .await
+        .expect("touch file");
+
+    let uri = tokio::time::timeout(std::time::Duration::from_secs(10), updates_rx.recv())
+        .await
+        .expect("update notification should arrive")
+        .expect("channel open");
+    assert_eq!(uri, "terraform://project-files");
+
+    client
+        .unsubscribe(UnsubscribeRequestParams::new("terraform://project-files"))
+        .await
+        .expect("unsubscribe");
+}
+
+// =============================================================================
+// E2E tests over streamable HTTP transport
+// =============================================================================
+
+#[tokio::test]
+async fn test_e2e_http_transport_lists_tools() {
+    let Some((server, _dir)) = setup_server().await else {
+        eprintln!("skipping: terraform not available");
+        return;
+    };
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind listener");
+    let addr = listener.local_addr().expect("local addr");
+    tokio::spawn(async move {
+        server.serve_http_on(listener).await.expect("serve http");
+    });
+
+    let transport = rmcp::transport::StreamableHttpClientTransport::from_uri(format!(
+        "http://{}{}",
+        addr,
+        tfmcp::mcp::server::HTTP_ENDPOINT_PATH
+    ));
+    let client = TestClientHandler
+        .serve(transport)
+        .await
+        .expect("client initialize over http");
+
+    let info = client.peer_info().expect("server info after initialize");
+    assert_eq!(info.server_info.name, "tfmcp");
+
+    let tools = client.list_tools(None).await.expect("list_tools over http");
+    let tool_names: Vec<&str> = tools.tools.iter().map(|t| t.name.as_ref()).collect();
+    assert!(tool_names.contains(&"list_terraform_resources"));
+    assert!(tool_names.contains(&"analyze_plan"));
+
+    client.cancel().await.expect("client cancel");
+}
+
+// =============================================================================
+// Shutdown tests
+// =============================================================================
+
+/// Create a project whose terraform binary is `script`, configured via a
+/// config file so the fake binary is not looked up on PATH
+#[cfg(unix)]
+fn setup_fake_terraform_server(
+    temp_dir: &std::path::Path,
+    script: &str,
+) -> (TfMcpServer, std::path::PathBuf) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project_dir = temp_dir.join("project");
+    std::fs::create_dir(&project_dir).expect("project dir");
+    std::fs::write(project_dir.join("main.tf"), "# empty\n").expect("main.tf");
+
+    let fake_terraform = temp_dir.join("terraform");
+    std::fs::write(&fake_terraform, script).expect("fake terraform");
+    std::fs::set_permissions(&fake_terraform, std::fs::Permissions::from_mode(0o755))
+        .expect("chmod");
+
+    let config_path = temp_dir.join("config.json");
+    std::fs::write(
+        &config_path,
+        serde_json::json!({
+            "terraform": { "executable_path": fake_terraform },
+            "mcp": { "tools": [] }
+        })
+        .to_string(),
+    )
+    .expect("config");
+
+    let tfmcp = TfMcp::new(
+        Some(config_path.to_string_lossy().to_string()),
+        Some(project_dir.to_string_lossy().to_string()),
+    )
+    .expect("tfmcp");
+    (
+        TfMcpServer::new(tfmcp, tfmcp::mcp::server::ToolFilter::all()),
+        project_dir,
+    )
+}
+
+/// Wait until the fake terraform has written its pid file
+#[cfg(unix)]
+async fn wait_for_pid_file(pid_file: &std::path::Path) -> String {
+    tokio::time::timeout(std::time::Duration::from_secs(10), async {
+        loop {
+            if let Ok(pid) = tokio::fs::read_to_string(pid_file).await {
+                if !pid.trim().is_empty() {
+                    break pid.trim().to_string();
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("terraform plan should start")
+}
+
+/// Closing the transport while `terraform plan` runs kills and reaps the child
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_e2e_transport_close_reaps_running_terraform() {
+    let temp_dir = tempfile::tempdir().expect("temp dir");
+    // `exec` keeps the pid written to plan.pid as the terraform child itself
+    let (server, project_dir) = setup_fake_terraform_server(
+        temp_dir.path(),
+        "#!/bin/sh\ncase \"$1\" in\n  plan) echo $$ > \"$PWD/plan.pid\"; exec sleep 30 ;;\n  *) exit 0 ;;\nesac\n",
+    );
+
+    let (server_transport, client_transport) = tokio::io::duplex(65536);
+    let server_task = tokio::spawn(TfMcpServer::serve_until_closed(server, server_transport));
+
+    let client = TestClientHandler
+        .serve(client_transport)
+        .await
+        .expect("client serve");
+    let peer = client.peer().clone();
+    tokio::spawn(async move {
+        let _ = peer
+            .call_tool(CallToolRequestParams::new("get_terraform_plan"))
+            .await;
+    });
+
+    let pid = wait_for_pid_file(&project_dir.join("plan.pid")).await;
+    assert!(std::path::Path::new(&format!("/proc/{}", pid)).exists());
+
+    client.cancel().await.expect("client cancel");
+
+    tokio::time::timeout(std::time::Duration::from_secs(15), server_task)
+        .await
+        .expect("server should shut down after the transport closes")
+        .expect("server task")
+        .expect("server result");
+    assert!(
+        !std::path::Path::new(&format!("/proc/{}", pid)).exists(),
+        "terraform child {} should be killed and reaped",
+        pid
+    );
+}
+
+/// A slow plan must not block set_terraform_directory, and keeps running
+/// against the directory it started in
+#[cfg(unix)]
+#[tokio::test]
+async fn test_e2e_directory_change_during_slow_plan() {
+    let temp_dir = tempfile::tempdir().expect("temp dir");
+    let (server, project_dir) = setup_fake_terraform_server(
+        temp_dir.path(),
+        "#!/bin/sh\ncase \"$1\" in\n  plan) echo $$ > \"$PWD/plan.pid\"; sleep 2; echo \"{\\\"cwd\\\":\\\"$PWD\\\"}\" ;;\n  *) exit 0 ;;\nesac\n",
+    );
+    let other_dir = temp_dir.path().join("other");
+    std::fs::create_dir(&other_dir).expect("other dir");
+    std::fs::write(other_dir.join("main.tf"), "# other\n").expect("other main.tf");
+
+    let (server_transport, client_transport) = tokio::io::duplex(65536);
+    tokio::spawn(TfMcpServer::serve_until_closed(server, server_transport));
+    let client = TestClientHandler
+        .serve(client_transport)
+        .await
+        .expect("client serve");
+
+    let peer = client.peer().clone();
+    let plan = tokio::spawn(async move {
+        peer.call_tool(CallToolRequestParams::new("get_terraform_plan"))
+            .await
+    });
+    wait_for_pid_file(&project_dir.join("plan.pid")).await;
+
+    let mut args = serde_json::Map::new();
+    args.insert(
+        "directory".to_string(),
+        serde_json::Value::String(other_dir.to_string_lossy().to_string()),
+    );
+    let switched = tokio::time::timeout(
+        std::time::Duration::from_secs(1),
+        client
+            .call_tool(CallToolRequestParams::new("set_terraform_directory").with_arguments(args)),
+    )
+    .await
+    .expect("directory change should not wait for the running plan")
+    .expect("set_terraform_directory");
+    assert_ne!(switched.is_error, Some(true));
+    assert!(!plan.is_finished(), "plan should still be running");
+
+    let result = plan.await.expect("plan task").expect("plan call");
+    assert_ne!(result.is_error, Some(true));
+    let text = result.content[0].as_text().expect("text").text.clone();
+    assert!(
+        text.contains(&*project_dir.canonicalize().unwrap().to_string_lossy())
+            || text.contains(&*project_dir.to_string_lossy()),
+        "plan should run in the original directory: {}",
+        text
+    );
+
+    client.cancel().await.expect("client cancel");
+}
+
+/// Fake `terraform plan -json` that pauses between its first message and the rest
+#[cfg(unix)]
+const STREAMING_PLAN_SCRIPT: &str = r#"#!/bin/sh
+case "$1" in
+  plan)
+    echo '{"@level":"info","@message":"aws_instance.web: Refreshing state...","type":"refresh_start"}'
+    sleep 1
+    echo '{"@level":"info","@message":"aws_instance.web: Plan to create","type":"planned_change","change":{"resource":{"addr":"aws_instance.web","resource_type":"aws_instance"},"action":"create"}}'
+    echo '{"@level":"info","@message":"Plan: 1 to add, 0 to change, 0 to destroy.","type":"change_summary","changes":{"add":1,"change":0,"remove":0}}'
+    ;;
+  *) exit 0 ;;
+esac
+"#;
+
+/// A streamed plan forwards plan messages while terraform is still running,
+/// then returns the summary
+#[cfg(unix)]
+#[tokio::test]
+async fn test_e2e_streamed_plan_notifies_before_result() {
+    let temp_dir = tempfile::tempdir().expect("temp dir");
+    let (server, _project_dir) =
+        setup_fake_terraform_server(temp_dir.path(), STREAMING_PLAN_SCRIPT);
+
+    let (server_transport, client_transport) = tokio::io::duplex(65536);
+    tokio::spawn(TfMcpServer::serve_until_closed(server, server_transport));
+    let (messages_tx, mut messages_rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = LoggingClientHandler {
+        messages: messages_tx,
+    }
+    .serve(client_transport)
+    .await
+    .expect("client serve");
+
+    let peer = client.peer().clone();
+    let plan = tokio::spawn(async move {
+        let mut args = serde_json::Map::new();
+        args.insert("stream".to_string(), serde_json::Value::Bool(true));
+        peer.call_tool(CallToolRequestParams::new("get_terraform_plan").with_arguments(args))
+            .await
+    });
+
+    let first = tokio::time::timeout(std::time::Duration::from_secs(10), messages_rx.recv())
+        .await
+        .expect("first plan message should arrive")
+        .expect("channel open");
+    assert!(
+        !plan.is_finished(),
+        "the first message must arrive while the plan is still running"
+    );
+    assert_eq!(first.logger.as_deref(), Some("terraform.plan"));
+    assert_eq!(first.data["type"], "refresh_start");
+
+    let result = plan.await.expect("plan task").expect("plan call");
+    assert_ne!(result.is_error, Some(true));
+    let summary: serde_json::Value =
+        serde_json::from_str(&result.content[0].as_text().expect("text").text).expect("json");
+    assert_eq!(summary["summary"]["add"], 1);
+    assert_eq!(summary["messages_streamed"], 3);
+    assert_eq!(
+        summary["resource_changes"][0]["address"],
+        "aws_instance.web"
+    );
+
+    let mut rest = Vec::new();
+    for _ in 0..2 {
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), messages_rx.recv())
+            .await
+            .expect("remaining plan messages should arrive")
+            .expect("channel open");
+        rest.push(
+            message.data["type"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        );
+    }
+    assert_eq!(rest, ["planned_change", "change_summary"]);
+
+    client.cancel().await.expect("client cancel");
+}
@@ -415,6 +415,66 @@ variable "subnet_cidr" {
     );
 }
 
+/// Test that module health flags a local module call that doesn't match the module
+#[tokio::test]
+async fn test_module_health_reports_module_wiring() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let project_path = temp_dir.path();
+
+    fs::write(
+        project_path.join("main.tf"),
+        r#"
+module "network" {
+  source = "./modules/network"
+  cidr   = "10.0.0.0/16"
+}
+
+output "vpc_id" {
+  value = module.network.id
+}
+"#,
+    )
+    .unwrap();
+    let module_dir = project_path.join("modules/network");
+    fs::create_dir_all(&module_dir).unwrap();
+    fs::write(
+        module_dir.join("variables.tf"),
+        "variable \"cidr_block\" {\n  type = string\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        module_dir.join("outputs.tf"),
+        "output \"vpc_id\" {\n  value = \"vpc-123\"\n}\n",
+    )
+    .unwrap();
+
+    let service = tfmcp::TerraformService::new(
+        std::path::PathBuf::from("terraform"),
+        project_path.to_path_buf(),
+    );
+    let health = service
+        .analyze_module_health()
+        .await
+        .expect("Module health analysis should succeed");
+
+    let mut wiring: Vec<&str> = health
+        .issues
+        .iter()
+        .filter(|i| {
+            matches!(
+                i.category,
+                tfmcp::terraform::model::IssueCategory::ModuleWiring
+            )
+        })
+        .map(|i| i.message.as_str())
+        .collect();
+    wiring.sort();
+    assert_eq!(wiring.len(), 3, "unexpected issues: {:?}", wiring);
+    assert!(wiring[0].contains("does not pass required input 'cidr_block'"));
+    assert!(wiring[1].contains("output 'id' is referenced"));
+    assert!(wiring[2].contains("passes 'cidr'"));
+}
+
 /// Test resource dependency graph generation
 #[tokio::test]
 async fn test_resource_dependency_graph() {
@@ -537,3 +597,1581 @@ variable "var5" { type = string }
         "Should suggest adding descriptions"
     );
 }
+
+// ==================== Init Handling Tests ====================
+
+/// Fake terraform binary that refuses to run anything but `init` until
+/// `.terraform/` exists in the working directory.
+#[cfg(unix)]
+const FAKE_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+if [ "$1" = "init" ]; then
+  mkdir -p .terraform
+  echo "Terraform has been successfully initialized!"
+  exit 0
+fi
+if [ ! -d .terraform ]; then
+  echo 'Error: Module not installed. Run "terraform init" to install all modules.' >&2
+  exit 1
+fi
+echo '{"valid":true,"error_count":0,"warning_count":0,"diagnostics":[]}'
+"#;
+
+#[cfg(unix)]
+fn write_fake_terraform(dir: &std::path::Path, script: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("fake-terraform");
+    fs::write(&path, script).expect("Failed to write fake terraform");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+        .expect("Failed to make fake terraform executable");
+    path
+}
+
+/// Test that commands report a friendly error when init is required
+#[cfg(unix)]
+#[tokio::test]
+async fn test_init_required_friendly_error() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), FAKE_TERRAFORM_SCRIPT);
+
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let err = service
+        .validate(false)
+        .await
+        .expect_err("validate should fail before init");
+    assert!(err.to_string().contains("initialization required"));
+
+    let err = service
+        .get_state(false, None)
+        .await
+        .expect_err("state should fail before init");
+    assert!(err.to_string().contains("initialization required"));
+
+    // Without auto_init the project must be left untouched
+    assert!(!project_dir.path().join(".terraform").exists());
+}
+
+/// Test that auto_init runs init once and retries the original command
+#[cfg(unix)]
+#[tokio::test]
+async fn test_init_required_auto_init_retry() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), FAKE_TERRAFORM_SCRIPT);
+
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let output = service
+        .validate(true)
+        .await
+        .expect("validate should succeed after auto init");
+    assert!(output.contains("\"valid\":true"));
+    assert!(project_dir.path().join(".terraform").exists());
+}
+
+// ==================== Version Compatibility Tests ====================
+
+#[cfg(unix)]
+async fn analyze_with_binary_version(version: &str) -> tfmcp::model::TerraformAnalysis {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let script = format!(
+        "#!/bin/sh\necho \"Terraform v{}\"\necho \"on linux_amd64\"\n",
+        version
+    );
+    let fake_terraform = write_fake_terraform(bin_dir.path(), &script);
+
+    let main_tf = r#"
+terraform {
+  required_version = ">= 1.5.0"
+}
+
+resource "local_file" "example" {
+  content  = "hello"
+  filename = "example.txt"
+}
+"#;
+    fs::write(project_dir.path().join("main.tf"), main_tf).expect("Failed to write main.tf");
+
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+    service
+        .analyze_configurations()
+        .await
+        .expect("Analysis should succeed")
+}
+
+/// Test that analysis flags a binary older than required_version
+#[cfg(unix)]
+#[tokio::test]
+async fn test_required_version_unsatisfied() {
+    let analysis = analyze_with_binary_version("1.4.0").await;
+
+    assert_eq!(analysis.required_version.as_deref(), Some(">= 1.5.0"));
+    let compat = analysis
+        .version_compatibility
+        .expect("version_compatibility should be present");
+    assert_eq!(compat.installed_version.as_deref(), Some("1.4.0"));
+    assert_eq!(compat.satisfied, Some(false));
+}
+
+/// Test that analysis accepts a binary satisfying required_version
+#[cfg(unix)]
+#[tokio::test]
+async fn test_required_version_satisfied() {
+    let analysis = analyze_with_binary_version("1.6.0").await;
+
+    let compat = analysis
+        .version_compatibility
+        .expect("version_compatibility should be present");
+    assert_eq!(compat.constraint, ">= 1.5.0");
+    assert_eq!(compat.installed_version.as_deref(), Some("1.6.0"));
+    assert_eq!(compat.satisfied, Some(true));
+}
+
+// ==================== Project File Listing Tests ====================
+
+/// Test that the file listing covers nested files and omits blocked paths
+#[test]
+fn test_list_project_files_respects_blocked_patterns() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let root = project_dir.path();
+    fs::write(
+        root.join("main.tf"),
+        "resource \"aws_instance\" \"web\" {\n  ami = \"ami-123\"\n}\n",
+    )
+    .expect("Failed to write main.tf");
+    fs::write(root.join("variables.tf"), "variable \"region\" {}\n")
+        .expect("Failed to write variables.tf");
+    fs::write(root.join("dev.tfvars"), "region = \"us-east-1\"\n")
+        .expect("Failed to write dev.tfvars");
+    fs::write(root.join("db_secret.tf"), "variable \"password\" {}\n")
+        .expect("Failed to write db_secret.tf");
+    fs::create_dir_all(root.join("production")).expect("Failed to create production dir");
+    fs::write(root.join("production/main.tf"), "module \"app\" {}\n")
+        .expect("Failed to write production/main.tf");
+    fs::create_dir_all(root.join("modules/network")).expect("Failed to create module dir");
+    fs::write(
+        root.join("modules/network/main.tf"),
+        "resource \"aws_vpc\" \"this\" {}\n",
+    )
+    .expect("Failed to write module main.tf");
+
+    let service = tfmcp::TerraformService::new("terraform".into(), root.to_path_buf());
+    let files = service
+        .list_project_files()
+        .expect("Listing should succeed");
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+
+    assert_eq!(
+        paths,
+        vec![
+            "dev.tfvars",
+            "main.tf",
+            "modules/network/main.tf",
+            "variables.tf"
+        ]
+    );
+    let main = files.iter().find(|f| f.path == "main.tf").unwrap();
+    assert!(main.size > 0);
+    assert_eq!(main.preview, vec![r#"resource "aws_instance" "web""#]);
+}
+
+// ==================== Path-Scoped Validation Tests ====================
+
+/// Fake terraform binary whose validate output reports its working directory
+#[cfg(unix)]
+const PWD_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+echo "{\"valid\":true,\"error_count\":0,\"warning_count\":1,\"diagnostics\":[{\"severity\":\"warning\",\"summary\":\"$(pwd)\",\"detail\":null,\"range\":null}]}"
+"#;
+
+/// Test that paths escaping the project root are rejected
+#[test]
+fn test_validate_path_rejects_escapes() {
+    let parent = tempdir().expect("Failed to create temp dir");
+    let project = parent.path().join("project");
+    fs::create_dir_all(&project).expect("Failed to create project dir");
+    fs::create_dir_all(parent.path().join("sibling")).expect("Failed to create sibling dir");
+
+    let service = tfmcp::TerraformService::new("terraform".into(), project.clone());
+
+    assert!(service.resolve_project_path(Some("../sibling")).is_err());
+    assert!(service.resolve_project_path(Some("..")).is_err());
+    let outside = parent.path().join("sibling");
+    assert!(
+        service
+            .resolve_project_path(Some(outside.to_str().unwrap()))
+            .is_err()
+    );
+    assert!(service.resolve_project_path(Some("missing")).is_err());
+}
+
+/// Test that validation runs inside the requested subdirectory
+#[cfg(unix)]
+#[tokio::test]
+async fn test_validate_subdirectory() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), PWD_TERRAFORM_SCRIPT);
+
+    let example_dir = project_dir.path().join("examples/basic");
+    fs::create_dir_all(&example_dir).expect("Failed to create example dir");
+    fs::write(example_dir.join("main.tf"), "variable \"name\" {}\n")
+        .expect("Failed to write example main.tf");
+
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let (validated_path, validation) = service
+        .validate_path(Some("examples/basic"), false)
+        .await
+        .expect("Validation should succeed");
+    assert_eq!(validated_path, "examples/basic");
+    // The fake terraform reports its working directory as a warning
+    assert!(validation.diagnostics[0].summary.contains("examples/basic"));
+
+    let (validated_path, _) = service
+        .validate_path(Some("./examples/basic/main.tf"), false)
+        .await
+        .expect("Validation of a file's directory should succeed");
+    assert_eq!(validated_path, "examples/basic");
+
+    let detailed = service
+        .validate_detailed(Some("examples/basic"))
+        .await
+        .expect("Detailed validation should succeed");
+    assert_eq!(detailed.validated_path, "examples/basic");
+    assert_eq!(detailed.checked_files, 1);
+
+    let (validated_path, _) = service
+        .validate_path(None, false)
+        .await
+        .expect("Root validation should succeed");
+    assert_eq!(validated_path, ".");
+}
+
+// ==================== File Cache Tests ====================
+
+/// Test that repeated analysis only re-parses files whose mtime changed
+#[tokio::test]
+async fn test_analysis_reparses_only_modified_files() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let network = project_dir.path().join("network.tf");
+    fs::write(&network, "resource \"aws_vpc\" \"main\" {}\n").unwrap();
+    fs::write(
+        project_dir.path().join("compute.tf"),
+        "resource \"aws_instance\" \"web\" {}\n",
+    )
+    .unwrap();
+    let service = tfmcp::TerraformService::new(
+        std::path::PathBuf::from("terraform"),
+        project_dir.path().to_path_buf(),
+    );
+
+    service.analyze_configurations().await.unwrap();
+    assert_eq!(service.file_cache().parse_count(), 2);
+
+    service.analyze_configurations().await.unwrap();
+    assert_eq!(service.file_cache().parse_count(), 2);
+
+    fs::write(&network, "resource \"aws_subnet\" \"public\" {}\n").unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&network)
+        .unwrap()
+        .set_modified(std::time::SystemTime::now() + Duration::from_secs(5))
+        .unwrap();
+
+    let analysis = service.analyze_configurations().await.unwrap();
+    assert_eq!(service.file_cache().parse_count(), 3);
+    let mut types: Vec<_> = analysis
+        .resources
+        .iter()
+        .map(|r| r.resource_type.as_str())
+        .collect();
+    types.sort();
+    assert_eq!(types, vec!["aws_instance", "aws_subnet"]);
+}
+
+// ==================== Provider Alias Tests ====================
+
+/// Test that aliases declared across files are merged and attached to resources
+#[tokio::test]
+async fn test_analysis_reports_provider_aliases() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        project_dir.path().join("providers.tf"),
+        r#"
+provider "aws" {
+  region = "us-east-1"
+}
+
+provider "aws" {
+  alias  = "west"
+  region = "us-west-2"
+}
+"#,
+    )
+    .expect("Failed to write providers.tf");
+    fs::write(
+        project_dir.path().join("replica.tf"),
+        r#"
+provider "aws" {
+  alias  = "eu"
+  region = "eu-west-1"
+}
+
+resource "aws_s3_bucket" "west" {
+  provider = aws.west
+  bucket   = "replica-west"
+}
+
+resource "aws_s3_bucket" "eu" {
+  provider = aws.eu
+  bucket   = "replica-eu"
+}
+"#,
+    )
+    .expect("Failed to write replica.tf");
+
+    let service =
+        tfmcp::TerraformService::new("terraform".into(), project_dir.path().to_path_buf());
+    let analysis = service
+        .analyze_configurations()
+        .await
+        .expect("Analysis should succeed");
+
+    assert_eq!(analysis.providers.len(), 1);
+    assert_eq!(analysis.providers[0].aliases, vec!["eu", "west"]);
+
+    let alias_of = |name: &str| {
+        analysis
+            .resources
+            .iter()
+            .find(|r| r.name == name)
+            .and_then(|r| r.provider_alias.clone())
+    };
+    assert_eq!(alias_of("west").as_deref(), Some("west"));
+    assert_eq!(alias_of("eu").as_deref(), Some("eu"));
+}
+
+// ==================== Destroy Preview Tests ====================
+
+/// Fake terraform that records its arguments and streams a destroy plan
+#[cfg(unix)]
+const PLAN_DESTROY_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+[ "$1" = "version" ] && exit 1
+echo "$@" > "$PWD/args.txt"
+case " $* " in
+  *" -destroy "*)
+    echo '{"@level":"info","type":"version","terraform":"1.11.4"}'
+    echo '{"@level":"info","change":{"resource":{"addr":"aws_instance.web","resource_type":"aws_instance","implied_provider":"aws"},"action":"delete"},"type":"planned_change"}'
+    echo '{"@level":"info","change":{"resource":{"addr":"aws_s3_bucket.logs","resource_type":"aws_s3_bucket","implied_provider":"aws"},"action":"delete"},"type":"planned_change"}'
+    ;;
+  *) exit 1 ;;
+esac
+"#;
+
+/// Test that plan_destroy passes -destroy and lists deletions without the dangerous-ops gate
+#[cfg(unix)]
+#[tokio::test]
+async fn test_plan_destroy_lists_deletions() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), PLAN_DESTROY_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let preview = service
+        .plan_destroy(false)
+        .await
+        .expect("Destroy preview should succeed");
+
+    let args = fs::read_to_string(project_dir.path().join("args.txt")).unwrap();
+    assert_eq!(args.trim(), "plan -destroy -json -input=false");
+    assert!(preview.preview);
+    assert_eq!(preview.summary.destroy, 2);
+    let addresses: Vec<_> = preview
+        .resources
+        .iter()
+        .map(|r| r.address.as_str())
+        .collect();
+    assert_eq!(addresses, vec!["aws_instance.web", "aws_s3_bucket.logs"]);
+}
+
+// ==================== Preflight Tests ====================
+
+/// Fake terraform that logs each project subcommand to `calls.txt`;
+/// validation fails when an `invalid` marker file exists in the project
+#[cfg(unix)]
+const PREFLIGHT_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+[ "$1" = version ] && exit 1
+echo "$1" >> "$PWD/calls.txt"
+case "$1" in
+  validate)
+    if [ -f "$PWD/invalid" ]; then
+      echo '{"valid":false,"error_count":1,"warning_count":0,"diagnostics":[{"severity":"error","summary":"Unsupported argument","detail":"An argument named \"bogus\" is not expected here."}]}'
+      exit 1
+    fi
+    echo '{"valid":true,"error_count":0,"warning_count":0,"diagnostics":[]}'
+    ;;
+  plan)
+    echo '{"@level":"info","type":"version","terraform":"1.11.4"}'
+    echo '{"@level":"info","change":{"resource":{"addr":"aws_instance.web","resource_type":"aws_instance","implied_provider":"aws"},"action":"create"},"type":"planned_change"}'
+    ;;
+  *) exit 1 ;;
+esac
+"#;
+
+/// Test that a valid configuration is planned in the same call
+#[cfg(unix)]
+#[tokio::test]
+async fn test_preflight_plans_valid_configuration() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), PREFLIGHT_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let result = service
+        .preflight(false)
+        .await
+        .expect("Preflight should succeed");
+
+    assert!(result.valid);
+    assert!(result.planned);
+    let plan = result.plan.expect("Plan should be included");
+    assert_eq!(plan.summary.add, 1);
+    let calls = fs::read_to_string(project_dir.path().join("calls.txt")).unwrap();
+    assert_eq!(calls.lines().collect::<Vec<_>>(), vec!["validate", "plan"]);
+}
+
+/// Test that an invalid configuration returns diagnostics without planning
+#[cfg(unix)]
+#[tokio::test]
+async fn test_preflight_short_circuits_on_invalid_configuration() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(project_dir.path().join("invalid"), "").unwrap();
+    let fake_terraform = write_fake_terraform(bin_dir.path(), PREFLIGHT_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let result = service
+        .preflight(false)
+        .await
+        .expect("Preflight should succeed");
+
+    assert!(!result.valid);
+    assert!(!result.planned);
+    assert!(result.plan.is_none());
+    assert_eq!(result.validation.error_count, 1);
+    assert_eq!(
+        result.validation.diagnostics[0].summary,
+        "Unsupported argument"
+    );
+    let calls = fs::read_to_string(project_dir.path().join("calls.txt")).unwrap();
+    assert_eq!(calls.lines().collect::<Vec<_>>(), vec!["validate"]);
+}
+
+// ==================== Workspace-Aware State Tests ====================
+
+/// Fake terraform that keeps the selected workspace in `.ws`, overridden by
+/// `TF_WORKSPACE`, and logs each command to `calls.log`; only "default" and
+/// "staging" exist, and `state list` fails in staging
+#[cfg(unix)]
+const WORKSPACE_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+echo "$* TF_WORKSPACE=$TF_WORKSPACE" >> "$PWD/calls.log"
+selected=$(cat "$PWD/.ws" 2>/dev/null || echo default)
+ws=${TF_WORKSPACE:-$selected}
+case "$1 $2" in
+  "workspace show") echo "$ws" ;;
+  "workspace list")
+    for name in default staging; do
+      if [ "$name" = "$selected" ]; then echo "* $name"; else echo "  $name"; fi
+    done
+    ;;
+  "workspace select")
+    case "$3" in
+      default|staging) echo "$3" > "$PWD/.ws" ;;
+      *) echo "Workspace \"$3\" doesn't exist." >&2; exit 1 ;;
+    esac
+    ;;
+  "state pull")
+    if [ "$ws" = "staging" ]; then
+      echo '{"version":4,"terraform_version":"1.11.4","serial":3,"resources":[{"mode":"managed","type":"aws_instance","name":"web","provider":"provider[\"registry.terraform.io/hashicorp/aws\"]","instances":[{"attributes":{"id":"i-123"}}]}]}'
+    fi
+    ;;
+  "state list")
+    if [ "$ws" = "staging" ]; then
+      echo "Error: backend unreachable" >&2
+      exit 1
+    fi
+    echo "aws_vpc.main"
+    ;;
+  *) exit 1 ;;
+esac
+"#;
+
+#[cfg(unix)]
+fn selected_workspace(project_dir: &std::path::Path) -> String {
+    fs::read_to_string(project_dir.join(".ws"))
+        .map(|ws| ws.trim().to_string())
+        .unwrap_or_else(|_| "default".to_string())
+}
+
+/// Test that state tools report the current workspace when none is requested
+#[cfg(unix)]
+#[tokio::test]
+async fn test_state_reports_current_workspace() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), WORKSPACE_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let analysis = service
+        .analyze_state(None, false, false, None)
+        .await
+        .expect("State analysis should succeed");
+    assert_eq!(analysis.workspace.as_deref(), Some("default"));
+    assert_eq!(analysis.total_resources, 0);
+
+    let (workspace, state) = service
+        .get_state(false, None)
+        .await
+        .expect("State listing should succeed");
+    assert_eq!(workspace.as_deref(), Some("default"));
+    assert_eq!(state.trim(), "aws_vpc.main");
+}
+
+/// Test that a requested workspace is analyzed without changing the selected one
+#[cfg(unix)]
+#[tokio::test]
+async fn test_analyze_state_in_workspace_keeps_selection() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), WORKSPACE_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let analysis = service
+        .analyze_state(None, false, false, Some("staging"))
+        .await
+        .expect("State analysis should succeed");
+    assert_eq!(analysis.workspace.as_deref(), Some("staging"));
+    assert_eq!(analysis.total_resources, 1);
+    assert_eq!(selected_workspace(project_dir.path()), "default");
+}
+
+/// Test that failures in a requested workspace leave the selected one alone
+#[cfg(unix)]
+#[tokio::test]
+async fn test_state_workspace_kept_on_error() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), WORKSPACE_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let err = service
+        .get_state(false, Some("staging"))
+        .await
+        .expect_err("State listing should fail in staging");
+    assert!(err.to_string().contains("backend unreachable"));
+    assert_eq!(selected_workspace(project_dir.path()), "default");
+
+    let err = service
+        .analyze_state(None, false, false, Some("missing"))
+        .await
+        .expect_err("Missing workspace should be reported");
+    assert!(err.to_string().contains("does not exist"));
+    assert_eq!(selected_workspace(project_dir.path()), "default");
+}
+
+/// Test that a requested workspace reaches terraform only as `TF_WORKSPACE`:
+/// `workspace select` never runs, so the selection on disk is never touched
+#[cfg(unix)]
+#[tokio::test]
+async fn test_state_in_workspace_never_selects() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), WORKSPACE_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    service
+        .analyze_state(None, false, false, Some("staging"))
+        .await
+        .expect("State analysis should succeed");
+    let _ = service.get_state(false, Some("staging")).await;
+
+    let calls = fs::read_to_string(project_dir.path().join("calls.log")).unwrap();
+    assert!(
+        !calls
+            .lines()
+            .any(|call| call.starts_with("workspace select")),
+        "{calls}"
+    );
+    assert!(
+        calls
+            .lines()
+            .filter(|call| call.starts_with("state "))
+            .all(|call| call.ends_with("TF_WORKSPACE=staging")),
+        "{calls}"
+    );
+    assert!(!project_dir.path().join(".ws").exists());
+    assert!(!project_dir.path().join(".terraform/environment").exists());
+}
+
+// ==================== Output Masking Tests ====================
+
+/// Fake terraform binary returning a mix of sensitive and non-sensitive outputs
+#[cfg(unix)]
+const OUTPUTS_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+cat <<'JSON'
+{
+  "db_password": {"sensitive": true, "type": "string", "value": "hunter2"},
+  "endpoint": {"sensitive": false, "type": "string", "value": "db.example.com"}
+}
+JSON
+"#;
+
+/// Test that sensitive outputs are masked by default and reveal is gated
+#[cfg(unix)]
+#[tokio::test]
+async fn test_output_masks_sensitive_values() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), OUTPUTS_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let result = service
+        .output(None, false)
+        .await
+        .expect("Output should succeed");
+    assert_eq!(result.outputs.len(), 2);
+    let password = &result.outputs[0];
+    assert_eq!(password.name, "db_password");
+    assert_eq!(password.value, serde_json::json!("<sensitive>"));
+    let endpoint = &result.outputs[1];
+    assert_eq!(endpoint.value, serde_json::json!("db.example.com"));
+    assert!(!serde_json::to_string(&result).unwrap().contains("hunter2"));
+
+    let single = service
+        .output(Some("db_password"), false)
+        .await
+        .expect("Single output should succeed");
+    assert_eq!(single.outputs.len(), 1);
+    assert_eq!(single.outputs[0].value, serde_json::json!("<sensitive>"));
+
+    if !service.get_security_policy().allow_dangerous_operations {
+        let err = service
+            .output(None, true)
+            .await
+            .expect_err("Reveal should be blocked without dangerous ops");
+        assert!(err.to_string().contains("TFMCP_ALLOW_DANGEROUS_OPS"));
+    }
+}
+
+#[tokio::test]
+async fn test_evaluate_policies_reports_missing_configuration() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let service = tfmcp::TerraformService::new(
+        std::path::PathBuf::from("terraform"),
+        project_dir.path().to_path_buf(),
+    );
+
+    let result = service
+        .evaluate_policies(None, false)
+        .await
+        .expect("Missing policy dir should not be an error");
+    assert_eq!(
+        result.status,
+        tfmcp::terraform::conftest::EvaluationStatus::NotConfigured
+    );
+    assert!(!result.passed);
+    assert!(result.message.contains("TFMCP_POLICY_DIR"));
+
+    let missing = project_dir.path().join("policies");
+    let result = service
+        .evaluate_policies(Some(&missing), false)
+        .await
+        .expect("Nonexistent policy dir should not be an error");
+    assert_eq!(
+        result.status,
+        tfmcp::terraform::conftest::EvaluationStatus::NotConfigured
+    );
+}
+
+// ==================== Typed Terraform Error Tests ====================
+
+/// Fake terraform binary whose state is held by another process
+#[cfg(unix)]
+const LOCKED_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+cat >&2 <<'MSG'
+Error: Error acquiring the state lock
+
+Lock Info:
+  ID:        a1b2c3d4-lock
+  Operation: OperationTypeApply
+MSG
+exit 1
+"#;
+
+/// Test that service failures carry a typed TerraformError
+#[cfg(unix)]
+#[tokio::test]
+async fn test_service_errors_are_typed() {
+    use tfmcp::terraform::error::TerraformError;
+
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+
+    let fake_terraform = write_fake_terraform(bin_dir.path(), FAKE_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+    let err = service
+        .get_plan(false, &[])
+        .await
+        .expect_err("plan needs init");
+    assert!(matches!(
+        err.downcast_ref::<TerraformError>(),
+        Some(TerraformError::InitRequired)
+    ));
+
+    let locked_terraform = write_fake_terraform(bin_dir.path(), LOCKED_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(locked_terraform, project_dir.path().to_path_buf());
+    let err = service
+        .get_plan(false, &[])
+        .await
+        .expect_err("state is locked");
+    match err.downcast_ref::<TerraformError>() {
+        Some(TerraformError::StateLocked { lock_id, .. }) => {
+            assert_eq!(lock_id.as_deref(), Some("a1b2c3d4-lock"))
+        }
+        other => panic!("expected StateLocked, got {:?}", other),
+    }
+
+    let service = tfmcp::TerraformService::new(
+        bin_dir.path().join("no-such-terraform"),
+        project_dir.path().to_path_buf(),
+    );
+    let err = service
+        .get_plan(false, &[])
+        .await
+        .expect_err("binary is missing");
+    assert!(matches!(
+        err.downcast_ref::<TerraformError>(),
+        Some(TerraformError::BinaryNotFound { .. })
+    ));
+}
+
+// ==================== Provider Schema Tests ====================
+
+/// Fake terraform binary printing a minimal provider schema document
+#[cfg(unix)]
+const SCHEMA_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+if [ "$1 $2 $3" = "providers schema -json" ]; then
+  cat <<'JSON'
+{"format_version":"1.0","provider_schemas":{"registry.terraform.io/hashicorp/random":{"resource_schemas":{"random_pet":{"version":0,"block":{"attributes":{"length":{"type":"number","optional":true},"id":{"type":"string","computed":true}}}}}}}}
+JSON
+  exit 0
+fi
+exit 1
+"#;
+
+/// Test that provider schemas need an initialized project and are parsed once it is
+#[cfg(unix)]
+#[tokio::test]
+async fn test_providers_schema_requires_init() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), SCHEMA_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let err = service
+        .providers_schema()
+        .await
+        .expect_err("schema should need init");
+    assert!(err.to_string().contains("initialization required"));
+
+    fs::create_dir(project_dir.path().join(".terraform")).unwrap();
+    let schema = service.resource_schema("random_pet", false).await.unwrap();
+    assert_eq!(schema.provider, "registry.terraform.io/hashicorp/random");
+    assert!(schema.schema.block.attributes["id"].computed);
+
+    let err = service
+        .resource_schema("random_pet", true)
+        .await
+        .expect_err("random_pet is not a data source");
+    assert!(err.to_string().contains("not defined by any provider"));
+}
+
+/// Fake terraform that also reports a valid configuration from `validate -json`
+#[cfg(unix)]
+const SCHEMA_VALIDATE_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+case "$1" in
+  providers)
+    cat <<'JSON'
+{"format_version":"1.0","provider_schemas":{"registry.terraform.io/hashicorp/random":{"resource_schemas":{"random_pet":{"version":0,"block":{"attributes":{"length":{"type":"number","optional":true},"prefix":{"type":"string","optional":true},"id":{"type":"string","computed":true}}}}}}}}
+JSON
+    ;;
+  validate)
+    echo '{"valid":true,"error_count":0,"warning_count":0,"diagnostics":[]}'
+    ;;
+  *)
+    exit 1
+    ;;
+esac
+"#;
+
+/// Test that detailed validation flags unknown arguments only once schemas are warmed
+#[cfg(unix)]
+#[tokio::test]
+async fn test_warmed_schemas_flag_unknown_arguments() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::create_dir(project_dir.path().join(".terraform")).unwrap();
+    fs::write(
+        project_dir.path().join("main.tf"),
+        "resource \"random_pet\" \"name\" {\n  lenght = 2\n  prefix = \"app\"\n}\n",
+    )
+    .unwrap();
+    let fake_terraform = write_fake_terraform(bin_dir.path(), SCHEMA_VALIDATE_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let result = service.validate_detailed(None).await.unwrap();
+    assert!(
+        !result
+            .additional_warnings
+            .iter()
+            .any(|w| w.starts_with("[Schema]"))
+    );
+
+    let warmed = service.warm_provider_schemas().await.unwrap();
+    assert_eq!(warmed.resource_types, 1);
+
+    let result = service.validate_detailed(None).await.unwrap();
+    let schema_warnings: Vec<&String> = result
+        .additional_warnings
+        .iter()
+        .filter(|w| w.starts_with("[Schema]"))
+        .collect();
+    assert_eq!(
+        schema_warnings,
+        vec![
+            "[Schema] random_pet.name has no argument 'lenght' at main.tf:2 (did you mean 'length'?)"
+        ]
+    );
+}
+
+// ==================== Saved Plan Tests ====================
+
+/// Fake terraform whose `plan -out=FILE` writes FILE and whose `show -json` prints an empty plan
+#[cfg(unix)]
+const SAVED_PLAN_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+case "$1" in
+  plan)
+    for arg in "$@"; do
+      case "$arg" in
+        -out=*) echo "binary plan" > "${arg#-out=}" ;;
+      esac
+    done
+    exit 0
+    ;;
+  show)
+    echo '{"format_version":"1.2","resource_changes":[]}'
+    exit 0
+    ;;
+  apply)
+    echo "Apply complete! Resources: 0 added, 0 changed, 0 destroyed."
+    exit 0
+    ;;
+esac
+exit 1
+"#;
+
+/// Test that a saved plan file edited after save_plan is refused by apply_saved_plan
+#[cfg(unix)]
+#[tokio::test]
+async fn test_apply_saved_plan_refuses_changed_plan_file() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), SAVED_PLAN_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let saved = service
+        .save_plan(Some("plans/prod.tfplan"), false)
+        .await
+        .expect("save_plan should succeed");
+    assert_eq!(saved.plan_file, "plans/prod.tfplan");
+    assert_eq!(saved.sha256.len(), 64);
+    assert!(
+        project_dir
+            .path()
+            .join("plans/prod.tfplan.tfmcp.json")
+            .exists()
+    );
+
+    fs::write(
+        project_dir.path().join("plans/prod.tfplan"),
+        "a different plan\n",
+    )
+    .unwrap();
+    let err = service
+        .apply_saved_plan(Some("plans/prod.tfplan"), false)
+        .await
+        .expect_err("a changed plan file must not be applied");
+    let tf_error = err
+        .downcast_ref::<tfmcp::terraform::error::TerraformError>()
+        .expect("refusal should be a TerraformError");
+    assert_eq!(tf_error.kind(), "plan_file_changed");
+    assert!(err.to_string().contains(&saved.sha256));
+
+    let err = service
+        .apply_saved_plan(Some("../outside.tfplan"), false)
+        .await
+        .expect_err("plan files outside the project are rejected");
+    assert!(err.to_string().contains("inside the project directory"));
+}
+
+/// `terraform show -json` output captured from a plan replacing a database
+const CAPTURED_PLAN_JSON: &str = r#"{
+  "format_version": "1.2",
+  "terraform_version": "1.9.5",
+  "resource_changes": [
+    {
+      "address": "aws_s3_bucket.logs",
+      "mode": "managed",
+      "type": "aws_s3_bucket",
+      "name": "logs",
+      "provider_name": "registry.terraform.io/hashicorp/aws",
+      "change": {"actions": ["create"], "before": null, "after": {"bucket": "acme-logs"}}
+    },
+    {
+      "address": "aws_db_instance.main",
+      "mode": "managed",
+      "type": "aws_db_instance",
+      "name": "main",
+      "provider_name": "registry.terraform.io/hashicorp/aws",
+      "change": {"actions": ["delete", "create"], "before": {"engine": "postgres"}, "after": {"engine": "postgres"}}
+    }
+  ]
+}"#;
+
+/// Test that a captured plan JSON is analyzed without running terraform
+#[tokio::test]
+async fn test_analyze_plan_file_reads_captured_plan_json() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::create_dir(project_dir.path().join("ci")).unwrap();
+    fs::write(project_dir.path().join("ci/plan.json"), CAPTURED_PLAN_JSON).unwrap();
+    // A binary that does not exist proves no plan is run
+    let service = tfmcp::TerraformService::new(
+        std::path::PathBuf::from("/nonexistent/terraform"),
+        project_dir.path().to_path_buf(),
+    );
+
+    let analysis = service
+        .analyze_plan_file("ci/plan.json", true, None)
+        .await
+        .expect("analyze_plan_file should succeed");
+    assert_eq!(analysis.summary.add, 1);
+    assert_eq!(analysis.summary.replace, 1);
+    assert_eq!(analysis.resource_changes.len(), 2);
+    assert!(analysis.risk_assessment.score > 0);
+    assert_eq!(analysis.terraform_version.as_deref(), Some("1.9.5"));
+
+    let err = service
+        .analyze_plan_file("../plan.json", true, None)
+        .await
+        .expect_err("plan files outside the project are rejected");
+    assert!(err.to_string().contains("inside the project directory"));
+}
+
+/// Test that a binary plan file is rendered with `terraform show -json` first
+#[cfg(unix)]
+#[tokio::test]
+async fn test_analyze_plan_file_shows_binary_plan() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), SAVED_PLAN_TERRAFORM_SCRIPT);
+    fs::write(project_dir.path().join("ci.tfplan"), "PK binary plan").unwrap();
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let analysis = service
+        .analyze_plan_file("ci.tfplan", false, None)
+        .await
+        .expect("analyze_plan_file should succeed");
+    assert!(analysis.resource_changes.is_empty());
+    assert_eq!(analysis.format_version.as_deref(), Some("1.2"));
+}
+
+// ==================== CLI Config Tests ====================
+
+/// Fake terraform that reports the CLI config it was started with
+#[cfg(unix)]
+const CLI_CONFIG_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+echo "cli config: ${TF_CLI_CONFIG_FILE:-none}"
+exit 0
+"#;
+
+/// Test that the project CLI config is passed to terraform as TF_CLI_CONFIG_FILE
+#[cfg(unix)]
+#[tokio::test]
+async fn test_cli_config_file_passed_to_terraform() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), CLI_CONFIG_TERRAFORM_SCRIPT);
+    let mut service =
+        tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let cli_config = project_dir.path().join("mirror.tfrc");
+    fs::write(
+        &cli_config,
+        "provider_installation {\n  network_mirror {\n    url = \"https://mirror.example.com/\"\n  }\n}\n",
+    )
+    .unwrap();
+
+    service.set_cli_config_file(Some(cli_config.clone()));
+    let output = service.init(&[]).await.unwrap();
+    assert!(output.contains(&format!("cli config: {}", cli_config.display())));
+    let output = service.get_plan(false, &[]).await.unwrap();
+    assert!(output.contains(&format!("cli config: {}", cli_config.display())));
+
+    let info = service.get_cli_config();
+    assert_eq!(
+        info.path.as_deref(),
+        Some(cli_config.to_string_lossy().as_ref())
+    );
+    assert!(info.exists);
+    assert!(info.summary.network_mirror);
+    assert_eq!(
+        info.summary.network_mirror_urls,
+        vec!["https://mirror.example.com/"]
+    );
+}
+
+// ==================== Analyze CLI Tests ====================
+
+/// Run `tfmcp --dir <project> analyze <args>` against a fake terraform binary
+#[cfg(unix)]
+fn run_analyze(project_dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), "#!/bin/sh\nexit 0\n");
+    std::process::Command::new(env!("CARGO_BIN_EXE_tfmcp"))
+        .args(["--dir", &project_dir.to_string_lossy(), "analyze"])
+        .args(args)
+        .current_dir(project_dir)
+        .env("TFMCP_TERRAFORM_BIN", fake_terraform)
+        .output()
+        .expect("Failed to run tfmcp")
+}
+
+/// Test that analyze --json prints a JSON report and exits 2 on critical issues
+#[cfg(unix)]
+#[test]
+fn test_analyze_json_report_and_exit_code() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        project_dir.path().join("main.tf"),
+        r#"
+variable "bucket_name" {
+  type        = string
+  description = "Name of the log bucket"
+}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = var.bucket_name
+}
+"#,
+    )
+    .unwrap();
+
+    let output = run_analyze(project_dir.path(), &["--json"]);
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be a JSON report");
+    assert_eq!(report["critical_issues"], 0);
+    assert_eq!(report["analysis"]["resources"][0]["name"], "logs");
+    assert!(report["guidelines"]["compliance_score"].is_number());
+    assert!(report["module_health"]["health_score"].is_number());
+
+    // The same resource declared in a second file is a critical issue
+    fs::write(
+        project_dir.path().join("storage.tf"),
+        "resource \"aws_s3_bucket\" \"logs\" {\n  bucket = \"copy\"\n}\n",
+    )
+    .unwrap();
+
+    let output = run_analyze(project_dir.path(), &["--json"]);
+    assert_eq!(output.status.code(), Some(2));
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be a JSON report");
+    assert_eq!(report["critical_issues"], 1);
+}
+
+/// Test that --fail-under gates on the module health score
+#[cfg(unix)]
+#[test]
+fn test_analyze_fail_under_health_score() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        project_dir.path().join("main.tf"),
+        r#"
+variable "bucket_name" {
+  type        = string
+  description = "Name of the log bucket"
+}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = var.bucket_name
+}
+"#,
+    )
+    .unwrap();
+
+    let output = run_analyze(project_dir.path(), &["--json"]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be a JSON report");
+    let score = report["module_health"]["health_score"].as_u64().unwrap();
+    assert!(
+        score > 0 && score < 100,
+        "unexpected health score {}",
+        score
+    );
+
+    let at_score = score.to_string();
+    let output = run_analyze(project_dir.path(), &["--fail-under", &at_score]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let above_score = (score + 1).to_string();
+    let output = run_analyze(project_dir.path(), &["--fail-under", &above_score]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is below"));
+
+    // Without the flag the same project only reports
+    let output = run_analyze(project_dir.path(), &[]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+/// Test that --min-cohesion gates on the cohesion score, naming the categories
+#[cfg(unix)]
+#[test]
+fn test_analyze_min_cohesion() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        project_dir.path().join("main.tf"),
+        r#"
+resource "aws_vpc" "main" {
+  cidr_block = "10.0.0.0/16"
+}
+
+resource "aws_subnet" "private" {
+  vpc_id     = aws_vpc.main.id
+  cidr_block = "10.0.1.0/24"
+}
+"#,
+    )
+    .unwrap();
+
+    let output = run_analyze(project_dir.path(), &["--min-cohesion", "90"]);
+    assert_eq!(output.status.code(), Some(0));
+
+    // Compute, storage and identity resources in the same module
+    fs::write(
+        project_dir.path().join("app.tf"),
+        r#"
+resource "aws_instance" "app" {
+  ami = "ami-123456"
+}
+
+resource "aws_s3_bucket" "assets" {
+  bucket = "assets"
+}
+
+resource "aws_iam_role" "app" {
+  name = "app"
+}
+"#,
+    )
+    .unwrap();
+
+    let output = run_analyze(project_dir.path(), &["--min-cohesion", "90"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Cohesion score"), "{}", stderr);
+    assert!(stderr.contains("aws_s3_bucket"), "{}", stderr);
+
+    // The cohesion gate is independent of the health score gate
+    let output = run_analyze(project_dir.path(), &["--fail-under", "0"]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+/// Test that --fail-on-secrets gates on hardcoded secrets
+#[cfg(unix)]
+#[test]
+fn test_analyze_fail_on_secrets() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        project_dir.path().join("main.tf"),
+        r#"
+variable "db_password" {
+  type        = string
+  description = "Database password"
+  sensitive   = true
+}
+"#,
+    )
+    .unwrap();
+
+    let output = run_analyze(project_dir.path(), &["--fail-on-secrets"]);
+    assert_eq!(output.status.code(), Some(0));
+
+    fs::write(
+        project_dir.path().join("db.tf"),
+        "resource \"aws_db_instance\" \"main\" {\n  password = \"correct-horse-battery\"\n}\n",
+    )
+    .unwrap();
+
+    let output = run_analyze(project_dir.path(), &["--fail-on-secrets"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hardcoded secrets"));
+
+    // Without the flag the same project only reports
+    let output = run_analyze(project_dir.path(), &[]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+// ==================== Variable Value Tests ====================
+
+/// Test that analysis reports each variable's value from terraform.tfvars or its default
+#[cfg(unix)]
+#[tokio::test]
+async fn test_analysis_resolves_tfvars_values() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), "#!/bin/sh\nexit 0\n");
+    fs::write(
+        project_dir.path().join("variables.tf"),
+        r#"
+variable "region" {
+  type    = string
+  default = "us-east-1"
+}
+
+variable "instance_type" {
+  type    = string
+  default = "t3.micro"
+}
+
+variable "db_password" {
+  type      = string
+  sensitive = true
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        project_dir.path().join("terraform.tfvars"),
+        "region      = \"eu-west-1\"\ndb_password = \"correct-horse-battery\"\n",
+    )
+    .unwrap();
+
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+    let analysis = service.analyze_configurations().await.unwrap();
+    let effective = |name: &str| {
+        let variable = analysis
+            .variables
+            .iter()
+            .find(|v| v.name == name)
+            .unwrap_or_else(|| panic!("variable {} should be analyzed", name));
+        serde_json::to_value(variable.effective.as_ref().unwrap()).unwrap()
+    };
+
+    assert_eq!(
+        effective("region"),
+        serde_json::json!({"source": "file", "file": "terraform.tfvars", "value": "eu-west-1"})
+    );
+    assert_eq!(
+        effective("instance_type"),
+        serde_json::json!({"source": "default", "value": "t3.micro"})
+    );
+    assert_eq!(effective("db_password")["value"], "(sensitive)");
+
+    let serialized = serde_json::to_string(&analysis).unwrap();
+    assert!(!serialized.contains("correct-horse-battery"));
+}
+
+// ==================== Multi-Directory Analysis Tests ====================
+
+/// Test that analyze_all summarizes each matching root and skips blocked ones
+#[tokio::test]
+async fn test_analyze_all_environments() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let root = project_dir.path();
+    let roots = [
+        (
+            "environments/dev",
+            "provider \"aws\" {}\nresource \"aws_s3_bucket\" \"logs\" {}\nvariable \"region\" {}\n",
+        ),
+        (
+            "environments/staging",
+            "provider \"google\" {}\nresource \"google_storage_bucket\" \"logs\" {}\nresource \"google_storage_bucket\" \"assets\" {}\noutput \"name\" {\n  value = \"x\"\n}\n",
+        ),
+        (
+            "environments/production",
+            "resource \"aws_instance\" \"web\" {}\n",
+        ),
+        ("modules/network", "resource \"aws_vpc\" \"this\" {}\n"),
+    ];
+    for (dir, content) in roots {
+        fs::create_dir_all(root.join(dir)).expect("Failed to create root dir");
+        fs::write(root.join(dir).join("main.tf"), content).expect("Failed to write main.tf");
+    }
+
+    let service = tfmcp::TerraformService::new("terraform".into(), root.to_path_buf());
+    let analysis = service
+        .analyze_all("environments/*/", 10)
+        .await
+        .expect("Multi-directory analysis should succeed");
+
+    let paths: Vec<&str> = analysis
+        .directories
+        .iter()
+        .map(|d| d.path.as_str())
+        .collect();
+    assert_eq!(paths, vec!["environments/dev", "environments/staging"]);
+    assert!(!analysis.truncated);
+    assert_eq!(analysis.directories[0].resource_count, 1);
+    assert_eq!(analysis.directories[0].variable_count, 1);
+    assert_eq!(analysis.directories[1].resource_count, 2);
+    assert_eq!(analysis.directories[1].output_count, 1);
+
+    let aggregate = &analysis.aggregate;
+    assert_eq!(aggregate.directory_count, 2);
+    assert_eq!(aggregate.failed_count, 0);
+    assert_eq!(aggregate.resource_count, 3);
+    assert_eq!(aggregate.providers, vec!["aws", "google"]);
+
+    let capped = service
+        .analyze_all("**", 1)
+        .await
+        .expect("Capped analysis should succeed");
+    assert_eq!(capped.directories.len(), 1);
+    assert!(capped.truncated);
+
+    assert!(service.analyze_all("../*", 10).await.is_err());
+}
+
+/// Test that remote state data sources report the outputs the project reads
+#[tokio::test]
+async fn test_analyze_remote_state_dependencies() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        project_dir.path().join("remote.tf"),
+        r#"
+data "terraform_remote_state" "network" {
+  backend = "s3"
+  config = {
+    bucket = "acme-state"
+    key    = "network/terraform.tfstate"
+  }
+}
+
+data "terraform_remote_state" "dns" {
+  backend = "remote"
+  config = {
+    organization = "acme"
+    workspaces = {
+      name = "dns"
+    }
+  }
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        project_dir.path().join("main.tf"),
+        r#"
+resource "aws_instance" "web" {
+  subnet_id = data.terraform_remote_state.network.outputs.private_subnet_id
+}
+
+resource "aws_route53_record" "web" {
+  zone_id = data.terraform_remote_state.dns.outputs["zone_id"]
+  records = [aws_instance.web.private_ip]
+}
+
+resource "aws_security_group" "web" {
+  vpc_id = data.terraform_remote_state.network.outputs.vpc_id
+}
+"#,
+    )
+    .unwrap();
+
+    let service =
+        tfmcp::TerraformService::new("terraform".into(), project_dir.path().to_path_buf());
+    let analysis = service.analyze_configurations().await.unwrap();
+
+    let mut dependencies = analysis.remote_state_dependencies.clone();
+    dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+    let summary: Vec<(&str, Option<&str>, &str, Vec<&str>)> = dependencies
+        .iter()
+        .map(|d| {
+            (
+                d.name.as_str(),
+                d.backend.as_deref(),
+                d.file.as_str(),
+                d.outputs.iter().map(String::as_str).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("dns", Some("remote"), "remote.tf", vec!["zone_id"]),
+            (
+                "network",
+                Some("s3"),
+                "remote.tf",
+                vec!["private_subnet_id", "vpc_id"]
+            ),
+        ]
+    );
+}
+
+/// Fake terraform with three workspaces, "dev" selected unless `TF_WORKSPACE`
+/// overrides it, whose states hold 2, 0 and 3 resources
+#[cfg(unix)]
+const WORKSPACE_COUNTS_TERRAFORM_SCRIPT: &str = r#"#!/bin/sh
+selected=$(cat "$PWD/.ws" 2>/dev/null || echo dev)
+ws=${TF_WORKSPACE:-$selected}
+case "$1 $2" in
+  "workspace list")
+    for name in default dev prod; do
+      if [ "$name" = "$selected" ]; then echo "* $name"; else echo "  $name"; fi
+    done
+    ;;
+  "workspace show") echo "$ws" ;;
+  "workspace select") echo "$3" > "$PWD/.ws" ;;
+  "state list")
+    case "$ws" in
+      default) printf 'aws_vpc.main\naws_subnet.a\n' ;;
+      dev) echo "No state file was found!" >&2; exit 1 ;;
+      prod) printf 'aws_vpc.main\naws_subnet.a\naws_instance.web\n' ;;
+    esac
+    ;;
+  *) exit 1 ;;
+esac
+"#;
+
+/// Test that workspace list with counts reports each state without changing the selection
+#[cfg(unix)]
+#[tokio::test]
+async fn test_workspace_list_with_counts() {
+    let bin_dir = tempdir().expect("Failed to create temp dir");
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    let fake_terraform = write_fake_terraform(bin_dir.path(), WORKSPACE_COUNTS_TERRAFORM_SCRIPT);
+    let service = tfmcp::TerraformService::new(fake_terraform, project_dir.path().to_path_buf());
+
+    let plain = service
+        .workspace("list", None, false)
+        .await
+        .expect("Workspace list should succeed");
+    let workspaces = plain.workspaces.unwrap();
+    assert_eq!(workspaces.len(), 3);
+    assert!(workspaces.iter().all(|w| w.resource_count.is_none()));
+
+    let result = service
+        .workspace("list", None, true)
+        .await
+        .expect("Workspace list with counts should succeed");
+    assert_eq!(result.current_workspace.as_deref(), Some("dev"));
+    let counts: Vec<(&str, Option<bool>, Option<usize>)> = result
+        .workspaces
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|w| (w.name.as_str(), w.has_state, w.resource_count))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("default", Some(true), Some(2)),
+            ("dev", Some(false), Some(0)),
+            ("prod", Some(true), Some(3)),
+        ]
+    );
+    // Counting never selects another workspace
+    assert!(!project_dir.path().join(".ws").exists());
+}
+
+// ==================== tfvars Generation Tests ====================
+
+/// Test that generate_tfvars writes a new file but refuses to overwrite one without force
+#[tokio::test]
+async fn test_generate_tfvars_writes_only_with_force_to_overwrite() {
+    let project_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        project_dir.path().join("variables.tf"),
+        r#"
+variable "region" {
+  description = "Region to deploy into"
+  type        = string
+}
+
+variable "instance_count" {
+  type    = number
+  default = 1
+}
+"#,
+    )
+    .unwrap();
+    let service = tfmcp::TerraformService::new(
+        std::path::PathBuf::from("terraform"),
+        project_dir.path().to_path_buf(),
+    );
+    let tfvars = project_dir.path().join("terraform.tfvars");
+
+    let preview = service
+        .generate_tfvars(false, None, false)
+        .await
+        .expect("generate_tfvars should succeed");
+    assert_eq!(preview.required, vec!["region"]);
+    assert!(preview.written_to.is_none());
+    assert!(!tfvars.exists());
+
+    let written = service
+        .generate_tfvars(true, Some("terraform.tfvars"), false)
+        .await
+        .expect("writing a new file should succeed");
+    assert_eq!(written.written_to.as_deref(), Some("terraform.tfvars"));
+    let content = fs::read_to_string(&tfvars).unwrap();
+    assert!(content.contains("# Region to deploy into\nregion = \"REPLACE_ME\"\n"));
+    assert!(content.contains("# instance_count = 1\n"));
+
+    fs::write(&tfvars, "region = \"eu-west-1\"\n").unwrap();
+    let err = service
+        .generate_tfvars(false, Some("terraform.tfvars"), false)
+        .await
+        .expect_err("an existing file must not be overwritten without force");
+    assert!(err.to_string().contains("set force"));
+    assert_eq!(
+        fs::read_to_string(&tfvars).unwrap(),
+        "region = \"eu-west-1\"\n"
+    );
+
+    service
+        .generate_tfvars(false, Some("terraform.tfvars"), true)
+        .await
+        .expect("force should overwrite");
+    assert!(
+        fs::read_to_string(&tfvars)
+            .unwrap()
+            .contains("region = \"REPLACE_ME\"")
+    );
+
+    for outside in ["../escape.tfvars", "main.tf"] {
+        assert!(
+            service
+                .generate_tfvars(false, Some(outside), true)
+                .await
+                .is_err(),
+            "{} should be refused",
+            outside
+        );
+    }
+}
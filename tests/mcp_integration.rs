@@ -55,7 +55,7 @@ async fn test_mcp_initialize_response() -> Result<()> {
             "capabilities": {
                 "experimental": {},
                 "prompts": { "listChanged": false },
-                "resources": { "listChanged": false, "subscribe": false },
+                "resources": { "listChanged": false, "subscribe": true },
                 "tools": { "listChanged": false }
             },
             "protocolVersion": "2024-11-05",
@@ -86,7 +86,7 @@ async fn test_mcp_initialize_response() -> Result<()> {
                     "capabilities": {
                         "experimental": {},
                         "prompts": { "listChanged": false },
-                        "resources": { "listChanged": false, "subscribe": false },
+                        "resources": { "listChanged": false, "subscribe": true },
                         "tools": { "listChanged": false }
                     },
                     "protocolVersion": "2024-11-05",